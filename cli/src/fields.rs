@@ -0,0 +1,246 @@
+//! Column registry for `--fields` output.
+//!
+//! `--columns` shows a path/name/tag-count layout. `--fields` lets the
+//! user choose the columns instead: every selectable field is a variant of
+//! [`Field`], which knows its header label and how to render itself from a
+//! match. Adding a column means adding a variant here and nowhere else.
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+
+use std::io::Write;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use typg_core::family::family_of;
+use typg_core::search::TypgFontFaceMatch;
+use typg_core::tags::tag_to_string;
+
+use crate::theme::{Role, Theme};
+use crate::{truncate_middle, WidthLimits};
+
+/// A selectable output column.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Field {
+    /// File path (with #index for collection faces when --collections is set)
+    Path,
+    /// Stable content-derived face ID (same as JSON `font_id`)
+    Id,
+    /// Numeric ID of the face in the LMDB index (`cache find --index` only)
+    #[value(alias = "index_id")]
+    IndexId,
+    /// First name from the name table
+    Name,
+    /// Family name
+    Family,
+    /// Style (subfamily) name
+    Style,
+    /// OS/2 usWeightClass
+    Weight,
+    /// OS/2 usWidthClass
+    Width,
+    /// OS/2 family class as major.subclass
+    Class,
+    /// Heuristic style category (serif, sans, mono, ...)
+    Category,
+    /// Whether the font is variable
+    Variable,
+    /// Variation axis tags
+    Axes,
+    /// Number of variation axes
+    #[value(alias = "axis_count")]
+    AxisCount,
+    /// OpenType feature tags
+    Features,
+    /// Feature tags from GSUB
+    Gsub,
+    /// Feature tags from GPOS
+    Gpos,
+    /// Script tags
+    Scripts,
+    /// Language system tags
+    Langs,
+    /// Table tags
+    Tables,
+    /// Units per em
+    Upm,
+    /// Glyph count (maxp)
+    Glyphs,
+    /// Font revision (head)
+    Revision,
+    /// OS/2 vendor ID
+    Vendor,
+    /// Embedding permission from OS/2 fsType
+    Embedding,
+    /// Number of faces in the source collection
+    Faces,
+    /// Number of mapped codepoints
+    Codepoints,
+    /// cmap subtables as platform.encoding:format
+    Cmap,
+}
+
+impl Field {
+    /// Column header label.
+    pub fn header(self) -> &'static str {
+        match self {
+            Field::Path => "PATH",
+            Field::Id => "ID",
+            Field::IndexId => "INDEX_ID",
+            Field::Name => "NAME",
+            Field::Family => "FAMILY",
+            Field::Style => "STYLE",
+            Field::Weight => "WEIGHT",
+            Field::Width => "WIDTH",
+            Field::Class => "CLASS",
+            Field::Category => "CATEGORY",
+            Field::Variable => "VARIABLE",
+            Field::Axes => "AXES",
+            Field::AxisCount => "AXIS_COUNT",
+            Field::Features => "FEATURES",
+            Field::Gsub => "GSUB",
+            Field::Gpos => "GPOS",
+            Field::Scripts => "SCRIPTS",
+            Field::Langs => "LANGS",
+            Field::Tables => "TABLES",
+            Field::Upm => "UPM",
+            Field::Glyphs => "GLYPHS",
+            Field::Revision => "REVISION",
+            Field::Vendor => "VENDOR",
+            Field::Embedding => "EMBEDDING",
+            Field::Faces => "FACES",
+            Field::Codepoints => "CODEPOINTS",
+            Field::Cmap => "CMAP",
+        }
+    }
+
+    /// Render this field for one match. Missing values render as `-`.
+    pub fn render(self, item: &TypgFontFaceMatch, collections: bool) -> String {
+        let meta = &item.metadata;
+        let opt = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        match self {
+            Field::Path => {
+                if collections {
+                    item.source.path_with_index()
+                } else {
+                    item.source.path.display().to_string()
+                }
+            }
+            Field::Id => item.font_id(),
+            Field::IndexId => opt(meta.index_id.map(|id| id.to_string())),
+            Field::Name => opt(meta.names.first().map(|name| name.to_string())),
+            Field::Family => opt(Some(family_of(meta).to_string()).filter(|f| !f.is_empty())),
+            Field::Style => opt(meta.style_name.as_deref().map(str::to_string)),
+            Field::Weight => opt(meta.weight_class.map(|w| w.to_string())),
+            Field::Width => opt(meta.width_class.map(|w| w.to_string())),
+            Field::Class => opt(meta
+                .family_class
+                .map(|(major, sub)| format!("{major}.{sub}"))),
+            Field::Category => opt(meta.classified_as.clone()),
+            Field::Variable => if meta.is_variable { "yes" } else { "no" }.to_string(),
+            Field::Axes => join_tags(meta.axis_tags.iter().map(|t| tag_to_string(*t))),
+            Field::AxisCount => meta.axis_tags.len().to_string(),
+            Field::Features => join_tags(meta.feature_tags.iter().map(|t| tag_to_string(*t))),
+            Field::Gsub => join_tags(meta.gsub_feature_tags.iter().map(|t| tag_to_string(*t))),
+            Field::Gpos => join_tags(meta.gpos_feature_tags.iter().map(|t| tag_to_string(*t))),
+            Field::Scripts => join_tags(meta.script_tags.iter().map(|t| tag_to_string(*t))),
+            Field::Langs => join_tags(
+                meta.language_tags
+                    .iter()
+                    .map(|t| tag_to_string(*t).trim_end().to_string()),
+            ),
+            Field::Tables => join_tags(meta.table_tags.iter().map(|t| tag_to_string(*t))),
+            Field::Upm => opt(meta.units_per_em.map(|u| u.to_string())),
+            Field::Glyphs => opt(meta.glyph_count.map(|g| g.to_string())),
+            Field::Revision => opt(meta.font_revision.map(|r| format!("{r:.3}"))),
+            Field::Vendor => opt(meta.vendor_id.clone()),
+            Field::Embedding => opt(meta.embedding.map(|e| e.to_string())),
+            Field::Faces => opt(meta.faces_in_collection.map(|n| n.to_string())),
+            Field::Codepoints => meta.codepoints.len().to_string(),
+            Field::Cmap => join_tags(meta.cmap_subtables.iter().map(|s| s.to_string())),
+        }
+    }
+}
+
+fn join_tags(tags: impl Iterator<Item = String>) -> String {
+    let joined = tags.collect::<Vec<_>>().join(",");
+    if joined.is_empty() {
+        "-".to_string()
+    } else {
+        joined
+    }
+}
+
+/// Write matches as aligned columns of the selected fields.
+///
+/// Columns are separated by two spaces and padded to the widest cell; the
+/// last column is not padded. With `color`, the header row is bold and the
+/// path column cyan, matching `--columns`.
+pub fn write_fields(
+    matches: &[TypgFontFaceMatch],
+    fields: &[Field],
+    mut w: impl Write,
+    header: bool,
+    theme: &Theme,
+    collections: bool,
+    limits: WidthLimits,
+) -> Result<()> {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    if header {
+        rows.push(fields.iter().map(|f| f.header().to_string()).collect());
+    }
+    for item in matches {
+        rows.push(fields.iter().map(|f| f.render(item, collections)).collect());
+    }
+
+    let widest = |col: usize, rows: &[Vec<String>]| {
+        rows.iter()
+            .map(|row| row[col].chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+    let others: usize = (0..fields.len())
+        .filter(|&col| fields[col] != Field::Path)
+        .map(|col| widest(col, &rows) + 2)
+        .sum();
+    if let Some(limit) = limits.path_width(others) {
+        let body = usize::from(header);
+        for row in &mut rows[body..] {
+            for (cell, field) in row.iter_mut().zip(fields) {
+                if *field == Field::Path {
+                    *cell = truncate_middle(cell, Some(limit));
+                }
+            }
+        }
+    }
+
+    let widths: Vec<usize> = (0..fields.len()).map(|col| widest(col, &rows)).collect();
+
+    let last = fields.len().saturating_sub(1);
+    for (row_idx, row) in rows.into_iter().enumerate() {
+        let is_header = header && row_idx == 0;
+        let line: Vec<String> = row
+            .into_iter()
+            .enumerate()
+            .map(|(col, cell)| {
+                let padded = if col == last {
+                    cell
+                } else {
+                    format!("{cell:<width$}", width = widths[col])
+                };
+                if is_header {
+                    return theme.paint(&padded, Role::Header);
+                }
+                match fields[col] {
+                    Field::Path => theme.paint(&padded, Role::Path),
+                    Field::Name | Field::Family => theme.paint_name(&padded, Role::Name),
+                    Field::Variable if padded.trim_end() == "yes" => {
+                        theme.paint(&padded, Role::Variable)
+                    }
+                    _ => padded,
+                }
+            })
+            .collect();
+        writeln!(w, "{}", line.join("  "))?;
+    }
+    Ok(())
+}
@@ -1,9 +1,15 @@
 //! typg CLI.
 //!
-//! The CLI exposes three user-facing modes:
+//! The CLI exposes these user-facing modes:
 //! - `find` for live scans over directories,
 //! - `cache` for reusing saved metadata or an LMDB index,
-//! - `serve` for HTTP access to the same query model.
+//! - `serve` for HTTP access to the same query model,
+//! - `validate` for lightweight QA checks in CI,
+//! - `watch` for keeping a cache or index in sync as fonts come and go,
+//! - `show` for dumping one font's metadata, including a font piped on stdin,
+//! - `inspect` for a deeper look at one font: name records, table directory,
+//!   features per script and language, cmap and OS/2 metrics,
+//! - `merge` for combining saved result files from several runs or machines.
 //!
 //! The command-line flags map directly onto the shared `Query` type in
 //! `typg-core`, so the same filter semantics apply across live, cached, HTTP,
@@ -11,32 +17,71 @@
 //!
 //! Made by FontLab https://www.fontlab.com/
 
+mod fields;
+mod remote;
 mod server;
+mod template;
+mod theme;
+mod watch;
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
-use std::fs::{self, File};
-use std::io::{self, BufRead, BufReader, BufWriter, IsTerminal, Write};
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::num::NonZeroUsize;
 use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Context, Result};
-use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum, ValueHint};
+use clap::{ArgAction, ArgGroup, Args, Parser, Subcommand, ValueEnum, ValueHint};
+use rayon::prelude::*;
 use regex::Regex;
 use serde_json::Deserializer;
 use tokio::runtime::Builder;
 
-use typg_core::output::{write_json_pretty, write_ndjson};
+use fields::{write_fields, Field};
+use remote::Remote;
+use server::{SearchRequest, SearchResponse};
+use template::{write_template, Template};
+use theme::{Role, Theme};
+use typg_core::cache::{
+    collection_path, list_collections, load_cache, load_roots, merge_entries,
+    parse_collection_name, record_roots, sort_entries, write_cache, write_roots, ScanRoot,
+};
+use typg_core::diff::{changed_faces, ChangeStatus, FaceChange};
+use typg_core::discovery::path_key;
+use typg_core::family::{family_key, limit_per_family};
+use typg_core::inspect::{inspect_file, inspect_font, FontInspection};
+use typg_core::output::{
+    file_uri, rfc3339_utc, to_json_value, write_css, write_fontconfig, write_json_envelope,
+    write_json_pretty_with, write_ndjson_with, CodepointFormat, Provenance,
+};
+use typg_core::presets::Preset;
 use typg_core::query::{
-    parse_codepoint_list, parse_family_class, parse_tag_list, parse_u16_range, FamilyClassFilter,
-    Query,
+    parse_codepoint_list, parse_coverage_filter, parse_embedding, parse_family_class,
+    parse_name_pattern, parse_path_glob, parse_query_expr, parse_table_checksum, parse_tag_list,
+    parse_u16_range, parse_unicode_block, FamilyClassFilter, NameMatch, Query,
 };
+use typg_core::rank::{rank, RankedMatch};
 use typg_core::search::{
-    filter_cached, search, search_streaming, SearchOptions, TypgFontFaceMatch,
+    expand_instances, filter_cached, group_families, hash_bytes, hash_file, read_metadata, search,
+    search_each, search_with_report, sort_matches_by_keys, ErrorPolicy, ScanCache, SearchOptions,
+    SearchReport, SortKey, TypgFamilyGroup, TypgFontFaceMatch, TypgFontFaceMeta, TypgInstanceRow,
+};
+use typg_core::select::nearest;
+use typg_core::stats::{weight_stats, WeightStats};
+use typg_core::validate::{
+    duplicate_names, validate, CheckStatus, DuplicateKind, DuplicateName, FontReport,
+    ValidationProfile,
 };
 
 #[cfg(feature = "hpindex")]
-use typg_core::index::FontIndex;
+use typg_core::index::{FontIndex, IndexWriter, IngestStats};
+#[cfg(feature = "hpindex")]
+use typg_core::search::search_each_file;
 
 /// Top-level CLI definition.
 #[derive(Debug, Parser)]
@@ -50,6 +95,26 @@ pub struct Cli {
     #[arg(short = 'q', long = "quiet", global = true, action = ArgAction::SetTrue)]
     quiet: bool,
 
+    /// Address space to map for the LMDB index, the most it can grow to (e.g. 512MB; default 10GB, 1GB on 32-bit)
+    #[cfg(feature = "hpindex")]
+    #[arg(
+        long = "index-map-size",
+        global = true,
+        value_name = "SIZE",
+        value_parser = parse_byte_size
+    )]
+    index_map_size: Option<usize>,
+
+    /// Use this named collection of the cache and index (e.g. masters) instead of the default store; cache find takes several (masters,clients) and searches them together
+    #[arg(
+        long = "collection",
+        global = true,
+        value_name = "NAME",
+        value_delimiter = ',',
+        value_parser = parse_collection
+    )]
+    collection_names: Vec<String>,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -66,6 +131,32 @@ enum Command {
 
     /// Start an HTTP search server
     Serve(ServeArgs),
+
+    /// Keep the cache or index open and answer --via-daemon searches over a unix socket
+    Daemon(DaemonArgs),
+
+    /// Run QA checks on fonts and report pass/warn/fail per font
+    Validate(ValidateArgs),
+
+    /// Keep the cache or index in sync with font folders as files change
+    Watch(WatchArgs),
+
+    /// Print the metadata of one font file, or of a font read from stdin (-)
+    Show(ShowArgs),
+
+    /// Print every name record, table, axis, script/language feature set,
+    /// cmap summary and OS/2 metric of one font
+    Inspect(InspectArgs),
+
+    /// Combine JSON/NDJSON result files into one result set
+    Merge(MergeArgs),
+
+    /// Print the face of a family a browser would use for a weight, width and style
+    Select(SelectArgs),
+
+    /// Time discovery, reading, parsing and matching over a corpus and report throughput
+    #[command(hide = true)]
+    Bench(BenchArgs),
 }
 
 /// Cache management subcommands.
@@ -79,8 +170,26 @@ enum CacheCommand {
     Find(Box<CacheFindArgs>),
     /// Remove entries for fonts that no longer exist on disk
     Clean(CacheCleanArgs),
+    /// Remove the entries matching a query, e.g. --name "Old Corp"
+    Remove(Box<CacheRemoveArgs>),
+    /// Check that cached fonts still exist and, with --hash, still match their content hash
+    Verify(CacheVerifyArgs),
+    /// Check that the LMDB index's databases agree with each other; --repair fixes what it finds
+    Fsck(CacheFsckArgs),
+    /// Rewrite the LMDB index without its free pages to reclaim disk space after removals
+    Compact(CacheCompactArgs),
+    /// Write the LMDB index to a portable, compressed snapshot file
+    Export(CacheExportArgs),
+    /// Load a snapshot written by cache export into the LMDB index
+    Import(CacheImportArgs),
+    /// Copy the catalog between the JSON cache and the LMDB index without re-reading fonts
+    Migrate(CacheMigrateArgs),
     /// Show cache location, size, and entry count
     Info(CacheInfoArgs),
+    /// Scan paths and report fonts whose metadata differs from the cache
+    Changed(CacheChangedArgs),
+    /// Rescan the folders earlier `cache add` runs recorded, with the options each was added with
+    Refresh(CacheRefreshArgs),
 }
 
 /// HTTP server configuration.
@@ -89,6 +198,390 @@ struct ServeArgs {
     /// Address to bind (host:port)
     #[arg(long = "bind", default_value = "127.0.0.1:8765")]
     bind: String,
+
+    /// Speak the Model Context Protocol (JSON-RPC) on stdin/stdout instead of HTTP, offering find, cache_find and inspect tools
+    #[arg(
+        long = "mcp",
+        action = ArgAction::SetTrue,
+        conflicts_with_all = [
+            "bind",
+            "access_log",
+            "serve_roots",
+            "token",
+            "cors_origins",
+            "max_concurrent_searches",
+            "request_timeout"
+        ]
+    )]
+    mcp: bool,
+
+    /// Highest `jobs` a request may ask for; requests without `jobs` get this many
+    #[arg(long = "max-jobs", value_hint = ValueHint::Other)]
+    max_jobs: Option<usize>,
+
+    /// Live scans allowed to run at once; more wait for a free slot
+    #[arg(long = "max-scans", value_hint = ValueHint::Other)]
+    max_scans: Option<usize>,
+
+    /// Append one NDJSON line per /search request (time, client, query, duration, matches) to FILE
+    #[arg(long = "access-log", value_hint = ValueHint::FilePath)]
+    access_log: Option<PathBuf>,
+
+    /// Let GET /font-file serve font files under DIR (repeatable); without it the endpoint is off
+    #[arg(long = "serve-root", value_name = "DIR", value_hint = ValueHint::DirPath)]
+    serve_roots: Vec<PathBuf>,
+
+    /// Require `Authorization: Bearer SECRET` on every endpoint but /health
+    #[arg(long = "token", value_name = "SECRET", value_hint = ValueHint::Other)]
+    token: Option<String>,
+
+    /// Serve HTTPS with the PEM certificate chain in FILE (leaf first); needs --tls-key
+    #[arg(
+        long = "tls-cert",
+        value_name = "FILE",
+        requires = "tls_key",
+        conflicts_with = "mcp",
+        value_hint = ValueHint::FilePath
+    )]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM private key for --tls-cert
+    #[arg(
+        long = "tls-key",
+        value_name = "FILE",
+        requires = "tls_cert",
+        value_hint = ValueHint::FilePath
+    )]
+    tls_key: Option<PathBuf>,
+
+    /// Let browser apps served from ORIGIN call the server (repeatable; `*` for any origin)
+    #[arg(long = "cors-origin", value_name = "ORIGIN", value_hint = ValueHint::Url)]
+    cors_origins: Vec<String>,
+
+    /// /search requests allowed in flight at once; more are refused with 429
+    #[arg(long = "max-concurrent-searches", value_hint = ValueHint::Other)]
+    max_concurrent_searches: Option<usize>,
+
+    /// Answer 503 to requests with no response after SECS seconds
+    #[arg(
+        long = "request-timeout",
+        value_name = "SECS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        value_hint = ValueHint::Other
+    )]
+    request_timeout: Option<u64>,
+
+    /// Index queries allowed to run at once; more wait for a free slot
+    #[cfg(feature = "hpindex")]
+    #[arg(
+        long = "max-readers",
+        default_value_t = typg_core::index::DEFAULT_MAX_READERS,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    max_readers: u32,
+
+    /// Disable OS read-ahead on the index (helps when it exceeds free RAM)
+    #[cfg(feature = "hpindex")]
+    #[arg(long = "no-read-ahead", action = ArgAction::SetTrue)]
+    no_read_ahead: bool,
+
+    /// Let requests name DIR as their index_path (repeatable); without it only the default index is served
+    #[cfg(feature = "hpindex")]
+    #[arg(long = "index-path", value_name = "DIR", value_hint = ValueHint::DirPath)]
+    index_paths: Vec<PathBuf>,
+
+    /// Open and check the index (the default one, or DIR) before binding the port; refuse to start if it is missing or unreadable
+    #[cfg(feature = "hpindex")]
+    #[arg(
+        long = "preload-index",
+        value_name = "DIR",
+        num_args = 0..=1,
+        require_equals = true,
+        value_hint = ValueHint::DirPath
+    )]
+    preload_index: Option<Option<PathBuf>>,
+
+    /// With --preload-index, read and decode every index record so the first queries hit a warm page cache
+    #[cfg(feature = "hpindex")]
+    #[arg(long = "warm-index", requires = "preload_index", action = ArgAction::SetTrue)]
+    warm_index: bool,
+
+    /// Allow clients to change the index over HTTP (POST /index/add, POST /index/clean, DELETE /index)
+    #[cfg(feature = "hpindex")]
+    #[arg(long = "index-admin", action = ArgAction::SetTrue)]
+    index_admin: bool,
+}
+
+/// Arguments for `daemon`.
+#[derive(Debug, Args)]
+struct DaemonArgs {
+    /// Socket to listen on (defaults to daemon.sock in the cache directory)
+    #[arg(long = "socket", value_name = "PATH", value_hint = ValueHint::FilePath)]
+    socket: Option<PathBuf>,
+
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
+
+    /// Override cache file location
+    #[arg(long = "cache-path", value_hint = ValueHint::FilePath)]
+    cache_path: Option<PathBuf>,
+
+    /// Answer from the LMDB index instead of the JSON cache (requires hpindex feature)
+    #[arg(long = "index", action = ArgAction::SetTrue)]
+    use_index: bool,
+
+    /// Override LMDB index directory
+    #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
+    index_path: Option<PathBuf>,
+
+    /// With --index, read and decode every index record at startup so the first queries hit a warm page cache
+    #[cfg(feature = "hpindex")]
+    #[arg(long = "warm-index", requires = "use_index", action = ArgAction::SetTrue)]
+    warm_index: bool,
+}
+
+/// Arguments for `watch`.
+#[derive(Debug, Args)]
+struct WatchArgs {
+    /// Paths to watch (directories or individual font files); defaults to the roots `cache add` recorded
+    #[arg(value_hint = ValueHint::DirPath)]
+    paths: Vec<PathBuf>,
+
+    /// Follow symlinks during directory traversal
+    #[arg(long = "follow-symlinks", action = ArgAction::SetTrue)]
+    follow_symlinks: bool,
+
+    /// Include hidden files and directories (names starting with `.`)
+    #[arg(long = "hidden", action = ArgAction::SetTrue)]
+    hidden: bool,
+
+    /// Number of parallel worker threads
+    #[arg(short = 'J', long = "jobs", value_hint = ValueHint::Other)]
+    jobs: Option<usize>,
+
+    /// Name table IDs to read into font names (default 1,16,2,17,4,6; e.g. add 25)
+    #[arg(long = "name-ids", value_delimiter = ',', value_hint = ValueHint::Other)]
+    name_ids: Vec<u16>,
+
+    /// Wait until files have been quiet this long before syncing (milliseconds)
+    #[arg(long = "debounce", default_value_t = 500, value_hint = ValueHint::Other)]
+    debounce_ms: u64,
+
+    /// Detach from the terminal and keep watching in the background
+    #[arg(long = "daemon", action = ArgAction::SetTrue)]
+    daemon: bool,
+
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
+
+    /// Override cache file location
+    #[arg(long = "cache-path", value_hint = ValueHint::FilePath)]
+    cache_path: Option<PathBuf>,
+
+    /// Use LMDB index backend instead of JSON cache
+    #[arg(long = "index", action = ArgAction::SetTrue)]
+    use_index: bool,
+
+    /// Override LMDB index directory
+    #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
+    index_path: Option<PathBuf>,
+}
+
+/// Arguments for `validate`.
+#[derive(Debug, Args)]
+struct ValidateArgs {
+    /// Paths to check (directories or individual font files)
+    #[arg(
+        value_hint = ValueHint::DirPath,
+        required_unless_present_any = ["system_fonts", "stdin_paths"]
+    )]
+    paths: Vec<PathBuf>,
+
+    /// Read additional paths from stdin, one per line
+    #[arg(long = "stdin-paths", action = ArgAction::SetTrue)]
+    stdin_paths: bool,
+
+    /// Include platform-default system font directories
+    #[arg(long = "system-fonts", action = ArgAction::SetTrue)]
+    system_fonts: bool,
+
+    /// Follow symlinks during directory traversal
+    #[arg(long = "follow-symlinks", action = ArgAction::SetTrue)]
+    follow_symlinks: bool,
+
+    /// Include hidden files and directories (names starting with `.`)
+    #[arg(long = "hidden", action = ArgAction::SetTrue)]
+    hidden: bool,
+
+    /// Number of parallel worker threads
+    #[arg(short = 'J', long = "jobs", value_hint = ValueHint::Other)]
+    jobs: Option<usize>,
+
+    /// Set of checks to run
+    #[arg(long = "profile", default_value_t = ValidateProfile::FontbakeryLite, value_enum)]
+    profile: ValidateProfile,
+
+    /// Instead of checking each face, list PostScript and full names used in more than one file
+    #[arg(long = "duplicates", action = ArgAction::SetTrue, conflicts_with = "profile")]
+    duplicates: bool,
+
+    /// Exit non-zero on warnings as well as failures
+    #[arg(long = "strict", action = ArgAction::SetTrue)]
+    strict: bool,
+
+    /// Output reports as a JSON array
+    #[arg(long = "json", action = ArgAction::SetTrue, conflicts_with = "ndjson")]
+    json: bool,
+
+    /// Output reports as newline-delimited JSON
+    #[arg(long = "ndjson", action = ArgAction::SetTrue)]
+    ndjson: bool,
+}
+
+/// Arguments for `merge`.
+#[derive(Debug, Args)]
+struct MergeArgs {
+    /// Result files written with --json or --ndjson (or a JSON cache); "-" reads stdin
+    #[arg(required = true, value_hint = ValueHint::FilePath)]
+    files: Vec<PathBuf>,
+
+    /// Keep one entry per face: by path (the later file wins, as in `cache add`) or by font ID
+    #[arg(
+        long = "dedupe",
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "path",
+        require_equals = true
+    )]
+    dedupe: Option<DedupeKey>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// Arguments for `select`.
+#[derive(Debug, Args)]
+struct SelectArgs {
+    /// Paths to search (directories or files)
+    #[arg(
+        value_name = "PATHS",
+        value_hint = ValueHint::DirPath,
+        required_unless_present_any = ["system_fonts", "stdin_paths"]
+    )]
+    roots: Vec<PathBuf>,
+
+    /// Read newline-delimited paths from STDIN
+    #[arg(long = "stdin-paths", action = ArgAction::SetTrue)]
+    stdin_paths: bool,
+
+    /// Include common system font directories automatically
+    #[arg(long = "system-fonts", action = ArgAction::SetTrue)]
+    system_fonts: bool,
+
+    /// Family to choose from (typographic family name, case-insensitive)
+    #[arg(long = "family", value_hint = ValueHint::Other)]
+    family: String,
+
+    /// Requested CSS font-weight (1-1000)
+    #[arg(long = "weight", default_value_t = typg_core::select::NORMAL_WEIGHT, value_parser = parse_css_weight)]
+    weight: f32,
+
+    /// Requested CSS font-stretch in percent (e.g. 75 for condensed)
+    #[arg(long = "width", default_value_t = typg_core::select::NORMAL_WIDTH, value_parser = parse_css_width)]
+    width: f32,
+
+    /// Request an italic face (oblique, then upright, when the family has none)
+    #[arg(long = "italic", action = ArgAction::SetTrue)]
+    italic: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// Arguments for `bench`.
+#[derive(Debug, Args)]
+struct BenchArgs {
+    /// Fonts to measure (directories or files)
+    #[arg(
+        value_hint = ValueHint::DirPath,
+        required_unless_present_any = ["system_fonts", "stdin_paths"]
+    )]
+    paths: Vec<PathBuf>,
+
+    /// Read newline-delimited paths from STDIN
+    #[arg(long = "stdin-paths", action = ArgAction::SetTrue)]
+    stdin_paths: bool,
+
+    /// Include common system font directories automatically
+    #[arg(long = "system-fonts", action = ArgAction::SetTrue)]
+    system_fonts: bool,
+
+    /// Follow symlinks during directory traversal
+    #[arg(long = "follow-symlinks", action = ArgAction::SetTrue)]
+    follow_symlinks: bool,
+
+    /// Include hidden files and directories (names starting with `.`)
+    #[arg(long = "hidden", action = ArgAction::SetTrue)]
+    hidden: bool,
+
+    /// Number of parallel worker threads
+    #[arg(short = 'J', long = "jobs", value_hint = ValueHint::Other)]
+    jobs: Option<usize>,
+
+    /// Passes over the corpus; the first is usually slower, reading past a cold file cache
+    #[arg(long = "runs", default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    runs: u32,
+
+    /// Output every pass as JSON
+    #[arg(long = "json", action = ArgAction::SetTrue)]
+    json: bool,
+}
+
+/// What `merge --dedupe` treats as the same face.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum DedupeKey {
+    /// File path and collection index, compared like cache entries
+    Path,
+    /// Content-derived font ID, so copies at different paths collapse too
+    Id,
+}
+
+/// Arguments for `show`.
+#[derive(Debug, Args)]
+struct ShowArgs {
+    /// Font file to inspect; "-" reads the font binary from stdin
+    #[arg(value_hint = ValueHint::FilePath)]
+    file: PathBuf,
+
+    /// Name table IDs to read into font names (default 1,16,2,17,4,6; e.g. add 25)
+    #[arg(long = "name-ids", value_delimiter = ',', value_hint = ValueHint::Other)]
+    name_ids: Vec<u16>,
+
+    /// Output faces as a JSON array
+    #[arg(long = "json", action = ArgAction::SetTrue, conflicts_with = "ndjson")]
+    json: bool,
+
+    /// Output faces as newline-delimited JSON
+    #[arg(long = "ndjson", action = ArgAction::SetTrue)]
+    ndjson: bool,
+
+    /// Codepoint representation in JSON/NDJSON output (chars|ranges)
+    #[arg(long = "codepoint-format", default_value_t = CodepointOutput::Chars, value_enum)]
+    codepoint_format: CodepointOutput,
+}
+
+/// Arguments for `inspect`.
+#[derive(Debug, Args)]
+struct InspectArgs {
+    /// Font file to inspect; "-" reads the font binary from stdin
+    #[arg(value_hint = ValueHint::FilePath)]
+    file: PathBuf,
+
+    /// Output faces as a JSON array
+    #[arg(long = "json", action = ArgAction::SetTrue)]
+    json: bool,
 }
 
 /// Arguments for `cache add`.
@@ -97,7 +590,7 @@ struct CacheAddArgs {
     /// Paths to scan (directories or individual font files)
     #[arg(
         value_hint = ValueHint::DirPath,
-        required_unless_present_any = ["system_fonts", "stdin_paths"]
+        required_unless_present_any = ["system_fonts", "stdin_paths", "from_ndjson", "manifest"]
     )]
     paths: Vec<PathBuf>,
 
@@ -109,14 +602,45 @@ struct CacheAddArgs {
     #[arg(long = "system-fonts", action = ArgAction::SetTrue)]
     system_fonts: bool,
 
+    /// Ingest precomputed results (NDJSON or a JSON array; - for stdin) instead of scanning
+    #[arg(
+        long = "from-ndjson",
+        value_hint = ValueHint::FilePath,
+        conflicts_with_all = ["paths", "stdin_paths", "system_fonts"]
+    )]
+    from_ndjson: Option<PathBuf>,
+
+    /// Re-read only the files of a manifest (path, mtime, size per line; - for stdin) that changed
+    #[arg(
+        long = "manifest",
+        value_hint = ValueHint::FilePath,
+        conflicts_with_all = ["paths", "stdin_paths", "system_fonts", "from_ndjson"]
+    )]
+    manifest: Option<PathBuf>,
+
     /// Follow symlinks during directory traversal
     #[arg(long = "follow-symlinks", action = ArgAction::SetTrue)]
     follow_symlinks: bool,
 
+    /// Include hidden files and directories (names starting with `.`)
+    #[arg(long = "hidden", action = ArgAction::SetTrue)]
+    hidden: bool,
+
     /// Number of parallel worker threads
     #[arg(short = 'J', long = "jobs", value_hint = ValueHint::Other)]
     jobs: Option<usize>,
 
+    /// Name table IDs to read into font names (default 1,16,2,17,4,6; e.g. add 25)
+    #[arg(long = "name-ids", value_delimiter = ',', value_hint = ValueHint::Other)]
+    name_ids: Vec<u16>,
+
+    #[command(flatten)]
+    errors: ErrorArgs,
+
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
+
     /// Override cache file location
     #[arg(long = "cache-path", value_hint = ValueHint::FilePath)]
     cache_path: Option<PathBuf>,
@@ -128,6 +652,15 @@ struct CacheAddArgs {
     /// Override LMDB index directory
     #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
     index_path: Option<PathBuf>,
+
+    /// With --index, commit after every N font files written instead of once at the end
+    #[arg(
+        long = "commit-every",
+        value_name = "N",
+        requires = "use_index",
+        value_hint = ValueHint::Other
+    )]
+    commit_every: Option<NonZeroUsize>,
 }
 
 /// Output format options shared across subcommands.
@@ -141,29 +674,90 @@ struct OutputArgs {
     #[arg(long = "ndjson", action = ArgAction::SetTrue)]
     ndjson: bool,
 
+    /// Codepoint representation in JSON/NDJSON output (chars|ranges)
+    #[arg(long = "codepoint-format", default_value_t = CodepointOutput::Chars, value_enum)]
+    codepoint_format: CodepointOutput,
+
+    /// Wrap --json output in an envelope recording typg version, query, roots and time
+    #[arg(long = "provenance", action = ArgAction::SetTrue, requires = "json")]
+    provenance: bool,
+
+    /// Write a configuration file for the matched fonts instead of a listing
+    #[arg(
+        long = "format",
+        value_enum,
+        conflicts_with_all = ["json", "ndjson", "paths", "columns", "fields", "expand_instances"]
+    )]
+    format: Option<ExportFormat>,
+
+    /// With --format css, let one unicode-range span gaps of up to N codepoints the font lacks (default 0: exact)
+    #[arg(
+        long = "unicode-range-gap",
+        value_name = "N",
+        default_value_t = 0,
+        requires = "format"
+    )]
+    unicode_range_gap: u32,
+
+    /// Output one line per match from a template like '{path}\t{family}\t{weight}' (--fields names or JSON metadata keys)
+    #[arg(
+        long = "template",
+        value_parser = Template::parse,
+        conflicts_with_all = ["json", "ndjson", "paths", "columns", "fields", "format", "expand_instances"]
+    )]
+    template: Option<Template>,
+
     /// Output file paths only (with #index for TTC faces)
     #[arg(
         long = "paths",
         action = ArgAction::SetTrue,
-        conflicts_with_all = ["json", "ndjson", "columns"]
+        conflicts_with_all = ["json", "ndjson", "columns", "fields"]
     )]
     paths: bool,
 
-    /// Output as aligned columns
-    #[arg(long = "columns", action = ArgAction::SetTrue)]
-    columns: bool,
+    /// Print paths as percent-encoded file:// URIs, in path lists, JSON and every other listing
+    #[arg(long = "uri", action = ArgAction::SetTrue, conflicts_with = "format")]
+    uri: bool,
+
+    /// Output as aligned columns; optionally choose which (path,name,tags)
+    #[arg(long = "columns", value_enum, value_delimiter = ',', num_args = 0..=1, require_equals = true)]
+    columns: Option<Vec<Column>>,
+
+    /// Middle-truncate paths in --columns/--fields output to at most N characters
+    #[arg(long = "max-path-width", value_hint = ValueHint::Other)]
+    max_path_width: Option<usize>,
+
+    /// Output aligned columns of the chosen fields (implies --columns)
+    #[arg(long = "fields", value_delimiter = ',', value_enum, conflicts_with_all = ["json", "ndjson"])]
+    fields: Vec<Field>,
+
+    /// Omit the header row from --fields output
+    #[arg(long = "no-header", action = ArgAction::SetTrue, requires = "fields")]
+    no_header: bool,
 
     /// Show individual TTC/OTC collection faces (path#index); default deduplicates by path
     #[arg(long = "collections", action = ArgAction::SetTrue)]
     collections: bool,
 
-    /// Colorize output (auto detects terminal)
+    /// List each named instance of a variable font as its own row
+    #[arg(long = "expand-instances", action = ArgAction::SetTrue, conflicts_with_all = ["paths", "columns", "fields"])]
+    expand_instances: bool,
+
+    /// Colorize output (auto detects terminal; NO_COLOR disables)
     #[arg(long = "color", default_value_t = ColorChoice::Auto, value_enum)]
     color: ColorChoice,
+
+    /// Color theme: default, mono, none, or role=SGR overrides such as name=1;33:match=7 (default: $TYPOG_THEME)
+    #[arg(long = "theme", value_hint = ValueHint::Other)]
+    theme: Option<String>,
 }
 
 #[derive(Debug, Args)]
 struct CacheListArgs {
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
+
     /// Override cache location (defaults to ~/.cache/typg/cache.json)
     #[arg(long = "cache-path", value_hint = ValueHint::FilePath)]
     cache_path: Option<PathBuf>,
@@ -182,6 +776,10 @@ struct CacheListArgs {
 
 #[derive(Debug, Args)]
 struct CacheFindArgs {
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor); with --index, several (system,user) search each profile's index
+    #[arg(long = "cache", value_enum, value_delimiter = ',')]
+    profiles: Vec<CacheProfile>,
+
     /// Override cache location (defaults to ~/.cache/typg/cache.json)
     #[arg(long = "cache-path", value_hint = ValueHint::FilePath)]
     cache_path: Option<PathBuf>,
@@ -190,9 +788,20 @@ struct CacheFindArgs {
     #[arg(long = "index", action = ArgAction::SetTrue)]
     use_index: bool,
 
-    /// Override index directory (defaults to ~/.cache/typg/index/)
+    /// Override index directory (defaults to ~/.cache/typg/index/); repeat to search several indexes at once
     #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
-    index_path: Option<PathBuf>,
+    index_paths: Vec<PathBuf>,
+
+    /// Ask a running `typg daemon` (optionally at SOCKET), which holds its cache or index open, instead of loading one here
+    #[arg(
+        long = "via-daemon",
+        value_name = "SOCKET",
+        num_args = 0..=1,
+        require_equals = true,
+        value_hint = ValueHint::FilePath,
+        conflicts_with_all = ["profiles", "cache_path", "use_index", "index_paths", "rank"]
+    )]
+    via_daemon: Option<Option<PathBuf>>,
 
     /// Require fonts to define these axis tags
     #[arg(short = 'a', long = "axes", value_delimiter = ',', value_hint = ValueHint::Other)]
@@ -206,14 +815,30 @@ struct CacheFindArgs {
     #[arg(short = 's', long = "scripts", value_delimiter = ',', value_hint = ValueHint::Other)]
     scripts: Vec<String>,
 
+    /// Require fonts to declare these language systems (e.g. TRK,SRB)
+    #[arg(long = "langs", value_delimiter = ',', value_hint = ValueHint::Other)]
+    langs: Vec<String>,
+
     /// Require fonts to contain these table tags
     #[arg(short = 'T', long = "tables", value_delimiter = ',', value_hint = ValueHint::Other)]
     tables: Vec<String>,
 
+    /// Require tables with these table directory checksums (TAG=HEX, e.g. glyf=0xDEADBEEF)
+    #[arg(
+        long = "table-checksum",
+        value_name = "TAG=HEX",
+        value_delimiter = ',',
+        value_hint = ValueHint::Other
+    )]
+    table_checksums: Vec<String>,
+
     /// Regex patterns that must match at least one font name
     #[arg(short = 'n', long = "name", value_hint = ValueHint::Other)]
     name_patterns: Vec<String>,
 
+    #[command(flatten)]
+    name_match: NameMatchArgs,
+
     /// Regex patterns that must match creator info (copyright, trademark, manufacturer, designer, description, URLs, license)
     #[arg(short = 'c', long = "creator", value_hint = ValueHint::Other)]
     creator_patterns: Vec<String>,
@@ -230,6 +855,14 @@ struct CacheFindArgs {
     #[arg(short = 't', long = "text")]
     text: Option<String>,
 
+    /// Require full coverage of a Unicode block (e.g. "Cyrillic"; repeatable)
+    #[arg(long = "unicode-block", value_hint = ValueHint::Other)]
+    unicode_blocks: Vec<String>,
+
+    /// Require minimum block coverage as BLOCK:PERCENT (e.g. "Cyrillic:90%"; repeatable)
+    #[arg(long = "coverage", value_hint = ValueHint::Other)]
+    coverage: Vec<String>,
+
     /// Only include variable fonts
     #[arg(short = 'v', long = "variable", action = ArgAction::SetTrue)]
     variable: bool,
@@ -246,8 +879,88 @@ struct CacheFindArgs {
     #[arg(long = "family-class", value_hint = ValueHint::Other)]
     family_class: Option<String>,
 
+    /// Require fonts whose OS/2 fsType allows at least this embedding (installable|editable|preview|restricted)
+    #[arg(long = "embedding", value_hint = ValueHint::Other)]
+    embedding: Option<String>,
+
+    /// Require at least N glyphs (maxp numGlyphs)
+    #[arg(long = "min-glyphs", value_hint = ValueHint::Other)]
+    min_glyphs: Option<u16>,
+
+    /// Require at least N variation axes (e.g. 2 for multi-axis designs)
+    #[arg(long = "min-axes", value_hint = ValueHint::Other)]
+    min_axes: Option<usize>,
+
+    /// Allow at most N variation axes (e.g. 1 with --variable for weight-only VFs)
+    #[arg(long = "max-axes", value_hint = ValueHint::Other)]
+    max_axes: Option<usize>,
+
+    /// Require one of these OS/2 vendor IDs (comma-separated, case-insensitive)
+    #[arg(long = "vendor", value_delimiter = ',', value_hint = ValueHint::Other)]
+    vendors: Vec<String>,
+
+    /// Require a named instance of a variable font (e.g. SemiBold; repeatable; case and spaces ignored)
+    #[arg(long = "instance", value_hint = ValueHint::Other)]
+    instances: Vec<String>,
+
+    /// Only match faces inside TTC/OTC collections
+    #[arg(long = "only-collections", action = ArgAction::SetTrue)]
+    only_collections: bool,
+
+    /// Only match the face at this index within each collection
+    #[arg(long = "collection-index", value_hint = ValueHint::Other)]
+    collection_index: Option<u32>,
+
+    /// Skip fonts that map characters only through legacy (format 0/6) or symbol cmap subtables
+    #[arg(long = "unicode-cmap-only", action = ArgAction::SetTrue)]
+    unicode_cmap_only: bool,
+
+    /// Boolean filter expression, ANDed with the other filters (e.g. "script:arab OR script:hebr")
+    #[arg(long = "query-expr", value_hint = ValueHint::Other)]
+    query_expr: Option<String>,
+
+    /// Start from a built-in query; other filters narrow it further
+    #[arg(long = "preset", value_enum)]
+    preset: Option<QueryPreset>,
+
+    #[command(flatten)]
+    exclude: ExcludeArgs,
+
+    #[command(flatten)]
+    ranking: RankArgs,
+
+    /// Keep at most N faces per family (after sorting)
+    #[arg(long = "per-family", value_hint = ValueHint::Other)]
+    per_family: Option<usize>,
+
+    /// Collapse results into one entry per family listing member styles
+    #[arg(long = "group-by", value_enum, conflicts_with_all = ["rank", "paths", "fields", "expand_instances", "format", "template"])]
+    group_by: Option<GroupBy>,
+
+    /// Order results by these keys instead of by path, most significant first
+    /// (e.g. `family,weight,width,italic`)
+    #[arg(
+        long = "sort",
+        value_enum,
+        value_delimiter = ',',
+        conflicts_with = "rank"
+    )]
+    sort: Vec<SortBy>,
+
+    /// Reverse the result order (descending with --sort)
+    #[arg(long = "reverse", action = ArgAction::SetTrue, conflicts_with = "rank")]
+    reverse: bool,
+
+    /// Skip the first N results (after sorting)
+    #[arg(long = "offset", value_hint = ValueHint::Other)]
+    offset: Option<usize>,
+
+    /// Output at most N results (after sorting and --offset)
+    #[arg(long = "limit", value_hint = ValueHint::Other)]
+    limit: Option<usize>,
+
     /// Only output the count of matching fonts (useful for scripting)
-    #[arg(long = "count", action = ArgAction::SetTrue, conflicts_with_all = ["json", "ndjson", "paths", "columns"])]
+    #[arg(long = "count", action = ArgAction::SetTrue, conflicts_with_all = ["json", "ndjson", "paths", "columns", "fields", "template"])]
     count_only: bool,
 
     #[command(flatten)]
@@ -256,6 +969,10 @@ struct CacheFindArgs {
 
 #[derive(Debug, Args)]
 struct CacheCleanArgs {
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
+
     /// Override cache location (defaults to ~/.cache/typg/cache.json)
     #[arg(long = "cache-path", value_hint = ValueHint::FilePath)]
     cache_path: Option<PathBuf>,
@@ -267,10 +984,60 @@ struct CacheCleanArgs {
     /// Override index directory (defaults to ~/.cache/typg/index/)
     #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
     index_path: Option<PathBuf>,
+
+    /// Also remove entries whose file no longer matches its recorded content hash
+    #[arg(long = "verify-hash", action = ArgAction::SetTrue)]
+    verify_hash: bool,
+
+    /// List the entries that would be removed, one per line with the reason, without changing anything
+    #[arg(long = "dry-run", action = ArgAction::SetTrue)]
+    dry_run: bool,
 }
 
 #[derive(Debug, Args)]
-struct CacheInfoArgs {
+#[command(group(
+    ArgGroup::new("filters").required(true).multiple(true).args([
+        "axes",
+        "features",
+        "scripts",
+        "langs",
+        "tables",
+        "name_patterns",
+        "creator_patterns",
+        "license_patterns",
+        "codepoints",
+        "text",
+        "unicode_blocks",
+        "coverage",
+        "variable",
+        "weight",
+        "width",
+        "family_class",
+        "embedding",
+        "min_glyphs",
+        "min_axes",
+        "max_axes",
+        "vendors",
+        "instances",
+        "only_collections",
+        "collection_index",
+        "unicode_cmap_only",
+        "query_expr",
+        "preset",
+        "not_axes",
+        "not_features",
+        "not_scripts",
+        "not_tables",
+        "not_name",
+        "exclude_path",
+        "not_variable",
+    ])
+))]
+struct CacheRemoveArgs {
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
+
     /// Override cache location (defaults to ~/.cache/typg/cache.json)
     #[arg(long = "cache-path", value_hint = ValueHint::FilePath)]
     cache_path: Option<PathBuf>,
@@ -283,28 +1050,6 @@ struct CacheInfoArgs {
     #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
     index_path: Option<PathBuf>,
 
-    /// Output as JSON
-    #[arg(long = "json", action = ArgAction::SetTrue)]
-    json: bool,
-}
-
-#[derive(Debug, Args)]
-struct FindArgs {
-    /// Paths to search (directories or files)
-    #[arg(
-        value_hint = ValueHint::DirPath,
-        required_unless_present_any = ["system_fonts", "stdin_paths"]
-    )]
-    paths: Vec<PathBuf>,
-
-    /// Read newline-delimited paths from STDIN
-    #[arg(long = "stdin-paths", action = ArgAction::SetTrue)]
-    stdin_paths: bool,
-
-    /// Include common system font directories automatically
-    #[arg(long = "system-fonts", action = ArgAction::SetTrue)]
-    system_fonts: bool,
-
     /// Require fonts to define these axis tags
     #[arg(short = 'a', long = "axes", value_delimiter = ',', value_hint = ValueHint::Other)]
     axes: Vec<String>,
@@ -317,6 +1062,10 @@ struct FindArgs {
     #[arg(short = 's', long = "scripts", value_delimiter = ',', value_hint = ValueHint::Other)]
     scripts: Vec<String>,
 
+    /// Require fonts to declare these language systems (e.g. TRK,SRB)
+    #[arg(long = "langs", value_delimiter = ',', value_hint = ValueHint::Other)]
+    langs: Vec<String>,
+
     /// Require fonts to contain these table tags
     #[arg(short = 'T', long = "tables", value_delimiter = ',', value_hint = ValueHint::Other)]
     tables: Vec<String>,
@@ -325,6 +1074,9 @@ struct FindArgs {
     #[arg(short = 'n', long = "name", value_hint = ValueHint::Other)]
     name_patterns: Vec<String>,
 
+    #[command(flatten)]
+    name_match: NameMatchArgs,
+
     /// Regex patterns that must match creator info (copyright, trademark, manufacturer, designer, description, URLs, license)
     #[arg(short = 'c', long = "creator", value_hint = ValueHint::Other)]
     creator_patterns: Vec<String>,
@@ -341,6 +1093,14 @@ struct FindArgs {
     #[arg(short = 't', long = "text")]
     text: Option<String>,
 
+    /// Require full coverage of a Unicode block (e.g. "Cyrillic"; repeatable)
+    #[arg(long = "unicode-block", value_hint = ValueHint::Other)]
+    unicode_blocks: Vec<String>,
+
+    /// Require minimum block coverage as BLOCK:PERCENT (e.g. "Cyrillic:90%"; repeatable)
+    #[arg(long = "coverage", value_hint = ValueHint::Other)]
+    coverage: Vec<String>,
+
     /// Only include variable fonts
     #[arg(short = 'v', long = "variable", action = ArgAction::SetTrue)]
     variable: bool,
@@ -357,572 +1117,4308 @@ struct FindArgs {
     #[arg(long = "family-class", value_hint = ValueHint::Other)]
     family_class: Option<String>,
 
-    /// Follow symlinks while walking paths
-    #[arg(long = "follow-symlinks", action = ArgAction::SetTrue)]
-    follow_symlinks: bool,
+    /// Require fonts whose OS/2 fsType allows at least this embedding (installable|editable|preview|restricted)
+    #[arg(long = "embedding", value_hint = ValueHint::Other)]
+    embedding: Option<String>,
 
-    /// Number of worker threads (defaults to CPU count)
-    #[arg(short = 'J', long = "jobs", value_hint = ValueHint::Other)]
-    jobs: Option<usize>,
+    /// Require at least N glyphs (maxp numGlyphs)
+    #[arg(long = "min-glyphs", value_hint = ValueHint::Other)]
+    min_glyphs: Option<u16>,
 
-    /// Emit a single JSON array
-    #[arg(long = "json", action = ArgAction::SetTrue, conflicts_with = "ndjson")]
-    json: bool,
+    /// Require at least N variation axes (e.g. 2 for multi-axis designs)
+    #[arg(long = "min-axes", value_hint = ValueHint::Other)]
+    min_axes: Option<usize>,
 
-    /// Emit newline-delimited JSON
-    #[arg(long = "ndjson", action = ArgAction::SetTrue)]
-    ndjson: bool,
+    /// Allow at most N variation axes (e.g. 1 with --variable for weight-only VFs)
+    #[arg(long = "max-axes", value_hint = ValueHint::Other)]
+    max_axes: Option<usize>,
 
-    /// Emit newline-delimited font paths (with #index for TTC)
-    #[arg(
-        long = "paths",
-        action = ArgAction::SetTrue,
-        conflicts_with_all = ["json", "ndjson", "columns"]
-    )]
-    paths_only: bool,
+    /// Require one of these OS/2 vendor IDs (comma-separated, case-insensitive)
+    #[arg(long = "vendor", value_delimiter = ',', value_hint = ValueHint::Other)]
+    vendors: Vec<String>,
 
-    /// Format output as padded columns
-    #[arg(long = "columns", action = ArgAction::SetTrue)]
-    columns: bool,
+    /// Require a named instance of a variable font (e.g. SemiBold; repeatable; case and spaces ignored)
+    #[arg(long = "instance", value_hint = ValueHint::Other)]
+    instances: Vec<String>,
 
-    /// Show individual TTC/OTC collection faces (path#index); default deduplicates by path
-    #[arg(long = "collections", action = ArgAction::SetTrue)]
-    collections: bool,
+    /// Only match faces inside TTC/OTC collections
+    #[arg(long = "only-collections", action = ArgAction::SetTrue)]
+    only_collections: bool,
 
-    /// Only output the count of matching fonts (useful for scripting)
-    #[arg(long = "count", action = ArgAction::SetTrue, conflicts_with_all = ["json", "ndjson", "paths_only", "columns"])]
-    count_only: bool,
+    /// Only match the face at this index within each collection
+    #[arg(long = "collection-index", value_hint = ValueHint::Other)]
+    collection_index: Option<u32>,
 
-    /// Control colorized output (auto|always|never)
-    #[arg(long = "color", default_value_t = ColorChoice::Auto, value_enum)]
-    color: ColorChoice,
-}
+    /// Skip fonts that map characters only through legacy (format 0/6) or symbol cmap subtables
+    #[arg(long = "unicode-cmap-only", action = ArgAction::SetTrue)]
+    unicode_cmap_only: bool,
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
-enum ColorChoice {
-    Auto,
-    Always,
-    Never,
-}
+    /// Boolean filter expression, ANDed with the other filters (e.g. "script:arab OR script:hebr")
+    #[arg(long = "query-expr", value_hint = ValueHint::Other)]
+    query_expr: Option<String>,
 
-/// Parse CLI arguments and dispatch to the appropriate handler.
-pub fn run() -> Result<()> {
-    let cli = Cli::parse();
-    let quiet = cli.quiet;
+    /// Start from a built-in query; other filters narrow it further
+    #[arg(long = "preset", value_enum)]
+    preset: Option<QueryPreset>,
 
-    match cli.command {
-        Command::Find(args) => run_find(*args),
-        Command::Cache(cmd) => match cmd {
-            CacheCommand::Add(args) => run_cache_add(args, quiet),
-            CacheCommand::List(args) => run_cache_list(args),
-            CacheCommand::Find(args) => run_cache_find(*args),
-            CacheCommand::Clean(args) => run_cache_clean(args, quiet),
-            CacheCommand::Info(args) => run_cache_info(args),
-        },
-        Command::Serve(args) => run_serve(args),
-    }
+    #[command(flatten)]
+    exclude: ExcludeArgs,
+
+    /// List the entries that would be removed without changing anything
+    #[arg(long = "dry-run", action = ArgAction::SetTrue)]
+    dry_run: bool,
 }
 
-/// Search directories for matching fonts, streaming results where possible.
-fn run_find(args: FindArgs) -> Result<()> {
-    if matches!(args.jobs, Some(0)) {
-        return Err(anyhow!("--jobs must be at least 1"));
-    }
+#[derive(Debug, Args)]
+struct CacheVerifyArgs {
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
 
-    let stdin = io::stdin();
-    let paths = gather_paths(
-        &args.paths,
-        args.stdin_paths,
-        args.system_fonts,
-        stdin.lock(),
-    )?;
-    let query = build_query(&args)?;
-    let opts = SearchOptions {
-        follow_symlinks: args.follow_symlinks,
-        jobs: args.jobs,
-    };
+    /// Override cache location (defaults to ~/.cache/typg/cache.json)
+    #[arg(long = "cache-path", value_hint = ValueHint::FilePath)]
+    cache_path: Option<PathBuf>,
 
-    let output = OutputFormat::from_find(&args);
+    /// Use high-performance LMDB index instead of JSON cache (requires hpindex feature)
+    #[arg(long = "index", action = ArgAction::SetTrue)]
+    use_index: bool,
 
-    // Formats that need all results before writing
-    if args.count_only || output.json || output.columns {
-        let matches = search(&paths, &query, &opts)?;
-        if args.count_only {
-            println!("{}", matches.len());
-            return Ok(());
-        }
-        return write_matches(&matches, &output);
-    }
+    /// Override index directory (defaults to ~/.cache/typg/index/)
+    #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
+    index_path: Option<PathBuf>,
 
-    // Stream results to stdout as they're found
-    let (tx, rx) = std::sync::mpsc::channel();
+    /// Also re-hash every file and report content that differs from the recorded hash
+    #[arg(long = "hash", action = ArgAction::SetTrue)]
+    hash: bool,
 
-    std::thread::scope(|s| -> Result<()> {
-        let handle = s.spawn(|| search_streaming(&paths, &query, &opts, tx));
+    /// Output problems as a JSON array
+    #[arg(long = "json", action = ArgAction::SetTrue)]
+    json: bool,
+}
 
-        let stdout = io::stdout();
-        let mut w = stdout.lock();
-        let use_color = match output.color {
-            ColorChoice::Always => true,
-            ColorChoice::Never => false,
-            ColorChoice::Auto => w.is_terminal(),
-        };
+#[derive(Debug, Args)]
+struct CacheFsckArgs {
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
 
-        let mut seen = std::collections::HashSet::new();
-        for m in rx {
-            if output.paths {
-                if output.collections {
-                    let _ = writeln!(w, "{}", m.source.path_with_index());
-                } else if seen.insert(m.source.path.clone()) {
-                    let _ = writeln!(w, "{}", m.source.path.display());
-                }
-            } else if output.ndjson {
-                if let Ok(line) = serde_json::to_string(&m) {
-                    let _ = w.write_all(line.as_bytes());
-                    let _ = w.write_all(b"\n");
-                }
-            } else if output.collections {
-                let rendered = render_path(&m, use_color, true);
-                let _ = writeln!(w, "{rendered}");
-            } else if seen.insert(m.source.path.clone()) {
-                let rendered = render_path(&m, use_color, false);
-                let _ = writeln!(w, "{rendered}");
-            }
-        }
+    /// Check the high-performance LMDB index (the JSON cache has no databases to cross-check)
+    #[arg(long = "index", action = ArgAction::SetTrue)]
+    use_index: bool,
 
-        match handle.join() {
-            Ok(result) => result,
-            Err(_) => Err(anyhow!("search thread panicked")),
-        }
-    })
-}
+    /// Override index directory (defaults to ~/.cache/typg/index/)
+    #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
+    index_path: Option<PathBuf>,
 
-fn run_serve(args: ServeArgs) -> Result<()> {
-    let runtime = Builder::new_multi_thread().enable_all().build()?;
-    runtime.block_on(server::serve(&args.bind))
-}
+    /// Fix what the check finds: drop broken records and dangling bitmap members, re-map unmapped faces
+    #[arg(long = "repair", action = ArgAction::SetTrue)]
+    repair: bool,
 
-#[derive(Clone, Debug)]
-struct OutputFormat {
+    /// Output the findings as JSON
+    #[arg(long = "json", action = ArgAction::SetTrue)]
     json: bool,
-    ndjson: bool,
-    paths: bool,
-    columns: bool,
-    collections: bool,
-    color: ColorChoice,
 }
 
-impl OutputFormat {
-    fn from_find(args: &FindArgs) -> Self {
-        Self {
-            json: args.json,
-            ndjson: args.ndjson,
-            paths: args.paths_only,
-            columns: args.columns,
-            collections: args.collections,
-            color: args.color,
-        }
-    }
+#[derive(Debug, Args)]
+struct CacheCompactArgs {
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
 
-    fn from_output(args: &OutputArgs) -> Self {
-        Self {
-            json: args.json,
-            ndjson: args.ndjson,
-            paths: args.paths,
-            columns: args.columns,
-            collections: args.collections,
-            color: args.color,
-        }
-    }
+    /// Compact the high-performance LMDB index (the JSON cache is rewritten whole on every save)
+    #[arg(long = "index", action = ArgAction::SetTrue)]
+    use_index: bool,
+
+    /// Override index directory (defaults to ~/.cache/typg/index/)
+    #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
+    index_path: Option<PathBuf>,
+
+    /// Output the sizes before and after as JSON
+    #[arg(long = "json", action = ArgAction::SetTrue)]
+    json: bool,
 }
 
-fn write_matches(matches: &[TypgFontFaceMatch], format: &OutputFormat) -> Result<()> {
-    let stdout = io::stdout();
-    let mut handle = stdout.lock();
-    let use_color = match format.color {
-        ColorChoice::Always => true,
-        ColorChoice::Never => false,
-        ColorChoice::Auto => handle.is_terminal(),
-    };
+#[derive(Debug, Args)]
+struct CacheExportArgs {
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
 
-    if format.paths {
-        write_paths(matches, &mut handle, format.collections)?;
-    } else if format.ndjson {
-        write_ndjson(matches, &mut handle)?;
-    } else if format.json {
-        write_json_pretty(matches, &mut handle)?;
-    } else if format.columns {
-        write_columns(matches, &mut handle, use_color, format.collections)?;
-    } else {
-        write_plain(matches, &mut handle, use_color, format.collections)?;
-    }
+    /// Export the high-performance LMDB index (the JSON cache file is already portable)
+    #[arg(long = "index", action = ArgAction::SetTrue)]
+    use_index: bool,
 
-    Ok(())
-}
+    /// Override index directory (defaults to ~/.cache/typg/index/)
+    #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
+    index_path: Option<PathBuf>,
 
-/// Build a `Query` from the CLI filter arguments.
-fn build_query(args: &FindArgs) -> Result<Query> {
-    build_query_from_parts(
-        &args.axes,
-        &args.features,
-        &args.scripts,
-        &args.tables,
-        &args.name_patterns,
-        &args.creator_patterns,
-        &args.license_patterns,
-        &args.codepoints,
-        &args.text,
-        args.variable,
-        &args.weight,
-        &args.width,
-        &args.family_class,
-    )
+    /// Snapshot file to write, e.g. catalog.typg; - writes to stdout
+    #[arg(short = 'o', long = "output", value_name = "FILE", value_hint = ValueHint::FilePath)]
+    output: PathBuf,
 }
 
-#[allow(clippy::too_many_arguments)]
-fn build_query_from_parts(
-    axes: &[String],
-    features: &[String],
-    scripts: &[String],
-    tables: &[String],
-    name_patterns: &[String],
-    creator_patterns: &[String],
-    license_patterns: &[String],
-    codepoints: &[String],
-    text: &Option<String>,
-    variable: bool,
-    weight: &Option<String>,
-    width: &Option<String>,
-    family_class: &Option<String>,
-) -> Result<Query> {
-    let axes = parse_tag_list(axes)?;
-    let features = parse_tag_list(features)?;
-    let scripts = parse_tag_list(scripts)?;
-    let tables = parse_tag_list(tables)?;
-    let name_patterns = compile_patterns(name_patterns)?;
-    let creator_patterns = compile_patterns(creator_patterns)?;
-    let license_patterns = compile_patterns(license_patterns)?;
-    let mut codepoints = parse_codepoints(codepoints)?;
-    let weight_range = parse_optional_range(weight)?;
-    let width_range = parse_optional_range(width)?;
-    let family_class = parse_optional_family_class(family_class)?;
+#[derive(Debug, Args)]
+struct CacheImportArgs {
+    /// Snapshot file written by cache export; - reads stdin
+    #[arg(value_name = "FILE", value_hint = ValueHint::FilePath)]
+    snapshot: PathBuf,
 
-    if let Some(text) = text {
-        codepoints.extend(text.chars());
-    }
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
+
+    /// Import into the high-performance LMDB index
+    #[arg(long = "index", action = ArgAction::SetTrue)]
+    use_index: bool,
+
+    /// Override index directory (defaults to ~/.cache/typg/index/)
+    #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
+    index_path: Option<PathBuf>,
+
+    /// Move paths under OLD to NEW, e.g. /build/fonts=/opt/fonts; repeatable, the first that applies wins
+    #[arg(long = "rebase", value_name = "OLD=NEW", value_parser = parse_rebase)]
+    rebase: Vec<(PathBuf, PathBuf)>,
+
+    /// Output the files and faces imported as JSON
+    #[arg(long = "json", action = ArgAction::SetTrue)]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+#[command(group(ArgGroup::new("direction").required(true).args(["to_index", "to_json"])))]
+struct CacheMigrateArgs {
+    /// Load the JSON cache into the LMDB index
+    #[arg(long = "to-index", action = ArgAction::SetTrue)]
+    to_index: bool,
+
+    /// Write the LMDB index out to the JSON cache
+    #[arg(long = "to-json", action = ArgAction::SetTrue)]
+    to_json: bool,
+
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
+
+    /// Override cache location (defaults to ~/.cache/typg/cache.json)
+    #[arg(long = "cache-path", value_hint = ValueHint::FilePath)]
+    cache_path: Option<PathBuf>,
+
+    /// Override index directory (defaults to ~/.cache/typg/index/)
+    #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
+    index_path: Option<PathBuf>,
+
+    /// Output the files and faces migrated as JSON
+    #[arg(long = "json", action = ArgAction::SetTrue)]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+struct CacheRefreshArgs {
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
+
+    /// Override cache location (defaults to ~/.cache/typg/cache.json)
+    #[arg(long = "cache-path", value_hint = ValueHint::FilePath)]
+    cache_path: Option<PathBuf>,
+
+    /// Use high-performance LMDB index instead of JSON cache (requires hpindex feature)
+    #[arg(long = "index", action = ArgAction::SetTrue)]
+    use_index: bool,
+
+    /// Override index directory (defaults to ~/.cache/typg/index/)
+    #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
+    index_path: Option<PathBuf>,
+
+    /// Number of parallel worker threads
+    #[arg(short = 'J', long = "jobs", value_hint = ValueHint::Other)]
+    jobs: Option<usize>,
+}
+
+#[derive(Debug, Args)]
+struct CacheInfoArgs {
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
+
+    /// Override cache location (defaults to ~/.cache/typg/cache.json)
+    #[arg(long = "cache-path", value_hint = ValueHint::FilePath)]
+    cache_path: Option<PathBuf>,
+
+    /// Use high-performance LMDB index instead of JSON cache (requires hpindex feature)
+    #[arg(long = "index", action = ArgAction::SetTrue)]
+    use_index: bool,
+
+    /// Override index directory (defaults to ~/.cache/typg/index/)
+    #[arg(long = "index-path", value_hint = ValueHint::DirPath)]
+    index_path: Option<PathBuf>,
+
+    /// Also count faces per standard weight and list families without a bold
+    #[arg(long = "weights", action = ArgAction::SetTrue)]
+    weights: bool,
+
+    /// Output as JSON
+    #[arg(long = "json", action = ArgAction::SetTrue)]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+struct CacheChangedArgs {
+    /// Paths to scan (directories or individual font files)
+    #[arg(
+        value_hint = ValueHint::DirPath,
+        required_unless_present_any = ["system_fonts", "stdin_paths"]
+    )]
+    paths: Vec<PathBuf>,
+
+    /// Read additional paths from stdin, one per line
+    #[arg(long = "stdin-paths", action = ArgAction::SetTrue)]
+    stdin_paths: bool,
+
+    /// Include platform-default system font directories
+    #[arg(long = "system-fonts", action = ArgAction::SetTrue)]
+    system_fonts: bool,
+
+    /// Follow symlinks during directory traversal
+    #[arg(long = "follow-symlinks", action = ArgAction::SetTrue)]
+    follow_symlinks: bool,
+
+    /// Include hidden files and directories (names starting with `.`)
+    #[arg(long = "hidden", action = ArgAction::SetTrue)]
+    hidden: bool,
+
+    /// Number of parallel worker threads
+    #[arg(short = 'J', long = "jobs", value_hint = ValueHint::Other)]
+    jobs: Option<usize>,
+
+    /// Name table IDs to read into font names (default 1,16,2,17,4,6; e.g. add 25)
+    #[arg(long = "name-ids", value_delimiter = ',', value_hint = ValueHint::Other)]
+    name_ids: Vec<u16>,
+
+    /// Cache profile: system-wide, per-user, or project-local (.typg/ in the nearest ancestor)
+    #[arg(long = "cache", value_enum)]
+    profile: Option<CacheProfile>,
+
+    /// Override cache location (defaults to ~/.cache/typg/cache.json)
+    #[arg(long = "cache-path", value_hint = ValueHint::FilePath)]
+    cache_path: Option<PathBuf>,
+
+    /// Output changes as a JSON array
+    #[arg(long = "json", action = ArgAction::SetTrue, conflicts_with = "paths_only")]
+    json: bool,
+
+    /// Output changed font paths only (with #index for TTC faces)
+    #[arg(long = "paths", action = ArgAction::SetTrue)]
+    paths_only: bool,
+}
+
+#[derive(Debug, Args)]
+struct FindArgs {
+    /// Paths to search (directories or files)
+    #[arg(
+        value_hint = ValueHint::DirPath,
+        required_unless_present_any = ["system_fonts", "stdin_paths", "remote", "via_daemon"]
+    )]
+    paths: Vec<PathBuf>,
+
+    /// Read newline-delimited paths from STDIN
+    #[arg(long = "stdin-paths", action = ArgAction::SetTrue)]
+    stdin_paths: bool,
+
+    /// Include common system font directories automatically
+    #[arg(long = "system-fonts", action = ArgAction::SetTrue)]
+    system_fonts: bool,
+
+    /// Require fonts to define these axis tags
+    #[arg(short = 'a', long = "axes", value_delimiter = ',', value_hint = ValueHint::Other)]
+    axes: Vec<String>,
+
+    /// Require fonts to define these OpenType feature tags
+    #[arg(short = 'f', long = "features", value_delimiter = ',', value_hint = ValueHint::Other)]
+    features: Vec<String>,
+
+    /// Require fonts to cover these script tags
+    #[arg(short = 's', long = "scripts", value_delimiter = ',', value_hint = ValueHint::Other)]
+    scripts: Vec<String>,
+
+    /// Require fonts to declare these language systems (e.g. TRK,SRB)
+    #[arg(long = "langs", value_delimiter = ',', value_hint = ValueHint::Other)]
+    langs: Vec<String>,
+
+    /// Require fonts to contain these table tags
+    #[arg(short = 'T', long = "tables", value_delimiter = ',', value_hint = ValueHint::Other)]
+    tables: Vec<String>,
+
+    /// Require tables with these table directory checksums (TAG=HEX, e.g. glyf=0xDEADBEEF)
+    #[arg(
+        long = "table-checksum",
+        value_name = "TAG=HEX",
+        value_delimiter = ',',
+        value_hint = ValueHint::Other
+    )]
+    table_checksums: Vec<String>,
+
+    /// Regex patterns that must match at least one font name
+    #[arg(short = 'n', long = "name", value_hint = ValueHint::Other)]
+    name_patterns: Vec<String>,
+
+    #[command(flatten)]
+    name_match: NameMatchArgs,
+
+    /// Regex patterns that must match creator info (copyright, trademark, manufacturer, designer, description, URLs, license)
+    #[arg(short = 'c', long = "creator", value_hint = ValueHint::Other)]
+    creator_patterns: Vec<String>,
+
+    /// Regex patterns that must match license info (copyright, license description, license URL)
+    #[arg(short = 'l', long = "license", value_hint = ValueHint::Other)]
+    license_patterns: Vec<String>,
+
+    /// Unicode codepoints or ranges (e.g. U+0041-U+0044,B)
+    #[arg(short = 'u', long = "codepoints", value_delimiter = ',', value_hint = ValueHint::Other)]
+    codepoints: Vec<String>,
+
+    /// Require fonts to cover this text sample
+    #[arg(short = 't', long = "text")]
+    text: Option<String>,
+
+    /// Require full coverage of a Unicode block (e.g. "Cyrillic"; repeatable)
+    #[arg(long = "unicode-block", value_hint = ValueHint::Other)]
+    unicode_blocks: Vec<String>,
+
+    /// Require minimum block coverage as BLOCK:PERCENT (e.g. "Cyrillic:90%"; repeatable)
+    #[arg(long = "coverage", value_hint = ValueHint::Other)]
+    coverage: Vec<String>,
+
+    /// Only include variable fonts
+    #[arg(short = 'v', long = "variable", action = ArgAction::SetTrue)]
+    variable: bool,
+
+    /// Match OS/2 weight class (single value like 400 or range like 300-500)
+    #[arg(short = 'w', long = "weight", value_hint = ValueHint::Other)]
+    weight: Option<String>,
+
+    /// Match OS/2 width class (1-9, single value or range)
+    #[arg(short = 'W', long = "width", value_hint = ValueHint::Other)]
+    width: Option<String>,
+
+    /// Match OS/2 family class (major like 8 or major.subclass like 8.11; accepts names like sans)
+    #[arg(long = "family-class", value_hint = ValueHint::Other)]
+    family_class: Option<String>,
+
+    /// Require fonts whose OS/2 fsType allows at least this embedding (installable|editable|preview|restricted)
+    #[arg(long = "embedding", value_hint = ValueHint::Other)]
+    embedding: Option<String>,
+
+    /// Require at least N glyphs (maxp numGlyphs)
+    #[arg(long = "min-glyphs", value_hint = ValueHint::Other)]
+    min_glyphs: Option<u16>,
+
+    /// Require at least N variation axes (e.g. 2 for multi-axis designs)
+    #[arg(long = "min-axes", value_hint = ValueHint::Other)]
+    min_axes: Option<usize>,
+
+    /// Allow at most N variation axes (e.g. 1 with --variable for weight-only VFs)
+    #[arg(long = "max-axes", value_hint = ValueHint::Other)]
+    max_axes: Option<usize>,
+
+    /// Require one of these OS/2 vendor IDs (comma-separated, case-insensitive)
+    #[arg(long = "vendor", value_delimiter = ',', value_hint = ValueHint::Other)]
+    vendors: Vec<String>,
+
+    /// Require a named instance of a variable font (e.g. SemiBold; repeatable; case and spaces ignored)
+    #[arg(long = "instance", value_hint = ValueHint::Other)]
+    instances: Vec<String>,
+
+    /// Only match faces inside TTC/OTC collections
+    #[arg(long = "only-collections", action = ArgAction::SetTrue)]
+    only_collections: bool,
+
+    /// Only match the face at this index within each collection
+    #[arg(long = "collection-index", value_hint = ValueHint::Other)]
+    collection_index: Option<u32>,
+
+    /// Skip fonts that map characters only through legacy (format 0/6) or symbol cmap subtables
+    #[arg(long = "unicode-cmap-only", action = ArgAction::SetTrue)]
+    unicode_cmap_only: bool,
+
+    /// Boolean filter expression, ANDed with the other filters (e.g. "script:arab OR script:hebr")
+    #[arg(long = "query-expr", value_hint = ValueHint::Other)]
+    query_expr: Option<String>,
+
+    /// Start from a built-in query; other filters narrow it further
+    #[arg(long = "preset", value_enum)]
+    preset: Option<QueryPreset>,
+
+    #[command(flatten)]
+    exclude: ExcludeArgs,
+
+    #[command(flatten)]
+    ranking: RankArgs,
+
+    /// Follow symlinks while walking paths
+    #[arg(long = "follow-symlinks", action = ArgAction::SetTrue)]
+    follow_symlinks: bool,
+
+    /// Include hidden files and directories (names starting with `.`)
+    #[arg(long = "hidden", action = ArgAction::SetTrue)]
+    hidden: bool,
+
+    /// Number of worker threads (defaults to CPU count)
+    #[arg(short = 'J', long = "jobs", value_hint = ValueHint::Other)]
+    jobs: Option<usize>,
+
+    /// Name table IDs to read into font names (default 1,16,2,17,4,6; e.g. add 25)
+    #[arg(long = "name-ids", value_delimiter = ',', value_hint = ValueHint::Other)]
+    name_ids: Vec<u16>,
+
+    /// Reuse metadata of files unchanged since the last --incremental scan of these paths, and update it (optionally kept in FILE)
+    #[arg(
+        long = "incremental",
+        value_name = "FILE",
+        num_args = 0..=1,
+        require_equals = true,
+        value_hint = ValueHint::FilePath
+    )]
+    incremental: Option<Option<PathBuf>>,
+
+    /// Give up after this long (e.g. 30s, 500ms, 2m) and print the matches found so far
+    #[arg(
+        long = "timeout",
+        value_name = "DURATION",
+        value_parser = parse_timeout,
+        value_hint = ValueHint::Other
+    )]
+    timeout: Option<Duration>,
+
+    /// Send the query to a `typg serve` instance at URL instead of scanning here (paths are on the server; none searches its index)
+    #[arg(
+        long = "remote",
+        value_name = "URL",
+        value_hint = ValueHint::Url,
+        conflicts_with_all = ["system_fonts", "incremental", "name_ids", "rank", "strict", "skip_errors", "errors_json"]
+    )]
+    remote: Option<String>,
+
+    /// Bearer token for a --remote server started with --token
+    #[arg(long = "remote-token", value_name = "SECRET", requires = "remote", value_hint = ValueHint::Other)]
+    remote_token: Option<String>,
+
+    /// Ask a running `typg daemon` (optionally at SOCKET) instead of scanning; paths narrow its answer to fonts under them
+    #[arg(
+        long = "via-daemon",
+        value_name = "SOCKET",
+        num_args = 0..=1,
+        require_equals = true,
+        value_hint = ValueHint::FilePath,
+        conflicts_with_all = ["remote", "system_fonts", "incremental", "name_ids", "rank", "strict", "skip_errors", "errors_json", "timeout"]
+    )]
+    via_daemon: Option<Option<PathBuf>>,
+
+    #[command(flatten)]
+    errors: ErrorArgs,
+
+    /// Emit a single JSON array
+    #[arg(long = "json", action = ArgAction::SetTrue, conflicts_with = "ndjson")]
+    json: bool,
+
+    /// Emit newline-delimited JSON
+    #[arg(long = "ndjson", action = ArgAction::SetTrue)]
+    ndjson: bool,
+
+    /// Codepoint representation in JSON/NDJSON output (chars|ranges)
+    #[arg(long = "codepoint-format", default_value_t = CodepointOutput::Chars, value_enum)]
+    codepoint_format: CodepointOutput,
+
+    /// Wrap --json output in an envelope recording typg version, query, roots and time
+    #[arg(long = "provenance", action = ArgAction::SetTrue, requires = "json")]
+    provenance: bool,
+
+    /// Write a configuration file for the matched fonts instead of a listing
+    #[arg(
+        long = "format",
+        value_enum,
+        conflicts_with_all = ["json", "ndjson", "paths_only", "columns", "fields", "expand_instances"]
+    )]
+    format: Option<ExportFormat>,
+
+    /// With --format css, let one unicode-range span gaps of up to N codepoints the font lacks (default 0: exact)
+    #[arg(
+        long = "unicode-range-gap",
+        value_name = "N",
+        default_value_t = 0,
+        requires = "format"
+    )]
+    unicode_range_gap: u32,
+
+    /// Emit one line per match from a template like '{path}\t{family}\t{weight}' (--fields names or JSON metadata keys)
+    #[arg(
+        long = "template",
+        value_parser = Template::parse,
+        conflicts_with_all = ["json", "ndjson", "paths_only", "columns", "fields", "format", "expand_instances"]
+    )]
+    template: Option<Template>,
+
+    /// Emit newline-delimited font paths (with #index for TTC)
+    #[arg(
+        long = "paths",
+        action = ArgAction::SetTrue,
+        conflicts_with_all = ["json", "ndjson", "columns", "fields"]
+    )]
+    paths_only: bool,
+
+    /// Print paths as percent-encoded file:// URIs, in path lists, JSON and every other listing
+    #[arg(long = "uri", action = ArgAction::SetTrue, conflicts_with = "format")]
+    uri: bool,
+
+    /// Format output as padded columns; optionally choose which (path,name,tags)
+    #[arg(long = "columns", value_enum, value_delimiter = ',', num_args = 0..=1, require_equals = true)]
+    columns: Option<Vec<Column>>,
+
+    /// Middle-truncate paths in --columns/--fields output to at most N characters
+    #[arg(long = "max-path-width", value_hint = ValueHint::Other)]
+    max_path_width: Option<usize>,
+
+    /// Format output as padded columns of the chosen fields (implies --columns)
+    #[arg(long = "fields", value_delimiter = ',', value_enum, conflicts_with_all = ["json", "ndjson"])]
+    fields: Vec<Field>,
+
+    /// Omit the header row from --fields output
+    #[arg(long = "no-header", action = ArgAction::SetTrue, requires = "fields")]
+    no_header: bool,
+
+    /// Show individual TTC/OTC collection faces (path#index); default deduplicates by path
+    #[arg(long = "collections", action = ArgAction::SetTrue)]
+    collections: bool,
+
+    /// List each named instance of a variable font as its own row
+    #[arg(long = "expand-instances", action = ArgAction::SetTrue, conflicts_with_all = ["paths_only", "columns", "fields"])]
+    expand_instances: bool,
+
+    /// Keep at most N faces per family (after sorting)
+    #[arg(long = "per-family", value_hint = ValueHint::Other)]
+    per_family: Option<usize>,
+
+    /// Collapse results into one entry per family listing member styles
+    #[arg(long = "group-by", value_enum, conflicts_with_all = ["rank", "paths_only", "fields", "expand_instances", "format", "template"])]
+    group_by: Option<GroupBy>,
+
+    /// Order results by these keys instead of by path, most significant first
+    /// (e.g. `family,weight,width,italic`)
+    #[arg(
+        long = "sort",
+        value_enum,
+        value_delimiter = ',',
+        conflicts_with = "rank"
+    )]
+    sort: Vec<SortBy>,
+
+    /// Reverse the result order (descending with --sort)
+    #[arg(long = "reverse", action = ArgAction::SetTrue, conflicts_with = "rank")]
+    reverse: bool,
+
+    /// Skip the first N results (after sorting)
+    #[arg(long = "offset", value_hint = ValueHint::Other)]
+    offset: Option<usize>,
+
+    /// Output at most N results (after sorting and --offset)
+    #[arg(long = "limit", value_hint = ValueHint::Other)]
+    limit: Option<usize>,
+
+    /// Only output the count of matching fonts (useful for scripting)
+    #[arg(long = "count", action = ArgAction::SetTrue, conflicts_with_all = ["json", "ndjson", "paths_only", "columns", "fields", "template"])]
+    count_only: bool,
+
+    /// Control colorized output (auto|always|never; NO_COLOR disables auto)
+    #[arg(long = "color", default_value_t = ColorChoice::Auto, value_enum)]
+    color: ColorChoice,
+
+    /// Color theme: default, mono, none, or role=SGR overrides such as name=1;33:match=7 (default: $TYPOG_THEME)
+    #[arg(long = "theme", value_hint = ValueHint::Other)]
+    theme: Option<String>,
+}
+
+/// A part of the `--columns` layout.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum Column {
+    /// File path (with #index under --collections)
+    Path,
+    /// First name from the name table
+    Name,
+    /// Axis, feature, script and table counts, and "var" for variable fonts
+    Tags,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+/// Built-in cache locations selectable with `--cache`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum CacheProfile {
+    /// Machine-wide cache shared by every user
+    System,
+    /// Per-user cache under the platform cache directory
+    User,
+    /// Project-local cache in the nearest `.typg/` directory
+    Project,
+}
+
+/// Exclusion filters shared by `find`, `cache find`, and HTTP `/search`.
+#[derive(
+    Debug, Clone, Default, Args, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(default)]
+pub struct ExcludeArgs {
+    /// Reject fonts that define any of these axis tags
+    #[arg(long = "not-axes", value_delimiter = ',', value_hint = ValueHint::Other)]
+    not_axes: Vec<String>,
+
+    /// Reject fonts that define any of these feature tags (e.g. kern)
+    #[arg(long = "not-features", value_delimiter = ',', value_hint = ValueHint::Other)]
+    not_features: Vec<String>,
+
+    /// Reject fonts that cover any of these script tags
+    #[arg(long = "not-scripts", value_delimiter = ',', value_hint = ValueHint::Other)]
+    not_scripts: Vec<String>,
+
+    /// Reject fonts that contain any of these table tags
+    #[arg(long = "not-tables", value_delimiter = ',', value_hint = ValueHint::Other)]
+    not_tables: Vec<String>,
+
+    /// Reject fonts where any name matches one of these regex patterns
+    #[arg(long = "not-name", value_hint = ValueHint::Other)]
+    not_name: Vec<String>,
+
+    /// Skip files matching these globs (e.g. "*Test*", "**/old/**")
+    #[arg(long = "exclude-path", value_hint = ValueHint::Other)]
+    exclude_path: Vec<String>,
+
+    /// Only include static (non-variable) fonts
+    #[arg(long = "not-variable", action = ArgAction::SetTrue, conflicts_with = "variable")]
+    not_variable: bool,
+}
+
+/// `--name` pattern modifiers shared by `find`, `cache find`, and HTTP `/search`.
+#[derive(
+    Debug, Clone, Copy, Default, Args, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(default)]
+pub struct NameMatchArgs {
+    /// Match --name patterns regardless of letter case
+    #[arg(long = "name-ignore-case", action = ArgAction::SetTrue)]
+    name_ignore_case: bool,
+
+    /// Require --name patterns to match a whole name, not part of one
+    #[arg(long = "name-exact", action = ArgAction::SetTrue)]
+    name_exact: bool,
+}
+
+impl From<NameMatchArgs> for NameMatch {
+    fn from(args: NameMatchArgs) -> Self {
+        NameMatch {
+            ignore_case: args.name_ignore_case,
+            exact: args.name_exact,
+        }
+    }
+}
+
+/// Relevance ranking options shared by `find` and `cache find`.
+#[derive(Debug, Clone, Default, Args)]
+struct RankArgs {
+    /// Score fonts by how many criteria they satisfy and sort best-first instead of filtering
+    #[arg(long = "rank", action = ArgAction::SetTrue, conflicts_with = "expand_instances")]
+    rank: bool,
+
+    /// With --rank, drop fonts scoring below this value (0.0-1.0)
+    #[arg(long = "min-score", requires = "rank", value_hint = ValueHint::Other)]
+    min_score: Option<f32>,
+}
+
+/// Unreadable-font handling shared by `find` and `cache add`.
+#[derive(Debug, Clone, Default, Args)]
+struct ErrorArgs {
+    /// Stop with an error at the first font that cannot be read or parsed
+    #[arg(long = "strict", action = ArgAction::SetTrue, conflicts_with = "skip_errors")]
+    strict: bool,
+
+    /// Skip fonts that cannot be read or parsed and count them in the summary (default)
+    #[arg(long = "skip-errors", action = ArgAction::SetTrue)]
+    skip_errors: bool,
+
+    /// Write skipped fonts as a JSON array of {path, reason} to FILE ("-" for stderr)
+    #[arg(
+        long = "errors-json",
+        value_name = "FILE",
+        conflicts_with = "strict",
+        value_hint = ValueHint::FilePath
+    )]
+    errors_json: Option<PathBuf>,
+}
+
+impl ErrorArgs {
+    fn policy(&self) -> ErrorPolicy {
+        if self.strict {
+            ErrorPolicy::Fail
+        } else if self.errors_json.is_some() {
+            ErrorPolicy::Collect
+        } else {
+            ErrorPolicy::Skip
+        }
+    }
+
+    /// Write `report.failures` where `--errors-json` asked for them.
+    fn write_failures(&self, report: &SearchReport) -> Result<()> {
+        let Some(path) = &self.errors_json else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(&report.failures)?;
+        if path.as_os_str() == "-" {
+            eprintln!("{json}");
+        } else {
+            fs::write(path, json + "\n").with_context(|| format!("writing {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Grouping modes selectable with `--group-by`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum GroupBy {
+    /// Typographic family (name ID 16, else ID 1)
+    Family,
+}
+
+/// Sort keys selectable with `--sort`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum SortBy {
+    /// File path, then collection index (the default order)
+    Path,
+    /// First font name, ignoring case
+    Name,
+    /// Family name, ignoring case
+    Family,
+    /// OS/2 weight class
+    Weight,
+    /// OS/2 width class
+    Width,
+    /// File size
+    Size,
+    /// File modification time
+    Mtime,
+    /// Upright before italic and oblique
+    Italic,
+}
+
+impl From<SortBy> for SortKey {
+    fn from(value: SortBy) -> Self {
+        match value {
+            SortBy::Path => SortKey::Path,
+            SortBy::Name => SortKey::Name,
+            SortBy::Family => SortKey::Family,
+            SortBy::Weight => SortKey::Weight,
+            SortBy::Width => SortKey::Width,
+            SortBy::Size => SortKey::Size,
+            SortBy::Mtime => SortKey::Modified,
+            SortBy::Italic => SortKey::Italic,
+        }
+    }
+}
+
+/// Configuration formats selectable with `--format`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum ExportFormat {
+    /// fontconfig XML accepting the fonts and preferring their families for generic names
+    Fontconfig,
+    /// CSS @font-face rules with local()/url() sources and a unicode-range from the cmap
+    Css,
+}
+
+/// Built-in queries selectable with `--preset`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum QueryPreset {
+    /// Standalone fonts with OS/2 and GPOS tables, kern, and printable ASCII
+    WebfontReady,
+    /// Arabic script with init/medi/fina/rlig and the basic Arabic letters and digits
+    ArabicUi,
+    /// Monospaced fonts covering printable ASCII
+    CodeFonts,
+}
+
+impl From<QueryPreset> for Preset {
+    fn from(value: QueryPreset) -> Self {
+        match value {
+            QueryPreset::WebfontReady => Preset::WebfontReady,
+            QueryPreset::ArabicUi => Preset::ArabicUi,
+            QueryPreset::CodeFonts => Preset::CodeFonts,
+        }
+    }
+}
+
+/// Codepoint representations selectable with `--codepoint-format`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+enum CodepointOutput {
+    /// One string per character
+    #[default]
+    Chars,
+    /// Compact ranges ("U+0020-007E, U+00A0-00FF")
+    Ranges,
+}
+
+impl From<CodepointOutput> for CodepointFormat {
+    fn from(value: CodepointOutput) -> Self {
+        match value {
+            CodepointOutput::Chars => CodepointFormat::Chars,
+            CodepointOutput::Ranges => CodepointFormat::Ranges,
+        }
+    }
+}
+
+/// Check sets selectable with `validate --profile`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum ValidateProfile {
+    /// Structural subset of Font Bakery's universal checks
+    FontbakeryLite,
+    /// Naming consistency: family, subfamily, full and PostScript names
+    /// against each other and against OS/2 weight, width and style bits
+    Names,
+}
+
+/// Parse CLI arguments and dispatch to the appropriate handler.
+pub fn run() -> Result<()> {
+    let cli = Cli::parse();
+    let quiet = cli.quiet;
+    #[cfg(feature = "hpindex")]
+    if let Some(map_size) = cli.index_map_size {
+        let _ = INDEX_OPTIONS.set(typg_core::index::IndexOptions {
+            map_size,
+            ..Default::default()
+        });
+    }
+    if !cli.collection_names.is_empty() {
+        let mut collections = cli.collection_names.clone();
+        let mut seen = HashSet::new();
+        collections.retain(|name| seen.insert(name.clone()));
+        if collections.len() > 1 && !matches!(cli.command, Command::Cache(CacheCommand::Find(_))) {
+            return Err(anyhow!(
+                "several --collection names can only be searched together, with cache find"
+            ));
+        }
+        let _ = COLLECTIONS.set(collections);
+    }
+
+    match cli.command {
+        Command::Find(args) => run_find(*args, quiet),
+        Command::Cache(cmd) => match cmd {
+            CacheCommand::Add(args) => run_cache_add(args, quiet),
+            CacheCommand::List(args) => run_cache_list(args),
+            CacheCommand::Find(args) => run_cache_find(*args, quiet),
+            CacheCommand::Clean(args) => run_cache_clean(args, quiet),
+            CacheCommand::Remove(args) => run_cache_remove(*args, quiet),
+            CacheCommand::Verify(args) => run_cache_verify(args, quiet),
+            CacheCommand::Fsck(args) => run_cache_fsck(args, quiet),
+            CacheCommand::Compact(args) => run_cache_compact(args, quiet),
+            CacheCommand::Export(args) => run_cache_export(args, quiet),
+            CacheCommand::Import(args) => run_cache_import(args, quiet),
+            CacheCommand::Migrate(args) => run_cache_migrate(args, quiet),
+            CacheCommand::Info(args) => run_cache_info(args),
+            CacheCommand::Changed(args) => run_cache_changed(args),
+            CacheCommand::Refresh(args) => run_cache_refresh(args, quiet),
+        },
+        Command::Serve(args) => run_serve(args, quiet),
+        Command::Daemon(args) => run_daemon(args, quiet),
+        Command::Validate(args) => run_validate(args),
+        Command::Watch(args) => run_watch(args, quiet),
+        Command::Show(args) => run_show(args, io::stdin().lock()),
+        Command::Inspect(args) => run_inspect(args, io::stdin().lock()),
+        Command::Merge(args) => run_merge(args, io::stdin().lock()),
+        Command::Select(args) => run_select(args, io::stdin().lock()),
+        Command::Bench(args) => run_bench(args, io::stdin().lock(), quiet),
+    }
+}
+
+/// Search directories for matching fonts, streaming results where possible.
+fn run_find(args: FindArgs, quiet: bool) -> Result<()> {
+    let deadline = args.timeout.map(|timeout| Instant::now() + timeout);
+    if matches!(args.jobs, Some(0)) {
+        return Err(anyhow!("--jobs must be at least 1"));
+    }
+    if matches!(args.per_family, Some(0)) {
+        return Err(anyhow!("--per-family must be at least 1"));
+    }
+
+    if let Some(url) = &args.remote {
+        return run_remote_find(&args, url, quiet);
+    }
+    if let Some(socket) = &args.via_daemon {
+        return run_daemon_find(&args, socket, quiet);
+    }
+
+    let stdin = io::stdin();
+    let paths = gather_paths(
+        &args.paths,
+        args.stdin_paths,
+        args.system_fonts,
+        stdin.lock(),
+    )?;
+    let query = build_query(&args)?;
+    let mut opts = SearchOptions {
+        follow_symlinks: args.follow_symlinks,
+        include_hidden: args.hidden,
+        jobs: args.jobs,
+        name_ids: name_ids(&args.name_ids),
+        error_policy: args.errors.policy(),
+        deadline,
+        ..SearchOptions::default()
+    };
+    let scan_cache = match &args.incremental {
+        Some(file) => {
+            let file = match file {
+                Some(file) => file.clone(),
+                None => scan_cache_path(&paths)?,
+            };
+            let cache = Arc::new(ScanCache::load(&file, &opts));
+            opts.scan_cache = Some(Arc::clone(&cache));
+            Some((file, cache))
+        }
+        None => None,
+    };
+    let scan_cache = scan_cache.as_ref();
+
+    let output = OutputFormat::from_find(&args).with_roots(paths.clone());
+
+    if args.ranking.rank {
+        let (candidates, mut report) = search_with_report(&paths, &query.relaxed(), &opts)?;
+        let ranked = rank(candidates, &query, args.ranking.min_score.unwrap_or(0.0));
+        let ranked = per_family(ranked, args.per_family, |r| &r.face.metadata);
+        report.matches = ranked.len();
+        let ranked = paginate(ranked, args.offset, args.limit);
+        if args.count_only {
+            println!("{}", ranked.len());
+        } else {
+            write_ranked_stdout(&ranked, &output)?;
+        }
+        save_scan_cache(scan_cache, quiet)?;
+        print_summary(&report, quiet);
+        args.errors.write_failures(&report)?;
+        return Ok(());
+    }
+
+    // Formats that need all results before writing
+    if args.count_only
+        || output.json
+        || output.columns
+        || output.expand_instances
+        || output.export.is_some()
+        || output.template.is_some()
+        || args.per_family.is_some()
+        || args.group_by.is_some()
+        || !args.sort.is_empty()
+        || args.reverse
+        || args.offset.is_some()
+        || args.limit.is_some()
+    {
+        let (matches, mut report) = search_with_report(&paths, &query, &opts)?;
+        let matches = sort_results(matches, &args.sort, args.reverse);
+        let matches = per_family(matches, args.per_family, |m| &m.metadata);
+        report.matches = matches.len();
+        let matches = paginate(matches, args.offset, args.limit);
+        write_results(&matches, &query, args.group_by, args.count_only, &output)?;
+        save_scan_cache(scan_cache, quiet)?;
+        print_summary(&report, quiet);
+        args.errors.write_failures(&report)?;
+        return Ok(());
+    }
+
+    // Stream results to stdout as they're found
+    let theme = output.theme(io::stdout().is_terminal())?;
+    // Paths already printed; one lock also keeps lines from interleaving.
+    let seen = Mutex::new(std::collections::HashSet::new());
+
+    let report = search_each(&paths, &query, &opts, |mut m| {
+        output.locate(&mut m);
+        let mut seen = seen.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut w = io::stdout().lock();
+        if output.paths {
+            if output.collections {
+                let _ = writeln!(w, "{}", m.source.path_with_index());
+            } else if seen.insert(m.source.path.clone()) {
+                let _ = writeln!(w, "{}", m.source.path.display());
+            }
+        } else if output.ndjson {
+            if let Ok(line) = to_json_value(&m, output.codepoints)
+                .and_then(|value| Ok(serde_json::to_string(&value)?))
+            {
+                let _ = w.write_all(line.as_bytes());
+                let _ = w.write_all(b"\n");
+            }
+        } else if output.collections {
+            let rendered = render_path(&m, &theme, true);
+            let _ = writeln!(w, "{rendered}");
+        } else if seen.insert(m.source.path.clone()) {
+            let rendered = render_path(&m, &theme, false);
+            let _ = writeln!(w, "{rendered}");
+        }
+    })?;
+    save_scan_cache(scan_cache, quiet)?;
+    print_summary(&report, quiet);
+    args.errors.write_failures(&report)?;
+    Ok(())
+}
+
+/// `find --remote`: run the query on a `typg serve` instance and print its
+/// matches as if they had been found here.
+fn run_remote_find(args: &FindArgs, url: &str, quiet: bool) -> Result<()> {
+    let mut remote = Remote::parse(url)?;
+    if let Some(token) = &args.remote_token {
+        remote = remote.with_token(token);
+    }
+    if let Some(timeout) = args.timeout {
+        remote = remote.with_timeout(timeout + remote::RESPONSE_GRACE);
+    }
+    find_elsewhere(args, url, quiet, |req| remote.search(req))
+}
+
+/// `find --via-daemon`: ask a running `typg daemon`, which answers from the
+/// cache or index it holds open, and print its matches.
+fn run_daemon_find(args: &FindArgs, socket: &Option<PathBuf>, quiet: bool) -> Result<()> {
+    let socket = resolve_daemon_socket(socket)?;
+    find_elsewhere(args, &socket.display().to_string(), quiet, |req| {
+        server::daemon::ask(&socket, req)
+    })
+}
+
+/// Have `send` (a server or a daemon) answer the query for `find` flags,
+/// and print the matches as if they had been found here.
+fn find_elsewhere(
+    args: &FindArgs,
+    source: &str,
+    quiet: bool,
+    send: impl FnOnce(&SearchRequest) -> Result<SearchResponse>,
+) -> Result<()> {
+    let started = Instant::now();
+    // Paths are the other side's business, so they are passed along unchecked.
+    let paths = if args.paths.is_empty() && !args.stdin_paths {
+        Vec::new()
+    } else {
+        gather_paths(&args.paths, args.stdin_paths, false, io::stdin().lock())?
+    };
+    // Parsed here too, so bad flags fail before anything is sent.
+    let query = build_query(args)?;
+
+    // Sorting and per-family limits need every match; page after them here.
+    let page_there = args.sort.is_empty() && !args.reverse && args.per_family.is_none();
+    let req = remote_request(args, paths, page_there);
+    let response = send(&req)?;
+    let mut matches = response.matches.unwrap_or_default();
+    let mut found = response.total;
+    if !page_there {
+        matches = sort_results(matches, &args.sort, args.reverse);
+        matches = per_family(matches, args.per_family, |m| &m.metadata);
+        found = matches.len();
+        matches = paginate(matches, args.offset, args.limit);
+    }
+
+    let output = OutputFormat::from_find(args).with_roots(req.paths);
+    write_results(&matches, &query, args.group_by, args.count_only, &output)?;
+    print_elsewhere_summary(found, source, started, quiet);
+    if response.truncated && !quiet {
+        eprintln!("{source} ran out of time; results are incomplete");
+    }
+    Ok(())
+}
+
+/// Say how many matches came from a server or daemon, and how fast.
+fn print_elsewhere_summary(found: usize, source: &str, started: Instant, quiet: bool) {
+    if quiet {
+        return;
+    }
+    let elapsed = started.elapsed();
+    let took = if elapsed.as_secs() == 0 {
+        format!("{:.1}ms", elapsed.as_secs_f64() * 1000.0)
+    } else {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    };
+    eprintln!(
+        "{} matches from {source} in {took}",
+        thousands(found as u64)
+    );
+}
+
+/// The `/search` request for `find` flags; an empty `paths` asks for the
+/// server's index.
+fn remote_request(args: &FindArgs, paths: Vec<PathBuf>, page: bool) -> SearchRequest {
+    SearchRequest {
+        use_index: paths.is_empty(),
+        paths,
+        axes: args.axes.clone(),
+        features: args.features.clone(),
+        scripts: args.scripts.clone(),
+        langs: args.langs.clone(),
+        tables: args.tables.clone(),
+        table_checksums: args.table_checksums.clone(),
+        names: args.name_patterns.clone(),
+        name_match: args.name_match,
+        creator: args.creator_patterns.clone(),
+        license: args.license_patterns.clone(),
+        codepoints: args.codepoints.clone(),
+        text: args.text.clone(),
+        unicode_blocks: args.unicode_blocks.clone(),
+        coverage: args.coverage.clone(),
+        variable: args.variable,
+        follow_symlinks: args.follow_symlinks,
+        hidden: args.hidden,
+        jobs: args.jobs,
+        timeout_ms: args
+            .timeout
+            .map(|timeout| u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX)),
+        paths_only: false,
+        weight: args.weight.clone(),
+        width: args.width.clone(),
+        family_class: args.family_class.clone(),
+        embedding: args.embedding.clone(),
+        min_glyphs: args.min_glyphs,
+        min_axes: args.min_axes,
+        max_axes: args.max_axes,
+        vendors: args.vendors.clone(),
+        instances: args.instances.clone(),
+        only_collections: args.only_collections,
+        collection_index: args.collection_index,
+        unicode_cmap_only: args.unicode_cmap_only,
+        query: args.query_expr.clone(),
+        preset: args.preset.map(|p| Preset::from(p).name().to_string()),
+        exclude: args.exclude.clone(),
+        sort: None,
+        reverse: false,
+        offset: if page { args.offset } else { None },
+        limit: if page { args.limit } else { None },
+        index_path: None,
+    }
+}
+
+/// A byte count for `--index-map-size`: a number with an optional `K`,
+/// `M`, `G` or `T` unit (`KB`/`KiB` spellings too), in binary multiples
+/// as `cache info` prints them.
+#[cfg(feature = "hpindex")]
+fn parse_byte_size(raw: &str) -> Result<usize, String> {
+    let trimmed = raw.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let shift = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 0,
+        "k" | "kb" | "kib" => 10,
+        "m" | "mb" | "mib" => 20,
+        "g" | "gb" | "gib" => 30,
+        "t" | "tb" | "tib" => 40,
+        _ => return Err(format!("unknown size unit in {raw:?} (use K, M, G or T)")),
+    };
+    let bytes = number
+        .parse::<f64>()
+        .map(|n| n * (1u64 << shift) as f64)
+        .map_err(|_| format!("expected a size such as 512MB or 2GB, got {raw:?}"))?;
+    if !(bytes >= 1.0 && bytes <= usize::MAX as f64) {
+        return Err(format!("size {raw:?} is out of range"));
+    }
+    Ok(bytes as usize)
+}
+
+/// Parse a `--timeout` such as `30s`, `500ms`, `2m` or `1h`; a bare number
+/// counts seconds.
+fn parse_timeout(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| anyhow!("invalid duration `{input}` (e.g. 30s, 500ms, 2m)"))?;
+    let seconds = match unit.trim() {
+        "" | "s" => value,
+        "ms" => value / 1000.0,
+        "m" => value * 60.0,
+        "h" => value * 3600.0,
+        other => {
+            return Err(anyhow!(
+                "unknown duration unit `{other}` (use ms, s, m or h)"
+            ))
+        }
+    };
+    if seconds <= 0.0 {
+        return Err(anyhow!("the timeout must be greater than zero"));
+    }
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| anyhow!("invalid duration `{input}` (e.g. 30s, 500ms, 2m)"))
+}
+
+/// Default `--incremental` sidecar for a set of search roots: one file per
+/// root set under the cache directory's `scans/`.
+fn scan_cache_path(roots: &[PathBuf]) -> Result<PathBuf> {
+    let mut key: Vec<String> = roots
+        .iter()
+        .map(|root| {
+            let root = root.canonicalize().unwrap_or_else(|_| root.clone());
+            path_key(&root)
+        })
+        .collect();
+    key.sort();
+    key.dedup();
+    let root = cache_root(None).ok_or_else(|| {
+        anyhow!("--incremental=FILE is required because no cache directory could be detected")
+    })?;
+    Ok(root.join("scans").join(format!(
+        "{:016x}.json",
+        hash_bytes(key.join("\n").as_bytes())
+    )))
+}
+
+/// Write back the `--incremental` cache and say how much of it was reused.
+fn save_scan_cache(scan_cache: Option<&(PathBuf, Arc<ScanCache>)>, quiet: bool) -> Result<()> {
+    let Some((file, cache)) = scan_cache else {
+        return Ok(());
+    };
+    cache.save(file)?;
+    if !quiet {
+        eprintln!(
+            "incremental: reused {} and parsed {} files ({})",
+            cache.reused(),
+            cache.parsed(),
+            file.display()
+        );
+    }
+    Ok(())
+}
+
+/// `--name-ids` as a search option; empty means the default IDs.
+fn name_ids(ids: &[u16]) -> Option<Vec<u16>> {
+    (!ids.is_empty()).then(|| ids.to_vec())
+}
+
+/// Print the one-line search summary to stderr unless `--quiet` is set.
+fn print_summary(report: &SearchReport, quiet: bool) {
+    if !quiet {
+        eprintln!("{report}");
+    }
+}
+
+/// Watch font folders and mirror every change into the cache or index.
+fn run_watch(args: WatchArgs, quiet: bool) -> Result<()> {
+    if matches!(args.jobs, Some(0)) {
+        return Err(anyhow!("--jobs must be at least 1"));
+    }
+    let store = sync_store(
+        args.use_index,
+        &args.cache_path,
+        &args.index_path,
+        args.profile,
+    )?;
+    // Without paths, watch the roots `cache add` recorded, as it walked them.
+    let recorded = if args.paths.is_empty() {
+        let recorded = store.roots()?;
+        if recorded.is_empty() {
+            return Err(anyhow!(
+                "no paths given and no roots recorded in the cache or index; \
+                 pass paths or run `typg cache add PATH` first"
+            ));
+        }
+        recorded
+    } else {
+        Vec::new()
+    };
+    // Notifications carry absolute paths; store entries the same way.
+    let roots = args
+        .paths
+        .iter()
+        .chain(recorded.iter().map(|root| &root.path))
+        .map(|path| {
+            path.canonicalize()
+                .with_context(|| format!("path does not exist: {}", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if args.daemon {
+        return watch::spawn_daemon(quiet);
+    }
+
+    let opts = SearchOptions {
+        follow_symlinks: args.follow_symlinks || recorded.iter().any(|root| root.follow_symlinks),
+        include_hidden: args.hidden || recorded.iter().any(|root| root.include_hidden),
+        jobs: args.jobs,
+        name_ids: name_ids(&args.name_ids)
+            .or_else(|| recorded.iter().find_map(|root| root.name_ids.clone())),
+        ..SearchOptions::default()
+    };
+    let mut syncer = watch::Syncer::new(store, opts);
+    watch::watch(
+        &roots,
+        &mut syncer,
+        Duration::from_millis(args.debounce_ms),
+        quiet,
+    )
+}
+
+/// The JSON cache or LMDB index that `watch` and `cache refresh` keep in
+/// step with the font folders.
+fn sync_store(
+    use_index: bool,
+    cache_path: &Option<PathBuf>,
+    index_path: &Option<PathBuf>,
+    profile: Option<CacheProfile>,
+) -> Result<watch::Store> {
+    #[cfg(feature = "hpindex")]
+    if use_index {
+        let index_path = resolve_index_path(index_path, profile)?;
+        return Ok(watch::Store::Index(open_index(&index_path)?));
+    }
+
+    #[cfg(not(feature = "hpindex"))]
+    if use_index {
+        let _ = index_path;
+        return Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ));
+    }
+
+    Ok(watch::Store::Cache(resolve_cache_path(
+        cache_path, profile,
+    )?))
+}
+
+#[cfg_attr(not(feature = "hpindex"), allow(unused_variables))]
+fn run_serve(args: ServeArgs, quiet: bool) -> Result<()> {
+    if matches!(args.max_jobs, Some(0)) {
+        return Err(anyhow!("--max-jobs must be at least 1"));
+    }
+    if matches!(args.max_scans, Some(0)) {
+        return Err(anyhow!("--max-scans must be at least 1"));
+    }
+    if args
+        .token
+        .as_deref()
+        .is_some_and(|token| token.trim().is_empty())
+    {
+        return Err(anyhow!("--token must not be empty"));
+    }
+    if matches!(args.max_concurrent_searches, Some(0)) {
+        return Err(anyhow!("--max-concurrent-searches must be at least 1"));
+    }
+    for origin in &args.cors_origins {
+        check_cors_origin(origin)?;
+    }
+    let state = server::ServerState {
+        max_jobs: args.max_jobs,
+        scan_slots: args
+            .max_scans
+            .map(|slots| Arc::new(tokio::sync::Semaphore::new(slots))),
+        access_log: args
+            .access_log
+            .as_deref()
+            .map(server::AccessLog::open)
+            .transpose()?
+            .map(Arc::new),
+        font_roots: args
+            .serve_roots
+            .iter()
+            .map(|root| {
+                root.canonicalize()
+                    .with_context(|| format!("--serve-root {}", root.display()))
+            })
+            .collect::<Result<_>>()?,
+        token: args.token.as_deref().map(Arc::from),
+        cors_origins: args
+            .cors_origins
+            .iter()
+            .map(|origin| origin.trim_end_matches('/').to_string())
+            .collect(),
+        search_slots: args
+            .max_concurrent_searches
+            .map(|slots| Arc::new(tokio::sync::Semaphore::new(slots))),
+        request_timeout: args.request_timeout.map(Duration::from_secs),
+        #[cfg(feature = "hpindex")]
+        index_options: typg_core::index::IndexOptions {
+            max_readers: args.max_readers,
+            no_read_ahead: args.no_read_ahead,
+            ..index_options()
+        },
+        #[cfg(feature = "hpindex")]
+        index_paths: args
+            .index_paths
+            .iter()
+            .chain(args.preload_index.iter().flatten())
+            .map(|dir| resolve_index_path(&Some(dir.clone()), None))
+            .collect::<Result<_>>()?,
+        #[cfg(feature = "hpindex")]
+        index_admin: args.index_admin,
+        #[cfg(feature = "hpindex")]
+        index_changes: Arc::default(),
+    };
+    #[cfg(feature = "hpindex")]
+    if let Some(dir) = &args.preload_index {
+        preload_index(dir, &state.index_options, args.warm_index, quiet)?;
+    }
+    let tls = match (&args.tls_cert, &args.tls_key) {
+        (Some(cert), Some(key)) => Some(server::tls::load(cert, key)?),
+        _ => None,
+    };
+    let runtime = Builder::new_multi_thread().enable_all().build()?;
+    if args.mcp {
+        return server::mcp::serve_stdio(state, runtime.handle());
+    }
+    runtime.block_on(server::serve(&args.bind, state, tls))
+}
+
+/// Hold the cache or index open and answer `--via-daemon` searches on a
+/// unix socket until stopped.
+fn run_daemon(args: DaemonArgs, quiet: bool) -> Result<()> {
+    let socket = resolve_daemon_socket(&args.socket)?;
+    let started = Instant::now();
+    let (store, source) = if args.use_index {
+        #[cfg(feature = "hpindex")]
+        {
+            let index_path = resolve_index_path(&args.index_path, args.profile)?;
+            let store = server::daemon::Store::index(&index_path, &index_options())?;
+            if args.warm_index {
+                if let server::daemon::Store::Index(index) = &store {
+                    index
+                        .warm()
+                        .with_context(|| format!("warming index {}", index_path.display()))?;
+                }
+            }
+            (store, index_path)
+        }
+        #[cfg(not(feature = "hpindex"))]
+        return Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ));
+    } else {
+        if args.index_path.is_some() {
+            return Err(anyhow!("--index-path requires --index"));
+        }
+        let cache_path = resolve_cache_path(&args.cache_path, args.profile)?;
+        (server::daemon::Store::cache(&cache_path)?, cache_path)
+    };
+    if !quiet {
+        eprintln!(
+            "typg daemon: {} faces from {} loaded in {:.1}s, listening on {}",
+            thousands(store.len()? as u64),
+            source.display(),
+            started.elapsed().as_secs_f64(),
+            socket.display()
+        );
+    }
+    server::daemon::serve(store, &socket)
+}
+
+/// Check that a `--cors-origin` is `*` or a bare origin such as
+/// `https://fonts.example.com`: a scheme and host, no path.
+fn check_cors_origin(origin: &str) -> Result<()> {
+    if origin == "*" {
+        return Ok(());
+    }
+    let host = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+        .map(|rest| rest.trim_end_matches('/'));
+    match host {
+        Some(host) if !host.is_empty() && !host.contains(['/', '?', '#', ' ']) => Ok(()),
+        _ => Err(anyhow!(
+            "--cors-origin {origin:?} is not an origin (expected e.g. https://example.com, or *)"
+        )),
+    }
+}
+
+/// `serve --preload-index`: open the index the server will query and, with
+/// `--warm-index`, read all of it, before the port is bound. A load
+/// balancer probing `/health` then never reaches an instance that would
+/// answer its first queries cold, or not at all.
+#[cfg(feature = "hpindex")]
+fn preload_index(
+    dir: &Option<PathBuf>,
+    options: &typg_core::index::IndexOptions,
+    warm: bool,
+    quiet: bool,
+) -> Result<()> {
+    let started = Instant::now();
+    let index_path = resolve_index_path(dir, None)?;
+    if !index_path.is_dir() {
+        return Err(anyhow!(
+            "no index at {} to preload (build one with `typg cache add --index`)",
+            index_path.display()
+        ));
+    }
+    let index = typg_core::index::FontIndex::shared(&index_path, options)
+        .with_context(|| format!("preloading index {}", index_path.display()))?;
+    let faces = index.count()?;
+    let warmed = if warm {
+        let bytes = index
+            .warm()
+            .with_context(|| format!("warming index {}", index_path.display()))?;
+        format!(", {} read", human_bytes(bytes))
+    } else {
+        String::new()
+    };
+    if !quiet {
+        eprintln!(
+            "index: {} faces ready in {:.1}s{warmed} ({})",
+            thousands(faces as u64),
+            started.elapsed().as_secs_f64(),
+            index_path.display()
+        );
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+struct OutputFormat {
+    json: bool,
+    ndjson: bool,
+    codepoints: CodepointFormat,
+    provenance: bool,
+    roots: Vec<PathBuf>,
+    paths: bool,
+    uri: bool,
+    columns: bool,
+    column_set: Vec<Column>,
+    max_path_width: Option<usize>,
+    fields: Vec<Field>,
+    header: bool,
+    collections: bool,
+    expand_instances: bool,
+    export: Option<ExportFormat>,
+    unicode_range_gap: u32,
+    template: Option<Template>,
+    color: ColorChoice,
+    theme: Option<String>,
+}
+
+impl OutputFormat {
+    fn from_find(args: &FindArgs) -> Self {
+        Self {
+            json: args.json,
+            ndjson: args.ndjson,
+            codepoints: args.codepoint_format.into(),
+            provenance: args.provenance,
+            roots: Vec::new(),
+            paths: args.paths_only,
+            uri: args.uri,
+            columns: args.columns.is_some() || !args.fields.is_empty(),
+            column_set: column_set(args.columns.as_deref()),
+            max_path_width: args.max_path_width,
+            fields: args.fields.clone(),
+            header: !args.no_header,
+            collections: args.collections,
+            expand_instances: args.expand_instances,
+            export: args.format,
+            unicode_range_gap: args.unicode_range_gap,
+            template: args.template.clone(),
+            color: args.color,
+            theme: args.theme.clone(),
+        }
+    }
+
+    fn from_output(args: &OutputArgs) -> Self {
+        Self {
+            json: args.json,
+            ndjson: args.ndjson,
+            codepoints: args.codepoint_format.into(),
+            provenance: args.provenance,
+            roots: Vec::new(),
+            paths: args.paths,
+            uri: args.uri,
+            columns: args.columns.is_some() || !args.fields.is_empty(),
+            column_set: column_set(args.columns.as_deref()),
+            max_path_width: args.max_path_width,
+            fields: args.fields.clone(),
+            header: !args.no_header,
+            collections: args.collections,
+            expand_instances: args.expand_instances,
+            export: args.format,
+            unicode_range_gap: args.unicode_range_gap,
+            template: args.template.clone(),
+            color: args.color,
+            theme: args.theme.clone(),
+        }
+    }
+}
+
+/// The `--columns` parts to show: the ones listed, or all of them.
+fn column_set(columns: Option<&[Column]>) -> Vec<Column> {
+    match columns {
+        Some(columns) if !columns.is_empty() => columns.to_vec(),
+        _ => Column::value_variants().to_vec(),
+    }
+}
+
+impl OutputFormat {
+    /// The color theme for writing to a terminal or not.
+    fn theme(&self, is_terminal: bool) -> Result<Theme> {
+        Theme::resolve(self.color, self.theme.as_deref(), is_terminal)
+    }
+
+    /// Give `item` the path it is written with: with `--uri`, its
+    /// `file://` URI.
+    fn locate(&self, item: &mut TypgFontFaceMatch) {
+        if self.uri {
+            item.source.path = PathBuf::from(file_uri(&item.source.path));
+        }
+    }
+
+    /// `matches` as they are written; see [`locate`](Self::locate).
+    fn located<'a>(&self, matches: &'a [TypgFontFaceMatch]) -> Cow<'a, [TypgFontFaceMatch]> {
+        if !self.uri {
+            return Cow::Borrowed(matches);
+        }
+        Cow::Owned(
+            matches
+                .iter()
+                .map(|item| {
+                    let mut item = item.clone();
+                    self.locate(&mut item);
+                    item
+                })
+                .collect(),
+        )
+    }
+
+    /// Record what was searched, for the `--provenance` envelope.
+    fn with_roots(mut self, roots: Vec<PathBuf>) -> Self {
+        self.roots = roots;
+        self
+    }
+}
+
+/// Write a JSON array, or a `{provenance, results}` envelope with `--provenance`.
+fn write_json<T: serde::Serialize>(
+    items: &[T],
+    mut w: impl Write,
+    format: &OutputFormat,
+) -> Result<()> {
+    if format.provenance {
+        let query = env::args().skip(1).collect();
+        let provenance = Provenance::capture(query, format.roots.clone());
+        write_json_envelope(items, &provenance, &mut w, format.codepoints)?;
+    } else {
+        write_json_pretty_with(items, &mut w, format.codepoints)?;
+    }
+    Ok(())
+}
+
+fn write_matches(
+    matches: &[TypgFontFaceMatch],
+    query: &Query,
+    format: &OutputFormat,
+) -> Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    let theme = format
+        .theme(handle.is_terminal())?
+        .with_highlight(query.name_patterns());
+
+    if let Some(export) = format.export {
+        write_export(matches, export, format.unicode_range_gap, &mut handle)?;
+    } else if let Some(template) = &format.template {
+        write_template(matches, template, &mut handle, format.collections)?;
+    } else if format.paths {
+        write_paths(matches, &mut handle, format.collections)?;
+    } else if format.ndjson {
+        write_ndjson_with(matches, &mut handle, format.codepoints)?;
+    } else if format.json {
+        write_json(matches, &mut handle, format)?;
+    } else if !format.fields.is_empty() {
+        let limits = WidthLimits::for_output(format.max_path_width, handle.is_terminal());
+        write_fields(
+            matches,
+            &format.fields,
+            &mut handle,
+            format.header,
+            &theme,
+            format.collections,
+            limits,
+        )?;
+    } else if format.columns {
+        let limits = WidthLimits::for_output(format.max_path_width, handle.is_terminal());
+        write_columns(
+            matches,
+            &format.column_set,
+            limits,
+            &mut handle,
+            &theme,
+            format.collections,
+        )?;
+    } else {
+        write_plain(matches, &mut handle, &theme, format.collections)?;
+    }
+
+    Ok(())
+}
+
+/// Build a `Query` from the CLI filter arguments.
+fn build_query(args: &FindArgs) -> Result<Query> {
+    build_query_from_parts(
+        &args.axes,
+        &args.features,
+        &args.scripts,
+        &args.langs,
+        &args.tables,
+        &args.name_patterns,
+        args.name_match.into(),
+        &args.creator_patterns,
+        &args.license_patterns,
+        &args.codepoints,
+        &args.text,
+        &args.unicode_blocks,
+        &args.coverage,
+        args.variable,
+        &args.weight,
+        &args.width,
+        &args.family_class,
+        &args.embedding,
+        args.min_glyphs,
+        args.min_axes,
+        args.max_axes,
+        &args.vendors,
+        &args.instances,
+        args.only_collections,
+        args.collection_index,
+        args.unicode_cmap_only,
+        &args.query_expr,
+        args.preset.map(Preset::from),
+    )
+    .and_then(|query| with_table_checksums(query, &args.table_checksums))
+    .and_then(|query| apply_exclusions(query, &args.exclude))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_query_from_parts(
+    axes: &[String],
+    features: &[String],
+    scripts: &[String],
+    langs: &[String],
+    tables: &[String],
+    name_patterns: &[String],
+    name_match: NameMatch,
+    creator_patterns: &[String],
+    license_patterns: &[String],
+    codepoints: &[String],
+    text: &Option<String>,
+    unicode_blocks: &[String],
+    coverage_specs: &[String],
+    variable: bool,
+    weight: &Option<String>,
+    width: &Option<String>,
+    family_class: &Option<String>,
+    embedding: &Option<String>,
+    min_glyphs: Option<u16>,
+    min_axes: Option<usize>,
+    max_axes: Option<usize>,
+    vendors: &[String],
+    instances: &[String],
+    only_collections: bool,
+    collection_index: Option<u32>,
+    unicode_cmap_only: bool,
+    query_expr: &Option<String>,
+    preset: Option<Preset>,
+) -> Result<Query> {
+    let axes = in_field("axes", parse_tag_list(axes))?;
+    let features = in_field("features", parse_tag_list(features))?;
+    let scripts = in_field("scripts", parse_tag_list(scripts))?;
+    let langs = in_field("langs", parse_tag_list(langs))?;
+    let tables = in_field("tables", parse_tag_list(tables))?;
+    let name_patterns = in_field(
+        "names",
+        name_patterns
+            .iter()
+            .map(|pattern| parse_name_pattern(pattern, name_match))
+            .collect::<Result<Vec<_>>>(),
+    )?;
+    let creator_patterns = in_field("creator", compile_patterns(creator_patterns))?;
+    let license_patterns = in_field("license", compile_patterns(license_patterns))?;
+    let mut codepoints = in_field("codepoints", parse_codepoints(codepoints))?;
+    let weight_range = in_field("weight", parse_optional_range(weight))?;
+    let width_range = in_field("width", parse_optional_range(width))?;
+    let family_class = in_field("family_class", parse_optional_family_class(family_class))?;
+    let embedding = in_field(
+        "embedding",
+        embedding.as_deref().map(parse_embedding).transpose(),
+    )?;
+    let mut coverage = in_field(
+        "unicode_blocks",
+        unicode_blocks
+            .iter()
+            .map(|name| parse_unicode_block(name))
+            .collect::<Result<Vec<_>>>(),
+    )?;
+    coverage.extend(in_field(
+        "coverage",
+        coverage_specs
+            .iter()
+            .map(|spec| parse_coverage_filter(spec))
+            .collect::<Result<Vec<_>>>(),
+    )?);
+    let expr = in_field(
+        "query",
+        query_expr.as_deref().map(parse_query_expr).transpose(),
+    )?;
+
+    if let Some(text) = text {
+        codepoints.extend(text.chars());
+    }
 
     dedup_chars(&mut codepoints);
 
-    Ok(Query::new()
-        .with_axes(axes)
-        .with_features(features)
-        .with_scripts(scripts)
-        .with_tables(tables)
-        .with_name_patterns(name_patterns)
-        .with_creator_patterns(creator_patterns)
-        .with_license_patterns(license_patterns)
-        .with_codepoints(codepoints)
-        .require_variable(variable)
-        .with_weight_range(weight_range)
-        .with_width_range(width_range)
-        .with_family_class(family_class))
+    let query = Query::new()
+        .with_axes(axes)
+        .with_features(features)
+        .with_scripts(scripts)
+        .with_languages(langs)
+        .with_tables(tables)
+        .with_name_patterns(name_patterns)
+        .with_creator_patterns(creator_patterns)
+        .with_license_patterns(license_patterns)
+        .with_codepoints(codepoints)
+        .with_coverage(coverage)
+        .require_variable(variable)
+        .with_weight_range(weight_range)
+        .with_width_range(width_range)
+        .with_family_class(family_class)
+        .with_min_glyphs(min_glyphs)
+        .with_min_axes(min_axes)
+        .with_max_axes(max_axes)
+        .with_vendors(vendors.to_vec())
+        .with_instances(instances.to_vec())
+        .require_collection(only_collections)
+        .with_collection_index(collection_index)
+        .require_unicode_cmap(unicode_cmap_only)
+        .with_embedding(embedding)
+        .with_expr(expr);
+    Ok(match preset {
+        Some(preset) => preset.query().and(query),
+        None => query,
+    })
+}
+
+/// Add the `--table-checksum` requirements to `query`.
+fn with_table_checksums(query: Query, specs: &[String]) -> Result<Query> {
+    let checksums = in_field(
+        "table_checksums",
+        specs
+            .iter()
+            .map(|spec| parse_table_checksum(spec))
+            .collect::<Result<Vec<_>>>(),
+    )?;
+    Ok(query.with_table_checksums(checksums))
+}
+
+/// Add the `--not-*` and `--exclude-path` filters to a query.
+fn apply_exclusions(query: Query, exclude: &ExcludeArgs) -> Result<Query> {
+    let excluded_paths = in_field(
+        "exclude_path",
+        exclude
+            .exclude_path
+            .iter()
+            .map(|glob| parse_path_glob(glob))
+            .collect::<Result<Vec<_>>>(),
+    )?;
+
+    Ok(query
+        .without_axes(in_field("not_axes", parse_tag_list(&exclude.not_axes))?)
+        .without_features(in_field(
+            "not_features",
+            parse_tag_list(&exclude.not_features),
+        )?)
+        .without_scripts(in_field(
+            "not_scripts",
+            parse_tag_list(&exclude.not_scripts),
+        )?)
+        .without_tables(in_field("not_tables", parse_tag_list(&exclude.not_tables))?)
+        .without_name_patterns(in_field("not_name", compile_patterns(&exclude.not_name))?)
+        .without_paths(excluded_paths)
+        .without_variable(exclude.not_variable))
+}
+
+/// A filter value that failed to parse, with the `/search` request field
+/// it came from. Displays as the underlying error and its causes.
+#[derive(Debug)]
+pub(crate) struct InvalidField {
+    pub(crate) field: &'static str,
+    message: String,
+}
+
+impl std::fmt::Display for InvalidField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for InvalidField {}
+
+/// Tag a parse error with the request field it came from.
+fn in_field<T>(field: &'static str, result: Result<T>) -> Result<T> {
+    result.map_err(|err| {
+        InvalidField {
+            field,
+            message: format!("{err:#}"),
+        }
+        .into()
+    })
+}
+
+fn dedup_chars(cps: &mut Vec<char>) {
+    cps.sort();
+    cps.dedup();
+}
+
+fn compile_patterns(patterns: &[String]) -> Result<Vec<Regex>> {
+    patterns
+        .iter()
+        .map(|p| Regex::new(p).with_context(|| format!("invalid regex: {p}")))
+        .collect()
+}
+
+fn parse_codepoints(raw: &[String]) -> Result<Vec<char>> {
+    let mut cps = Vec::new();
+    for chunk in raw {
+        cps.extend(parse_codepoint_list(chunk)?);
+    }
+    Ok(cps)
+}
+
+fn parse_optional_range(raw: &Option<String>) -> Result<Option<RangeInclusive<u16>>> {
+    match raw {
+        Some(value) => Ok(Some(parse_u16_range(value)?)),
+        None => Ok(None),
+    }
+}
+
+fn parse_optional_family_class(raw: &Option<String>) -> Result<Option<FamilyClassFilter>> {
+    match raw {
+        Some(value) => Ok(Some(parse_family_class(value)?)),
+        None => Ok(None),
+    }
+}
+
+fn gather_paths(
+    raw_paths: &[PathBuf],
+    read_stdin: bool,
+    include_system: bool,
+    mut stdin: impl BufRead,
+) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+
+    if read_stdin {
+        paths.extend(read_paths_from(&mut stdin)?);
+    }
+
+    for path in raw_paths {
+        if path == Path::new("-") {
+            paths.extend(read_paths_from(&mut stdin)?);
+        } else {
+            paths.push(path.clone());
+        }
+    }
+
+    if include_system {
+        paths.extend(system_font_roots()?);
+    }
+
+    if paths.is_empty() {
+        return Err(anyhow!("no search paths provided"));
+    }
+
+    Ok(paths)
+}
+
+fn read_paths_from(reader: &mut impl BufRead) -> Result<Vec<PathBuf>> {
+    let mut buf = String::new();
+    let mut paths = Vec::new();
+
+    loop {
+        buf.clear();
+        let read = reader.read_line(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+
+        let trimmed = buf.trim();
+        if !trimmed.is_empty() {
+            paths.push(PathBuf::from(trimmed));
+        }
+    }
+
+    Ok(paths)
+}
+
+fn system_font_roots() -> Result<Vec<PathBuf>> {
+    if let Ok(raw) = env::var("TYPOG_SYSTEM_FONT_DIRS") {
+        let mut overrides: Vec<PathBuf> = raw
+            .split([':', ';'])
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from)
+            .filter(|p| p.exists())
+            .collect();
+
+        overrides.sort();
+        overrides.dedup();
+
+        return if overrides.is_empty() {
+            Err(anyhow!("TYPOG_SYSTEM_FONT_DIRS is set but no paths exist"))
+        } else {
+            Ok(overrides)
+        };
+    }
+
+    let mut candidates: Vec<PathBuf> = Vec::new();
+
+    #[cfg(target_os = "macos")]
+    {
+        candidates.push(PathBuf::from("/System/Library/Fonts"));
+        candidates.push(PathBuf::from("/Library/Fonts"));
+        if let Some(home) = env::var_os("HOME") {
+            candidates.push(PathBuf::from(home).join("Library/Fonts"));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        candidates.push(PathBuf::from("/usr/share/fonts"));
+        candidates.push(PathBuf::from("/usr/local/share/fonts"));
+        if let Some(home) = env::var_os("HOME") {
+            candidates.push(PathBuf::from(home).join(".local/share/fonts"));
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        if let Some(system_root) = env::var_os("SYSTEMROOT") {
+            candidates.push(PathBuf::from(system_root).join("Fonts"));
+        }
+        if let Some(local_appdata) = env::var_os("LOCALAPPDATA") {
+            candidates.push(PathBuf::from(local_appdata).join("Microsoft/Windows/Fonts"));
+        }
+    }
+
+    candidates.retain(|p| p.exists());
+    candidates.sort();
+    candidates.dedup();
+
+    if candidates.is_empty() {
+        return Err(anyhow!(
+            "no system font directories found for this platform"
+        ));
+    }
+
+    Ok(candidates)
+}
+
+/// Apply `--sort` and `--reverse`; without either, keep path order.
+fn sort_results(
+    mut matches: Vec<TypgFontFaceMatch>,
+    sort: &[SortBy],
+    reverse: bool,
+) -> Vec<TypgFontFaceMatch> {
+    if !sort.is_empty() || reverse {
+        let keys: Vec<SortKey> = sort.iter().copied().map(SortKey::from).collect();
+        sort_matches_by_keys(&mut matches, &keys, reverse);
+    }
+    matches
+}
+
+/// Apply `--offset` and `--limit` to results already in their final order.
+fn paginate<T>(items: Vec<T>, offset: Option<usize>, limit: Option<usize>) -> Vec<T> {
+    if offset.is_none() && limit.is_none() {
+        return items;
+    }
+    items
+        .into_iter()
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+/// Apply `--per-family`: keep at most N faces per family, or everything.
+fn per_family<T>(
+    items: Vec<T>,
+    limit: Option<usize>,
+    meta: impl Fn(&T) -> &TypgFontFaceMeta,
+) -> Vec<T> {
+    match limit {
+        Some(n) => limit_per_family(items, n, meta),
+        None => items,
+    }
+}
+
+/// Write matches, their family groups with `--group-by`, or their named
+/// instances with `--expand-instances`; `--count` counts whichever is being
+/// written. `query` picks the instances to list.
+fn write_results(
+    matches: &[TypgFontFaceMatch],
+    query: &Query,
+    group_by: Option<GroupBy>,
+    count_only: bool,
+    output: &OutputFormat,
+) -> Result<()> {
+    let matches = output.located(matches);
+    let matches = matches.as_ref();
+    if output.expand_instances {
+        let rows = expand_instances(matches, query);
+        if count_only {
+            println!("{}", rows.len());
+            return Ok(());
+        }
+        let stdout = io::stdout();
+        let mut handle = stdout.lock();
+        let theme = output.theme(handle.is_terminal())?;
+        return write_instances(&rows, &mut handle, output, &theme);
+    }
+
+    let Some(GroupBy::Family) = group_by else {
+        if count_only {
+            println!("{}", matches.len());
+            return Ok(());
+        }
+        return write_matches(matches, query, output);
+    };
+
+    let groups = group_families(matches);
+    if count_only {
+        println!("{}", groups.len());
+        return Ok(());
+    }
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    let theme = output
+        .theme(handle.is_terminal())?
+        .with_highlight(query.name_patterns());
+    write_groups(&groups, &mut handle, output, &theme)
+}
+
+/// Write family groups: JSON/NDJSON objects, or a family heading followed by
+/// one indented `style  weight  path` line per member.
+fn write_groups(
+    groups: &[TypgFamilyGroup],
+    mut w: impl Write,
+    format: &OutputFormat,
+    theme: &Theme,
+) -> Result<()> {
+    if format.ndjson {
+        return write_ndjson_with(groups, w, format.codepoints);
+    }
+    if format.json {
+        write_json(groups, &mut w, format)?;
+        writeln!(w)?;
+        return Ok(());
+    }
+
+    for group in groups {
+        let family = if group.family.is_empty() {
+            "(unnamed)"
+        } else {
+            &group.family
+        };
+        let heading = theme.paint_name(family, Role::Name);
+        writeln!(w, "{heading} ({})", group.members.len())?;
+
+        let styles: Vec<String> = group
+            .members
+            .iter()
+            .map(|m| m.style.as_deref().unwrap_or("-").to_string())
+            .collect();
+        let style_width = styles.iter().map(|s| s.chars().count()).max().unwrap_or(0);
+        for (member, style) in group.members.iter().zip(styles) {
+            let weight = member
+                .weight_class
+                .map(|w| w.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let path = if format.collections {
+                member.source.path_with_index()
+            } else {
+                member.source.path.display().to_string()
+            };
+            let path = theme.paint(&path, Role::Path);
+            writeln!(w, "  {style:<style_width$}  {weight:>4}  {path}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Write instance rows: JSON/NDJSON objects, or one aligned
+/// `family style  coordinates  path` line per row.
+fn write_instances(
+    rows: &[TypgInstanceRow],
+    mut w: impl Write,
+    format: &OutputFormat,
+    theme: &Theme,
+) -> Result<()> {
+    if format.ndjson {
+        return write_ndjson_with(rows, w, format.codepoints);
+    }
+    if format.json {
+        write_json(rows, &mut w, format)?;
+        writeln!(w)?;
+        return Ok(());
+    }
+
+    let lines: Vec<(String, String, String)> = rows
+        .iter()
+        .map(|row| {
+            let name = match row.style.as_deref() {
+                Some(style) if !row.family.is_empty() => format!("{} {style}", row.family),
+                Some(style) => style.to_string(),
+                None if row.family.is_empty() => "(unnamed)".to_string(),
+                None => row.family.clone(),
+            };
+            let coordinates = row
+                .coordinates
+                .iter()
+                .map(|(axis, value)| format!("{axis}={value}"))
+                .collect::<Vec<_>>()
+                .join(",");
+            let path = if format.collections {
+                row.source.path_with_index()
+            } else {
+                row.source.path.display().to_string()
+            };
+            (name, coordinates, path)
+        })
+        .collect();
+    let name_width = lines.iter().map(|l| l.0.chars().count()).max().unwrap_or(0);
+    let coord_width = lines.iter().map(|l| l.1.chars().count()).max().unwrap_or(0);
+
+    for (name, coordinates, path) in lines {
+        let name = theme.paint_name(&format!("{name:<name_width$}"), Role::Name);
+        let path = theme.paint(&path, Role::Path);
+        if coord_width == 0 {
+            writeln!(w, "{name}  {path}")?;
+            continue;
+        }
+        let coordinates = theme.paint(&format!("{coordinates:<coord_width$}"), Role::Tags);
+        writeln!(w, "{name}  {coordinates}  {path}")?;
+    }
+    Ok(())
+}
+
+fn write_ranked_stdout(ranked: &[RankedMatch], format: &OutputFormat) -> Result<()> {
+    let mut ranked = Cow::Borrowed(ranked);
+    if format.uri {
+        for item in ranked.to_mut() {
+            format.locate(&mut item.face);
+        }
+    }
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    let theme = format.theme(handle.is_terminal())?;
+    write_ranked(&ranked, &mut handle, format, &theme)
+}
+
+/// Write matches in a `--format` configuration format.
+fn write_export(
+    matches: &[TypgFontFaceMatch],
+    export: ExportFormat,
+    unicode_range_gap: u32,
+    w: impl Write,
+) -> Result<()> {
+    match export {
+        ExportFormat::Fontconfig => write_fontconfig(matches, w),
+        ExportFormat::Css => write_css(matches, unicode_range_gap, w),
+    }
+}
+
+/// Write ranked results best-first; plain output prefixes each path with its score.
+fn write_ranked(
+    ranked: &[RankedMatch],
+    mut w: impl Write,
+    format: &OutputFormat,
+    theme: &Theme,
+) -> Result<()> {
+    if let Some(export) = format.export {
+        let faces: Vec<TypgFontFaceMatch> = ranked.iter().map(|r| r.face.clone()).collect();
+        return write_export(&faces, export, format.unicode_range_gap, w);
+    }
+    if let Some(template) = &format.template {
+        let faces: Vec<TypgFontFaceMatch> = ranked.iter().map(|r| r.face.clone()).collect();
+        return write_template(&faces, template, w, format.collections);
+    }
+    if format.ndjson {
+        return write_ndjson_with(ranked, w, format.codepoints);
+    }
+    if format.json {
+        write_json(ranked, &mut w, format)?;
+        writeln!(w)?;
+        return Ok(());
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    for item in ranked {
+        if !format.collections && !seen.insert(item.face.source.path.clone()) {
+            continue;
+        }
+        if format.paths {
+            let path = if format.collections {
+                item.face.source.path_with_index()
+            } else {
+                item.face.source.path.display().to_string()
+            };
+            writeln!(w, "{path}")?;
+        } else {
+            let rendered = render_path(&item.face, theme, format.collections);
+            writeln!(w, "{:.3}  {rendered}", item.score)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_plain(
+    matches: &[TypgFontFaceMatch],
+    mut w: impl Write,
+    theme: &Theme,
+    collections: bool,
+) -> Result<()> {
+    if collections {
+        for item in matches {
+            let rendered = render_path(item, theme, true);
+            writeln!(w, "{rendered}")?;
+        }
+    } else {
+        let mut seen = std::collections::HashSet::new();
+        for item in matches {
+            if seen.insert(item.source.path.clone()) {
+                let rendered = render_path(item, theme, false);
+                writeln!(w, "{rendered}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_paths(
+    matches: &[TypgFontFaceMatch],
+    mut w: impl Write,
+    collections: bool,
+) -> Result<()> {
+    if collections {
+        for item in matches {
+            writeln!(w, "{}", item.source.path_with_index())?;
+        }
+    } else {
+        let mut seen = std::collections::HashSet::new();
+        for item in matches {
+            if seen.insert(item.source.path.clone()) {
+                writeln!(w, "{}", item.source.path.display())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_columns(
+    matches: &[TypgFontFaceMatch],
+    columns: &[Column],
+    limits: WidthLimits,
+    mut w: impl Write,
+    theme: &Theme,
+    collections: bool,
+) -> Result<()> {
+    // (path, name, tag counts, variable marker)
+    let rows: Vec<(String, String, String, &str)> = matches
+        .iter()
+        .map(|m| {
+            let path = if collections {
+                m.source.path_with_index()
+            } else {
+                m.source.path.display().to_string()
+            };
+            let name = m
+                .metadata
+                .names
+                .first()
+                .map_or("(unnamed)", |name| name.as_ref())
+                .to_string();
+
+            let tags = format!(
+                "axes:{:<2} feats:{:<2} scripts:{:<2} tables:{:<2}",
+                m.metadata.axis_tags.len(),
+                m.metadata.feature_tags.len(),
+                m.metadata.script_tags.len(),
+                m.metadata.table_tags.len(),
+            );
+            let marker = if m.metadata.is_variable { " var" } else { "" };
+
+            (path, name, tags, marker)
+        })
+        .collect();
+
+    let widest = |cell: fn(&(String, String, String, &str)) -> usize| {
+        rows.iter().map(cell).max().unwrap_or(0)
+    };
+    let name_width = widest(|row| row.1.chars().count()).min(MAX_NAME_WIDTH);
+    let tags_width = widest(|row| row.2.len() + row.3.len());
+    let others: usize = columns
+        .iter()
+        .map(|column| match column {
+            Column::Path => 0,
+            Column::Name => name_width + 2,
+            Column::Tags => tags_width + 2,
+        })
+        .sum();
+    let path_limit = limits.path_width(others);
+    let path_width = widest(|row| row.0.chars().count()).min(path_limit.unwrap_or(usize::MAX));
+
+    let last = columns.len().saturating_sub(1);
+    for (path, name, tags, marker) in rows {
+        let line: Vec<String> = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let (painted, len, width) = match column {
+                    Column::Path => {
+                        let path = truncate_middle(&path, path_limit);
+                        (
+                            theme.paint(&path, Role::Path),
+                            path.chars().count(),
+                            path_width,
+                        )
+                    }
+                    Column::Name => (
+                        theme.paint_name(&name, Role::Name),
+                        name.chars().count(),
+                        name_width,
+                    ),
+                    Column::Tags => (
+                        theme.paint(&tags, Role::Tags) + &theme.paint(marker, Role::Variable),
+                        tags.len() + marker.len(),
+                        tags_width,
+                    ),
+                };
+                // Pad outside the escape codes so they don't count as width.
+                let pad = if i == last {
+                    0
+                } else {
+                    width.saturating_sub(len)
+                };
+                painted + &" ".repeat(pad)
+            })
+            .collect();
+        writeln!(w, "{}", line.join("  "))?;
+    }
+
+    Ok(())
+}
+
+/// Names longer than this overflow the name column instead of widening it.
+const MAX_NAME_WIDTH: usize = 80;
+
+/// Paths are never squeezed narrower than this to fit the terminal.
+const MIN_PATH_WIDTH: usize = 20;
+
+/// How wide the path column of `--columns`/`--fields` output may get.
+#[derive(Clone, Copy, Debug, Default)]
+struct WidthLimits {
+    /// `--max-path-width`.
+    max_path: Option<usize>,
+    /// Width of the terminal being written to, if any.
+    line: Option<usize>,
+}
+
+impl WidthLimits {
+    /// Limits for output to stdout; the terminal width only counts when
+    /// stdout is one.
+    fn for_output(max_path: Option<usize>, is_terminal: bool) -> Self {
+        Self {
+            max_path,
+            line: if is_terminal { terminal_width() } else { None },
+        }
+    }
+
+    /// Widest the path column may be when the other columns, with their
+    /// separators, take `others` characters. `None` means unlimited.
+    fn path_width(self, others: usize) -> Option<usize> {
+        let fit = self
+            .line
+            .map(|line| line.saturating_sub(others).max(MIN_PATH_WIDTH));
+        match (self.max_path, fit) {
+            (Some(max), Some(fit)) => Some(max.min(fit)),
+            (max, fit) => max.or(fit),
+        }
+    }
+}
+
+/// Width of the terminal: `COLUMNS` when set, else the size of the tty on
+/// stdout.
+fn terminal_width() -> Option<usize> {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|&width| width > 0)
+        .or_else(tty_width)
+}
+
+#[cfg(unix)]
+fn tty_width() -> Option<usize> {
+    // SAFETY: `winsize` is plain old data, and TIOCGWINSZ only writes into
+    // the struct we pass, which outlives the call.
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+    (ok && size.ws_col > 0).then_some(usize::from(size.ws_col))
+}
+
+#[cfg(not(unix))]
+fn tty_width() -> Option<usize> {
+    None
+}
+
+/// Shorten `text` to `width` characters by replacing its middle with `…`,
+/// keeping the end (the file name) slightly longer than the start.
+fn truncate_middle(text: &str, width: Option<usize>) -> String {
+    let len = text.chars().count();
+    let Some(width) = width.filter(|&width| len > width) else {
+        return text.to_string();
+    };
+    if width == 0 {
+        return String::new();
+    }
+    let keep = width - 1;
+    let head = keep / 2;
+    let tail = keep - head;
+    let mut out: String = text.chars().take(head).collect();
+    out.push('…');
+    out.extend(text.chars().skip(len - tail));
+    out
+}
+
+fn render_path(item: &TypgFontFaceMatch, theme: &Theme, collections: bool) -> String {
+    let rendered = if collections {
+        item.source.path_with_index()
+    } else {
+        item.source.path.display().to_string()
+    };
+    theme.paint(&rendered, Role::Path)
+}
+
+fn run_cache_add(args: CacheAddArgs, quiet: bool) -> Result<()> {
+    if matches!(args.jobs, Some(0)) {
+        return Err(anyhow!("--jobs must be at least 1"));
+    }
+
+    #[cfg(feature = "hpindex")]
+    if args.use_index {
+        return run_cache_add_index(args, quiet);
+    }
+
+    #[cfg(not(feature = "hpindex"))]
+    if args.use_index {
+        return Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ));
+    }
+
+    let cache_path = resolve_cache_path(&args.cache_path, args.profile)?;
+    let existing = if cache_path.exists() {
+        load_cache(&cache_path)?
+    } else {
+        Vec::new()
+    };
+
+    let stored: HashMap<String, (Option<u64>, Option<u64>)> = existing
+        .iter()
+        .map(|entry| {
+            let meta = &entry.metadata;
+            (
+                path_key(&entry.source.path),
+                (meta.modified, meta.file_size),
+            )
+        })
+        .collect();
+    let additions = cache_additions(&args, io::stdin().lock(), |entry| {
+        let current = (Some(entry.mtime), Some(entry.size));
+        Ok(stored.get(&path_key(&entry.path)) == Some(&current))
+    })?;
+
+    let merged = merge_entries(existing, additions.faces);
+    write_cache(&cache_path, &merged)?;
+    if !additions.roots.is_empty() {
+        let roots = record_roots(load_roots(&cache_path)?, additions.roots);
+        write_roots(&cache_path, &roots)?;
+    }
+
+    if !quiet {
+        eprintln!(
+            "cached {} font faces at {}{}",
+            merged.len(),
+            cache_path.display(),
+            unchanged_note(additions.unchanged)
+        );
+    }
+    if let Some(report) = additions.report {
+        print_summary(&report, quiet);
+        args.errors.write_failures(&report)?;
+    }
+    Ok(())
+}
+
+/// Faces for `cache add` to store, and how they were obtained.
+struct CacheAdditions {
+    faces: Vec<TypgFontFaceMatch>,
+    /// The scan's report; `None` when nothing was scanned.
+    report: Option<SearchReport>,
+    /// `--manifest` files skipped because the store already has them.
+    unchanged: Option<usize>,
+    /// Paths scanned, to record for `cache refresh` and `watch`; empty
+    /// for imported records and manifests.
+    roots: Vec<ScanRoot>,
+}
+
+/// ` (N manifest files unchanged)` after the `cache add` message.
+fn unchanged_note(unchanged: Option<usize>) -> String {
+    match unchanged {
+        Some(n) => format!(" ({n} manifest files unchanged)"),
+        None => String::new(),
+    }
+}
+
+/// The faces `cache add` stores: scanned from its paths, read from
+/// `--from-ndjson` without touching the fonts, or scanned from the
+/// `--manifest` files for which `is_current` says the store is out of date.
+fn cache_additions(
+    args: &CacheAddArgs,
+    mut stdin: impl BufRead,
+    mut is_current: impl FnMut(&ManifestEntry) -> Result<bool>,
+) -> Result<CacheAdditions> {
+    if let Some(file) = &args.from_ndjson {
+        let mut faces = read_records(file, stdin)?;
+        sort_entries(&mut faces);
+        return Ok(CacheAdditions {
+            faces,
+            report: None,
+            unchanged: None,
+            roots: Vec::new(),
+        });
+    }
+
+    let opts = cache_add_options(args);
+
+    if let Some(file) = &args.manifest {
+        let mut text = String::new();
+        if file == Path::new("-") {
+            stdin
+                .read_to_string(&mut text)
+                .context("reading manifest from stdin")?;
+        } else {
+            text =
+                fs::read_to_string(file).with_context(|| format!("reading {}", file.display()))?;
+        }
+        let entries =
+            parse_manifest(&text).with_context(|| format!("parsing {}", file.display()))?;
+        let total = entries.len();
+        let mut changed = Vec::new();
+        for entry in entries {
+            if !is_current(&entry)? {
+                changed.push(entry);
+            }
+        }
+        let unchanged = Some(total - changed.len());
+        if changed.is_empty() {
+            return Ok(CacheAdditions {
+                faces: Vec::new(),
+                report: None,
+                unchanged,
+                roots: Vec::new(),
+            });
+        }
+
+        let paths: Vec<PathBuf> = changed.iter().map(|entry| entry.path.clone()).collect();
+        let (mut faces, report) = search_with_report(&paths, &Query::new(), &opts)?;
+        // Record the manifest's mtimes, which the next run compares against.
+        let mtimes: HashMap<&Path, u64> = changed
+            .iter()
+            .map(|entry| (entry.path.as_path(), entry.mtime))
+            .collect();
+        for face in &mut faces {
+            if let Some(&mtime) = mtimes.get(face.source.path.as_path()) {
+                face.metadata.modified = Some(mtime);
+            }
+        }
+        return Ok(CacheAdditions {
+            faces,
+            report: Some(report),
+            unchanged,
+            roots: Vec::new(),
+        });
+    }
+
+    let paths = gather_paths(&args.paths, args.stdin_paths, args.system_fonts, stdin)?;
+    let (faces, report) = search_with_report(&paths, &Query::new(), &opts)?;
+    let roots = scan_roots(&paths, &opts)?;
+    Ok(CacheAdditions {
+        faces,
+        report: Some(report),
+        unchanged: None,
+        roots,
+    })
+}
+
+/// Scan settings from `cache add` flags.
+fn cache_add_options(args: &CacheAddArgs) -> SearchOptions {
+    SearchOptions {
+        follow_symlinks: args.follow_symlinks,
+        include_hidden: args.hidden,
+        jobs: args.jobs,
+        name_ids: name_ids(&args.name_ids),
+        error_policy: args.errors.policy(),
+        ..SearchOptions::default()
+    }
+}
+
+/// The scan roots to record for `paths`, skipping those that do not exist.
+fn scan_roots(paths: &[PathBuf], opts: &SearchOptions) -> Result<Vec<ScanRoot>> {
+    paths
+        .iter()
+        .filter(|path| path.exists())
+        .map(|path| ScanRoot::new(path, opts))
+        .collect()
+}
+
+/// One font file listed in a `cache add --manifest` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ManifestEntry {
+    path: PathBuf,
+    /// Modification time in seconds since the Unix epoch.
+    mtime: u64,
+    /// File size in bytes.
+    size: u64,
+}
+
+/// Parse a manifest: one `PATH MTIME SIZE` line per font file, separated by
+/// tabs or spaces (the path may contain spaces; the two numbers come last).
+/// Blank lines and lines starting with `#` are skipped.
+fn parse_manifest(text: &str) -> Result<Vec<ManifestEntry>> {
+    let mut entries = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.rsplitn(3, char::is_whitespace);
+        let (Some(size), Some(mtime), Some(path)) = (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(anyhow!(
+                "line {}: expected PATH MTIME SIZE, got '{line}'",
+                number + 1
+            ));
+        };
+        let number_field = |value: &str, what: &str| -> Result<u64> {
+            value
+                .parse()
+                .map_err(|_| anyhow!("line {}: invalid {what} '{value}'", number + 1))
+        };
+        entries.push(ManifestEntry {
+            path: PathBuf::from(path.trim_end()),
+            mtime: number_field(mtime, "mtime")?,
+            size: number_field(size, "size")?,
+        });
+    }
+    Ok(entries)
+}
+
+/// Read result records from a file, or from stdin for `-`.
+fn read_records(file: &Path, mut stdin: impl Read) -> Result<Vec<TypgFontFaceMatch>> {
+    let data = if file == Path::new("-") {
+        let mut data = Vec::new();
+        stdin
+            .read_to_end(&mut data)
+            .context("reading records from stdin")?;
+        data
+    } else {
+        fs::read(file).with_context(|| format!("reading {}", file.display()))?
+    };
+    parse_results(&data).with_context(|| format!("parsing {}", file.display()))
+}
+
+fn run_cache_list(args: CacheListArgs) -> Result<()> {
+    #[cfg(feature = "hpindex")]
+    if args.use_index {
+        return run_cache_list_index(args);
+    }
+
+    #[cfg(not(feature = "hpindex"))]
+    if args.use_index {
+        return Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ));
+    }
+
+    let cache_path = resolve_cache_path(&args.cache_path, args.profile)?;
+    let entries = load_cache(&cache_path)?;
+    let output = OutputFormat::from_output(&args.output).with_roots(vec![cache_path.clone()]);
+    write_results(&entries, &Query::new(), None, false, &output)
+}
+
+fn run_cache_find(args: CacheFindArgs, quiet: bool) -> Result<()> {
+    if matches!(args.per_family, Some(0)) {
+        return Err(anyhow!("--per-family must be at least 1"));
+    }
+
+    if let Some(socket) = &args.via_daemon {
+        return run_daemon_cache_find(&args, socket, quiet);
+    }
+
+    #[cfg(feature = "hpindex")]
+    if args.use_index {
+        return run_cache_find_index(args);
+    }
+
+    #[cfg(not(feature = "hpindex"))]
+    if args.use_index {
+        return Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ));
+    }
+
+    let profile = match args.profiles.as_slice() {
+        [] => None,
+        [profile] => Some(*profile),
+        _ => {
+            return Err(anyhow!(
+                "several --cache profiles can only be searched with --index"
+            ))
+        }
+    };
+    if !args.index_paths.is_empty() {
+        return Err(anyhow!("--index-path requires --index"));
+    }
+    let cache_paths = collection_stores(
+        &default_cache_path(&args.cache_path, profile)?,
+        collections(),
+    );
+    let started = Instant::now();
+    let entries = match cache_paths.as_slice() {
+        [cache_path] => load_cache(cache_path)?,
+        several => {
+            let mut entries = Vec::new();
+            let mut seen = HashSet::new();
+            for cache_path in several {
+                extend_unseen(&mut entries, &mut seen, load_cache(cache_path)?);
+            }
+            entries
+        }
+    };
+    let query = cache_find_query(&args)?;
+
+    let output = OutputFormat::from_output(&args.output).with_roots(cache_paths);
+
+    if args.ranking.rank {
+        let candidates = filter_cached(&entries, &query.relaxed());
+        let ranked = rank(candidates, &query, args.ranking.min_score.unwrap_or(0.0));
+        let ranked = per_family(ranked, args.per_family, |r| &r.face.metadata);
+        let total = ranked.len();
+        let ranked = paginate(ranked, args.offset, args.limit);
+        if args.count_only {
+            println!("{}", ranked.len());
+        } else {
+            write_ranked_stdout(&ranked, &output)?;
+        }
+        print_summary(&cached_report(&entries, total, started), quiet);
+        return Ok(());
+    }
+
+    let matches = filter_cached(&entries, &query);
+    let matches = sort_results(matches, &args.sort, args.reverse);
+    let matches = per_family(matches, args.per_family, |m| &m.metadata);
+    let total = matches.len();
+    let matches = paginate(matches, args.offset, args.limit);
+    write_results(&matches, &query, args.group_by, args.count_only, &output)?;
+    print_summary(&cached_report(&entries, total, started), quiet);
+    Ok(())
+}
+
+/// `cache find --via-daemon`: filter the cache or index a running
+/// `typg daemon` holds open, and print the matches.
+fn run_daemon_cache_find(
+    args: &CacheFindArgs,
+    socket: &Option<PathBuf>,
+    quiet: bool,
+) -> Result<()> {
+    let started = Instant::now();
+    let socket = resolve_daemon_socket(socket)?;
+    // Parsed here too, so bad flags fail before anything is sent.
+    let query = cache_find_query(args)?;
+
+    // Sorting and per-family limits need every match; page after them here.
+    let page_there = args.sort.is_empty() && !args.reverse && args.per_family.is_none();
+    let req = cache_find_request(args, page_there);
+    let response = server::daemon::ask(&socket, &req)?;
+    let mut matches = response.matches.unwrap_or_default();
+    let mut found = response.total;
+    if !page_there {
+        matches = sort_results(matches, &args.sort, args.reverse);
+        matches = per_family(matches, args.per_family, |m| &m.metadata);
+        found = matches.len();
+        matches = paginate(matches, args.offset, args.limit);
+    }
+
+    let output = OutputFormat::from_output(&args.output).with_roots(vec![socket.clone()]);
+    write_results(&matches, &query, args.group_by, args.count_only, &output)?;
+    print_elsewhere_summary(found, &socket.display().to_string(), started, quiet);
+    Ok(())
+}
+
+/// The daemon request for `cache find` flags.
+fn cache_find_request(args: &CacheFindArgs, page: bool) -> SearchRequest {
+    SearchRequest {
+        axes: args.axes.clone(),
+        features: args.features.clone(),
+        scripts: args.scripts.clone(),
+        langs: args.langs.clone(),
+        tables: args.tables.clone(),
+        table_checksums: args.table_checksums.clone(),
+        names: args.name_patterns.clone(),
+        name_match: args.name_match,
+        creator: args.creator_patterns.clone(),
+        license: args.license_patterns.clone(),
+        codepoints: args.codepoints.clone(),
+        text: args.text.clone(),
+        unicode_blocks: args.unicode_blocks.clone(),
+        coverage: args.coverage.clone(),
+        variable: args.variable,
+        weight: args.weight.clone(),
+        width: args.width.clone(),
+        family_class: args.family_class.clone(),
+        embedding: args.embedding.clone(),
+        min_glyphs: args.min_glyphs,
+        min_axes: args.min_axes,
+        max_axes: args.max_axes,
+        vendors: args.vendors.clone(),
+        instances: args.instances.clone(),
+        only_collections: args.only_collections,
+        collection_index: args.collection_index,
+        unicode_cmap_only: args.unicode_cmap_only,
+        query: args.query_expr.clone(),
+        preset: args.preset.map(|p| Preset::from(p).name().to_string()),
+        exclude: args.exclude.clone(),
+        sort: None,
+        reverse: false,
+        offset: if page { args.offset } else { None },
+        limit: if page { args.limit } else { None },
+        ..SearchRequest::default()
+    }
+}
+
+/// The query `cache find` flags describe.
+fn cache_find_query(args: &CacheFindArgs) -> Result<Query> {
+    build_query_from_parts(
+        &args.axes,
+        &args.features,
+        &args.scripts,
+        &args.langs,
+        &args.tables,
+        &args.name_patterns,
+        args.name_match.into(),
+        &args.creator_patterns,
+        &args.license_patterns,
+        &args.codepoints,
+        &args.text,
+        &args.unicode_blocks,
+        &args.coverage,
+        args.variable,
+        &args.weight,
+        &args.width,
+        &args.family_class,
+        &args.embedding,
+        args.min_glyphs,
+        args.min_axes,
+        args.max_axes,
+        &args.vendors,
+        &args.instances,
+        args.only_collections,
+        args.collection_index,
+        args.unicode_cmap_only,
+        &args.query_expr,
+        args.preset.map(Preset::from),
+    )
+    .and_then(|query| with_table_checksums(query, &args.table_checksums))
+    .and_then(|query| apply_exclusions(query, &args.exclude))
+}
+
+/// Summary for a cache search: files are the distinct paths in the cache.
+fn cached_report(entries: &[TypgFontFaceMatch], matches: usize, started: Instant) -> SearchReport {
+    let files: std::collections::HashSet<&Path> =
+        entries.iter().map(|e| e.source.path.as_path()).collect();
+    SearchReport {
+        files_scanned: files.len(),
+        matches,
+        elapsed: started.elapsed(),
+        ..SearchReport::default()
+    }
+}
+
+fn run_cache_clean(args: CacheCleanArgs, quiet: bool) -> Result<()> {
+    if args.dry_run {
+        return run_cache_clean_dry_run(args, quiet);
+    }
+
+    #[cfg(feature = "hpindex")]
+    if args.use_index {
+        return run_cache_clean_index(args, quiet);
+    }
+
+    #[cfg(not(feature = "hpindex"))]
+    if args.use_index {
+        return Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ));
+    }
+
+    let cache_path = resolve_cache_path(&args.cache_path, args.profile)?;
+    let entries = load_cache(&cache_path)?;
+    let before = entries.len();
+    let mut pruned = prune_missing(entries);
+    let missing = before - pruned.len();
+    if args.verify_hash {
+        pruned = prune_changed(pruned);
+    }
+    let after = pruned.len();
+
+    write_cache(&cache_path, &pruned)?;
+    if !quiet {
+        eprintln!(
+            "{}",
+            clean_summary(missing, before, after, args.verify_hash, false)
+        );
+    }
+    Ok(())
+}
+
+/// `cache clean --dry-run`: print the entries a clean would remove, with
+/// `missing` or `changed` as the reason, and leave the cache or index as
+/// it is.
+fn run_cache_clean_dry_run(args: CacheCleanArgs, quiet: bool) -> Result<()> {
+    let entries = if args.use_index {
+        #[cfg(feature = "hpindex")]
+        {
+            let index_path = resolve_index_path(&args.index_path, args.profile)?;
+            if !index_path.is_dir() {
+                return Err(anyhow!("no index at {}", index_path.display()));
+            }
+            open_index_ro(&index_path)?.reader()?.list_all()?
+        }
+        #[cfg(not(feature = "hpindex"))]
+        return Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ));
+    } else {
+        load_cache(&resolve_cache_path(&args.cache_path, args.profile)?)?
+    };
+
+    // Run the same pruning a real clean does, on a copy.
+    let face = |entry: &TypgFontFaceMatch| (entry.source.path.clone(), entry.source.ttc_index);
+    let present = prune_missing(entries.clone());
+    let present_faces: HashSet<_> = present.iter().map(face).collect();
+    let kept = if args.verify_hash {
+        prune_changed(present)
+    } else {
+        present
+    };
+    let kept_faces: HashSet<_> = kept.iter().map(face).collect();
+
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    for entry in entries
+        .iter()
+        .filter(|entry| !kept_faces.contains(&face(entry)))
+    {
+        let reason = if present_faces.contains(&face(entry)) {
+            "changed"
+        } else {
+            "missing"
+        };
+        writeln!(handle, "{}\t{reason}", entry.source.path_with_index())?;
+    }
+
+    if !quiet {
+        let missing = entries.len() - present_faces.len();
+        eprintln!(
+            "{}",
+            clean_summary(missing, entries.len(), kept.len(), args.verify_hash, true)
+        );
+    }
+    Ok(())
+}
+
+/// `cache clean` summary line; `changed` counts the `--verify-hash` removals.
+/// With `dry_run` it says what would be removed.
+fn clean_summary(
+    missing: usize,
+    before: usize,
+    after: usize,
+    verify_hash: bool,
+    dry_run: bool,
+) -> String {
+    let removed = before.saturating_sub(after);
+    let verb = if dry_run { "would remove" } else { "removed" };
+    if verify_hash {
+        format!(
+            "{verb} {} missing and {} changed entries ({} → {})",
+            missing,
+            removed.saturating_sub(missing),
+            before,
+            after
+        )
+    } else {
+        format!(
+            "{verb} {} missing entries ({} → {})",
+            removed, before, after
+        )
+    }
+}
+
+/// `cache remove`: drop the entries matching a query, printing each one.
+/// With `--dry-run` only the list is printed.
+fn run_cache_remove(args: CacheRemoveArgs, quiet: bool) -> Result<()> {
+    let query = build_query_from_parts(
+        &args.axes,
+        &args.features,
+        &args.scripts,
+        &args.langs,
+        &args.tables,
+        &args.name_patterns,
+        args.name_match.into(),
+        &args.creator_patterns,
+        &args.license_patterns,
+        &args.codepoints,
+        &args.text,
+        &args.unicode_blocks,
+        &args.coverage,
+        args.variable,
+        &args.weight,
+        &args.width,
+        &args.family_class,
+        &args.embedding,
+        args.min_glyphs,
+        args.min_axes,
+        args.max_axes,
+        &args.vendors,
+        &args.instances,
+        args.only_collections,
+        args.collection_index,
+        args.unicode_cmap_only,
+        &args.query_expr,
+        args.preset.map(Preset::from),
+    )
+    .and_then(|query| apply_exclusions(query, &args.exclude))?;
+
+    #[cfg(feature = "hpindex")]
+    if args.use_index {
+        return run_cache_remove_index(args, &query, quiet);
+    }
+
+    #[cfg(not(feature = "hpindex"))]
+    if args.use_index {
+        return Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ));
+    }
+
+    let cache_path = resolve_cache_path(&args.cache_path, args.profile)?;
+    let entries = load_cache(&cache_path)?;
+    let before = entries.len();
+    let (doomed, kept): (Vec<_>, Vec<_>) = entries
+        .into_iter()
+        .partition(|entry| query.matches_source(&entry.source) && query.matches(&entry.metadata));
+
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    for entry in &doomed {
+        writeln!(handle, "{}", entry.source.path_with_index())?;
+    }
+    if !args.dry_run && !doomed.is_empty() {
+        write_cache(&cache_path, &kept)?;
+    }
+    if !quiet {
+        eprintln!("{}", remove_summary(doomed.len(), before, args.dry_run));
+    }
+    Ok(())
+}
+
+/// `cache remove` summary line.
+fn remove_summary(removed: usize, before: usize, dry_run: bool) -> String {
+    let verb = if dry_run { "would remove" } else { "removed" };
+    format!(
+        "{verb} {} matching entries ({} → {})",
+        removed,
+        before,
+        before.saturating_sub(removed)
+    )
+}
+
+fn run_cache_verify(args: CacheVerifyArgs, quiet: bool) -> Result<()> {
+    // With --index, the databases are also cross-checked; each row is a
+    // kind of problem, its count and the IDs or tags involved.
+    let (entries, index_problems): (_, Vec<(&str, u64, String)>) = if args.use_index {
+        #[cfg(feature = "hpindex")]
+        {
+            let index_path = resolve_index_path(&args.index_path, args.profile)?;
+            let index = open_index_ro(&index_path)?;
+            let entries = index.reader()?.list_all()?;
+            (entries, index_check_rows(&index.check()?))
+        }
+        #[cfg(not(feature = "hpindex"))]
+        return Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ));
+    } else {
+        (
+            load_cache(&resolve_cache_path(&args.cache_path, args.profile)?)?,
+            Vec::new(),
+        )
+    };
+
+    let checks = verify_files(&entries, args.hash);
+    let problems: Vec<&FileCheck> = checks
+        .iter()
+        .filter(|check| check.status.is_problem())
+        .collect();
+
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    if args.json {
+        let rendered: Vec<serde_json::Value> = problems
+            .iter()
+            .map(|check| {
+                serde_json::json!({
+                    "path": check.path.display().to_string(),
+                    "status": check.status.label(),
+                })
+            })
+            .chain(index_problems.iter().map(|(label, count, detail)| {
+                serde_json::json!({
+                    "problem": label,
+                    "count": count,
+                    "detail": detail,
+                })
+            }))
+            .collect();
+        writeln!(handle, "{}", serde_json::to_string_pretty(&rendered)?)?;
+    } else {
+        for check in &problems {
+            writeln!(handle, "{}\t{}", check.path.display(), check.status.label())?;
+        }
+        for (label, count, detail) in &index_problems {
+            writeln!(handle, "{label}\t{count}\t{detail}")?;
+        }
+    }
+
+    let index_problem_count: u64 = index_problems.iter().map(|(_, count, _)| count).sum();
+    if !quiet {
+        let mut summary = verify_summary(&checks, args.hash);
+        if args.use_index {
+            summary.push_str(&format!("; {index_problem_count} index problem(s)"));
+        }
+        eprintln!("{summary}");
+    }
+    let mut failures = Vec::new();
+    if !problems.is_empty() {
+        failures.push(format!(
+            "{} of {} cached file(s) failed verification",
+            problems.len(),
+            checks.len()
+        ));
+    }
+    if index_problem_count > 0 {
+        failures.push(format!(
+            "index has {index_problem_count} problem(s); run `typg cache fsck --index --repair` to fix them"
+        ));
+    }
+    if !failures.is_empty() {
+        return Err(anyhow!(failures.join("; ")));
+    }
+    Ok(())
+}
+
+fn run_cache_fsck(args: CacheFsckArgs, quiet: bool) -> Result<()> {
+    if !args.use_index {
+        return Err(anyhow!(
+            "cache fsck checks the LMDB index; pass --index (use cache verify for the JSON cache)"
+        ));
+    }
+
+    #[cfg(feature = "hpindex")]
+    return run_cache_fsck_index(args, quiet);
+
+    #[cfg(not(feature = "hpindex"))]
+    {
+        let _ = quiet;
+        Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ))
+    }
+}
+
+fn run_cache_compact(args: CacheCompactArgs, quiet: bool) -> Result<()> {
+    if !args.use_index {
+        return Err(anyhow!(
+            "cache compact rewrites the LMDB index; pass --index (the JSON cache has no free space to reclaim)"
+        ));
+    }
+
+    #[cfg(feature = "hpindex")]
+    return run_cache_compact_index(args, quiet);
+
+    #[cfg(not(feature = "hpindex"))]
+    {
+        let _ = quiet;
+        Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ))
+    }
+}
+
+fn run_cache_export(args: CacheExportArgs, quiet: bool) -> Result<()> {
+    if !args.use_index {
+        return Err(anyhow!(
+            "cache export snapshots the LMDB index; pass --index (the JSON cache file is already portable)"
+        ));
+    }
+
+    #[cfg(feature = "hpindex")]
+    return run_cache_export_index(args, quiet);
+
+    #[cfg(not(feature = "hpindex"))]
+    {
+        let _ = quiet;
+        Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ))
+    }
+}
+
+fn run_cache_import(args: CacheImportArgs, quiet: bool) -> Result<()> {
+    if !args.use_index {
+        return Err(anyhow!(
+            "cache import loads into the LMDB index; pass --index (copy a JSON cache file as it is)"
+        ));
+    }
+
+    #[cfg(feature = "hpindex")]
+    return run_cache_import_index(args, quiet);
+
+    #[cfg(not(feature = "hpindex"))]
+    {
+        let _ = quiet;
+        Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ))
+    }
+}
+
+fn run_cache_migrate(args: CacheMigrateArgs, quiet: bool) -> Result<()> {
+    #[cfg(feature = "hpindex")]
+    return run_cache_migrate_index(args, quiet);
+
+    #[cfg(not(feature = "hpindex"))]
+    {
+        let _ = (args, quiet);
+        Err(anyhow!(
+            "cache migrate requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ))
+    }
+}
+
+/// Parse a `--rebase OLD=NEW` path pair.
+fn parse_rebase(raw: &str) -> Result<(PathBuf, PathBuf), String> {
+    match raw.split_once('=') {
+        Some((old, new)) if !old.is_empty() && !new.is_empty() => {
+            Ok((PathBuf::from(old), PathBuf::from(new)))
+        }
+        _ => Err(format!("expected OLD=NEW, got {raw:?}")),
+    }
+}
+
+/// What `cache verify` found for one cached file.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum FileStatus {
+    /// Present, and with `--hash` its content matches the recorded hash.
+    Ok,
+    /// No longer on disk.
+    Missing,
+    /// Present, but its content no longer hashes to the recorded value.
+    Modified,
+    /// Present but could not be read for hashing.
+    Unreadable,
+    /// Present, but no hash was recorded to compare against.
+    Unhashed,
+}
+
+impl FileStatus {
+    fn label(self) -> &'static str {
+        match self {
+            FileStatus::Ok => "ok",
+            FileStatus::Missing => "missing",
+            FileStatus::Modified => "modified",
+            FileStatus::Unreadable => "unreadable",
+            FileStatus::Unhashed => "unhashed",
+        }
+    }
+
+    fn is_problem(self) -> bool {
+        matches!(
+            self,
+            FileStatus::Missing | FileStatus::Modified | FileStatus::Unreadable
+        )
+    }
+}
+
+#[derive(Debug)]
+struct FileCheck {
+    path: PathBuf,
+    status: FileStatus,
+}
+
+/// Check every distinct file behind `entries`, in path order.
+///
+/// Collections are checked once for all their faces. With `hash`, files
+/// are re-read in parallel and compared with the recorded `file_hash`.
+fn verify_files(entries: &[TypgFontFaceMatch], hash: bool) -> Vec<FileCheck> {
+    let files: BTreeMap<&Path, Option<u64>> = entries
+        .iter()
+        .map(|entry| (entry.source.path.as_path(), entry.metadata.file_hash))
+        .collect();
+    files
+        .into_par_iter()
+        .map(|(path, recorded)| {
+            let status = if !path.exists() {
+                FileStatus::Missing
+            } else if !hash {
+                FileStatus::Ok
+            } else {
+                match (recorded, hash_file(path)) {
+                    (None, _) => FileStatus::Unhashed,
+                    (Some(_), Err(_)) => FileStatus::Unreadable,
+                    (Some(recorded), Ok(current)) if recorded == current => FileStatus::Ok,
+                    (Some(_), Ok(_)) => FileStatus::Modified,
+                }
+            };
+            FileCheck {
+                path: path.to_path_buf(),
+                status,
+            }
+        })
+        .collect()
 }
 
-fn dedup_chars(cps: &mut Vec<char>) {
-    cps.sort();
-    cps.dedup();
-}
+/// `cache verify` summary line, e.g. `verified 120 files: 2 missing, 1 modified`.
+fn verify_summary(checks: &[FileCheck], hash: bool) -> String {
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let mut summary = format!(
+        "verified {} files: {} missing",
+        checks.len(),
+        count(FileStatus::Missing)
+    );
+    if hash {
+        summary.push_str(&format!(
+            ", {} modified, {} unreadable",
+            count(FileStatus::Modified),
+            count(FileStatus::Unreadable)
+        ));
+        let unhashed = count(FileStatus::Unhashed);
+        if unhashed > 0 {
+            summary.push_str(&format!(" ({unhashed} without a recorded hash)"));
+        }
+    }
+    summary
+}
+
+/// `cache refresh`: rescan the roots `cache add` recorded, each with the
+/// options it was added with. Changed fonts are read again and fonts that
+/// are gone, or under a root that is gone, are dropped.
+fn run_cache_refresh(args: CacheRefreshArgs, quiet: bool) -> Result<()> {
+    if matches!(args.jobs, Some(0)) {
+        return Err(anyhow!("--jobs must be at least 1"));
+    }
+    let store = sync_store(
+        args.use_index,
+        &args.cache_path,
+        &args.index_path,
+        args.profile,
+    )?;
+    let roots = store.roots()?;
+    if roots.is_empty() {
+        return Err(anyhow!(
+            "no roots recorded in the cache or index; add folders with `typg cache add PATH`"
+        ));
+    }
+
+    // Roots recorded with the same options are rescanned together.
+    let mut groups: Vec<(ScanRoot, Vec<PathBuf>)> = Vec::new();
+    for root in &roots {
+        let settings = ScanRoot {
+            path: PathBuf::new(),
+            ..root.clone()
+        };
+        match groups.iter_mut().find(|(known, _)| *known == settings) {
+            Some((_, paths)) => paths.push(root.path.clone()),
+            None => groups.push((settings, vec![root.path.clone()])),
+        }
+        if !root.path.exists() && !quiet {
+            eprintln!(
+                "warning: {} is gone; dropping the fonts recorded under it",
+                root.path.display()
+            );
+        }
+    }
+
+    let mut syncer = watch::Syncer::new(store, SearchOptions::default());
+    let mut stats = watch::SyncStats::default();
+    for (settings, paths) in groups {
+        syncer.set_options(SearchOptions {
+            jobs: args.jobs,
+            ..settings.options()
+        });
+        let pass = syncer.initial_sync(&paths)?;
+        stats.indexed += pass.indexed;
+        stats.removed += pass.removed;
+    }
+    if !quiet {
+        let noun = if roots.len() == 1 { "root" } else { "roots" };
+        eprintln!("refreshed {} {noun}: {stats}", roots.len());
+    }
+    Ok(())
+}
+
+fn run_cache_info(args: CacheInfoArgs) -> Result<()> {
+    #[cfg(feature = "hpindex")]
+    if args.use_index {
+        return run_cache_info_index(args);
+    }
+
+    #[cfg(not(feature = "hpindex"))]
+    if args.use_index {
+        return Err(anyhow!(
+            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+        ));
+    }
 
-fn compile_patterns(patterns: &[String]) -> Result<Vec<Regex>> {
-    patterns
-        .iter()
-        .map(|p| Regex::new(p).with_context(|| format!("invalid regex: {p}")))
-        .collect()
-}
+    let cache_path = resolve_cache_path(&args.cache_path, args.profile)?;
 
-fn parse_codepoints(raw: &[String]) -> Result<Vec<char>> {
-    let mut cps = Vec::new();
-    for chunk in raw {
-        cps.extend(parse_codepoint_list(chunk)?);
+    if !cache_path.exists() {
+        if args.json {
+            println!(r#"{{"exists":false,"path":"{}"}}"#, cache_path.display());
+        } else {
+            println!("Cache does not exist at {}", cache_path.display());
+        }
+        return Ok(());
     }
-    Ok(cps)
-}
 
-fn parse_optional_range(raw: &Option<String>) -> Result<Option<RangeInclusive<u16>>> {
-    match raw {
-        Some(value) => Ok(Some(parse_u16_range(value)?)),
-        None => Ok(None),
+    let entries = load_cache(&cache_path)?;
+    let roots = load_roots(&cache_path)?;
+    let file_meta = fs::metadata(&cache_path)?;
+    let info = StoreInfo {
+        label: "Cache",
+        kind: "json",
+        path: &cache_path,
+        entries: &entries,
+        roots: &roots,
+        collection: collections().first().map(String::as_str),
+        collections: store_collections(&default_cache_path(&args.cache_path, args.profile)?),
+        size_bytes: file_meta.len(),
+        updated: file_meta.modified().ok(),
+    };
+
+    if args.json {
+        let json = with_weight_stats(info.to_json(), args.weights.then(|| weight_stats(&entries)))?;
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        let mut stdout = io::stdout().lock();
+        info.write_summary(&mut stdout)?;
+        info.write_roots(&mut stdout)?;
+        info.write_directories(&mut stdout)?;
+        if args.weights {
+            writeln!(stdout)?;
+            write_weight_stats(&weight_stats(&entries), &mut stdout)?;
+        }
     }
+
+    Ok(())
 }
 
-fn parse_optional_family_class(raw: &Option<String>) -> Result<Option<FamilyClassFilter>> {
-    match raw {
-        Some(value) => Ok(Some(parse_family_class(value)?)),
-        None => Ok(None),
-    }
+/// What `cache info` reports about a JSON cache or an LMDB index.
+struct StoreInfo<'a> {
+    /// "Cache" or "Index", for the text output.
+    label: &'static str,
+    /// `json` or `lmdb`.
+    kind: &'static str,
+    path: &'a Path,
+    entries: &'a [TypgFontFaceMatch],
+    /// Roots recorded by `cache add`.
+    roots: &'a [ScanRoot],
+    /// The `--collection` this store belongs to, if one was named.
+    collection: Option<&'a str>,
+    /// Named collections beside the default store; listed when reporting
+    /// the default store itself.
+    collections: Vec<String>,
+    size_bytes: u64,
+    /// When the store was last written.
+    updated: Option<std::time::SystemTime>,
 }
 
-fn gather_paths(
-    raw_paths: &[PathBuf],
-    read_stdin: bool,
-    include_system: bool,
-    mut stdin: impl BufRead,
-) -> Result<Vec<PathBuf>> {
-    let mut paths = Vec::new();
+/// Directories listed by `cache info` before the rest are summarized.
+const INFO_TOP_DIRECTORIES: usize = 10;
 
-    if read_stdin {
-        paths.extend(read_paths_from(&mut stdin)?);
+impl StoreInfo<'_> {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "exists": true,
+            "path": self.path.display().to_string(),
+            "type": self.kind,
+            "entries": self.entries.len(),
+            "size_bytes": self.size_bytes,
+            "size": human_bytes(self.size_bytes),
+            "updated": self.updated.map(rfc3339_utc),
+            "roots": self.roots,
+            "directories": directory_counts(self.entries),
+            "collection": self.collection,
+            "collections": self.collections,
+        })
     }
 
-    for path in raw_paths {
-        if path == Path::new("-") {
-            paths.extend(read_paths_from(&mut stdin)?);
-        } else {
-            paths.push(path.clone());
+    /// The roots `cache add` recorded, with the options that differ from
+    /// the defaults.
+    fn write_roots(&self, mut w: impl Write) -> Result<()> {
+        if self.roots.is_empty() {
+            return Ok(());
         }
+        writeln!(w, "Roots:   {}", thousands(self.roots.len() as u64))?;
+        for root in self.roots {
+            let mut options = Vec::new();
+            if root.follow_symlinks {
+                options.push("follow symlinks".to_string());
+            }
+            if root.include_hidden {
+                options.push("hidden".to_string());
+            }
+            if let Some(ids) = &root.name_ids {
+                let ids: Vec<String> = ids.iter().map(u16::to_string).collect();
+                options.push(format!("name IDs {}", ids.join(",")));
+            }
+            if options.is_empty() {
+                writeln!(w, "  {}", root.path.display())?;
+            } else {
+                writeln!(w, "  {} ({})", root.path.display(), options.join(", "))?;
+            }
+        }
+        Ok(())
     }
 
-    if include_system {
-        paths.extend(system_font_roots()?);
+    /// Location, type, count, size and update time.
+    fn write_summary(&self, mut w: impl Write) -> Result<()> {
+        writeln!(
+            w,
+            "{:<9}{}",
+            format!("{}:", self.label),
+            self.path.display()
+        )?;
+        writeln!(w, "Type:    {}", self.kind.to_uppercase())?;
+        writeln!(w, "Fonts:   {}", thousands(self.entries.len() as u64))?;
+        writeln!(
+            w,
+            "Size:    {} ({} bytes)",
+            human_bytes(self.size_bytes),
+            thousands(self.size_bytes)
+        )?;
+        if let Some(updated) = self.updated {
+            writeln!(w, "Updated: {}", rfc3339_utc(updated))?;
+        }
+        if let Some(collection) = self.collection {
+            writeln!(w, "Collection: {collection}")?;
+        }
+        if !self.collections.is_empty() {
+            writeln!(w, "Collections: {}", self.collections.join(", "))?;
+        }
+        Ok(())
     }
 
-    if paths.is_empty() {
-        return Err(anyhow!("no search paths provided"));
+    /// The directories holding the most faces.
+    fn write_directories(&self, mut w: impl Write) -> Result<()> {
+        let mut dirs: Vec<(String, usize)> = directory_counts(self.entries).into_iter().collect();
+        if dirs.is_empty() {
+            return Ok(());
+        }
+        dirs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let width = thousands(dirs[0].1 as u64).len();
+        writeln!(w, "Directories: {}", thousands(dirs.len() as u64))?;
+        for (dir, count) in dirs.iter().take(INFO_TOP_DIRECTORIES) {
+            writeln!(w, "  {:>width$}  {dir}", thousands(*count as u64))?;
+        }
+        if dirs.len() > INFO_TOP_DIRECTORIES {
+            let rest: usize = dirs[INFO_TOP_DIRECTORIES..].iter().map(|d| d.1).sum();
+            writeln!(
+                w,
+                "  {:>width$}  in {} more directories",
+                thousands(rest as u64),
+                thousands((dirs.len() - INFO_TOP_DIRECTORIES) as u64)
+            )?;
+        }
+        Ok(())
     }
+}
 
-    Ok(paths)
+/// The named collections beside the default store `store`, when no
+/// `--collection` was given; `cache info` lists them.
+fn store_collections(store: &Path) -> Vec<String> {
+    if collections().is_empty() {
+        list_collections(store)
+    } else {
+        Vec::new()
+    }
 }
 
-fn read_paths_from(reader: &mut impl BufRead) -> Result<Vec<PathBuf>> {
-    let mut buf = String::new();
-    let mut paths = Vec::new();
+/// Faces per directory holding the font files. Recorded roots can be few
+/// and broad, so the files' parent directories are counted instead.
+fn directory_counts(entries: &[TypgFontFaceMatch]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for entry in entries {
+        let dir = entry
+            .source
+            .path
+            .parent()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        *counts.entry(dir).or_insert(0) += 1;
+    }
+    counts
+}
 
-    loop {
-        buf.clear();
-        let read = reader.read_line(&mut buf)?;
-        if read == 0 {
-            break;
-        }
+/// A byte count in binary units with one decimal: `512 B`, `3.4 MB`.
+fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
 
-        let trimmed = buf.trim();
-        if !trimmed.is_empty() {
-            paths.push(PathBuf::from(trimmed));
+/// A count with thousands separators: `12,345`.
+fn thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
         }
+        out.push(c);
     }
-
-    Ok(paths)
+    out
 }
 
-fn system_font_roots() -> Result<Vec<PathBuf>> {
-    if let Ok(raw) = env::var("TYPOG_SYSTEM_FONT_DIRS") {
-        let mut overrides: Vec<PathBuf> = raw
-            .split([':', ';'])
-            .filter(|s| !s.is_empty())
-            .map(PathBuf::from)
-            .filter(|p| p.exists())
-            .collect();
+fn run_cache_changed(args: CacheChangedArgs) -> Result<()> {
+    if matches!(args.jobs, Some(0)) {
+        return Err(anyhow!("--jobs must be at least 1"));
+    }
 
-        overrides.sort();
-        overrides.dedup();
+    let stdin = io::stdin();
+    let paths = gather_paths(
+        &args.paths,
+        args.stdin_paths,
+        args.system_fonts,
+        stdin.lock(),
+    )?;
 
-        return if overrides.is_empty() {
-            Err(anyhow!("TYPOG_SYSTEM_FONT_DIRS is set but no paths exist"))
-        } else {
-            Ok(overrides)
-        };
-    }
+    let cache_path = resolve_cache_path(&args.cache_path, args.profile)?;
+    let cached = if cache_path.exists() {
+        load_cache(&cache_path)?
+    } else {
+        Vec::new()
+    };
 
-    let mut candidates: Vec<PathBuf> = Vec::new();
+    let opts = SearchOptions {
+        follow_symlinks: args.follow_symlinks,
+        include_hidden: args.hidden,
+        jobs: args.jobs,
+        name_ids: name_ids(&args.name_ids),
+        ..SearchOptions::default()
+    };
+    let scanned = search(&paths, &Query::new(), &opts)?;
+    let changes = changed_faces(&cached, &scanned);
 
-    #[cfg(target_os = "macos")]
-    {
-        candidates.push(PathBuf::from("/System/Library/Fonts"));
-        candidates.push(PathBuf::from("/Library/Fonts"));
-        if let Some(home) = env::var_os("HOME") {
-            candidates.push(PathBuf::from(home).join("Library/Fonts"));
-        }
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    write_changes(&changes, &mut handle, args.json, args.paths_only)
+}
+
+fn write_changes(
+    changes: &[FaceChange],
+    mut w: impl Write,
+    json: bool,
+    paths_only: bool,
+) -> Result<()> {
+    if json {
+        let rendered = serde_json::to_string_pretty(changes)?;
+        writeln!(w, "{rendered}")?;
+        return Ok(());
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        candidates.push(PathBuf::from("/usr/share/fonts"));
-        candidates.push(PathBuf::from("/usr/local/share/fonts"));
-        if let Some(home) = env::var_os("HOME") {
-            candidates.push(PathBuf::from(home).join(".local/share/fonts"));
+    for change in changes {
+        let path = change.source.path_with_index();
+        if paths_only {
+            writeln!(w, "{path}")?;
+        } else {
+            match change.status {
+                ChangeStatus::New => writeln!(w, "{path}\tnew")?,
+                ChangeStatus::Modified => writeln!(w, "{path}\t{}", change.details.join("; "))?,
+            }
         }
     }
+    Ok(())
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        if let Some(system_root) = env::var_os("SYSTEMROOT") {
-            candidates.push(PathBuf::from(system_root).join("Fonts"));
+/// Print every face of one font; `-` reads the font bytes from `stdin`.
+fn run_show(args: ShowArgs, mut stdin: impl Read) -> Result<()> {
+    let data = if args.file == Path::new("-") {
+        let mut data = Vec::new();
+        stdin
+            .read_to_end(&mut data)
+            .context("reading font from stdin")?;
+        data
+    } else {
+        fs::read(&args.file).with_context(|| format!("reading {}", args.file.display()))?
+    };
+    let opts = SearchOptions {
+        name_ids: name_ids(&args.name_ids),
+        ..SearchOptions::default()
+    };
+    let faces = read_metadata(&data, &args.file, &opts)
+        .with_context(|| format!("parsing {}", args.file.display()))?;
+
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    let codepoints = args.codepoint_format.into();
+    if args.ndjson {
+        write_ndjson_with(&faces, &mut handle, codepoints)?;
+    } else if args.json {
+        write_json_pretty_with(&faces, &mut handle, codepoints)?;
+        writeln!(handle)?;
+    } else {
+        write_show(&faces, &mut handle)?;
+    }
+    Ok(())
+}
+
+/// Write each face as a `path#index` heading followed by one `LABEL  value`
+/// line per `--fields` column.
+fn write_show(faces: &[TypgFontFaceMatch], mut w: impl Write) -> Result<()> {
+    let fields: Vec<Field> = Field::value_variants()
+        .iter()
+        .copied()
+        .filter(|field| *field != Field::Path)
+        .collect();
+    let width = fields.iter().map(|f| f.header().len()).max().unwrap_or(0);
+    for (i, face) in faces.iter().enumerate() {
+        if i > 0 {
+            writeln!(w)?;
         }
-        if let Some(local_appdata) = env::var_os("LOCALAPPDATA") {
-            candidates.push(PathBuf::from(local_appdata).join("Microsoft/Windows/Fonts"));
+        writeln!(w, "{}", face.source.path_with_index())?;
+        for field in &fields {
+            writeln!(
+                w,
+                "  {:<width$}  {}",
+                field.header(),
+                field.render(face, true)
+            )?;
         }
     }
+    Ok(())
+}
 
-    candidates.retain(|p| p.exists());
-    candidates.sort();
-    candidates.dedup();
+/// Dump every face of one font in depth; `-` reads the font bytes from `stdin`.
+fn run_inspect(args: InspectArgs, mut stdin: impl Read) -> Result<()> {
+    let faces = if args.file == Path::new("-") {
+        let mut data = Vec::new();
+        stdin
+            .read_to_end(&mut data)
+            .context("reading font from stdin")?;
+        inspect_font(&data, &args.file).context("parsing font from stdin")?
+    } else {
+        inspect_file(&args.file)?
+    };
 
-    if candidates.is_empty() {
-        return Err(anyhow!(
-            "no system font directories found for this platform"
-        ));
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    if args.json {
+        serde_json::to_writer_pretty(&mut handle, &faces)?;
+        writeln!(handle)?;
+    } else {
+        write_inspection(&faces, &mut handle)?;
     }
-
-    Ok(candidates)
+    Ok(())
 }
 
-fn write_plain(
-    matches: &[TypgFontFaceMatch],
-    mut w: impl Write,
-    color: bool,
-    collections: bool,
-) -> Result<()> {
-    if collections {
-        for item in matches {
-            let rendered = render_path(item, color, true);
-            writeln!(w, "{rendered}")?;
+/// Write each face as a `path#index` heading followed by one indented
+/// section per part of the font.
+fn write_inspection(faces: &[FontInspection], mut w: impl Write) -> Result<()> {
+    for (i, face) in faces.iter().enumerate() {
+        if i > 0 {
+            writeln!(w)?;
         }
-    } else {
-        let mut seen = std::collections::HashSet::new();
-        for item in matches {
-            if seen.insert(item.source.path.clone()) {
-                let rendered = render_path(item, color, false);
-                writeln!(w, "{rendered}")?;
+        writeln!(w, "{}", face.source.path_with_index())?;
+
+        writeln!(w, "Names:")?;
+        for name in &face.names {
+            let language = name
+                .language
+                .clone()
+                .unwrap_or_else(|| format!("0x{:04X}", name.language_id));
+            writeln!(
+                w,
+                "  {:>5}  {}.{} {:<8}  {}",
+                name.name_id, name.platform_id, name.encoding_id, language, name.value
+            )?;
+        }
+
+        writeln!(w, "Tables:")?;
+        for table in &face.tables {
+            writeln!(
+                w,
+                "  {}  {:>10}  {:08X}",
+                table.tag, table.length, table.checksum
+            )?;
+        }
+
+        if !face.axes.is_empty() {
+            writeln!(w, "Axes:")?;
+            for axis in &face.axes {
+                writeln!(
+                    w,
+                    "  {}  {}..{}..{}  {}{}",
+                    axis.tag,
+                    axis.min,
+                    axis.default,
+                    axis.max,
+                    axis.name.as_deref().unwrap_or(""),
+                    if axis.hidden { " (hidden)" } else { "" }
+                )?;
+            }
+        }
+
+        if !face.layout.is_empty() {
+            writeln!(w, "Features:")?;
+            for lang_sys in &face.layout {
+                writeln!(
+                    w,
+                    "  {} {}/{:<4}  {}",
+                    lang_sys.table,
+                    lang_sys.script,
+                    lang_sys.language.as_deref().unwrap_or("dflt"),
+                    lang_sys.features.join(" ")
+                )?;
+            }
+        }
+
+        let cmap = &face.cmap;
+        let subtables: Vec<String> = cmap.subtables.iter().map(ToString::to_string).collect();
+        writeln!(w, "Cmap:")?;
+        writeln!(w, "  subtables   {}", subtables.join(" "))?;
+        writeln!(w, "  codepoints  {}", cmap.codepoints)?;
+        if !cmap.ranges.is_empty() {
+            writeln!(w, "  ranges      {}", cmap.ranges)?;
+        }
+        for (block, count) in &cmap.blocks {
+            writeln!(w, "  {count:>10}  {block}")?;
+        }
+
+        writeln!(w, "Metrics:")?;
+        if let Some(upem) = face.units_per_em {
+            writeln!(w, "  unitsPerEm      {upem}")?;
+        }
+        if let Some(os2) = &face.os2 {
+            writeln!(w, "  OS/2 version    {}", os2.version)?;
+            writeln!(w, "  usWeightClass   {}", os2.weight_class)?;
+            writeln!(w, "  usWidthClass    {}", os2.width_class)?;
+            writeln!(w, "  fsType          0x{:04X}", os2.fs_type)?;
+            writeln!(w, "  fsSelection     0x{:04X}", os2.fs_selection)?;
+            writeln!(w, "  achVendID       {}", os2.vendor_id)?;
+            writeln!(
+                w,
+                "  typo            {} / {} / {}",
+                os2.typo_ascender, os2.typo_descender, os2.typo_line_gap
+            )?;
+            writeln!(
+                w,
+                "  win             {} / {}",
+                os2.win_ascent, os2.win_descent
+            )?;
+            if let Some(x_height) = os2.x_height {
+                writeln!(w, "  sxHeight        {x_height}")?;
             }
+            if let Some(cap_height) = os2.cap_height {
+                writeln!(w, "  sCapHeight      {cap_height}")?;
+            }
+        }
+        if let Some(hhea) = &face.hhea {
+            writeln!(
+                w,
+                "  hhea            {} / {} / {}",
+                hhea.ascender, hhea.descender, hhea.line_gap
+            )?;
         }
     }
     Ok(())
 }
 
-fn write_paths(
-    matches: &[TypgFontFaceMatch],
-    mut w: impl Write,
-    collections: bool,
-) -> Result<()> {
-    if collections {
-        for item in matches {
-            writeln!(w, "{}", item.source.path_with_index())?;
-        }
+/// Add a `weights` key to a `cache info --json` object when stats were asked for.
+fn with_weight_stats(
+    mut info: serde_json::Value,
+    weights: Option<WeightStats>,
+) -> Result<serde_json::Value> {
+    if let Some(weights) = weights {
+        info["weights"] = serde_json::to_value(weights)?;
+    }
+    Ok(info)
+}
+
+/// Weight histogram for `cache info --weights`, one line per standard weight.
+fn write_weight_stats(stats: &WeightStats, mut w: impl Write) -> Result<()> {
+    let width = stats
+        .buckets
+        .iter()
+        .map(|b| b.faces)
+        .chain([stats.unknown])
+        .max()
+        .unwrap_or(0)
+        .to_string()
+        .len();
+    writeln!(w, "Weights:")?;
+    for bucket in &stats.buckets {
+        writeln!(
+            w,
+            "  {} {:<10}  {:>width$}",
+            bucket.weight, bucket.name, bucket.faces
+        )?;
+    }
+    if stats.unknown > 0 {
+        writeln!(w, "  {:<14}  {:>width$}", "unknown", stats.unknown)?;
+    }
+    if stats.families_without_bold.is_empty() {
+        writeln!(w, "Every family has a bold")?;
     } else {
-        let mut seen = std::collections::HashSet::new();
-        for item in matches {
-            if seen.insert(item.source.path.clone()) {
-                writeln!(w, "{}", item.source.path.display())?;
-            }
+        writeln!(
+            w,
+            "Families without bold ({}):",
+            stats.families_without_bold.len()
+        )?;
+        for family in &stats.families_without_bold {
+            writeln!(w, "  {family}")?;
         }
     }
     Ok(())
 }
 
-fn write_columns(
-    matches: &[TypgFontFaceMatch],
-    mut w: impl Write,
-    color: bool,
-    collections: bool,
-) -> Result<()> {
-    let mut rows: Vec<(String, String, String)> = matches
-        .iter()
-        .map(|m| {
-            let path = if collections {
-                m.source.path_with_index()
-            } else {
-                m.source.path.display().to_string()
-            };
-            let name = m
-                .metadata
-                .names
-                .first()
-                .cloned()
-                .unwrap_or_else(|| "(unnamed)".to_string());
+fn run_merge(args: MergeArgs, mut stdin: impl Read) -> Result<()> {
+    let mut sets = Vec::with_capacity(args.files.len());
+    let mut stdin_used = false;
+    for file in &args.files {
+        let data = if file == Path::new("-") {
+            if std::mem::replace(&mut stdin_used, true) {
+                return Err(anyhow!("stdin (-) can only be given once"));
+            }
+            let mut data = Vec::new();
+            stdin
+                .read_to_end(&mut data)
+                .context("reading results from stdin")?;
+            data
+        } else {
+            fs::read(file).with_context(|| format!("reading {}", file.display()))?
+        };
+        sets.push(parse_results(&data).with_context(|| format!("parsing {}", file.display()))?);
+    }
 
-            let tags = format!(
-                "axes:{:<2} feats:{:<2} scripts:{:<2} tables:{:<2}{}",
-                m.metadata.axis_tags.len(),
-                m.metadata.feature_tags.len(),
-                m.metadata.script_tags.len(),
-                m.metadata.table_tags.len(),
-                if m.metadata.is_variable { " var" } else { "" },
-            );
+    let merged = merge_results(sets, args.dedupe);
+    let output = OutputFormat::from_output(&args.output);
+    write_results(&merged, &Query::new(), None, false, &output)
+}
 
-            (path, name, tags)
-        })
+/// Pick the face of `--family` that CSS font matching would use.
+fn run_select(args: SelectArgs, stdin: impl BufRead) -> Result<()> {
+    let paths = gather_paths(&args.roots, args.stdin_paths, args.system_fonts, stdin)?;
+    let family = args.family.to_lowercase();
+    let faces: Vec<TypgFontFaceMatch> = search(&paths, &Query::new(), &SearchOptions::default())?
+        .into_iter()
+        .filter(|face| family_key(&face.metadata) == family)
         .collect();
+    let chosen = nearest(&faces, args.weight, args.width, args.italic)
+        .ok_or_else(|| anyhow!("no faces of family \"{}\" found", args.family))?;
+
+    let output = OutputFormat::from_output(&args.output).with_roots(paths);
+    write_results(
+        std::slice::from_ref(chosen),
+        &Query::new(),
+        None,
+        false,
+        &output,
+    )
+}
 
-    let path_width = rows
-        .iter()
-        .map(|r| r.0.len())
-        .max()
-        .unwrap_or(0)
-        .clamp(0, 120);
-    let name_width = rows
-        .iter()
-        .map(|r| r.1.len())
-        .max()
-        .unwrap_or(0)
-        .clamp(0, 80);
-
-    for (path, name, tags) in rows.drain(..) {
-        let padded_path = format!("{:<path_width$}", path);
-        let padded_name = format!("{:<name_width$}", name);
-        let rendered_path = apply_color(&padded_path, color, AnsiColor::Cyan);
-        let rendered_name = apply_color(&padded_name, color, AnsiColor::Yellow);
-        let rendered_tags = apply_color(&tags, color, AnsiColor::Green);
+fn run_bench(args: BenchArgs, stdin: impl BufRead, quiet: bool) -> Result<()> {
+    if matches!(args.jobs, Some(0)) {
+        return Err(anyhow!("--jobs must be at least 1"));
+    }
+    let paths = gather_paths(&args.paths, args.stdin_paths, args.system_fonts, stdin)?;
+    let opts = SearchOptions {
+        follow_symlinks: args.follow_symlinks,
+        include_hidden: args.hidden,
+        jobs: args.jobs,
+        ..SearchOptions::default()
+    };
 
-        writeln!(w, "{rendered_path}  {rendered_name}  {rendered_tags}")?;
+    let mut runs = Vec::new();
+    for pass in 1..=args.runs {
+        let run = typg_core::bench::run(&paths, &opts)?;
+        if !quiet && !args.json {
+            eprintln!(
+                "pass {pass}: {:.2}s",
+                run.discover_seconds + run.scan_seconds
+            );
+        }
+        runs.push(run);
     }
 
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    if args.json {
+        let queries: Vec<&str> = typg_core::bench::standard_queries()
+            .into_iter()
+            .map(|(label, _)| label)
+            .collect();
+        let json = serde_json::json!({ "queries": queries, "runs": runs });
+        writeln!(out, "{}", serde_json::to_string_pretty(&json)?)?;
+    } else {
+        write_bench(&runs, &mut out)?;
+    }
     Ok(())
 }
 
-#[derive(Copy, Clone)]
-enum AnsiColor {
-    Cyan,
-    Yellow,
-    Green,
+/// Print bench passes as a table, one row per pass, then the best pass.
+fn write_bench(runs: &[typg_core::bench::BenchRun], mut out: impl Write) -> Result<()> {
+    writeln!(
+        out,
+        "{:<5}{:>8}{:>8}{:>10}{:>9}{:>10}{:>9}{:>10}{:>9}{:>9}{:>9}",
+        "pass",
+        "files",
+        "faces",
+        "MB",
+        "scan",
+        "fonts/s",
+        "MB/s",
+        "discover",
+        "read",
+        "parse",
+        "match"
+    )?;
+    for (pass, run) in runs.iter().enumerate() {
+        writeln!(
+            out,
+            "{:<5}{:>8}{:>8}{:>10.1}{:>8.2}s{:>10.1}{:>9.1}{:>9.3}s{:>8.2}s{:>8.2}s{:>8.2}s",
+            pass + 1,
+            run.files,
+            run.faces,
+            run.bytes as f64 / 1e6,
+            run.scan_seconds,
+            run.fonts_per_second,
+            run.mb_per_second,
+            run.discover_seconds,
+            run.read_seconds,
+            run.parse_seconds,
+            run.match_seconds,
+        )?;
+    }
+    if let Some((pass, best)) = runs
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.fonts_per_second.total_cmp(&b.fonts_per_second))
+    {
+        let threads = if best.threads == 1 {
+            "thread"
+        } else {
+            "threads"
+        };
+        write!(
+            out,
+            "best: pass {}, {:.1} fonts/s, {:.1} MB/s; read, parse and match summed over {} {threads}",
+            pass + 1,
+            best.fonts_per_second,
+            best.mb_per_second,
+            best.threads,
+        )?;
+        if best.errors > 0 {
+            write!(out, "; {} files unreadable", best.errors)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
 }
 
-fn apply_color(text: &str, color: bool, code: AnsiColor) -> String {
-    if !color {
-        return text.to_string();
+/// A CSS `font-weight` number, 1 to 1000.
+fn parse_css_weight(raw: &str) -> Result<f32, String> {
+    match raw.trim().parse::<f32>() {
+        Ok(weight) if (1.0..=1000.0).contains(&weight) => Ok(weight),
+        _ => Err(format!("expected a weight from 1 to 1000, got {raw:?}")),
     }
-
-    let code_str = match code {
-        AnsiColor::Cyan => "36",
-        AnsiColor::Yellow => "33",
-        AnsiColor::Green => "32",
-    };
-
-    format!("\u{1b}[{}m{}\u{1b}[0m", code_str, text)
 }
 
-fn render_path(item: &TypgFontFaceMatch, color: bool, collections: bool) -> String {
-    let rendered = if collections {
-        item.source.path_with_index()
-    } else {
-        item.source.path.display().to_string()
-    };
-    apply_color(&rendered, color, AnsiColor::Cyan)
+/// A CSS `font-stretch` percentage, with or without the `%`.
+fn parse_css_width(raw: &str) -> Result<f32, String> {
+    let trimmed = raw.trim();
+    match trimmed.strip_suffix('%').unwrap_or(trimmed).parse::<f32>() {
+        Ok(width) if width > 0.0 => Ok(width),
+        _ => Err(format!("expected a positive percentage, got {raw:?}")),
+    }
 }
 
-fn run_cache_add(args: CacheAddArgs, quiet: bool) -> Result<()> {
-    if matches!(args.jobs, Some(0)) {
-        return Err(anyhow!("--jobs must be at least 1"));
+/// Parse saved results: a JSON array (`--json`, caches) or NDJSON.
+fn parse_results(data: &[u8]) -> Result<Vec<TypgFontFaceMatch>> {
+    if let Ok(entries) = serde_json::from_slice(data) {
+        return Ok(entries);
     }
+    Deserializer::from_slice(data)
+        .into_iter::<TypgFontFaceMatch>()
+        .collect::<Result<_, _>>()
+        .context("expected a JSON array or NDJSON of results")
+}
 
-    #[cfg(feature = "hpindex")]
-    if args.use_index {
-        return run_cache_add_index(args, quiet);
+/// Union result sets in order, optionally keeping one entry per face.
+///
+/// By path this is the cache's own merge (later sets win, sorted by path);
+/// by font ID the first occurrence wins.
+fn merge_results(
+    sets: Vec<Vec<TypgFontFaceMatch>>,
+    dedupe: Option<DedupeKey>,
+) -> Vec<TypgFontFaceMatch> {
+    let all = sets.into_iter().flatten();
+    match dedupe {
+        None => all.collect(),
+        Some(DedupeKey::Path) => merge_entries(Vec::new(), all.collect()),
+        Some(DedupeKey::Id) => {
+            let mut seen = HashSet::new();
+            let mut merged: Vec<TypgFontFaceMatch> = all
+                .filter(|entry| seen.insert(entry.metadata.fingerprint()))
+                .collect();
+            sort_entries(&mut merged);
+            merged
+        }
     }
+}
 
-    #[cfg(not(feature = "hpindex"))]
-    if args.use_index {
-        return Err(anyhow!(
-            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
-        ));
+fn run_validate(args: ValidateArgs) -> Result<()> {
+    if matches!(args.jobs, Some(0)) {
+        return Err(anyhow!("--jobs must be at least 1"));
     }
 
     let stdin = io::stdin();
@@ -935,294 +5431,336 @@ fn run_cache_add(args: CacheAddArgs, quiet: bool) -> Result<()> {
 
     let opts = SearchOptions {
         follow_symlinks: args.follow_symlinks,
+        include_hidden: args.hidden,
         jobs: args.jobs,
+        ..SearchOptions::default()
     };
-    let additions = search(&paths, &Query::new(), &opts)?;
-
-    let cache_path = resolve_cache_path(&args.cache_path)?;
-    let existing = if cache_path.exists() {
-        load_cache(&cache_path)?
-    } else {
-        Vec::new()
+    if args.duplicates {
+        return run_validate_duplicates(&paths, &opts, &args);
+    }
+    let profile = match args.profile {
+        ValidateProfile::FontbakeryLite => ValidationProfile::FontbakeryLite,
+        ValidateProfile::Names => ValidationProfile::Names,
     };
+    let reports = validate(&paths, profile, &opts)?;
 
-    let merged = merge_entries(existing, additions);
-    write_cache(&cache_path, &merged)?;
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    write_reports(&reports, &mut handle, args.json, args.ndjson)?;
 
-    if !quiet {
-        eprintln!(
-            "cached {} font faces at {}",
-            merged.len(),
-            cache_path.display()
-        );
+    let threshold = if args.strict {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Fail
+    };
+    let failing = reports.iter().filter(|r| r.status >= threshold).count();
+    if failing > 0 {
+        return Err(anyhow!(
+            "{failing} of {} font(s) failed validation",
+            reports.len()
+        ));
     }
     Ok(())
 }
 
-fn run_cache_list(args: CacheListArgs) -> Result<()> {
-    #[cfg(feature = "hpindex")]
-    if args.use_index {
-        return run_cache_list_index(args);
-    }
+/// `validate --duplicates`: names used by more than one file. Different
+/// files under one name fail; identical copies only fail with `--strict`.
+fn run_validate_duplicates(
+    paths: &[PathBuf],
+    opts: &SearchOptions,
+    args: &ValidateArgs,
+) -> Result<()> {
+    let duplicates = duplicate_names(paths, opts)?;
 
-    #[cfg(not(feature = "hpindex"))]
-    if args.use_index {
+    let stdout = io::stdout();
+    write_duplicates(&duplicates, stdout.lock(), args.json, args.ndjson)?;
+
+    let failing = duplicates
+        .iter()
+        .filter(|dup| dup.contents_differ || args.strict)
+        .count();
+    if failing > 0 {
         return Err(anyhow!(
-            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
+            "{failing} name(s) used by more than one {}",
+            if args.strict {
+                "file"
+            } else {
+                "different file"
+            }
         ));
     }
-
-    let cache_path = resolve_cache_path(&args.cache_path)?;
-    let entries = load_cache(&cache_path)?;
-    let output = OutputFormat::from_output(&args.output);
-    write_matches(&entries, &output)
+    Ok(())
 }
 
-fn run_cache_find(args: CacheFindArgs) -> Result<()> {
-    #[cfg(feature = "hpindex")]
-    if args.use_index {
-        return run_cache_find_index(args);
-    }
-
-    #[cfg(not(feature = "hpindex"))]
-    if args.use_index {
-        return Err(anyhow!(
-            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
-        ));
+fn write_duplicates(
+    duplicates: &[DuplicateName],
+    mut w: impl Write,
+    json: bool,
+    ndjson: bool,
+) -> Result<()> {
+    if json {
+        let rendered = serde_json::to_string_pretty(duplicates)?;
+        writeln!(w, "{rendered}")?;
+        return Ok(());
     }
-
-    let cache_path = resolve_cache_path(&args.cache_path)?;
-    let entries = load_cache(&cache_path)?;
-    let query = build_query_from_parts(
-        &args.axes,
-        &args.features,
-        &args.scripts,
-        &args.tables,
-        &args.name_patterns,
-        &args.creator_patterns,
-        &args.license_patterns,
-        &args.codepoints,
-        &args.text,
-        args.variable,
-        &args.weight,
-        &args.width,
-        &args.family_class,
-    )?;
-
-    let matches = filter_cached(&entries, &query);
-
-    if args.count_only {
-        println!("{}", matches.len());
+    if ndjson {
+        for duplicate in duplicates {
+            let line = serde_json::to_string(duplicate)?;
+            writeln!(w, "{line}")?;
+        }
         return Ok(());
     }
 
-    let output = OutputFormat::from_output(&args.output);
-    write_matches(&matches, &output)
+    for duplicate in duplicates {
+        let kind = match duplicate.kind {
+            DuplicateKind::Postscript => "postscript",
+            DuplicateKind::Full => "full",
+        };
+        let contents = if duplicate.contents_differ {
+            "differ"
+        } else {
+            "identical"
+        };
+        writeln!(w, "{kind}\t{}\t{contents}", duplicate.name)?;
+        for face in &duplicate.faces {
+            writeln!(w, "  {}", face.path_with_index())?;
+        }
+    }
+    Ok(())
 }
 
-fn run_cache_clean(args: CacheCleanArgs, quiet: bool) -> Result<()> {
-    #[cfg(feature = "hpindex")]
-    if args.use_index {
-        return run_cache_clean_index(args, quiet);
+fn write_reports(
+    reports: &[FontReport],
+    mut w: impl Write,
+    json: bool,
+    ndjson: bool,
+) -> Result<()> {
+    if json {
+        let rendered = serde_json::to_string_pretty(reports)?;
+        writeln!(w, "{rendered}")?;
+        return Ok(());
     }
-
-    #[cfg(not(feature = "hpindex"))]
-    if args.use_index {
-        return Err(anyhow!(
-            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
-        ));
+    if ndjson {
+        for report in reports {
+            let line = serde_json::to_string(report)?;
+            writeln!(w, "{line}")?;
+        }
+        return Ok(());
     }
 
-    let cache_path = resolve_cache_path(&args.cache_path)?;
-    let entries = load_cache(&cache_path)?;
-    let before = entries.len();
-    let pruned = prune_missing(entries);
-    let after = pruned.len();
-
-    write_cache(&cache_path, &pruned)?;
-    if !quiet {
-        eprintln!(
-            "removed {} missing entries ({} → {})",
-            before.saturating_sub(after),
-            before,
-            after
-        );
+    for report in reports {
+        writeln!(
+            w,
+            "{}\t{}",
+            report.source.path_with_index(),
+            status_label(report.status)
+        )?;
+        for check in report
+            .checks
+            .iter()
+            .filter(|c| c.status != CheckStatus::Pass)
+        {
+            writeln!(
+                w,
+                "  {} {}: {}",
+                status_label(check.status),
+                check.id,
+                check.message
+            )?;
+        }
     }
     Ok(())
 }
 
-fn run_cache_info(args: CacheInfoArgs) -> Result<()> {
-    #[cfg(feature = "hpindex")]
-    if args.use_index {
-        return run_cache_info_index(args);
-    }
-
-    #[cfg(not(feature = "hpindex"))]
-    if args.use_index {
-        return Err(anyhow!(
-            "--index requires the hpindex feature; rebuild with: cargo build --features hpindex"
-        ));
+fn status_label(status: CheckStatus) -> &'static str {
+    match status {
+        CheckStatus::Pass => "pass",
+        CheckStatus::Warn => "warn",
+        CheckStatus::Fail => "fail",
     }
+}
 
-    let cache_path = resolve_cache_path(&args.cache_path)?;
+/// The cache file to use: the default one `default_cache_path` resolves, or
+/// its counterpart in the `--collection` given.
+fn resolve_cache_path(custom: &Option<PathBuf>, profile: Option<CacheProfile>) -> Result<PathBuf> {
+    in_collection(default_cache_path(custom, profile)?)
+}
 
-    if !cache_path.exists() {
-        if args.json {
-            println!(r#"{{"exists":false,"path":"{}"}}"#, cache_path.display());
-        } else {
-            println!("Cache does not exist at {}", cache_path.display());
-        }
-        return Ok(());
+/// The cache file of the default collection: `custom`, the
+/// `TYPOG_CACHE_PATH` override, or `cache.json` in the profile's directory.
+fn default_cache_path(custom: &Option<PathBuf>, profile: Option<CacheProfile>) -> Result<PathBuf> {
+    if let Some(path) = custom {
+        return Ok(path.clone());
     }
 
-    let entries = load_cache(&cache_path)?;
-    let file_meta = fs::metadata(&cache_path)?;
-    let size_bytes = file_meta.len();
-
-    if args.json {
-        let info = serde_json::json!({
-            "exists": true,
-            "path": cache_path.display().to_string(),
-            "type": "json",
-            "entries": entries.len(),
-            "size_bytes": size_bytes,
-        });
-        println!("{}", serde_json::to_string_pretty(&info)?);
-    } else {
-        println!("Cache: {}", cache_path.display());
-        println!("Type:  JSON");
-        println!("Fonts: {}", entries.len());
-        println!("Size:  {} bytes", size_bytes);
+    if profile.is_none() {
+        if let Ok(env_override) = env::var("TYPOG_CACHE_PATH") {
+            return Ok(PathBuf::from(env_override));
+        }
     }
 
-    Ok(())
+    cache_root(profile)
+        .map(|root| root.join("cache.json"))
+        .ok_or_else(|| {
+            anyhow!("--cache-path is required because no cache directory could be detected")
+        })
 }
 
-fn resolve_cache_path(custom: &Option<PathBuf>) -> Result<PathBuf> {
+/// The `typg daemon` socket: `custom`, or `daemon.sock` in the cache
+/// directory.
+fn resolve_daemon_socket(custom: &Option<PathBuf>) -> Result<PathBuf> {
     if let Some(path) = custom {
         return Ok(path.clone());
     }
+    cache_root(None)
+        .map(|root| root.join("daemon.sock"))
+        .ok_or_else(|| {
+            anyhow!("a daemon socket path is required because no cache directory could be detected")
+        })
+}
 
-    if let Ok(env_override) = env::var("TYPOG_CACHE_PATH") {
-        return Ok(PathBuf::from(env_override));
-    }
+/// Resolve the index directory path, in the `--collection` given.
+#[cfg_attr(not(feature = "hpindex"), allow(dead_code))]
+fn resolve_index_path(custom: &Option<PathBuf>, profile: Option<CacheProfile>) -> Result<PathBuf> {
+    in_collection(default_index_path(custom, profile)?)
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        if let Some(local_appdata) = env::var_os("LOCALAPPDATA") {
-            return Ok(PathBuf::from(local_appdata).join("typg").join("cache.json"));
-        }
-        if let Some(home) = env::var_os("HOME") {
-            return Ok(PathBuf::from(home).join("AppData/Local/typg/cache.json"));
-        }
+/// The index directory of the default collection.
+#[cfg_attr(not(feature = "hpindex"), allow(dead_code))]
+fn default_index_path(custom: &Option<PathBuf>, profile: Option<CacheProfile>) -> Result<PathBuf> {
+    if let Some(path) = custom {
+        return Ok(path.clone());
     }
-
-    #[cfg(not(target_os = "windows"))]
-    {
-        if let Some(xdg) = env::var_os("XDG_CACHE_HOME") {
-            return Ok(PathBuf::from(xdg).join("typg").join("cache.json"));
-        }
-        if let Some(home) = env::var_os("HOME") {
-            return Ok(PathBuf::from(home)
-                .join(".cache")
-                .join("typg")
-                .join("cache.json"));
+
+    if profile.is_none() {
+        if let Ok(env_override) = env::var("TYPOG_INDEX_PATH") {
+            return Ok(PathBuf::from(env_override));
         }
     }
 
-    Err(anyhow!(
-        "--cache-path is required because no cache directory could be detected"
-    ))
+    cache_root(profile)
+        .map(|root| root.join("index"))
+        .ok_or_else(|| {
+            anyhow!("--index-path is required because no cache directory could be detected")
+        })
 }
 
-/// Resolve the index directory path.
-#[cfg_attr(not(feature = "hpindex"), allow(dead_code))]
-fn resolve_index_path(custom: &Option<PathBuf>) -> Result<PathBuf> {
-    if let Some(path) = custom {
-        return Ok(path.clone());
+/// Collections named by `--collection`, without repeats; set once at
+/// startup.
+static COLLECTIONS: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
+
+fn collections() -> &'static [String] {
+    COLLECTIONS.get().map(Vec::as_slice).unwrap_or(&[])
+}
+
+/// Parse a `--collection` name.
+fn parse_collection(raw: &str) -> Result<String, String> {
+    parse_collection_name(raw).map_err(|err| err.to_string())
+}
+
+/// `store` in each of the collections `names`, or just `store` when
+/// `names` is empty.
+fn collection_stores(store: &Path, names: &[String]) -> Vec<PathBuf> {
+    if names.is_empty() {
+        return vec![store.to_path_buf()];
+    }
+    names
+        .iter()
+        .map(|name| collection_path(store, name))
+        .collect()
+}
+
+/// `store` in the one collection `--collection` names, if any.
+fn in_collection(store: PathBuf) -> Result<PathBuf> {
+    match collection_stores(&store, collections()).as_slice() {
+        [one] => Ok(one.clone()),
+        _ => Err(anyhow!(
+            "several --collection names can only be searched together, with cache find"
+        )),
     }
+}
+
+/// Add the faces of `found` that no earlier store of a union returned,
+/// wherever they live; duplicates within one store are kept.
+fn extend_unseen(
+    matches: &mut Vec<TypgFontFaceMatch>,
+    seen: &mut HashSet<u64>,
+    found: Vec<TypgFontFaceMatch>,
+) {
+    let fresh: Vec<_> = found
+        .into_iter()
+        .filter(|m| !seen.contains(&m.metadata.fingerprint()))
+        .collect();
+    seen.extend(fresh.iter().map(|m| m.metadata.fingerprint()));
+    matches.extend(fresh);
+}
 
-    if let Ok(env_override) = env::var("TYPOG_INDEX_PATH") {
-        return Ok(PathBuf::from(env_override));
+/// Directory holding `cache.json` and `index/` for a profile.
+///
+/// Without an explicit profile, a project cache discovered above the working
+/// directory wins over the per-user default, the same way git finds `.git`.
+fn cache_root(profile: Option<CacheProfile>) -> Option<PathBuf> {
+    match profile {
+        Some(CacheProfile::System) => system_cache_root(),
+        Some(CacheProfile::User) => user_cache_root(),
+        Some(CacheProfile::Project) => {
+            let cwd = env::current_dir().ok()?;
+            Some(find_project_cache_root(&cwd).unwrap_or_else(|| cwd.join(".typg")))
+        }
+        None => env::current_dir()
+            .ok()
+            .and_then(|cwd| find_project_cache_root(&cwd))
+            .or_else(user_cache_root),
     }
+}
 
+/// Walk up from `start` looking for an existing `.typg/` directory.
+fn find_project_cache_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(".typg"))
+        .find(|candidate| candidate.is_dir())
+}
+
+fn user_cache_root() -> Option<PathBuf> {
     #[cfg(target_os = "windows")]
     {
         if let Some(local_appdata) = env::var_os("LOCALAPPDATA") {
-            return Ok(PathBuf::from(local_appdata).join("typg").join("index"));
+            return Some(PathBuf::from(local_appdata).join("typg"));
         }
         if let Some(home) = env::var_os("HOME") {
-            return Ok(PathBuf::from(home).join("AppData/Local/typg/index"));
+            return Some(PathBuf::from(home).join("AppData/Local/typg"));
         }
     }
 
     #[cfg(not(target_os = "windows"))]
     {
         if let Some(xdg) = env::var_os("XDG_CACHE_HOME") {
-            return Ok(PathBuf::from(xdg).join("typg").join("index"));
+            return Some(PathBuf::from(xdg).join("typg"));
         }
         if let Some(home) = env::var_os("HOME") {
-            return Ok(PathBuf::from(home)
-                .join(".cache")
-                .join("typg")
-                .join("index"));
+            return Some(PathBuf::from(home).join(".cache").join("typg"));
         }
     }
 
-    Err(anyhow!(
-        "--index-path is required because no cache directory could be detected"
-    ))
+    None
 }
 
-/// Load cached font entries from disk. Tries JSON array first, falls back to NDJSON.
-fn load_cache(path: &Path) -> Result<Vec<TypgFontFaceMatch>> {
-    let file = File::open(path).with_context(|| format!("opening cache {}", path.display()))?;
-    let reader = BufReader::new(file);
-
-    match serde_json::from_reader(reader) {
-        Ok(entries) => Ok(entries),
-        Err(_) => {
-            // Fall back to NDJSON parsing for forward compatibility
-            let file =
-                File::open(path).with_context(|| format!("re-opening cache {}", path.display()))?;
-            let reader = BufReader::new(file);
-            let stream = Deserializer::from_reader(reader).into_iter::<TypgFontFaceMatch>();
-            let mut entries = Vec::new();
-            for item in stream {
-                entries.push(item?);
-            }
-            Ok(entries)
-        }
+fn system_cache_root() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        env::var_os("PROGRAMDATA").map(|data| PathBuf::from(data).join("typg"))
     }
-}
 
-/// Write font entries to the cache file as pretty-printed JSON.
-fn write_cache(path: &Path, entries: &[TypgFontFaceMatch]) -> Result<()> {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+    #[cfg(target_os = "macos")]
+    {
+        Some(PathBuf::from("/Library/Caches/typg"))
     }
 
-    let file = File::create(path).with_context(|| format!("creating cache {}", path.display()))?;
-    let mut writer = BufWriter::new(file);
-    serde_json::to_writer_pretty(&mut writer, entries)
-        .with_context(|| format!("writing cache {}", path.display()))?;
-    writer.flush()?;
-    Ok(())
-}
-
-fn merge_entries(
-    existing: Vec<TypgFontFaceMatch>,
-    additions: Vec<TypgFontFaceMatch>,
-) -> Vec<TypgFontFaceMatch> {
-    let mut map: HashMap<(PathBuf, Option<u32>), TypgFontFaceMatch> = HashMap::new();
-
-    for entry in existing.into_iter().chain(additions.into_iter()) {
-        map.insert(cache_key(&entry), entry);
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        Some(PathBuf::from("/var/cache/typg"))
     }
-
-    let mut merged: Vec<TypgFontFaceMatch> = map.into_values().collect();
-    sort_entries(&mut merged);
-    merged
 }
 
 fn prune_missing(entries: Vec<TypgFontFaceMatch>) -> Vec<TypgFontFaceMatch> {
@@ -1234,17 +5772,24 @@ fn prune_missing(entries: Vec<TypgFontFaceMatch>) -> Vec<TypgFontFaceMatch> {
     pruned
 }
 
-fn sort_entries(entries: &mut [TypgFontFaceMatch]) {
-    entries.sort_by(|a, b| {
-        a.source
-            .path
-            .cmp(&b.source.path)
-            .then_with(|| a.source.ttc_index.cmp(&b.source.ttc_index))
-    });
-}
-
-fn cache_key(entry: &TypgFontFaceMatch) -> (PathBuf, Option<u32>) {
-    (entry.source.path.clone(), entry.source.ttc_index)
+/// Drop entries whose file no longer hashes to the recorded `file_hash`.
+///
+/// Each file is read once however many faces it has. Entries without a
+/// recorded hash are kept.
+fn prune_changed(entries: Vec<TypgFontFaceMatch>) -> Vec<TypgFontFaceMatch> {
+    let mut current: HashMap<PathBuf, Option<u64>> = HashMap::new();
+    entries
+        .into_iter()
+        .filter(|entry| {
+            let Some(recorded) = entry.metadata.file_hash else {
+                return true;
+            };
+            let hash = current
+                .entry(entry.source.path.clone())
+                .or_insert_with(|| hash_file(&entry.source.path).ok());
+            *hash == Some(recorded)
+        })
+        .collect()
 }
 
 // ============================================================================
@@ -1255,144 +5800,506 @@ fn cache_key(entry: &TypgFontFaceMatch) -> (PathBuf, Option<u32>) {
 fn run_cache_add_index(args: CacheAddArgs, quiet: bool) -> Result<()> {
     use std::time::SystemTime;
 
-    let stdin = io::stdin();
+    let index_path = resolve_index_path(&args.index_path, args.profile)?;
+    let index = open_index(&index_path)?;
+
+    // Write one file (all its faces) at a time, in one transaction unless
+    // --commit-every asks for more. Roots are recorded with the last one.
+    let writer = index.writer()?;
+    let (writer, stats, report, unchanged) =
+        if args.from_ndjson.is_none() && args.manifest.is_none() {
+            let (writer, stats, report) = ingest_scan(&args, writer)?;
+            (writer, stats, Some(report), None)
+        } else {
+            // The index stores no file sizes, so manifests compare mtimes.
+            let additions = cache_additions(&args, io::stdin().lock(), |entry| {
+                let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(entry.mtime);
+                Ok(!writer.needs_update(&entry.path, mtime)?)
+            })?;
+            // Imported records and manifest files carry their own mtimes.
+            let (mut writer, stats) =
+                writer.ingest(group_by_file(additions.faces), true, args.commit_every)?;
+            writer.record_roots(&additions.roots)?;
+            (writer, stats, additions.report, additions.unchanged)
+        };
+    writer.commit()?;
+
+    if !quiet {
+        let total = index.count()?;
+        eprintln!(
+            "indexed {} font faces at {} (added: {}, skipped: {}){}",
+            total,
+            index_path.display(),
+            stats.added,
+            stats.skipped,
+            unchanged_note(unchanged)
+        );
+    }
+    if let Some(report) = report {
+        print_summary(&report, quiet);
+        args.errors.write_failures(&report)?;
+    }
+
+    Ok(())
+}
+
+/// Files handed from the scan to the index writer that may wait in the
+/// channel; the scan pauses while it is full.
+#[cfg(feature = "hpindex")]
+const INGEST_QUEUE: usize = 256;
+
+/// Scan the `cache add` paths and store each file as soon as it is read:
+/// parser threads feed this thread's writer over a bounded channel, so
+/// neither the scan's results nor (with `--commit-every`) the write
+/// transaction grow with the collection. Returns the writer, with the
+/// roots recorded, for the caller to commit.
+#[cfg(feature = "hpindex")]
+fn ingest_scan<'a>(
+    args: &CacheAddArgs,
+    writer: IndexWriter<'a>,
+) -> Result<(IndexWriter<'a>, IngestStats, SearchReport)> {
+    let opts = cache_add_options(args);
     let paths = gather_paths(
         &args.paths,
         args.stdin_paths,
         args.system_fonts,
-        stdin.lock(),
+        io::stdin().lock(),
     )?;
+    let roots = scan_roots(&paths, &opts)?;
+
+    let (tx, rx) = std::sync::mpsc::sync_channel(INGEST_QUEUE);
+    let (ingested, scanned) = std::thread::scope(|scope| {
+        let scan = scope.spawn(|| {
+            // The channel closes when the scan is done and `tx` is dropped.
+            search_each_file(&paths, &Query::new(), &opts, move |faces| {
+                // A writer that failed has hung up; its error is reported below.
+                let _ = tx.send(faces);
+            })
+        });
+        let ingested = writer.ingest(rx, false, args.commit_every);
+        let scanned = scan
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+        (ingested, scanned)
+    });
+    let (mut writer, stats) = ingested?;
+    let report = scanned?;
+    writer.record_roots(&roots)?;
+    Ok((writer, stats, report))
+}
 
-    let index_path = resolve_index_path(&args.index_path)?;
-    let index = FontIndex::open(&index_path)?;
+/// Split faces listed file by file into one list per file.
+#[cfg(feature = "hpindex")]
+fn group_by_file(faces: Vec<TypgFontFaceMatch>) -> Vec<Vec<TypgFontFaceMatch>> {
+    let mut files: Vec<Vec<TypgFontFaceMatch>> = Vec::new();
+    for face in faces {
+        match files.last_mut() {
+            Some(file) if file[0].source.path == face.source.path => file.push(face),
+            _ => files.push(vec![face]),
+        }
+    }
+    files
+}
 
-    // Use the existing search pipeline to discover and extract metadata.
-    let opts = SearchOptions {
-        follow_symlinks: args.follow_symlinks,
-        jobs: args.jobs,
-    };
-    let additions = search(&paths, &Query::new(), &opts)?;
+#[cfg(feature = "hpindex")]
+fn run_cache_list_index(args: CacheListArgs) -> Result<()> {
+    let index_path = resolve_index_path(&args.index_path, args.profile)?;
+    let index = open_index_ro(&index_path)?;
+    let reader = index.reader()?;
+    let entries = reader.list_all()?;
+    let output = OutputFormat::from_output(&args.output).with_roots(vec![index_path.clone()]);
+    write_results(&entries, &Query::new(), None, false, &output)
+}
 
-    // Write to index in a single transaction.
-    let mut writer = index.writer()?;
-    let mut added = 0usize;
-    let mut skipped = 0usize;
+#[cfg(feature = "hpindex")]
+fn run_cache_find_index(args: CacheFindArgs) -> Result<()> {
+    let index_paths = resolve_index_paths(&args.index_paths, &args.profiles)?;
 
-    for entry in additions {
-        // Get file mtime for incremental update detection.
-        let mtime = entry
-            .source
-            .path
-            .metadata()
-            .and_then(|m| m.modified())
-            .unwrap_or(SystemTime::UNIX_EPOCH);
+    let query = cache_find_query(&args)?;
+    let search = if args.ranking.rank {
+        query.relaxed()
+    } else {
+        query.clone()
+    };
 
-        // Check if update is needed.
-        if !writer.needs_update(&entry.source.path, mtime)? {
-            skipped += 1;
-            continue;
+    let mut matches = Vec::new();
+    let mut seen = HashSet::new();
+    for index_path in &index_paths {
+        let index = open_index_ro(index_path)?;
+        let found = index
+            .reader()?
+            .find(&search)
+            .with_context(|| format!("searching index {}", index_path.display()))?;
+        extend_unseen(&mut matches, &mut seen, found);
+    }
+    let output = OutputFormat::from_output(&args.output).with_roots(index_paths);
+
+    if args.ranking.rank {
+        let ranked = rank(matches, &query, args.ranking.min_score.unwrap_or(0.0));
+        let ranked = per_family(ranked, args.per_family, |r| &r.face.metadata);
+        let ranked = paginate(ranked, args.offset, args.limit);
+        if args.count_only {
+            println!("{}", ranked.len());
+            return Ok(());
         }
+        return write_ranked_stdout(&ranked, &output);
+    }
 
-        writer.add_font(
-            &entry.source.path,
-            entry.source.ttc_index,
-            mtime,
-            entry.metadata.names.clone(),
-            &entry.metadata.axis_tags,
-            &entry.metadata.feature_tags,
-            &entry.metadata.script_tags,
-            &entry.metadata.table_tags,
-            &entry.metadata.codepoints,
-            entry.metadata.is_variable,
-            entry.metadata.weight_class,
-            entry.metadata.width_class,
-            entry.metadata.family_class,
-        )?;
-        added += 1;
+    let matches = sort_results(matches, &args.sort, args.reverse);
+    let matches = per_family(matches, args.per_family, |m| &m.metadata);
+    let matches = paginate(matches, args.offset, args.limit);
+    write_results(&matches, &query, args.group_by, args.count_only, &output)
+}
+
+/// The indexes `cache find --index` searches: every `--index-path`, then
+/// every `--cache` profile's index, without repeats. With neither, the
+/// default index. Each is taken in every `--collection` named.
+#[cfg(feature = "hpindex")]
+fn resolve_index_paths(paths: &[PathBuf], profiles: &[CacheProfile]) -> Result<Vec<PathBuf>> {
+    let mut bases: Vec<PathBuf> = paths.to_vec();
+    for &profile in profiles {
+        bases.push(default_index_path(&None, Some(profile))?);
+    }
+    if bases.is_empty() {
+        bases.push(default_index_path(&None, None)?);
     }
+    let mut resolved: Vec<PathBuf> = bases
+        .iter()
+        .flat_map(|base| collection_stores(base, collections()))
+        .collect();
+    let mut keys = HashSet::new();
+    resolved.retain(|path| keys.insert(path.canonicalize().unwrap_or_else(|_| path.clone())));
+    Ok(resolved)
+}
+
+#[cfg(feature = "hpindex")]
+fn run_cache_clean_index(args: CacheCleanArgs, quiet: bool) -> Result<()> {
+    let index_path = resolve_index_path(&args.index_path, args.profile)?;
+    let index = open_index(&index_path)?;
 
+    let mut writer = index.writer()?;
+    let (before, mut after) = writer.prune_missing()?;
+    let missing = before - after;
+    if args.verify_hash {
+        after -= writer.prune_changed()?;
+    }
     writer.commit()?;
 
     if !quiet {
-        let total = index.count()?;
         eprintln!(
-            "indexed {} font faces at {} (added: {}, skipped: {})",
-            total,
-            index_path.display(),
-            added,
-            skipped
+            "{}",
+            clean_summary(missing, before, after, args.verify_hash, false)
         );
     }
-
     Ok(())
 }
 
 #[cfg(feature = "hpindex")]
-fn run_cache_list_index(args: CacheListArgs) -> Result<()> {
-    let index_path = resolve_index_path(&args.index_path)?;
-    let index = FontIndex::open(&index_path)?;
-    let reader = index.reader()?;
-    let entries = reader.list_all()?;
-    let output = OutputFormat::from_output(&args.output);
-    write_matches(&entries, &output)
+fn run_cache_remove_index(args: CacheRemoveArgs, query: &Query, quiet: bool) -> Result<()> {
+    let index_path = resolve_index_path(&args.index_path, args.profile)?;
+    if !index_path.is_dir() {
+        return Err(anyhow!("no index at {}", index_path.display()));
+    }
+    let index = open_index(&index_path)?;
+    let before = index.count()?;
+    let doomed = index.reader()?.find(query)?;
+
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    for entry in &doomed {
+        writeln!(handle, "{}", entry.source.path_with_index())?;
+    }
+    if !args.dry_run && !doomed.is_empty() {
+        let ids: Vec<_> = doomed.iter().filter_map(|m| m.metadata.index_id).collect();
+        let mut writer = index.writer()?;
+        writer.remove_faces(&ids)?;
+        writer.commit()?;
+    }
+    if !quiet {
+        eprintln!("{}", remove_summary(doomed.len(), before, args.dry_run));
+    }
+    Ok(())
 }
 
 #[cfg(feature = "hpindex")]
-fn run_cache_find_index(args: CacheFindArgs) -> Result<()> {
-    let index_path = resolve_index_path(&args.index_path)?;
-    let index = FontIndex::open(&index_path)?;
+fn run_cache_fsck_index(args: CacheFsckArgs, quiet: bool) -> Result<()> {
+    let index_path = resolve_index_path(&args.index_path, args.profile)?;
+    if !index_path.is_dir() {
+        return Err(anyhow!("no index at {}", index_path.display()));
+    }
+    let index = if args.repair {
+        open_index(&index_path)?
+    } else {
+        open_index_ro(&index_path)?
+    };
 
-    let query = build_query_from_parts(
-        &args.axes,
-        &args.features,
-        &args.scripts,
-        &args.tables,
-        &args.name_patterns,
-        &args.creator_patterns,
-        &args.license_patterns,
-        &args.codepoints,
-        &args.text,
-        args.variable,
-        &args.weight,
-        &args.width,
-        &args.family_class,
-    )?;
+    let check = if args.repair {
+        let mut writer = index.writer()?;
+        let check = writer.repair()?;
+        writer.commit()?;
+        check
+    } else {
+        index.check()?
+    };
 
-    let reader = index.reader()?;
-    let matches = reader.find(&query)?;
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    if args.json {
+        let mut json = serde_json::to_value(&check)?;
+        json["path"] = serde_json::json!(index_path);
+        json["repaired"] = serde_json::json!(args.repair && !check.is_consistent());
+        writeln!(handle, "{}", serde_json::to_string_pretty(&json)?)?;
+    } else {
+        for (label, count, detail) in index_check_rows(&check) {
+            writeln!(handle, "{label}\t{count}\t{detail}")?;
+        }
+    }
 
-    if args.count_only {
-        println!("{}", matches.len());
+    if check.is_consistent() {
+        if !quiet {
+            eprintln!("index is consistent ({} faces)", check.faces);
+        }
+        return Ok(());
+    }
+    if args.repair {
+        if !quiet {
+            eprintln!(
+                "repaired {} problem(s) in {}",
+                check.problems(),
+                index_path.display()
+            );
+            if !check.unmapped_faces.is_empty() || !check.corrupt_bitmaps.is_empty() {
+                eprintln!("re-run `typg cache add --index` on your font folders to re-read the affected files");
+            }
+        }
         return Ok(());
     }
+    Err(anyhow!(
+        "index has {} problem(s); run `typg cache fsck --index --repair` to fix them",
+        check.problems()
+    ))
+}
 
-    let output = OutputFormat::from_output(&args.output);
-    write_matches(&matches, &output)
+/// Options every index the CLI opens starts from; `--index-map-size` sets
+/// them once at startup.
+#[cfg(feature = "hpindex")]
+static INDEX_OPTIONS: std::sync::OnceLock<typg_core::index::IndexOptions> =
+    std::sync::OnceLock::new();
+
+#[cfg(feature = "hpindex")]
+fn index_options() -> typg_core::index::IndexOptions {
+    INDEX_OPTIONS.get().cloned().unwrap_or_default()
 }
 
+/// Open the index at `path` for writing, creating it if needed.
 #[cfg(feature = "hpindex")]
-fn run_cache_clean_index(args: CacheCleanArgs, quiet: bool) -> Result<()> {
-    let index_path = resolve_index_path(&args.index_path)?;
-    let index = FontIndex::open(&index_path)?;
+fn open_index(path: &Path) -> Result<FontIndex> {
+    FontIndex::open_with(path, &index_options())
+}
+
+/// Open the index at `path` for queries only, so indexes on read-only
+/// file systems work. Where there is no index yet, an empty one is
+/// created, as commands that query a fresh install always have.
+#[cfg(feature = "hpindex")]
+fn open_index_ro(path: &Path) -> Result<FontIndex> {
+    if !path.join("data.mdb").is_file() {
+        return open_index(path);
+    }
+    let options = typg_core::index::IndexOptions {
+        read_only: true,
+        ..index_options()
+    };
+    FontIndex::open_with(path, &options)
+}
+
+#[cfg(feature = "hpindex")]
+fn run_cache_compact_index(args: CacheCompactArgs, quiet: bool) -> Result<()> {
+    let index_path = resolve_index_path(&args.index_path, args.profile)?;
+    if !index_path.is_dir() {
+        return Err(anyhow!("no index at {}", index_path.display()));
+    }
+    let stats = open_index(&index_path)?.compact()?;
+
+    if args.json {
+        let mut json = serde_json::to_value(stats)?;
+        json["path"] = serde_json::json!(index_path);
+        json["reclaimed_bytes"] = serde_json::json!(stats.reclaimed_bytes());
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    }
+    if !quiet {
+        eprintln!(
+            "compacted {}: {} → {} ({} reclaimed)",
+            index_path.display(),
+            human_bytes(stats.before_bytes),
+            human_bytes(stats.after_bytes),
+            human_bytes(stats.reclaimed_bytes())
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "hpindex")]
+fn run_cache_export_index(args: CacheExportArgs, quiet: bool) -> Result<()> {
+    let index_path = resolve_index_path(&args.index_path, args.profile)?;
+    if !index_path.join("data.mdb").is_file() {
+        return Err(anyhow!("no index at {}", index_path.display()));
+    }
+    let index = open_index_ro(&index_path)?;
+    let reader = index.reader()?;
+
+    let stats = if args.output == Path::new("-") {
+        reader.export(io::stdout().lock())?
+    } else {
+        // Write beside the target and rename, so a failed export never
+        // leaves a truncated snapshot under the requested name.
+        let mut partial = args.output.clone().into_os_string();
+        partial.push(".partial");
+        let partial = PathBuf::from(partial);
+        let failed = |err: anyhow::Error| anyhow!("writing {}: {err:#}", args.output.display());
+        let file = fs::File::create(&partial).map_err(|err| failed(err.into()))?;
+        let stats = reader.export(io::BufWriter::new(file)).map_err(failed)?;
+        fs::rename(&partial, &args.output).map_err(|err| failed(err.into()))?;
+        stats
+    };
+
+    if !quiet {
+        eprintln!(
+            "exported {} faces of {} files from {} to {}",
+            stats.faces,
+            stats.files,
+            index_path.display(),
+            args.output.display()
+        );
+    }
+    Ok(())
+}
 
+#[cfg(feature = "hpindex")]
+fn run_cache_import_index(args: CacheImportArgs, quiet: bool) -> Result<()> {
+    let index_path = resolve_index_path(&args.index_path, args.profile)?;
+    let index = open_index(&index_path)?;
     let mut writer = index.writer()?;
-    let (before, after) = writer.prune_missing()?;
+
+    let stats = if args.snapshot == Path::new("-") {
+        writer.import(io::stdin().lock(), &args.rebase)?
+    } else {
+        let failed = |err: anyhow::Error| anyhow!("importing {}: {err:#}", args.snapshot.display());
+        let file = fs::File::open(&args.snapshot).map_err(|err| failed(err.into()))?;
+        writer
+            .import(io::BufReader::new(file), &args.rebase)
+            .map_err(failed)?
+    };
     writer.commit()?;
 
+    if args.json {
+        let mut json = serde_json::to_value(stats)?;
+        json["path"] = serde_json::json!(index_path);
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    }
     if !quiet {
         eprintln!(
-            "removed {} missing entries ({} → {})",
-            before.saturating_sub(after),
-            before,
-            after
+            "imported {} faces of {} files into {}",
+            stats.faces,
+            stats.files,
+            index_path.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "hpindex")]
+fn run_cache_migrate_index(args: CacheMigrateArgs, quiet: bool) -> Result<()> {
+    use typg_core::migrate::{cache_to_index, index_to_cache};
+
+    let cache_path = resolve_cache_path(&args.cache_path, args.profile)?;
+    let index_path = resolve_index_path(&args.index_path, args.profile)?;
+    let (stats, from, to) = if args.to_index {
+        if !cache_path.is_file() {
+            return Err(anyhow!("no cache at {}", cache_path.display()));
+        }
+        let index = open_index(&index_path)?;
+        let mut writer = index.writer()?;
+        let stats = cache_to_index(&cache_path, &mut writer)?;
+        writer.commit()?;
+        (stats, &cache_path, &index_path)
+    } else {
+        if !index_path.join("data.mdb").is_file() {
+            return Err(anyhow!("no index at {}", index_path.display()));
+        }
+        let index = open_index_ro(&index_path)?;
+        let stats = index_to_cache(&index.reader()?, &cache_path)?;
+        (stats, &index_path, &cache_path)
+    };
+
+    if args.json {
+        let mut json = serde_json::to_value(stats)?;
+        json["from"] = serde_json::json!(from);
+        json["to"] = serde_json::json!(to);
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    }
+    if !quiet {
+        eprintln!(
+            "migrated {} faces of {} files from {} to {}",
+            stats.faces,
+            stats.files,
+            from.display(),
+            to.display()
         );
+        if stats.partial > 0 {
+            eprintln!(
+                "warning: {} faces were indexed before full metadata was kept and carry only names, classes, tags and codepoints; run `typg cache refresh --index` first to complete them",
+                stats.partial
+            );
+        }
     }
     Ok(())
 }
 
+/// The non-empty findings of an index check as (problem, count, detail)
+/// rows, the detail listing the face IDs or tags involved.
+#[cfg(feature = "hpindex")]
+fn index_check_rows(check: &typg_core::index::IndexCheck) -> Vec<(&'static str, u64, String)> {
+    let ids = |ids: &[typg_core::index::FontID]| {
+        ids.iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    [
+        (
+            "unreadable faces",
+            check.unreadable_faces.len() as u64,
+            ids(&check.unreadable_faces),
+        ),
+        (
+            "unmapped faces",
+            check.unmapped_faces.len() as u64,
+            ids(&check.unmapped_faces),
+        ),
+        (
+            "orphan path entries",
+            check.orphan_paths as u64,
+            String::new(),
+        ),
+        (
+            "dangling bitmap members",
+            check.dangling_members,
+            ids(&check.orphan_ids),
+        ),
+        (
+            "corrupt bitmaps",
+            check.corrupt_bitmaps.len() as u64,
+            check.corrupt_bitmaps.join(","),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, count, _)| *count > 0)
+    .collect()
+}
+
 #[cfg(feature = "hpindex")]
 fn run_cache_info_index(args: CacheInfoArgs) -> Result<()> {
-    let index_path = resolve_index_path(&args.index_path)?;
+    let index_path = resolve_index_path(&args.index_path, args.profile)?;
 
     if !index_path.exists() {
         if args.json {
@@ -1403,35 +6310,87 @@ fn run_cache_info_index(args: CacheInfoArgs) -> Result<()> {
         return Ok(());
     }
 
-    let index = FontIndex::open(&index_path)?;
-    let count = index.count()?;
+    let index = open_index_ro(&index_path)?;
+    let reader = index.reader()?;
+    let entries = reader.list_all()?;
+    let roots = reader.roots()?;
+    drop(reader);
+    let databases = index.database_stats()?;
 
-    // Calculate total directory size (non-recursive, LMDB is flat).
-    let size_bytes: u64 = fs::read_dir(&index_path)?
+    // LMDB keeps everything in a flat directory (data.mdb and lock.mdb).
+    let files: Vec<fs::Metadata> = fs::read_dir(&index_path)?
         .filter_map(|e| e.ok())
         .filter_map(|e| e.metadata().ok())
         .filter(|m| m.is_file())
-        .map(|m| m.len())
-        .sum();
+        .collect();
+    let info = StoreInfo {
+        label: "Index",
+        kind: "lmdb",
+        path: &index_path,
+        entries: &entries,
+        roots: &roots,
+        collection: collections().first().map(String::as_str),
+        collections: store_collections(&default_index_path(&args.index_path, args.profile)?),
+        size_bytes: files.iter().map(|m| m.len()).sum(),
+        updated: files.iter().filter_map(|m| m.modified().ok()).max(),
+    };
 
     if args.json {
-        let info = serde_json::json!({
-            "exists": true,
-            "path": index_path.display().to_string(),
-            "type": "lmdb",
-            "entries": count,
-            "size_bytes": size_bytes,
-        });
-        println!("{}", serde_json::to_string_pretty(&info)?);
+        let mut json = info.to_json();
+        json["databases"] = databases
+            .iter()
+            .map(|db| {
+                (
+                    db.name.to_string(),
+                    serde_json::json!({
+                        "entries": db.entries,
+                        "size_bytes": db.size_bytes,
+                        "size": human_bytes(db.size_bytes),
+                    }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into();
+        let json = with_weight_stats(json, args.weights.then(|| weight_stats(&entries)))?;
+        println!("{}", serde_json::to_string_pretty(&json)?);
     } else {
-        println!("Index: {}", index_path.display());
-        println!("Type:  LMDB");
-        println!("Fonts: {}", count);
-        println!("Size:  {} bytes", size_bytes);
+        let mut stdout = io::stdout().lock();
+        info.write_summary(&mut stdout)?;
+        info.write_roots(&mut stdout)?;
+        write_database_stats(&databases, &mut stdout)?;
+        info.write_directories(&mut stdout)?;
+        if args.weights {
+            writeln!(stdout)?;
+            write_weight_stats(&weight_stats(&entries), &mut stdout)?;
+        }
     }
 
     Ok(())
 }
 
+/// Per-database lines for `cache info --index`.
+#[cfg(feature = "hpindex")]
+fn write_database_stats(
+    databases: &[typg_core::index::DatabaseStats],
+    mut w: impl Write,
+) -> Result<()> {
+    let width = databases
+        .iter()
+        .map(|db| thousands(db.entries as u64).len())
+        .max()
+        .unwrap_or(0);
+    writeln!(w, "Databases:")?;
+    for db in databases {
+        writeln!(
+            w,
+            "  {:<10}  {:>width$} entries  {:>9}",
+            db.name,
+            thousands(db.entries as u64),
+            human_bytes(db.size_bytes)
+        )?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,237 @@
+//! Client side of `typg serve`, for `find --remote`.
+//!
+//! `typg find --remote http://fontserver:8765 -s arab` sends the query to a
+//! running server's `/search` instead of scanning locally, then prints the
+//! answer with the usual output flags. Paths on the command line are paths
+//! on the server; without any, the server searches its default index.
+//!
+//! The client speaks just enough HTTP/1.1 for that one POST, over a plain
+//! TCP connection: `http://` URLs only. `--remote-token` adds the bearer
+//! token a server started with `--token` asks for. A server that stops
+//! sending or reading for [`DEFAULT_TIMEOUT`], or for `--timeout` plus
+//! [`RESPONSE_GRACE`] when that is given, fails the search.
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::server::{ApiError, SearchRequest, SearchResponse};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long the connection may go without any bytes moving before the
+/// search fails, unless [`Remote::with_timeout`] sets another limit.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Time beyond `--timeout` allowed for the server's answer to arrive: the
+/// server stops scanning at `--timeout` and still has to send its matches.
+pub const RESPONSE_GRACE: Duration = Duration::from_secs(10);
+
+/// A `typg serve` instance, parsed from an `http://host[:port][/prefix]` URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Remote {
+    /// `host:port`, as sent in the `Host` header.
+    authority: String,
+    /// Path prefix before `/search`, without a trailing slash.
+    prefix: String,
+    /// Bearer token sent with every request.
+    token: Option<String>,
+    /// Read and write timeout of the connection.
+    timeout: Duration,
+}
+
+impl Remote {
+    /// Parse a server URL. The port defaults to 80.
+    pub fn parse(url: &str) -> Result<Self> {
+        let rest = match url.split_once("://") {
+            Some(("http", rest)) => rest,
+            Some((scheme, _)) => {
+                return Err(anyhow!(
+                    "unsupported --remote scheme '{scheme}' (expected http://host:port)"
+                ))
+            }
+            None => url,
+        };
+        let (authority, prefix) = match rest.find('/') {
+            Some(slash) => rest.split_at(slash),
+            None => (rest, ""),
+        };
+        if authority.is_empty() {
+            return Err(anyhow!("--remote URL '{url}' has no host"));
+        }
+        let has_port = match authority.rfind(']') {
+            Some(bracket) => authority[bracket..].contains(':'),
+            None => authority.contains(':'),
+        };
+        let authority = if has_port {
+            authority.to_string()
+        } else {
+            format!("{authority}:80")
+        };
+        Ok(Self {
+            authority,
+            prefix: prefix.trim_end_matches('/').to_string(),
+            token: None,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Send `token` as `Authorization: Bearer` with every request.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Fail a request when no bytes move for `timeout` while sending it or
+    /// waiting for the answer.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Send one search request and return the server's answer.
+    ///
+    /// Error responses become errors carrying the server's message (and the
+    /// offending field, when it names one).
+    pub fn search(&self, req: &SearchRequest) -> Result<SearchResponse> {
+        let body = serde_json::to_vec(req)?;
+        let (status, body) = self.post("/search", &body)?;
+        if !(200..300).contains(&status) {
+            return Err(match serde_json::from_slice::<ApiError>(&body) {
+                Ok(err) => match &err.field {
+                    Some(field) => anyhow!("server rejected the query ({field}): {}", err.message),
+                    None => anyhow!("server rejected the query: {}", err.message),
+                },
+                Err(_) => anyhow!(
+                    "server answered {status}: {}",
+                    String::from_utf8_lossy(&body).trim()
+                ),
+            });
+        }
+        serde_json::from_slice(&body).context("reading the server's search response")
+    }
+
+    /// POST a JSON body and return the status code and response body.
+    fn post(&self, path: &str, body: &[u8]) -> Result<(u16, Vec<u8>)> {
+        let mut stream = self.connect()?;
+        let authorization = match &self.token {
+            Some(token) => format!("Authorization: Bearer {token}\r\n"),
+            None => String::new(),
+        };
+        let head = format!(
+            "POST {}{path} HTTP/1.1\r\nHost: {}\r\nUser-Agent: typg/{}\r\n\
+             {authorization}Content-Type: application/json\r\nAccept: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n",
+            self.prefix,
+            self.authority,
+            env!("CARGO_PKG_VERSION"),
+            body.len()
+        );
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        let timed_out = |err: std::io::Error, doing: &str| match err.kind() {
+            ErrorKind::WouldBlock | ErrorKind::TimedOut => anyhow!(
+                "{} stalled for {}s while {doing}",
+                self.authority,
+                self.timeout.as_secs_f64()
+            ),
+            _ => anyhow!("{doing} {}: {err}", self.authority),
+        };
+        stream
+            .write_all(head.as_bytes())
+            .and_then(|()| stream.write_all(body))
+            .map_err(|err| timed_out(err, "sending the request to"))?;
+        let mut response = Vec::new();
+        stream
+            .read_to_end(&mut response)
+            .map_err(|err| timed_out(err, "reading the response from"))?;
+        parse_response(&response)
+    }
+
+    fn connect(&self) -> Result<TcpStream> {
+        let addrs = self
+            .authority
+            .to_socket_addrs()
+            .with_context(|| format!("resolving {}", self.authority))?;
+        let mut last_err = None;
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                Ok(stream) => return Ok(stream),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(match last_err {
+            Some(err) => anyhow!("connecting to {}: {err}", self.authority),
+            None => anyhow!("{} resolved to no addresses", self.authority),
+        })
+    }
+}
+
+/// Split a complete HTTP/1.1 response into its status code and body,
+/// undoing chunked transfer encoding.
+pub(crate) fn parse_response(response: &[u8]) -> Result<(u16, Vec<u8>)> {
+    let end = response
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| anyhow!("malformed HTTP response: no end of headers"))?;
+    let head = String::from_utf8_lossy(&response[..end]);
+    let body = &response[end + 4..];
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| anyhow!("malformed HTTP response: bad status line"))?;
+
+    let mut chunked = false;
+    let mut length = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("transfer-encoding") {
+            chunked = value.eq_ignore_ascii_case("chunked");
+        } else if name.eq_ignore_ascii_case("content-length") {
+            length = value.parse::<usize>().ok();
+        }
+    }
+
+    let body = if chunked {
+        dechunk(body)?
+    } else {
+        match length {
+            Some(len) if len <= body.len() => body[..len].to_vec(),
+            Some(_) => return Err(anyhow!("HTTP response body ended early")),
+            None => body.to_vec(),
+        }
+    };
+    Ok((status, body))
+}
+
+fn dechunk(mut data: &[u8]) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = data
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or_else(|| anyhow!("malformed chunked HTTP body"))?;
+        let size_field = String::from_utf8_lossy(&data[..line_end]);
+        let size_hex = size_field.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size_hex, 16)
+            .map_err(|_| anyhow!("malformed chunk size '{size_hex}'"))?;
+        data = &data[line_end + 2..];
+        if size == 0 {
+            return Ok(body);
+        }
+        if data.len() < size + 2 {
+            return Err(anyhow!("HTTP response body ended early"));
+        }
+        body.extend_from_slice(&data[..size]);
+        data = &data[size + 2..];
+    }
+}
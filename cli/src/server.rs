@@ -2,31 +2,109 @@
 //!
 //! `/search` accepts the same filter vocabulary as the CLI. Requests can run a
 //! live filesystem scan or, when `use_index` is true, query the LMDB index.
-//! Responses can return either full match objects or just paths.
+//! Responses can return either full match objects or just paths, as one
+//! JSON document or, for clients that ask for it, streamed as the scan
+//! finds them (the `stream` module).
+//!
+//! The operator can cap what live scans cost the host: `--max-jobs` bounds
+//! the `jobs` a request may ask for (and fills it in when absent), and
+//! `--max-scans` makes scans beyond that many wait for a free slot.
+//!
+//! With `--token SECRET` every endpoint but `/health` requires an
+//! `Authorization: Bearer SECRET` header and answers 401 without it. Beyond
+//! a trusted network, serve HTTPS with `--tls-cert` and `--tls-key` (the
+//! `tls` module) or put the server behind a reverse proxy that terminates
+//! TLS; over plain HTTP the token travels in the clear.
+//!
+//! Browser apps on other origins need `--cors-origin`; `--max-concurrent-searches`
+//! and `--request-timeout` keep a misbehaving client from tying the server
+//! up (the `layers` module).
+//!
+//! `typg serve --mcp` answers the same searches over stdio with the Model
+//! Context Protocol instead of HTTP (the `mcp` module), and `typg daemon`
+//! answers them from a cache or index it keeps open, over a unix socket
+//! (the `daemon` module).
+//!
+//! `GET /openapi.json` describes the API as an OpenAPI 3.0 document, for
+//! generating client SDKs, and `GET /docs` shows it in Swagger UI (the
+//! `openapi` module).
+//!
+//! With `--access-log FILE` every `/search` request appends one NDJSON line
+//! to FILE: when it arrived, the client address, the filters it set, how
+//! long it took, the response status and the number of matches.
+//!
+//! Index queries share one open LMDB environment per index directory for the
+//! life of the process (see `FontIndex::shared`), so a request costs a read
+//! transaction rather than an environment open. `--preload-index` opens
+//! (and `--warm-index` reads through) that environment before the port is
+//! bound, so the server only accepts connections once its index is usable.
+//! Requests reach the default index and those named with `--index-path`;
+//! no request opens or creates any other directory, so the open
+//! environments stay the ones the operator chose.
+//! The `/index` endpoints (see the `index` module) let a client inspect the
+//! index and, with `--index-admin`, add to, clean or empty it; `/fonts`
+//! (the `fonts` module) returns one indexed face in full.
 //!
 //! Made by FontLab https://www.fontlab.com/
 
-use std::path::PathBuf;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::{Context, Result};
-use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use axum::http::{Extensions, HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
+use axum_server::tls_rustls::RustlsConfig;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use tokio::net::TcpListener;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio::task;
-use typg_core::search::{search, SearchOptions, TypgFontFaceMatch};
+use typg_core::output::rfc3339_utc;
+use typg_core::presets::parse_preset;
+use typg_core::query::Query;
+use typg_core::search::{
+    parse_sort_keys, search_with_report, sort_matches_by_keys, SearchOptions, SortKey,
+    TypgFontFaceMatch,
+};
 
 #[cfg(feature = "hpindex")]
-use typg_core::index::FontIndex;
+use axum::routing::delete;
+#[cfg(feature = "hpindex")]
+use typg_core::index::{FontIndex, IndexOptions};
 
-use crate::build_query_from_parts;
 #[cfg(feature = "hpindex")]
 use crate::resolve_index_path;
+use crate::{
+    apply_exclusions, build_query_from_parts, paginate, with_table_checksums, ExcludeArgs,
+    InvalidField, NameMatchArgs,
+};
+
+pub(crate) mod daemon;
+mod files;
+#[cfg(feature = "hpindex")]
+mod fonts;
+#[cfg(feature = "hpindex")]
+mod index;
+mod layers;
+pub(crate) mod mcp;
+mod openapi;
+mod stream;
+pub mod tls;
+mod woff2;
 
 /// Parameters for an HTTP font search request.
-#[derive(Clone, Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
 #[serde(default)]
 pub struct SearchRequest {
     /// Directories to search for fonts.
@@ -38,10 +116,18 @@ pub struct SearchRequest {
     pub features: Vec<String>,
     /// Script tags to require (e.g. latn, arab, cyrl, hani).
     pub scripts: Vec<String>,
+    /// Language system tags to require (e.g. TRK, SRB).
+    pub langs: Vec<String>,
     /// Font table tags to require (e.g. GDEF, GSUB, GPOS).
     pub tables: Vec<String>,
+    /// Table directory checksums to require, as `TAG=HEX` (e.g.
+    /// `glyf=0xDEADBEEF`).
+    pub table_checksums: Vec<String>,
     /// Name or family name patterns to match.
     pub names: Vec<String>,
+    /// Modifiers for `names`: `name_ignore_case` and `name_exact`.
+    #[serde(flatten)]
+    pub name_match: NameMatchArgs,
     /// Regex patterns that must match creator fields (copyright, trademark, manufacturer, designer, description, URLs, license).
     pub creator: Vec<String>,
     /// Regex patterns that must match license fields (copyright, license description, license URL).
@@ -50,12 +136,21 @@ pub struct SearchRequest {
     pub codepoints: Vec<String>,
     /// Sample text whose codepoints must all be present.
     pub text: Option<String>,
+    /// Unicode block names the font must cover completely.
+    pub unicode_blocks: Vec<String>,
+    /// Block coverage thresholds such as `"Cyrillic:90%"`.
+    pub coverage: Vec<String>,
     /// Restrict results to variable fonts.
     pub variable: bool,
     /// Follow symbolic links during directory traversal.
     pub follow_symlinks: bool,
+    /// Include hidden files and directories (names starting with `.`).
+    pub hidden: bool,
     /// Number of parallel worker threads.
     pub jobs: Option<usize>,
+    /// Stop a directory scan after this many milliseconds and answer with
+    /// the matches found so far, marked `truncated`. Index searches ignore it.
+    pub timeout_ms: Option<u64>,
     /// Return file paths only, without full metadata.
     pub paths_only: bool,
     /// Required weight class value or range.
@@ -64,44 +159,495 @@ pub struct SearchRequest {
     pub width: Option<String>,
     /// Required font family class (e.g. serif, sans-serif, script).
     pub family_class: Option<String>,
+    /// Least permissive OS/2 embedding level the font must grant:
+    /// `installable`, `editable`, `preview` or `restricted`.
+    #[schemars(with = "Option<typg_core::search::Embedding>")]
+    pub embedding: Option<String>,
+    /// Minimum glyph count (maxp numGlyphs).
+    pub min_glyphs: Option<u16>,
+    /// Minimum number of variation axes.
+    pub min_axes: Option<usize>,
+    /// Maximum number of variation axes.
+    pub max_axes: Option<usize>,
+    /// OS/2 vendor IDs; the font must carry one of them (case-insensitive).
+    pub vendors: Vec<String>,
+    /// Named instance names; a variable font must have one of them (e.g. `SemiBold`).
+    pub instances: Vec<String>,
+    /// Only match faces inside TTC/OTC collections.
+    pub only_collections: bool,
+    /// Only match the face at this index within each collection.
+    pub collection_index: Option<u32>,
+    /// Skip fonts with only legacy (format 0/6) or symbol cmap subtables.
+    pub unicode_cmap_only: bool,
+    /// Boolean filter expression such as `(feature:liga AND feature:smcp) OR feature:dlig`.
+    /// Combined with the other filters by AND.
+    pub query: Option<String>,
+    /// Built-in query to start from: `webfont-ready`, `arabic-ui` or
+    /// `code-fonts`. The other filters narrow it further.
+    pub preset: Option<String>,
+    /// Exclusion filters: `not_axes`, `not_features`, `not_scripts`,
+    /// `not_tables`, `not_name`, `exclude_path` globs, and `not_variable`.
+    #[serde(flatten)]
+    pub exclude: ExcludeArgs,
+    /// Sort keys, most significant first, such as `"family,weight,width,italic"`
+    /// (see `--sort`); path order when absent. Streamed answers arrive
+    /// unsorted.
+    pub sort: Option<String>,
+    /// Reverse the result order, applied before `offset` and `limit`.
+    pub reverse: bool,
+    /// Number of matches to skip, for pagination.
+    pub offset: Option<usize>,
+    /// Maximum number of matches to return, for pagination.
+    pub limit: Option<usize>,
     /// Use the LMDB index instead of a live directory scan (requires hpindex feature).
     pub use_index: bool,
     /// Path to the LMDB index directory (defaults to ~/.cache/typg/index or TYPOG_INDEX_PATH).
+    /// A server only opens its default index and those named with `--index-path`.
     pub index_path: Option<PathBuf>,
 }
 
 /// Search result wrapper returned by the HTTP server.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SearchResponse {
     /// Full font match details, present when `paths_only` is false.
     pub matches: Option<Vec<TypgFontFaceMatch>>,
     /// File paths only, present when `paths_only` is true.
     pub paths: Option<Vec<String>>,
+    /// Number of matches before `offset` and `limit` were applied.
+    #[serde(default)]
+    pub total: usize,
+    /// The scan ran out of `timeout_ms` before reading every font file, so
+    /// the matches are incomplete.
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+impl SearchResponse {
+    /// Sort and page through `matches` and shape them as the request asked.
+    fn new(mut matches: Vec<TypgFontFaceMatch>, req: &SearchRequest) -> Self {
+        let total = matches.len();
+        // `request_query` already rejected bad keys.
+        let keys = sort_keys(req).unwrap_or_default();
+        if !keys.is_empty() || req.reverse {
+            sort_matches_by_keys(&mut matches, &keys, req.reverse);
+        }
+        let matches = paginate(matches, req.offset, req.limit);
+        if req.paths_only {
+            Self {
+                matches: None,
+                paths: Some(matches.iter().map(|m| m.source.path_with_index()).collect()),
+                total,
+                truncated: false,
+            }
+        } else {
+            Self {
+                matches: Some(matches),
+                paths: None,
+                total,
+                truncated: false,
+            }
+        }
+    }
+}
+
+/// Settings shared by all request handlers.
+#[derive(Clone, Debug, Default)]
+pub struct ServerState {
+    /// Highest `jobs` a request may ask for, and the value used when it
+    /// asks for none. `None` leaves `jobs` to the client.
+    pub max_jobs: Option<usize>,
+    /// One permit per live scan allowed to run at once; `None` is unlimited.
+    pub scan_slots: Option<Arc<Semaphore>>,
+    /// Where `/search` requests are logged; `None` logs nothing.
+    pub access_log: Option<Arc<AccessLog>>,
+    /// Canonical directories `/font-file` may serve files from
+    /// (`--serve-root`); empty disables the endpoint.
+    pub font_roots: Arc<[PathBuf]>,
+    /// Bearer token every request but `/health` must carry (`--token`);
+    /// `None` leaves the server open.
+    pub token: Option<Arc<str>>,
+    /// Origins browsers may call the server from (`--cors-origin`), `*`
+    /// for any; empty sends no CORS headers.
+    pub cors_origins: Arc<[String]>,
+    /// One permit per `/search` request allowed in flight
+    /// (`--max-concurrent-searches`); `None` is unlimited.
+    pub search_slots: Option<Arc<Semaphore>>,
+    /// Time a request has to produce its response (`--request-timeout`);
+    /// `None` waits as long as it takes.
+    pub request_timeout: Option<Duration>,
+    /// LMDB tuning applied when a request first opens an index directory.
+    #[cfg(feature = "hpindex")]
+    pub index_options: IndexOptions,
+    /// Index directories requests may name in `index_path` besides the
+    /// default one (`--index-path`); others are refused.
+    #[cfg(feature = "hpindex")]
+    pub index_paths: Arc<[PathBuf]>,
+    /// Whether `/index/add`, `/index/clean` and `DELETE /index` may change
+    /// the index (`--index-admin`).
+    #[cfg(feature = "hpindex")]
+    pub index_admin: bool,
+    /// Held by the index change in progress, if any.
+    #[cfg(feature = "hpindex")]
+    pub index_changes: Arc<tokio::sync::Mutex<()>>,
+}
+
+/// An append-only NDJSON log of `/search` requests.
+#[derive(Debug)]
+pub struct AccessLog {
+    file: Mutex<File>,
+}
+
+impl AccessLog {
+    /// Open `path` for appending, creating it if needed.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("opening access log {}", path.display()))?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Append one record. Each record is a single write, so lines from
+    /// concurrent requests never interleave. A failed write is reported
+    /// on stderr and does not fail the request.
+    fn record(&self, record: &Value) {
+        let mut line = record.to_string();
+        line.push('\n');
+        let mut file = self.file.lock().unwrap_or_else(|p| p.into_inner());
+        if let Err(err) = file.write_all(line.as_bytes()) {
+            eprintln!("warning: writing access log: {err}");
+        }
+    }
+}
+
+/// One access log line.
+fn access_record(
+    client: Option<SocketAddr>,
+    headers: &HeaderMap,
+    query: Value,
+    elapsed: Duration,
+    outcome: Result<usize, StatusCode>,
+) -> Value {
+    let mut record = json!({
+        "timestamp": rfc3339_utc(SystemTime::now()),
+        "client": client.map(|addr| addr.ip().to_string()),
+        "query": query,
+        "duration_ms": (elapsed.as_secs_f64() * 10_000.0).round() / 10.0,
+        "status": outcome.map_or_else(|status| status.as_u16(), |_| 200),
+        "matches": outcome.ok(),
+    });
+    if let Some(forwarded) = headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+    {
+        record["forwarded_for"] = json!(forwarded);
+    }
+    record
 }
 
-/// Start the HTTP server bound to the given address.
-pub async fn serve(bind: &str) -> Result<()> {
+/// The filters a request set: its JSON form without `null`, `false`,
+/// empty lists and other defaults.
+fn query_summary(req: &SearchRequest) -> Value {
+    let Ok(Value::Object(fields)) = serde_json::to_value(req) else {
+        return Value::Null;
+    };
+    let set = fields
+        .into_iter()
+        .filter(|(_, value)| match value {
+            Value::Null | Value::Bool(false) => false,
+            Value::Array(items) => !items.is_empty(),
+            _ => true,
+        })
+        .collect();
+    Value::Object(set)
+}
+
+/// Start the HTTP server bound to the given address, speaking HTTPS when
+/// `tls` is given (see [`tls::load`]).
+///
+/// With the hpindex feature, the default index is opened before the first
+/// request arrives when it already exists.
+pub async fn serve(
+    bind: &str,
+    state: ServerState,
+    tls: Option<Arc<rustls::ServerConfig>>,
+) -> Result<()> {
+    #[cfg(feature = "hpindex")]
+    if let Ok(index_path) = resolve_index_path(&None, None) {
+        if index_path.is_dir() {
+            FontIndex::shared(&index_path, &state.index_options)?;
+        }
+    }
+
+    let app = router(state).into_make_service_with_connect_info::<SocketAddr>();
+    if let Some(config) = tls {
+        let listener = std::net::TcpListener::bind(bind)
+            .and_then(|listener| listener.set_nonblocking(true).map(|()| listener))
+            .with_context(|| format!("binding HTTPS server to {bind}"))?;
+        axum_server::from_tcp_rustls(listener, RustlsConfig::from_config(config))
+            .serve(app)
+            .await
+            .context("serving HTTPS")?;
+        return Ok(());
+    }
+
     let listener = TcpListener::bind(bind)
         .await
         .with_context(|| format!("binding HTTP server to {bind}"))?;
-
-    axum::serve(listener, router())
-        .await
-        .context("serving HTTP")?;
+    axum::serve(listener, app).await.context("serving HTTP")?;
     Ok(())
 }
 
-/// Build the router with `/health` and `/search` endpoints.
-pub fn router() -> Router {
-    Router::new()
+/// Build the router with `/health`, `/search` and `/font-file` endpoints,
+/// plus the `/fonts` detail and `/index` management endpoints with the
+/// hpindex feature, and `/openapi.json` and `/docs` describing them. All
+/// but `/health`, `/openapi.json` and `/docs` check the server's token;
+/// CORS and the request time limit apply to every endpoint.
+pub fn router(state: ServerState) -> Router {
+    let search = post(search_handler).layer(middleware::from_fn_with_state(
+        state.clone(),
+        layers::limit_searches,
+    ));
+    let router = Router::new()
+        .route("/search", search)
+        .route("/font-file", get(files::font_file_handler));
+    #[cfg(feature = "hpindex")]
+    let router = router
+        .route("/fonts", get(fonts::fonts_by_path_handler))
+        .route("/fonts/{id}", get(fonts::font_handler))
+        .route("/index", delete(index::delete_handler))
+        .route("/index/add", post(index::add_handler))
+        .route("/index/clean", post(index::clean_handler))
+        .route("/index/info", get(index::info_handler));
+    router
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
         .route("/health", get(|| async { "ok" }))
-        .route("/search", post(search_handler))
+        .route("/openapi.json", get(openapi::openapi_handler))
+        .route("/docs", get(openapi::docs_handler))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            layers::time_limit,
+        ))
+        .layer(middleware::from_fn_with_state(state.clone(), layers::cors))
+        .with_state(state)
+}
+
+/// Turn away requests without the server's bearer token, when it has one,
+/// with 401 `unauthorized`.
+async fn require_token(State(state): State<ServerState>, request: Request, next: Next) -> Response {
+    let Some(token) = &state.token else {
+        return next.run(request).await;
+    };
+    let presented = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_once(' '))
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+        .map(|(_, credentials)| credentials.trim());
+    if presented.is_some_and(|presented| same_secret(presented.as_bytes(), token.as_bytes())) {
+        return next.run(request).await;
+    }
+    let mut response = ApiError::new(
+        StatusCode::UNAUTHORIZED,
+        "unauthorized",
+        "this server requires an Authorization: Bearer token",
+    )
+    .into_response();
+    response
+        .headers_mut()
+        .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
+
+/// Compare two secrets in time that depends on their lengths only, not on
+/// where they first differ.
+fn same_secret(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y));
+    std::hint::black_box(diff) == 0
+}
+
+/// A failed request, sent as `{"code", "message", "field"}` JSON.
+///
+/// The status tells clients whose fault it was: 400 for a request that is
+/// malformed or asks for something the server does not allow, 422 for a
+/// filter value that does not parse (a bad tag, regex or range; `field`
+/// names it), and 5xx when the server could not carry out a valid request.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ApiError {
+    /// HTTP status, not part of the body.
+    #[serde(skip, default = "internal_status")]
+    pub status: StatusCode,
+    /// Stable machine-readable error kind, e.g. `invalid_value`.
+    pub code: String,
+    /// Human-readable explanation.
+    pub message: String,
+    /// The request field at fault, when there is one.
+    pub field: Option<String>,
+}
+
+fn internal_status() -> StatusCode {
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+impl ApiError {
+    fn new(status: StatusCode, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code: code.to_string(),
+            message: message.into(),
+            field: None,
+        }
+    }
+
+    fn with_field(mut self, field: &str) -> Self {
+        self.field = Some(field.to_string());
+        self
+    }
+
+    /// A filter value that does not parse: 422 with the field it came
+    /// from when the error knows it.
+    fn invalid_value(err: anyhow::Error) -> Self {
+        let field = err.downcast_ref::<InvalidField>().map(|e| e.field);
+        let error = Self::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_value",
+            format!("{err:#}"),
+        );
+        match field {
+            Some(field) => error.with_field(field),
+            None => error,
+        }
+    }
+
+    /// A scan that failed on the roots it was given, e.g. a path that
+    /// does not exist.
+    fn unreadable_path(err: anyhow::Error) -> Self {
+        Self::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "unreadable_path",
+            format!("{err:#}"),
+        )
+        .with_field("paths")
+    }
+
+    fn task_failed(err: task::JoinError) -> Self {
+        Self::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal",
+            format!("task join error: {err}"),
+        )
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        let code = match rejection {
+            JsonRejection::JsonDataError(_) => "invalid_value",
+            JsonRejection::JsonSyntaxError(_) => "invalid_json",
+            JsonRejection::MissingJsonContentType(_) => "unsupported_media_type",
+            _ => "bad_request",
+        };
+        Self::new(rejection.status(), code, rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::new(rejection.status(), "invalid_value", rejection.body_text())
+    }
 }
 
-/// Handle a POST `/search` request and return matching fonts.
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        let code = match rejection {
+            PathRejection::FailedToDeserializePathParams(_) => "invalid_value",
+            _ => "internal",
+        };
+        Self::new(rejection.status(), code, rejection.body_text())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(&self)).into_response()
+    }
+}
+
+/// Handle a POST `/search` request and return matching fonts, logging it
+/// when the server has an access log. With `Accept: application/x-ndjson`
+/// or `text/event-stream` the matches are streamed (see the `stream`
+/// module).
 async fn search_handler(
-    Json(req): Json<SearchRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+    State(state): State<ServerState>,
+    extensions: Extensions,
+    headers: HeaderMap,
+    payload: Result<Json<SearchRequest>, JsonRejection>,
+) -> Result<Response, ApiError> {
+    let Json(req) = payload?;
+    let pending = state.access_log.clone().map(|log| PendingLog {
+        log,
+        client: extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| *addr),
+        forwarded_for: headers.clone(),
+        query: query_summary(&req),
+        started: Instant::now(),
+    });
+
+    let result = match stream::requested_format(&headers) {
+        Some(format) => match plan_search(&req, &state) {
+            Ok(plan) => return Ok(stream::stream_search(state, req, plan, format, pending)),
+            Err(err) => Err(err),
+        },
+        None => run_search(state, req).await,
+    };
+    if let Some(pending) = pending {
+        pending.finish(result.as_ref().map(|response| response.total));
+    }
+    result.map(|response| Json(response).into_response())
+}
+
+/// A `/search` request to be written to the access log once it finishes.
+struct PendingLog {
+    log: Arc<AccessLog>,
+    client: Option<SocketAddr>,
+    forwarded_for: HeaderMap,
+    query: Value,
+    started: Instant,
+}
+
+impl PendingLog {
+    /// Log the request with its match count, or the status it failed with.
+    fn finish(self, outcome: Result<usize, &ApiError>) {
+        self.log.record(&access_record(
+            self.client,
+            &self.forwarded_for,
+            self.query,
+            self.started.elapsed(),
+            outcome.map_err(|err| err.status),
+        ));
+    }
+}
+
+/// A `/search` request checked and parsed, ready to run.
+struct SearchPlan {
+    query: Query,
+    opts: SearchOptions,
+    /// The index to query, or `None` for a live scan.
+    #[cfg(feature = "hpindex")]
+    index_path: Option<PathBuf>,
+}
+
+/// Check a search request and parse its filters, with `jobs` held to the
+/// server's `max_jobs` and the index to one the server may open.
+fn plan_search(req: &SearchRequest, state: &ServerState) -> Result<SearchPlan, ApiError> {
     // Index mode searches the LMDB index and does not require paths.
     #[cfg(feature = "hpindex")]
     let needs_paths = !req.use_index;
@@ -109,113 +655,176 @@ async fn search_handler(
     let needs_paths = true;
 
     if needs_paths && req.paths.is_empty() {
-        return Err((
+        return Err(ApiError::new(
             StatusCode::BAD_REQUEST,
-            "at least one search path is required".to_string(),
-        ));
+            "missing_paths",
+            "at least one search path is required",
+        )
+        .with_field("paths"));
     }
 
-    if matches!(req.jobs, Some(0)) {
-        return Err((
+    let jobs = allowed_jobs(req.jobs, state.max_jobs)?;
+    let query = request_query(req)?;
+
+    #[cfg(feature = "hpindex")]
+    let index_path = match req.use_index {
+        true => Some(index::existing_index(state, &req.index_path)?),
+        false => None,
+    };
+
+    #[cfg(not(feature = "hpindex"))]
+    if req.use_index {
+        return Err(ApiError::new(
             StatusCode::BAD_REQUEST,
-            "jobs must be at least 1 when provided".to_string(),
-        ));
+            "index_unsupported",
+            "index search requires hpindex feature",
+        )
+        .with_field("use_index"));
     }
 
-    let query = build_query_from_parts(
+    let opts = SearchOptions {
+        follow_symlinks: req.follow_symlinks,
+        include_hidden: req.hidden,
+        jobs,
+        deadline: req
+            .timeout_ms
+            .map(|ms| Instant::now() + Duration::from_millis(ms)),
+        ..SearchOptions::default()
+    };
+    Ok(SearchPlan {
+        query,
+        opts,
+        #[cfg(feature = "hpindex")]
+        index_path,
+    })
+}
+
+/// Parse the filters of a search request into a query.
+fn request_query(req: &SearchRequest) -> Result<Query, ApiError> {
+    // Checked here so that a bad key fails before any scan starts.
+    sort_keys(req)?;
+    let preset = req
+        .preset
+        .as_deref()
+        .map(parse_preset)
+        .transpose()
+        .map_err(|err| ApiError::invalid_value(err).with_field("preset"))?;
+    build_query_from_parts(
         &req.axes,
         &req.features,
         &req.scripts,
+        &req.langs,
         &req.tables,
         &req.names,
+        req.name_match.into(),
         &req.creator,
         &req.license,
         &req.codepoints,
         &req.text,
+        &req.unicode_blocks,
+        &req.coverage,
         req.variable,
         &req.weight,
         &req.width,
         &req.family_class,
+        &req.embedding,
+        req.min_glyphs,
+        req.min_axes,
+        req.max_axes,
+        &req.vendors,
+        &req.instances,
+        req.only_collections,
+        req.collection_index,
+        req.unicode_cmap_only,
+        &req.query,
+        preset,
     )
-    .map_err(to_bad_request)?;
+    .and_then(|query| with_table_checksums(query, &req.table_checksums))
+    .and_then(|query| apply_exclusions(query, &req.exclude))
+    .map_err(ApiError::invalid_value)
+}
 
-    #[cfg(feature = "hpindex")]
-    if req.use_index {
-        let index_path = resolve_index_path(&req.index_path).map_err(to_bad_request)?;
-        let query_clone = query.clone();
+/// Run one search request.
+async fn run_search(state: ServerState, req: SearchRequest) -> Result<SearchResponse, ApiError> {
+    let plan = plan_search(&req, &state)?;
 
+    #[cfg(feature = "hpindex")]
+    if let Some(index_path) = plan.index_path {
         let matches = task::spawn_blocking(move || {
-            let index = FontIndex::open(&index_path)?;
+            let index = FontIndex::shared(&index_path, &state.index_options)?;
             let reader = index.reader()?;
-            reader.find(&query_clone)
+            reader.find(&plan.query)
         })
         .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("task join error: {e}"),
-            )
-        })?
-        .map_err(to_bad_request)?;
+        .map_err(ApiError::task_failed)?
+        .map_err(index::index_error)?;
 
-        return if req.paths_only {
-            let paths: Vec<String> = matches.iter().map(|m| m.source.path_with_index()).collect();
-            Ok(Json(SearchResponse {
-                matches: None,
-                paths: Some(paths),
-            }))
-        } else {
-            Ok(Json(SearchResponse {
-                matches: Some(matches),
-                paths: None,
-            }))
-        };
+        return Ok(SearchResponse::new(matches, &req));
     }
 
-    #[cfg(not(feature = "hpindex"))]
-    if req.use_index {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "index search requires hpindex feature".to_string(),
-        ));
-    }
+    let paths = req.paths.clone();
 
-    let opts = SearchOptions {
-        follow_symlinks: req.follow_symlinks,
-        jobs: req.jobs,
-    };
+    // Hold the slot until the scan finishes, even if the client hangs up.
+    let permit = scan_permit(&state).await?;
 
-    let paths = req.paths.clone();
-    let query_clone = query.clone();
-    let opts_clone = opts.clone();
+    let (matches, report) = task::spawn_blocking(move || {
+        let _permit = permit;
+        search_with_report(&paths, &plan.query, &plan.opts)
+    })
+    .await
+    .map_err(ApiError::task_failed)?
+    .map_err(ApiError::unreadable_path)?;
 
-    let matches = task::spawn_blocking(move || search(&paths, &query_clone, &opts_clone))
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("task join error: {e}"),
-            )
-        })?
-        .map_err(to_bad_request)?;
+    let mut response = SearchResponse::new(matches, &req);
+    response.truncated = report.truncated;
+    Ok(response)
+}
 
-    if req.paths_only {
-        let paths: Vec<String> = matches.iter().map(|m| m.source.path_with_index()).collect();
-        Ok(Json(SearchResponse {
-            matches: None,
-            paths: Some(paths),
-        }))
-    } else {
-        Ok(Json(SearchResponse {
-            matches: Some(matches),
-            paths: None,
-        }))
+/// The request's `sort` keys; empty for path order.
+fn sort_keys(req: &SearchRequest) -> Result<Vec<SortKey>, ApiError> {
+    req.sort
+        .as_deref()
+        .map(parse_sort_keys)
+        .transpose()
+        .map(Option::unwrap_or_default)
+        .map_err(|err| ApiError::invalid_value(err).with_field("sort"))
+}
+
+/// The `jobs` a live scan runs with: the request's, within the server's
+/// `--max-jobs`, which also fills it in when the request has none.
+fn allowed_jobs(jobs: Option<usize>, max_jobs: Option<usize>) -> Result<Option<usize>, ApiError> {
+    if matches!(jobs, Some(0)) {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_jobs",
+            "jobs must be at least 1 when provided",
+        )
+        .with_field("jobs"));
+    }
+    match (jobs, max_jobs) {
+        (Some(jobs), Some(max)) if jobs > max => Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_jobs",
+            format!("jobs must be at most {max} on this server"),
+        )
+        .with_field("jobs")),
+        (jobs, max) => Ok(jobs.or(max)),
     }
 }
 
-/// Convert an error into a 400 Bad Request response.
-fn to_bad_request(err: impl std::fmt::Display) -> (StatusCode, String) {
-    (StatusCode::BAD_REQUEST, err.to_string())
+/// Wait for a live-scan slot under `--max-scans`; `None` when scans are
+/// unlimited.
+async fn scan_permit(state: &ServerState) -> Result<Option<OwnedSemaphorePermit>, ApiError> {
+    match &state.scan_slots {
+        Some(slots) => slots.clone().acquire_owned().await.map(Some).map_err(|e| {
+            ApiError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "unavailable",
+                format!("scan slots closed: {e}"),
+            )
+        }),
+        None => Ok(None),
+    }
 }
 
 #[cfg(test)]
@@ -270,7 +879,7 @@ mod tests {
             None => return, // skip when fixtures are unavailable
         };
 
-        let app = router();
+        let app = router(ServerState::default());
         let payload = json!({
             "paths": [fonts],
             "scripts": ["latn"],
@@ -292,10 +901,115 @@ mod tests {
         assert!(paths.iter().any(|p| p.ends_with("NotoSans-Regular.ttf")));
     }
 
+    /// Verify that `offset` and `limit` page through matches and `total` counts them all.
+    #[tokio::test]
+    async fn search_endpoint_paginates_with_total() {
+        let fonts = match fonts_dir() {
+            Some(dir) => dir,
+            None => return, // skip when fixtures are unavailable
+        };
+
+        let search_page = |offset: usize, limit: usize| {
+            let payload = json!({
+                "paths": [fonts],
+                "paths_only": true,
+                "offset": offset,
+                "limit": limit
+            });
+            Request::post("/search")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap()
+        };
+
+        let app = router(ServerState::default());
+        let response = app.clone().oneshot(search_page(0, 1)).await.unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let first: SearchResponse = serde_json::from_slice(&body).expect("parse response");
+        let first_paths = first.paths.expect("paths response present");
+        assert_eq!(first_paths.len(), 1);
+        assert!(first.total > 1, "fixtures hold several fonts");
+
+        let response = app.oneshot(search_page(1, 1)).await.unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let second: SearchResponse = serde_json::from_slice(&body).expect("parse response");
+        assert_eq!(second.total, first.total);
+        assert_ne!(second.paths.expect("paths response present"), first_paths);
+    }
+
+    /// Verify that `sort` and `reverse` order matches before they are paged.
+    #[tokio::test]
+    async fn search_endpoint_sorts_before_paging() {
+        let fonts = match fonts_dir() {
+            Some(dir) => dir,
+            None => return, // skip when fixtures are unavailable
+        };
+
+        let app = router(ServerState::default());
+        let weights = |payload: Value| {
+            let app = app.clone();
+            async move {
+                let request = Request::post("/search")
+                    .header("content-type", "application/json")
+                    .body(Body::from(payload.to_string()))
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                let body = response.into_body().collect().await.unwrap().to_bytes();
+                let parsed: SearchResponse = serde_json::from_slice(&body).expect("parse response");
+                parsed
+                    .matches
+                    .expect("matches present")
+                    .iter()
+                    .map(|m| m.metadata.weight_class.unwrap_or(u16::MAX))
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        let ascending = weights(json!({"paths": [fonts], "sort": "weight,italic"})).await;
+        assert!(ascending.len() > 1, "fixtures hold several fonts");
+        assert!(ascending.is_sorted(), "{ascending:?}");
+
+        let heaviest =
+            weights(json!({"paths": [fonts], "sort": "weight", "reverse": true, "limit": 1})).await;
+        assert_eq!(heaviest, [*ascending.iter().max().unwrap()]);
+    }
+
+    /// Verify that a scan out of `timeout_ms` answers with what it has,
+    /// marked `truncated`.
+    #[tokio::test]
+    async fn search_endpoint_reports_a_truncated_scan() {
+        let fonts = match fonts_dir() {
+            Some(dir) => dir,
+            None => return, // skip when fixtures are unavailable
+        };
+
+        let search = |payload: Value| async move {
+            let request = Request::post("/search")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap();
+            let response = router(ServerState::default())
+                .oneshot(request)
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            serde_json::from_slice::<SearchResponse>(&body).expect("parse response")
+        };
+
+        let complete = search(json!({"paths": [fonts], "timeout_ms": 60_000})).await;
+        assert!(!complete.truncated);
+        assert!(complete.total > 0);
+
+        let late = search(json!({"paths": [fonts], "timeout_ms": 0})).await;
+        assert!(late.truncated);
+        assert_eq!(late.total, 0);
+    }
+
     /// Verify that a search request with no paths returns 400 Bad Request.
     #[tokio::test]
     async fn search_endpoint_requires_paths() {
-        let app = router();
+        let app = router(ServerState::default());
         let payload = json!({"paths": [], "scripts": ["latn"]});
 
         let request = Request::post("/search")
@@ -314,10 +1028,70 @@ mod tests {
         );
     }
 
+    /// Send a raw `/search` body and decode the structured error.
+    async fn search_error(body: &str) -> (StatusCode, ApiError) {
+        let request = Request::post("/search")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router(ServerState::default())
+            .oneshot(request)
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let error: ApiError = serde_json::from_slice(&body).expect("JSON error body");
+        (status, error)
+    }
+
+    /// Verify that errors are `{code, message, field}` JSON with 400 for
+    /// malformed requests and 422 for filter values that do not parse.
+    #[tokio::test]
+    async fn search_errors_are_structured() {
+        let (status, error) = search_error(r#"{"paths": ["/tmp"], "axes": ["toolong"]}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.code, "invalid_value");
+        assert_eq!(error.field.as_deref(), Some("axes"));
+
+        let (status, error) = search_error(r#"{"paths": ["/tmp"], "names": ["("]}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.field.as_deref(), Some("names"));
+        assert!(error.message.contains("regex"), "{}", error.message);
+
+        let (status, error) =
+            search_error(r#"{"paths": ["/tmp"], "not_scripts": ["x y z w"]}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.field.as_deref(), Some("not_scripts"));
+
+        let (status, error) =
+            search_error(r#"{"paths": ["/no/such/typg/dir"], "sort": "family,slant"}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.field.as_deref(), Some("sort"));
+
+        let (status, error) = search_error(r#"{"paths": ["/no/such/typg/dir"]}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.code, "unreadable_path");
+
+        let (status, error) = search_error(r#"{"paths": ["/tmp"], "jobs": "many"}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.code, "invalid_value");
+
+        let (status, error) = search_error("{not json").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.code, "invalid_json");
+
+        let (status, error) = search_error(r#"{"paths": []}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            (error.code.as_str(), error.field.as_deref()),
+            ("missing_paths", Some("paths"))
+        );
+    }
+
     /// Verify that `jobs: 0` returns 400 Bad Request.
     #[tokio::test]
     async fn search_endpoint_rejects_zero_jobs() {
-        let app = router();
+        let app = router(ServerState::default());
         let payload = json!({"paths": ["/tmp"], "jobs": 0});
 
         let request = Request::post("/search")
@@ -333,10 +1107,108 @@ mod tests {
         assert!(text.contains("jobs must be at least 1"), "body: {text}");
     }
 
+    /// A server allowing 4 jobs per request and one live scan at a time.
+    fn capped_state() -> ServerState {
+        ServerState {
+            max_jobs: Some(4),
+            scan_slots: Some(Arc::new(Semaphore::new(1))),
+            access_log: None,
+            font_roots: Arc::default(),
+            token: None,
+            cors_origins: Arc::default(),
+            search_slots: None,
+            request_timeout: None,
+            #[cfg(feature = "hpindex")]
+            index_options: IndexOptions::default(),
+            #[cfg(feature = "hpindex")]
+            index_paths: Arc::default(),
+            #[cfg(feature = "hpindex")]
+            index_admin: false,
+            #[cfg(feature = "hpindex")]
+            index_changes: Arc::default(),
+        }
+    }
+
+    /// Verify that `jobs` above the server's `max_jobs` returns 400 Bad Request.
+    #[tokio::test]
+    async fn search_endpoint_rejects_jobs_above_cap() {
+        let app = router(capped_state());
+        let payload = json!({"paths": ["/tmp"], "jobs": 256});
+
+        let request = Request::post("/search")
+            .header("content-type", "application/json")
+            .body(Body::from(payload.to_string()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(body.to_vec()).expect("utf8 body");
+        assert!(text.contains("jobs must be at most 4"), "body: {text}");
+
+        // Within the cap the scan takes the single slot and gives it back.
+        let dir = tempfile::tempdir().expect("tempdir");
+        let state = capped_state();
+        let slots = state.scan_slots.clone().unwrap();
+        let payload = json!({"paths": [dir.path()]});
+        let request = Request::post("/search")
+            .header("content-type", "application/json")
+            .body(Body::from(payload.to_string()))
+            .unwrap();
+        let response = router(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(slots.available_permits(), 1);
+    }
+
+    /// Verify that each `/search` request appends one NDJSON access log line.
+    #[tokio::test]
+    async fn search_requests_are_written_to_the_access_log() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let log_path = dir.path().join("access.ndjson");
+        let state = ServerState {
+            access_log: Some(Arc::new(AccessLog::open(&log_path).expect("open log"))),
+            ..ServerState::default()
+        };
+        let app = router(state);
+
+        for payload in [
+            json!({"paths": [dir.path()], "scripts": ["latn"], "jobs": 1}),
+            json!({"paths": []}),
+        ] {
+            let request = Request::post("/search")
+                .header("content-type", "application/json")
+                .header("x-forwarded-for", "203.0.113.7")
+                .body(Body::from(payload.to_string()))
+                .unwrap();
+            app.clone().oneshot(request).await.unwrap();
+        }
+
+        let log = std::fs::read_to_string(&log_path).expect("read log");
+        let records: Vec<serde_json::Value> = log
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("json line"))
+            .collect();
+        assert_eq!(records.len(), 2, "log: {log}");
+        assert_eq!(records[0]["status"], 200);
+        assert_eq!(records[0]["matches"], 0);
+        assert_eq!(records[0]["query"]["scripts"], json!(["latn"]));
+        assert_eq!(records[0]["query"]["jobs"], 1);
+        assert!(
+            records[0]["query"].get("variable").is_none(),
+            "defaults are left out"
+        );
+        assert_eq!(records[0]["forwarded_for"], "203.0.113.7");
+        assert!(records[0]["duration_ms"].is_number());
+        assert!(records[0]["timestamp"].as_str().unwrap().ends_with('Z'));
+        assert_eq!(records[1]["status"], 400);
+        assert!(records[1]["matches"].is_null());
+    }
+
     /// Verify that `GET /health` returns 200 OK with body `"ok"`.
     #[tokio::test]
     async fn health_endpoint_returns_ok() {
-        let app = router();
+        let app = router(ServerState::default());
         let request = Request::get("/health").body(Body::empty()).unwrap();
 
         let response = app.oneshot(request).await.unwrap();
@@ -346,14 +1218,85 @@ mod tests {
         assert_eq!(body.as_ref(), b"ok");
     }
 
+    fn tls_fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(format!("tests/fixtures/tls/{name}"))
+    }
+
+    /// Verify that `--tls-cert` and `--tls-key` must be readable PEM files
+    /// that belong together.
+    #[test]
+    fn tls_files_are_checked_at_startup() {
+        let (cert, key) = (tls_fixture("cert.pem"), tls_fixture("key.pem"));
+        assert!(tls::load(&cert, &key).is_ok());
+
+        let err = tls::load(&key, &key).unwrap_err();
+        assert!(err.to_string().contains("no PEM certificate"), "{err}");
+        let err = tls::load(&cert, &cert).unwrap_err();
+        assert!(err.to_string().contains("--tls-key"), "{err}");
+        let missing = tls_fixture("missing.pem");
+        assert!(tls::load(&missing, &key).is_err());
+    }
+
+    /// Verify that the server answers over HTTPS with `--tls-cert`.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serve_speaks_https_with_a_certificate() {
+        use std::io::Read;
+        use std::net::TcpStream;
+
+        let (cert, key) = (tls_fixture("cert.pem"), tls_fixture("key.pem"));
+        let config = tls::load(&cert, &key).unwrap();
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .unwrap()
+            .port();
+        let bind = format!("127.0.0.1:{port}");
+        tokio::spawn({
+            let bind = bind.clone();
+            async move { serve(&bind, ServerState::default(), Some(config)).await }
+        });
+
+        let body = task::spawn_blocking(move || {
+            let mut roots = rustls::RootCertStore::empty();
+            use rustls::pki_types::pem::PemObject;
+            for der in rustls::pki_types::CertificateDer::pem_file_iter(&cert).unwrap() {
+                roots.add(der.unwrap()).unwrap();
+            }
+            let provider = Arc::new(rustls::crypto::ring::default_provider());
+            let client = rustls::ClientConfig::builder_with_provider(provider)
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            let stream = (0..50)
+                .find_map(|_| {
+                    TcpStream::connect(&bind).ok().or_else(|| {
+                        std::thread::sleep(Duration::from_millis(20));
+                        None
+                    })
+                })
+                .expect("server listening");
+            let name = "localhost".try_into().unwrap();
+            let conn = rustls::ClientConnection::new(Arc::new(client), name).unwrap();
+            let mut tls = rustls::StreamOwned::new(conn, stream);
+            tls.write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            let mut response = Vec::new();
+            // Servers may close without a TLS close_notify; what arrived counts.
+            let _ = tls.read_to_end(&mut response);
+            String::from_utf8_lossy(&response).into_owned()
+        })
+        .await
+        .unwrap();
+        assert!(body.starts_with("HTTP/1.1 200"), "{body}");
+        assert!(body.ends_with("ok"), "{body}");
+    }
+
     /// Build a temporary LMDB index, populate it with test fonts, and verify the HTTP search endpoint returns results.
     #[cfg(feature = "hpindex")]
     #[tokio::test]
     async fn search_endpoint_with_index() {
-        use crate::resolve_index_path;
         use std::fs;
         use std::time::SystemTime;
-        use typg_core::discovery::{FontDiscovery, PathDiscovery};
         use typg_core::index::FontIndex;
         use typg_core::query::Query;
         use typg_core::search::{search, SearchOptions};
@@ -366,11 +1309,8 @@ mod tests {
         let index_dir = tempfile::TempDir::new().unwrap();
         let index_path = index_dir.path().to_path_buf();
 
-        let discovery = PathDiscovery::new([fonts.clone()]);
-        let font_sources = discovery.discover().unwrap();
-
         let all_matches = search(
-            &[fonts.clone()],
+            std::slice::from_ref(&fonts),
             &Query::default(),
             &SearchOptions::default(),
         )
@@ -386,12 +1326,16 @@ mod tests {
                 &m.source.path,
                 m.source.ttc_index,
                 mtime,
-                m.metadata.names.clone(),
+                m.metadata
+                    .names
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect(),
                 &m.metadata.axis_tags,
                 &m.metadata.feature_tags,
                 &m.metadata.script_tags,
                 &m.metadata.table_tags,
-                &m.metadata.codepoints.iter().copied().collect::<Vec<_>>(),
+                &m.metadata.codepoints,
                 m.metadata.is_variable,
                 m.metadata.weight_class,
                 m.metadata.width_class,
@@ -401,7 +1345,7 @@ mod tests {
         writer.commit().unwrap();
         drop(index);
 
-        let app = router();
+        let app = router(index_state(&index_path));
         let payload = json!({
             "use_index": true,
             "index_path": index_path,
@@ -425,4 +1369,993 @@ mod tests {
             "expected at least one result from index search"
         );
     }
+
+    /// A server that lets requests name the index at `index`.
+    #[cfg(feature = "hpindex")]
+    fn index_state(index: &Path) -> ServerState {
+        ServerState {
+            index_paths: Arc::from([index.to_path_buf()]),
+            ..ServerState::default()
+        }
+    }
+
+    #[cfg(feature = "hpindex")]
+    fn index_admin_state(index: &Path) -> ServerState {
+        ServerState {
+            index_admin: true,
+            ..index_state(index)
+        }
+    }
+
+    /// Send `request` and return the status with the JSON body.
+    async fn call(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).expect("json body"))
+    }
+
+    #[cfg(feature = "hpindex")]
+    fn post_json(uri: &str, payload: Value) -> Request<Body> {
+        Request::post(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(payload.to_string()))
+            .unwrap()
+    }
+
+    /// Verify that index changes are refused without `--index-admin`, while
+    /// `GET /index/info` still answers.
+    #[cfg(feature = "hpindex")]
+    #[tokio::test]
+    async fn index_changes_need_index_admin() {
+        let index_dir = tempfile::TempDir::new().unwrap();
+        let app = router(index_state(index_dir.path()));
+
+        let add = json!({"paths": [index_dir.path()], "index_path": index_dir.path()});
+        let (status, body) = call(&app, post_json("/index/add", add)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "index_admin_disabled");
+
+        let uri = format!("/index?index_path={}", index_dir.path().display());
+        let (status, _) = call(&app, Request::delete(uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let uri = format!("/index/info?index_path={}", index_dir.path().display());
+        let (status, _) = call(&app, Request::get(uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    /// Add fonts to a fresh index, inspect it, clean it and empty it.
+    #[cfg(feature = "hpindex")]
+    #[tokio::test]
+    async fn index_endpoints_manage_an_index() {
+        let scratch = tempfile::TempDir::new().unwrap();
+        let index_path = scratch.path().join("index");
+        // Without fixtures the cycle still runs, over a folder with no fonts.
+        let fonts = fonts_dir().unwrap_or_else(|| scratch.path().to_path_buf());
+        let app = router(index_admin_state(&index_path));
+        let info_uri = format!("/index/info?index_path={}", index_path.display());
+
+        let (status, info) = call(&app, Request::get(&info_uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(info["exists"], false);
+
+        let (status, body) = call(
+            &app,
+            post_json("/index/clean", json!({"index_path": index_path})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "index_not_found");
+
+        let add = json!({"paths": [fonts], "index_path": index_path});
+        let (status, added) = call(&app, post_json("/index/add", add.clone())).await;
+        assert_eq!(status, StatusCode::OK, "body: {added}");
+        let faces = added["faces"].as_u64().unwrap();
+        assert_eq!(added["added"].as_u64(), Some(faces));
+
+        // A second pass finds every file unchanged.
+        let (_, again) = call(&app, post_json("/index/add", add)).await;
+        assert_eq!(again["added"], 0);
+        assert_eq!(again["skipped"].as_u64(), Some(faces));
+
+        let (_, info) = call(&app, Request::get(&info_uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(info["exists"], true);
+        assert_eq!(info["faces"].as_u64(), Some(faces));
+        assert!(info["size_bytes"].as_u64().unwrap() > 0);
+        assert!(info["databases"]
+            .as_object()
+            .is_some_and(|dbs| !dbs.is_empty()));
+
+        let (status, cleaned) = call(
+            &app,
+            post_json("/index/clean", json!({"index_path": index_path})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(cleaned["removed"], 0);
+        assert_eq!(cleaned["faces"].as_u64(), Some(faces));
+
+        let uri = format!("/index?index_path={}", index_path.display());
+        let (status, deleted) = call(&app, Request::delete(uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(deleted["removed"].as_u64(), Some(faces));
+        assert_eq!(deleted["faces"], 0);
+
+        let (_, info) = call(&app, Request::get(&info_uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(info["faces"], 0);
+    }
+
+    /// Verify that requests cannot open or create indexes the server was
+    /// not started with, and that searches never create one.
+    #[cfg(feature = "hpindex")]
+    #[tokio::test]
+    async fn requests_only_reach_configured_indexes() {
+        let scratch = tempfile::TempDir::new().unwrap();
+        let listed = scratch.path().join("listed");
+        let other = scratch.path().join("other");
+        let app = router(index_admin_state(&listed));
+
+        let search = json!({"use_index": true, "index_path": other});
+        let (status, body) = call(&app, post_json("/search", search)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "index_not_allowed");
+        let add = json!({"paths": [scratch.path()], "index_path": other});
+        let (status, _) = call(&app, post_json("/index/add", add)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let uri = format!("/index/info?index_path={}", other.display());
+        let (status, _) = call(&app, Request::get(uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(!other.exists());
+
+        let search = json!({"use_index": true, "index_path": listed});
+        let (status, body) = call(&app, post_json("/search", search)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "index_not_found");
+        assert!(!listed.exists());
+    }
+
+    /// Verify that a change arriving while another runs gets 409 Conflict.
+    #[cfg(feature = "hpindex")]
+    #[tokio::test]
+    async fn index_change_conflicts_with_one_in_progress() {
+        let index_dir = tempfile::TempDir::new().unwrap();
+        let state = index_admin_state(index_dir.path());
+        let _running = state.index_changes.clone().try_lock_owned().unwrap();
+        let app = router(state);
+
+        let clean = json!({"index_path": index_dir.path()});
+        let (status, body) = call(&app, post_json("/index/clean", clean)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "index_busy");
+    }
+
+    /// Look up a face whose file is gone: the index's record comes back,
+    /// marked as not live.
+    #[cfg(feature = "hpindex")]
+    #[tokio::test]
+    async fn fonts_endpoint_falls_back_to_the_index_record() {
+        use std::time::SystemTime;
+
+        let index_dir = tempfile::TempDir::new().unwrap();
+        let font = index_dir.path().join("Gone.ttf");
+        let id = {
+            let index = FontIndex::shared(index_dir.path(), &IndexOptions::default()).unwrap();
+            let mut writer = index.writer().unwrap();
+            let id = writer
+                .add_font(
+                    &font,
+                    None,
+                    SystemTime::UNIX_EPOCH,
+                    vec!["Gone Sans".to_string()],
+                    &[],
+                    &[],
+                    &[],
+                    &[],
+                    &['a'],
+                    false,
+                    Some(400),
+                    None,
+                    None,
+                )
+                .unwrap();
+            writer.commit().unwrap();
+            id
+        };
+        let app = router(index_state(index_dir.path()));
+        let at = format!("index_path={}", index_dir.path().display());
+
+        let uri = format!("/fonts/{id}?{at}");
+        let (status, detail) = call(&app, Request::get(uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK, "body: {detail}");
+        assert_eq!(detail["index_id"], id.0);
+        assert_eq!(detail["live"], false);
+        assert!(detail["details"].is_null());
+        assert!(detail["read_error"].is_string());
+        assert_eq!(detail["metadata"]["names"][0], "Gone Sans");
+
+        let uri = format!("/fonts?path={}&{at}", font.display());
+        let (status, faces) = call(&app, Request::get(uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(faces.as_array().map(Vec::len), Some(1));
+
+        let uri = format!("/fonts/{}?{at}", id.0 + 1);
+        let (status, body) = call(&app, Request::get(uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "font_not_found");
+
+        let uri = format!("/fonts/latest?{at}");
+        let response = app
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    /// Verify that `/fonts/{id}` reads the file for names, tables and
+    /// named instances.
+    #[cfg(feature = "hpindex")]
+    #[tokio::test]
+    async fn fonts_endpoint_reads_the_file() {
+        let Some(fonts) = fonts_dir() else {
+            return; // skip when fixtures are unavailable
+        };
+        let index_dir = tempfile::TempDir::new().unwrap();
+        let app = router(index_admin_state(index_dir.path()));
+        let at = format!("index_path={}", index_dir.path().display());
+        let add = json!({"paths": [fonts], "index_path": index_dir.path()});
+        let (status, _) = call(&app, post_json("/index/add", add)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let index = FontIndex::shared(index_dir.path(), &IndexOptions::default()).unwrap();
+        let stored = index.reader().unwrap().list_all().unwrap();
+        let id = stored[0].metadata.index_id.unwrap();
+
+        let uri = format!("/fonts/{id}?{at}");
+        let (status, detail) = call(&app, Request::get(uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(detail["live"], true);
+        assert_eq!(detail["font_id"], stored[0].font_id());
+        assert!(detail["metadata"]["named_instances"].is_array());
+        assert!(!detail["details"]["names"].as_array().unwrap().is_empty());
+        assert!(!detail["details"]["tables"].as_array().unwrap().is_empty());
+    }
+
+    fn font_file_state(roots: &[&Path]) -> ServerState {
+        let roots = roots.iter().map(|root| root.canonicalize().unwrap());
+        ServerState {
+            font_roots: roots.collect(),
+            ..ServerState::default()
+        }
+    }
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::get(uri).body(Body::empty()).unwrap()
+    }
+
+    /// Verify that `/font-file` is off without `--serve-root`, and only
+    /// serves fonts under the served roots.
+    #[tokio::test]
+    async fn font_file_is_limited_to_served_roots() {
+        let root = tempfile::tempdir().expect("tempdir");
+        let outside = tempfile::tempdir().expect("tempdir");
+        std::fs::write(root.path().join("notes.ttf"), b"not a font").unwrap();
+        std::fs::write(outside.path().join("secret.ttf"), b"not a font").unwrap();
+
+        let uri = format!("/font-file?path={}/notes.ttf", root.path().display());
+        let (status, body) = call(&router(ServerState::default()), get(&uri)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "font_files_disabled");
+
+        let app = router(font_file_state(&[root.path()]));
+        let (status, body) = call(&app, get("/font-file")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "missing_path");
+
+        let (status, body) = call(&app, get(&uri)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "not_a_font");
+
+        // Outside the root, by name or by `..`, looks like a missing file.
+        let secret = outside.path().join("secret.ttf");
+        let escape = root
+            .path()
+            .join("..")
+            .join(secret.strip_prefix("/").unwrap());
+        for path in [secret, escape, root.path().join("missing.ttf")] {
+            let uri = format!("/font-file?path={}", path.display());
+            let (status, body) = call(&app, get(&uri)).await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{}", path.display());
+            assert_eq!(body["code"], "file_not_found");
+        }
+    }
+
+    /// Verify that `/font-file` sends the file with its type and an ETag,
+    /// and answers a matching `If-None-Match` with 304.
+    #[tokio::test]
+    async fn font_file_serves_fonts_with_an_etag() {
+        let Some(fonts) = fonts_dir() else {
+            return; // skip when fixtures are unavailable
+        };
+        let font = std::fs::read_dir(&fonts)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "ttf"))
+            .expect("a .ttf fixture");
+        let app = router(font_file_state(&[&fonts]));
+        let uri = format!("/font-file?path={}", font.display());
+
+        let response = app.clone().oneshot(get(&uri)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers().clone();
+        assert_eq!(headers["content-type"], "font/ttf");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, std::fs::read(&font).unwrap());
+
+        let request = Request::get(&uri)
+            .header("if-none-match", headers["etag"].clone())
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let (status, body) = call(&app, get(&format!("{uri}&face=1"))).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["field"], "face");
+
+        let response = app
+            .clone()
+            .oneshot(get(&format!("{uri}&format=woff2")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "font/woff2");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(body.starts_with(b"wOF2"));
+    }
+
+    /// Verify that a font packed as WOFF2 unpacks to the same tables, with
+    /// unknown tags spelled out and `glyf`/`loca` left untransformed.
+    #[test]
+    fn woff2_keeps_every_table() {
+        let tables: [(&[u8; 4], &[u8]); 4] = [
+            (b"glyf", b"glyph"),
+            (b"head", &[7; 54]),
+            (b"loca", &[0, 0, 0, 5]),
+            (b"zzzz", &[1, 2, 3]),
+        ];
+        let mut sfnt = vec![0, 1, 0, 0, 0, 4, 0, 64, 0, 2, 0, 0];
+        let mut offset = 12 + 16 * tables.len();
+        for (tag, data) in &tables {
+            sfnt.extend(*tag);
+            sfnt.extend([0; 4]);
+            sfnt.extend((offset as u32).to_be_bytes());
+            sfnt.extend((data.len() as u32).to_be_bytes());
+            offset += data.len().next_multiple_of(4);
+        }
+        for (_, data) in &tables {
+            sfnt.extend(*data);
+            sfnt.resize(sfnt.len().next_multiple_of(4), 0);
+        }
+
+        let packed = woff2::encode(&sfnt).expect("encode");
+        let be32 = |at: usize| u32::from_be_bytes(packed[at..at + 4].try_into().unwrap());
+        assert_eq!(&packed[..8], b"wOF2\0\x01\0\0");
+        assert_eq!(be32(8) as usize, packed.len());
+        assert_eq!(packed.len() % 4, 0);
+        assert_eq!(u16::from_be_bytes([packed[12], packed[13]]), 4);
+        assert_eq!(be32(16) as usize, sfnt.len());
+
+        // glyf (code 10) and loca (11) carry the null transform 3; the
+        // unknown tag follows code 63. Every length fits one base-128 byte.
+        let directory = [
+            &[10 | 0xc0, 5][..],
+            &[1, 54],
+            &[11 | 0xc0, 4],
+            &[63],
+            b"zzzz",
+            &[3],
+        ]
+        .concat();
+        assert_eq!(&packed[48..48 + directory.len()], directory);
+        let start = 48 + directory.len();
+        let compressed = &packed[start..start + be32(20) as usize];
+        let mut stream = Vec::new();
+        brotli::BrotliDecompress(&mut &compressed[..], &mut stream).expect("decompress");
+        let expected: Vec<u8> = tables.iter().flat_map(|(_, data)| data.to_vec()).collect();
+        assert_eq!(stream, expected);
+    }
+
+    /// Verify that a face copied out of a font keeps every table and gets
+    /// a valid `head` checksum adjustment.
+    #[test]
+    fn extracted_face_keeps_its_tables() {
+        let Some(fonts) = fonts_dir() else {
+            return; // skip when fixtures are unavailable
+        };
+        let font = std::fs::read_dir(&fonts)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "ttf"))
+            .expect("a .ttf fixture");
+        let data = std::fs::read(&font).unwrap();
+        let extracted = files::extract_face(&data, 0).expect("extract face");
+
+        let original = read_fonts::FontRef::new(&data).unwrap();
+        let copy = read_fonts::FontRef::new(&extracted).expect("parse extracted face");
+        let records = original.table_directory.table_records();
+        assert_eq!(copy.table_directory.table_records().len(), records.len());
+        for record in records {
+            let tag = record.tag();
+            if tag != read_fonts::types::Tag::new(b"head") {
+                assert_eq!(
+                    copy.table_data(tag).unwrap().as_bytes(),
+                    original.table_data(tag).unwrap().as_bytes(),
+                    "{tag}"
+                );
+            }
+        }
+        let sum = extracted.chunks(4).fold(0u32, |sum, chunk| {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            sum.wrapping_add(u32::from_be_bytes(word))
+        });
+        assert_eq!(sum, 0xB1B0_AFBA);
+    }
+
+    /// Verify that a face with more table records than the directory's
+    /// search fields can describe is still copied.
+    #[test]
+    fn extracted_face_survives_thousands_of_tables() {
+        let count: u16 = 5000;
+        let mut data = vec![0, 1, 0, 0];
+        data.extend(count.to_be_bytes());
+        data.extend([0; 6]);
+        let end = 12 + 16 * u32::from(count);
+        for n in 0..u32::from(count) {
+            data.push(b'x');
+            data.extend(&n.to_be_bytes()[1..]);
+            data.extend([0; 4]);
+            data.extend(end.to_be_bytes());
+            data.extend([0; 4]);
+        }
+
+        let extracted = files::extract_face(&data, 0).expect("extract face");
+        let copy = read_fonts::FontRef::new(&extracted).expect("parse extracted face");
+        assert_eq!(copy.table_directory.num_tables(), count);
+        assert_eq!(copy.table_directory.search_range(), u16::MAX);
+        assert_eq!(copy.table_directory.entry_selector(), 12);
+        // 5000 * 16 - 4096 * 16
+        assert_eq!(copy.table_directory.range_shift(), 14464);
+    }
+
+    fn search_stream(payload: Value, accept: &str) -> Request<Body> {
+        Request::post("/search")
+            .header("content-type", "application/json")
+            .header("accept", accept)
+            .body(Body::from(payload.to_string()))
+            .unwrap()
+    }
+
+    /// Verify that `Accept: application/x-ndjson` streams one event per
+    /// line, ending with `done`, and that bad requests still fail up front.
+    #[tokio::test]
+    async fn search_streams_ndjson_events() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let app = router(ServerState::default());
+
+        let request = search_stream(json!({"paths": [dir.path()]}), "application/x-ndjson");
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/x-ndjson");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let events: Vec<Value> = body
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).expect("json line"))
+            .collect();
+        assert_eq!(events.len(), 1, "events: {events:?}");
+        assert_eq!(events[0]["type"], "done");
+        assert_eq!(events[0]["total"], 0);
+
+        let request = search_stream(json!({"paths": []}), "application/x-ndjson");
+        let (status, body) = call(&app, request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "missing_paths");
+    }
+
+    /// Verify that streamed matches honour `paths_only` and `limit`, and
+    /// that `text/event-stream` names each event.
+    #[tokio::test]
+    async fn search_streams_matches_as_server_sent_events() {
+        let Some(fonts) = fonts_dir() else {
+            return; // skip when fixtures are unavailable
+        };
+        let app = router(ServerState::default());
+        let payload = json!({"paths": [fonts], "paths_only": true, "limit": 1});
+
+        let request = search_stream(payload.clone(), "application/x-ndjson");
+        let response = app.clone().oneshot(request).await.unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let events: Vec<stream::SearchEvent> = body
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).expect("event"))
+            .collect();
+        assert!(matches!(&events[..], [
+            stream::SearchEvent::Path { .. },
+            stream::SearchEvent::Done { total, .. },
+        ] if *total > 1));
+
+        let request = search_stream(payload, "text/event-stream");
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(body.to_vec()).expect("utf8 body");
+        assert!(text.starts_with("event: path\n"), "body: {text}");
+        assert!(text.contains("event: done\ndata: {\"type\":\"done\""));
+    }
+
+    /// Verify that a quiet stream sends heartbeats and ends with its channel.
+    #[tokio::test]
+    async fn event_stream_sends_heartbeats_while_quiet() {
+        use futures_util::StreamExt;
+
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let events = stream::with_heartbeats(rx, Instant::now(), Duration::from_millis(10));
+        let mut events = std::pin::pin!(events);
+        let first = events.next().await.expect("heartbeat");
+        assert!(matches!(first, stream::SearchEvent::Heartbeat { .. }));
+
+        let path = "/fonts/A.ttf".to_string();
+        tx.send(stream::SearchEvent::Path { path }).await.unwrap();
+        drop(tx);
+        let second = events.next().await.expect("path");
+        assert!(matches!(second, stream::SearchEvent::Path { .. }));
+        assert!(events.next().await.is_none());
+    }
+
+    /// Verify that `--token` guards every endpoint but `/health`.
+    #[tokio::test]
+    async fn token_is_required_everywhere_but_health() {
+        let app = router(ServerState {
+            token: Some(Arc::from("s3cret")),
+            ..ServerState::default()
+        });
+        let search = |authorization: Option<&str>| {
+            let mut request = Request::post("/search").header("content-type", "application/json");
+            if let Some(value) = authorization {
+                request = request.header("authorization", value);
+            }
+            request
+                .body(Body::from(json!({"paths": []}).to_string()))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(get("/health")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        for authorization in [None, Some("Bearer wrong"), Some("Basic s3cret")] {
+            let response = app.clone().oneshot(search(authorization)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(response.headers()["www-authenticate"], "Bearer");
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let body: Value = serde_json::from_slice(&body).expect("json body");
+            assert_eq!(body["code"], "unauthorized");
+        }
+        let (status, _) = call(&app, get("/font-file")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // Past the check, the request itself is looked at.
+        let (status, body) = call(&app, search(Some("bearer s3cret"))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "missing_paths");
+    }
+
+    /// Verify that allowed origins get CORS headers and preflight answers,
+    /// even from a server that requires a token, and others get neither.
+    #[tokio::test]
+    async fn cors_headers_go_to_allowed_origins_only() {
+        let app = router(ServerState {
+            token: Some(Arc::from("s3cret")),
+            cors_origins: Arc::from(["https://fonts.example.com".to_string()]),
+            ..ServerState::default()
+        });
+        let preflight = |origin: &str| {
+            Request::options("/search")
+                .header("origin", origin)
+                .header("access-control-request-method", "POST")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(preflight("https://fonts.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let headers = response.headers();
+        assert_eq!(
+            headers["access-control-allow-origin"],
+            "https://fonts.example.com"
+        );
+        assert!(headers["access-control-allow-headers"]
+            .to_str()
+            .unwrap()
+            .contains("authorization"));
+        assert_eq!(headers["vary"], "origin");
+
+        let response = app
+            .clone()
+            .oneshot(preflight("https://evil.example.com"))
+            .await
+            .unwrap();
+        assert_ne!(response.status(), StatusCode::NO_CONTENT);
+        assert!(!response
+            .headers()
+            .contains_key("access-control-allow-origin"));
+
+        // Errors reach the browser too, so the app can read them.
+        let request = Request::get("/font-file")
+            .header("origin", "https://fonts.example.com")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers()["access-control-allow-origin"],
+            "https://fonts.example.com"
+        );
+
+        let app = router(ServerState {
+            cors_origins: Arc::from(["*".to_string()]),
+            ..ServerState::default()
+        });
+        let response = app
+            .oneshot(preflight("http://localhost:3000"))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["access-control-allow-origin"], "*");
+    }
+
+    /// Verify that searches beyond `--max-concurrent-searches` get 429.
+    #[tokio::test]
+    async fn searches_beyond_the_limit_are_refused() {
+        let slots = Arc::new(Semaphore::new(1));
+        let app = router(ServerState {
+            search_slots: Some(slots.clone()),
+            ..ServerState::default()
+        });
+        let search = || {
+            Request::post("/search")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({"paths": []}).to_string()))
+                .unwrap()
+        };
+
+        let busy = slots.clone().try_acquire_owned().unwrap();
+        let response = app.clone().oneshot(search()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["retry-after"], "1");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&body).expect("json body");
+        assert_eq!(body["code"], "too_many_searches");
+
+        drop(busy);
+        let (status, body) = call(&app, search()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "missing_paths");
+        assert_eq!(slots.available_permits(), 1);
+    }
+
+    /// Verify that a request still waiting when `--request-timeout` runs
+    /// out gets 503.
+    #[tokio::test]
+    async fn slow_requests_time_out() {
+        let state = ServerState {
+            request_timeout: Some(Duration::from_millis(50)),
+            ..capped_state()
+        };
+        // Hold the only scan slot, so the search waits for it.
+        let _busy = state
+            .scan_slots
+            .clone()
+            .unwrap()
+            .try_acquire_owned()
+            .unwrap();
+        let dir = tempfile::TempDir::new().unwrap();
+        let request = Request::post("/search")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({"paths": [dir.path()]}).to_string()))
+            .unwrap();
+
+        let (status, body) = call(&router(state), request).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["code"], "request_timeout");
+    }
+
+    /// The property names of schema `name` in the OpenAPI document.
+    fn schema_properties(doc: &Value, name: &str) -> Vec<String> {
+        let mut names: Vec<String> = doc["components"]["schemas"][name]["properties"]
+            .as_object()
+            .unwrap_or_else(|| panic!("schema {name}"))
+            .keys()
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// The field names `value` serializes with.
+    fn serialized_fields(value: impl Serialize) -> Vec<String> {
+        let mut names: Vec<String> = serde_json::to_value(value)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Verify that the OpenAPI schemas list exactly the fields the types
+    /// serialize, so the document cannot fall behind the code.
+    #[test]
+    fn openapi_schemas_match_the_types() {
+        let doc = openapi::document(false);
+        assert_eq!(
+            schema_properties(&doc, "SearchRequest"),
+            serialized_fields(SearchRequest::default())
+        );
+        assert_eq!(
+            schema_properties(&doc, "ApiError"),
+            serialized_fields(ApiError::new(StatusCode::BAD_REQUEST, "x", "y"))
+        );
+        let response = SearchResponse {
+            matches: None,
+            paths: None,
+            total: 0,
+            truncated: false,
+        };
+        assert_eq!(
+            schema_properties(&doc, "SearchResponse"),
+            serialized_fields(response)
+        );
+
+        let face: TypgFontFaceMatch = serde_json::from_value(json!({
+            "source": {"path": "/fonts/A.ttf", "ttc_index": null},
+            "metadata": {
+                "names": ["A"], "axis_tags": [], "feature_tags": [], "script_tags": [],
+                "table_tags": [], "codepoints": [], "is_variable": false, "index_id": 7,
+                "extra": {"x": 1},
+            },
+        }))
+        .unwrap();
+        assert_eq!(
+            schema_properties(&doc, "TypgFontFaceMeta"),
+            serialized_fields(&face.metadata)
+        );
+        assert_eq!(
+            schema_properties(&doc, "TypgFontFaceMatch"),
+            serialized_fields(&face)
+        );
+
+        #[cfg(feature = "hpindex")]
+        {
+            use index::{IndexAddRequest, IndexChange, IndexCleanRequest, IndexInfo};
+            assert_eq!(
+                schema_properties(&doc, "IndexAddRequest"),
+                serialized_fields(IndexAddRequest::default())
+            );
+            assert_eq!(
+                schema_properties(&doc, "IndexCleanRequest"),
+                serialized_fields(IndexCleanRequest::default())
+            );
+            let change = IndexChange {
+                added: Some(1),
+                skipped: Some(1),
+                errors: Some(1),
+                removed: Some(1),
+                ..IndexChange::default()
+            };
+            assert_eq!(
+                schema_properties(&doc, "IndexChange"),
+                serialized_fields(change)
+            );
+            assert_eq!(
+                schema_properties(&doc, "IndexInfo"),
+                serialized_fields(IndexInfo::default())
+            );
+        }
+    }
+
+    /// Verify that `/openapi.json` and `/docs` need no token, and that the
+    /// document then marks the other endpoints as needing it.
+    #[tokio::test]
+    async fn openapi_document_is_served_openly() {
+        let app = router(ServerState {
+            token: Some(Arc::from("s3cret")),
+            ..ServerState::default()
+        });
+        let (status, doc) = call(&app, get("/openapi.json")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(doc["openapi"], "3.0.3");
+        assert_eq!(doc["security"], json!([{"bearer": []}]));
+        assert_eq!(doc["paths"]["/health"]["get"]["security"], json!([]));
+        assert_eq!(
+            doc["paths"]["/search"]["post"]["requestBody"]["content"]["application/json"]["schema"]
+                ["$ref"],
+            "#/components/schemas/SearchRequest"
+        );
+        assert_eq!(
+            doc["paths"]["/index/add"].is_object(),
+            cfg!(feature = "hpindex")
+        );
+
+        let response = app.clone().oneshot(get("/docs")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let page = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(String::from_utf8_lossy(&page).contains("SwaggerUIBundle"));
+    }
+
+    #[test]
+    fn table_checksums_reach_the_request_query() {
+        let req = SearchRequest {
+            table_checksums: vec!["head=0xDEADBEEF".into()],
+            ..SearchRequest::default()
+        };
+        let query = request_query(&req).unwrap();
+        assert_eq!(
+            query.table_checksums(),
+            [(typg_core::tags::tag4("head").unwrap(), 0xDEAD_BEEF)]
+        );
+
+        let req = SearchRequest {
+            table_checksums: vec!["head".into()],
+            ..SearchRequest::default()
+        };
+        let err = request_query(&req).unwrap_err();
+        assert_eq!(err.field.as_deref(), Some("table_checksums"));
+    }
+
+    #[test]
+    fn same_secret_compares_whole_secrets() {
+        assert!(same_secret(b"s3cret", b"s3cret"));
+        assert!(!same_secret(b"s3cret", b"s3creT"));
+        assert!(!same_secret(b"s3cret", b"s3cre"));
+        assert!(!same_secret(b"", b"s3cret"));
+    }
+
+    /// Drive `serve --mcp` through a session: handshake, tool list, a
+    /// search, tool errors and protocol errors.
+    #[test]
+    fn mcp_session_answers_tools_and_errors() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("cache.json");
+        std::fs::write(&cache, "[]").unwrap();
+        let messages = [
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize",
+                   "params": {"protocolVersion": "2024-11-05"}}),
+            json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}),
+            json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call",
+                   "params": {"name": "find", "arguments": {"paths": [dir.path()]}}}),
+            json!({"jsonrpc": "2.0", "id": 4, "method": "tools/call",
+                   "params": {"name": "find", "arguments": {"weight": "heavy"}}}),
+            json!({"jsonrpc": "2.0", "id": 5, "method": "tools/call",
+                   "params": {"name": "cache_find", "arguments": {"cache_path": cache}}}),
+            json!({"jsonrpc": "2.0", "id": 6, "method": "tools/call",
+                   "params": {"name": "nope"}}),
+            json!({"jsonrpc": "2.0", "id": 7, "method": "resources/list"}),
+        ];
+        let mut input: String = messages.iter().map(|m| format!("{m}\n")).collect();
+        input.push_str("not json\n");
+        let mut output = Vec::new();
+
+        mcp::serve_lines(
+            &ServerState::default(),
+            runtime.handle(),
+            input.as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+
+        let answers: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(answers.len(), 8, "the notification gets no answer");
+        assert_eq!(answers[0]["result"]["protocolVersion"], "2024-11-05");
+        let tools: Vec<&str> = answers[1]["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(tools, ["find", "cache_find", "inspect"]);
+        let schema = &answers[1]["result"]["tools"][0]["inputSchema"];
+        assert_eq!(
+            schema["properties"]["weight"]["type"],
+            json!(["string", "null"])
+        );
+
+        assert_eq!(answers[2]["result"]["isError"], false);
+        assert_eq!(answers[2]["result"]["structuredContent"]["total"], 0);
+        assert_eq!(answers[3]["result"]["isError"], true);
+        let error: Value =
+            serde_json::from_str(answers[3]["result"]["content"][0]["text"].as_str().unwrap())
+                .unwrap();
+        assert_eq!(error["code"], "missing_paths");
+        assert_eq!(
+            answers[4]["result"]["structuredContent"]["matches"],
+            json!([])
+        );
+
+        assert_eq!(answers[5]["error"]["code"], -32602);
+        assert_eq!(answers[6]["error"]["code"], -32601);
+        assert_eq!(answers[7]["error"]["code"], -32700);
+        assert_eq!(answers[7]["id"], Value::Null);
+    }
+
+    /// Answer daemon requests from a cache: filters, path narrowing, errors,
+    /// and a fresh read once the cache file changes.
+    #[test]
+    fn daemon_answers_from_its_cache_and_rereads_it() {
+        let Some(fonts) = fonts_dir() else {
+            return;
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("cache.json");
+        let entries = typg_core::search::search(
+            std::slice::from_ref(&fonts),
+            &Query::new(),
+            &SearchOptions::default(),
+        )
+        .unwrap();
+        typg_core::cache::write_cache(&cache, &entries).unwrap();
+        let store = daemon::Store::cache(&cache).unwrap();
+        let ask = |lines: &[String]| -> Vec<Value> {
+            let input: String = lines.iter().map(|line| format!("{line}\n")).collect();
+            let mut output = Vec::new();
+            daemon::serve_lines(&store, input.as_bytes(), &mut output).unwrap();
+            String::from_utf8(output)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        };
+
+        let answers = ask(&[
+            json!({"scripts": ["latn"], "limit": 1}).to_string(),
+            json!({"scripts": ["latn"], "paths": [dir.path()]}).to_string(),
+            json!({"weight": "heavy"}).to_string(),
+            "not json".to_string(),
+        ]);
+        let total = answers[0]["response"]["total"].as_u64().unwrap();
+        assert!(total > 0);
+        assert_eq!(
+            answers[0]["response"]["matches"].as_array().unwrap().len(),
+            1
+        );
+        assert_eq!(answers[1]["response"]["total"], 0);
+        assert_eq!(answers[2]["error"]["code"], "invalid_value");
+        assert_eq!(answers[2]["error"]["field"], "weight");
+        assert_eq!(answers[3]["error"]["code"], "invalid_json");
+
+        std::fs::write(&cache, "[]").unwrap();
+        let later = SystemTime::now() + Duration::from_secs(60);
+        File::options()
+            .write(true)
+            .open(&cache)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        let answers = ask(&[json!({"scripts": ["latn"]}).to_string()]);
+        assert_eq!(answers[0]["response"]["total"], 0);
+    }
 }
@@ -0,0 +1,311 @@
+//! `typg daemon`: one long-lived process that holds the JSON cache or the
+//! LMDB index open and answers searches over a unix domain socket, for
+//! `find --via-daemon` and `cache find --via-daemon`.
+//!
+//! A plain `cache find` starts cold every time: it reads and parses the
+//! whole JSON cache, or opens the index environment, before it filters
+//! anything. The daemon pays that once, so a query over the socket costs
+//! the filtering alone. The cache is read again when its file changes
+//! (`cache add` or `watch` rewrote it); the index stays open, and every
+//! query runs in a fresh read transaction, so it sees the latest write.
+//! Tag bitmaps the index decodes for one query stay in memory for the
+//! next, until a write commits.
+//!
+//! Each line a client sends is one `/search` request body and each answer
+//! is one line: `{"response": ...}` with the `/search` response, or
+//! `{"error": ...}` with the `{code, message, field}` body the HTTP server
+//! sends. A connection may ask any number of questions. `paths` narrow the
+//! answer to faces recorded under those paths instead of starting a scan;
+//! `use_index`, `index_path`, `jobs`, `timeout_ms`, `follow_symlinks` and
+//! `hidden` are ignored, as the daemon only answers from what it holds.
+//!
+//! The socket is created owner-only. A daemon that was killed leaves it
+//! behind; the next one to start on that path replaces it. Windows named
+//! pipes are not supported: there, use `typg serve` and `find --remote`.
+//!
+//! Made by FontLab https://www.fontlab.com/
+
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Context, Result};
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use typg_core::cache::load_cache;
+use typg_core::search::{filter_cached, TypgFontFaceMatch};
+
+#[cfg(feature = "hpindex")]
+use typg_core::index::{FontIndex, IndexOptions};
+
+use super::{request_query, ApiError, SearchRequest, SearchResponse};
+
+/// One answer line on the socket.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Answer {
+    Response(SearchResponse),
+    Error(ApiError),
+}
+
+/// What a daemon answers from, held for its lifetime.
+pub(crate) enum Store {
+    /// A JSON cache, read again when the file changes.
+    Cache {
+        path: PathBuf,
+        loaded: Mutex<LoadedCache>,
+    },
+    /// An open LMDB index.
+    #[cfg(feature = "hpindex")]
+    Index(Arc<FontIndex>),
+}
+
+/// The cache entries and the modification time of the file they came from.
+pub(crate) struct LoadedCache {
+    modified: Option<SystemTime>,
+    entries: Arc<Vec<TypgFontFaceMatch>>,
+}
+
+impl Store {
+    /// Load the JSON cache at `path`.
+    pub(crate) fn cache(path: &Path) -> Result<Self> {
+        let loaded = LoadedCache::read(path)?;
+        Ok(Self::Cache {
+            path: path.to_path_buf(),
+            loaded: Mutex::new(loaded),
+        })
+    }
+
+    /// Open the LMDB index at `path`, which must already exist.
+    #[cfg(feature = "hpindex")]
+    pub(crate) fn index(path: &Path, options: &IndexOptions) -> Result<Self> {
+        if !path.is_dir() {
+            return Err(anyhow!(
+                "no index at {} (build one with `typg cache add --index`)",
+                path.display()
+            ));
+        }
+        let index = FontIndex::shared(path, options)
+            .with_context(|| format!("opening index {}", path.display()))?;
+        Ok(Self::Index(index))
+    }
+
+    /// Number of faces held.
+    pub(crate) fn len(&self) -> Result<usize> {
+        match self {
+            Self::Cache { loaded, .. } => Ok(loaded
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .entries
+                .len()),
+            #[cfg(feature = "hpindex")]
+            Self::Index(index) => index.count(),
+        }
+    }
+
+    /// Answer one search request.
+    #[cfg_attr(not(unix), allow(dead_code))]
+    fn search(&self, req: &SearchRequest) -> Result<SearchResponse, ApiError> {
+        let query = request_query(req)?;
+        let mut matches = match self {
+            Self::Cache { path, loaded } => filter_cached(&fresh_entries(path, loaded)?, &query),
+            #[cfg(feature = "hpindex")]
+            Self::Index(index) => index
+                .reader()
+                .and_then(|reader| reader.find(&query))
+                .map_err(super::index::index_error)?,
+        };
+        if !req.paths.is_empty() {
+            let roots: Vec<(&Path, Option<PathBuf>)> = req
+                .paths
+                .iter()
+                .map(|root| (root.as_path(), root.canonicalize().ok()))
+                .collect();
+            matches.retain(|m| {
+                roots.iter().any(|(root, canonical)| {
+                    m.source.path.starts_with(root)
+                        || canonical
+                            .as_ref()
+                            .is_some_and(|canonical| m.source.path.starts_with(canonical))
+                })
+            });
+        }
+        Ok(SearchResponse::new(matches, req))
+    }
+}
+
+impl LoadedCache {
+    fn read(path: &Path) -> Result<Self> {
+        let modified = modified(path);
+        let entries = load_cache(path)?;
+        Ok(Self {
+            modified,
+            entries: Arc::new(entries),
+        })
+    }
+}
+
+/// The cache entries, read again first if the file changed since.
+fn fresh_entries(
+    path: &Path,
+    loaded: &Mutex<LoadedCache>,
+) -> Result<Arc<Vec<TypgFontFaceMatch>>, ApiError> {
+    let mut loaded = loaded
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if modified(path) != loaded.modified {
+        *loaded = LoadedCache::read(path).map_err(|err| {
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "cache_unreadable",
+                format!("{err:#}"),
+            )
+        })?;
+    }
+    Ok(Arc::clone(&loaded.entries))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Listen on `socket` and answer every connection, each on its own
+/// thread, until the process is stopped.
+#[cfg(unix)]
+pub(crate) fn serve(store: Store, socket: &Path) -> Result<()> {
+    use std::io::{BufReader, BufWriter};
+    use std::os::unix::net::UnixStream;
+
+    let listener = bind(socket)?;
+    let store = Arc::new(store);
+    for stream in listener.incoming() {
+        let stream: UnixStream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("typg daemon: accepting a connection: {err}");
+                continue;
+            }
+        };
+        let store = Arc::clone(&store);
+        std::thread::spawn(move || {
+            // A client that hangs up mid-answer only ends its own connection.
+            let _ = serve_lines(&store, BufReader::new(&stream), BufWriter::new(&stream));
+        });
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn serve(_store: Store, _socket: &Path) -> Result<()> {
+    Err(unsupported())
+}
+
+/// Bind `socket`, replacing one left behind by a daemon that is gone, and
+/// make it owner-only.
+#[cfg(unix)]
+fn bind(socket: &Path) -> Result<std::os::unix::net::UnixListener> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            return Err(anyhow!(
+                "a typg daemon is already listening on {}",
+                socket.display()
+            ));
+        }
+        fs::remove_file(socket)
+            .with_context(|| format!("removing stale socket {}", socket.display()))?;
+    }
+    if let Some(parent) = socket.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("creating socket directory {}", parent.display()))?;
+    }
+    // Create the socket owner-only rather than narrowing it after the fact,
+    // when another user could already have connected. The umask is
+    // process-wide, but no other thread is running yet.
+    // SAFETY: umask only swaps the process's file mode mask.
+    let umask = unsafe { libc::umask(0o177) };
+    let bound = UnixListener::bind(socket);
+    unsafe { libc::umask(umask) };
+    let listener = bound.with_context(|| format!("listening on {}", socket.display()))?;
+    fs::set_permissions(socket, fs::Permissions::from_mode(0o600))
+        .with_context(|| format!("restricting {}", socket.display()))?;
+    Ok(listener)
+}
+
+/// Answer each request line of `input` on `output`.
+#[cfg_attr(not(unix), allow(dead_code))]
+pub(super) fn serve_lines(
+    store: &Store,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let answer = match serde_json::from_str::<SearchRequest>(&line) {
+            Ok(req) => store.search(&req),
+            Err(err) => Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                if err.is_data() {
+                    "invalid_value"
+                } else {
+                    "invalid_json"
+                },
+                err.to_string(),
+            )),
+        };
+        let answer = match answer {
+            Ok(response) => Answer::Response(response),
+            Err(err) => Answer::Error(err),
+        };
+        serde_json::to_writer(&mut output, &answer)?;
+        writeln!(output)?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+/// Send one search request to the daemon listening on `socket` and return
+/// its answer.
+#[cfg(unix)]
+pub(crate) fn ask(socket: &Path, req: &SearchRequest) -> Result<SearchResponse> {
+    use std::io::BufReader;
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(socket).with_context(|| {
+        format!(
+            "connecting to a typg daemon at {} (start one with `typg daemon`)",
+            socket.display()
+        )
+    })?;
+    let mut line = serde_json::to_vec(req)?;
+    line.push(b'\n');
+    stream.write_all(&line)?;
+    let mut answer = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut answer)
+        .context("reading the daemon's answer")?;
+    match serde_json::from_str(&answer).context("reading the daemon's answer")? {
+        Answer::Response(response) => Ok(response),
+        Answer::Error(err) => Err(match &err.field {
+            Some(field) => anyhow!("daemon rejected the query ({field}): {}", err.message),
+            None => anyhow!("daemon rejected the query: {}", err.message),
+        }),
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn ask(_socket: &Path, _req: &SearchRequest) -> Result<SearchResponse> {
+    Err(unsupported())
+}
+
+#[cfg(not(unix))]
+fn unsupported() -> anyhow::Error {
+    anyhow!("typg daemon needs unix domain sockets; on this platform use `typg serve` and `find --remote`")
+}
@@ -0,0 +1,319 @@
+//! `GET /font-file?path=...`: the bytes of a font file, for a web specimen
+//! viewer to load with `@font-face`.
+//!
+//! Only files under the directories given with `serve --serve-root` are
+//! served; without any the endpoint answers 403. The requested path is
+//! resolved (symlinks and `..` included) before it is compared with the
+//! roots, and a path outside them gets the same 404 as a missing file, so
+//! the endpoint cannot be used to probe the rest of the filesystem.
+//!
+//! Browsers do not load TrueType/OpenType collections, so `face=N` returns
+//! face `N` of a `.ttc`/`.otc` as a standalone font. `format=woff2` packs
+//! the font (or that face) as WOFF2 on the way out; see [`super::woff2`].
+//! Glyph subsetting (a `text=` parameter) is not offered: the server would
+//! need a subsetter to build it on. Responses carry an `ETag` (the xxh3
+//! hash of the body) and answer a matching `If-None-Match` with 304.
+//!
+//! Made by FontLab https://www.fontlab.com/
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Query as UrlQuery, State};
+use axum::http::header::{CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use read_fonts::{FileRef, FontRef};
+use serde::Deserialize;
+use tokio::task;
+use typg_core::search::hash_bytes;
+
+use super::{woff2, ApiError, ServerState};
+
+/// Query string of `GET /font-file`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub(super) struct FontFileRequest {
+    /// The font file, as a path on the server.
+    pub path: Option<PathBuf>,
+    /// Collection face to extract as a standalone font.
+    pub face: Option<u32>,
+    /// Encoding to send the font in; the file's own when absent.
+    pub format: Option<FontFileFormat>,
+}
+
+/// Encodings `GET /font-file` can send a font in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum FontFileFormat {
+    /// The TrueType/OpenType data as stored (or as extracted with `face`).
+    Sfnt,
+    /// Brotli-compressed WOFF2, for the web.
+    Woff2,
+}
+
+/// A font ready to send.
+struct FontFile {
+    bytes: Vec<u8>,
+    content_type: &'static str,
+    file_name: String,
+}
+
+/// Handle `GET /font-file`.
+pub(super) async fn font_file_handler(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    request: Result<UrlQuery<FontFileRequest>, QueryRejection>,
+) -> Result<Response, ApiError> {
+    let UrlQuery(request) = request?;
+    if state.font_roots.is_empty() {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "font_files_disabled",
+            "this server serves no font files (start it with --serve-root DIR)",
+        ));
+    }
+    let Some(path) = request.path else {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "missing_path",
+            "a font file path is required",
+        )
+        .with_field("path"));
+    };
+
+    let file = task::spawn_blocking(move || {
+        let path = served_path(&path, &state.font_roots)?;
+        let data = fs::read(&path).map_err(|_| not_found(&path))?;
+        let file = font_file(&path, data, request.face)?;
+        match request.format {
+            Some(FontFileFormat::Woff2) => woff2_file(&path, file),
+            Some(FontFileFormat::Sfnt) | None => Ok(file),
+        }
+    })
+    .await
+    .map_err(ApiError::task_failed)??;
+
+    let etag = format!("\"{:016x}\"", hash_bytes(&file.bytes));
+    let headers_out = [
+        (ETAG, header_value(&etag)),
+        (CACHE_CONTROL, HeaderValue::from_static("no-cache")),
+    ];
+    if headers
+        .get(IF_NONE_MATCH)
+        .is_some_and(|tags| tags.as_bytes() == etag.as_bytes())
+    {
+        return Ok((StatusCode::NOT_MODIFIED, headers_out).into_response());
+    }
+    let disposition = format!("inline; filename=\"{}\"", file.file_name);
+    Ok((
+        headers_out,
+        [
+            (CONTENT_TYPE, HeaderValue::from_static(file.content_type)),
+            (CONTENT_DISPOSITION, header_value(&disposition)),
+        ],
+        file.bytes,
+    )
+        .into_response())
+}
+
+/// Resolve `path` and check that it lies under one of the (canonical)
+/// `roots`.
+fn served_path(path: &Path, roots: &[PathBuf]) -> Result<PathBuf, ApiError> {
+    let resolved = path.canonicalize().map_err(|_| not_found(path))?;
+    if !resolved.is_file() || !roots.iter().any(|root| resolved.starts_with(root)) {
+        return Err(not_found(path));
+    }
+    Ok(resolved)
+}
+
+/// Check that `data` is a font and pick what to send: the file as it is,
+/// or one face of a collection.
+fn font_file(path: &Path, data: Vec<u8>, face: Option<u32>) -> Result<FontFile, ApiError> {
+    let stem = path
+        .file_stem()
+        .map(|stem| safe_file_name(&stem.to_string_lossy()))
+        .unwrap_or_else(|| "font".to_string());
+    let file = FileRef::new(&data).map_err(|err| {
+        ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "not_a_font",
+            format!("{} is not a font file: {err}", path.display()),
+        )
+        .with_field("path")
+    })?;
+
+    match (file, face) {
+        (FileRef::Collection(_), None) => Ok(FontFile {
+            content_type: "font/collection",
+            file_name: format!("{stem}.{}", extension(path, "ttc")),
+            bytes: data,
+        }),
+        (FileRef::Collection(collection), Some(index)) => {
+            let bytes = extract_face(&data, index).map_err(|err| {
+                ApiError::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "invalid_value",
+                    format!("face {index}: {err:#} ({} faces)", collection.len()),
+                )
+                .with_field("face")
+            })?;
+            let (content_type, ext) = sfnt_kind(&bytes);
+            Ok(FontFile {
+                content_type,
+                file_name: format!("{stem}-{index}.{ext}"),
+                bytes,
+            })
+        }
+        (FileRef::Font(_), None | Some(0)) => {
+            let (content_type, ext) = sfnt_kind(&data);
+            Ok(FontFile {
+                content_type,
+                file_name: format!("{stem}.{}", extension(path, ext)),
+                bytes: data,
+            })
+        }
+        (FileRef::Font(_), Some(index)) => Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_value",
+            format!(
+                "{} is a single font; it has no face {index}",
+                path.display()
+            ),
+        )
+        .with_field("face")),
+    }
+}
+
+/// Pack `file` as WOFF2; collections need a `face` picked first.
+fn woff2_file(path: &Path, file: FontFile) -> Result<FontFile, ApiError> {
+    if file.content_type == "font/collection" {
+        return Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_value",
+            format!(
+                "{} is a collection; pick a face to send as WOFF2",
+                path.display()
+            ),
+        )
+        .with_field("face"));
+    }
+    let bytes = woff2::encode(&file.bytes).map_err(|err| {
+        ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "not_a_font",
+            format!("{} cannot be packed as WOFF2: {err:#}", path.display()),
+        )
+        .with_field("path")
+    })?;
+    let stem = file
+        .file_name
+        .rsplit_once('.')
+        .map_or(file.file_name.as_str(), |(stem, _)| stem);
+    Ok(FontFile {
+        content_type: "font/woff2",
+        file_name: format!("{stem}.woff2"),
+        bytes,
+    })
+}
+
+/// Copy face `index` of a collection into a standalone sfnt: the same
+/// tables, laid out after a fresh table directory, with the `head`
+/// checksum adjustment recomputed for the new file.
+pub(super) fn extract_face(data: &[u8], index: u32) -> Result<Vec<u8>> {
+    let font = FontRef::from_index(data, index).map_err(|err| anyhow!("{err}"))?;
+    let directory = &font.table_directory;
+    let records = directory.table_records();
+    // Worked out in u32: past 4095 tables, `searchRange` (and with more
+    // tables `rangeShift`) no longer fits its u16 field; those are stored
+    // as 0xFFFF.
+    let num_tables = records.len() as u32;
+    let entry_selector = num_tables.max(1).ilog2();
+    let search_range = (1u32 << entry_selector) * 16;
+    let range_shift = (num_tables * 16).saturating_sub(search_range);
+
+    let mut out = Vec::new();
+    out.extend(directory.sfnt_version().to_be_bytes());
+    for value in [num_tables, search_range, entry_selector, range_shift] {
+        out.extend(u16::try_from(value).unwrap_or(u16::MAX).to_be_bytes());
+    }
+
+    let mut offset = 12 + 16 * records.len();
+    let mut tables = Vec::with_capacity(records.len());
+    let mut head_offset = None;
+    for record in records {
+        let tag = record.tag();
+        let table = font
+            .table_data(tag)
+            .ok_or_else(|| anyhow!("table {tag} lies outside the file"))?;
+        out.extend(tag.into_bytes());
+        out.extend(record.checksum().to_be_bytes());
+        out.extend((offset as u32).to_be_bytes());
+        out.extend((table.len() as u32).to_be_bytes());
+        if tag == read_fonts::types::Tag::new(b"head") {
+            head_offset = Some(offset);
+        }
+        offset += table.len().next_multiple_of(4);
+        tables.push(table);
+    }
+    for table in tables {
+        out.extend(table.as_bytes());
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+
+    // `head.checkSumAdjustment` (at offset 8) makes the whole file sum to
+    // a fixed value; it is zero while summing.
+    if let Some(head) = head_offset.filter(|head| head + 12 <= out.len()) {
+        out[head + 8..head + 12].fill(0);
+        let sum = out.chunks(4).fold(0u32, |sum, chunk| {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            sum.wrapping_add(u32::from_be_bytes(word))
+        });
+        out[head + 8..head + 12].copy_from_slice(&0xB1B0_AFBAu32.wrapping_sub(sum).to_be_bytes());
+    }
+    Ok(out)
+}
+
+/// MIME type and usual extension of a standalone sfnt.
+fn sfnt_kind(data: &[u8]) -> (&'static str, &'static str) {
+    if data.starts_with(b"OTTO") {
+        ("font/otf", "otf")
+    } else {
+        ("font/ttf", "ttf")
+    }
+}
+
+/// The file's own extension, or `fallback` when it has none.
+fn extension(path: &Path, fallback: &str) -> String {
+    path.extension()
+        .map(|ext| safe_file_name(&ext.to_string_lossy()))
+        .unwrap_or_else(|| fallback.to_string())
+}
+
+/// `name` with everything but printable ASCII (and quotes and backslashes)
+/// replaced, fit for a `Content-Disposition` filename.
+fn safe_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '"' | '\\' => '_',
+            c if c.is_ascii_graphic() || c == ' ' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+fn header_value(value: &str) -> HeaderValue {
+    HeaderValue::from_str(value).unwrap_or_else(|_| HeaderValue::from_static(""))
+}
+
+fn not_found(path: &Path) -> ApiError {
+    ApiError::new(
+        StatusCode::NOT_FOUND,
+        "file_not_found",
+        format!("no font file at {} under the served roots", path.display()),
+    )
+    .with_field("path")
+}
@@ -0,0 +1,194 @@
+//! Font detail endpoints: `GET /fonts/{id}` and `GET /fonts?path=...`.
+//!
+//! A web frontend lists faces with `/search` and then shows one of them by
+//! the `index_id` it got back. The index stores only what queries need, so
+//! the detail is read from the font file itself — full metadata including
+//! named instances, plus the `typg inspect` view of name records, tables,
+//! axes, layout and metrics — one face at a time, without a scan. When the
+//! file can no longer be read the answer falls back to what the index
+//! holds, with `live: false` and the reason in `read_error`.
+//!
+//! Both take an optional `index_path` query parameter; without one they
+//! look in the server's default index.
+//!
+//! Made by FontLab https://www.fontlab.com/
+
+use std::fs;
+use std::path::PathBuf;
+
+use axum::extract::rejection::{PathRejection, QueryRejection};
+use axum::extract::{Path as UrlPath, Query as UrlQuery, State};
+use axum::http::StatusCode;
+use axum::Json;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::task;
+use typg_core::index::{FontID, FontIndex};
+use typg_core::inspect::{inspect_font, FontInspection};
+use typg_core::search::{
+    read_metadata, SearchOptions, TypgFontFaceMatch, TypgFontFaceMeta, TypgFontSource,
+};
+
+use super::index::{existing_index, index_error, IndexLocation};
+use super::{ApiError, ServerState};
+
+/// Query string of `GET /fonts`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub(super) struct FontLookup {
+    /// Font file whose faces to return.
+    pub path: Option<PathBuf>,
+    /// Index directory; the server's default index when absent.
+    pub index_path: Option<PathBuf>,
+}
+
+/// One indexed face in full.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FontDetail {
+    /// The face's ID in the index.
+    pub index_id: FontID,
+    /// Content fingerprint, as in `/search` results.
+    pub font_id: String,
+    /// File path and collection index.
+    pub source: TypgFontSource,
+    /// Everything typg reads from the face, or the index's stored subset
+    /// when `live` is false.
+    pub metadata: TypgFontFaceMeta,
+    /// Name records, tables, axes, layout and metrics; `None` when the
+    /// file could not be read.
+    pub details: Option<FontInspection>,
+    /// Whether `metadata` and `details` come from the file as it is now.
+    pub live: bool,
+    /// Why the file could not be read, when it could not.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_error: Option<String>,
+}
+
+/// Handle `GET /fonts/{id}`.
+pub(super) async fn font_handler(
+    State(state): State<ServerState>,
+    id: Result<UrlPath<u64>, PathRejection>,
+    location: Result<UrlQuery<IndexLocation>, QueryRejection>,
+) -> Result<Json<FontDetail>, ApiError> {
+    let UrlPath(id) = id.map_err(|err| ApiError::from(err).with_field("id"))?;
+    let UrlQuery(location) = location?;
+    let index_path = existing_index(&state, &location.index_path)?;
+
+    let stored = task::spawn_blocking(move || {
+        let index = FontIndex::shared(&index_path, &state.index_options)?;
+        let face = index.reader()?.get(FontID(id))?;
+        anyhow::Ok(face)
+    })
+    .await
+    .map_err(ApiError::task_failed)?
+    .map_err(index_error)?
+    .ok_or_else(|| {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            "font_not_found",
+            format!("no face with index_id {id}"),
+        )
+        .with_field("id")
+    })?;
+
+    let mut details = task::spawn_blocking(move || read_details(vec![stored]))
+        .await
+        .map_err(ApiError::task_failed)?;
+    Ok(Json(details.remove(0)))
+}
+
+/// Handle `GET /fonts?path=...`: every face the index holds for one file,
+/// in collection order.
+pub(super) async fn fonts_by_path_handler(
+    State(state): State<ServerState>,
+    lookup: Result<UrlQuery<FontLookup>, QueryRejection>,
+) -> Result<Json<Vec<FontDetail>>, ApiError> {
+    let UrlQuery(lookup) = lookup?;
+    let Some(path) = lookup.path else {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "missing_path",
+            "a font file path is required",
+        )
+        .with_field("path"));
+    };
+    let index_path = existing_index(&state, &lookup.index_path)?;
+
+    let stored = {
+        let path = path.clone();
+        task::spawn_blocking(move || {
+            let index = FontIndex::shared(&index_path, &state.index_options)?;
+            let faces = index.reader()?.get_by_path(&path)?;
+            anyhow::Ok(faces)
+        })
+        .await
+        .map_err(ApiError::task_failed)?
+        .map_err(index_error)?
+    };
+    if stored.is_empty() {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            "font_not_found",
+            format!("{} is not indexed", path.display()),
+        )
+        .with_field("path"));
+    }
+
+    task::spawn_blocking(move || read_details(stored))
+        .await
+        .map_err(ApiError::task_failed)
+        .map(Json)
+}
+
+/// Complete stored faces of one file from the file itself.
+fn read_details(stored: Vec<TypgFontFaceMatch>) -> Vec<FontDetail> {
+    let Some(path) = stored.first().map(|face| face.source.path.clone()) else {
+        return Vec::new();
+    };
+    let read = fs::read(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|data| {
+            let faces = read_metadata(&data, &path, &SearchOptions::default())?;
+            let inspections = inspect_font(&data, &path)?;
+            Ok((faces, inspections))
+        });
+
+    stored
+        .into_iter()
+        .map(|face| {
+            let index_id = face
+                .metadata
+                .index_id
+                .expect("index results carry an index_id");
+            let ttc_index = face.source.ttc_index;
+            let live = read.as_ref().ok().and_then(|(faces, inspections)| {
+                let meta = faces.iter().find(|m| m.source.ttc_index == ttc_index)?;
+                let details = inspections.iter().find(|i| i.source.ttc_index == ttc_index);
+                Some((meta.clone(), details.cloned()))
+            });
+            let (item, details, read_error) = match live {
+                Some((mut item, details)) => {
+                    item.metadata.index_id = Some(index_id);
+                    item.metadata.modified = face.metadata.modified;
+                    (item, details, None)
+                }
+                None => {
+                    let reason = match &read {
+                        Err(err) => format!("{err:#}"),
+                        Ok(_) => format!("the file no longer has face {ttc_index:?}"),
+                    };
+                    (face, None, Some(reason))
+                }
+            };
+            FontDetail {
+                index_id,
+                font_id: item.font_id(),
+                source: item.source,
+                metadata: item.metadata,
+                live: read_error.is_none(),
+                details,
+                read_error,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,347 @@
+//! Index management endpoints: `POST /index/add`, `POST /index/clean`,
+//! `GET /index/info` and `DELETE /index`.
+//!
+//! They act on the same LMDB catalog `/search` queries with `use_index`,
+//! through the same shared `FontIndex`, so searches keep running (against
+//! the last committed state) while an index change is written. Changes
+//! are refused with 403 unless the server runs with `--index-admin`, and
+//! with 409 while another change is in progress: LMDB would queue a second
+//! writer behind the first, and a client is better told than kept waiting.
+//! `GET /index/info` is always available.
+//!
+//! Every endpoint takes an optional `index_path` (in the JSON body, or the
+//! query string for `GET` and `DELETE`); without one it acts on the
+//! server's default index. Only the default index and those the server was
+//! started with (`--index-path`) may be named; others get 403.
+//!
+//! Made by FontLab https://www.fontlab.com/
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Query as UrlQuery, State};
+use axum::http::StatusCode;
+use axum::Json;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::OwnedMutexGuard;
+use tokio::task;
+use typg_core::index::FontIndex;
+use typg_core::query::Query;
+use typg_core::search::{search_with_report, SearchOptions};
+
+use super::{allowed_jobs, scan_permit, ApiError, ServerState};
+use crate::resolve_index_path;
+
+/// Body of `POST /index/add`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default)]
+pub struct IndexAddRequest {
+    /// Font files or directories to scan into the index.
+    pub paths: Vec<PathBuf>,
+    /// Follow symlinks while walking `paths`.
+    pub follow_symlinks: bool,
+    /// Include hidden files and directories (names starting with `.`).
+    pub hidden: bool,
+    /// Worker threads for the scan, within the server's `--max-jobs`.
+    pub jobs: Option<usize>,
+    /// Index directory; the server's default index when absent.
+    pub index_path: Option<PathBuf>,
+}
+
+/// Body of `POST /index/clean`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default)]
+pub struct IndexCleanRequest {
+    /// Also drop faces of files whose content no longer matches the
+    /// stored hash.
+    pub verify_hash: bool,
+    /// Index directory; the server's default index when absent.
+    pub index_path: Option<PathBuf>,
+}
+
+/// Query string of `GET /index/info` and `DELETE /index`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub(super) struct IndexLocation {
+    /// Index directory; the server's default index when absent.
+    pub index_path: Option<PathBuf>,
+}
+
+/// Outcome of an index change.
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+pub struct IndexChange {
+    /// The index directory that was changed.
+    pub path: PathBuf,
+    /// Faces in the index afterwards.
+    pub faces: usize,
+    /// Faces written by `/index/add`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added: Option<usize>,
+    /// Faces `/index/add` left alone because their files were unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<usize>,
+    /// Font files `/index/add` could not read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub errors: Option<usize>,
+    /// Faces removed by `/index/clean` or `DELETE /index`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removed: Option<usize>,
+}
+
+/// Answer to `GET /index/info`.
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+pub struct IndexInfo {
+    /// The index directory.
+    pub path: PathBuf,
+    /// Whether an index exists there; the other fields are empty if not.
+    pub exists: bool,
+    /// Faces in the index.
+    pub faces: usize,
+    /// Size of the LMDB files on disk.
+    pub size_bytes: u64,
+    /// Entries and bytes per LMDB database.
+    pub databases: BTreeMap<String, DatabaseInfo>,
+}
+
+/// One LMDB database in [`IndexInfo`].
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+pub struct DatabaseInfo {
+    pub entries: usize,
+    pub size_bytes: u64,
+}
+
+/// Handle `POST /index/add`: scan `paths` and store their faces, skipping
+/// files the index already holds unchanged.
+pub(super) async fn add_handler(
+    State(state): State<ServerState>,
+    payload: Result<Json<IndexAddRequest>, JsonRejection>,
+) -> Result<Json<IndexChange>, ApiError> {
+    let Json(req) = payload?;
+    if req.paths.is_empty() {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "missing_paths",
+            "at least one path to index is required",
+        )
+        .with_field("paths"));
+    }
+    let jobs = allowed_jobs(req.jobs, state.max_jobs)?;
+    let index_path = index_location(&state, &req.index_path)?;
+    let change = begin_change(&state)?;
+    let permit = scan_permit(&state).await?;
+
+    task::spawn_blocking(move || {
+        let _change = change;
+        let opts = SearchOptions {
+            follow_symlinks: req.follow_symlinks,
+            include_hidden: req.hidden,
+            jobs,
+            ..SearchOptions::default()
+        };
+        let (faces, report) = search_with_report(&req.paths, &Query::new(), &opts)
+            .map_err(ApiError::unreadable_path)?;
+        drop(permit);
+
+        let index = FontIndex::shared(&index_path, &state.index_options).map_err(index_error)?;
+        let mut writer = index.writer().map_err(index_error)?;
+        let (added, skipped) = writer.add_scanned(&faces, false).map_err(index_error)?;
+        writer.commit().map_err(index_error)?;
+        Ok(IndexChange {
+            faces: index.count().map_err(index_error)?,
+            path: index_path,
+            added: Some(added),
+            skipped: Some(skipped),
+            errors: Some(report.errors),
+            removed: None,
+        })
+    })
+    .await
+    .map_err(ApiError::task_failed)?
+    .map(Json)
+}
+
+/// Handle `POST /index/clean`: drop faces whose files are gone (or, with
+/// `verify_hash`, changed).
+pub(super) async fn clean_handler(
+    State(state): State<ServerState>,
+    payload: Result<Json<IndexCleanRequest>, JsonRejection>,
+) -> Result<Json<IndexChange>, ApiError> {
+    let Json(req) = payload?;
+    let index_path = existing_index(&state, &req.index_path)?;
+    let change = begin_change(&state)?;
+
+    task::spawn_blocking(move || {
+        let _change = change;
+        let index = FontIndex::shared(&index_path, &state.index_options).map_err(index_error)?;
+        let mut writer = index.writer().map_err(index_error)?;
+        let (before, mut after) = writer.prune_missing().map_err(index_error)?;
+        if req.verify_hash {
+            after -= writer.prune_changed().map_err(index_error)?;
+        }
+        writer.commit().map_err(index_error)?;
+        Ok(IndexChange {
+            path: index_path,
+            faces: after,
+            removed: Some(before - after),
+            ..IndexChange::default()
+        })
+    })
+    .await
+    .map_err(ApiError::task_failed)?
+    .map(Json)
+}
+
+/// Handle `DELETE /index`: remove every face, leaving an empty index.
+pub(super) async fn delete_handler(
+    State(state): State<ServerState>,
+    location: Result<UrlQuery<IndexLocation>, QueryRejection>,
+) -> Result<Json<IndexChange>, ApiError> {
+    let UrlQuery(location) = location?;
+    let index_path = existing_index(&state, &location.index_path)?;
+    let change = begin_change(&state)?;
+
+    task::spawn_blocking(move || {
+        let _change = change;
+        let index = FontIndex::shared(&index_path, &state.index_options).map_err(index_error)?;
+        let mut writer = index.writer().map_err(index_error)?;
+        let removed = writer.clear().map_err(index_error)?;
+        writer.commit().map_err(index_error)?;
+        Ok(IndexChange {
+            path: index_path,
+            faces: 0,
+            removed: Some(removed),
+            ..IndexChange::default()
+        })
+    })
+    .await
+    .map_err(ApiError::task_failed)?
+    .map(Json)
+}
+
+/// Handle `GET /index/info`: face count and on-disk size of the index.
+pub(super) async fn info_handler(
+    State(state): State<ServerState>,
+    location: Result<UrlQuery<IndexLocation>, QueryRejection>,
+) -> Result<Json<IndexInfo>, ApiError> {
+    let UrlQuery(location) = location?;
+    let index_path = index_location(&state, &location.index_path)?;
+    if !index_path.is_dir() {
+        return Ok(Json(IndexInfo {
+            path: index_path,
+            ..IndexInfo::default()
+        }));
+    }
+
+    task::spawn_blocking(move || {
+        let index = FontIndex::shared(&index_path, &state.index_options).map_err(index_error)?;
+        let databases = index
+            .database_stats()
+            .map_err(index_error)?
+            .into_iter()
+            .map(|db| {
+                let info = DatabaseInfo {
+                    entries: db.entries,
+                    size_bytes: db.size_bytes,
+                };
+                (db.name.to_string(), info)
+            })
+            .collect();
+        // LMDB keeps everything in a flat directory (data.mdb and lock.mdb).
+        let size_bytes = fs::read_dir(&index_path)
+            .map_err(|err| index_error(err.into()))?
+            .filter_map(|entry| entry.ok()?.metadata().ok())
+            .filter(|meta| meta.is_file())
+            .map(|meta| meta.len())
+            .sum();
+        Ok(IndexInfo {
+            faces: index.count().map_err(index_error)?,
+            path: index_path,
+            exists: true,
+            size_bytes,
+            databases,
+        })
+    })
+    .await
+    .map_err(ApiError::task_failed)?
+    .map(Json)
+}
+
+/// Claim the right to change an index: refused unless the server allows
+/// index changes, and while another change holds it.
+fn begin_change(state: &ServerState) -> Result<OwnedMutexGuard<()>, ApiError> {
+    if !state.index_admin {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "index_admin_disabled",
+            "index changes are disabled on this server (start it with --index-admin)",
+        ));
+    }
+    state.index_changes.clone().try_lock_owned().map_err(|_| {
+        ApiError::new(
+            StatusCode::CONFLICT,
+            "index_busy",
+            "another index change is in progress; retry when it has finished",
+        )
+    })
+}
+
+/// The index directory a request names, or the default one; 403 for a
+/// directory that is neither the default nor one of `--index-path`.
+pub(super) fn index_location(
+    state: &ServerState,
+    index_path: &Option<PathBuf>,
+) -> Result<PathBuf, ApiError> {
+    let path = resolve_index_path(index_path, None)
+        .map_err(|err| ApiError::invalid_value(err).with_field("index_path"))?;
+    if index_path.is_none() || index_allowed(state, &path) {
+        return Ok(path);
+    }
+    Err(ApiError::new(
+        StatusCode::FORBIDDEN,
+        "index_not_allowed",
+        format!(
+            "{} is not an index this server serves (start it with --index-path)",
+            path.display()
+        ),
+    )
+    .with_field("index_path"))
+}
+
+/// Whether `path` is the default index or one of `--index-path`, compared
+/// after resolving symlinks and `..` where the directories exist.
+fn index_allowed(state: &ServerState, path: &Path) -> bool {
+    let key = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let wanted = key(path);
+    resolve_index_path(&None, None)
+        .into_iter()
+        .chain(state.index_paths.iter().cloned())
+        .any(|allowed| key(&allowed) == wanted)
+}
+
+/// Like [`index_location`], but 404 when no index exists there yet.
+pub(super) fn existing_index(
+    state: &ServerState,
+    index_path: &Option<PathBuf>,
+) -> Result<PathBuf, ApiError> {
+    let path = index_location(state, index_path)?;
+    if !path.is_dir() {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            "index_not_found",
+            format!("no index at {}", path.display()),
+        )
+        .with_field("index_path"));
+    }
+    Ok(path)
+}
+
+pub(super) fn index_error(err: anyhow::Error) -> ApiError {
+    ApiError::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "index_error",
+        format!("{err:#}"),
+    )
+}
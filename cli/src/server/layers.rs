@@ -0,0 +1,167 @@
+//! Middleware for browsers and misbehaving clients: CORS
+//! (`--cors-origin`), a cap on `/search` requests in flight
+//! (`--max-concurrent-searches`) and a time limit on every request
+//! (`--request-timeout`).
+//!
+//! CORS headers go only to the origins the operator listed (`*` for any),
+//! and preflight `OPTIONS` requests are answered here, before the token
+//! check, as browsers send them without credentials. Searches beyond the
+//! cap are turned away with 429 `too_many_searches` rather than queued, so
+//! a client firing requests in a loop cannot pile up work; `--max-scans`
+//! still decides how many of the admitted ones scan at once. A request
+//! that has no response when its time is up gets 503 `request_timeout`. A
+//! live scan already running finishes in the background (holding its scan
+//! slot), and a streamed response that has started is not cut off: its
+//! heartbeats show the client it is alive.
+//!
+//! Made by FontLab https://www.fontlab.com/
+
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::header::{
+    ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+    ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_METHOD,
+    CONTENT_TYPE, ORIGIN, RETRY_AFTER, VARY,
+};
+use axum::http::{HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use futures_util::StreamExt;
+use tokio::time;
+
+use super::{ApiError, ServerState};
+
+/// Methods and request headers a cross-origin client may use.
+const ALLOW_METHODS: &str = "GET, POST, DELETE";
+const ALLOW_HEADERS: &str = "authorization, content-type, accept, if-none-match";
+/// Response headers a cross-origin client may read.
+const EXPOSE_HEADERS: &str = "etag, content-disposition, retry-after, www-authenticate";
+/// How long a browser may cache a preflight answer, in seconds.
+const PREFLIGHT_MAX_AGE: &str = "600";
+
+/// Add CORS headers for allowed origins, and answer their preflight
+/// requests.
+pub(super) async fn cors(
+    State(state): State<ServerState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(allowed) = allowed_origin(&state, &request) else {
+        return next.run(request).await;
+    };
+    let preflight = request.method() == Method::OPTIONS
+        && request
+            .headers()
+            .contains_key(ACCESS_CONTROL_REQUEST_METHOD);
+    let mut response = if preflight {
+        let mut response = StatusCode::NO_CONTENT.into_response();
+        let headers = response.headers_mut();
+        headers.insert(
+            ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_static(ALLOW_METHODS),
+        );
+        headers.insert(
+            ACCESS_CONTROL_ALLOW_HEADERS,
+            HeaderValue::from_static(ALLOW_HEADERS),
+        );
+        headers.insert(
+            ACCESS_CONTROL_MAX_AGE,
+            HeaderValue::from_static(PREFLIGHT_MAX_AGE),
+        );
+        response
+    } else {
+        next.run(request).await
+    };
+
+    let headers = response.headers_mut();
+    if allowed != "*" {
+        headers.append(VARY, HeaderValue::from_static("origin"));
+    }
+    headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allowed);
+    headers.insert(
+        ACCESS_CONTROL_EXPOSE_HEADERS,
+        HeaderValue::from_static(EXPOSE_HEADERS),
+    );
+    response
+}
+
+/// The `Access-Control-Allow-Origin` value for `request`, if its origin is
+/// allowed.
+fn allowed_origin(state: &ServerState, request: &Request) -> Option<HeaderValue> {
+    let origin = request.headers().get(ORIGIN)?;
+    let text = origin.to_str().ok()?;
+    state
+        .cors_origins
+        .iter()
+        .find_map(|allowed| match allowed.as_str() {
+            "*" => Some(HeaderValue::from_static("*")),
+            allowed if allowed.eq_ignore_ascii_case(text) => Some(origin.clone()),
+            _ => None,
+        })
+}
+
+/// Turn away `/search` requests beyond `--max-concurrent-searches` with
+/// 429. A streamed response keeps its slot until the stream ends.
+pub(super) async fn limit_searches(
+    State(state): State<ServerState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(slots) = &state.search_slots else {
+        return next.run(request).await;
+    };
+    let Ok(permit) = slots.clone().try_acquire_owned() else {
+        let mut response = ApiError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "too_many_searches",
+            "too many searches are running; retry shortly",
+        )
+        .into_response();
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from_static("1"));
+        return response;
+    };
+
+    let response = next.run(request).await;
+    if !is_stream(&response) {
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let body = body.into_data_stream().map(move |chunk| {
+        let _permit = &permit;
+        chunk
+    });
+    Response::from_parts(parts, Body::from_stream(body))
+}
+
+fn is_stream(response: &Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value.starts_with("application/x-ndjson") || value.starts_with("text/event-stream")
+        })
+}
+
+/// Answer 503 `request_timeout` when a request has no response within
+/// `--request-timeout`.
+pub(super) async fn time_limit(
+    State(state): State<ServerState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(limit) = state.request_timeout else {
+        return next.run(request).await;
+    };
+    match time::timeout(limit, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "request_timeout",
+            format!("no response within {}s", limit.as_secs_f64()),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,297 @@
+//! `typg serve --mcp`: the Model Context Protocol over stdin and stdout,
+//! for AI assistants and editors that query local fonts without HTTP.
+//!
+//! Each line on stdin is one JSON-RPC 2.0 message and each answer is one
+//! line on stdout; nothing else is written there. The server offers three
+//! tools:
+//!
+//! - `find` scans directories, taking the same arguments as a `/search`
+//!   request body;
+//! - `cache_find` filters the JSON cache (`cache_path`, or the default
+//!   cache) with the same filters, or the LMDB index with `use_index`;
+//! - `inspect` dumps one font file as `typg inspect --json` does.
+//!
+//! Tool input schemas are derived from `SearchRequest`, as the OpenAPI
+//! description of `/search` is, so both stay in step. Results are the `/search` response (or the inspect
+//! faces) as JSON text and as structured content. Assistants read results
+//! into a limited context, so searches return at most [`DEFAULT_LIMIT`]
+//! matches unless the call sets `limit`; `total` still counts them all.
+//! A tool that fails (a bad filter, a missing path) answers with
+//! `isError` and the same `{code, message, field}` body the HTTP server
+//! sends. `--max-jobs` and `--max-scans` apply as they do over HTTP.
+//!
+//! Made by FontLab https://www.fontlab.com/
+
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use anyhow::Result;
+use axum::http::StatusCode;
+use schemars::generate::SchemaSettings;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::runtime::Handle;
+use typg_core::cache::load_cache;
+use typg_core::inspect::inspect_file;
+use typg_core::search::filter_cached;
+
+use super::{request_query, run_search, ApiError, SearchRequest, SearchResponse, ServerState};
+use crate::resolve_cache_path;
+
+/// Protocol revisions this server speaks, newest first.
+const PROTOCOL_VERSIONS: [&str; 3] = ["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Matches a search returns when the call does not set `limit`.
+const DEFAULT_LIMIT: usize = 50;
+
+/// JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Arguments of the `cache_find` tool.
+#[derive(Debug, Deserialize)]
+struct CacheFindArgs {
+    /// JSON cache file; the default cache when absent.
+    #[serde(default)]
+    cache_path: Option<PathBuf>,
+    #[serde(flatten)]
+    filters: SearchRequest,
+}
+
+/// Arguments of the `inspect` tool.
+#[derive(Debug, Deserialize)]
+struct InspectArgs {
+    path: PathBuf,
+}
+
+/// Answer MCP messages from stdin on stdout until stdin closes. Searches
+/// run on `runtime`.
+pub(crate) fn serve_stdio(state: ServerState, runtime: &Handle) -> Result<()> {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    serve_lines(&state, runtime, stdin.lock(), stdout.lock())
+}
+
+/// Answer each JSON-RPC message line of `input` on `output`.
+pub(super) fn serve_lines(
+    state: &ServerState,
+    runtime: &Handle,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let answer = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle_message(state, runtime, message),
+            Err(err) => Some(failure(Value::Null, PARSE_ERROR, err.to_string())),
+        };
+        if let Some(answer) = answer {
+            serde_json::to_writer(&mut output, &answer)?;
+            writeln!(output)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The answer to one message; `None` for notifications and for responses
+/// from the client.
+fn handle_message(state: &ServerState, runtime: &Handle, message: Value) -> Option<Value> {
+    if message.get("result").is_some() || message.get("error").is_some() {
+        return None;
+    }
+    let id = message.get("id").cloned();
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        return Some(failure(
+            id.unwrap_or(Value::Null),
+            INVALID_REQUEST,
+            "not a JSON-RPC request",
+        ));
+    };
+    // Notifications (`notifications/initialized`, ...) need no answer.
+    let id = id?;
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    let result = match method {
+        "initialize" => Ok(initialize(&params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => call_tool(state, runtime, params),
+        _ => Err((METHOD_NOT_FOUND, format!("unknown method `{method}`"))),
+    };
+    Some(match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err((code, message)) => failure(id, code, message),
+    })
+}
+
+fn failure(id: Value, code: i64, message: impl Into<String>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": code, "message": message.into()},
+    })
+}
+
+/// Agree on the client's protocol revision when this server speaks it,
+/// else offer the newest one.
+fn initialize(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|version| PROTOCOL_VERSIONS.contains(version))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": {"tools": {}},
+        "serverInfo": {"name": "typg", "version": env!("CARGO_PKG_VERSION")},
+    })
+}
+
+/// The tools and their input schemas.
+fn tools() -> Vec<Value> {
+    let find = SchemaSettings::draft2020_12()
+        .with(|settings| settings.inline_subschemas = true)
+        .into_generator()
+        .into_root_schema_for::<SearchRequest>()
+        .to_value();
+
+    let mut cache_find = find.clone();
+    let properties = cache_find["properties"]
+        .as_object_mut()
+        .expect("search request properties");
+    for live_only in ["paths", "follow_symlinks", "hidden", "jobs"] {
+        properties.remove(live_only);
+    }
+    properties.insert(
+        "cache_path".into(),
+        json!({
+            "type": ["string", "null"],
+            "description": "JSON cache file (from `typg cache add`); the default cache when absent.",
+        }),
+    );
+
+    let inspect = json!({
+        "type": "object",
+        "properties": {
+            "path": {"type": "string", "description": "Font file to inspect."},
+        },
+        "required": ["path"],
+    });
+
+    vec![
+        json!({
+            "name": "find",
+            "description": format!(
+                "Scan font directories for fonts matching filters (scripts, features, axes, names, \
+                 codepoints, weight, ...). Returns up to {DEFAULT_LIMIT} matches unless `limit` is \
+                 set; `total` counts all. Set `paths_only` for file paths instead of metadata."
+            ),
+            "inputSchema": find,
+        }),
+        json!({
+            "name": "cache_find",
+            "description": format!(
+                "Filter the fonts recorded by `typg cache add` (or the LMDB index with \
+                 `use_index`) without scanning. Same filters and limit ({DEFAULT_LIMIT}) as `find`."
+            ),
+            "inputSchema": cache_find,
+        }),
+        json!({
+            "name": "inspect",
+            "description": "Every name record, table, axis, feature per script and language, \
+                            cmap summary and OS/2 metric of one font file, per face.",
+            "inputSchema": inspect,
+        }),
+    ]
+}
+
+/// Run a `tools/call`; only an unknown tool or malformed call is a
+/// JSON-RPC error, a failing tool is a result with `isError`.
+fn call_tool(state: &ServerState, runtime: &Handle, params: Value) -> Result<Value, (i64, String)> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or((INVALID_PARAMS, "tools/call needs a tool `name`".to_string()))?;
+    let arguments = match params.get("arguments") {
+        Some(Value::Null) | None => json!({}),
+        Some(arguments) => arguments.clone(),
+    };
+    let outcome = match name {
+        "find" => find(state, runtime, arguments),
+        "cache_find" => cache_find(state, runtime, arguments),
+        "inspect" => inspect(arguments),
+        _ => return Err((INVALID_PARAMS, format!("unknown tool `{name}`"))),
+    };
+    Ok(match outcome {
+        Ok(value) => json!({
+            "content": [{"type": "text", "text": value.to_string()}],
+            "structuredContent": value,
+            "isError": false,
+        }),
+        Err(err) => json!({
+            "content": [{"type": "text", "text": json!(err).to_string()}],
+            "isError": true,
+        }),
+    })
+}
+
+fn find(state: &ServerState, runtime: &Handle, arguments: Value) -> Result<Value, ApiError> {
+    let mut req: SearchRequest = parse_arguments(arguments)?;
+    req.limit = req.limit.or(Some(DEFAULT_LIMIT));
+    let response = runtime.block_on(run_search(state.clone(), req))?;
+    Ok(json!(response))
+}
+
+fn cache_find(state: &ServerState, runtime: &Handle, arguments: Value) -> Result<Value, ApiError> {
+    let CacheFindArgs {
+        cache_path,
+        filters: mut req,
+    } = parse_arguments(arguments)?;
+    req.limit = req.limit.or(Some(DEFAULT_LIMIT));
+    if req.use_index {
+        req.paths.clear();
+        let response = runtime.block_on(run_search(state.clone(), req))?;
+        return Ok(json!(response));
+    }
+
+    let query = request_query(&req)?;
+    let path = resolve_cache_path(&cache_path, None)
+        .map_err(|err| ApiError::invalid_value(err).with_field("cache_path"))?;
+    let entries = load_cache(&path).map_err(|err| {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            "cache_unreadable",
+            format!("{err:#}"),
+        )
+        .with_field("cache_path")
+    })?;
+    let matches = filter_cached(&entries, &query);
+    Ok(json!(SearchResponse::new(matches, &req)))
+}
+
+fn inspect(arguments: Value) -> Result<Value, ApiError> {
+    let InspectArgs { path } = parse_arguments(arguments)?;
+    let faces = inspect_file(&path).map_err(|err| {
+        ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "unreadable_path",
+            format!("{err:#}"),
+        )
+        .with_field("path")
+    })?;
+    Ok(json!({ "faces": faces }))
+}
+
+fn parse_arguments<T: for<'de> Deserialize<'de>>(arguments: Value) -> Result<T, ApiError> {
+    serde_json::from_value(arguments).map_err(|err| {
+        ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_value",
+            err.to_string(),
+        )
+    })
+}
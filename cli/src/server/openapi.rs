@@ -0,0 +1,371 @@
+//! `GET /openapi.json` and `GET /docs`: the HTTP API described as an
+//! OpenAPI 3.0 document, and a Swagger UI page to try it out.
+//!
+//! The endpoints are written out here; their schemas are derived from the
+//! request and response types with schemars, so a field added to a type
+//! shows up in the document without further work. It lists the `/fonts`
+//! and `/index` endpoints only when the server was built with them, and
+//! marks every endpoint but `/health` and these two as needing the bearer
+//! token when the server has one. Both endpoints are open, so SDK
+//! generators and browsers can fetch them without credentials; the UI page
+//! loads Swagger UI's scripts from unpkg.com.
+//!
+//! Made by FontLab https://www.fontlab.com/
+
+use axum::extract::State;
+use axum::response::Html;
+use axum::Json;
+use schemars::generate::SchemaSettings;
+use serde_json::{json, Map, Value};
+
+#[cfg(feature = "hpindex")]
+use super::fonts::FontDetail;
+#[cfg(feature = "hpindex")]
+use super::index::{IndexAddRequest, IndexChange, IndexCleanRequest, IndexInfo};
+use super::stream::SearchEvent;
+use super::{ApiError, SearchRequest, SearchResponse, ServerState};
+
+/// Handle `GET /openapi.json`.
+pub(super) async fn openapi_handler(State(state): State<ServerState>) -> Json<Value> {
+    Json(document(state.token.is_some()))
+}
+
+/// Handle `GET /docs`: Swagger UI over `/openapi.json`.
+pub(super) async fn docs_handler() -> Html<&'static str> {
+    Html(DOCS_PAGE)
+}
+
+const DOCS_PAGE: &str = r##"<!doctype html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>typg API</title>
+<link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+<div id="swagger-ui"></div>
+<script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+<script>SwaggerUIBundle({ url: "openapi.json", dom_id: "#swagger-ui" });</script>
+</body>
+</html>
+"##;
+
+/// The OpenAPI document; `token` marks the guarded endpoints as needing
+/// the bearer token.
+pub(super) fn document(token: bool) -> Value {
+    let mut paths = Map::new();
+    paths.insert("/health".into(), health_path());
+    paths.insert("/search".into(), search_path());
+    paths.insert("/font-file".into(), font_file_path());
+    #[cfg(feature = "hpindex")]
+    {
+        paths.insert("/fonts".into(), fonts_by_path_path());
+        paths.insert("/fonts/{id}".into(), font_path());
+        paths.insert("/index".into(), index_delete_path());
+        paths.insert("/index/add".into(), index_add_path());
+        paths.insert("/index/clean".into(), index_clean_path());
+        paths.insert("/index/info".into(), index_info_path());
+    }
+    paths.insert("/openapi.json".into(), openapi_path());
+    paths.insert("/docs".into(), docs_path());
+
+    let mut doc = json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "typg",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Font search over HTTP: live scans of font directories, or queries of a typg LMDB index.",
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas(),
+            "responses": {
+                "Error": {
+                    "description": "The request failed; `code` says why.",
+                    "content": {"application/json": {"schema": reference("ApiError")}},
+                },
+            },
+        },
+    });
+    if token {
+        doc["components"]["securitySchemes"] = json!({
+            "bearer": {"type": "http", "scheme": "bearer"},
+        });
+        doc["security"] = json!([{"bearer": []}]);
+        for open in ["/health", "/openapi.json", "/docs"] {
+            doc["paths"][open]["get"]["security"] = json!([]);
+        }
+    }
+    doc
+}
+
+fn health_path() -> Value {
+    json!({"get": {
+        "summary": "Liveness check; never needs the token.",
+        "operationId": "health",
+        "responses": {"200": {
+            "description": "The server is up.",
+            "content": {"text/plain": {"schema": {"type": "string", "example": "ok"}}},
+        }},
+    }})
+}
+
+fn search_path() -> Value {
+    json!({"post": {
+        "summary": "Search fonts by a live scan of `paths`, or in the index with `use_index`.",
+        "description": "Send `Accept: application/x-ndjson` or `Accept: text/event-stream` to receive `SearchEvent`s as matches are found instead of one JSON document.",
+        "operationId": "search",
+        "requestBody": {
+            "required": true,
+            "content": {"application/json": {"schema": reference("SearchRequest")}},
+        },
+        "responses": responses(
+            json!({
+                "description": "The matches, one page of them with `offset` and `limit`.",
+                "content": {
+                    "application/json": {"schema": reference("SearchResponse")},
+                    "application/x-ndjson": {"schema": reference("SearchEvent")},
+                    "text/event-stream": {"schema": reference("SearchEvent")},
+                },
+            }),
+            &["400", "401", "422", "429", "500", "503"],
+        ),
+    }})
+}
+
+fn font_file_path() -> Value {
+    json!({"get": {
+        "summary": "Download a font file under one of the server's `--serve-root` directories.",
+        "operationId": "fontFile",
+        "parameters": [
+            query_param("path", "Font file to send.", json!({"type": "string"}), true),
+            query_param(
+                "face",
+                "Face of a collection to send as a standalone font.",
+                json!({"type": "integer", "minimum": 0}),
+                false,
+            ),
+            query_param(
+                "format",
+                "`woff2` to send the font packed as WOFF2; the file's own encoding when absent.",
+                json!({"type": "string", "enum": ["sfnt", "woff2"]}),
+                false,
+            ),
+            {
+                "name": "If-None-Match",
+                "in": "header",
+                "schema": {"type": "string"},
+                "description": "ETag from an earlier response; 304 when unchanged.",
+            },
+        ],
+        "responses": responses(
+            json!({
+                "description": "The font.",
+                "content": {
+                    "font/ttf": {"schema": {"type": "string", "format": "binary"}},
+                    "font/otf": {"schema": {"type": "string", "format": "binary"}},
+                    "font/collection": {"schema": {"type": "string", "format": "binary"}},
+                    "font/woff2": {"schema": {"type": "string", "format": "binary"}},
+                },
+            }),
+            &["304", "400", "401", "403", "404", "422"],
+        ),
+    }})
+}
+
+#[cfg(feature = "hpindex")]
+fn fonts_by_path_path() -> Value {
+    json!({"get": {
+        "summary": "Every indexed face of one font file, read from the file.",
+        "operationId": "fontsByPath",
+        "parameters": [
+            query_param("path", "Font file whose faces to return.", json!({"type": "string"}), true),
+            index_path_param(),
+        ],
+        "responses": responses(
+            json!({
+                "description": "The file's faces, in collection order.",
+                "content": {"application/json": {"schema": {
+                    "type": "array",
+                    "items": reference("FontDetail"),
+                }}},
+            }),
+            &["400", "401", "404", "500"],
+        ),
+    }})
+}
+
+#[cfg(feature = "hpindex")]
+fn font_path() -> Value {
+    json!({"get": {
+        "summary": "One indexed face in full, by the `index_id` a search returned.",
+        "operationId": "font",
+        "parameters": [
+            {
+                "name": "id",
+                "in": "path",
+                "required": true,
+                "schema": {"type": "integer", "minimum": 0},
+                "description": "The face's `index_id`.",
+            },
+            index_path_param(),
+        ],
+        "responses": responses(
+            json!({
+                "description": "The face.",
+                "content": {"application/json": {"schema": reference("FontDetail")}},
+            }),
+            &["400", "401", "404", "500"],
+        ),
+    }})
+}
+
+#[cfg(feature = "hpindex")]
+fn index_delete_path() -> Value {
+    json!({"delete": {
+        "summary": "Remove every face from the index (needs `--index-admin`).",
+        "operationId": "indexDelete",
+        "parameters": [index_path_param()],
+        "responses": responses(index_change(), &["401", "403", "404", "409", "500"]),
+    }})
+}
+
+#[cfg(feature = "hpindex")]
+fn index_add_path() -> Value {
+    json!({"post": {
+        "summary": "Scan `paths` into the index (needs `--index-admin`).",
+        "operationId": "indexAdd",
+        "requestBody": {
+            "required": true,
+            "content": {"application/json": {"schema": reference("IndexAddRequest")}},
+        },
+        "responses": responses(index_change(), &["400", "401", "403", "409", "422", "500"]),
+    }})
+}
+
+#[cfg(feature = "hpindex")]
+fn index_clean_path() -> Value {
+    json!({"post": {
+        "summary": "Drop faces whose files are gone or changed (needs `--index-admin`).",
+        "operationId": "indexClean",
+        "requestBody": {
+            "content": {"application/json": {"schema": reference("IndexCleanRequest")}},
+        },
+        "responses": responses(index_change(), &["400", "401", "403", "404", "409", "500"]),
+    }})
+}
+
+#[cfg(feature = "hpindex")]
+fn index_info_path() -> Value {
+    json!({"get": {
+        "summary": "Face count and on-disk size of the index.",
+        "operationId": "indexInfo",
+        "parameters": [index_path_param()],
+        "responses": responses(
+            json!({
+                "description": "What the index holds.",
+                "content": {"application/json": {"schema": reference("IndexInfo")}},
+            }),
+            &["400", "401", "500"],
+        ),
+    }})
+}
+
+fn openapi_path() -> Value {
+    json!({"get": {
+        "summary": "This document.",
+        "operationId": "openapi",
+        "responses": {"200": {
+            "description": "The OpenAPI document.",
+            "content": {"application/json": {"schema": {"type": "object"}}},
+        }},
+    }})
+}
+
+fn docs_path() -> Value {
+    json!({"get": {
+        "summary": "Swagger UI for this document.",
+        "operationId": "docs",
+        "responses": {"200": {
+            "description": "An HTML page.",
+            "content": {"text/html": {"schema": {"type": "string"}}},
+        }},
+    }})
+}
+
+/// `ok` as the 200 response, and the shared error response for each of
+/// `errors` (304 gets a bodiless one).
+fn responses(ok: Value, errors: &[&str]) -> Value {
+    let mut all = Map::new();
+    all.insert("200".into(), ok);
+    for &status in errors {
+        let response = match status {
+            "304" => json!({"description": "Not modified since the given ETag."}),
+            _ => json!({"$ref": "#/components/responses/Error"}),
+        };
+        all.insert(status.into(), response);
+    }
+    Value::Object(all)
+}
+
+fn query_param(name: &str, description: &str, schema: Value, required: bool) -> Value {
+    json!({
+        "name": name,
+        "in": "query",
+        "required": required,
+        "schema": schema,
+        "description": description,
+    })
+}
+
+#[cfg(feature = "hpindex")]
+fn index_path_param() -> Value {
+    query_param(
+        "index_path",
+        "Index directory: the server's default index, or one it was started with `--index-path` for; the default when absent.",
+        json!({"type": "string"}),
+        false,
+    )
+}
+
+#[cfg(feature = "hpindex")]
+fn index_change() -> Value {
+    json!({
+        "description": "The index after the change.",
+        "content": {"application/json": {"schema": reference("IndexChange")}},
+    })
+}
+
+fn reference(name: &str) -> Value {
+    json!({"$ref": format!("#/components/schemas/{name}")})
+}
+
+/// The request and response types as OpenAPI 3.0 schemas, keyed by type
+/// name, with the types they refer to. Requests are described as the
+/// server reads them and responses as it writes them.
+fn schemas() -> Map<String, Value> {
+    let mut requests = SchemaSettings::openapi3()
+        .for_deserialize()
+        .into_generator();
+    requests.subschema_for::<SearchRequest>();
+    #[cfg(feature = "hpindex")]
+    {
+        requests.subschema_for::<IndexAddRequest>();
+        requests.subschema_for::<IndexCleanRequest>();
+    }
+
+    let mut responses = SchemaSettings::openapi3().for_serialize().into_generator();
+    responses.subschema_for::<SearchResponse>();
+    responses.subschema_for::<SearchEvent>();
+    responses.subschema_for::<ApiError>();
+    #[cfg(feature = "hpindex")]
+    {
+        responses.subschema_for::<FontDetail>();
+        responses.subschema_for::<IndexChange>();
+        responses.subschema_for::<IndexInfo>();
+    }
+
+    let mut schemas = requests.take_definitions(true);
+    schemas.extend(responses.take_definitions(true));
+    schemas
+}
@@ -0,0 +1,261 @@
+//! Streamed `/search` responses.
+//!
+//! A live scan of a large library can take minutes, and the plain JSON
+//! response arrives only when it is over. A request sent with
+//! `Accept: application/x-ndjson` instead gets one JSON event per line as
+//! the scan finds matches; `Accept: text/event-stream` gets the same events
+//! as Server-Sent Events, named by their `type`. Matches come in the order
+//! they are found, not sorted; `offset` and `limit` count in that order.
+//!
+//! After [`HEARTBEAT`] without a match a `heartbeat` event goes out, so
+//! clients and proxies can tell a slow scan from a dead connection. The
+//! stream ends with a `done` event carrying the totals, or an `error` event
+//! if the scan failed after the response had started. Index searches stream
+//! too, though their matches all arrive at once.
+//!
+//! Made by FontLab https://www.fontlab.com/
+
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use axum::body::{Body, Bytes};
+use axum::http::header::{ACCEPT, CACHE_CONTROL, CONTENT_TYPE};
+use axum::http::{HeaderMap, HeaderValue};
+use axum::response::sse::{Event, Sse};
+use axum::response::{IntoResponse, Response};
+use futures_util::stream::{self, Stream, StreamExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::{task, time};
+use typg_core::search::{search_each, SearchReport, TypgFontFaceMatch};
+
+#[cfg(feature = "hpindex")]
+use typg_core::index::FontIndex;
+
+use super::{scan_permit, ApiError, PendingLog, SearchPlan, SearchRequest, ServerState};
+
+/// Quiet time after which a `heartbeat` event is sent.
+pub const HEARTBEAT: Duration = Duration::from_secs(15);
+
+/// Events buffered between the scan and a slow client; beyond that the
+/// scan waits for the client.
+const BUFFER: usize = 256;
+
+/// One event of a streamed `/search` response.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SearchEvent {
+    /// A matching face, in full.
+    Match {
+        #[serde(rename = "match")]
+        face: Box<TypgFontFaceMatch>,
+    },
+    /// A matching face's path (`#N` for a collection face), sent instead
+    /// of `match` when the request set `paths_only`.
+    Path { path: String },
+    /// Nothing new since the last event; the scan is still running.
+    Heartbeat { elapsed_ms: u64 },
+    /// The search finished.
+    Done {
+        /// Matches found, before `offset` and `limit`.
+        total: usize,
+        /// Font files considered (0 for index searches).
+        files_scanned: usize,
+        /// Font files that could not be read and were skipped.
+        errors: usize,
+        /// The scan ran out of `timeout_ms`; the matches are incomplete.
+        truncated: bool,
+        elapsed_ms: u64,
+    },
+    /// The search failed after the response had started.
+    Error(ApiError),
+}
+
+/// How a client asked for a streamed response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum StreamFormat {
+    Ndjson,
+    EventStream,
+}
+
+/// The streamed format the `Accept` header asks for, if any.
+pub(super) fn requested_format(headers: &HeaderMap) -> Option<StreamFormat> {
+    let accept = headers.get(ACCEPT)?.to_str().ok()?;
+    accept
+        .split(',')
+        .map(|item| item.split(';').next().unwrap_or_default().trim())
+        .find_map(|media| match media {
+            "application/x-ndjson" => Some(StreamFormat::Ndjson),
+            "text/event-stream" => Some(StreamFormat::EventStream),
+            _ => None,
+        })
+}
+
+/// Start the search described by `plan` and answer with its event stream.
+/// `pending` is logged when the search ends.
+pub(super) fn stream_search(
+    state: ServerState,
+    req: SearchRequest,
+    plan: SearchPlan,
+    format: StreamFormat,
+    pending: Option<PendingLog>,
+) -> Response {
+    let (tx, rx) = mpsc::channel(BUFFER);
+    let started = Instant::now();
+    tokio::spawn(async move {
+        let outcome = run(state, req, plan, tx.clone()).await;
+        if let Some(pending) = pending {
+            pending.finish(outcome.as_ref().map(|report| report.matches));
+        }
+        let last = match outcome {
+            Ok(report) => SearchEvent::Done {
+                total: report.matches,
+                files_scanned: report.files_scanned,
+                errors: report.errors,
+                truncated: report.truncated,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            },
+            Err(err) => SearchEvent::Error(err),
+        };
+        let _ = tx.send(last).await;
+    });
+
+    let events = with_heartbeats(rx, started, HEARTBEAT);
+    match format {
+        StreamFormat::Ndjson => {
+            let lines = events.map(|event| {
+                let mut line = serde_json::to_vec(&event).unwrap_or_default();
+                line.push(b'\n');
+                Ok::<_, Infallible>(Bytes::from(line))
+            });
+            (
+                [
+                    (
+                        CONTENT_TYPE,
+                        HeaderValue::from_static("application/x-ndjson"),
+                    ),
+                    (CACHE_CONTROL, HeaderValue::from_static("no-cache")),
+                ],
+                Body::from_stream(lines),
+            )
+                .into_response()
+        }
+        StreamFormat::EventStream => Sse::new(events.map(|event| {
+            let name = match &event {
+                SearchEvent::Match { .. } => "match",
+                SearchEvent::Path { .. } => "path",
+                SearchEvent::Heartbeat { .. } => "heartbeat",
+                SearchEvent::Done { .. } => "done",
+                SearchEvent::Error(_) => "error",
+            };
+            Event::default().event(name).json_data(&event)
+        }))
+        .into_response(),
+    }
+}
+
+/// The events from `rx`, with a `heartbeat` after every `quiet` stretch
+/// without one; ends when `rx` closes.
+pub(super) fn with_heartbeats(
+    rx: mpsc::Receiver<SearchEvent>,
+    started: Instant,
+    quiet: Duration,
+) -> impl Stream<Item = SearchEvent> + Send {
+    stream::unfold(rx, move |mut rx| async move {
+        let event = match time::timeout(quiet, rx.recv()).await {
+            Ok(event) => event?,
+            Err(_) => SearchEvent::Heartbeat {
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            },
+        };
+        Some((event, rx))
+    })
+}
+
+/// Run the search, sending its matches to `tx` as they are found.
+#[cfg_attr(not(feature = "hpindex"), allow(unused_mut))]
+async fn run(
+    state: ServerState,
+    req: SearchRequest,
+    mut plan: SearchPlan,
+    tx: mpsc::Sender<SearchEvent>,
+) -> Result<SearchReport, ApiError> {
+    let page = Page::new(&req);
+
+    #[cfg(feature = "hpindex")]
+    if let Some(index_path) = plan.index_path.take() {
+        let started = Instant::now();
+        return task::spawn_blocking(move || {
+            let index = FontIndex::shared(&index_path, &state.index_options)?;
+            let matches = index.reader()?.find(&plan.query)?;
+            let total = matches.len();
+            for face in matches {
+                page.send(&tx, face);
+            }
+            Ok(SearchReport {
+                matches: total,
+                elapsed: started.elapsed(),
+                ..SearchReport::default()
+            })
+        })
+        .await
+        .map_err(ApiError::task_failed)?
+        .map_err(super::index::index_error);
+    }
+
+    // Hold the slot until the scan finishes, even if the client hangs up.
+    let permit = scan_permit(&state).await?;
+    task::spawn_blocking(move || {
+        let _permit = permit;
+        search_each(&req.paths, &plan.query, &plan.opts, |face| {
+            page.send(&tx, face)
+        })
+    })
+    .await
+    .map_err(ApiError::task_failed)?
+    .map_err(ApiError::unreadable_path)
+}
+
+/// Applies `offset`, `limit` and `paths_only` to matches as they arrive.
+struct Page {
+    seen: AtomicUsize,
+    offset: usize,
+    end: usize,
+    paths_only: bool,
+}
+
+impl Page {
+    fn new(req: &SearchRequest) -> Self {
+        let offset = req.offset.unwrap_or(0);
+        Self {
+            seen: AtomicUsize::new(0),
+            offset,
+            end: req
+                .limit
+                .map_or(usize::MAX, |limit| offset.saturating_add(limit)),
+            paths_only: req.paths_only,
+        }
+    }
+
+    /// Send `face` if it falls within the page. Runs on scan threads, so
+    /// it blocks while the buffer is full; a client that hung up is
+    /// ignored and the scan runs on.
+    fn send(&self, tx: &mpsc::Sender<SearchEvent>, face: TypgFontFaceMatch) {
+        let n = self.seen.fetch_add(1, Ordering::Relaxed);
+        if n < self.offset || n >= self.end {
+            return;
+        }
+        let event = if self.paths_only {
+            SearchEvent::Path {
+                path: face.source.path_with_index(),
+            }
+        } else {
+            SearchEvent::Match {
+                face: Box::new(face),
+            }
+        };
+        let _ = tx.blocking_send(event);
+    }
+}
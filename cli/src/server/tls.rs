@@ -0,0 +1,48 @@
+//! HTTPS for `typg serve` (`--tls-cert` and `--tls-key`).
+//!
+//! The certificate chain and private key are read from PEM files once, at
+//! startup, so a bad file stops the server before it binds its port. TLS is
+//! terminated in process by rustls with the `ring` provider, TLS 1.2 and
+//! 1.3; clients are not asked for certificates. With a token set, this
+//! keeps it from crossing the network in the clear.
+//!
+//! Made by FontLab https://www.fontlab.com/
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::ServerConfig;
+
+/// Read the PEM certificate chain in `cert` (leaf first) and the PEM private
+/// key in `key`, and check that they belong together.
+pub fn load(cert: &Path, key: &Path) -> Result<Arc<ServerConfig>> {
+    let chain = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| anyhow!("reading --tls-cert {}: {err}", cert.display()))?;
+    if chain.is_empty() {
+        return Err(anyhow!(
+            "--tls-cert {} holds no PEM certificate",
+            cert.display()
+        ));
+    }
+    let private_key = PrivateKeyDer::from_pem_file(key)
+        .map_err(|err| anyhow!("reading --tls-key {}: {err}", key.display()))?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(chain, private_key)
+        .with_context(|| {
+            format!(
+                "--tls-key {} does not match --tls-cert {}",
+                key.display(),
+                cert.display()
+            )
+        })?;
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
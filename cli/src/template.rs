@@ -0,0 +1,165 @@
+//! Line templates for `--template` output.
+//!
+//! A template is text with `{placeholder}`s, rendered once per match:
+//! `'{path}\t{family}\t{weight}'`. A placeholder is either a [`Field`]
+//! name, rendered exactly as in `--fields` output, or any key of the JSON
+//! metadata (`{license_url}`, `{file_hash}`, `{extra.my_collector}`), so
+//! every field a face records is reachable without piping JSON through jq.
+//!
+//! `\t`, `\n` and `\\` are escapes, since shells pass them through single
+//! quotes verbatim; `{{` and `}}` are literal braces.
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+
+use std::io::Write;
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use serde_json::Value;
+use typg_core::search::TypgFontFaceMatch;
+
+use crate::fields::Field;
+
+/// A parsed `--template`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Text(String),
+    Field(Field),
+    /// Dotted path into the JSON metadata, e.g. `["extra", "foo"]`.
+    Key(Vec<String>),
+}
+
+impl Template {
+    /// Parse a template, rejecting unbalanced braces and empty placeholders.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = spec.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t') => text.push('\t'),
+                    Some('n') => text.push('\n'),
+                    Some('\\') => text.push('\\'),
+                    Some(other) => {
+                        text.push('\\');
+                        text.push(other);
+                    }
+                    None => text.push('\\'),
+                },
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '}' => return Err(anyhow!("unmatched '}}' in template (write '}}}}')")),
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if !closed {
+                        return Err(anyhow!("unclosed placeholder '{{{name}' in template"));
+                    }
+                    let name = name.trim();
+                    if name.is_empty() {
+                        return Err(anyhow!("empty placeholder '{{}}' in template"));
+                    }
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(placeholder(name));
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Self { parts })
+    }
+
+    /// Render the template for one match.
+    ///
+    /// Metadata keys that no face records are an error; keys under
+    /// `extra` render as `-` when this face lacks them.
+    pub fn render(&self, item: &TypgFontFaceMatch, collections: bool) -> Result<String> {
+        let mut meta: Option<Value> = None;
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Field(field) => out.push_str(&field.render(item, collections)),
+                Part::Key(path) => {
+                    let meta = match &mut meta {
+                        Some(meta) => meta,
+                        None => meta.insert(serde_json::to_value(&item.metadata)?),
+                    };
+                    let value = path.iter().try_fold(&*meta, |value, key| value.get(key));
+                    match value {
+                        Some(value) => out.push_str(&render_value(value)),
+                        None if path.len() > 1 && path[0] == "extra" => out.push('-'),
+                        None => {
+                            return Err(anyhow!(
+                                "unknown template field '{}' (expected a --fields name or a JSON metadata key)",
+                                path.join(".")
+                            ))
+                        }
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// A [`Field`] if the name is one (`axis-count` and `axis_count` alike),
+/// else a metadata key path.
+fn placeholder(name: &str) -> Part {
+    match Field::from_str(&name.replace('_', "-"), true) {
+        Ok(field) => Part::Field(field),
+        Err(_) => Part::Key(name.split('.').map(str::to_string).collect()),
+    }
+}
+
+/// Scalars as plain text, lists of scalars comma-joined, `null` and empty
+/// lists as `-`, and anything nested as compact JSON.
+fn render_value(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::Bool(flag) => if *flag { "yes" } else { "no" }.to_string(),
+        Value::String(text) => text.clone(),
+        Value::Number(number) => number.to_string(),
+        Value::Array(items) if items.is_empty() => "-".to_string(),
+        Value::Array(items) if items.iter().all(|v| !v.is_array() && !v.is_object()) => {
+            items.iter().map(render_value).collect::<Vec<_>>().join(",")
+        }
+        nested => nested.to_string(),
+    }
+}
+
+/// Write one rendered line per match.
+pub fn write_template(
+    matches: &[TypgFontFaceMatch],
+    template: &Template,
+    mut w: impl Write,
+    collections: bool,
+) -> Result<()> {
+    for item in matches {
+        writeln!(w, "{}", template.render(item, collections)?)?;
+    }
+    Ok(())
+}
@@ -5,7 +5,9 @@ use clap::CommandFactory;
 use std::fs;
 use std::io::Cursor;
 use tempfile::tempdir;
-use typg_core::search::{TypgFontFaceMatch, TypgFontFaceMeta, TypgFontSource};
+use typg_core::search::{
+    CmapSubtable, NamedInstance, SearchError, TypgFontFaceMatch, TypgFontFaceMeta, TypgFontSource,
+};
 use typg_core::tags::tag4;
 
 /// Build a minimal `TypgFontFaceMatch` for use in tests.
@@ -17,18 +19,11 @@ fn metadata_with(name: &str, axis: Option<&str>, ttc: Option<u32>) -> TypgFontFa
             ttc_index: ttc,
         },
         metadata: TypgFontFaceMeta {
-            names: vec![name.to_string()],
+            names: vec![name.into()],
             axis_tags: axis.into_iter().map(|t| tag4(t).expect("tag")).collect(),
-            feature_tags: Vec::new(),
-            script_tags: Vec::new(),
-            table_tags: Vec::new(),
             codepoints: vec!['A'],
             is_variable: axis.is_some(),
-            weight_class: None,
-            width_class: None,
-            family_class: None,
-            creator_names: Vec::new(),
-            license_names: Vec::new(),
+            ..Default::default()
         },
     }
 }
@@ -96,27 +91,70 @@ fn invalid_regex_returns_error() {
         axes: Vec::new(),
         features: Vec::new(),
         scripts: Vec::new(),
+        langs: Vec::new(),
         tables: Vec::new(),
+        table_checksums: Vec::new(),
         name_patterns: vec!["(".to_string()],
+        name_match: NameMatchArgs::default(),
         creator_patterns: Vec::new(),
         license_patterns: Vec::new(),
         codepoints: Vec::new(),
         text: None,
+        unicode_blocks: Vec::new(),
+        coverage: Vec::new(),
         variable: false,
         weight: None,
         width: None,
         family_class: None,
+        embedding: None,
+        min_glyphs: None,
+        min_axes: None,
+        max_axes: None,
+        vendors: Vec::new(),
+        instances: Vec::new(),
+        only_collections: false,
+        collection_index: None,
+        unicode_cmap_only: false,
+        query_expr: None,
+        preset: None,
+        exclude: ExcludeArgs::default(),
+        ranking: RankArgs::default(),
+        per_family: None,
+        group_by: None,
+        sort: Vec::new(),
+        reverse: false,
+        offset: None,
+        limit: None,
         follow_symlinks: false,
+        hidden: false,
         stdin_paths: false,
         system_fonts: false,
         jobs: None,
+        name_ids: Vec::new(),
+        incremental: None,
+        timeout: None,
+        remote: None,
+        remote_token: None,
+        via_daemon: None,
+        errors: ErrorArgs::default(),
         json: false,
         ndjson: false,
+        codepoint_format: CodepointOutput::Chars,
+        provenance: false,
+        format: None,
+        unicode_range_gap: 0,
+        template: None,
         paths_only: false,
-        columns: false,
+        uri: false,
+        columns: None,
+        max_path_width: None,
+        fields: Vec::new(),
+        no_header: false,
         collections: false,
+        expand_instances: false,
         count_only: false,
         color: ColorChoice::Auto,
+        theme: None,
     };
 
     let built = build_query(&args);
@@ -131,7 +169,7 @@ fn writes_plain_with_ttc_suffix() {
     ];
 
     let mut buf = Cursor::new(Vec::new());
-    write_plain(&matches, &mut buf, false, true).expect("write");
+    write_plain(&matches, &mut buf, &Theme::plain(), true).expect("write");
 
     let output = String::from_utf8(buf.into_inner()).expect("utf8");
     assert!(output.contains("/fonts/A.ttf"));
@@ -157,6 +195,31 @@ fn writes_paths_output_without_color() {
     );
 }
 
+#[test]
+#[cfg(unix)]
+fn uri_flag_writes_file_uris_with_collection_fragments() {
+    let cli = Cli::try_parse_from(["typg", "cache", "list", "--uri", "--paths", "--collections"])
+        .expect("parse");
+    let Command::Cache(CacheCommand::List(args)) = cli.command else {
+        panic!("expected cache list");
+    };
+    let format = OutputFormat::from_output(&args.output);
+    let mut spaced = metadata_with("B", None, Some(3));
+    spaced.source.path = PathBuf::from("/fonts/Noto Sans.ttc");
+    let matches = vec![metadata_with("A", None, None), spaced];
+
+    let mut buf = Cursor::new(Vec::new());
+    write_paths(&format.located(&matches), &mut buf, format.collections).expect("write paths");
+
+    let output = String::from_utf8(buf.into_inner()).expect("utf8");
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines,
+        vec!["file:///fonts/A.ttf", "file:///fonts/Noto%20Sans.ttc#3"]
+    );
+    assert!(Cli::try_parse_from(["typg", "find", "--uri", "--format", "css", "/fonts"]).is_err());
+}
+
 #[test]
 fn text_flag_merges_into_codepoints() {
     let cli = Cli::try_parse_from(["typg", "find", "-u", "U+0041", "-t", "B", "/fonts"])
@@ -172,6 +235,149 @@ fn text_flag_merges_into_codepoints() {
     assert!(query.matches(&meta.metadata));
 }
 
+#[test]
+fn query_expr_flag_adds_boolean_filter() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "find",
+        "--query-expr",
+        "axis:wght OR name:^Beta$",
+        "/fonts",
+    ])
+    .expect("parse cli");
+
+    let Command::Find(args) = cli.command else {
+        panic!("expected find command");
+    };
+    let query = build_query(&args).expect("build");
+
+    assert!(query.matches(&metadata_with("Alpha", Some("wght"), None).metadata));
+    assert!(query.matches(&metadata_with("Beta", None, None).metadata));
+    assert!(!query.matches(&metadata_with("Gamma", None, None).metadata));
+}
+
+#[test]
+fn exclusion_flags_reject_matching_fonts() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "find",
+        "--not-axes",
+        "wght",
+        "--exclude-path",
+        "Gamma*",
+        "/fonts",
+    ])
+    .expect("parse cli");
+
+    let Command::Find(args) = cli.command else {
+        panic!("expected find command");
+    };
+    let query = build_query(&args).expect("build");
+
+    let alpha = metadata_with("Alpha", Some("wght"), None);
+    let beta = metadata_with("Beta", None, None);
+    let gamma = metadata_with("Gamma", None, None);
+    assert!(!query.matches(&alpha.metadata));
+    assert!(query.matches(&beta.metadata) && query.matches_path(&beta.source.path));
+    assert!(!query.matches_path(&gamma.source.path));
+}
+
+#[test]
+fn not_variable_conflicts_with_variable() {
+    let parse = Cli::try_parse_from(["typg", "find", "--variable", "--not-variable", "/fonts"]);
+    assert!(parse.is_err());
+}
+
+#[test]
+fn write_ranked_prefixes_scores_best_first() {
+    let query = build_query_from_parts(
+        &["wght".to_string(), "wdth".to_string()],
+        &[],
+        &[],
+        &[],
+        &[],
+        &[],
+        NameMatch::default(),
+        &[],
+        &[],
+        &[],
+        &None,
+        &[],
+        &[],
+        false,
+        &None,
+        &None,
+        &None,
+        &None,
+        None,
+        None,
+        None,
+        &[],
+        &[],
+        false,
+        None,
+        false,
+        &None,
+        None,
+    )
+    .expect("query");
+    let mut both = metadata_with("Both", Some("wght"), None);
+    both.metadata.axis_tags.push(tag4("wdth").unwrap());
+    let candidates = vec![metadata_with("Half", Some("wght"), None), both];
+    let ranked = rank(candidates, &query, 0.0);
+
+    let format = OutputFormat {
+        json: false,
+        ndjson: false,
+        codepoints: CodepointFormat::Chars,
+        provenance: false,
+        roots: Vec::new(),
+        paths: false,
+        uri: false,
+        columns: false,
+        column_set: Column::value_variants().to_vec(),
+        max_path_width: None,
+        fields: Vec::new(),
+        header: true,
+        collections: false,
+        expand_instances: false,
+        export: None,
+        unicode_range_gap: 0,
+        template: None,
+        color: ColorChoice::Never,
+        theme: None,
+    };
+    let mut buf = Cursor::new(Vec::new());
+    write_ranked(&ranked, &mut buf, &format, &Theme::plain()).expect("write");
+
+    let output = String::from_utf8(buf.into_inner()).expect("utf8");
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines,
+        vec!["1.000  /fonts/Both.ttf", "0.500  /fonts/Half.ttf"]
+    );
+}
+
+#[test]
+fn per_family_keeps_first_face_of_each_family() {
+    let mut bold = metadata_with("Alpha Bold", None, None);
+    bold.metadata.family_name = Some("Alpha".into());
+    let mut regular = metadata_with("Alpha Regular", None, None);
+    regular.metadata.family_name = Some("Alpha".into());
+    let beta = metadata_with("Beta", None, None);
+
+    let kept = per_family(vec![bold, regular, beta], Some(1), |m| &m.metadata);
+
+    let names: Vec<&str> = kept.iter().map(|m| m.metadata.names[0].as_ref()).collect();
+    assert_eq!(names, vec!["Alpha Bold", "Beta"]);
+}
+
+#[test]
+fn min_score_requires_rank() {
+    let parse = Cli::try_parse_from(["typg", "find", "--min-score", "0.5", "/fonts"]);
+    assert!(parse.is_err());
+}
+
 #[test]
 fn gathers_paths_from_stdin_when_flagged() {
     let mut stdin = Cursor::new(b"/fonts/A\n/fonts/B\n".to_vec());
@@ -221,7 +427,15 @@ fn columns_align_names() {
     ];
 
     let mut buf = Cursor::new(Vec::new());
-    write_columns(&matches, &mut buf, false, false).expect("write");
+    write_columns(
+        &matches,
+        Column::value_variants(),
+        WidthLimits::default(),
+        &mut buf,
+        &Theme::plain(),
+        false,
+    )
+    .expect("write");
 
     let output = String::from_utf8(buf.into_inner()).expect("utf8");
     let lines: Vec<&str> = output.lines().collect();
@@ -231,12 +445,93 @@ fn columns_align_names() {
     assert_eq!(alpha_pos, beta_pos);
 }
 
+#[test]
+fn columns_truncate_long_paths_to_fit() {
+    let mut long = metadata_with("Alpha", None, None);
+    long.source.path = PathBuf::from("/very/long/directory/tree/of/fonts/Alpha-Regular.ttf");
+    let matches = vec![long, metadata_with("Beta", None, None)];
+
+    let limits = WidthLimits {
+        max_path: Some(24),
+        line: None,
+    };
+    let mut buf = Cursor::new(Vec::new());
+    write_columns(
+        &matches,
+        &[Column::Path, Column::Name],
+        limits,
+        &mut buf,
+        &Theme::plain(),
+        false,
+    )
+    .expect("write");
+    let output = String::from_utf8(buf.into_inner()).expect("utf8");
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "/very/long/…-Regular.ttf  Alpha");
+    assert_eq!(lines[1], "/fonts/Beta.ttf           Beta");
+
+    // A 40-column terminal leaves the path what the name column doesn't use.
+    let limits = WidthLimits {
+        max_path: None,
+        line: Some(40),
+    };
+    assert_eq!(limits.path_width(7), Some(33));
+    assert_eq!(limits.path_width(35), Some(MIN_PATH_WIDTH));
+    assert_eq!(truncate_middle("abcdef", Some(4)), "a…ef");
+    assert_eq!(truncate_middle("abc", Some(4)), "abc");
+}
+
+#[test]
+fn themes_parse_and_respect_no_color() {
+    let red = Theme::parse("mono:path=31").expect("theme");
+    assert_eq!(red.paint("/f.ttf", Role::Path), "\u{1b}[31m/f.ttf\u{1b}[0m");
+    assert_eq!(red.paint("Inter", Role::Name), "\u{1b}[1mInter\u{1b}[0m");
+    assert_eq!(Theme::parse("none").unwrap().paint("x", Role::Path), "x");
+    assert!(Theme::parse("neon").is_err());
+    assert!(Theme::parse("path=blue").is_err());
+    assert!(Theme::parse("path=1:mono").is_err());
+
+    env::set_var("NO_COLOR", "1");
+    let auto = Theme::resolve(ColorChoice::Auto, None, true).expect("theme");
+    let forced = Theme::resolve(ColorChoice::Always, Some("path=4"), false).expect("theme");
+    env::remove_var("NO_COLOR");
+    assert_eq!(auto.paint("x", Role::Path), "x");
+    assert_eq!(forced.paint("x", Role::Path), "\u{1b}[4mx\u{1b}[0m");
+}
+
+#[test]
+fn theme_highlights_matched_names_and_variable_marker() {
+    let pattern = parse_name_pattern("ter", NameMatch::default()).expect("pattern");
+    let theme = Theme::parse("name=33:match=1")
+        .expect("theme")
+        .with_highlight(&[pattern]);
+    assert_eq!(
+        theme.paint_name("Inter Tight", Role::Name),
+        "\u{1b}[33mIn\u{1b}[0m\u{1b}[33;1mter\u{1b}[0m\u{1b}[33m Tight\u{1b}[0m"
+    );
+
+    let matches = vec![metadata_with("Inter", Some("wght"), None)];
+    let mut buf = Vec::new();
+    write_columns(
+        &matches,
+        &[Column::Tags, Column::Name],
+        WidthLimits::default(),
+        &mut buf,
+        &theme,
+        false,
+    )
+    .expect("write");
+    let output = String::from_utf8(buf).expect("utf8");
+    assert!(output.contains("\u{1b}[35m var\u{1b}[0m  "), "{output:?}");
+    assert!(output.contains("\u{1b}[33;1mter\u{1b}[0m"), "{output:?}");
+}
+
 #[test]
 fn color_choice_is_applied() {
     let matches = vec![metadata_with("Alpha", None, None)];
 
     let mut buf = Cursor::new(Vec::new());
-    write_plain(&matches, &mut buf, true, false).expect("write");
+    write_plain(&matches, &mut buf, &Theme::colored(), false).expect("write");
 
     let output = String::from_utf8(buf.into_inner()).expect("utf8");
     assert!(output.contains("\u{1b}["));
@@ -250,8 +545,16 @@ fn parses_color_and_columns_flags() {
     let Command::Find(args) = cli.command else {
         panic!("expected find command");
     };
-    assert!(args.columns);
+    assert_eq!(args.columns, Some(Vec::new()));
     assert_eq!(args.color, ColorChoice::Always);
+
+    let cli =
+        Cli::try_parse_from(["typg", "find", "--columns=name,path", "/fonts"]).expect("parse cli");
+    let Command::Find(args) = cli.command else {
+        panic!("expected find command");
+    };
+    assert_eq!(args.columns, Some(vec![Column::Name, Column::Path]));
+    assert_eq!(args.paths, vec![PathBuf::from("/fonts")]);
 }
 
 #[test]
@@ -278,6 +581,21 @@ fn parses_serve_bind_flag() {
     assert_eq!(args.bind, "0.0.0.0:9999");
 }
 
+#[test]
+fn cors_origins_must_be_bare_origins() {
+    for origin in ["*", "https://fonts.example.com", "http://localhost:3000/"] {
+        assert!(check_cors_origin(origin).is_ok(), "{origin}");
+    }
+    for origin in [
+        "fonts.example.com",
+        "https://",
+        "https://example.com/app",
+        "ftp://x",
+    ] {
+        assert!(check_cors_origin(origin).is_err(), "{origin}");
+    }
+}
+
 #[test]
 fn help_output_includes_new_flags() {
     let mut root = Cli::command();
@@ -310,33 +628,92 @@ fn rejects_zero_jobs() {
         axes: Vec::new(),
         features: Vec::new(),
         scripts: Vec::new(),
+        langs: Vec::new(),
         tables: Vec::new(),
+        table_checksums: Vec::new(),
         name_patterns: Vec::new(),
+        name_match: NameMatchArgs::default(),
         creator_patterns: Vec::new(),
         license_patterns: Vec::new(),
         codepoints: Vec::new(),
         text: None,
+        unicode_blocks: Vec::new(),
+        coverage: Vec::new(),
         variable: false,
         weight: None,
         width: None,
         family_class: None,
+        embedding: None,
+        min_glyphs: None,
+        min_axes: None,
+        max_axes: None,
+        vendors: Vec::new(),
+        instances: Vec::new(),
+        only_collections: false,
+        collection_index: None,
+        unicode_cmap_only: false,
+        query_expr: None,
+        preset: None,
+        exclude: ExcludeArgs::default(),
+        ranking: RankArgs::default(),
+        per_family: None,
+        group_by: None,
+        sort: Vec::new(),
+        reverse: false,
+        offset: None,
+        limit: None,
         follow_symlinks: false,
+        hidden: false,
         stdin_paths: false,
         system_fonts: false,
         jobs: Some(0),
+        name_ids: Vec::new(),
+        incremental: None,
+        timeout: None,
+        remote: None,
+        remote_token: None,
+        via_daemon: None,
+        errors: ErrorArgs::default(),
         json: false,
         ndjson: false,
+        codepoint_format: CodepointOutput::Chars,
+        provenance: false,
+        format: None,
+        unicode_range_gap: 0,
+        template: None,
         paths_only: false,
-        columns: false,
+        uri: false,
+        columns: None,
+        max_path_width: None,
+        fields: Vec::new(),
+        no_header: false,
         collections: false,
+        expand_instances: false,
         count_only: false,
         color: ColorChoice::Auto,
+        theme: None,
     };
 
-    let result = run_find(args);
+    let result = run_find(args, true);
     assert!(result.is_err(), "jobs=0 should be rejected");
 }
 
+#[test]
+fn merge_entries_compares_paths_per_platform() {
+    let spelled = metadata_with("Alpha", None, None);
+    let mut lowercase = metadata_with("Alpha", Some("wght"), None);
+    lowercase.source.path = PathBuf::from("/fonts/alpha.ttf");
+
+    let merged = merge_entries(vec![spelled], vec![lowercase]);
+
+    if typg_core::discovery::CASE_INSENSITIVE_PATHS {
+        assert_eq!(merged.len(), 1);
+        assert!(merged[0].metadata.is_variable, "the later entry wins");
+    } else {
+        assert_eq!(merged.len(), 2);
+    }
+}
+
 #[test]
 fn merge_entries_deduplicates_by_path_and_ttc() {
     let existing = vec![metadata_with("Alpha", None, None)];
@@ -364,7 +741,7 @@ fn resolve_cache_path_prefers_env_override() {
     let target = tmp.path().join("cache.json");
 
     env::set_var("TYPOG_CACHE_PATH", &target);
-    let resolved = resolve_cache_path(&None).expect("resolve");
+    let resolved = resolve_cache_path(&None, None).expect("resolve");
     env::remove_var("TYPOG_CACHE_PATH");
 
     assert_eq!(resolved, target);
@@ -402,3 +779,1721 @@ fn prune_missing_entries_drops_nonexistent_paths() {
     assert_eq!(pruned.len(), 1, "missing entry should be dropped");
     assert_eq!(pruned[0].source.path, keep_path);
 }
+
+#[test]
+fn project_cache_root_is_found_in_ancestor() {
+    let tmp = tempdir().expect("tempdir");
+    let project_cache = tmp.path().join(".typg");
+    let nested = tmp.path().join("src/fonts");
+    fs::create_dir_all(&project_cache).expect("mkdir .typg");
+    fs::create_dir_all(&nested).expect("mkdir nested");
+
+    assert_eq!(find_project_cache_root(&nested), Some(project_cache));
+}
+
+#[test]
+fn explicit_cache_path_beats_profile() {
+    let target = PathBuf::from("/tmp/explicit.json");
+    let resolved =
+        resolve_cache_path(&Some(target.clone()), Some(CacheProfile::System)).expect("resolve");
+    assert_eq!(resolved, target);
+}
+
+#[test]
+fn parses_cache_profile_flag() {
+    let cli =
+        Cli::try_parse_from(["typg", "cache", "find", "--cache", "project"]).expect("parse cli");
+
+    let Command::Cache(CacheCommand::Find(args)) = cli.command else {
+        panic!("expected cache find command");
+    };
+    assert_eq!(args.profiles, vec![CacheProfile::Project]);
+}
+
+#[test]
+fn write_changes_lists_details_per_face() {
+    let cached = vec![metadata_with("Alpha", None, None)];
+    let scanned = vec![
+        metadata_with("Alpha", Some("wght"), None),
+        metadata_with("Beta", None, None),
+    ];
+    let changes = changed_faces(&cached, &scanned);
+
+    let mut buf = Cursor::new(Vec::new());
+    write_changes(&changes, &mut buf, false, false).expect("write");
+
+    let output = String::from_utf8(buf.into_inner()).expect("utf8");
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "/fonts/Alpha.ttf\taxes +wght; is_variable false -> true",
+            "/fonts/Beta.ttf\tnew",
+        ]
+    );
+}
+
+#[test]
+fn write_reports_lists_only_problem_checks() {
+    use typg_core::validate::CheckResult;
+
+    let reports = vec![FontReport {
+        source: TypgFontSource {
+            path: PathBuf::from("/fonts/Alpha.ttf"),
+            ttc_index: None,
+        },
+        status: CheckStatus::Warn,
+        checks: vec![
+            CheckResult {
+                id: "name/family",
+                status: CheckStatus::Pass,
+                message: "family name is \"Alpha\"".to_string(),
+            },
+            CheckResult {
+                id: "name/license",
+                status: CheckStatus::Warn,
+                message: "license URL (ID 14) is missing".to_string(),
+            },
+        ],
+    }];
+
+    let mut buf = Cursor::new(Vec::new());
+    write_reports(&reports, &mut buf, false, false).expect("write");
+
+    let output = String::from_utf8(buf.into_inner()).expect("utf8");
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "/fonts/Alpha.ttf\twarn",
+            "  warn name/license: license URL (ID 14) is missing",
+        ]
+    );
+}
+
+#[test]
+fn parses_validate_profile() {
+    let cli = Cli::try_parse_from(["typg", "validate", "--profile", "fontbakery-lite", "/fonts"])
+        .expect("parse");
+    match cli.command {
+        Command::Validate(args) => assert_eq!(args.profile, ValidateProfile::FontbakeryLite),
+        other => panic!("unexpected command: {other:?}"),
+    }
+
+    let cli =
+        Cli::try_parse_from(["typg", "validate", "--profile", "names", "/fonts"]).expect("parse");
+    match cli.command {
+        Command::Validate(args) => assert_eq!(args.profile, ValidateProfile::Names),
+        other => panic!("unexpected command: {other:?}"),
+    }
+}
+
+#[test]
+fn writes_selected_fields_with_header() {
+    let mut alpha = metadata_with("Alpha", Some("wght"), None);
+    alpha.metadata.family_name = Some("Alpha Sans".into());
+    alpha.metadata.weight_class = Some(400);
+    alpha.metadata.units_per_em = Some(1000);
+    let beta = metadata_with("Beta", None, None);
+
+    let mut buf = Cursor::new(Vec::new());
+    write_fields(
+        &[alpha, beta],
+        &[
+            Field::Path,
+            Field::Family,
+            Field::Weight,
+            Field::Axes,
+            Field::Upm,
+        ],
+        &mut buf,
+        true,
+        &Theme::plain(),
+        false,
+        WidthLimits::default(),
+    )
+    .expect("write");
+
+    let output = String::from_utf8(buf.into_inner()).expect("utf8");
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "PATH              FAMILY      WEIGHT  AXES  UPM",
+            "/fonts/Alpha.ttf  Alpha Sans  400     wght  1000",
+            "/fonts/Beta.ttf   Beta        -       -     -",
+        ]
+    );
+}
+
+#[test]
+fn fields_flag_parses_list_and_implies_columns() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "find",
+        "--fields",
+        "path,style,scripts",
+        "--no-header",
+        "/fonts",
+    ])
+    .expect("parse");
+    let args = match cli.command {
+        Command::Find(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    let format = OutputFormat::from_find(&args);
+    assert_eq!(
+        format.fields,
+        vec![Field::Path, Field::Style, Field::Scripts]
+    );
+    assert!(format.columns);
+    assert!(!format.header);
+
+    let parse = Cli::try_parse_from(["typg", "find", "--no-header", "/fonts"]);
+    assert!(parse.is_err(), "--no-header without --fields should fail");
+}
+
+#[test]
+fn codepoint_format_flag_selects_ranges() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "find",
+        "--ndjson",
+        "--codepoint-format",
+        "ranges",
+        "/fonts",
+    ])
+    .expect("parse");
+    let args = match cli.command {
+        Command::Find(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    assert_eq!(
+        OutputFormat::from_find(&args).codepoints,
+        CodepointFormat::Ranges
+    );
+}
+
+#[test]
+fn write_groups_lists_members_under_family() {
+    let mut regular = metadata_with("Inter-Regular", None, None);
+    regular.metadata.family_name = Some("Inter".into());
+    regular.metadata.style_name = Some("Regular".into());
+    regular.metadata.weight_class = Some(400);
+    let mut bold = metadata_with("Inter-Bold", None, None);
+    bold.metadata.family_name = Some("Inter".into());
+    bold.metadata.style_name = Some("Bold".into());
+    bold.metadata.weight_class = Some(700);
+    let groups = group_families(&[bold, regular]);
+
+    let format = OutputFormat {
+        json: false,
+        ndjson: false,
+        codepoints: CodepointFormat::Chars,
+        provenance: false,
+        roots: Vec::new(),
+        paths: false,
+        uri: false,
+        columns: false,
+        column_set: Column::value_variants().to_vec(),
+        max_path_width: None,
+        fields: Vec::new(),
+        header: true,
+        collections: false,
+        expand_instances: false,
+        export: None,
+        unicode_range_gap: 0,
+        template: None,
+        color: ColorChoice::Never,
+        theme: None,
+    };
+    let mut buf = Cursor::new(Vec::new());
+    write_groups(&groups, &mut buf, &format, &Theme::plain()).expect("write");
+
+    let output = String::from_utf8(buf.into_inner()).expect("utf8");
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        vec![
+            "Inter (2)",
+            "  Regular   400  /fonts/Inter-Regular.ttf",
+            "  Bold      700  /fonts/Inter-Bold.ttf",
+        ]
+    );
+}
+
+#[test]
+fn write_instances_lists_one_row_per_instance() {
+    let mut flex = metadata_with("Flex", Some("wght"), None);
+    flex.metadata.family_name = Some("Flex".into());
+    flex.metadata.named_instances = [("Light", 300.0), ("SemiBold", 600.0)]
+        .into_iter()
+        .map(|(name, wght)| NamedInstance {
+            name: name.into(),
+            postscript_name: None,
+            coordinates: [("wght".to_string(), wght)].into_iter().collect(),
+        })
+        .collect();
+    let query = Query::new().with_instances(vec!["semibold".into()]);
+    let rows = expand_instances(&[flex], &query);
+
+    let format = OutputFormat {
+        json: false,
+        ndjson: false,
+        codepoints: CodepointFormat::Chars,
+        provenance: false,
+        roots: Vec::new(),
+        paths: false,
+        uri: false,
+        columns: false,
+        column_set: Column::value_variants().to_vec(),
+        max_path_width: None,
+        fields: Vec::new(),
+        header: true,
+        collections: false,
+        expand_instances: true,
+        export: None,
+        unicode_range_gap: 0,
+        template: None,
+        color: ColorChoice::Never,
+        theme: None,
+    };
+    let mut buf = Cursor::new(Vec::new());
+    write_instances(&rows, &mut buf, &format, &Theme::plain()).expect("write");
+
+    let output = String::from_utf8(buf.into_inner()).expect("utf8");
+    assert_eq!(output, "Flex SemiBold  wght=600  /fonts/Flex.ttf\n");
+}
+
+#[test]
+fn instance_flags_parse_and_expand_conflicts_with_paths() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "find",
+        "--instance",
+        "SemiBold",
+        "--instance",
+        "Bold",
+        "--expand-instances",
+        "/fonts",
+    ])
+    .expect("parse cli");
+    let Command::Find(args) = cli.command else {
+        panic!("expected find command");
+    };
+    assert!(args.expand_instances);
+    let query = build_query(&args).expect("query");
+    assert_eq!(query.instances(), ["semibold", "bold"]);
+
+    for extra in ["--paths", "--rank"] {
+        let parse = Cli::try_parse_from(["typg", "find", "--expand-instances", extra, "/fonts"]);
+        assert!(
+            parse.is_err(),
+            "--expand-instances and {extra} should conflict"
+        );
+    }
+}
+
+#[test]
+fn group_by_conflicts_with_paths() {
+    let parse = Cli::try_parse_from(["typg", "find", "--group-by", "family", "--paths", "/fonts"]);
+    assert!(parse.is_err(), "--group-by and --paths should conflict");
+}
+
+#[test]
+fn cache_add_ingests_ndjson_records_without_scanning() {
+    let dir = tempdir().expect("tempdir");
+    let records = dir.path().join("faces.ndjson");
+    let cache_path = dir.path().join("cache.json");
+    // The fonts need not exist: records are stored as given.
+    let ndjson: String = [
+        metadata_with("Remote", None, None),
+        metadata_with("Inter", Some("wght"), None),
+    ]
+    .iter()
+    .map(|face| serde_json::to_string(face).expect("serialize") + "\n")
+    .collect();
+    fs::write(&records, ndjson).expect("write records");
+
+    let cli = Cli::try_parse_from([
+        "typg",
+        "cache",
+        "add",
+        "--from-ndjson",
+        records.to_str().unwrap(),
+        "--cache-path",
+        cache_path.to_str().unwrap(),
+    ])
+    .expect("parse");
+    let args = match cli.command {
+        Command::Cache(CacheCommand::Add(args)) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    run_cache_add(args, true).expect("cache add");
+
+    let cached = load_cache(&cache_path).expect("load cache");
+    let paths: Vec<_> = cached.iter().map(|m| m.source.path.clone()).collect();
+    assert_eq!(
+        paths,
+        [
+            PathBuf::from("/fonts/Inter.ttf"),
+            PathBuf::from("/fonts/Remote.ttf")
+        ]
+    );
+    assert!(cached[0].metadata.is_variable);
+
+    let cli = Cli::try_parse_from(["typg", "cache", "add", "--from-ndjson", "-"]).expect("parse");
+    let args = match cli.command {
+        Command::Cache(CacheCommand::Add(args)) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    let from_stdin = cache_additions(&args, Cursor::new(fs::read(&records).unwrap()), |_| {
+        Ok(false)
+    })
+    .expect("read stdin");
+    assert_eq!(from_stdin.faces.len(), 2);
+    assert!(from_stdin.report.is_none(), "nothing was scanned");
+
+    let both = Cli::try_parse_from(["typg", "cache", "add", "--from-ndjson", "-", "/fonts"]);
+    assert!(both.is_err(), "--from-ndjson replaces scan paths");
+}
+
+#[test]
+fn format_fontconfig_parses_and_conflicts_with_json() {
+    let cli =
+        Cli::try_parse_from(["typg", "cache", "find", "--format", "fontconfig"]).expect("parse");
+    let args = match cli.command {
+        Command::Cache(CacheCommand::Find(args)) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    assert_eq!(
+        OutputFormat::from_output(&args.output).export,
+        Some(ExportFormat::Fontconfig)
+    );
+
+    let parse = Cli::try_parse_from(["typg", "find", "--format", "fontconfig", "--json", "/f"]);
+    assert!(parse.is_err(), "--format and --json should conflict");
+    let parse = Cli::try_parse_from(["typg", "find", "--unicode-range-gap", "16", "/f"]);
+    assert!(parse.is_err(), "--unicode-range-gap needs --format");
+
+    let mut out = Vec::new();
+    write_export(
+        &[metadata_with("Inter", None, None)],
+        ExportFormat::Fontconfig,
+        0,
+        &mut out,
+    )
+    .expect("write");
+    let text = String::from_utf8(out).expect("utf8");
+    assert!(
+        text.contains(r#"<patelt name="file"><string>/fonts/Inter.ttf</string></patelt>"#),
+        "{text}"
+    );
+
+    let mut out = Vec::new();
+    write_export(
+        &[metadata_with("Inter", None, None)],
+        ExportFormat::Css,
+        0,
+        &mut out,
+    )
+    .expect("write");
+    let text = String::from_utf8(out).expect("utf8");
+    assert!(text.starts_with("@font-face {"), "{text}");
+}
+
+#[test]
+fn template_renders_fields_and_metadata_keys() {
+    let mut face = metadata_with("Inter", Some("wght"), None);
+    face.metadata.family_name = Some("Inter".into());
+    face.metadata.weight_class = Some(400);
+    face.metadata.script_tags = vec![tag4("latn").unwrap(), tag4("cyrl").unwrap()];
+
+    let template = Template::parse(
+        r"{path}\t{family}\t{weight}\t{axis_count}\t{script_tags}\t{{{is_variable}}}",
+    )
+    .expect("parse");
+    assert_eq!(
+        template.render(&face, false).expect("render"),
+        "/fonts/Inter.ttf\tInter\t400\t1\tlatn,cyrl\t{yes}"
+    );
+    assert_eq!(
+        Template::parse("{license_url}|{extra.nothing}")
+            .unwrap()
+            .render(&face, false)
+            .unwrap(),
+        "-|-"
+    );
+
+    let unknown = Template::parse("{postscript}")
+        .unwrap()
+        .render(&face, false);
+    assert!(unknown.is_err(), "unknown keys are reported");
+    assert!(Template::parse("{path").is_err());
+    assert!(Template::parse("a}b").is_err());
+    assert!(Template::parse("{}").is_err());
+
+    let parse = Cli::try_parse_from(["typg", "find", "--template", "{path}", "--json", "/f"]);
+    assert!(parse.is_err(), "--template and --json should conflict");
+}
+
+#[test]
+fn manifest_lines_parse_with_spaces_in_paths() {
+    let manifest = "# path mtime size\n/fonts/My Font.ttf\t1700000000\t12345\n\n/fonts/B.otf 1 2\n";
+    let entries = parse_manifest(manifest).expect("parse");
+    assert_eq!(
+        entries,
+        [
+            ManifestEntry {
+                path: PathBuf::from("/fonts/My Font.ttf"),
+                mtime: 1_700_000_000,
+                size: 12345,
+            },
+            ManifestEntry {
+                path: PathBuf::from("/fonts/B.otf"),
+                mtime: 1,
+                size: 2,
+            },
+        ]
+    );
+
+    let err = parse_manifest("/fonts/A.ttf yesterday 10").unwrap_err();
+    assert_eq!(err.to_string(), "line 1: invalid mtime 'yesterday'");
+    assert!(parse_manifest("/fonts/A.ttf").is_err());
+}
+
+#[test]
+fn manifest_scans_only_changed_files() {
+    let dir = tempdir().expect("tempdir");
+    let changed = dir.path().join("changed.ttf");
+    fs::write(&changed, b"not a font").expect("write");
+    let manifest = format!(
+        "{} 100 10\n{} 200 20\n",
+        dir.path().join("current.ttf").display(),
+        changed.display()
+    );
+
+    let cli = Cli::try_parse_from(["typg", "cache", "add", "--manifest", "-"]).expect("parse");
+    let args = match cli.command {
+        Command::Cache(CacheCommand::Add(args)) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    let additions = cache_additions(&args, Cursor::new(manifest), |entry| {
+        Ok(entry.mtime == 100 && entry.size == 10)
+    })
+    .expect("scan");
+    assert_eq!(additions.unchanged, Some(1));
+    let report = additions.report.expect("the changed file was scanned");
+    assert_eq!(report.files_scanned, 1);
+    assert_eq!(report.errors, 1, "junk is reported, not stored");
+    assert!(additions.faces.is_empty());
+}
+
+#[test]
+fn offset_and_limit_page_through_results() {
+    let items: Vec<u32> = (0..10).collect();
+    assert_eq!(paginate(items.clone(), None, None), items);
+    assert_eq!(paginate(items.clone(), Some(8), None), [8, 9]);
+    assert_eq!(paginate(items.clone(), Some(2), Some(3)), [2, 3, 4]);
+    assert!(paginate(items, Some(20), Some(3)).is_empty());
+
+    let cli = Cli::try_parse_from([
+        "typg", "cache", "find", "--offset", "20", "--limit", "10", "--sort", "name",
+    ])
+    .expect("parse");
+    let args = match cli.command {
+        Command::Cache(CacheCommand::Find(args)) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    assert_eq!((args.offset, args.limit), (Some(20), Some(10)));
+}
+
+#[test]
+fn sort_flags_parse_and_conflict_with_rank() {
+    let cli = Cli::try_parse_from(["typg", "find", "--sort", "mtime", "--reverse", "/fonts"])
+        .expect("parse");
+    let args = match cli.command {
+        Command::Find(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    assert_eq!(args.sort, [SortBy::Mtime]);
+    assert!(args.reverse);
+
+    let cli = Cli::try_parse_from([
+        "typg",
+        "cache",
+        "find",
+        "--sort",
+        "family,weight,width,italic",
+    ])
+    .expect("parse");
+    let args = match cli.command {
+        Command::Cache(CacheCommand::Find(args)) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    assert_eq!(
+        args.sort,
+        [
+            SortBy::Family,
+            SortBy::Weight,
+            SortBy::Width,
+            SortBy::Italic
+        ]
+    );
+
+    let parse = Cli::try_parse_from(["typg", "find", "--sort", "weight", "--rank", "/fonts"]);
+    assert!(parse.is_err(), "--sort and --rank should conflict");
+}
+
+#[test]
+fn timeout_parses_units_and_rejects_the_daemon() {
+    assert_eq!(parse_timeout("30s").unwrap(), Duration::from_secs(30));
+    assert_eq!(parse_timeout("45").unwrap(), Duration::from_secs(45));
+    assert_eq!(parse_timeout("500ms").unwrap(), Duration::from_millis(500));
+    assert_eq!(parse_timeout("1.5m").unwrap(), Duration::from_secs(90));
+    assert_eq!(parse_timeout("2h").unwrap(), Duration::from_secs(7200));
+    for bad in ["", "0s", "soon", "10d", "-3s"] {
+        assert!(parse_timeout(bad).is_err(), "{bad:?}");
+    }
+
+    let cli = Cli::try_parse_from(["typg", "find", "--timeout", "30s", "/fonts"]).expect("parse");
+    let args = match cli.command {
+        Command::Find(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    assert_eq!(args.timeout, Some(Duration::from_secs(30)));
+    let parse = Cli::try_parse_from(["typg", "find", "--timeout", "30s", "--via-daemon"]);
+    assert!(parse.is_err(), "the daemon does not scan");
+}
+
+#[test]
+fn commit_every_needs_the_index_and_a_positive_count() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "cache",
+        "add",
+        "--index",
+        "--commit-every",
+        "500",
+        "/fonts",
+    ])
+    .expect("parse");
+    let args = match cli.command {
+        Command::Cache(CacheCommand::Add(args)) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    assert_eq!(args.commit_every.map(NonZeroUsize::get), Some(500));
+
+    for args in [
+        &["typg", "cache", "add", "--commit-every", "500", "/fonts"][..],
+        &[
+            "typg",
+            "cache",
+            "add",
+            "--index",
+            "--commit-every",
+            "0",
+            "/fonts",
+        ],
+    ] {
+        assert!(Cli::try_parse_from(args).is_err(), "{args:?}");
+    }
+}
+
+#[test]
+fn reverse_without_sort_flips_path_order() {
+    let matches = vec![
+        metadata_with("A", None, None),
+        metadata_with("B", None, None),
+    ];
+    let sorted = sort_results(matches.clone(), &[], true);
+    assert_eq!(sorted[0].source.path, PathBuf::from("/fonts/B.ttf"));
+    let unsorted = sort_results(matches, &[], false);
+    assert_eq!(unsorted[0].source.path, PathBuf::from("/fonts/A.ttf"));
+}
+
+#[test]
+fn min_glyphs_and_vendor_flags_reach_query() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "find",
+        "--min-glyphs",
+        "300",
+        "--vendor",
+        "ADBE,goog",
+        "/fonts",
+    ])
+    .expect("parse");
+    let args = match cli.command {
+        Command::Find(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    let query = build_query(&args).expect("query");
+
+    assert_eq!(query.min_glyphs(), Some(300));
+    assert_eq!(query.vendors(), ["ADBE".to_string(), "goog".to_string()]);
+}
+
+#[test]
+fn cache_import_parses_rebase_pairs() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "cache",
+        "import",
+        "catalog.typg",
+        "--index",
+        "--rebase",
+        "/build/fonts=/opt/fonts",
+        "--rebase",
+        "C:\\Fonts=/fonts",
+    ])
+    .expect("parse");
+    let Command::Cache(CacheCommand::Import(args)) = cli.command else {
+        panic!("expected cache import");
+    };
+    assert_eq!(
+        args.rebase,
+        [
+            (PathBuf::from("/build/fonts"), PathBuf::from("/opt/fonts")),
+            (PathBuf::from("C:\\Fonts"), PathBuf::from("/fonts")),
+        ]
+    );
+
+    for bad in ["/build/fonts", "=/opt", "/build="] {
+        assert!(
+            Cli::try_parse_from(["typg", "cache", "import", "x", "--rebase", bad]).is_err(),
+            "{bad} should be rejected"
+        );
+    }
+    assert!(Cli::try_parse_from(["typg", "cache", "export", "--index"]).is_err());
+}
+
+#[test]
+fn cache_migrate_needs_exactly_one_direction() {
+    let cli =
+        Cli::try_parse_from(["typg", "cache", "migrate", "--to-json", "--json"]).expect("parse");
+    let Command::Cache(CacheCommand::Migrate(args)) = cli.command else {
+        panic!("expected cache migrate");
+    };
+    assert!(args.to_json && !args.to_index && args.json);
+
+    assert!(Cli::try_parse_from(["typg", "cache", "migrate"]).is_err());
+    assert!(Cli::try_parse_from(["typg", "cache", "migrate", "--to-index", "--to-json"]).is_err());
+}
+
+#[test]
+fn table_checksum_flag_reaches_query() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "find",
+        "--table-checksum",
+        "glyf=0xDEADBEEF,CFF=1a2b",
+        "/fonts",
+    ])
+    .expect("parse");
+    let args = match cli.command {
+        Command::Find(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    let query = build_query(&args).expect("query");
+
+    assert_eq!(
+        query.table_checksums(),
+        [
+            (tag4("glyf").unwrap(), 0xDEAD_BEEF),
+            (tag4("CFF").unwrap(), 0x1A2B)
+        ]
+    );
+
+    for bad in ["glyf", "glyf=xyz", "glyf=123456789"] {
+        let cli = Cli::try_parse_from(["typg", "find", "--table-checksum", bad, "/fonts"])
+            .expect("parse");
+        let Command::Find(args) = cli.command else {
+            unreachable!()
+        };
+        assert!(build_query(&args).is_err(), "{bad} should be rejected");
+    }
+
+    // Servers and daemons get the checksums with the rest of the query.
+    let cli = Cli::try_parse_from([
+        "typg",
+        "find",
+        "--table-checksum",
+        "glyf=1",
+        "--remote",
+        "http://localhost:8765",
+    ])
+    .expect("parse");
+    let Command::Find(args) = cli.command else {
+        unreachable!()
+    };
+    assert_eq!(
+        remote_request(&args, Vec::new(), false).table_checksums,
+        ["glyf=1"]
+    );
+}
+
+#[test]
+fn langs_flag_pads_language_tags() {
+    let cli = Cli::try_parse_from(["typg", "find", "--langs", "TRK,SRB", "/fonts"]).expect("parse");
+    let args = match cli.command {
+        Command::Find(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    let query = build_query(&args).expect("query");
+
+    assert_eq!(
+        query.languages(),
+        [tag4("TRK ").unwrap(), tag4("SRB ").unwrap()]
+    );
+}
+
+#[test]
+fn collection_flags_reach_query() {
+    let cli =
+        Cli::try_parse_from(["typg", "find", "--collection-index", "0", "/fonts"]).expect("parse");
+    let args = match cli.command {
+        Command::Find(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    let query = build_query(&args).expect("query");
+
+    assert!(query.requires_collection());
+    assert!(query.matches_source(&metadata_with("Noto", None, Some(0)).source));
+    assert!(!query.matches_source(&metadata_with("Noto", None, Some(1)).source));
+    assert!(!query.matches_source(&metadata_with("Inter", None, None).source));
+}
+
+#[test]
+fn unicode_block_and_coverage_flags_reach_query() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "find",
+        "--unicode-block",
+        "Greek and Coptic",
+        "--coverage",
+        "cyrillic:90%",
+        "/fonts",
+    ])
+    .expect("parse");
+    let args = match cli.command {
+        Command::Find(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    let query = build_query(&args).expect("query");
+
+    let coverage = query.coverage();
+    assert_eq!(coverage.len(), 2);
+    assert_eq!(coverage[0].block.name, "Greek and Coptic");
+    assert_eq!(coverage[0].min_ratio, 1.0);
+    assert_eq!(coverage[1].block.name, "Cyrillic");
+    assert!((coverage[1].min_ratio - 0.9).abs() < 1e-6);
+}
+
+#[test]
+fn coverage_flag_rejects_unknown_block() {
+    let cli =
+        Cli::try_parse_from(["typg", "find", "--coverage", "Klingon:50", "/fonts"]).expect("parse");
+    let args = match cli.command {
+        Command::Find(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    let err = build_query(&args).expect_err("unknown block");
+    assert!(err.to_string().contains("Klingon"));
+}
+
+#[test]
+fn watch_flags_parse() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "watch",
+        "--debounce",
+        "250",
+        "--daemon",
+        "--cache-path",
+        "/tmp/cache.json",
+        "/fonts",
+        "/more",
+    ])
+    .expect("parse");
+    match cli.command {
+        Command::Watch(args) => {
+            assert_eq!(
+                args.paths,
+                [PathBuf::from("/fonts"), PathBuf::from("/more")]
+            );
+            assert_eq!(args.debounce_ms, 250);
+            assert!(args.daemon);
+            assert_eq!(args.cache_path, Some(PathBuf::from("/tmp/cache.json")));
+        }
+        other => panic!("unexpected command: {other:?}"),
+    }
+
+    // Without paths, watch falls back to the roots `cache add` recorded.
+    match Cli::try_parse_from(["typg", "watch"])
+        .expect("parse")
+        .command
+    {
+        Command::Watch(args) => assert!(args.paths.is_empty()),
+        other => panic!("unexpected command: {other:?}"),
+    }
+}
+
+#[test]
+fn name_ids_flag_parses_comma_list() {
+    let cli =
+        Cli::try_parse_from(["typg", "find", "--name-ids", "1,16,25", "/fonts"]).expect("parse");
+    match cli.command {
+        Command::Find(args) => {
+            assert_eq!(args.name_ids, [1, 16, 25]);
+            assert_eq!(name_ids(&args.name_ids), Some(vec![1, 16, 25]));
+        }
+        other => panic!("unexpected command: {other:?}"),
+    }
+
+    let cli = Cli::try_parse_from(["typg", "cache", "add", "/fonts"]).expect("parse");
+    match cli.command {
+        Command::Cache(CacheCommand::Add(args)) => assert_eq!(name_ids(&args.name_ids), None),
+        other => panic!("unexpected command: {other:?}"),
+    }
+
+    assert!(Cli::try_parse_from(["typg", "find", "--name-ids", "x", "/fonts"]).is_err());
+}
+
+#[test]
+fn error_flags_pick_policy() {
+    let policy = |extra: &[&str]| -> Result<ErrorPolicy, clap::Error> {
+        let argv = ["typg", "find"].iter().chain(extra).chain(&["/fonts"]);
+        match Cli::try_parse_from(argv)?.command {
+            Command::Find(args) => Ok(args.errors.policy()),
+            other => panic!("unexpected command: {other:?}"),
+        }
+    };
+
+    assert_eq!(policy(&[]).unwrap(), ErrorPolicy::Skip);
+    assert_eq!(policy(&["--skip-errors"]).unwrap(), ErrorPolicy::Skip);
+    assert_eq!(policy(&["--strict"]).unwrap(), ErrorPolicy::Fail);
+    assert_eq!(
+        policy(&["--errors-json", "errors.json"]).unwrap(),
+        ErrorPolicy::Collect
+    );
+    assert!(policy(&["--strict", "--skip-errors"]).is_err());
+    assert!(policy(&["--strict", "--errors-json", "-"]).is_err());
+}
+
+#[test]
+fn cache_add_reports_unreadable_fonts() {
+    let dir = tempdir().expect("tempdir");
+    let fonts = dir.path().join("fonts");
+    fs::create_dir_all(&fonts).expect("mkdir");
+    fs::write(fonts.join("Broken.ttf"), b"not a font").expect("write");
+    let cache_path = dir.path().join("cache.json");
+    let errors_path = dir.path().join("errors.json");
+
+    let cache_add = |extra: &[&str]| {
+        let argv = ["typg", "cache", "add", "--cache-path"]
+            .into_iter()
+            .map(String::from)
+            .chain([cache_path.display().to_string()])
+            .chain(extra.iter().map(|arg| arg.to_string()))
+            .chain([fonts.display().to_string()]);
+        match Cli::try_parse_from(argv).expect("parse").command {
+            Command::Cache(CacheCommand::Add(args)) => run_cache_add(args, true),
+            other => panic!("unexpected command: {other:?}"),
+        }
+    };
+
+    cache_add(&["--errors-json", &errors_path.display().to_string()]).expect("skips bad fonts");
+    let failures: Vec<SearchError> =
+        serde_json::from_str(&fs::read_to_string(&errors_path).expect("read")).expect("json");
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].path, fonts.join("Broken.ttf"));
+    assert!(failures[0].reason.contains("not a font file"));
+
+    fs::remove_file(&cache_path).expect("rm cache");
+    let err = cache_add(&["--strict"]).expect_err("strict fails");
+    assert!(err.to_string().contains("Broken.ttf"));
+    assert!(!cache_path.exists());
+}
+
+#[test]
+fn watch_sync_drops_deleted_and_unparsable_fonts_from_cache() {
+    let dir = tempdir().expect("tempdir");
+    let fonts = dir.path().join("fonts");
+    fs::create_dir_all(fonts.join("old")).expect("mkdir");
+    fs::write(fonts.join("Broken.ttf"), b"not a font").expect("write");
+    fs::write(fonts.join("notes.txt"), b"ignored").expect("write");
+
+    let entry = |path: PathBuf| {
+        let mut entry = metadata_with("Face", None, None);
+        entry.source.path = path;
+        entry
+    };
+    let cache_path = dir.path().join("cache.json");
+    let kept = entry(PathBuf::from("/elsewhere/Kept.ttf"));
+    write_cache(
+        &cache_path,
+        &[
+            kept.clone(),
+            entry(fonts.join("Broken.ttf")),
+            entry(fonts.join("Gone.ttf")),
+            entry(fonts.join("old").join("Inner.ttf")),
+        ],
+    )
+    .expect("write cache");
+
+    let mut syncer = watch::Syncer::new(
+        watch::Store::Cache(cache_path.clone()),
+        SearchOptions::default(),
+    );
+    let changed = [
+        fonts.join("Broken.ttf"),
+        fonts.join("Gone.ttf"),
+        fonts.join("notes.txt"),
+        fonts.join("old"),
+        fonts.join("old").join("Inner.ttf"),
+    ]
+    .into_iter()
+    .collect();
+    let stats = syncer.apply(changed).expect("sync");
+
+    assert_eq!(stats.indexed, 0);
+    assert_eq!(stats.removed, 3);
+    let remaining = load_cache(&cache_path).expect("load");
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].source.path, kept.source.path);
+}
+
+#[test]
+fn show_reads_font_from_stdin() {
+    let cli = Cli::try_parse_from(["typg", "show", "-"]).expect("parse");
+    let Command::Show(args) = cli.command else {
+        panic!("expected show command");
+    };
+    assert_eq!(args.file, PathBuf::from("-"));
+
+    let err = run_show(args, Cursor::new(b"not a font".to_vec())).expect_err("garbage");
+    assert!(format!("{err:#}").contains("not a font file"));
+}
+
+#[test]
+fn show_lists_every_field_per_face() {
+    let faces = vec![
+        metadata_with("Alpha", None, Some(0)),
+        metadata_with("Beta", Some("wght"), Some(1)),
+    ];
+    let mut buf = Vec::new();
+    write_show(&faces, &mut buf).expect("write");
+    let text = String::from_utf8(buf).expect("utf8");
+
+    let blocks: Vec<&str> = text.split("\n\n").collect();
+    assert_eq!(blocks.len(), 2);
+    assert!(blocks[0].starts_with("/fonts/Alpha.ttc#0\n"));
+    assert!(blocks[1].contains("  AXES        wght\n"));
+    assert!(blocks[1].contains("  VARIABLE    yes\n"));
+    assert!(!text.contains("PATH"));
+}
+
+#[test]
+fn inspect_writes_one_section_per_table() {
+    use typg_core::inspect::{
+        AxisInfo, CmapSummary, FontInspection, LangSysFeatures, NameEntry, TableEntry,
+    };
+    use typg_core::search::TypgFontSource;
+
+    let cli = Cli::try_parse_from(["typg", "inspect", "-"]).expect("parse");
+    let Command::Inspect(args) = cli.command else {
+        panic!("expected inspect command");
+    };
+    let err = run_inspect(args, Cursor::new(b"not a font".to_vec())).expect_err("garbage");
+    assert!(format!("{err:#}").contains("not a font file"));
+
+    let face = FontInspection {
+        source: TypgFontSource {
+            path: PathBuf::from("/fonts/Alpha.ttf"),
+            ttc_index: None,
+        },
+        names: vec![NameEntry {
+            name_id: 1,
+            platform_id: 3,
+            encoding_id: 1,
+            language_id: 0x0409,
+            language: Some("en-US".to_string()),
+            value: "Alpha".to_string(),
+        }],
+        tables: vec![TableEntry {
+            tag: "GSUB".to_string(),
+            length: 1234,
+            checksum: 0xDEADBEEF,
+        }],
+        axes: vec![AxisInfo {
+            tag: "wght".to_string(),
+            name: Some("Weight".to_string()),
+            min: 100.0,
+            default: 400.0,
+            max: 900.0,
+            hidden: false,
+        }],
+        layout: vec![LangSysFeatures {
+            table: "GSUB",
+            script: "latn".to_string(),
+            language: Some("TRK".to_string()),
+            features: vec!["liga".to_string(), "locl".to_string()],
+        }],
+        cmap: CmapSummary {
+            subtables: Vec::new(),
+            codepoints: 3,
+            ranges: "U+0041-0043".to_string(),
+            blocks: [("Basic Latin".to_string(), 3)].into_iter().collect(),
+        },
+        os2: None,
+        hhea: None,
+        units_per_em: Some(1000),
+    };
+    let mut buf = Vec::new();
+    write_inspection(&[face], &mut buf).expect("write");
+    let text = String::from_utf8(buf).expect("utf8");
+
+    assert!(text.starts_with("/fonts/Alpha.ttf\n"));
+    assert!(text.contains("      1  3.1 en-US     Alpha\n"), "{text}");
+    assert!(text.contains("  GSUB        1234  DEADBEEF\n"), "{text}");
+    assert!(text.contains("  wght  100..400..900  Weight\n"), "{text}");
+    assert!(text.contains("  GSUB latn/TRK   liga locl\n"), "{text}");
+    assert!(text.contains("           3  Basic Latin\n"), "{text}");
+    assert!(text.contains("  unitsPerEm      1000\n"), "{text}");
+}
+
+#[test]
+fn name_match_flags_shape_name_patterns() {
+    let query = |extra: &[&str]| {
+        let argv = ["typg", "find", "-n", "inter"]
+            .into_iter()
+            .chain(extra.iter().copied())
+            .chain(["/fonts"]);
+        let Command::Find(args) = Cli::try_parse_from(argv).expect("parse").command else {
+            panic!("expected find command");
+        };
+        build_query(&args).expect("query")
+    };
+    let mut face = metadata_with("Inter Display", None, None);
+
+    assert!(!query(&[]).matches(&face.metadata));
+    assert!(query(&["--name-ignore-case"]).matches(&face.metadata));
+    assert!(!query(&["--name-ignore-case", "--name-exact"]).matches(&face.metadata));
+
+    face.metadata.names = vec!["INTER".into()];
+    assert!(query(&["--name-ignore-case", "--name-exact"]).matches(&face.metadata));
+}
+
+#[test]
+fn preset_flag_combines_with_other_filters() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "find",
+        "--preset",
+        "code-fonts",
+        "-w",
+        "700",
+        "/fonts",
+    ])
+    .expect("parse");
+    let Command::Find(args) = cli.command else {
+        panic!("expected find command");
+    };
+    assert_eq!(args.preset, Some(QueryPreset::CodeFonts));
+    let query = build_query(&args).expect("query");
+
+    let mut face = metadata_with("Fira Mono", None, None);
+    face.metadata.codepoints = (' '..='~').collect();
+    face.metadata.weight_class = Some(700);
+    assert!(query.matches(&face.metadata));
+    face.metadata.weight_class = Some(400);
+    assert!(!query.matches(&face.metadata), "-w still applies");
+    face.metadata.weight_class = Some(700);
+    face.metadata.names = vec!["Fira Sans".into()];
+    assert!(!query.matches(&face.metadata), "preset still applies");
+
+    assert!(Cli::try_parse_from(["typg", "find", "--preset", "nope", "/fonts"]).is_err());
+}
+
+#[test]
+fn weight_stats_list_buckets_and_families_without_bold() {
+    let mut regular = metadata_with("Inter", None, None);
+    regular.metadata.weight_class = Some(400);
+    let mut bold = metadata_with("Inter Bold", None, None);
+    bold.metadata.family_name = Some("Inter".into());
+    bold.metadata.weight_class = Some(700);
+    regular.metadata.family_name = Some("Inter".into());
+    let mut light = metadata_with("Lora", None, None);
+    light.metadata.weight_class = Some(300);
+
+    let mut out = Vec::new();
+    write_weight_stats(&weight_stats(&[regular, bold, light]), &mut out).expect("write");
+    let text = String::from_utf8(out).expect("utf8");
+    assert!(text.contains("  400 Regular     1\n"), "{text}");
+    assert!(text.contains("  700 Bold        1\n"), "{text}");
+    assert!(text.contains("  900 Black       0\n"), "{text}");
+    assert!(!text.contains("unknown"), "{text}");
+    assert!(
+        text.ends_with("Families without bold (1):\n  Lora\n"),
+        "{text}"
+    );
+}
+
+#[test]
+fn cache_info_reports_readable_sizes_and_directories() {
+    assert_eq!(human_bytes(512), "512 B");
+    assert_eq!(human_bytes(3 * 1024 * 1024 + 400 * 1024), "3.4 MB");
+    assert_eq!(thousands(999), "999");
+    assert_eq!(thousands(1_234_567), "1,234,567");
+
+    let entries = [
+        metadata_with("A", None, None),
+        metadata_with("B", None, Some(0)),
+        metadata_with("C", None, Some(1)),
+    ];
+    let roots = [ScanRoot {
+        path: PathBuf::from("/fonts"),
+        include_hidden: true,
+        ..ScanRoot::default()
+    }];
+    let info = StoreInfo {
+        label: "Cache",
+        kind: "json",
+        path: Path::new("/tmp/cache.json"),
+        entries: &entries,
+        roots: &roots,
+        collection: None,
+        collections: vec!["masters".into(), "review".into()],
+        size_bytes: 2048,
+        updated: Some(std::time::UNIX_EPOCH),
+    };
+    let json = info.to_json();
+    assert_eq!(json["size"], "2.0 KB");
+    assert_eq!(json["updated"], "1970-01-01T00:00:00Z");
+    assert_eq!(json["directories"]["/fonts"], 3);
+    assert_eq!(json["roots"][0]["include_hidden"], true);
+    assert_eq!(json["collection"], serde_json::Value::Null);
+    assert_eq!(json["collections"][1], "review");
+
+    let mut out = Vec::new();
+    info.write_summary(&mut out).expect("write");
+    info.write_roots(&mut out).expect("write");
+    info.write_directories(&mut out).expect("write");
+    let text = String::from_utf8(out).expect("utf8");
+    assert!(text.contains("Fonts:   3\n"), "{text}");
+    assert!(text.contains("Size:    2.0 KB (2,048 bytes)\n"), "{text}");
+    assert!(text.contains("Collections: masters, review\n"), "{text}");
+    assert!(text.contains("Roots:   1\n  /fonts (hidden)\n"), "{text}");
+    assert!(text.ends_with("Directories: 1\n  3  /fonts\n"), "{text}");
+}
+
+#[test]
+fn collection_flag_takes_several_plain_names() {
+    let cli = Cli::try_parse_from(["typg", "--collection", "masters,review", "cache", "find"])
+        .expect("parse");
+    assert_eq!(cli.collection_names, ["masters", "review"]);
+    assert!(Cli::try_parse_from(["typg", "--collection", "../up", "cache", "list"]).is_err());
+
+    let store = Path::new("/home/me/.cache/typg/cache.json");
+    let names = ["default".to_string(), "masters".to_string()];
+    assert_eq!(
+        collection_stores(store, &names),
+        [
+            store.to_path_buf(),
+            PathBuf::from("/home/me/.cache/typg/collections/masters/cache.json")
+        ]
+    );
+    assert_eq!(collection_stores(store, &[]), [store.to_path_buf()]);
+}
+
+#[test]
+fn unicode_cmap_only_flag_drops_legacy_cmaps() {
+    let cli =
+        Cli::try_parse_from(["typg", "find", "--unicode-cmap-only", "/fonts"]).expect("parse");
+    let args = match cli.command {
+        Command::Find(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    let query = build_query(&args).expect("query");
+
+    let mut legacy = metadata_with("Legacy", None, None);
+    legacy.metadata.cmap_subtables = vec![CmapSubtable {
+        platform_id: 1,
+        encoding_id: 0,
+        format: 0,
+    }];
+    let mut modern = metadata_with("Modern", None, None);
+    modern.metadata.cmap_subtables = vec![CmapSubtable {
+        platform_id: 3,
+        encoding_id: 1,
+        format: 4,
+    }];
+    assert!(!query.matches(&legacy.metadata));
+    assert!(query.matches(&modern.metadata));
+    assert_eq!(Field::Cmap.render(&legacy, false), "1.0:0");
+}
+
+#[test]
+fn verify_hash_drops_entries_whose_file_changed() {
+    let dir = tempdir().expect("tempdir");
+    let kept = dir.path().join("Kept.ttf");
+    let edited = dir.path().join("Edited.ttf");
+    fs::write(&kept, b"kept").expect("write");
+    fs::write(&edited, b"before").expect("write");
+
+    let entry = |path: &Path, name: &str| {
+        let mut entry = metadata_with(name, None, None);
+        entry.source.path = path.to_path_buf();
+        entry.metadata.file_hash = Some(hash_file(path).expect("hash"));
+        entry
+    };
+    let mut unhashed = metadata_with("Unhashed", None, None);
+    unhashed.source.path = edited.clone();
+    let entries = vec![entry(&kept, "Kept"), entry(&edited, "Edited"), unhashed];
+    fs::write(&edited, b"after").expect("rewrite");
+
+    let names: Vec<String> = prune_changed(entries)
+        .into_iter()
+        .map(|e| e.metadata.names[0].to_string())
+        .collect();
+    assert_eq!(names, ["Kept", "Unhashed"]);
+    assert_eq!(
+        clean_summary(1, 5, 3, true, false),
+        "removed 1 missing and 1 changed entries (5 → 3)"
+    );
+    assert_eq!(
+        clean_summary(2, 5, 3, false, true),
+        "would remove 2 missing entries (5 → 3)"
+    );
+}
+
+#[test]
+fn cache_clean_dry_run_leaves_the_cache_alone() {
+    let dir = tempdir().expect("tempdir");
+    let present = dir.path().join("Present.ttf");
+    fs::write(&present, b"present").expect("write");
+    let cache_path = dir.path().join("cache.json");
+    let mut kept = metadata_with("Present", None, None);
+    kept.source.path = present;
+    let gone = metadata_with("Gone", None, None);
+    write_cache(&cache_path, &[kept, gone]).expect("write cache");
+
+    let clean = |extra: &[&str]| {
+        let argv = ["typg", "cache", "clean", "--cache-path"]
+            .into_iter()
+            .map(String::from)
+            .chain([cache_path.display().to_string()])
+            .chain(extra.iter().map(|arg| arg.to_string()));
+        match Cli::try_parse_from(argv).expect("parse").command {
+            Command::Cache(CacheCommand::Clean(args)) => run_cache_clean(args, true),
+            other => panic!("unexpected command: {other:?}"),
+        }
+    };
+
+    clean(&["--dry-run"]).expect("dry run");
+    assert_eq!(load_cache(&cache_path).expect("load").len(), 2);
+    clean(&[]).expect("clean");
+    assert_eq!(load_cache(&cache_path).expect("load").len(), 1);
+}
+
+#[test]
+fn cache_remove_drops_only_matching_entries() {
+    let dir = tempdir().expect("tempdir");
+    let cache_path = dir.path().join("cache.json");
+    let entries = [
+        metadata_with("Old Corp Sans", None, None),
+        metadata_with("Old Corp Serif", None, None),
+        metadata_with("Fresh Sans", None, None),
+    ];
+    write_cache(&cache_path, &entries).expect("write cache");
+
+    let remove = |extra: &[&str]| {
+        let argv = ["typg", "cache", "remove", "--cache-path"]
+            .into_iter()
+            .map(String::from)
+            .chain([cache_path.display().to_string()])
+            .chain(extra.iter().map(|arg| arg.to_string()));
+        match Cli::try_parse_from(argv)?.command {
+            Command::Cache(CacheCommand::Remove(args)) => run_cache_remove(*args, true),
+            other => panic!("unexpected command: {other:?}"),
+        }
+    };
+
+    // Without a filter the whole cache would match; that needs spelling out.
+    assert!(remove(&[]).is_err());
+    remove(&["--name", "Old Corp", "--dry-run"]).expect("dry run");
+    assert_eq!(load_cache(&cache_path).expect("load").len(), 3);
+    remove(&["--name", "Old Corp", "--not-name", "Serif"]).expect("remove");
+    let left: Vec<_> = load_cache(&cache_path)
+        .expect("load")
+        .into_iter()
+        .map(|entry| entry.metadata.names[0].to_string())
+        .collect();
+    assert_eq!(left, ["Fresh Sans", "Old Corp Serif"]);
+}
+
+#[test]
+fn cache_verify_reports_missing_and_modified_files_apart() {
+    let dir = tempdir().expect("tempdir");
+    let intact = dir.path().join("Intact.ttf");
+    let edited = dir.path().join("Edited.ttf");
+    let unhashed = dir.path().join("Unhashed.ttf");
+    for path in [&intact, &edited, &unhashed] {
+        fs::write(path, b"before").expect("write");
+    }
+
+    let entry = |path: &Path, hash: bool| {
+        let mut entry = metadata_with("Face", None, None);
+        entry.source.path = path.to_path_buf();
+        entry.metadata.file_hash = hash.then(|| hash_file(path).expect("hash"));
+        entry
+    };
+    let entries = vec![
+        entry(&intact, true),
+        entry(&edited, true),
+        entry(&unhashed, false),
+        entry(&dir.path().join("Gone.ttf"), false),
+    ];
+    fs::write(&edited, b"after").expect("rewrite");
+
+    let statuses = |hash| -> Vec<(String, &'static str)> {
+        verify_files(&entries, hash)
+            .into_iter()
+            .map(|c| {
+                let name = c.path.file_name().unwrap().to_string_lossy().into_owned();
+                (name, c.status.label())
+            })
+            .collect()
+    };
+    let checks = verify_files(&entries, true);
+    assert_eq!(
+        statuses(true),
+        [
+            ("Edited.ttf".to_string(), "modified"),
+            ("Gone.ttf".to_string(), "missing"),
+            ("Intact.ttf".to_string(), "ok"),
+            ("Unhashed.ttf".to_string(), "unhashed"),
+        ]
+    );
+    assert_eq!(
+        verify_summary(&checks, true),
+        "verified 4 files: 1 missing, 1 modified, 0 unreadable (1 without a recorded hash)"
+    );
+    assert_eq!(
+        statuses(false)[0].1,
+        "ok",
+        "without --hash only existence is checked"
+    );
+}
+
+#[test]
+fn axis_count_flags_and_field() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "find",
+        "--min-axes",
+        "2",
+        "--fields",
+        "name,axis_count",
+        "/fonts",
+    ])
+    .expect("parse");
+    let args = match cli.command {
+        Command::Find(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    let query = build_query(&args).expect("query");
+    let mut multi = metadata_with("Multi", Some("wght"), None);
+    multi.metadata.axis_tags.push(tag4("wdth").unwrap());
+    assert!(query.matches(&multi.metadata));
+    assert!(!query.matches(&metadata_with("Single", Some("wght"), None).metadata));
+
+    let format = OutputFormat::from_find(&args);
+    assert_eq!(format.fields, vec![Field::Name, Field::AxisCount]);
+    assert_eq!(Field::AxisCount.render(&multi, false), "2");
+}
+
+#[test]
+fn embedding_flag_rejects_restricted_fonts() {
+    let cli = Cli::try_parse_from([
+        "typg",
+        "find",
+        "--embedding",
+        "installable",
+        "--fields",
+        "name,embedding",
+        "/fonts",
+    ])
+    .expect("parse");
+    let args = match cli.command {
+        Command::Find(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    let query = build_query(&args).expect("query");
+    let mut free = metadata_with("Free", None, None);
+    free.metadata.embedding = Some(typg_core::search::Embedding::Installable);
+    let mut locked = metadata_with("Locked", None, None);
+    locked.metadata.embedding = Some(typg_core::search::Embedding::Restricted);
+    assert!(query.matches(&free.metadata));
+    assert!(!query.matches(&locked.metadata));
+    assert_eq!(Field::Embedding.render(&locked, false), "restricted");
+
+    let cli =
+        Cli::try_parse_from(["typg", "find", "--embedding", "free", "/fonts"]).expect("parse");
+    let Command::Find(args) = cli.command else {
+        panic!("expected find command");
+    };
+    assert!(build_query(&args).is_err());
+}
+
+#[test]
+fn merge_unions_result_files_and_dedupes() {
+    let alpha = metadata_with("Alpha", None, None);
+    let beta = metadata_with("Beta", None, None);
+    let mut moved_alpha = alpha.clone();
+    moved_alpha.source.path = PathBuf::from("/other/Alpha.ttf");
+
+    let mut json = Vec::new();
+    write_json_pretty_with(
+        &[beta.clone(), alpha.clone()],
+        &mut json,
+        CodepointFormat::Chars,
+    )
+    .expect("json");
+    let mut ndjson = Vec::new();
+    write_ndjson_with(
+        &[alpha.clone(), moved_alpha],
+        &mut ndjson,
+        CodepointFormat::Chars,
+    )
+    .expect("ndjson");
+    let sets = vec![
+        parse_results(&json).expect("parse json"),
+        parse_results(&ndjson).expect("parse ndjson"),
+    ];
+    assert!(parse_results(b"not json").is_err());
+
+    let paths = |entries: Vec<TypgFontFaceMatch>| -> Vec<String> {
+        entries
+            .iter()
+            .map(|e| e.source.path.display().to_string())
+            .collect()
+    };
+    assert_eq!(merge_results(sets.clone(), None).len(), 4);
+    assert_eq!(
+        paths(merge_results(sets.clone(), Some(DedupeKey::Path))),
+        ["/fonts/Alpha.ttf", "/fonts/Beta.ttf", "/other/Alpha.ttf"]
+    );
+    assert_eq!(
+        paths(merge_results(sets, Some(DedupeKey::Id))),
+        ["/fonts/Alpha.ttf", "/fonts/Beta.ttf"]
+    );
+
+    let cli =
+        Cli::try_parse_from(["typg", "merge", "--dedupe", "a.ndjson", "b.json"]).expect("parse");
+    match cli.command {
+        Command::Merge(args) => {
+            assert_eq!(args.dedupe, Some(DedupeKey::Path));
+            assert_eq!(args.files.len(), 2);
+        }
+        other => panic!("unexpected command: {other:?}"),
+    }
+}
+
+#[test]
+fn remote_search_round_trips_through_a_server() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    listener.set_nonblocking(true).expect("nonblocking");
+    std::thread::spawn(move || {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+            axum::serve(listener, server::router(server::ServerState::default()))
+                .await
+                .unwrap();
+        });
+    });
+
+    let remote = Remote::parse(&format!("http://{addr}/")).expect("parse url");
+    let dir = tempdir().expect("tempdir");
+    let response = remote
+        .search(&SearchRequest {
+            paths: vec![dir.path().to_path_buf()],
+            ..SearchRequest::default()
+        })
+        .expect("search");
+    assert_eq!(response.total, 0);
+    assert_eq!(response.matches.map(|m| m.len()), Some(0));
+
+    let err = remote
+        .search(&SearchRequest {
+            axes: vec!["toolong".into()],
+            paths: vec![dir.path().to_path_buf()],
+            ..SearchRequest::default()
+        })
+        .expect_err("bad tag");
+    assert!(
+        err.to_string()
+            .starts_with("server rejected the query (axes):"),
+        "{err}"
+    );
+}
+
+#[test]
+fn remote_search_sends_the_servers_token() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    listener.set_nonblocking(true).expect("nonblocking");
+    std::thread::spawn(move || {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+            let state = server::ServerState {
+                token: Some(Arc::from("s3cret")),
+                ..server::ServerState::default()
+            };
+            axum::serve(listener, server::router(state)).await.unwrap();
+        });
+    });
+
+    let remote = Remote::parse(&format!("http://{addr}/")).expect("parse url");
+    let dir = tempdir().expect("tempdir");
+    let req = SearchRequest {
+        paths: vec![dir.path().to_path_buf()],
+        ..SearchRequest::default()
+    };
+    let err = remote.search(&req).expect_err("no token");
+    assert!(err.to_string().contains("Bearer"), "{err}");
+    let response = remote.with_token("s3cret").search(&req).expect("search");
+    assert_eq!(response.total, 0);
+}
+
+#[test]
+fn remote_search_fails_when_the_server_stalls() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    // Accept the connection and never answer.
+    let holder = std::thread::spawn(move || listener.accept().map(|(stream, _)| stream));
+
+    let remote = Remote::parse(&format!("http://{addr}/"))
+        .expect("parse url")
+        .with_timeout(Duration::from_millis(200));
+    let err = remote
+        .search(&SearchRequest::default())
+        .expect_err("stalled server");
+    assert!(err.to_string().contains("stalled"), "{err}");
+    drop(holder.join());
+}
+
+#[test]
+fn remote_parses_urls_and_chunked_responses() {
+    assert!(Remote::parse("https://fonts.example").is_err());
+    assert_eq!(
+        Remote::parse("fonts.example").unwrap(),
+        Remote::parse("http://fonts.example:80").unwrap()
+    );
+
+    let response = b"HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n4\r\n{\"a\"\r\n3;x=y\r\n:1}\r\n0\r\n\r\n";
+    let (status, body) = remote::parse_response(response).unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(body, b"{\"a\":1}");
+
+    let response = b"HTTP/1.1 422 Unprocessable Entity\r\nContent-Length: 2\r\n\r\n{}";
+    assert_eq!(
+        remote::parse_response(response).unwrap(),
+        (422, b"{}".to_vec())
+    );
+}
+
+#[test]
+fn select_parses_css_weight_and_width() {
+    let cli = Cli::try_parse_from([
+        "typg", "select", "--family", "Inter", "--weight", "650", "--width", "87.5%", "--italic",
+        "/fonts",
+    ])
+    .expect("parse");
+    let args = match cli.command {
+        Command::Select(args) => args,
+        other => panic!("unexpected command: {other:?}"),
+    };
+    assert_eq!(args.weight, 650.0);
+    assert_eq!(args.width, 87.5);
+    assert!(args.italic);
+
+    let cli =
+        Cli::try_parse_from(["typg", "select", "--family", "Inter", "/fonts"]).expect("parse");
+    let Command::Select(args) = cli.command else {
+        panic!("expected select");
+    };
+    assert_eq!(
+        (args.weight, args.width, args.italic),
+        (400.0, 100.0, false)
+    );
+
+    for bad in ["0", "1001", "bold"] {
+        assert!(parse_css_weight(bad).is_err(), "{bad:?}");
+    }
+    assert!(parse_css_width("0%").is_err());
+    assert!(Cli::try_parse_from(["typg", "select", "/fonts"]).is_err());
+}
+
+#[test]
+fn bench_is_hidden_and_tabulates_every_pass() {
+    let cli = Cli::try_parse_from(["typg", "bench", "/fonts", "-J", "4"]).expect("parse");
+    let Command::Bench(args) = cli.command else {
+        panic!("expected bench");
+    };
+    assert_eq!((args.runs, args.jobs), (3, Some(4)));
+    assert!(Cli::try_parse_from(["typg", "bench", "/fonts", "--runs", "0"]).is_err());
+    let help = Cli::command().render_help().to_string();
+    assert!(!help.contains("bench"), "{help}");
+
+    let pass = |fonts_per_second: f64| typg_core::bench::BenchRun {
+        files: 1200,
+        faces: 1500,
+        bytes: 250_000_000,
+        errors: 2,
+        threads: 8,
+        scan_seconds: 2.0,
+        fonts_per_second,
+        mb_per_second: fonts_per_second / 4.0,
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    write_bench(&[pass(400.0), pass(600.0)], &mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 4, "{text}");
+    assert!(lines[0].starts_with("pass"));
+    assert!(lines[2].contains("  1500  ") && lines[2].contains("250.0"));
+    assert_eq!(
+        lines[3],
+        "best: pass 2, 600.0 fonts/s, 150.0 MB/s; read, parse and match summed over 8 threads; 2 files unreadable"
+    );
+}
+
+#[test]
+#[cfg(feature = "hpindex")]
+fn index_map_size_parses_units_and_is_global() {
+    assert_eq!(parse_byte_size("4096").unwrap(), 4096);
+    assert_eq!(parse_byte_size("512MB").unwrap(), 512 << 20);
+    assert_eq!(parse_byte_size("1.5g").unwrap(), 3 << 29);
+    assert_eq!(parse_byte_size("2 GiB").unwrap(), 2 << 30);
+    for bad in ["", "0", "MB", "12X", "-1G"] {
+        assert!(parse_byte_size(bad).is_err(), "{bad:?}");
+    }
+
+    let cli = Cli::try_parse_from([
+        "typg",
+        "cache",
+        "find",
+        "--index",
+        "--index-map-size",
+        "256M",
+        "--name",
+        "Inter",
+    ])
+    .expect("parse");
+    assert_eq!(cli.index_map_size, Some(256 << 20));
+}
@@ -0,0 +1,228 @@
+//! Output colors.
+//!
+//! Text output paints a few roles — paths, names, tag counts, headers,
+//! the part of a name a `--name` pattern matched, and the variable-font
+//! marker — each with its own ANSI style. A [`Theme`] maps roles to styles.
+//! Pick a built-in one with `--theme` or `TYPOG_THEME`, or override single
+//! roles GREP_COLORS-style: `name=1;33:match=7`.
+//!
+//! Colors are off when stdout is not a terminal or `NO_COLOR` is set, unless
+//! `--color always` asks for them.
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+
+use std::env;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use crate::ColorChoice;
+
+/// Something the text output paints.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Role {
+    /// File paths.
+    Path,
+    /// Font, family and instance names.
+    Name,
+    /// Tag counts and axis coordinates.
+    Tags,
+    /// Column headers.
+    Header,
+    /// The part of a name a `--name` pattern matched, on top of [`Role::Name`].
+    Match,
+    /// The variable-font marker.
+    Variable,
+}
+
+impl Role {
+    const ALL: [Role; 6] = [
+        Role::Path,
+        Role::Name,
+        Role::Tags,
+        Role::Header,
+        Role::Match,
+        Role::Variable,
+    ];
+
+    /// The role's key in a theme spec.
+    fn key(self) -> &'static str {
+        match self {
+            Role::Path => "path",
+            Role::Name => "name",
+            Role::Tags => "tags",
+            Role::Header => "header",
+            Role::Match => "match",
+            Role::Variable => "variable",
+        }
+    }
+}
+
+/// Built-in theme names, as accepted by `--theme`.
+pub const THEMES: [&str; 3] = ["default", "mono", "none"];
+
+/// ANSI SGR parameters per [`Role`] (e.g. `1;33`), plus the name patterns
+/// whose matches get [`Role::Match`].
+#[derive(Clone, Debug, Default)]
+pub struct Theme {
+    /// Indexed like [`Role::ALL`]; empty means unstyled.
+    styles: [String; 6],
+    highlight: Vec<Regex>,
+}
+
+impl Theme {
+    /// No styling at all.
+    pub fn plain() -> Self {
+        Self::default()
+    }
+
+    /// Cyan paths, yellow names, green tags, bold headers, bold red matches
+    /// and a magenta variable marker.
+    pub fn colored() -> Self {
+        Self::from_styles(["36", "33", "32", "1", "1;31", "35"])
+    }
+
+    /// Bold and underline only, for monochrome terminals.
+    pub fn mono() -> Self {
+        Self::from_styles(["", "1", "", "1", "4", "7"])
+    }
+
+    fn from_styles(styles: [&str; 6]) -> Self {
+        Self {
+            styles: styles.map(str::to_string),
+            highlight: Vec::new(),
+        }
+    }
+
+    /// Parse a theme spec: a built-in name, `role=SGR` overrides of the
+    /// default theme separated by `:`, or a built-in name followed by
+    /// overrides (`mono:match=4;31`).
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut theme = Theme::colored();
+        for (i, part) in spec.trim().split(':').enumerate() {
+            let Some((key, sgr)) = part.split_once('=') else {
+                if i > 0 {
+                    return Err(anyhow!(
+                        "theme name '{part}' must come first (e.g. mono:match=4)"
+                    ));
+                }
+                theme = match part.trim() {
+                    "default" => Theme::colored(),
+                    "mono" => Theme::mono(),
+                    "none" => Theme::plain(),
+                    other => {
+                        return Err(anyhow!(
+                            "unknown theme '{other}' (expected one of: {}, or role=SGR overrides)",
+                            THEMES.join(", ")
+                        ))
+                    }
+                };
+                continue;
+            };
+            let key = key.trim();
+            let role = Role::ALL
+                .into_iter()
+                .find(|role| role.key() == key)
+                .ok_or_else(|| {
+                    let keys: Vec<&str> = Role::ALL.iter().map(|r| r.key()).collect();
+                    anyhow!(
+                        "unknown theme role '{key}' (expected one of: {})",
+                        keys.join(", ")
+                    )
+                })?;
+            let sgr = sgr.trim();
+            if !sgr.chars().all(|c| c.is_ascii_digit() || c == ';') {
+                return Err(anyhow!(
+                    "invalid style '{sgr}' for {key}: expected SGR codes like 1;33"
+                ));
+            }
+            theme.styles[role as usize] = sgr.to_string();
+        }
+        Ok(theme)
+    }
+
+    /// The theme for output to stdout.
+    ///
+    /// `--color never`, or `auto` when stdout is not a terminal or
+    /// `NO_COLOR` is set, gives [`Theme::plain`]. Otherwise the spec comes
+    /// from `--theme`, else `TYPOG_THEME`, else the default.
+    pub fn resolve(color: ColorChoice, spec: Option<&str>, is_terminal: bool) -> Result<Self> {
+        let enabled = match color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                is_terminal && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+        };
+        if !enabled {
+            return Ok(Theme::plain());
+        }
+        match spec {
+            Some(spec) => Theme::parse(spec),
+            None => match env::var("TYPOG_THEME") {
+                Ok(spec) if !spec.trim().is_empty() => Theme::parse(&spec),
+                _ => Ok(Theme::colored()),
+            },
+        }
+    }
+
+    /// Highlight matches of these patterns in names.
+    pub fn with_highlight(mut self, patterns: &[Regex]) -> Self {
+        self.highlight = patterns.to_vec();
+        self
+    }
+
+    fn style(&self, role: Role) -> &str {
+        &self.styles[role as usize]
+    }
+
+    /// Wrap `text` in the role's style.
+    pub fn paint(&self, text: &str, role: Role) -> String {
+        paint_sgr(text, self.style(role))
+    }
+
+    /// Paint a name, with the parts the highlight patterns match styled as
+    /// [`Role::Match`] on top of `role`.
+    pub fn paint_name(&self, text: &str, role: Role) -> String {
+        let base = self.style(role);
+        let matched = self.style(Role::Match);
+        if matched.is_empty() || self.highlight.is_empty() {
+            return paint_sgr(text, base);
+        }
+
+        let mut spans: Vec<(usize, usize)> = self
+            .highlight
+            .iter()
+            .flat_map(|re| re.find_iter(text).map(|m| (m.start(), m.end())))
+            .filter(|(start, end)| start < end)
+            .collect();
+        spans.sort_unstable();
+        let match_style = if base.is_empty() {
+            matched.to_string()
+        } else {
+            format!("{base};{matched}")
+        };
+
+        let mut out = String::new();
+        let mut pos = 0;
+        for (start, end) in spans {
+            let start = start.max(pos);
+            if start >= end {
+                continue;
+            }
+            out.push_str(&paint_sgr(&text[pos..start], base));
+            out.push_str(&paint_sgr(&text[start..end], &match_style));
+            pos = end;
+        }
+        out.push_str(&paint_sgr(&text[pos..], base));
+        out
+    }
+}
+
+fn paint_sgr(text: &str, sgr: &str) -> String {
+    if sgr.is_empty() || text.is_empty() {
+        text.to_string()
+    } else {
+        format!("\u{1b}[{sgr}m{text}\u{1b}[0m")
+    }
+}
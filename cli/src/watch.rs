@@ -0,0 +1,334 @@
+//! Filesystem watcher behind `typg watch`.
+//!
+//! `cache add` is a snapshot: install a font afterwards and the cache is
+//! stale until the next run. The watcher closes that gap. It subscribes to
+//! filesystem notifications for the watched roots, gathers events until the
+//! folder has been quiet for the debounce period (installers and editors fire
+//! bursts of them), then re-reads only the touched files and directories and
+//! updates the JSON cache or LMDB index in one write.
+//!
+//! Made by FontLab https://www.fontlab.com/
+
+use std::collections::{BTreeSet, HashSet};
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use typg_core::cache::{load_roots, ScanRoot};
+use typg_core::discovery::{is_font, is_hidden_below, FontDiscovery, PathDiscovery};
+use typg_core::query::Query;
+use typg_core::search::{search, SearchOptions, TypgFontFaceMatch};
+
+#[cfg(feature = "hpindex")]
+use typg_core::index::FontIndex;
+
+use crate::{load_cache, sort_entries, write_cache};
+
+/// Where the watcher keeps metadata.
+pub(crate) enum Store {
+    /// JSON cache file, rewritten after each batch.
+    Cache(PathBuf),
+    /// LMDB index, updated in one transaction per batch.
+    #[cfg(feature = "hpindex")]
+    Index(FontIndex),
+}
+
+impl Store {
+    /// The roots `cache add` recorded in this store.
+    pub(crate) fn roots(&self) -> Result<Vec<ScanRoot>> {
+        match self {
+            Self::Cache(path) => load_roots(path),
+            #[cfg(feature = "hpindex")]
+            Self::Index(index) => index.reader()?.roots(),
+        }
+    }
+}
+
+/// What one sync pass changed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SyncStats {
+    /// Faces (re)written from freshly read files.
+    pub indexed: usize,
+    /// Faces dropped because their file or folder is gone.
+    pub removed: usize,
+}
+
+impl fmt::Display for SyncStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "indexed {} font faces, removed {}",
+            self.indexed, self.removed
+        )
+    }
+}
+
+/// Applies batches of changed paths to a [`Store`].
+pub(crate) struct Syncer {
+    store: Store,
+    opts: SearchOptions,
+}
+
+/// A batch of changed paths, sorted into what needs doing.
+#[derive(Debug, Default)]
+struct Plan {
+    /// Every target path; stored entries at or under these are stale.
+    touched: Vec<PathBuf>,
+    /// Existing directories to rescan.
+    dirs: Vec<PathBuf>,
+    /// Font files to re-read.
+    files: Vec<PathBuf>,
+}
+
+impl Syncer {
+    pub(crate) fn new(store: Store, opts: SearchOptions) -> Self {
+        Self { store, opts }
+    }
+
+    /// Walk and read later passes with `opts`.
+    pub(crate) fn set_options(&mut self, opts: SearchOptions) {
+        self.opts = opts;
+    }
+
+    /// Bring the store in line with `roots` at startup.
+    ///
+    /// The LMDB index skips files whose modification time it already has; the
+    /// JSON cache keeps no mtimes, so every font under the roots is re-read.
+    pub(crate) fn initial_sync(&mut self, roots: &[PathBuf]) -> Result<SyncStats> {
+        self.sync(roots.iter().cloned().collect(), true)
+    }
+
+    /// Apply one debounced batch of changed paths.
+    pub(crate) fn apply(&mut self, changed: BTreeSet<PathBuf>) -> Result<SyncStats> {
+        self.sync(changed, false)
+    }
+
+    #[cfg_attr(not(feature = "hpindex"), allow(unused_variables))]
+    fn sync(&mut self, changed: BTreeSet<PathBuf>, fresh_only: bool) -> Result<SyncStats> {
+        let plan = plan(changed);
+        match &self.store {
+            Store::Cache(path) => sync_cache(path, &plan, &self.opts),
+            #[cfg(feature = "hpindex")]
+            Store::Index(index) => sync_index(index, &plan, &self.opts, fresh_only),
+        }
+    }
+}
+
+/// Drop paths already covered by a changed ancestor, then classify the rest.
+fn plan(changed: BTreeSet<PathBuf>) -> Plan {
+    let mut plan = Plan::default();
+    // Sorted order puts every directory before its descendants.
+    for path in changed {
+        if plan.touched.iter().any(|seen| path.starts_with(seen)) {
+            continue;
+        }
+        if path.is_dir() {
+            plan.dirs.push(path.clone());
+        } else if path.is_file() {
+            if !is_font(&path) {
+                continue;
+            }
+            plan.files.push(path.clone());
+        }
+        plan.touched.push(path);
+    }
+    plan
+}
+
+/// Font files under the planned directories plus the planned files.
+fn files_to_read(plan: &Plan, opts: &SearchOptions) -> Result<Vec<PathBuf>> {
+    let mut files = plan.files.clone();
+    if !plan.dirs.is_empty() {
+        let found = PathDiscovery::new(plan.dirs.iter().cloned())
+            .follow_symlinks(opts.follow_symlinks)
+            .include_hidden(opts.include_hidden)
+            .discover()?;
+        files.extend(found.into_iter().map(|font| font.path));
+    }
+    Ok(files)
+}
+
+/// Extract metadata for `files`, skipping any that vanished meanwhile.
+fn read_faces(mut files: Vec<PathBuf>, opts: &SearchOptions) -> Result<Vec<TypgFontFaceMatch>> {
+    files.retain(|path| path.exists());
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+    search(&files, &Query::new(), opts)
+}
+
+fn sync_cache(cache_path: &Path, plan: &Plan, opts: &SearchOptions) -> Result<SyncStats> {
+    let faces = read_faces(files_to_read(plan, opts)?, opts)?;
+
+    let mut entries = if cache_path.exists() {
+        load_cache(cache_path)?
+    } else {
+        Vec::new()
+    };
+    let fresh: HashSet<(PathBuf, Option<u32>)> = faces
+        .iter()
+        .map(|face| (face.source.path.clone(), face.source.ttc_index))
+        .collect();
+    let mut removed = 0;
+    entries.retain(|entry| {
+        let stale = plan
+            .touched
+            .iter()
+            .any(|path| entry.source.path.starts_with(path));
+        if stale && !fresh.contains(&(entry.source.path.clone(), entry.source.ttc_index)) {
+            removed += 1;
+        }
+        !stale
+    });
+
+    let stats = SyncStats {
+        indexed: faces.len(),
+        removed,
+    };
+    entries.extend(faces);
+    sort_entries(&mut entries);
+    write_cache(cache_path, &entries)?;
+    Ok(stats)
+}
+
+#[cfg(feature = "hpindex")]
+fn sync_index(
+    index: &FontIndex,
+    plan: &Plan,
+    opts: &SearchOptions,
+    fresh_only: bool,
+) -> Result<SyncStats> {
+    use std::collections::BTreeMap;
+    use std::time::SystemTime;
+
+    let mtime = |path: &Path| {
+        path.metadata()
+            .and_then(|m| m.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    };
+
+    let mut writer = index.writer()?;
+    let mut stats = SyncStats::default();
+
+    for path in &plan.touched {
+        if !path.exists() {
+            stats.removed += writer.remove_path(path)?;
+        }
+    }
+    for dir in &plan.dirs {
+        stats.removed += writer.prune_missing_under(dir)?;
+    }
+
+    let mut files = files_to_read(plan, opts)?;
+    if fresh_only {
+        let mut stale = Vec::with_capacity(files.len());
+        for path in files {
+            if writer.needs_update(&path, mtime(&path))? {
+                stale.push(path);
+            }
+        }
+        files = stale;
+    }
+
+    // Files that no longer parse still get an (empty) entry here, which
+    // drops whatever the index held for them.
+    let mut by_file: BTreeMap<PathBuf, Vec<TypgFontFaceMatch>> = files
+        .iter()
+        .map(|path| (path.clone(), Vec::new()))
+        .collect();
+    for face in read_faces(files, opts)? {
+        by_file
+            .entry(face.source.path.clone())
+            .or_default()
+            .push(face);
+    }
+    for (path, faces) in by_file {
+        stats.indexed += writer.replace_file(&path, mtime(&path), &faces)?;
+    }
+
+    writer.commit()?;
+    Ok(stats)
+}
+
+/// Watch `roots` and keep the store in sync until the process is stopped.
+pub(crate) fn watch(
+    roots: &[PathBuf],
+    syncer: &mut Syncer,
+    debounce: Duration,
+    quiet: bool,
+) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("starting filesystem watcher")?;
+    for root in roots {
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .with_context(|| format!("watching {}", root.display()))?;
+    }
+
+    // Subscribe first so changes made during the initial sync are not lost.
+    let stats = syncer.initial_sync(roots)?;
+    if !quiet {
+        eprintln!("initial sync: {stats}");
+        eprintln!("watching {} path(s); press Ctrl-C to stop", roots.len());
+    }
+
+    while let Ok(first) = rx.recv() {
+        let mut pending = BTreeSet::new();
+        collect_event(first, &mut pending, quiet);
+        // Keep draining until the tree has been quiet for `debounce`.
+        while let Ok(next) = rx.recv_timeout(debounce) {
+            collect_event(next, &mut pending, quiet);
+        }
+        if !syncer.opts.include_hidden {
+            pending.retain(|path| !roots.iter().any(|root| is_hidden_below(path, root)));
+        }
+        if pending.is_empty() {
+            continue;
+        }
+
+        let count = pending.len();
+        match syncer.apply(pending) {
+            Ok(stats) if !quiet => eprintln!("{count} change(s): {stats}"),
+            Ok(_) => {}
+            Err(err) => eprintln!("warning: sync failed: {err:#}"),
+        }
+    }
+    Ok(())
+}
+
+fn collect_event(event: notify::Result<Event>, pending: &mut BTreeSet<PathBuf>, quiet: bool) {
+    match event {
+        Ok(event) if matches!(event.kind, EventKind::Access(_)) => {}
+        Ok(event) => pending.extend(event.paths),
+        Err(err) if !quiet => eprintln!("warning: watch error: {err}"),
+        Err(_) => {}
+    }
+}
+
+/// Re-launch this command detached from the terminal, minus `--daemon`.
+pub(crate) fn spawn_daemon(quiet: bool) -> Result<()> {
+    let exe = env::current_exe().context("locating the typg executable")?;
+    let mut command = Command::new(exe);
+    command
+        .args(env::args_os().skip(1).filter(|arg| arg != "--daemon"))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Own process group, so Ctrl-C in this terminal does not reach it.
+        command.process_group(0);
+    }
+
+    let child = command.spawn().context("starting background watcher")?;
+    if !quiet {
+        eprintln!("typg watch running in the background (pid {})", child.id());
+    }
+    Ok(())
+}
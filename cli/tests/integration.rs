@@ -4,8 +4,9 @@
 /// by script, name, and feature filters.
 use std::env;
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 use serde_json::Value;
 use tempfile::tempdir;
@@ -450,6 +451,396 @@ fn cache_info_index_shows_lmdb_stats() {
     assert_eq!(parsed["type"].as_str(), Some("lmdb"));
 }
 
+/// Verify that `cache add --index --commit-every` stores what a single
+/// transaction stores and skips unchanged files on the next run (requires
+/// hpindex feature).
+#[test]
+#[cfg(feature = "hpindex")]
+fn cache_add_index_commit_every_matches_one_transaction() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+
+    let tmp = tempdir().expect("tempdir");
+    let typg = |args: &[&str], index_path: &PathBuf| -> (Vec<u8>, String) {
+        let out = Command::new(env!("CARGO_BIN_EXE_typg"))
+            .args(args)
+            .arg("--index-path")
+            .arg(index_path)
+            .output()
+            .expect("run typg");
+        let stderr = String::from_utf8_lossy(&out.stderr).to_string();
+        assert!(out.status.success(), "stderr: {stderr}");
+        (out.stdout, stderr)
+    };
+    let list = |index_path: &PathBuf| -> Vec<String> {
+        let (stdout, _) = typg(&["cache", "list", "--index", "--paths"], index_path);
+        let mut paths: Vec<String> = String::from_utf8(stdout)
+            .expect("utf8 paths")
+            .lines()
+            .map(str::to_string)
+            .collect();
+        paths.sort();
+        paths
+    };
+
+    let batched = tmp.path().join("batched");
+    let single = tmp.path().join("single");
+    let fonts = fonts.to_str().unwrap();
+    typg(
+        &["cache", "add", "--index", "--commit-every", "1", fonts],
+        &batched,
+    );
+    typg(&["cache", "add", "--index", fonts], &single);
+    let stored = list(&batched);
+    assert!(!stored.is_empty());
+    assert_eq!(stored, list(&single));
+
+    let (_, stderr) = typg(
+        &["cache", "add", "--index", "--commit-every", "1", fonts],
+        &batched,
+    );
+    assert!(stderr.contains("added: 0,"), "stderr: {stderr}");
+}
+
+/// Verify that `serve --preload-index` exits before binding when the index is missing (requires hpindex feature).
+#[test]
+#[cfg(feature = "hpindex")]
+fn serve_preload_index_refuses_missing_index() {
+    let tmp = tempdir().expect("tempdir");
+    let index_path = tmp.path().join("index");
+
+    let serve = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["serve", "--bind", "127.0.0.1:0", "--warm-index"])
+        .arg(format!("--preload-index={}", index_path.display()))
+        .output()
+        .expect("run serve --preload-index");
+
+    assert!(!serve.status.success());
+    let stderr = String::from_utf8_lossy(&serve.stderr);
+    assert!(stderr.contains("no index at"), "stderr: {stderr}");
+    assert!(!index_path.exists(), "preloading must not create the index");
+}
+
+/// Verify that `cache fsck --index` passes a fresh index and reports JSON findings.
+#[test]
+#[cfg(feature = "hpindex")]
+fn cache_fsck_index_passes_a_fresh_index() {
+    let tmp = tempdir().expect("tempdir");
+    let index_path = tmp.path().join("index");
+
+    let missing = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["cache", "fsck", "--index", "--index-path"])
+        .arg(&index_path)
+        .output()
+        .expect("run cache fsck");
+    assert!(!missing.status.success());
+
+    fs::create_dir_all(&index_path).expect("create index dir");
+    let output = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["cache", "fsck", "--index", "--json", "--index-path"])
+        .arg(&index_path)
+        .output()
+        .expect("run cache fsck --json");
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let report: Value = serde_json::from_slice(&output.stdout).expect("json report");
+    assert_eq!(report["faces"], 0);
+    assert_eq!(report["dangling_members"], 0);
+    assert_eq!(report["repaired"], false);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("index is consistent"), "stderr: {stderr}");
+}
+
+/// Verify that `cache remove --index` drops the faces a query matches and
+/// keeps the rest (requires hpindex feature).
+#[test]
+#[cfg(feature = "hpindex")]
+fn cache_remove_index_drops_matching_faces() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let tmp = tempdir().expect("tempdir");
+    let index_path = tmp.path().join("index");
+    let typg = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_typg"))
+            .args(args)
+            .arg("--index-path")
+            .arg(&index_path)
+            .output()
+            .expect("run typg");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).expect("utf8 stdout")
+    };
+    let fonts = fonts.display().to_string();
+    typg(&["cache", "add", "--index", &fonts]);
+    let count = |args: &[&str]| {
+        let mut argv = vec!["cache", "find", "--index", "--count"];
+        argv.extend(args);
+        typg(&argv).trim().parse::<usize>().expect("count")
+    };
+    let total = count(&["--name", "."]);
+
+    // Filters the index records do not hold are checked on the stored
+    // metadata, not dropped.
+    let listed = typg(&[
+        "cache",
+        "remove",
+        "--index",
+        "--vendor",
+        "ZZZZ",
+        "--dry-run",
+    ]);
+    assert_eq!(listed.lines().count(), 0);
+    typg(&["cache", "remove", "--index", "--vendor", "ZZZZ"]);
+    assert_eq!(count(&["--name", "."]), total);
+
+    let faces: Value =
+        serde_json::from_str(&typg(&["cache", "find", "--index", "--json"])).expect("json");
+    let vendor = faces[0]["metadata"]["vendor_id"]
+        .as_str()
+        .expect("vendor")
+        .to_string();
+    let by_vendor = count(&["--vendor", &vendor]);
+    typg(&["cache", "remove", "--index", "--vendor", &vendor]);
+    assert_eq!(count(&["--vendor", &vendor]), 0);
+    let total = count(&["--name", "."]);
+    assert_eq!(total, faces.as_array().expect("array").len() - by_vendor);
+    let variable = count(&["--variable"]);
+
+    let listed = typg(&["cache", "remove", "--index", "--not-variable", "--dry-run"]);
+    assert_eq!(listed.lines().count(), total - variable);
+    assert_eq!(count(&["--name", "."]), total);
+
+    typg(&["cache", "remove", "--index", "--not-variable"]);
+    assert_eq!(count(&["--name", "."]), variable);
+}
+
+/// Verify that `cache verify --index` finds nothing after a removal, and
+/// that `cache compact --index` keeps every face (requires hpindex feature).
+#[test]
+#[cfg(feature = "hpindex")]
+fn cache_verify_and_compact_index() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let tmp = tempdir().expect("tempdir");
+    let index_path = tmp.path().join("index");
+    let typg = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_typg"))
+            .args(args)
+            .arg("--index-path")
+            .arg(&index_path)
+            .output()
+            .expect("run typg")
+    };
+    let fonts = fonts.display().to_string();
+    assert!(typg(&["cache", "add", "--index", &fonts]).status.success());
+    let verified = typg(&["cache", "verify", "--index"]);
+    assert!(
+        verified.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&verified.stderr)
+    );
+
+    assert!(typg(&["cache", "remove", "--index", "--not-variable"])
+        .status
+        .success());
+    // Removals take the faces out of their bitmaps, so nothing dangles.
+    let verified = typg(&["cache", "verify", "--index", "--json"]);
+    assert!(
+        verified.status.success(),
+        "stdout: {}",
+        String::from_utf8_lossy(&verified.stdout)
+    );
+    assert!(typg(&["cache", "fsck", "--index", "--repair"])
+        .status
+        .success());
+    assert!(typg(&["cache", "verify", "--index"]).status.success());
+
+    let before = typg(&["cache", "list", "--index"]).stdout;
+    let compacted = typg(&["cache", "compact", "--index", "--json"]);
+    assert!(
+        compacted.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&compacted.stderr)
+    );
+    let stats: Value = serde_json::from_slice(&compacted.stdout).expect("json stats");
+    assert!(stats["after_bytes"].as_u64().unwrap() <= stats["before_bytes"].as_u64().unwrap());
+    assert_eq!(typg(&["cache", "list", "--index"]).stdout, before);
+}
+
+/// Export an index to a snapshot and import it elsewhere with the font paths rebased (requires hpindex feature).
+#[test]
+#[cfg(feature = "hpindex")]
+fn cache_export_and_import_index_snapshot() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let tmp = tempdir().expect("tempdir");
+    let typg = |index: &str, args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_typg"))
+            .args(args)
+            .arg("--index-path")
+            .arg(tmp.path().join(index))
+            .output()
+            .expect("run typg")
+    };
+    let fonts = fonts.display().to_string();
+    let snapshot = tmp.path().join("catalog.typg").display().to_string();
+    assert!(typg("built", &["cache", "add", "--index", &fonts])
+        .status
+        .success());
+    let exported = typg("built", &["cache", "export", "--index", "-o", &snapshot]);
+    assert!(
+        exported.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&exported.stderr)
+    );
+
+    let rebase = format!("{fonts}=/opt/catalog");
+    let imported = typg(
+        "shipped",
+        &[
+            "cache", "import", "--index", &snapshot, "--rebase", &rebase, "--json",
+        ],
+    );
+    assert!(
+        imported.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&imported.stderr)
+    );
+    let stats: Value = serde_json::from_slice(&imported.stdout).expect("json stats");
+    assert!(stats["faces"].as_u64().unwrap() > 0);
+
+    let built = typg("built", &["cache", "list", "--index", "--paths"]).stdout;
+    let shipped = typg("shipped", &["cache", "list", "--index", "--paths"]).stdout;
+    let moved = String::from_utf8_lossy(&built).replace(&fonts, "/opt/catalog");
+    assert_eq!(String::from_utf8_lossy(&shipped), moved);
+
+    let foreign = typg("shipped", &["cache", "import", "--index", &fonts]);
+    assert!(!foreign.status.success());
+}
+
+/// Migrate a JSON cache into the index and back without losing a field (requires hpindex feature).
+#[test]
+#[cfg(feature = "hpindex")]
+fn cache_migrate_round_trips_between_cache_and_index() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let tmp = tempdir().expect("tempdir");
+    let typg = |cache: &str, args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_typg"))
+            .args(args)
+            .arg("--cache-path")
+            .arg(tmp.path().join(cache))
+            .output()
+            .expect("run typg")
+    };
+    let index_path = tmp.path().join("index").display().to_string();
+    let fonts = fonts.display().to_string();
+    assert!(typg("cache.json", &["cache", "add", &fonts])
+        .status
+        .success());
+
+    let to_index = typg(
+        "cache.json",
+        &[
+            "cache",
+            "migrate",
+            "--to-index",
+            "--index-path",
+            &index_path,
+            "--json",
+        ],
+    );
+    assert!(
+        to_index.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&to_index.stderr)
+    );
+    let stats: Value = serde_json::from_slice(&to_index.stdout).expect("json stats");
+    assert!(stats["faces"].as_u64().unwrap() > 0);
+
+    let to_json = typg(
+        "back.json",
+        &["cache", "migrate", "--to-json", "--index-path", &index_path],
+    );
+    assert!(
+        to_json.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&to_json.stderr)
+    );
+    let list = |cache: &str| {
+        let out = typg(cache, &["cache", "list", "--json"]).stdout;
+        serde_json::from_slice::<Value>(&out).expect("json list")
+    };
+    assert_eq!(list("back.json"), list("cache.json"));
+}
+
+/// A `--collection` keeps its own cache beside the default one; cache find
+/// searches several together and cache info lists them.
+#[test]
+fn cache_collections_are_separate_stores_searched_together() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let tmp = tempdir().expect("tempdir");
+    let cache_path = tmp.path().join("cache.json");
+    let typg = |args: &[&str]| {
+        let out = Command::new(env!("CARGO_BIN_EXE_typg"))
+            .args(args)
+            .arg("--cache-path")
+            .arg(&cache_path)
+            .output()
+            .expect("run typg");
+        assert!(
+            out.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        out.stdout
+    };
+    let count = |args: &[&str]| {
+        serde_json::from_slice::<Value>(&typg(args))
+            .expect("json")
+            .as_array()
+            .expect("array")
+            .len()
+    };
+    let one_font = fonts.join("DejaVuSans.ttf").display().to_string();
+    let fonts = fonts.display().to_string();
+    typg(&["cache", "add", &one_font]);
+    typg(&["--collection", "masters", "cache", "add", &fonts]);
+
+    assert!(tmp.path().join("collections/masters/cache.json").exists());
+    assert_eq!(count(&["cache", "list", "--json"]), 1);
+    let masters = count(&["--collection", "masters", "cache", "list", "--json"]);
+    assert!(masters > 1);
+    assert_eq!(
+        count(&["--collection", "default,masters", "cache", "find", "--json"]),
+        masters
+    );
+
+    let info: Value =
+        serde_json::from_slice(&typg(&["cache", "info", "--json"])).expect("json info");
+    assert_eq!(info["collections"][0], "masters");
+}
+
 /// Exercise the full LMDB index lifecycle: add, list, find by script, and filter for variable fonts (requires hpindex feature).
 #[test]
 #[cfg(feature = "hpindex")]
@@ -536,3 +927,572 @@ fn index_add_find_and_list_cycle() {
         "indexed find --variable should include Kalnia"
     );
 }
+
+/// Verify that repeated `--index-path`s union their results, dropping faces an earlier index already returned (requires hpindex feature).
+#[test]
+#[cfg(feature = "hpindex")]
+fn index_find_unions_several_indexes() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+
+    let tmp = tempdir().expect("tempdir");
+    let copy_dir = tmp.path().join("copy");
+    fs::create_dir(&copy_dir).expect("create copy dir");
+    fs::copy(
+        fonts.join("NotoSans-Regular.ttf"),
+        copy_dir.join("NotoSans-Regular.ttf"),
+    )
+    .expect("copy font");
+
+    let first = tmp.path().join("first");
+    let second = tmp.path().join("second");
+    for (index, dir) in [(&first, &fonts), (&second, &copy_dir)] {
+        let add = Command::new(env!("CARGO_BIN_EXE_typg"))
+            .args(["cache", "add", "--index", "--index-path"])
+            .arg(index)
+            .arg(dir)
+            .output()
+            .expect("run cache add --index");
+        assert!(
+            add.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&add.stderr)
+        );
+    }
+
+    let find = |indexes: &[&PathBuf]| -> Vec<String> {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_typg"));
+        cmd.args(["cache", "find", "--index", "--json"]);
+        for index in indexes {
+            cmd.arg("--index-path").arg(index);
+        }
+        let out = cmd.output().expect("run cache find --index");
+        assert!(
+            out.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        let found: Value = serde_json::from_slice(&out.stdout).expect("parse find json");
+        found
+            .as_array()
+            .expect("find returns array")
+            .iter()
+            .map(|entry| entry["source"]["path"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let only_first = find(&[&first]);
+    let union = find(&[&first, &second]);
+    assert_eq!(
+        union, only_first,
+        "the copy duplicates a face of the first index"
+    );
+
+    let reversed = find(&[&second, &first]);
+    assert_eq!(reversed.len(), only_first.len());
+    assert!(
+        reversed
+            .iter()
+            .any(|path| path.starts_with(copy_dir.to_str().unwrap())),
+        "the earlier index wins a duplicate"
+    );
+}
+
+/// Verify that faces read back from the index carry the same tag lists as
+/// the JSON cache, and that `--rank` works against the index (requires
+/// hpindex feature).
+#[test]
+#[cfg(feature = "hpindex")]
+fn index_find_reports_the_cache_tag_lists() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+
+    let tmp = tempdir().expect("tempdir");
+    let cache_path = tmp.path().join("cache.json");
+    let index_path = tmp.path().join("index");
+    let typg = |args: &[&str], store: &[&str], path: &PathBuf| -> Vec<u8> {
+        let out = Command::new(env!("CARGO_BIN_EXE_typg"))
+            .args(args)
+            .args(store)
+            .arg(path)
+            .output()
+            .expect("run typg");
+        assert!(
+            out.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        out.stdout
+    };
+    let json_cache = ["--cache-path"];
+    let index = ["--index", "--index-path"];
+    typg(
+        &["cache", "add", fonts.to_str().unwrap()],
+        &json_cache,
+        &cache_path,
+    );
+    typg(
+        &["cache", "add", fonts.to_str().unwrap()],
+        &index,
+        &index_path,
+    );
+
+    let tags = |stdout: Vec<u8>| -> Vec<Value> {
+        let found: Value = serde_json::from_slice(&stdout).expect("parse find json");
+        found
+            .as_array()
+            .expect("find returns array")
+            .iter()
+            .map(|entry| {
+                let meta = &entry["metadata"];
+                serde_json::json!([
+                    entry["source"],
+                    meta["axis_tags"],
+                    meta["feature_tags"],
+                    meta["gsub_feature_tags"],
+                    meta["gpos_feature_tags"],
+                    meta["script_tags"],
+                    meta["language_tags"],
+                    meta["table_tags"],
+                ])
+            })
+            .collect()
+    };
+    let find = ["cache", "find", "--json", "--name", "."];
+    let cached = tags(typg(&find, &json_cache, &cache_path));
+    let indexed = tags(typg(&find, &index, &index_path));
+    assert!(!cached.is_empty());
+    assert!(cached.iter().any(|face| face[7] != serde_json::json!([])));
+    assert_eq!(indexed, cached);
+
+    let rank = ["cache", "find", "--rank", "--paths", "-f", "smcp,kern"];
+    assert_eq!(
+        typg(&rank, &index, &index_path),
+        typg(&rank, &json_cache, &cache_path)
+    );
+}
+
+/// Verify that `find` prints a summary line to stderr, and `--quiet` suppresses it.
+#[test]
+fn find_prints_summary_to_stderr() {
+    let tmp = tempdir().expect("tempdir");
+    fs::write(tmp.path().join("broken.ttf"), b"not a font").expect("write");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["find", "--paths"])
+        .arg(tmp.path())
+        .output()
+        .expect("run find");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with("scanned 1 file in ") && stderr.contains("0 matches, 1 error"),
+        "unexpected summary: {stderr}"
+    );
+
+    let quiet = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["--quiet", "find", "--paths"])
+        .arg(tmp.path())
+        .output()
+        .expect("run find --quiet");
+    assert!(quiet.stderr.is_empty());
+}
+
+/// Verify that `find` skips hidden files and directories unless `--hidden`.
+#[test]
+fn find_skips_hidden_entries_unless_asked() {
+    let tmp = tempdir().expect("tempdir");
+    fs::create_dir(tmp.path().join(".Trash")).expect("mkdir");
+    for name in ["broken.ttf", "._broken.ttf", ".Trash/old.ttf"] {
+        fs::write(tmp.path().join(name), b"not a font").expect("write");
+    }
+
+    let scanned = |hidden: bool| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_typg"));
+        command.args(["find", "--paths"]);
+        if hidden {
+            command.arg("--hidden");
+        }
+        let output = command.arg(tmp.path()).output().expect("run find");
+        String::from_utf8_lossy(&output.stderr).to_string()
+    };
+    assert!(scanned(false).starts_with("scanned 1 file in "));
+    assert!(scanned(true).starts_with("scanned 3 files in "));
+}
+
+/// Verify that `--provenance` wraps JSON output in a self-describing envelope.
+#[test]
+fn find_json_provenance_records_query_and_roots() {
+    let tmp = tempdir().expect("tempdir");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["-q", "find", "--json", "--provenance", "--scripts", "arab"])
+        .arg(tmp.path())
+        .output()
+        .expect("run find");
+    assert!(output.status.success());
+
+    let envelope: Value = serde_json::from_slice(&output.stdout).expect("json");
+    let provenance = &envelope["provenance"];
+    assert_eq!(provenance["typg_version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(provenance["query"][0], "-q");
+    assert!(provenance["query"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|arg| arg == "arab"));
+    assert_eq!(provenance["roots"][0], tmp.path().to_str().unwrap());
+    assert!(provenance["timestamp"].as_str().unwrap().ends_with('Z'));
+    assert_eq!(envelope["results"], serde_json::json!([]));
+
+    let without_json = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["find", "--provenance"])
+        .arg(tmp.path())
+        .output()
+        .expect("run find");
+    assert!(!without_json.status.success());
+}
+
+/// Verify that `show -` parses a font piped on stdin.
+#[test]
+fn show_reads_font_bytes_from_stdin() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let font = fonts.join("NotoSans-Regular.ttf");
+    let Ok(data) = fs::read(&font) else {
+        return; // fixture set without NotoSans
+    };
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["show", "-", "--json"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("run typg");
+    child
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all(&data)
+        .expect("pipe font");
+    let output = child.wait_with_output().expect("wait");
+
+    assert!(output.status.success());
+    let faces: Value = serde_json::from_slice(&output.stdout).expect("json");
+    let face = &faces.as_array().expect("array")[0];
+    assert_eq!(face["source"]["path"], "-");
+    assert!(face["metadata"]["names"]
+        .as_array()
+        .expect("names")
+        .iter()
+        .any(|name| name == "Noto Sans"));
+}
+
+/// Verify that `validate --duplicates` groups files sharing a PostScript or
+/// full name, lists differing files first, and fails only on those.
+#[test]
+fn validate_duplicates_reports_shared_names() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+
+    let tmp = tempdir().expect("tempdir");
+    let copies = tmp.path().join("copies");
+    fs::create_dir(&copies).expect("create copies dir");
+    for name in ["A.ttf", "B.ttf"] {
+        fs::copy(fonts.join("DejaVuSans.ttf"), copies.join(name)).expect("copy font");
+    }
+    let duplicates = |dir: &PathBuf| {
+        Command::new(env!("CARGO_BIN_EXE_typg"))
+            .args(["validate", "--duplicates", "--json"])
+            .arg(dir)
+            .output()
+            .expect("run validate --duplicates")
+    };
+
+    let identical = duplicates(&copies);
+    assert!(
+        identical.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&identical.stderr)
+    );
+    let found: Value = serde_json::from_slice(&identical.stdout).expect("parse json");
+    assert_eq!(found.as_array().expect("array").len(), 2);
+    assert_eq!(found[0]["kind"], "postscript");
+    assert_eq!(found[0]["name"], "DejaVuSans");
+    assert_eq!(found[0]["contents_differ"], false);
+    assert_eq!(found[0]["faces"].as_array().expect("faces").len(), 2);
+
+    // Trailing bytes change the file but not its names.
+    let mut bold = fs::read(fonts.join("DejaVuSans-Bold.ttf")).expect("read font");
+    fs::write(copies.join("C.ttf"), &bold).expect("write font");
+    bold.extend_from_slice(&[0; 4]);
+    fs::write(copies.join("D.ttf"), &bold).expect("write font");
+
+    let differing = duplicates(&copies);
+    assert!(!differing.status.success());
+    let found: Value = serde_json::from_slice(&differing.stdout).expect("parse json");
+    let listed: Vec<(&str, bool)> = found
+        .as_array()
+        .expect("array")
+        .iter()
+        .map(|dup| {
+            (
+                dup["name"].as_str().unwrap(),
+                dup["contents_differ"].as_bool().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        listed,
+        [
+            ("DejaVuSans-Bold", true),
+            ("DejaVu Sans Bold", true),
+            ("DejaVuSans", false),
+            ("DejaVu Sans", false),
+        ]
+    );
+}
+
+/// Verify that `inspect --json` dumps name records and the table directory.
+#[test]
+fn inspect_dumps_names_and_tables() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let font = fonts.join("NotoSans-Regular.ttf");
+    if !font.exists() {
+        return; // fixture set without NotoSans
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["inspect", "--json"])
+        .arg(&font)
+        .output()
+        .expect("run typg");
+
+    assert!(output.status.success());
+    let faces: Value = serde_json::from_slice(&output.stdout).expect("json");
+    let face = &faces.as_array().expect("array")[0];
+    assert!(face["names"]
+        .as_array()
+        .expect("names")
+        .iter()
+        .any(|name| name["name_id"] == 1 && name["value"] == "Noto Sans"));
+    assert!(face["tables"]
+        .as_array()
+        .expect("tables")
+        .iter()
+        .any(|table| table["tag"] == "cmap" && table["length"].as_u64() > Some(0)));
+    assert!(face["cmap"]["codepoints"].as_u64() > Some(0));
+}
+
+/// Verify that `--via-daemon` searches answer from a running `typg daemon`
+/// as `cache find` answers from the cache file, and that paths narrow them.
+#[cfg(unix)]
+#[test]
+fn via_daemon_answers_like_the_cache() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+
+    let tmp = tempdir().expect("tempdir");
+    let cache_path = tmp.path().join("cache.json");
+    let socket = tmp.path().join("typg.sock");
+    let add = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["cache", "add", "--cache-path"])
+        .arg(&cache_path)
+        .arg(&fonts)
+        .output()
+        .expect("run cache add");
+    assert!(add.status.success());
+
+    let mut daemon = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["daemon", "--cache-path"])
+        .arg(&cache_path)
+        .arg("--socket")
+        .arg(&socket)
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("run daemon");
+    for _ in 0..100 {
+        if socket.exists() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+
+    let via_daemon = format!("--via-daemon={}", socket.display());
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_typg"))
+            .args(args)
+            .output()
+            .expect("run typg")
+    };
+    let direct = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["cache", "find", "--cache-path"])
+        .arg(&cache_path)
+        .args(["--scripts", "latn", "--paths"])
+        .output()
+        .expect("run cache find");
+    let cached = run(&["cache", "find", &via_daemon, "--scripts", "latn", "--paths"]);
+    let under_fonts = run(&[
+        "find",
+        &via_daemon,
+        "--scripts",
+        "latn",
+        "--paths",
+        fonts.to_str().expect("utf-8 path"),
+    ]);
+    let elsewhere = run(&[
+        "find",
+        &via_daemon,
+        "--count",
+        tmp.path().to_str().expect("utf-8 path"),
+    ]);
+    daemon.kill().expect("stop daemon");
+    let _ = daemon.wait();
+
+    assert!(
+        cached.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&cached.stderr)
+    );
+    assert!(!direct.stdout.is_empty());
+    assert_eq!(cached.stdout, direct.stdout);
+    assert_eq!(under_fonts.stdout, direct.stdout);
+    assert_eq!(String::from_utf8_lossy(&elsewhere.stdout).trim(), "0");
+}
+
+/// Verify that `cache add` records its roots and `cache refresh` rescans
+/// them: new files are read, removed files and vanished roots are dropped.
+#[test]
+fn cache_refresh_rescans_recorded_roots() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let mut files: Vec<PathBuf> = fs::read_dir(&fonts)
+        .expect("read fixtures")
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ttf"))
+        .collect();
+    files.sort();
+    if files.len() < 3 {
+        return;
+    }
+
+    let tmp = tempdir().expect("tempdir");
+    let (kept, gone) = (tmp.path().join("kept"), tmp.path().join("gone"));
+    fs::create_dir_all(&kept).expect("mkdir");
+    fs::create_dir_all(&gone).expect("mkdir");
+    let name = |path: &PathBuf| path.file_name().expect("file name").to_owned();
+    fs::copy(&files[0], kept.join(name(&files[0]))).expect("copy");
+    fs::copy(&files[1], gone.join(name(&files[1]))).expect("copy");
+    let cache_path = tmp.path().join("cache.json");
+
+    let add = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["cache", "add", "--cache-path"])
+        .arg(&cache_path)
+        .arg(&kept)
+        .arg(&gone)
+        .output()
+        .expect("run cache add");
+    assert!(add.status.success());
+    let roots: Value =
+        serde_json::from_slice(&fs::read(tmp.path().join("cache.roots.json")).expect("roots"))
+            .expect("json");
+    assert_eq!(roots.as_array().expect("array").len(), 2);
+
+    fs::copy(&files[2], kept.join(name(&files[2]))).expect("copy");
+    fs::remove_dir_all(&gone).expect("rm");
+    let refresh = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["cache", "refresh", "--cache-path"])
+        .arg(&cache_path)
+        .output()
+        .expect("run cache refresh");
+    assert!(
+        refresh.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&refresh.stderr)
+    );
+
+    let list = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["cache", "list", "--paths", "--cache-path"])
+        .arg(&cache_path)
+        .output()
+        .expect("run cache list");
+    let listed: Vec<PathBuf> = String::from_utf8_lossy(&list.stdout)
+        .lines()
+        .map(PathBuf::from)
+        .collect();
+    let expected: Vec<PathBuf> = [&files[0], &files[2]]
+        .into_iter()
+        .map(|file| std::path::absolute(kept.join(name(file))).expect("absolute"))
+        .collect();
+    assert_eq!(listed, expected);
+}
+
+/// Verify that `select` answers with a face of the requested family and
+/// fails for a family that is not installed.
+#[test]
+fn select_picks_a_face_of_the_family() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let found = Command::new(env!("CARGO_BIN_EXE_typg"))
+        .args(["find", "--json"])
+        .arg(&fonts)
+        .output()
+        .expect("run typg find");
+    let faces: Value = serde_json::from_slice(&found.stdout).expect("json results");
+    let Some(family) = faces
+        .as_array()
+        .expect("result array")
+        .iter()
+        .find_map(|face| face["metadata"]["family_name"].as_str())
+    else {
+        return;
+    };
+    let family_paths: Vec<&str> = faces
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|face| face["metadata"]["family_name"].as_str() == Some(family))
+        .filter_map(|face| face["source"]["path"].as_str())
+        .collect();
+
+    let select = |family: &str| {
+        Command::new(env!("CARGO_BIN_EXE_typg"))
+            .args(["select", "--paths", "--weight", "650", "--family", family])
+            .arg(&fonts)
+            .output()
+            .expect("run typg select")
+    };
+    let output = select(family);
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let chosen = String::from_utf8_lossy(&output.stdout);
+    let chosen = chosen.trim();
+    assert!(
+        family_paths.iter().any(|path| chosen.starts_with(path)),
+        "{chosen} is not in {family}"
+    );
+
+    let missing = select("No Such Family Anywhere");
+    assert!(!missing.status.success());
+}
@@ -102,7 +102,7 @@ fn bench_lmdb_index(c: &mut Criterion) {
 
         // Get metadata via search for this single font
         if let Ok(matches) = search(
-            &[source.path.clone()],
+            std::slice::from_ref(&source.path),
             &Query::default(),
             &SearchOptions::default(),
         ) {
@@ -111,12 +111,16 @@ fn bench_lmdb_index(c: &mut Criterion) {
                     &m.source.path,
                     m.source.ttc_index,
                     mtime,
-                    m.metadata.names.clone(),
+                    m.metadata
+                        .names
+                        .iter()
+                        .map(|name| name.to_string())
+                        .collect(),
                     &m.metadata.axis_tags,
                     &m.metadata.feature_tags,
                     &m.metadata.script_tags,
                     &m.metadata.table_tags,
-                    &m.metadata.codepoints.iter().copied().collect::<Vec<_>>(),
+                    &m.metadata.codepoints,
                     m.metadata.is_variable,
                     m.metadata.weight_class,
                     m.metadata.width_class,
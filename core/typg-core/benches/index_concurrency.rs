@@ -0,0 +1,166 @@
+//! Benchmarks for concurrent queries against one LMDB index.
+//!
+//! Run with: cargo bench --features hpindex -p typg-core --bench index_concurrency
+//!
+//! The index is synthetic, so no font fixtures are needed. Size and tuning
+//! come from the environment:
+//!
+//! - `TYPG_BENCH_INDEX_FONTS`: faces to index (default 50,000; use 1000000
+//!   for the 1M-font case; building it dominates the run).
+//! - `TYPG_BENCH_MAX_READERS`: `IndexOptions::max_readers` (default 126).
+//! - `TYPG_BENCH_NO_READ_AHEAD=1`: set `IndexOptions::no_read_ahead`.
+//!
+//! `open_per_query` is what the HTTP server used to do on every request;
+//! `shared_query` and `concurrent_queries/N` use one pre-opened environment
+//! from `FontIndex::shared`, with N threads querying at once.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use read_fonts::types::Tag;
+use tempfile::TempDir;
+use typg_core::index::{FontIndex, IndexOptions, DEFAULT_MAX_READERS};
+use typg_core::query::Query;
+use typg_core::tags::tag4;
+
+const SCRIPTS: [&[u8; 4]; 4] = [b"latn", b"grek", b"cyrl", b"arab"];
+const FEATURES: [&[u8; 4]; 8] = [
+    b"kern", b"liga", b"smcp", b"onum", b"ss01", b"salt", b"case", b"frac",
+];
+
+fn env_number<T: std::str::FromStr>(name: &str, default: T) -> T {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+fn options() -> IndexOptions {
+    IndexOptions {
+        max_readers: env_number("TYPG_BENCH_MAX_READERS", DEFAULT_MAX_READERS),
+        no_read_ahead: env::var("TYPG_BENCH_NO_READ_AHEAD").is_ok_and(|v| v == "1"),
+        ..IndexOptions::default()
+    }
+}
+
+/// Build the synthetic index once per benchmark run.
+fn index_dir() -> &'static Path {
+    static DIR: OnceLock<TempDir> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let dir = TempDir::new().unwrap();
+        let fonts: u32 = env_number("TYPG_BENCH_INDEX_FONTS", 50_000);
+        let index = FontIndex::open(dir.path()).unwrap();
+        let mut writer = index.writer().unwrap();
+        let latin: Vec<char> = ('!'..='~').collect();
+
+        for i in 0..fonts {
+            let script = [Tag::new(SCRIPTS[i as usize % SCRIPTS.len()])];
+            let features: Vec<Tag> = FEATURES
+                .iter()
+                .enumerate()
+                .filter(|(bit, _)| i & (1 << bit) != 0)
+                .map(|(_, tag)| Tag::new(tag))
+                .collect();
+            let axes = if i % 5 == 0 {
+                vec![Tag::new(b"wght")]
+            } else {
+                Vec::new()
+            };
+            let path = PathBuf::from(format!("/fonts/{}/Family{i}-Regular.ttf", i % 997));
+            writer
+                .add_font(
+                    &path,
+                    None,
+                    SystemTime::UNIX_EPOCH,
+                    vec![format!("Family {i}"), format!("Family{i}-Regular")],
+                    &axes,
+                    &features,
+                    &script,
+                    &[Tag::new(b"cmap"), Tag::new(b"GSUB")],
+                    &latin,
+                    !axes.is_empty(),
+                    Some(100 * (1 + (i % 9) as u16)),
+                    Some(5),
+                    None,
+                )
+                .unwrap();
+        }
+        writer.commit().unwrap();
+        eprintln!("Index built with {fonts} synthetic faces");
+        dir
+    })
+    .path()
+}
+
+/// Roughly one face in 64: a script and two features.
+fn query() -> Query {
+    Query::new()
+        .with_scripts(vec![tag4("grek").unwrap()])
+        .with_features(vec![tag4("smcp").unwrap(), tag4("ss01").unwrap()])
+}
+
+/// Open the environment, query, close it: the old per-request pattern.
+///
+/// Runs first: once the shared handle below exists, a second open of the
+/// same directory is refused.
+fn bench_open_per_query(c: &mut Criterion) {
+    let dir = index_dir();
+    let query = query();
+    let options = options();
+
+    c.bench_function("open_per_query", |b| {
+        b.iter(|| {
+            let index = FontIndex::open_with(dir, &options).unwrap();
+            let matches = index.reader().unwrap().find(black_box(&query)).unwrap();
+            black_box(matches.len())
+        })
+    });
+}
+
+/// Query a pre-opened shared environment.
+fn bench_shared_query(c: &mut Criterion) {
+    let index = FontIndex::shared(index_dir(), &options()).unwrap();
+    let query = query();
+
+    c.bench_function("shared_query", |b| {
+        b.iter(|| {
+            let matches = index.reader().unwrap().find(black_box(&query)).unwrap();
+            black_box(matches.len())
+        })
+    });
+}
+
+/// N threads each run one query against the shared environment.
+fn bench_concurrent_queries(c: &mut Criterion) {
+    let index = FontIndex::shared(index_dir(), &options()).unwrap();
+    let query = query();
+
+    let mut group = c.benchmark_group("concurrent_queries");
+    for threads in [1usize, 4, 16, 64, 256] {
+        group.throughput(Throughput::Elements(threads as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &threads, |b, &n| {
+            b.iter(|| {
+                std::thread::scope(|s| {
+                    for _ in 0..n {
+                        s.spawn(|| {
+                            let matches = index.reader().unwrap().find(&query).unwrap();
+                            black_box(matches.len())
+                        });
+                    }
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_open_per_query,
+    bench_shared_query,
+    bench_concurrent_queries
+);
+criterion_main!(benches);
@@ -0,0 +1,218 @@
+//! Measuring how fast typg reads a font collection.
+//!
+//! [`run`] walks a corpus and reads, parses and matches every font once,
+//! timing each phase, so two machines — or a local disk and an NFS mount —
+//! can be compared on the same fonts without a benchmarking setup. Every
+//! face is checked against the same [`standard_queries`], so the matching
+//! cost does not depend on what the caller happens to search for.
+//!
+//! Files are read and parsed in parallel, as a search reads them. Discovery
+//! and the scan as a whole are wall-clock times; the read, parse and match
+//! times are summed over the worker threads, so with eight threads they can
+//! add up to eight times the scan's wall-clock time. Nothing is cached: a
+//! second run over the same corpus reads through the operating system's
+//! file cache, which is what makes a first, cold run slower.
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+use std::fs;
+use std::ops::Add;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use serde::Serialize;
+
+use crate::discovery::{FontDiscovery, PathDiscovery};
+use crate::query::Query;
+use crate::search::{read_metadata, SearchOptions};
+use crate::tags::tag4;
+
+/// Timings and throughput of one pass over a corpus. Produced by [`run`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BenchRun {
+    /// Font files found.
+    pub files: usize,
+    /// Faces parsed from them.
+    pub faces: usize,
+    /// Bytes read.
+    pub bytes: u64,
+    /// Files that could not be read or parsed.
+    pub errors: usize,
+    /// Face and query pairs that matched, over all [`standard_queries`].
+    pub matches: usize,
+    /// Worker threads the scan ran on.
+    pub threads: usize,
+    /// Wall-clock seconds spent walking the roots.
+    pub discover_seconds: f64,
+    /// Wall-clock seconds spent reading, parsing and matching.
+    pub scan_seconds: f64,
+    /// Seconds spent reading files, summed over the threads.
+    pub read_seconds: f64,
+    /// Seconds spent parsing font data, summed over the threads.
+    pub parse_seconds: f64,
+    /// Seconds spent matching faces, summed over the threads.
+    pub match_seconds: f64,
+    /// Files scanned per wall-clock second of the scan.
+    pub fonts_per_second: f64,
+    /// Megabytes (10^6 bytes) read per wall-clock second of the scan.
+    pub mb_per_second: f64,
+}
+
+/// The queries every face is matched against, with a short label each:
+/// everything, Latin script support, variable fonts, ligatures and
+/// Cyrillic coverage.
+pub fn standard_queries() -> Vec<(&'static str, Query)> {
+    let tag = |raw: &str| tag4(raw).expect("static tag");
+    vec![
+        ("all", Query::new()),
+        ("latn", Query::new().with_scripts(vec![tag("latn")])),
+        ("variable", Query::new().require_variable(true)),
+        ("liga", Query::new().with_features(vec![tag("liga")])),
+        ("cyrillic", Query::new().with_codepoints(vec!['Ж', 'я'])),
+    ]
+}
+
+/// Walk `paths` and read, parse and match every font found, once.
+///
+/// Honors the walk, thread and name settings of `opts`; its scan cache,
+/// deadline and error policy are ignored, since a benchmark reads every
+/// file and counts the ones that fail. Errors only when a root does not
+/// exist.
+pub fn run(paths: &[PathBuf], opts: &SearchOptions) -> Result<BenchRun> {
+    let started = Instant::now();
+    let candidates = PathDiscovery::new(paths.iter().cloned())
+        .follow_symlinks(opts.follow_symlinks)
+        .include_hidden(opts.include_hidden)
+        .discover()?;
+    let discovered = started.elapsed();
+
+    let queries = standard_queries();
+    let scan = || {
+        let totals = candidates
+            .par_iter()
+            .map(|loc| measure(&loc.path, opts, &queries))
+            .reduce(Totals::default, Totals::add);
+        (totals, rayon::current_num_threads())
+    };
+    let started = Instant::now();
+    let (totals, threads) = match opts.jobs {
+        Some(jobs) => ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build()?
+            .install(scan),
+        None => scan(),
+    };
+    let scanned = started.elapsed().as_secs_f64();
+
+    let per_second = |amount: f64| {
+        if scanned > 0.0 {
+            amount / scanned
+        } else {
+            0.0
+        }
+    };
+    Ok(BenchRun {
+        files: candidates.len(),
+        faces: totals.faces,
+        bytes: totals.bytes,
+        errors: totals.errors,
+        matches: totals.matches,
+        threads,
+        discover_seconds: discovered.as_secs_f64(),
+        scan_seconds: scanned,
+        read_seconds: totals.read.as_secs_f64(),
+        parse_seconds: totals.parse.as_secs_f64(),
+        match_seconds: totals.matching.as_secs_f64(),
+        fonts_per_second: per_second(candidates.len() as f64),
+        mb_per_second: per_second(totals.bytes as f64 / 1e6),
+    })
+}
+
+/// Counts and phase times of the files one worker measured.
+#[derive(Default)]
+struct Totals {
+    faces: usize,
+    bytes: u64,
+    errors: usize,
+    matches: usize,
+    read: Duration,
+    parse: Duration,
+    matching: Duration,
+}
+
+impl Add for Totals {
+    type Output = Totals;
+
+    fn add(self, other: Totals) -> Totals {
+        Totals {
+            faces: self.faces + other.faces,
+            bytes: self.bytes + other.bytes,
+            errors: self.errors + other.errors,
+            matches: self.matches + other.matches,
+            read: self.read + other.read,
+            parse: self.parse + other.parse,
+            matching: self.matching + other.matching,
+        }
+    }
+}
+
+/// Read, parse and match one file, timing each step.
+fn measure(path: &Path, opts: &SearchOptions, queries: &[(&str, Query)]) -> Totals {
+    let mut totals = Totals::default();
+
+    let started = Instant::now();
+    let data = fs::read(path);
+    totals.read = started.elapsed();
+    let Ok(data) = data else {
+        totals.errors = 1;
+        return totals;
+    };
+    totals.bytes = data.len() as u64;
+
+    let started = Instant::now();
+    let faces = read_metadata(&data, path, opts);
+    totals.parse = started.elapsed();
+    let Ok(faces) = faces else {
+        totals.errors = 1;
+        return totals;
+    };
+    totals.faces = faces.len();
+
+    let started = Instant::now();
+    totals.matches = faces
+        .iter()
+        .map(|face| {
+            queries
+                .iter()
+                .filter(|(_, query)| query.matches(&face.metadata))
+                .count()
+        })
+        .sum();
+    totals.matching = started.elapsed();
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_unreadable_files_as_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("broken.ttf"), b"not a font").unwrap();
+        fs::write(dir.path().join("notes.txt"), b"ignored").unwrap();
+
+        let opts = SearchOptions {
+            jobs: Some(2),
+            ..SearchOptions::default()
+        };
+        let run = run(&[dir.path().to_path_buf()], &opts).unwrap();
+        assert_eq!(run.files, 1);
+        assert_eq!(run.errors, 1);
+        assert_eq!(run.faces, 0);
+        assert_eq!(run.bytes, 10);
+        assert_eq!(run.threads, 2);
+    }
+}
@@ -0,0 +1,482 @@
+//! Unicode block table and coverage measurement.
+//!
+//! Listing every codepoint of a script on the command line is tedious.
+//! Blocks name the ranges instead: "Cyrillic" is U+0400–U+04FF, "Greek and
+//! Coptic" U+0370–U+03FF. [`block_coverage`](crate::blocks::block_coverage)
+//! measures how much of a block a font's `cmap` covers, and
+//! [`CoverageFilter`](crate::query::CoverageFilter) turns that into a query
+//! criterion ("Cyrillic:90%").
+//!
+//! Coverage is measured against *assigned* characters only, so blocks with
+//! reserved holes (Arabic Extended-A, say) can still reach 100%. Surrogates
+//! are not counted — they can never appear in a `cmap`. The table follows
+//! Unicode 14.0 (`Blocks.txt`).
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+
+/// A named Unicode block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnicodeBlock {
+    /// Block name as in `Blocks.txt`, e.g. "Latin Extended-A".
+    pub name: &'static str,
+    /// First codepoint of the block.
+    pub start: u32,
+    /// Last codepoint of the block (inclusive).
+    pub end: u32,
+    /// Number of assigned, non-surrogate characters in the block.
+    pub assigned: u32,
+}
+
+impl UnicodeBlock {
+    /// Whether `cp` falls inside this block.
+    pub fn contains(&self, cp: char) -> bool {
+        (self.start..=self.end).contains(&(cp as u32))
+    }
+}
+
+/// Look up a block by name.
+///
+/// Matching follows UAX #44 loose matching: case, spaces, hyphens, and
+/// underscores are ignored, so "latin extended a", "Latin_Extended-A", and
+/// "LatinExtendedA" all find "Latin Extended-A".
+pub fn find_block(name: &str) -> Option<&'static UnicodeBlock> {
+    let wanted = loose(name);
+    BLOCKS.iter().find(|block| loose(block.name) == wanted)
+}
+
+/// The block containing `cp`, if any.
+pub fn block_of(cp: char) -> Option<&'static UnicodeBlock> {
+    let cp = cp as u32;
+    let idx = BLOCKS.partition_point(|block| block.end < cp);
+    BLOCKS.get(idx).filter(|block| block.start <= cp)
+}
+
+/// Fraction (0.0–1.0) of a block's assigned characters present in
+/// `codepoints`.
+///
+/// `codepoints` must be sorted, as [`TypgFontFaceMeta::codepoints`](crate::search::TypgFontFaceMeta::codepoints)
+/// always is. Unassigned codepoints a font happens to map are counted too,
+/// so the result is capped at 1.0.
+pub fn block_coverage(block: &UnicodeBlock, codepoints: &[char]) -> f32 {
+    let lo = codepoints.partition_point(|&cp| (cp as u32) < block.start);
+    let hi = codepoints.partition_point(|&cp| (cp as u32) <= block.end);
+    coverage_ratio(block, (hi - lo) as u64)
+}
+
+/// Turn a count of covered codepoints into a coverage ratio for `block`.
+pub fn coverage_ratio(block: &UnicodeBlock, covered: u64) -> f32 {
+    if block.assigned == 0 {
+        return 0.0;
+    }
+    (covered as f32 / block.assigned as f32).min(1.0)
+}
+
+fn loose(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Every Unicode 14.0 block, sorted by start codepoint.
+pub const BLOCKS: &[UnicodeBlock] = &[
+    block("Basic Latin", 0x0000, 0x007F, 128),
+    block("Latin-1 Supplement", 0x0080, 0x00FF, 128),
+    block("Latin Extended-A", 0x0100, 0x017F, 128),
+    block("Latin Extended-B", 0x0180, 0x024F, 208),
+    block("IPA Extensions", 0x0250, 0x02AF, 96),
+    block("Spacing Modifier Letters", 0x02B0, 0x02FF, 80),
+    block("Combining Diacritical Marks", 0x0300, 0x036F, 112),
+    block("Greek and Coptic", 0x0370, 0x03FF, 135),
+    block("Cyrillic", 0x0400, 0x04FF, 256),
+    block("Cyrillic Supplement", 0x0500, 0x052F, 48),
+    block("Armenian", 0x0530, 0x058F, 91),
+    block("Hebrew", 0x0590, 0x05FF, 88),
+    block("Arabic", 0x0600, 0x06FF, 256),
+    block("Syriac", 0x0700, 0x074F, 77),
+    block("Arabic Supplement", 0x0750, 0x077F, 48),
+    block("Thaana", 0x0780, 0x07BF, 50),
+    block("NKo", 0x07C0, 0x07FF, 62),
+    block("Samaritan", 0x0800, 0x083F, 61),
+    block("Mandaic", 0x0840, 0x085F, 29),
+    block("Syriac Supplement", 0x0860, 0x086F, 11),
+    block("Arabic Extended-B", 0x0870, 0x089F, 41),
+    block("Arabic Extended-A", 0x08A0, 0x08FF, 96),
+    block("Devanagari", 0x0900, 0x097F, 128),
+    block("Bengali", 0x0980, 0x09FF, 96),
+    block("Gurmukhi", 0x0A00, 0x0A7F, 80),
+    block("Gujarati", 0x0A80, 0x0AFF, 91),
+    block("Oriya", 0x0B00, 0x0B7F, 91),
+    block("Tamil", 0x0B80, 0x0BFF, 72),
+    block("Telugu", 0x0C00, 0x0C7F, 100),
+    block("Kannada", 0x0C80, 0x0CFF, 90),
+    block("Malayalam", 0x0D00, 0x0D7F, 118),
+    block("Sinhala", 0x0D80, 0x0DFF, 91),
+    block("Thai", 0x0E00, 0x0E7F, 87),
+    block("Lao", 0x0E80, 0x0EFF, 82),
+    block("Tibetan", 0x0F00, 0x0FFF, 211),
+    block("Myanmar", 0x1000, 0x109F, 160),
+    block("Georgian", 0x10A0, 0x10FF, 88),
+    block("Hangul Jamo", 0x1100, 0x11FF, 256),
+    block("Ethiopic", 0x1200, 0x137F, 358),
+    block("Ethiopic Supplement", 0x1380, 0x139F, 26),
+    block("Cherokee", 0x13A0, 0x13FF, 92),
+    block("Unified Canadian Aboriginal Syllabics", 0x1400, 0x167F, 640),
+    block("Ogham", 0x1680, 0x169F, 29),
+    block("Runic", 0x16A0, 0x16FF, 89),
+    block("Tagalog", 0x1700, 0x171F, 23),
+    block("Hanunoo", 0x1720, 0x173F, 23),
+    block("Buhid", 0x1740, 0x175F, 20),
+    block("Tagbanwa", 0x1760, 0x177F, 18),
+    block("Khmer", 0x1780, 0x17FF, 114),
+    block("Mongolian", 0x1800, 0x18AF, 158),
+    block(
+        "Unified Canadian Aboriginal Syllabics Extended",
+        0x18B0,
+        0x18FF,
+        70,
+    ),
+    block("Limbu", 0x1900, 0x194F, 68),
+    block("Tai Le", 0x1950, 0x197F, 35),
+    block("New Tai Lue", 0x1980, 0x19DF, 83),
+    block("Khmer Symbols", 0x19E0, 0x19FF, 32),
+    block("Buginese", 0x1A00, 0x1A1F, 30),
+    block("Tai Tham", 0x1A20, 0x1AAF, 127),
+    block("Combining Diacritical Marks Extended", 0x1AB0, 0x1AFF, 31),
+    block("Balinese", 0x1B00, 0x1B7F, 124),
+    block("Sundanese", 0x1B80, 0x1BBF, 64),
+    block("Batak", 0x1BC0, 0x1BFF, 56),
+    block("Lepcha", 0x1C00, 0x1C4F, 74),
+    block("Ol Chiki", 0x1C50, 0x1C7F, 48),
+    block("Cyrillic Extended-C", 0x1C80, 0x1C8F, 9),
+    block("Georgian Extended", 0x1C90, 0x1CBF, 46),
+    block("Sundanese Supplement", 0x1CC0, 0x1CCF, 8),
+    block("Vedic Extensions", 0x1CD0, 0x1CFF, 43),
+    block("Phonetic Extensions", 0x1D00, 0x1D7F, 128),
+    block("Phonetic Extensions Supplement", 0x1D80, 0x1DBF, 64),
+    block("Combining Diacritical Marks Supplement", 0x1DC0, 0x1DFF, 64),
+    block("Latin Extended Additional", 0x1E00, 0x1EFF, 256),
+    block("Greek Extended", 0x1F00, 0x1FFF, 233),
+    block("General Punctuation", 0x2000, 0x206F, 111),
+    block("Superscripts and Subscripts", 0x2070, 0x209F, 42),
+    block("Currency Symbols", 0x20A0, 0x20CF, 33),
+    block(
+        "Combining Diacritical Marks for Symbols",
+        0x20D0,
+        0x20FF,
+        33,
+    ),
+    block("Letterlike Symbols", 0x2100, 0x214F, 80),
+    block("Number Forms", 0x2150, 0x218F, 60),
+    block("Arrows", 0x2190, 0x21FF, 112),
+    block("Mathematical Operators", 0x2200, 0x22FF, 256),
+    block("Miscellaneous Technical", 0x2300, 0x23FF, 256),
+    block("Control Pictures", 0x2400, 0x243F, 39),
+    block("Optical Character Recognition", 0x2440, 0x245F, 11),
+    block("Enclosed Alphanumerics", 0x2460, 0x24FF, 160),
+    block("Box Drawing", 0x2500, 0x257F, 128),
+    block("Block Elements", 0x2580, 0x259F, 32),
+    block("Geometric Shapes", 0x25A0, 0x25FF, 96),
+    block("Miscellaneous Symbols", 0x2600, 0x26FF, 256),
+    block("Dingbats", 0x2700, 0x27BF, 192),
+    block("Miscellaneous Mathematical Symbols-A", 0x27C0, 0x27EF, 48),
+    block("Supplemental Arrows-A", 0x27F0, 0x27FF, 16),
+    block("Braille Patterns", 0x2800, 0x28FF, 256),
+    block("Supplemental Arrows-B", 0x2900, 0x297F, 128),
+    block("Miscellaneous Mathematical Symbols-B", 0x2980, 0x29FF, 128),
+    block("Supplemental Mathematical Operators", 0x2A00, 0x2AFF, 256),
+    block("Miscellaneous Symbols and Arrows", 0x2B00, 0x2BFF, 253),
+    block("Glagolitic", 0x2C00, 0x2C5F, 96),
+    block("Latin Extended-C", 0x2C60, 0x2C7F, 32),
+    block("Coptic", 0x2C80, 0x2CFF, 123),
+    block("Georgian Supplement", 0x2D00, 0x2D2F, 40),
+    block("Tifinagh", 0x2D30, 0x2D7F, 59),
+    block("Ethiopic Extended", 0x2D80, 0x2DDF, 79),
+    block("Cyrillic Extended-A", 0x2DE0, 0x2DFF, 32),
+    block("Supplemental Punctuation", 0x2E00, 0x2E7F, 94),
+    block("CJK Radicals Supplement", 0x2E80, 0x2EFF, 115),
+    block("Kangxi Radicals", 0x2F00, 0x2FDF, 214),
+    block("Ideographic Description Characters", 0x2FF0, 0x2FFF, 12),
+    block("CJK Symbols and Punctuation", 0x3000, 0x303F, 64),
+    block("Hiragana", 0x3040, 0x309F, 93),
+    block("Katakana", 0x30A0, 0x30FF, 96),
+    block("Bopomofo", 0x3100, 0x312F, 43),
+    block("Hangul Compatibility Jamo", 0x3130, 0x318F, 94),
+    block("Kanbun", 0x3190, 0x319F, 16),
+    block("Bopomofo Extended", 0x31A0, 0x31BF, 32),
+    block("CJK Strokes", 0x31C0, 0x31EF, 36),
+    block("Katakana Phonetic Extensions", 0x31F0, 0x31FF, 16),
+    block("Enclosed CJK Letters and Months", 0x3200, 0x32FF, 255),
+    block("CJK Compatibility", 0x3300, 0x33FF, 256),
+    block("CJK Unified Ideographs Extension A", 0x3400, 0x4DBF, 6592),
+    block("Yijing Hexagram Symbols", 0x4DC0, 0x4DFF, 64),
+    block("CJK Unified Ideographs", 0x4E00, 0x9FFF, 20992),
+    block("Yi Syllables", 0xA000, 0xA48F, 1165),
+    block("Yi Radicals", 0xA490, 0xA4CF, 55),
+    block("Lisu", 0xA4D0, 0xA4FF, 48),
+    block("Vai", 0xA500, 0xA63F, 300),
+    block("Cyrillic Extended-B", 0xA640, 0xA69F, 96),
+    block("Bamum", 0xA6A0, 0xA6FF, 88),
+    block("Modifier Tone Letters", 0xA700, 0xA71F, 32),
+    block("Latin Extended-D", 0xA720, 0xA7FF, 193),
+    block("Syloti Nagri", 0xA800, 0xA82F, 45),
+    block("Common Indic Number Forms", 0xA830, 0xA83F, 10),
+    block("Phags-pa", 0xA840, 0xA87F, 56),
+    block("Saurashtra", 0xA880, 0xA8DF, 82),
+    block("Devanagari Extended", 0xA8E0, 0xA8FF, 32),
+    block("Kayah Li", 0xA900, 0xA92F, 48),
+    block("Rejang", 0xA930, 0xA95F, 37),
+    block("Hangul Jamo Extended-A", 0xA960, 0xA97F, 29),
+    block("Javanese", 0xA980, 0xA9DF, 91),
+    block("Myanmar Extended-B", 0xA9E0, 0xA9FF, 31),
+    block("Cham", 0xAA00, 0xAA5F, 83),
+    block("Myanmar Extended-A", 0xAA60, 0xAA7F, 32),
+    block("Tai Viet", 0xAA80, 0xAADF, 72),
+    block("Meetei Mayek Extensions", 0xAAE0, 0xAAFF, 23),
+    block("Ethiopic Extended-A", 0xAB00, 0xAB2F, 32),
+    block("Latin Extended-E", 0xAB30, 0xAB6F, 60),
+    block("Cherokee Supplement", 0xAB70, 0xABBF, 80),
+    block("Meetei Mayek", 0xABC0, 0xABFF, 56),
+    block("Hangul Syllables", 0xAC00, 0xD7AF, 11172),
+    block("Hangul Jamo Extended-B", 0xD7B0, 0xD7FF, 72),
+    block("High Surrogates", 0xD800, 0xDB7F, 0),
+    block("High Private Use Surrogates", 0xDB80, 0xDBFF, 0),
+    block("Low Surrogates", 0xDC00, 0xDFFF, 0),
+    block("Private Use Area", 0xE000, 0xF8FF, 6400),
+    block("CJK Compatibility Ideographs", 0xF900, 0xFAFF, 472),
+    block("Alphabetic Presentation Forms", 0xFB00, 0xFB4F, 58),
+    block("Arabic Presentation Forms-A", 0xFB50, 0xFDFF, 631),
+    block("Variation Selectors", 0xFE00, 0xFE0F, 16),
+    block("Vertical Forms", 0xFE10, 0xFE1F, 10),
+    block("Combining Half Marks", 0xFE20, 0xFE2F, 16),
+    block("CJK Compatibility Forms", 0xFE30, 0xFE4F, 32),
+    block("Small Form Variants", 0xFE50, 0xFE6F, 26),
+    block("Arabic Presentation Forms-B", 0xFE70, 0xFEFF, 141),
+    block("Halfwidth and Fullwidth Forms", 0xFF00, 0xFFEF, 225),
+    block("Specials", 0xFFF0, 0xFFFF, 5),
+    block("Linear B Syllabary", 0x10000, 0x1007F, 88),
+    block("Linear B Ideograms", 0x10080, 0x100FF, 123),
+    block("Aegean Numbers", 0x10100, 0x1013F, 57),
+    block("Ancient Greek Numbers", 0x10140, 0x1018F, 79),
+    block("Ancient Symbols", 0x10190, 0x101CF, 14),
+    block("Phaistos Disc", 0x101D0, 0x101FF, 46),
+    block("Lycian", 0x10280, 0x1029F, 29),
+    block("Carian", 0x102A0, 0x102DF, 49),
+    block("Coptic Epact Numbers", 0x102E0, 0x102FF, 28),
+    block("Old Italic", 0x10300, 0x1032F, 39),
+    block("Gothic", 0x10330, 0x1034F, 27),
+    block("Old Permic", 0x10350, 0x1037F, 43),
+    block("Ugaritic", 0x10380, 0x1039F, 31),
+    block("Old Persian", 0x103A0, 0x103DF, 50),
+    block("Deseret", 0x10400, 0x1044F, 80),
+    block("Shavian", 0x10450, 0x1047F, 48),
+    block("Osmanya", 0x10480, 0x104AF, 40),
+    block("Osage", 0x104B0, 0x104FF, 72),
+    block("Elbasan", 0x10500, 0x1052F, 40),
+    block("Caucasian Albanian", 0x10530, 0x1056F, 53),
+    block("Vithkuqi", 0x10570, 0x105BF, 70),
+    block("Linear A", 0x10600, 0x1077F, 341),
+    block("Latin Extended-F", 0x10780, 0x107BF, 57),
+    block("Cypriot Syllabary", 0x10800, 0x1083F, 55),
+    block("Imperial Aramaic", 0x10840, 0x1085F, 31),
+    block("Palmyrene", 0x10860, 0x1087F, 32),
+    block("Nabataean", 0x10880, 0x108AF, 40),
+    block("Hatran", 0x108E0, 0x108FF, 26),
+    block("Phoenician", 0x10900, 0x1091F, 29),
+    block("Lydian", 0x10920, 0x1093F, 27),
+    block("Meroitic Hieroglyphs", 0x10980, 0x1099F, 32),
+    block("Meroitic Cursive", 0x109A0, 0x109FF, 90),
+    block("Kharoshthi", 0x10A00, 0x10A5F, 68),
+    block("Old South Arabian", 0x10A60, 0x10A7F, 32),
+    block("Old North Arabian", 0x10A80, 0x10A9F, 32),
+    block("Manichaean", 0x10AC0, 0x10AFF, 51),
+    block("Avestan", 0x10B00, 0x10B3F, 61),
+    block("Inscriptional Parthian", 0x10B40, 0x10B5F, 30),
+    block("Inscriptional Pahlavi", 0x10B60, 0x10B7F, 27),
+    block("Psalter Pahlavi", 0x10B80, 0x10BAF, 29),
+    block("Old Turkic", 0x10C00, 0x10C4F, 73),
+    block("Old Hungarian", 0x10C80, 0x10CFF, 108),
+    block("Hanifi Rohingya", 0x10D00, 0x10D3F, 50),
+    block("Rumi Numeral Symbols", 0x10E60, 0x10E7F, 31),
+    block("Yezidi", 0x10E80, 0x10EBF, 47),
+    block("Old Sogdian", 0x10F00, 0x10F2F, 40),
+    block("Sogdian", 0x10F30, 0x10F6F, 42),
+    block("Old Uyghur", 0x10F70, 0x10FAF, 26),
+    block("Chorasmian", 0x10FB0, 0x10FDF, 28),
+    block("Elymaic", 0x10FE0, 0x10FFF, 23),
+    block("Brahmi", 0x11000, 0x1107F, 115),
+    block("Kaithi", 0x11080, 0x110CF, 68),
+    block("Sora Sompeng", 0x110D0, 0x110FF, 35),
+    block("Chakma", 0x11100, 0x1114F, 71),
+    block("Mahajani", 0x11150, 0x1117F, 39),
+    block("Sharada", 0x11180, 0x111DF, 96),
+    block("Sinhala Archaic Numbers", 0x111E0, 0x111FF, 20),
+    block("Khojki", 0x11200, 0x1124F, 62),
+    block("Multani", 0x11280, 0x112AF, 38),
+    block("Khudawadi", 0x112B0, 0x112FF, 69),
+    block("Grantha", 0x11300, 0x1137F, 86),
+    block("Newa", 0x11400, 0x1147F, 97),
+    block("Tirhuta", 0x11480, 0x114DF, 82),
+    block("Siddham", 0x11580, 0x115FF, 92),
+    block("Modi", 0x11600, 0x1165F, 79),
+    block("Mongolian Supplement", 0x11660, 0x1167F, 13),
+    block("Takri", 0x11680, 0x116CF, 68),
+    block("Ahom", 0x11700, 0x1174F, 65),
+    block("Dogra", 0x11800, 0x1184F, 60),
+    block("Warang Citi", 0x118A0, 0x118FF, 84),
+    block("Dives Akuru", 0x11900, 0x1195F, 72),
+    block("Nandinagari", 0x119A0, 0x119FF, 65),
+    block("Zanabazar Square", 0x11A00, 0x11A4F, 72),
+    block("Soyombo", 0x11A50, 0x11AAF, 83),
+    block(
+        "Unified Canadian Aboriginal Syllabics Extended-A",
+        0x11AB0,
+        0x11ABF,
+        16,
+    ),
+    block("Pau Cin Hau", 0x11AC0, 0x11AFF, 57),
+    block("Bhaiksuki", 0x11C00, 0x11C6F, 97),
+    block("Marchen", 0x11C70, 0x11CBF, 68),
+    block("Masaram Gondi", 0x11D00, 0x11D5F, 75),
+    block("Gunjala Gondi", 0x11D60, 0x11DAF, 63),
+    block("Makasar", 0x11EE0, 0x11EFF, 25),
+    block("Lisu Supplement", 0x11FB0, 0x11FBF, 1),
+    block("Tamil Supplement", 0x11FC0, 0x11FFF, 51),
+    block("Cuneiform", 0x12000, 0x123FF, 922),
+    block("Cuneiform Numbers and Punctuation", 0x12400, 0x1247F, 116),
+    block("Early Dynastic Cuneiform", 0x12480, 0x1254F, 196),
+    block("Cypro-Minoan", 0x12F90, 0x12FFF, 99),
+    block("Egyptian Hieroglyphs", 0x13000, 0x1342F, 1071),
+    block("Egyptian Hieroglyph Format Controls", 0x13430, 0x1343F, 9),
+    block("Anatolian Hieroglyphs", 0x14400, 0x1467F, 583),
+    block("Bamum Supplement", 0x16800, 0x16A3F, 569),
+    block("Mro", 0x16A40, 0x16A6F, 43),
+    block("Tangsa", 0x16A70, 0x16ACF, 89),
+    block("Bassa Vah", 0x16AD0, 0x16AFF, 36),
+    block("Pahawh Hmong", 0x16B00, 0x16B8F, 127),
+    block("Medefaidrin", 0x16E40, 0x16E9F, 91),
+    block("Miao", 0x16F00, 0x16F9F, 149),
+    block("Ideographic Symbols and Punctuation", 0x16FE0, 0x16FFF, 7),
+    block("Tangut", 0x17000, 0x187FF, 6136),
+    block("Tangut Components", 0x18800, 0x18AFF, 768),
+    block("Khitan Small Script", 0x18B00, 0x18CFF, 470),
+    block("Tangut Supplement", 0x18D00, 0x18D7F, 9),
+    block("Kana Extended-B", 0x1AFF0, 0x1AFFF, 13),
+    block("Kana Supplement", 0x1B000, 0x1B0FF, 256),
+    block("Kana Extended-A", 0x1B100, 0x1B12F, 35),
+    block("Small Kana Extension", 0x1B130, 0x1B16F, 7),
+    block("Nushu", 0x1B170, 0x1B2FF, 396),
+    block("Duployan", 0x1BC00, 0x1BC9F, 143),
+    block("Shorthand Format Controls", 0x1BCA0, 0x1BCAF, 4),
+    block("Znamenny Musical Notation", 0x1CF00, 0x1CFCF, 185),
+    block("Byzantine Musical Symbols", 0x1D000, 0x1D0FF, 246),
+    block("Musical Symbols", 0x1D100, 0x1D1FF, 233),
+    block("Ancient Greek Musical Notation", 0x1D200, 0x1D24F, 70),
+    block("Mayan Numerals", 0x1D2E0, 0x1D2FF, 20),
+    block("Tai Xuan Jing Symbols", 0x1D300, 0x1D35F, 87),
+    block("Counting Rod Numerals", 0x1D360, 0x1D37F, 25),
+    block("Mathematical Alphanumeric Symbols", 0x1D400, 0x1D7FF, 996),
+    block("Sutton SignWriting", 0x1D800, 0x1DAAF, 672),
+    block("Latin Extended-G", 0x1DF00, 0x1DFFF, 31),
+    block("Glagolitic Supplement", 0x1E000, 0x1E02F, 38),
+    block("Nyiakeng Puachue Hmong", 0x1E100, 0x1E14F, 71),
+    block("Toto", 0x1E290, 0x1E2BF, 31),
+    block("Wancho", 0x1E2C0, 0x1E2FF, 59),
+    block("Ethiopic Extended-B", 0x1E7E0, 0x1E7FF, 28),
+    block("Mende Kikakui", 0x1E800, 0x1E8DF, 213),
+    block("Adlam", 0x1E900, 0x1E95F, 88),
+    block("Indic Siyaq Numbers", 0x1EC70, 0x1ECBF, 68),
+    block("Ottoman Siyaq Numbers", 0x1ED00, 0x1ED4F, 61),
+    block(
+        "Arabic Mathematical Alphabetic Symbols",
+        0x1EE00,
+        0x1EEFF,
+        143,
+    ),
+    block("Mahjong Tiles", 0x1F000, 0x1F02F, 44),
+    block("Domino Tiles", 0x1F030, 0x1F09F, 100),
+    block("Playing Cards", 0x1F0A0, 0x1F0FF, 82),
+    block("Enclosed Alphanumeric Supplement", 0x1F100, 0x1F1FF, 200),
+    block("Enclosed Ideographic Supplement", 0x1F200, 0x1F2FF, 64),
+    block(
+        "Miscellaneous Symbols and Pictographs",
+        0x1F300,
+        0x1F5FF,
+        768,
+    ),
+    block("Emoticons", 0x1F600, 0x1F64F, 80),
+    block("Ornamental Dingbats", 0x1F650, 0x1F67F, 48),
+    block("Transport and Map Symbols", 0x1F680, 0x1F6FF, 117),
+    block("Alchemical Symbols", 0x1F700, 0x1F77F, 116),
+    block("Geometric Shapes Extended", 0x1F780, 0x1F7FF, 102),
+    block("Supplemental Arrows-C", 0x1F800, 0x1F8FF, 150),
+    block(
+        "Supplemental Symbols and Pictographs",
+        0x1F900,
+        0x1F9FF,
+        256,
+    ),
+    block("Chess Symbols", 0x1FA00, 0x1FA6F, 98),
+    block("Symbols and Pictographs Extended-A", 0x1FA70, 0x1FAFF, 88),
+    block("Symbols for Legacy Computing", 0x1FB00, 0x1FBFF, 212),
+    block(
+        "CJK Unified Ideographs Extension B",
+        0x20000,
+        0x2A6DF,
+        42720,
+    ),
+    block("CJK Unified Ideographs Extension C", 0x2A700, 0x2B73F, 4153),
+    block("CJK Unified Ideographs Extension D", 0x2B740, 0x2B81F, 222),
+    block("CJK Unified Ideographs Extension E", 0x2B820, 0x2CEAF, 5762),
+    block("CJK Unified Ideographs Extension F", 0x2CEB0, 0x2EBEF, 7473),
+    block(
+        "CJK Compatibility Ideographs Supplement",
+        0x2F800,
+        0x2FA1F,
+        542,
+    ),
+    block("CJK Unified Ideographs Extension G", 0x30000, 0x3134F, 4939),
+    block("Tags", 0xE0000, 0xE007F, 97),
+    block("Variation Selectors Supplement", 0xE0100, 0xE01EF, 240),
+    block("Supplementary Private Use Area-A", 0xF0000, 0xFFFFF, 65534),
+    block(
+        "Supplementary Private Use Area-B",
+        0x100000,
+        0x10FFFF,
+        65534,
+    ),
+];
+
+const fn block(name: &'static str, start: u32, end: u32, assigned: u32) -> UnicodeBlock {
+    UnicodeBlock {
+        name,
+        start,
+        end,
+        assigned,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_blocks_with_loose_names() {
+        let block = find_block("latin extended_a").expect("block");
+        assert_eq!(block.name, "Latin Extended-A");
+        assert_eq!((block.start, block.end), (0x0100, 0x017F));
+        assert!(find_block("Klingon").is_none());
+    }
+
+    #[test]
+    fn coverage_counts_only_codepoints_in_block() {
+        let cyrillic = find_block("Cyrillic").expect("block");
+        let mut cps: Vec<char> = ('A'..='Z').collect();
+        cps.extend((0x0400..0x0480).filter_map(char::from_u32));
+        cps.sort_unstable();
+
+        let ratio = block_coverage(cyrillic, &cps);
+        assert!((ratio - 0.5).abs() < 0.001, "ratio {ratio}");
+        assert_eq!(block_of('Ж').map(|b| b.name), Some("Cyrillic"));
+    }
+}
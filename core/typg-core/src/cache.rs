@@ -0,0 +1,292 @@
+//! The JSON cache file behind `typg cache`.
+//!
+//! A cache is a pretty-printed JSON array of [`TypgFontFaceMatch`] records,
+//! one per face, sorted by path and collection index. Readers also accept
+//! NDJSON, one record per line. The CLI's `cache add`/`find`/`list`/`clean`
+//! and the Python bindings go through these functions, so a cache written
+//! by one is read by the other.
+//!
+//! The folders `cache add` scanned, with the options it walked them with,
+//! are kept apart from the entries in a [`ScanRoot`] list next to the cache
+//! (`cache.json` has `cache.roots.json`), so the cache itself stays a plain
+//! array. `cache refresh` and `watch` rescan those roots when given no
+//! paths; the LMDB index records the same list in its `info` database.
+//!
+//! A cache root can hold several catalogs side by side — system fonts,
+//! foundry masters, client deliveries — as named collections. A collection
+//! keeps its cache file and index under `collections/NAME/` beside the
+//! default ones (see [`collection_path`]), so each is a complete store of
+//! its own that any command can be pointed at.
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Deserializer;
+
+use crate::discovery::path_key;
+use crate::intern::share_strings;
+use crate::search::{SearchOptions, TypgFontFaceMatch};
+
+/// A folder or file `cache add` scanned, and how, so that later commands
+/// can scan it again without being told.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanRoot {
+    /// The path as scanned, made absolute.
+    pub path: PathBuf,
+    /// Whether symlinks were followed (`--follow-symlinks`).
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// Whether hidden files and folders were read (`--hidden`).
+    #[serde(default)]
+    pub include_hidden: bool,
+    /// Name IDs read into font names (`--name-ids`); `None` for the default.
+    #[serde(default)]
+    pub name_ids: Option<Vec<u16>>,
+}
+
+impl ScanRoot {
+    /// Record `path`, made absolute against the current directory, with
+    /// the walk settings of `opts`.
+    pub fn new(path: &Path, opts: &SearchOptions) -> Result<Self> {
+        let path =
+            std::path::absolute(path).with_context(|| format!("resolving {}", path.display()))?;
+        Ok(Self {
+            path,
+            follow_symlinks: opts.follow_symlinks,
+            include_hidden: opts.include_hidden,
+            name_ids: opts.name_ids.clone(),
+        })
+    }
+
+    /// Search options that walk this root the way it was recorded.
+    pub fn options(&self) -> SearchOptions {
+        SearchOptions {
+            follow_symlinks: self.follow_symlinks,
+            include_hidden: self.include_hidden,
+            name_ids: self.name_ids.clone(),
+            ..SearchOptions::default()
+        }
+    }
+}
+
+/// Load cached font entries from disk. Tries JSON array first, falls back to NDJSON.
+pub fn load_cache(path: &Path) -> Result<Vec<TypgFontFaceMatch>> {
+    let file = File::open(path).with_context(|| format!("opening cache {}", path.display()))?;
+    let reader = BufReader::new(file);
+
+    let entries: Vec<TypgFontFaceMatch> = match serde_json::from_reader(reader) {
+        Ok(entries) => entries,
+        Err(_) => {
+            // Fall back to NDJSON parsing for forward compatibility
+            let file =
+                File::open(path).with_context(|| format!("re-opening cache {}", path.display()))?;
+            let reader = BufReader::new(file);
+            let stream = Deserializer::from_reader(reader).into_iter::<TypgFontFaceMatch>();
+            let mut entries = Vec::new();
+            for item in stream {
+                entries.push(item?);
+            }
+            entries
+        }
+    };
+    // Caches written before paths were compared per platform may list one
+    // file under two spellings; keep the later entry. The next write saves
+    // the merged list.
+    let mut entries = merge_entries(entries, Vec::new());
+    // A family's faces repeat names and license text; keep one copy each.
+    share_strings(&mut entries);
+    Ok(entries)
+}
+
+/// Write font entries to the cache file as pretty-printed JSON.
+pub fn write_cache(path: &Path, entries: &[TypgFontFaceMatch]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+    }
+
+    let file = File::create(path).with_context(|| format!("creating cache {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut writer, entries)
+        .with_context(|| format!("writing cache {}", path.display()))?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Combine two entry lists, sorted. A face present in both keeps its entry
+/// from `additions`.
+pub fn merge_entries(
+    existing: Vec<TypgFontFaceMatch>,
+    additions: Vec<TypgFontFaceMatch>,
+) -> Vec<TypgFontFaceMatch> {
+    let mut map: HashMap<(String, Option<u32>), TypgFontFaceMatch> = HashMap::new();
+
+    for entry in existing.into_iter().chain(additions) {
+        map.insert(cache_key(&entry), entry);
+    }
+
+    let mut merged: Vec<TypgFontFaceMatch> = map.into_values().collect();
+    sort_entries(&mut merged);
+    merged
+}
+
+/// Sort entries the way caches store them: by path, then collection index.
+pub fn sort_entries(entries: &mut [TypgFontFaceMatch]) {
+    entries.sort_by(|a, b| {
+        a.source
+            .path
+            .cmp(&b.source.path)
+            .then_with(|| a.source.ttc_index.cmp(&b.source.ttc_index))
+    });
+}
+
+/// Identity of a cache entry: its face, with the path compared the way the
+/// platform's filesystem compares it (see [`path_key`]).
+fn cache_key(entry: &TypgFontFaceMatch) -> (String, Option<u32>) {
+    (path_key(&entry.source.path), entry.source.ttc_index)
+}
+
+/// Add `added` to `roots`, sorted by path. A root recorded again takes its
+/// new settings.
+pub fn record_roots(
+    roots: Vec<ScanRoot>,
+    added: impl IntoIterator<Item = ScanRoot>,
+) -> Vec<ScanRoot> {
+    let mut map: HashMap<String, ScanRoot> = HashMap::new();
+    for root in roots.into_iter().chain(added) {
+        map.insert(path_key(&root.path), root);
+    }
+    let mut roots: Vec<ScanRoot> = map.into_values().collect();
+    roots.sort_by(|a, b| a.path.cmp(&b.path));
+    roots
+}
+
+/// The file recording the roots of the cache at `cache_path`:
+/// `cache.roots.json` beside `cache.json`.
+pub fn roots_path(cache_path: &Path) -> PathBuf {
+    cache_path.with_extension("roots.json")
+}
+
+/// Read the roots recorded for the cache at `cache_path`; none when the
+/// cache has no roots file (it was built before roots were kept, or only
+/// from imported records).
+pub fn load_roots(cache_path: &Path) -> Result<Vec<ScanRoot>> {
+    let path = roots_path(cache_path);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file = File::open(&path).with_context(|| format!("opening {}", path.display()))?;
+    serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("reading {}", path.display()))
+}
+
+/// Record `roots` for the cache at `cache_path`, replacing the list.
+pub fn write_roots(cache_path: &Path, roots: &[ScanRoot]) -> Result<()> {
+    let path = roots_path(cache_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+    }
+    let file = File::create(&path).with_context(|| format!("creating {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut writer, roots)
+        .with_context(|| format!("writing {}", path.display()))?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// The collection name that stands for the store itself rather than one of
+/// its named collections.
+pub const DEFAULT_COLLECTION: &str = "default";
+
+/// Check a collection name: letters, digits, `-`, `_` and `.`, not
+/// starting with `.`, so that it is always one plain directory name.
+pub fn parse_collection_name(raw: &str) -> Result<String> {
+    let name = raw.trim();
+    anyhow::ensure!(!name.is_empty(), "collection name is empty");
+    anyhow::ensure!(
+        !name.starts_with('.')
+            && name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.')),
+        "collection name {name:?} may only use letters, digits, '-', '_' and '.', and may not start with '.'"
+    );
+    Ok(name.to_string())
+}
+
+/// Where the collection `name` keeps the store that `store` — a cache file
+/// or index directory — holds for the default collection: under
+/// `collections/NAME/` beside it, by the same file name. So
+/// `~/.cache/typg/cache.json` becomes
+/// `~/.cache/typg/collections/masters/cache.json`. [`DEFAULT_COLLECTION`]
+/// is `store` itself.
+pub fn collection_path(store: &Path, name: &str) -> PathBuf {
+    if name == DEFAULT_COLLECTION {
+        return store.to_path_buf();
+    }
+    let parent = store.parent().unwrap_or(Path::new(""));
+    let file_name = store.file_name().unwrap_or(store.as_os_str());
+    parent.join("collections").join(name).join(file_name)
+}
+
+/// The named collections beside `store` that hold a store of the same
+/// name (see [`collection_path`]), sorted; none when there are no
+/// collections or they cannot be read.
+pub fn list_collections(store: &Path) -> Vec<String> {
+    let Some(file_name) = store.file_name() else {
+        return Vec::new();
+    };
+    let dir = store.parent().unwrap_or(Path::new("")).join("collections");
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join(file_name).exists())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name != DEFAULT_COLLECTION && parse_collection_name(name).is_ok())
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collections_live_beside_the_default_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("cache.json");
+        let masters = collection_path(&cache, "masters");
+        assert_eq!(masters, dir.path().join("collections/masters/cache.json"));
+        assert_eq!(collection_path(&cache, DEFAULT_COLLECTION), cache);
+        assert_eq!(
+            roots_path(&masters),
+            dir.path().join("collections/masters/cache.roots.json")
+        );
+
+        write_cache(&masters, &[]).unwrap();
+        write_cache(&collection_path(&cache, "clients"), &[]).unwrap();
+        // An index of that name is not a cache.
+        fs::create_dir_all(collection_path(&dir.path().join("index"), "archive")).unwrap();
+        assert_eq!(list_collections(&cache), ["clients", "masters"]);
+        assert_eq!(list_collections(&dir.path().join("index")), ["archive"]);
+    }
+
+    #[test]
+    fn collection_names_are_plain_directory_names() {
+        assert_eq!(parse_collection_name(" masters ").unwrap(), "masters");
+        assert_eq!(
+            parse_collection_name("client_2024.q3-b").unwrap(),
+            "client_2024.q3-b"
+        );
+        for bad in ["", "..", ".hidden", "a/b", "a\\b", "two words"] {
+            assert!(parse_collection_name(bad).is_err(), "{bad:?}");
+        }
+    }
+}
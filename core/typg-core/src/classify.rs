@@ -0,0 +1,154 @@
+//! Heuristic style classification for fonts that don't classify themselves.
+//!
+//! The OS/2 `sFamilyClass` field is the official way for a font to say "I am
+//! a sans serif", but a large share of real-world fonts leave it at 0 (no
+//! classification). That makes `--family-class sans` miss fonts that are
+//! obviously sans serif to any human.
+//!
+//! This module produces a best-guess category from whatever evidence the font
+//! does carry, strongest first:
+//!
+//! 1. The `post` table's `isFixedPitch` flag or PANOSE proportion → `mono`.
+//! 2. A non-zero OS/2 family class.
+//! 3. PANOSE family kind and serif style (OS/2 `panose` bytes).
+//! 4. Keywords in the font's names ("Sans", "Mono", "Script", "Display", …).
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+
+/// Category for monospaced fonts.
+pub const MONO: &str = "mono";
+/// Category for serif text faces.
+pub const SERIF: &str = "serif";
+/// Category for sans-serif text faces.
+pub const SANS: &str = "sans";
+/// Category for script and handwriting faces.
+pub const SCRIPT: &str = "script";
+/// Category for decorative, ornamental, and display faces.
+pub const DISPLAY: &str = "display";
+
+/// Guess a style category from the evidence a font carries.
+///
+/// `family_class` is the split OS/2 `sFamilyClass`, `panose` the 10 PANOSE
+/// bytes from OS/2 (empty if absent), `fixed_pitch` the `post.isFixedPitch`
+/// flag. Returns one of [`MONO`], [`SERIF`], [`SANS`], [`SCRIPT`], [`DISPLAY`],
+/// or `None` when nothing points anywhere.
+pub fn classify(
+    names: &[String],
+    family_class: Option<(u8, u8)>,
+    panose: &[u8],
+    fixed_pitch: bool,
+) -> Option<&'static str> {
+    // PANOSE proportion 9 means monospaced for Latin Text (kind 2).
+    let panose_mono = panose.len() > 3 && panose[0] == 2 && panose[3] == 9;
+    if fixed_pitch || panose_mono {
+        return Some(MONO);
+    }
+
+    if let Some(category) = family_class.and_then(|(major, _)| category_for_major(major)) {
+        return Some(category);
+    }
+
+    if let Some(category) = category_for_panose(panose) {
+        return Some(category);
+    }
+
+    category_for_names(names)
+}
+
+/// Map an OS/2 family class major value to a category.
+pub fn category_for_major(major: u8) -> Option<&'static str> {
+    match major {
+        1..=5 | 7 => Some(SERIF),
+        8 => Some(SANS),
+        9 => Some(DISPLAY),
+        10 => Some(SCRIPT),
+        _ => None,
+    }
+}
+
+/// Whether a heuristic category is compatible with an OS/2 major class.
+///
+/// Serif categories accept every serif major (1–5, 7), since the heuristic
+/// cannot tell oldstyle from transitional.
+pub fn category_matches_major(category: &str, major: u8) -> bool {
+    category_for_major(major) == Some(category)
+}
+
+fn category_for_panose(panose: &[u8]) -> Option<&'static str> {
+    let (&kind, &serif_style) = (panose.first()?, panose.get(1)?);
+    match kind {
+        // Latin Text: serif style 11–13 are the sans variants, 2–10 serifs.
+        2 => match serif_style {
+            11..=13 => Some(SANS),
+            2..=10 => Some(SERIF),
+            _ => None,
+        },
+        3 => Some(SCRIPT),
+        4 => Some(DISPLAY),
+        _ => None,
+    }
+}
+
+fn category_for_names(names: &[String]) -> Option<&'static str> {
+    let joined = names.join(" ").to_ascii_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| joined.contains(w));
+
+    if has(&["mono", "code", "console", "typewriter"]) {
+        Some(MONO)
+    } else if has(&["sans", "grotesk", "grotesque", "gothic"]) {
+        Some(SANS)
+    } else if has(&["script", "hand", "brush", "calligraph"]) {
+        Some(SCRIPT)
+    } else if has(&["display", "decorative", "ornament", "poster"]) {
+        Some(DISPLAY)
+    } else if has(&["serif", "slab", "antiqua", "roman"]) {
+        Some(SERIF)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn fixed_pitch_wins_over_everything() {
+        assert_eq!(
+            classify(&names(&["Noto Sans"]), Some((8, 0)), &[], true),
+            Some(MONO)
+        );
+    }
+
+    #[test]
+    fn os2_class_beats_panose_and_names() {
+        let panose = [3, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(
+            classify(&names(&["Foo Sans"]), Some((1, 0)), &panose, false),
+            Some(SERIF)
+        );
+    }
+
+    #[test]
+    fn panose_used_when_family_class_is_zero() {
+        let panose = [2, 11, 5, 2, 4, 5, 4, 2, 2, 4];
+        assert_eq!(classify(&[], Some((0, 0)), &panose, false), Some(SANS));
+    }
+
+    #[test]
+    fn names_are_the_last_resort() {
+        assert_eq!(
+            classify(&names(&["PT Sans Caption"]), Some((0, 0)), &[0; 10], false),
+            Some(SANS)
+        );
+        assert_eq!(
+            classify(&names(&["Source Serif 4"]), None, &[], false),
+            Some(SERIF)
+        );
+        assert_eq!(classify(&names(&["Mystery"]), None, &[], false), None);
+    }
+}
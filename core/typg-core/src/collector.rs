@@ -0,0 +1,76 @@
+//! Pluggable metadata extractors.
+//!
+//! typg extracts a fixed set of fields from every font (names, tags,
+//! codepoints, OS/2 classification). Embedders sometimes need more — a value
+//! from a private vendor table, a glyph count, a custom hash. Rather than
+//! forking the extraction code, implement [`MetadataCollector`] and pass it in
+//! [`SearchOptions::collectors`](crate::search::SearchOptions::collectors).
+//!
+//! Each collector's result lands under its [`key`](MetadataCollector::key) in
+//! [`TypgFontFaceMeta::extra`](crate::search::TypgFontFaceMeta::extra), and
+//! from there in JSON/NDJSON output and cache files.
+//!
+//! ```rust
+//! use read_fonts::{FontRef, TableProvider};
+//! use serde_json::Value;
+//! use typg_core::collector::MetadataCollector;
+//!
+//! /// Records the font's units-per-em.
+//! struct UnitsPerEm;
+//!
+//! impl MetadataCollector for UnitsPerEm {
+//!     fn key(&self) -> &str {
+//!         "units_per_em"
+//!     }
+//!
+//!     fn collect(&self, font: &FontRef) -> Option<Value> {
+//!         Some(font.head().ok()?.units_per_em().into())
+//!     }
+//! }
+//! ```
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use read_fonts::FontRef;
+use serde_json::Value;
+
+/// A custom per-face metadata extractor.
+///
+/// Collectors run on rayon worker threads, once per face, so they must be
+/// `Send + Sync` and should be cheap. Returning `None` leaves the key out of
+/// the face's `extra` map.
+pub trait MetadataCollector: Send + Sync {
+    /// Key under which the result is stored in `extra`.
+    fn key(&self) -> &str;
+
+    /// Extract a value from one font face.
+    fn collect(&self, font: &FontRef) -> Option<Value>;
+}
+
+impl fmt::Debug for dyn MetadataCollector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MetadataCollector")
+            .field(&self.key())
+            .finish()
+    }
+}
+
+/// Run every collector against a face and gather the non-`None` results.
+///
+/// When two collectors share a key, the later one wins.
+pub fn run_collectors(
+    collectors: &[Arc<dyn MetadataCollector>],
+    font: &FontRef,
+) -> BTreeMap<String, Value> {
+    collectors
+        .iter()
+        .filter_map(|collector| {
+            collector
+                .collect(font)
+                .map(|value| (collector.key().to_string(), value))
+        })
+        .collect()
+}
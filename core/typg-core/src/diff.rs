@@ -0,0 +1,465 @@
+//! Metadata comparison between a fresh scan and a saved cache.
+//!
+//! When a font library is updated in place, the interesting question is not
+//! "what fonts are here" but "what changed since last time". This module
+//! compares two snapshots of [`TypgFontFaceMatch`] entries face by face and
+//! reports added features, lost coverage, reclassified weights, and so on.
+//!
+//! Faces are paired by `(path, ttc_index)`, the same key the JSON cache uses.
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use read_fonts::types::Tag;
+use serde::Serialize;
+
+use crate::discovery::path_key;
+use crate::search::{
+    CmapSubtable, NamedInstance, TypgFontFaceMatch, TypgFontFaceMeta, TypgFontSource, VariationAxis,
+};
+use crate::tags::tag_to_string;
+
+/// Whether a scanned face is new or differs from its cached counterpart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeStatus {
+    /// The face is not in the cache at all.
+    New,
+    /// The face is cached, but its metadata differs.
+    Modified,
+}
+
+/// One face whose metadata differs from the cached snapshot.
+#[derive(Debug, Clone, Serialize)]
+pub struct FaceChange {
+    /// Where the changed face lives.
+    pub source: TypgFontSource,
+    /// New or modified.
+    pub status: ChangeStatus,
+    /// Human-readable change descriptions, one per changed field.
+    ///
+    /// Examples: `features +liga -smcp`, `codepoints -12 (coverage loss)`,
+    /// `weight_class 400 -> 700`. Empty for [`ChangeStatus::New`].
+    pub details: Vec<String>,
+}
+
+/// Compare freshly scanned faces against cached ones.
+///
+/// Returns only faces that are new or whose metadata differs; unchanged
+/// faces are omitted. Cached faces that are absent from the scan are not
+/// reported — a scan usually covers a subset of the cache.
+///
+/// Output is sorted by path, then TTC index.
+pub fn changed_faces(
+    cached: &[TypgFontFaceMatch],
+    scanned: &[TypgFontFaceMatch],
+) -> Vec<FaceChange> {
+    let by_key: HashMap<(String, Option<u32>), &TypgFontFaceMeta> = cached
+        .iter()
+        .map(|entry| {
+            (
+                (path_key(&entry.source.path), entry.source.ttc_index),
+                &entry.metadata,
+            )
+        })
+        .collect();
+
+    let mut changes: Vec<FaceChange> = scanned
+        .iter()
+        .filter_map(|entry| {
+            let key = (path_key(&entry.source.path), entry.source.ttc_index);
+            match by_key.get(&key) {
+                None => Some(FaceChange {
+                    source: entry.source.clone(),
+                    status: ChangeStatus::New,
+                    details: Vec::new(),
+                }),
+                Some(old) => {
+                    let details = diff_metadata(old, &entry.metadata);
+                    if details.is_empty() {
+                        None
+                    } else {
+                        Some(FaceChange {
+                            source: entry.source.clone(),
+                            status: ChangeStatus::Modified,
+                            details,
+                        })
+                    }
+                }
+            }
+        })
+        .collect();
+
+    changes.sort_by(|a, b| {
+        a.source
+            .path
+            .cmp(&b.source.path)
+            .then_with(|| a.source.ttc_index.cmp(&b.source.ttc_index))
+    });
+    changes
+}
+
+/// Describe every field that differs between two metadata snapshots.
+///
+/// Covers every serialized field but `modified` and `index_id`: a touched
+/// file or a face stored under a new ID is not a changed font. `file_hash`,
+/// `cmap_subtables` and `table_checksums` are compared only when both
+/// snapshots recorded them, since caches written before those fields
+/// existed leave them empty.
+///
+/// Returns an empty vec when the snapshots are equivalent.
+pub fn diff_metadata(old: &TypgFontFaceMeta, new: &TypgFontFaceMeta) -> Vec<String> {
+    let mut details = Vec::new();
+
+    diff_tags("axes", &old.axis_tags, &new.axis_tags, &mut details);
+    diff_tags(
+        "features",
+        &old.feature_tags,
+        &new.feature_tags,
+        &mut details,
+    );
+    diff_tags(
+        "gsub_features",
+        &old.gsub_feature_tags,
+        &new.gsub_feature_tags,
+        &mut details,
+    );
+    diff_tags(
+        "gpos_features",
+        &old.gpos_feature_tags,
+        &new.gpos_feature_tags,
+        &mut details,
+    );
+    diff_tags("scripts", &old.script_tags, &new.script_tags, &mut details);
+    diff_tags(
+        "languages",
+        &old.language_tags,
+        &new.language_tags,
+        &mut details,
+    );
+    diff_tags("tables", &old.table_tags, &new.table_tags, &mut details);
+
+    if old.codepoints != new.codepoints {
+        let gained = new
+            .codepoints
+            .iter()
+            .filter(|cp| old.codepoints.binary_search(cp).is_err())
+            .count();
+        let lost = old
+            .codepoints
+            .iter()
+            .filter(|cp| new.codepoints.binary_search(cp).is_err())
+            .count();
+        let mut parts = Vec::new();
+        if gained > 0 {
+            parts.push(format!("+{gained}"));
+        }
+        if lost > 0 {
+            parts.push(format!("-{lost} (coverage loss)"));
+        }
+        details.push(format!("codepoints {}", parts.join(" ")));
+    }
+
+    if old.is_variable != new.is_variable {
+        details.push(format!(
+            "is_variable {} -> {}",
+            old.is_variable, new.is_variable
+        ));
+    }
+    diff_option(
+        "weight_class",
+        &old.weight_class,
+        &new.weight_class,
+        &mut details,
+    );
+    diff_option(
+        "width_class",
+        &old.width_class,
+        &new.width_class,
+        &mut details,
+    );
+    diff_option(
+        "family_class",
+        &old.family_class,
+        &new.family_class,
+        &mut details,
+    );
+
+    diff_option(
+        "classified_as",
+        &old.classified_as,
+        &new.classified_as,
+        &mut details,
+    );
+
+    diff_option(
+        "units_per_em",
+        &old.units_per_em,
+        &new.units_per_em,
+        &mut details,
+    );
+    diff_option("style_name", &old.style_name, &new.style_name, &mut details);
+    diff_option(
+        "glyph_count",
+        &old.glyph_count,
+        &new.glyph_count,
+        &mut details,
+    );
+    diff_option(
+        "font_revision",
+        &old.font_revision,
+        &new.font_revision,
+        &mut details,
+    );
+    diff_option("vendor_id", &old.vendor_id, &new.vendor_id, &mut details);
+    diff_option(
+        "family_name",
+        &old.family_name,
+        &new.family_name,
+        &mut details,
+    );
+
+    diff_option(
+        "faces_in_collection",
+        &old.faces_in_collection,
+        &new.faces_in_collection,
+        &mut details,
+    );
+    diff_option("fs_type", &old.fs_type, &new.fs_type, &mut details);
+    diff_option("embedding", &old.embedding, &new.embedding, &mut details);
+    diff_option("file_size", &old.file_size, &new.file_size, &mut details);
+    if let (Some(before), Some(after)) = (old.file_hash, new.file_hash) {
+        if before != after {
+            details.push(format!("file_hash {before:016x} -> {after:016x}"));
+        }
+    }
+
+    diff_axes(old, new, &mut details);
+    diff_instances(old, new, &mut details);
+
+    if !old.cmap_subtables.is_empty()
+        && !new.cmap_subtables.is_empty()
+        && old.cmap_subtables != new.cmap_subtables
+    {
+        let render = |subtables: &[CmapSubtable]| {
+            subtables
+                .iter()
+                .map(|s| format!("{}/{}/{}", s.platform_id, s.encoding_id, s.format))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        details.push(format!(
+            "cmap_subtables {} -> {}",
+            render(&old.cmap_subtables),
+            render(&new.cmap_subtables)
+        ));
+    }
+    if !old.table_checksums.is_empty() && !new.table_checksums.is_empty() {
+        // Added and removed tables already show under `tables`.
+        let changed: Vec<&str> = new
+            .table_checksums
+            .iter()
+            .filter(|(tag, sum)| old.table_checksums.get(*tag).is_some_and(|old| old != *sum))
+            .map(|(tag, _)| tag.as_str())
+            .collect();
+        if !changed.is_empty() {
+            details.push(format!("table_checksums {}", changed.join(" ")));
+        }
+    }
+
+    if old.names != new.names {
+        details.push("names changed".to_string());
+    }
+    if old.creator_names != new.creator_names {
+        details.push("creator_names changed".to_string());
+    }
+    if old.license_names != new.license_names {
+        details.push("license_names changed".to_string());
+    }
+    if old.license_description != new.license_description {
+        details.push("license_description changed".to_string());
+    }
+    if old.license_url != new.license_url {
+        details.push("license_url changed".to_string());
+    }
+    if old.extra != new.extra {
+        details.push("extra changed".to_string());
+    }
+
+    details
+}
+
+/// Report axes whose range or default moved; axes that came or went show
+/// under `axes` through the axis tags.
+fn diff_axes(old: &TypgFontFaceMeta, new: &TypgFontFaceMeta, details: &mut Vec<String>) {
+    let render = |axis: &VariationAxis| format!("{}/{}/{}", axis.min, axis.default, axis.max);
+    for axis in &new.axes {
+        let Some(before) = old.axes.iter().find(|a| a.tag == axis.tag) else {
+            continue;
+        };
+        if before != axis {
+            details.push(format!(
+                "axis {} {} -> {}",
+                axis.tag,
+                render(before),
+                render(axis)
+            ));
+        }
+    }
+}
+
+/// Report named instances added or removed by name, or a plain "changed"
+/// when the same names now sit at other coordinates.
+fn diff_instances(old: &TypgFontFaceMeta, new: &TypgFontFaceMeta, details: &mut Vec<String>) {
+    if old.named_instances == new.named_instances {
+        return;
+    }
+    let names = |instances: &[NamedInstance]| -> Vec<Arc<str>> {
+        instances.iter().map(|i| Arc::clone(&i.name)).collect()
+    };
+    let (before, after) = (names(&old.named_instances), names(&new.named_instances));
+    let added = after
+        .iter()
+        .filter(|n| !before.contains(n))
+        .map(|n| format!("+{n}"));
+    let removed = before
+        .iter()
+        .filter(|n| !after.contains(n))
+        .map(|n| format!("-{n}"));
+    let parts: Vec<String> = added.chain(removed).collect();
+    if parts.is_empty() {
+        details.push("named_instances changed".to_string());
+    } else {
+        details.push(format!("named_instances {}", parts.join(" ")));
+    }
+}
+
+fn diff_tags(label: &str, old: &[Tag], new: &[Tag], details: &mut Vec<String>) {
+    let added: Vec<String> = new
+        .iter()
+        .filter(|tag| !old.contains(tag))
+        .map(|tag| format!("+{}", tag_to_string(*tag)))
+        .collect();
+    let removed: Vec<String> = old
+        .iter()
+        .filter(|tag| !new.contains(tag))
+        .map(|tag| format!("-{}", tag_to_string(*tag)))
+        .collect();
+
+    if !added.is_empty() || !removed.is_empty() {
+        let parts: Vec<String> = added.into_iter().chain(removed).collect();
+        details.push(format!("{label} {}", parts.join(" ")));
+    }
+}
+
+fn diff_option<T: PartialEq + std::fmt::Debug>(
+    label: &str,
+    old: &Option<T>,
+    new: &Option<T>,
+    details: &mut Vec<String>,
+) {
+    if old != new {
+        details.push(format!("{label} {} -> {}", render(old), render(new)));
+    }
+}
+
+fn render<T: std::fmt::Debug>(value: &Option<T>) -> String {
+    match value {
+        Some(v) => format!("{v:?}"),
+        None => "none".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::tags::tag4;
+
+    fn face(path: &str, features: &[&str], codepoints: &[char]) -> TypgFontFaceMatch {
+        TypgFontFaceMatch {
+            source: TypgFontSource {
+                path: PathBuf::from(path),
+                ttc_index: None,
+            },
+            metadata: TypgFontFaceMeta {
+                names: vec!["A".into()],
+                feature_tags: features.iter().map(|t| tag4(t).unwrap()).collect(),
+                codepoints: codepoints.to_vec(),
+                weight_class: Some(400),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn reports_feature_and_coverage_changes() {
+        let cached = vec![face("/fonts/A.ttf", &["kern", "smcp"], &['a', 'b', 'c'])];
+        let mut updated = face("/fonts/A.ttf", &["kern", "liga"], &['a', 'b']);
+        updated.metadata.weight_class = Some(700);
+
+        let changes = changed_faces(&cached, &[updated]);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].status, ChangeStatus::Modified);
+        assert!(changes[0]
+            .details
+            .contains(&"features +liga -smcp".to_string()));
+        assert!(changes[0]
+            .details
+            .contains(&"codepoints -1 (coverage loss)".to_string()));
+        assert!(changes[0]
+            .details
+            .contains(&"weight_class 400 -> 700".to_string()));
+    }
+
+    #[test]
+    fn omits_unchanged_and_flags_new_faces() {
+        let cached = vec![face("/fonts/A.ttf", &["kern"], &['a'])];
+        let scanned = vec![
+            face("/fonts/A.ttf", &["kern"], &['a']),
+            face("/fonts/B.ttf", &[], &[]),
+        ];
+
+        let changes = changed_faces(&cached, &scanned);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].source.path, PathBuf::from("/fonts/B.ttf"));
+        assert_eq!(changes[0].status, ChangeStatus::New);
+    }
+
+    #[test]
+    fn reports_axis_range_and_checksum_changes() {
+        let axis = |max: f32| VariationAxis {
+            tag: "wght".into(),
+            min: 100.0,
+            default: 400.0,
+            max,
+        };
+        let mut cached = face("/fonts/A.ttf", &[], &['a']);
+        cached.metadata.axes = vec![axis(900.0)];
+        cached.metadata.table_checksums = [("glyf".to_string(), 1), ("head".to_string(), 2)].into();
+        let mut scanned = cached.clone();
+        scanned.metadata.axes = vec![axis(1000.0)];
+        scanned.metadata.table_checksums.insert("glyf".into(), 3);
+        scanned.metadata.modified = Some(42);
+
+        let changes = changed_faces(&[cached.clone()], &[scanned]);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            changes[0].details,
+            [
+                "axis wght 100/400/900 -> 100/400/1000",
+                "table_checksums glyf"
+            ]
+        );
+
+        // Checksums a cache never recorded are not a change.
+        let mut scanned = cached.clone();
+        scanned.metadata.table_checksums.clear();
+        assert!(changed_faces(&[scanned], &[cached]).is_empty());
+    }
+}
@@ -9,12 +9,18 @@
 /// WOFF/WOFF2 web fonts are not included — they're compressed containers
 /// meant for browsers, not typically installed on the system.
 ///
+/// Hidden files and directories (names starting with `.`, such as `.Trash`,
+/// `.git` or macOS `._` AppleDouble files; on Windows also those with the
+/// hidden attribute) are skipped unless asked for, the way ripgrep and fd
+/// walk. A root you name yourself is always walked, hidden or not.
+///
 /// Directories that can't be read (permissions, broken mounts, dangling
 /// symlinks) are silently skipped. The walk continues. A single locked
 /// folder shouldn't kill a search across thousands of fonts.
 ///
 /// Made by FontLab <https://www.fontlab.com/>
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use anyhow::{anyhow, Result};
 use walkdir::WalkDir;
@@ -75,6 +81,10 @@ pub struct PathDiscovery {
     /// Follow symbolic links during traversal. Off by default to prevent
     /// infinite loops from circular symlinks.
     follow_symlinks: bool,
+    /// Walk into hidden directories and return hidden files. Off by default.
+    include_hidden: bool,
+    /// Stop walking once this passes, returning what was found so far.
+    deadline: Option<Instant>,
 }
 
 impl PathDiscovery {
@@ -91,6 +101,8 @@ impl PathDiscovery {
         Self {
             roots,
             follow_symlinks: false,
+            include_hidden: false,
+            deadline: None,
         }
     }
 
@@ -99,6 +111,19 @@ impl PathDiscovery {
         self.follow_symlinks = follow;
         self
     }
+
+    /// Include hidden files and directories below the roots.
+    pub fn include_hidden(mut self, include: bool) -> Self {
+        self.include_hidden = include;
+        self
+    }
+
+    /// Stop walking at `deadline` and return the fonts found until then;
+    /// `None` walks every root to the end.
+    pub fn deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
 }
 
 impl FontDiscovery for PathDiscovery {
@@ -118,7 +143,19 @@ impl FontDiscovery for PathDiscovery {
                 return Err(anyhow!("path does not exist: {}", root.display()));
             }
 
-            for entry in WalkDir::new(root).follow_links(self.follow_symlinks) {
+            let walk = WalkDir::new(root)
+                .follow_links(self.follow_symlinks)
+                .into_iter()
+                .filter_entry(|entry| {
+                    self.include_hidden || entry.depth() == 0 || !is_hidden_entry(entry)
+                });
+            for entry in walk {
+                if self
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline)
+                {
+                    return Ok(found);
+                }
                 let entry = match entry {
                     Ok(e) => e,
                     Err(_) => {
@@ -145,7 +182,7 @@ impl FontDiscovery for PathDiscovery {
 ///
 /// Not recognized: `.woff`, `.woff2` (web font containers), `.dfont`
 /// (legacy macOS resource-fork format), `.fon` (Windows bitmap fonts).
-fn is_font(path: &Path) -> bool {
+pub fn is_font(path: &Path) -> bool {
     let ext = match path.extension().and_then(|e| e.to_str()) {
         Some(ext) => ext.to_ascii_lowercase(),
         None => return false,
@@ -154,9 +191,84 @@ fn is_font(path: &Path) -> bool {
     matches!(ext.as_str(), "ttf" | "otf" | "ttc" | "otc")
 }
 
+/// Whether a file or directory name marks it hidden: it starts with `.`.
+///
+/// Covers dot-directories like `.Trash` and `.git` and macOS `._`
+/// AppleDouble files. `.` and `..` are not names of entries and do not
+/// count.
+pub fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.'))
+}
+
+/// Whether `path` lies inside a hidden directory below `root`, or is itself
+/// hidden. `root` and its ancestors do not count.
+pub fn is_hidden_below(path: &Path, root: &Path) -> bool {
+    let Ok(rest) = path.strip_prefix(root) else {
+        return false;
+    };
+    rest.components().any(|part| {
+        part.as_os_str()
+            .to_str()
+            .is_some_and(|p| p.starts_with('.'))
+    })
+}
+
+fn is_hidden_entry(entry: &walkdir::DirEntry) -> bool {
+    if is_hidden(entry.path()) {
+        return true;
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        if let Ok(meta) = entry.metadata() {
+            return meta.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0;
+        }
+    }
+    false
+}
+
+/// Whether this platform's default filesystems compare paths without regard
+/// to case: NTFS on Windows, APFS and HFS+ on macOS.
+pub const CASE_INSENSITIVE_PATHS: bool = cfg!(any(windows, target_os = "macos"));
+
+/// Comparison key for a font path under this platform's rules.
+///
+/// `C:\Fonts\A.ttf` and `c:/fonts/a.ttf` name the same file on Windows, as
+/// do `/Library/Fonts/A.ttf` and `/library/fonts/a.ttf` on a default macOS
+/// volume. Caches and the index key their entries by this instead of the
+/// path as spelled, so one file never gets two entries. On Linux the key is
+/// the path unchanged. Stored paths keep their original spelling.
+pub fn path_key(path: &Path) -> String {
+    fold_path(
+        &path.to_string_lossy(),
+        CASE_INSENSITIVE_PATHS,
+        cfg!(windows),
+    )
+}
+
+/// Lowercase `path` when `fold_case` is set, and turn `/` into `\` when
+/// `backslashes` is set.
+fn fold_path(path: &str, fold_case: bool, backslashes: bool) -> String {
+    let path = if backslashes {
+        path.replace('/', "\\")
+    } else {
+        path.to_string()
+    };
+    if fold_case {
+        path.to_lowercase()
+    } else {
+        path
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::fold_path;
     use super::is_font;
+    use super::is_hidden_below;
     use super::FontDiscovery;
     use super::PathDiscovery;
     use std::fs;
@@ -170,6 +282,19 @@ mod tests {
         assert!(!is_font("/A/B/font".as_ref()));
     }
 
+    #[test]
+    fn fold_path_follows_platform_rules() {
+        assert_eq!(
+            fold_path("C:\\Fonts\\A.ttf", true, true),
+            fold_path("c:/fonts/a.ttf", true, true)
+        );
+        assert_eq!(
+            fold_path("/Library/Fonts/Ä.ttf", true, false),
+            "/library/fonts/ä.ttf"
+        );
+        assert_eq!(fold_path("/fonts/A.ttf", false, false), "/fonts/A.ttf");
+    }
+
     #[test]
     fn discovers_nested_fonts() {
         let tmp = tempdir().expect("tempdir");
@@ -202,4 +327,32 @@ mod tests {
 
         assert!(fonts.iter().any(|f| f.path.ends_with("linked.otf")));
     }
+
+    #[test]
+    fn skips_hidden_entries_unless_included() {
+        let tmp = tempdir().expect("tempdir");
+        let root = tmp.path().join(".fonts");
+        fs::create_dir_all(root.join(".Trash")).expect("mkdir");
+        for name in ["kept.ttf", "._kept.ttf", ".Trash/old.ttf"] {
+            fs::write(root.join(name), b"").expect("touch font");
+        }
+
+        let mut default: Vec<_> = PathDiscovery::new([&root])
+            .discover()
+            .expect("discover")
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        default.sort();
+        assert_eq!(default, [root.join("kept.ttf")]);
+
+        let all = PathDiscovery::new([&root])
+            .include_hidden(true)
+            .discover()
+            .expect("discover");
+        assert_eq!(all.len(), 3);
+
+        assert!(is_hidden_below(&root.join(".Trash/old.ttf"), &root));
+        assert!(!is_hidden_below(&root.join("kept.ttf"), &root));
+    }
 }
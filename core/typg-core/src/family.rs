@@ -0,0 +1,89 @@
+//! Grouping faces by family.
+//!
+//! A search for "sans fonts with Cyrillic" often returns every weight and
+//! width of every matching family — forty faces of one superfamily crowding
+//! out everything else. These helpers work on whole families instead.
+//!
+//! The family comes from [`TypgFontFaceMeta::family_name`]. Entries without
+//! one (older caches, index results) fall back to their shortest name, which
+//! for most fonts is the bare family ("Inter" beside "Inter Bold").
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+use std::collections::HashMap;
+
+use crate::search::TypgFontFaceMeta;
+
+/// The family a face belongs to, as displayed.
+pub fn family_of(meta: &TypgFontFaceMeta) -> &str {
+    if let Some(family) = meta.family_name.as_deref() {
+        return family;
+    }
+    meta.names
+        .iter()
+        .min_by_key(|name| name.chars().count())
+        .map(|name| name.as_ref())
+        .unwrap_or("")
+}
+
+/// Case-insensitive grouping key for [`family_of`].
+pub fn family_key(meta: &TypgFontFaceMeta) -> String {
+    family_of(meta).to_lowercase()
+}
+
+/// Keep at most `n` faces per family, preserving input order.
+///
+/// Sort first: with path order the first faces of each family survive, with
+/// ranked order the best-scoring ones do. `meta` extracts the metadata from
+/// each item, so this works for plain and ranked results alike.
+pub fn limit_per_family<T>(
+    items: Vec<T>,
+    n: usize,
+    meta: impl Fn(&T) -> &TypgFontFaceMeta,
+) -> Vec<T> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    items
+        .into_iter()
+        .filter(|item| {
+            let count = seen.entry(family_key(meta(item))).or_insert(0);
+            *count += 1;
+            *count <= n
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(family: Option<&str>, names: &[&str]) -> TypgFontFaceMeta {
+        TypgFontFaceMeta {
+            names: names.iter().map(|&s| s.into()).collect(),
+            family_name: family.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn family_falls_back_to_shortest_name() {
+        assert_eq!(family_of(&meta(Some("Inter"), &["Inter Bold"])), "Inter");
+        assert_eq!(
+            family_of(&meta(None, &["Alpha Bold", "Alpha", "Alpha-Bold"])),
+            "Alpha"
+        );
+    }
+
+    #[test]
+    fn keeps_first_n_per_family_in_order() {
+        let items = vec![
+            meta(Some("Inter"), &["Inter Regular"]),
+            meta(Some("Lora"), &["Lora Regular"]),
+            meta(Some("inter"), &["Inter Bold"]),
+            meta(Some("Inter"), &["Inter Black"]),
+        ];
+
+        let kept = limit_per_family(items, 2, |m| m);
+
+        let names: Vec<&str> = kept.iter().map(|m| m.names[0].as_ref()).collect();
+        assert_eq!(names, vec!["Inter Regular", "Lora Regular", "Inter Bold"]);
+    }
+}
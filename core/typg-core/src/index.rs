@@ -5,32 +5,144 @@
 //! re-reading font files on every query.
 //!
 //! Made by FontLab https://www.fontlab.com/
-use std::collections::HashSet;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::{BufReader, Read, Write};
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::SystemTime;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
 
 use anyhow::{Context, Result};
 use bytemuck::{Pod, Zeroable};
-use heed::types::{Bytes, U64};
-use heed::{Database, Env, EnvOpenOptions, RoTxn, RwTxn};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use heed::types::{Bytes, DecodeIgnore, Str, U64};
+use heed::{CompactionOption, Database, Env, EnvFlags, EnvOpenOptions, RoTxn, RwTxn};
 use read_fonts::types::Tag;
 use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 
-use crate::query::{FamilyClassFilter, Query};
-use crate::search::TypgFontFaceMatch;
+use crate::blocks::coverage_ratio;
+use crate::cache::{record_roots, ScanRoot};
+use crate::classify::classify;
+use crate::discovery::{path_key, CASE_INSENSITIVE_PATHS};
+use crate::intern::share_strings;
+use crate::query::{Query, QueryExpr};
+use crate::search::{hash_file, TypgFontFaceMatch, TypgFontFaceMeta, TypgFontSource};
 
-/// Numeric identifier assigned to each indexed font face.
-pub type FontID = u64;
+pub use crate::search::FontID;
 
-/// Maximum LMDB environment size (10 GB, sufficient for over 1 million fonts).
-const MAX_DB_SIZE: usize = 10 * 1024 * 1024 * 1024;
+/// Default size of the memory map, the most the index can grow to: 10 GB,
+/// enough for over a million fonts, where the address space allows it and
+/// 1 GB on 32-bit targets.
+pub const DEFAULT_MAP_SIZE: usize = if cfg!(target_pointer_width = "64") {
+    10 << 30
+} else {
+    1 << 30
+};
+
+/// Map sizes are rounded up to a multiple of this, which every page size
+/// LMDB runs with (4, 16 or 64 KB) divides.
+const MAP_SIZE_GRANULE: usize = 64 * 1024;
 
 /// Maximum number of named LMDB databases.
 const MAX_DBS: u32 = 10;
 
+/// `info` key recording that `path_to_id` is keyed by [`path_key`] and that
+/// faces stored under differently spelled paths of one file were merged.
+const PATH_KEYS_MARKER: &str = "path_keys";
+
+/// `info` key recording that faces indexed before [`IndexedFontMeta::tags`]
+/// existed were marked stale, so the next `cache add --index` re-reads them.
+const TAG_LISTS_MARKER: &str = "tag_lists";
+
+/// `info` key recording that faces indexed before
+/// [`IndexedFontMeta::details`] existed were marked stale, so the next
+/// `cache add --index` re-reads them.
+const DETAILS_MARKER: &str = "details";
+
+/// `info` key recording that every face is filed under its cmap pages in
+/// `inverted` (see [`cmap_page_key`]), so codepoint queries can use them.
+const CMAP_PAGES_MARKER: &str = "cmap_pages";
+
+/// Top byte of the `inverted` keys of cmap pages; no tag contains it.
+const CMAP_PAGE_PREFIX: u32 = 0x0100_0000;
+
+/// `info` key recording that every face is filed under the trigrams of its
+/// names in `inverted` (see [`name_trigram_keys`]).
+const NAME_TRIGRAMS_MARKER: &str = "name_trigrams";
+
+/// `info` key holding the next font ID to hand out, as a little-endian
+/// `u64`. Every writer reads it in its own transaction, so processes
+/// sharing an index never hand out the same ID, and IDs of removed faces
+/// are not reused.
+const NEXT_ID_KEY: &str = "next_id";
+
+/// Top byte of the `inverted` keys of name trigrams; no tag contains it.
+const NAME_TRIGRAM_PREFIX: u32 = 0x0200_0000;
+
+/// `info` key holding the JSON list of [`ScanRoot`]s added to the index.
+const ROOTS_KEY: &str = "roots";
+
+/// First bytes of a snapshot written by [`IndexReader::export`].
+const SNAPSHOT_MAGIC: &[u8; 8] = b"TYPGSNAP";
+
+/// Layout version of the snapshots [`IndexReader::export`] writes;
+/// [`IndexWriter::import`] reads this version and older ones.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// LMDB's own default for simultaneous read transactions.
+pub const DEFAULT_MAX_READERS: u32 = 126;
+
+/// Tuning knobs for [`FontIndex::open_with`] and [`FontIndex::shared`].
+///
+/// The defaults suit the CLI, which opens an index, runs one query and
+/// exits. A long-running server answering many queries at once should open
+/// the index once through [`FontIndex::shared`] and size `max_readers` to
+/// the number of queries it wants in flight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexOptions {
+    /// Reader slots: how many [`IndexReader`]s may be open at the same time.
+    /// Further [`FontIndex::reader`] calls wait for a slot instead of
+    /// failing with LMDB's `MDB_READERS_FULL`. LMDB records the slot count
+    /// in the lock file when the environment is first opened, so other
+    /// processes sharing the index see the largest value any of them asked
+    /// for. Must be at least 1.
+    pub max_readers: u32,
+    /// Turn off OS read-ahead on the memory map (`MDB_NORDAHEAD`). Query
+    /// lookups touch scattered pages; once the index is larger than free
+    /// RAM, read-ahead mostly evicts pages other queries still need.
+    pub no_read_ahead: bool,
+    /// Bytes of address space to map, which caps how large the index can
+    /// grow; writes past it fail with `MDB_MAP_FULL`. Only address space is
+    /// reserved, not memory or disk, but 32-bit processes and containers
+    /// with a virtual memory limit need a smaller value. Rounded up to a
+    /// multiple of 64 KB; an index already larger than this opens at its
+    /// own size. Must be at least 1.
+    pub map_size: usize,
+    /// Open the environment read-only (`MDB_RDONLY`), as
+    /// [`open_ro`](FontIndex::open_ro) does: the index must exist, and
+    /// [`writer`](FontIndex::writer) fails.
+    pub read_only: bool,
+}
+
+impl Default for IndexOptions {
+    fn default() -> Self {
+        Self {
+            max_readers: DEFAULT_MAX_READERS,
+            no_read_ahead: false,
+            map_size: DEFAULT_MAP_SIZE,
+            read_only: false,
+        }
+    }
+}
+
+/// Environments opened through [`FontIndex::shared`], by canonical path.
+static SHARED: OnceLock<Mutex<HashMap<PathBuf, Arc<FontIndex>>>> = OnceLock::new();
+
 /// Metadata stored in the index for a single font face.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct IndexedFontMeta {
@@ -50,24 +162,304 @@ pub struct IndexedFontMeta {
     pub family_class: Option<(u8, u8)>,
     /// Serialized Roaring Bitmap of Unicode codepoints covered by the font.
     pub cmap_bitmap: Vec<u8>,
+    /// The face's tag lists; `None` for faces indexed before tag lists were
+    /// stored, which come back with empty lists until their files are
+    /// re-read.
+    pub tags: Option<IndexedTags>,
+    /// The face's whole [`TypgFontFaceMeta`] as JSON, codepoints left out
+    /// (`cmap_bitmap` holds them), so a face reads back as it was scanned;
+    /// `None` for faces stored before it was recorded or through
+    /// [`IndexWriter::add_font`], which come back with only the fields
+    /// above until their files are re-read.
+    pub details: Option<String>,
+}
+
+/// Metadata records written before [`IndexedFontMeta::details`] existed.
+#[derive(Serialize, Deserialize)]
+struct TaggedFontMeta {
+    path: String,
+    ttc_index: Option<u32>,
+    names: Vec<String>,
+    is_variable: bool,
+    weight_class: Option<u16>,
+    width_class: Option<u16>,
+    family_class: Option<(u8, u8)>,
+    cmap_bitmap: Vec<u8>,
+    tags: Option<IndexedTags>,
+}
+
+impl From<TaggedFontMeta> for IndexedFontMeta {
+    fn from(tagged: TaggedFontMeta) -> Self {
+        Self {
+            path: tagged.path,
+            ttc_index: tagged.ttc_index,
+            names: tagged.names,
+            is_variable: tagged.is_variable,
+            weight_class: tagged.weight_class,
+            width_class: tagged.width_class,
+            family_class: tagged.family_class,
+            cmap_bitmap: tagged.cmap_bitmap,
+            tags: tagged.tags,
+            details: None,
+        }
+    }
+}
+
+/// Metadata records written before [`IndexedFontMeta::tags`] existed.
+/// bincode records carry no field names, so these need their own layout.
+#[derive(Deserialize)]
+struct LegacyFontMeta {
+    path: String,
+    ttc_index: Option<u32>,
+    names: Vec<String>,
+    is_variable: bool,
+    weight_class: Option<u16>,
+    width_class: Option<u16>,
+    family_class: Option<(u8, u8)>,
+    cmap_bitmap: Vec<u8>,
+}
+
+impl From<LegacyFontMeta> for IndexedFontMeta {
+    fn from(legacy: LegacyFontMeta) -> Self {
+        Self {
+            path: legacy.path,
+            ttc_index: legacy.ttc_index,
+            names: legacy.names,
+            is_variable: legacy.is_variable,
+            weight_class: legacy.weight_class,
+            width_class: legacy.width_class,
+            family_class: legacy.family_class,
+            cmap_bitmap: legacy.cmap_bitmap,
+            tags: None,
+            details: None,
+        }
+    }
+}
+
+/// The tag lists of one face, in the order a scan reports them, so faces
+/// read back from the index carry the same tags as the JSON cache.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexedTags {
+    /// Variation axis tags (`fvar`).
+    pub axis: Vec<[u8; 4]>,
+    /// GSUB and GPOS feature tags together.
+    pub feature: Vec<[u8; 4]>,
+    /// GSUB feature tags.
+    pub gsub_feature: Vec<[u8; 4]>,
+    /// GPOS feature tags.
+    pub gpos_feature: Vec<[u8; 4]>,
+    /// GSUB and GPOS script tags.
+    pub script: Vec<[u8; 4]>,
+    /// GSUB and GPOS language system tags.
+    pub language: Vec<[u8; 4]>,
+    /// Top-level table tags.
+    pub table: Vec<[u8; 4]>,
+}
+
+impl IndexedTags {
+    /// The tag lists of a scanned face.
+    pub fn of(meta: &TypgFontFaceMeta) -> Self {
+        Self {
+            axis: tag_bytes(&meta.axis_tags),
+            feature: tag_bytes(&meta.feature_tags),
+            gsub_feature: tag_bytes(&meta.gsub_feature_tags),
+            gpos_feature: tag_bytes(&meta.gpos_feature_tags),
+            script: tag_bytes(&meta.script_tags),
+            language: tag_bytes(&meta.language_tags),
+            table: tag_bytes(&meta.table_tags),
+        }
+    }
+
+    /// Every tag the inverted index files the face under: axes, features,
+    /// scripts and tables.
+    fn searchable(&self) -> impl Iterator<Item = &[u8; 4]> {
+        self.axis
+            .iter()
+            .chain(&self.feature)
+            .chain(&self.script)
+            .chain(&self.table)
+    }
+}
+
+fn tag_bytes(tags: &[Tag]) -> Vec<[u8; 4]> {
+    tags.iter().map(|tag| tag.into_bytes()).collect()
+}
+
+fn tags_from_bytes(tags: &[[u8; 4]]) -> Vec<Tag> {
+    tags.iter().map(Tag::new).collect()
+}
+
+/// Entry count and size of one named LMDB database; see
+/// [`FontIndex::database_stats`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DatabaseStats {
+    /// Database name: `metadata`, `inverted`, `path_to_id` or `info`.
+    pub name: &'static str,
+    /// Number of key/value pairs.
+    pub entries: usize,
+    /// Bytes in the pages the database occupies, free pages not included.
+    pub size_bytes: u64,
+}
+
+/// What [`IndexWriter::ingest`] did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IngestStats {
+    /// Faces stored.
+    pub added: usize,
+    /// Faces of files the index already held unchanged.
+    pub skipped: usize,
+    /// Transactions committed along the way, the last one not included.
+    pub commits: usize,
+}
+
+/// Size of the data file before and after [`FontIndex::compact`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CompactStats {
+    /// Bytes of `data.mdb` before compaction, free pages included.
+    pub before_bytes: u64,
+    /// Bytes of `data.mdb` after compaction.
+    pub after_bytes: u64,
+}
+
+impl CompactStats {
+    /// Bytes returned to the filesystem.
+    pub fn reclaimed_bytes(&self) -> u64 {
+        self.before_bytes.saturating_sub(self.after_bytes)
+    }
+}
+
+/// Font files and faces written by [`IndexReader::export`] or read by
+/// [`IndexWriter::import`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SnapshotStats {
+    /// Font files.
+    pub files: usize,
+    /// Faces of those files.
+    pub faces: usize,
+}
+
+/// What a snapshot holds besides its files.
+#[derive(Serialize, Deserialize)]
+struct SnapshotHeader {
+    /// The index roots as JSON, the way the `info` database keeps them.
+    roots: String,
+}
+
+/// One font file of a snapshot: its faces as the index stores them, and
+/// the mtime and hash its path-to-ID entry records (0 when unknown).
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    path: String,
+    mtime_secs: u64,
+    file_hash: u64,
+    faces: Vec<IndexedFontMeta>,
+}
+
+/// A [`SnapshotFile`] of a version 1 snapshot, written before
+/// [`IndexedFontMeta::details`] existed.
+#[derive(Deserialize)]
+struct SnapshotFileV1 {
+    path: String,
+    mtime_secs: u64,
+    file_hash: u64,
+    faces: Vec<TaggedFontMeta>,
+}
+
+impl From<SnapshotFileV1> for SnapshotFile {
+    fn from(file: SnapshotFileV1) -> Self {
+        Self {
+            path: file.path,
+            mtime_secs: file.mtime_secs,
+            file_hash: file.file_hash,
+            faces: file.faces.into_iter().map(IndexedFontMeta::from).collect(),
+        }
+    }
+}
+
+/// Where an index's databases disagree with each other; see
+/// [`FontIndex::check`] and [`IndexWriter::repair`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IndexCheck {
+    /// Faces in the `metadata` database, readable or not.
+    pub faces: usize,
+    /// Faces whose metadata record does not deserialize.
+    pub unreadable_faces: Vec<FontID>,
+    /// Faces whose file has no `path_to_id` entry, so incremental updates
+    /// never look at them again.
+    pub unmapped_faces: Vec<FontID>,
+    /// `path_to_id` entries that do not decode or point at no stored face.
+    pub orphan_paths: usize,
+    /// Bitmap members that name no stored face. Removals take a face's ID
+    /// out of its bitmaps, but indexes written by earlier versions kept
+    /// them; a face later stored under such an ID would inherit its tags.
+    pub dangling_members: u64,
+    /// The distinct IDs behind [`dangling_members`](Self::dangling_members):
+    /// IDs some tag bitmap still names but no face is stored under.
+    pub orphan_ids: Vec<FontID>,
+    /// Tags whose bitmap does not deserialize; queries on them fail.
+    pub corrupt_bitmaps: Vec<String>,
+}
+
+impl IndexCheck {
+    /// Whether nothing was found.
+    pub fn is_consistent(&self) -> bool {
+        self.problems() == 0
+    }
+
+    /// Number of problems found, counting each face, entry, member and
+    /// bitmap once.
+    pub fn problems(&self) -> u64 {
+        (self.unreadable_faces.len()
+            + self.unmapped_faces.len()
+            + self.orphan_paths
+            + self.corrupt_bitmaps.len()) as u64
+            + self.dangling_members
+    }
 }
 
-/// Stored record mapping a path hash to its font ID and last-modified time.
+/// Stored record mapping a path hash to its font ID, last-modified time and
+/// file hash.
 ///
-/// Used for incremental index updates: if the mtime matches, the entry is current.
+/// Used for incremental index updates: if the mtime (and, when known, the
+/// content hash) matches, the entry is current.
 #[derive(Copy, Clone, Pod, Zeroable)]
 #[repr(C)]
 struct PathEntry {
     font_id: u64,
     mtime_secs: u64,
+    /// xxh3 of the file bytes; 0 when not recorded.
+    file_hash: u64,
+}
+
+impl PathEntry {
+    /// Decode a stored entry. Indexes written before file hashes were
+    /// recorded hold 16-byte entries without one.
+    fn decode(bytes: &[u8]) -> Option<PathEntry> {
+        const LEGACY_LEN: usize = 2 * std::mem::size_of::<u64>();
+        match bytes.len() {
+            len if len == std::mem::size_of::<PathEntry>() => {
+                Some(bytemuck::pod_read_unaligned(bytes))
+            }
+            LEGACY_LEN => {
+                let [font_id, mtime_secs]: [u64; 2] = bytemuck::pod_read_unaligned(bytes);
+                Some(PathEntry {
+                    font_id,
+                    mtime_secs,
+                    file_hash: 0,
+                })
+            }
+            _ => None,
+        }
+    }
 }
 
 /// LMDB-backed index for fast font queries using Roaring Bitmap tag intersection.
 ///
-/// Maintains three named databases:
+/// Maintains four named databases:
 /// - `metadata`: `FontID` → serialized `IndexedFontMeta`
 /// - `inverted`: tag (u32) → serialized `RoaringBitmap` of font IDs
-/// - `path_to_id`: path hash (u64) → `PathEntry` (font ID + mtime)
+/// - `path_to_id`: path hash (u64) → `PathEntry` (font ID, mtime, file hash)
+/// - `info`: name → value markers describing the index layout
 pub struct FontIndex {
     env: Env,
     /// `metadata` database: `FontID` → serialized font metadata.
@@ -76,87 +468,454 @@ pub struct FontIndex {
     db_inverted: Database<Bytes, Bytes>,
     /// `path_to_id` database: path hash → `PathEntry` for incremental updates.
     db_path_to_id: Database<U64<byteorder::NativeEndian>, Bytes>,
-    /// Monotonically increasing counter for allocating font IDs.
-    next_id: AtomicU64,
+    /// `info` database: layout markers such as [`PATH_KEYS_MARKER`].
+    db_info: Database<Str, Bytes>,
+    /// Free reader slots; see [`IndexOptions::max_readers`].
+    readers: ReaderSlots,
+    /// Decoded `inverted` bitmaps shared by readers of the same snapshot.
+    bitmaps: Mutex<TagBitmapCache>,
+    options: IndexOptions,
 }
 
 impl FontIndex {
     /// Open the index at `index_dir`, creating it if it does not exist.
     ///
-    /// Initializes the LMDB environment and the three named databases.
-    /// Scans existing metadata entries to determine the next available font ID.
+    /// Initializes the LMDB environment and the four named databases, with
+    /// default [`IndexOptions`].
+    ///
+    /// LMDB allows one open environment per path and process: opening a path
+    /// that is still open elsewhere in the process fails. Code that queries
+    /// the same index from many threads should use [`shared`](Self::shared).
     pub fn open(index_dir: &Path) -> Result<Self> {
-        fs::create_dir_all(index_dir)
-            .with_context(|| format!("creating index directory {}", index_dir.display()))?;
+        Self::open_with(index_dir, &IndexOptions::default())
+    }
+
+    /// Open the existing index at `index_dir` for reading only.
+    ///
+    /// Nothing is created or written, so this works on read-only file
+    /// systems such as mounted snapshots and backups; LMDB then also skips
+    /// its lock file. Where the lock file can be written, the index stays
+    /// safe to read while another process writes to it. Fails when the
+    /// index does not exist or lacks one of its databases, and every
+    /// [`writer`](Self::writer) call on the result fails.
+    pub fn open_ro(index_dir: &Path) -> Result<Self> {
+        Self::open_with(
+            index_dir,
+            &IndexOptions {
+                read_only: true,
+                ..IndexOptions::default()
+            },
+        )
+    }
+
+    /// Like [`open`](Self::open), with explicit tuning options.
+    pub fn open_with(index_dir: &Path, options: &IndexOptions) -> Result<Self> {
+        anyhow::ensure!(options.max_readers > 0, "max_readers must be at least 1");
+        anyhow::ensure!(options.map_size > 0, "map_size must be at least 1");
+        if options.read_only {
+            anyhow::ensure!(
+                index_dir.join("data.mdb").is_file(),
+                "no index at {}",
+                index_dir.display()
+            );
+        } else {
+            fs::create_dir_all(index_dir)
+                .with_context(|| format!("creating index directory {}", index_dir.display()))?;
+        }
 
+        // MDB_NOTLS ties reader slots to transactions rather than threads.
+        // Without it, every thread that ever reads (tokio's blocking pool
+        // churns through hundreds) keeps a slot until it exits.
+        let mut flags = EnvFlags::NO_TLS;
+        if options.no_read_ahead {
+            flags |= EnvFlags::NO_READ_AHEAD;
+        }
+        if options.read_only {
+            flags |= EnvFlags::READ_ONLY;
+        }
+        let mut env_options = EnvOpenOptions::new();
+        env_options
+            .map_size(options.map_size.next_multiple_of(MAP_SIZE_GRANULE))
+            .max_dbs(MAX_DBS)
+            .max_readers(options.max_readers);
+        // SAFETY: these flags only change how reader slots, page read-ahead
+        // and write access are managed; heed's transaction types stay sound.
+        unsafe {
+            env_options.flags(flags);
+        }
         let env = unsafe {
-            EnvOpenOptions::new()
-                .map_size(MAX_DB_SIZE)
-                .max_dbs(MAX_DBS)
+            env_options
                 .open(index_dir)
                 .with_context(|| format!("opening LMDB at {}", index_dir.display()))?
         };
 
-        // Create or open the named databases.
-        let mut wtxn = env.write_txn()?;
-        let db_metadata = env.create_database(&mut wtxn, Some("metadata"))?;
-        let db_inverted = env.create_database(&mut wtxn, Some("inverted"))?;
-        let db_path_to_id = env.create_database(&mut wtxn, Some("path_to_id"))?;
-        wtxn.commit()?;
-
-        // Determine the next FontID by scanning existing entries.
-        let rtxn = env.read_txn()?;
-        let mut max_id: u64 = 0;
-        for result in db_metadata.iter(&rtxn)? {
-            let (id, _) = result?;
-            if id > max_id {
-                max_id = id;
+        let (db_metadata, db_inverted, db_path_to_id, db_info) = if options.read_only {
+            // Handles opened in a read transaction only outlive it once it
+            // is committed.
+            let rtxn = env.read_txn()?;
+            fn open<K: 'static, V: 'static>(
+                env: &Env,
+                rtxn: &RoTxn,
+                index_dir: &Path,
+                name: &str,
+            ) -> Result<Database<K, V>> {
+                env.open_database(rtxn, Some(name))?.with_context(|| {
+                    format!(
+                        "index at {} has no {name} database; open it writable once (e.g. `typg cache add --index`)",
+                        index_dir.display()
+                    )
+                })
             }
-        }
-        drop(rtxn);
+            let databases = (
+                open(&env, &rtxn, index_dir, "metadata")?,
+                open(&env, &rtxn, index_dir, "inverted")?,
+                open(&env, &rtxn, index_dir, "path_to_id")?,
+                open(&env, &rtxn, index_dir, "info")?,
+            );
+            rtxn.commit()?;
+            databases
+        } else {
+            // Create or open the named databases.
+            let mut wtxn = env.write_txn()?;
+            let databases = (
+                env.create_database(&mut wtxn, Some("metadata"))?,
+                env.create_database(&mut wtxn, Some("inverted"))?,
+                env.create_database(&mut wtxn, Some("path_to_id"))?,
+                env.create_database(&mut wtxn, Some("info"))?,
+            );
+            wtxn.commit()?;
+            databases
+        };
 
         Ok(Self {
             env,
             db_metadata,
             db_inverted,
             db_path_to_id,
-            next_id: AtomicU64::new(max_id + 1),
+            db_info,
+            readers: ReaderSlots::new(options.max_readers),
+            bitmaps: Mutex::new(TagBitmapCache::default()),
+            options: options.clone(),
         })
     }
 
+    /// Open `index_dir` once per process and hand out the same instance on
+    /// every later call.
+    ///
+    /// Opening an environment costs a write transaction, and per-request
+    /// opens from concurrent threads collide on LMDB's one-environment-per-
+    /// path rule. Servers should call this instead of [`open`](Self::open):
+    /// the first call opens the index with `options`, later calls for the
+    /// same directory return the already-open index and ignore `options`.
+    /// Shared indexes stay open until the process exits.
+    pub fn shared(index_dir: &Path, options: &IndexOptions) -> Result<Arc<Self>> {
+        fs::create_dir_all(index_dir)
+            .with_context(|| format!("creating index directory {}", index_dir.display()))?;
+        let key = index_dir
+            .canonicalize()
+            .with_context(|| format!("resolving index directory {}", index_dir.display()))?;
+
+        let mut shared = SHARED
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(index) = shared.get(&key) {
+            return Ok(Arc::clone(index));
+        }
+        let index = Arc::new(Self::open_with(&key, options)?);
+        shared.insert(key, Arc::clone(&index));
+        Ok(index)
+    }
+
+    /// The options this index was opened with.
+    pub fn options(&self) -> &IndexOptions {
+        &self.options
+    }
+
     /// Return the number of font faces currently stored in the index.
     pub fn count(&self) -> Result<usize> {
+        let _slot = self.readers.acquire();
         let rtxn = self.env.read_txn()?;
         Ok(self.db_metadata.len(&rtxn)? as usize)
     }
 
+    /// Entry count and size of each named database, in the order listed
+    /// on [`FontIndex`].
+    pub fn database_stats(&self) -> Result<Vec<DatabaseStats>> {
+        let _slot = self.readers.acquire();
+        let rtxn = self.env.read_txn()?;
+        let stats = [
+            ("metadata", self.db_metadata.stat(&rtxn)?),
+            ("inverted", self.db_inverted.stat(&rtxn)?),
+            ("path_to_id", self.db_path_to_id.stat(&rtxn)?),
+            ("info", self.db_info.stat(&rtxn)?),
+        ];
+        Ok(stats
+            .into_iter()
+            .map(|(name, stat)| DatabaseStats {
+                name,
+                entries: stat.entries,
+                size_bytes: stat.page_size as u64
+                    * (stat.branch_pages + stat.leaf_pages + stat.overflow_pages) as u64,
+            })
+            .collect())
+    }
+
+    /// Read every record of every database, decoding the font metadata and
+    /// tag bitmaps on the way, and return the bytes read.
+    ///
+    /// Faults the whole memory map into the page cache, so the first
+    /// queries after startup run as fast as later ones, and fails on the
+    /// first record that does not decode.
+    pub fn warm(&self) -> Result<u64> {
+        let _slot = self.readers.acquire();
+        let rtxn = self.env.read_txn()?;
+        let mut bytes = 0u64;
+        for entry in self.db_metadata.iter(&rtxn)? {
+            let (id, value) = entry?;
+            deserialize_meta(value).with_context(|| format!("font {id} in metadata"))?;
+            bytes += (std::mem::size_of::<u64>() + value.len()) as u64;
+        }
+        for entry in self.db_inverted.iter(&rtxn)? {
+            let (key, value) = entry?;
+            RoaringBitmap::deserialize_from(value)
+                .with_context(|| format!("tag {} in inverted", String::from_utf8_lossy(key)))?;
+            bytes += (key.len() + value.len()) as u64;
+        }
+        for entry in self.db_path_to_id.iter(&rtxn)? {
+            let (_, value) = entry?;
+            bytes += (std::mem::size_of::<u64>() + value.len()) as u64;
+        }
+        for entry in self.db_info.iter(&rtxn)? {
+            let (key, value) = entry?;
+            bytes += (key.len() + value.len()) as u64;
+        }
+        Ok(bytes)
+    }
+
+    /// Check that the databases agree: every face is readable and reachable
+    /// from its file's `path_to_id` entry, every entry points at a face, and
+    /// every tag bitmap deserializes and names only stored faces. Read-only;
+    /// [`IndexWriter::repair`] fixes what this finds.
+    pub fn check(&self) -> Result<IndexCheck> {
+        let reader = self.reader()?;
+        Ok(find_inconsistencies(self, &reader.rtxn)?.check)
+    }
+
+    /// Rewrite the data file without its free pages, the way
+    /// `mdb_copy -c` does, and close the index.
+    ///
+    /// LMDB never shrinks its file: pages freed by removals and prunes are
+    /// reused for later writes but stay on disk. This copies the live
+    /// pages into `data.mdb.compact` next to the data file, closes the
+    /// environment and renames the copy over `data.mdb`. Writes another
+    /// process makes to the index meanwhile are lost, so run it while
+    /// nothing else has the index open.
+    pub fn compact(self) -> Result<CompactStats> {
+        let dir = self.env.path().to_path_buf();
+        let data = dir.join("data.mdb");
+        let staged = dir.join("data.mdb.compact");
+        let before_bytes = self.env.real_disk_size()?;
+
+        match fs::remove_file(&staged) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                return Err(err).with_context(|| format!("removing {}", staged.display()));
+            }
+            _ => {}
+        }
+        self.env
+            .copy_to_file(&staged, CompactionOption::Enabled)
+            .with_context(|| format!("copying the index to {}", staged.display()))?
+            .sync_all()?;
+        self.env.prepare_for_closing().wait();
+        fs::rename(&staged, &data)
+            .with_context(|| format!("replacing {} with its compacted copy", data.display()))?;
+
+        let after_bytes = fs::metadata(&data)?.len();
+        Ok(CompactStats {
+            before_bytes,
+            after_bytes,
+        })
+    }
+
     /// Open a write transaction and return an [`IndexWriter`] for adding or updating fonts.
+    ///
+    /// The first writer on an index built before paths were keyed per
+    /// platform runs [`IndexWriter::merge_duplicate_paths`], the first on
+    /// an index built before tag lists were stored marks the files of faces
+    /// without them stale, and the first on an index built before cmap
+    /// pages or name trigrams were filed files every face under them; all
+    /// are saved with the writer's commit.
     pub fn writer(&self) -> Result<IndexWriter<'_>> {
+        anyhow::ensure!(
+            !self.options.read_only,
+            "the index at {} is open read-only",
+            self.env.path().display()
+        );
         let wtxn = self.env.write_txn()?;
-        Ok(IndexWriter {
+        // Writers are serialized by LMDB, across processes too, so the
+        // counter read here stays current until this transaction ends.
+        let next_id = next_free_id(self, &wtxn)?;
+        let mut writer = IndexWriter {
             index: self,
             wtxn,
-            modified_tags: HashSet::new(),
-        })
+            bitmaps: HashMap::new(),
+            next_id,
+        };
+        if self.db_info.get(&writer.wtxn, PATH_KEYS_MARKER)?.is_none() {
+            // Where path keys are the paths themselves, nothing can collide.
+            if CASE_INSENSITIVE_PATHS {
+                writer.merge_duplicate_paths()?;
+            }
+            self.db_info.put(&mut writer.wtxn, PATH_KEYS_MARKER, &[1])?;
+        }
+        if self.db_info.get(&writer.wtxn, TAG_LISTS_MARKER)?.is_none() {
+            writer.mark_stale_faces(|meta| meta.tags.is_none())?;
+            self.db_info.put(&mut writer.wtxn, TAG_LISTS_MARKER, &[1])?;
+        }
+        if self.db_info.get(&writer.wtxn, DETAILS_MARKER)?.is_none() {
+            writer.mark_stale_faces(|meta| meta.details.is_none())?;
+            self.db_info.put(&mut writer.wtxn, DETAILS_MARKER, &[1])?;
+        }
+        if self.db_info.get(&writer.wtxn, CMAP_PAGES_MARKER)?.is_none() {
+            writer.file_stored_faces(|meta| {
+                let cmap = RoaringBitmap::deserialize_from(meta.cmap_bitmap.as_slice())
+                    .unwrap_or_default();
+                cmap_pages(cmap.iter())
+                    .into_iter()
+                    .map(cmap_page_key)
+                    .collect()
+            })?;
+            self.db_info
+                .put(&mut writer.wtxn, CMAP_PAGES_MARKER, &[1])?;
+        }
+        if self
+            .db_info
+            .get(&writer.wtxn, NAME_TRIGRAMS_MARKER)?
+            .is_none()
+        {
+            writer.file_stored_faces(|meta| name_trigram_keys(&meta.names))?;
+            self.db_info
+                .put(&mut writer.wtxn, NAME_TRIGRAMS_MARKER, &[1])?;
+        }
+        Ok(writer)
     }
 
     /// Open a read transaction and return an [`IndexReader`] for querying the index.
+    ///
+    /// Blocks while all [`IndexOptions::max_readers`] slots are taken.
     pub fn reader(&self) -> Result<IndexReader<'_>> {
+        let slot = self.readers.acquire();
+        // The snapshot is the last committed transaction, unless a commit
+        // lands while the transaction opens; such a reader skips the cache.
+        let before = self.env.info().last_txn_id;
         let rtxn = self.env.read_txn()?;
-        Ok(IndexReader { index: self, rtxn })
+        let txn_id = self.env.info().last_txn_id;
+        Ok(IndexReader {
+            index: self,
+            rtxn,
+            txn_id: (txn_id == before).then_some(txn_id),
+            _slot: slot,
+        })
+    }
+}
+
+/// The next font ID a writer may hand out: the stored counter, or one past
+/// the highest stored ID for indexes written before the counter was kept.
+fn next_free_id(index: &FontIndex, txn: &RoTxn) -> Result<u64> {
+    let stored = index
+        .db_info
+        .get(txn, NEXT_ID_KEY)?
+        .and_then(|bytes| <[u8; 8]>::try_from(bytes).ok())
+        .map(u64::from_le_bytes);
+    match stored {
+        Some(next_id) => Ok(next_id),
+        None => Ok(stored_ids(index, txn)?.last().map_or(1, |id| id + 1)),
+    }
+}
+
+/// Every stored font ID, in numeric order.
+///
+/// The `metadata` keys are compared as bytes, which on little-endian
+/// machines is not numeric order, so `last()` and key walks cannot stand in
+/// for this.
+fn stored_ids(index: &FontIndex, txn: &RoTxn) -> Result<Vec<u64>> {
+    let mut ids = Vec::new();
+    for result in index
+        .db_metadata
+        .remap_data_type::<DecodeIgnore>()
+        .iter(txn)?
+    {
+        ids.push(result?.0);
+    }
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+/// Counting semaphore over LMDB reader slots.
+struct ReaderSlots {
+    free: Mutex<u32>,
+    released: Condvar,
+}
+
+impl ReaderSlots {
+    fn new(slots: u32) -> Self {
+        Self {
+            free: Mutex::new(slots),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Wait for a free slot and hold it until the guard drops.
+    fn acquire(&self) -> ReaderSlot<'_> {
+        let mut free = self.free.lock().unwrap_or_else(|p| p.into_inner());
+        while *free == 0 {
+            free = self.released.wait(free).unwrap_or_else(|p| p.into_inner());
+        }
+        *free -= 1;
+        ReaderSlot { slots: self }
     }
+}
+
+/// One taken reader slot, given back on drop.
+struct ReaderSlot<'a> {
+    slots: &'a ReaderSlots,
+}
 
-    /// Allocate and return the next available font ID atomically.
-    fn alloc_id(&self) -> FontID {
-        self.next_id.fetch_add(1, Ordering::Relaxed)
+impl Drop for ReaderSlot<'_> {
+    fn drop(&mut self) {
+        *self.slots.free.lock().unwrap_or_else(|p| p.into_inner()) += 1;
+        self.slots.released.notify_one();
     }
 }
 
+/// Most bitmaps [`TagBitmapCache`] keeps before it stops adding more.
+const TAG_BITMAP_CACHE_ENTRIES: usize = 4096;
+
+/// `inverted` bitmaps decoded by earlier readers, valid for one committed
+/// transaction: a long-lived process such as the daemon answers repeat
+/// queries without decoding `latn` or `wght` again. A newer snapshot
+/// empties it; readers of an older one bypass it.
+#[derive(Default)]
+struct TagBitmapCache {
+    txn_id: usize,
+    bitmaps: HashMap<u32, RoaringBitmap>,
+}
+
 /// Writer handle for atomic index ingestion.
+///
+/// Inverted-index bitmaps touched by [`add_font`](Self::add_font) and
+/// friends stay decoded in memory and are written back once, at
+/// [`commit`](Self::commit), instead of being re-read and re-serialized for
+/// every face: a bulk add of 50k Latin fonts decodes the `latn` bitmap
+/// once, not 50k times.
 pub struct IndexWriter<'a> {
     index: &'a FontIndex,
     wtxn: RwTxn<'a>,
-    modified_tags: HashSet<u32>,
+    /// Bitmaps changed in this transaction, by tag, not yet written back.
+    bitmaps: HashMap<u32, RoaringBitmap>,
+    /// The next font ID to hand out, saved as [`NEXT_ID_KEY`] at commit.
+    next_id: u64,
 }
 
 impl<'a> IndexWriter<'a> {
@@ -168,15 +927,42 @@ impl<'a> IndexWriter<'a> {
             .map(|d| d.as_secs())
             .unwrap_or(0);
 
-        if let Some(bytes) = self.index.db_path_to_id.get(&self.wtxn, &path_hash)? {
-            if bytes.len() == std::mem::size_of::<PathEntry>() {
-                let entry: PathEntry = *bytemuck::from_bytes(bytes);
-                return Ok(entry.mtime_secs != mtime_secs);
-            }
+        if let Some(entry) = self.path_entry(path_hash)? {
+            return Ok(entry.mtime_secs != mtime_secs);
         }
         Ok(true) // Not found, needs indexing
     }
 
+    /// Like [`needs_update`](Self::needs_update), but also re-index when the
+    /// file's content hash ([`TypgFontFaceMeta::file_hash`]) differs from
+    /// the stored one — which catches files modified in place on
+    /// filesystems with unreliable mtimes. Entries indexed without a hash
+    /// always need an update, so the next run records one.
+    ///
+    /// [`TypgFontFaceMeta::file_hash`]: crate::search::TypgFontFaceMeta::file_hash
+    pub fn needs_update_hashed(
+        &self,
+        path: &Path,
+        mtime: SystemTime,
+        file_hash: u64,
+    ) -> Result<bool> {
+        if self.needs_update(path, mtime)? {
+            return Ok(true);
+        }
+        Ok(self
+            .path_entry(hash_path(path))?
+            .is_none_or(|entry| entry.file_hash != file_hash))
+    }
+
+    /// The stored path-to-ID entry for a path hash.
+    fn path_entry(&self, path_hash: u64) -> Result<Option<PathEntry>> {
+        Ok(self
+            .index
+            .db_path_to_id
+            .get(&self.wtxn, &path_hash)?
+            .and_then(PathEntry::decode))
+    }
+
     /// Add a font face to the index.
     #[allow(clippy::too_many_arguments)]
     pub fn add_font(
@@ -196,172 +982,930 @@ impl<'a> IndexWriter<'a> {
         family_class: Option<(u8, u8)>,
     ) -> Result<FontID> {
         let path_hash = hash_path(path);
-        let mtime_secs = mtime
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
 
         // Check for existing entry and remove it first.
-        if let Some(bytes) = self.index.db_path_to_id.get(&self.wtxn, &path_hash)? {
-            if bytes.len() == std::mem::size_of::<PathEntry>() {
-                let entry: PathEntry = *bytemuck::from_bytes(bytes);
-                self.remove_font_by_id(entry.font_id)?;
-            }
+        if let Some(entry) = self.path_entry(path_hash)? {
+            self.remove_font_by_id(entry.font_id)?;
         }
 
-        // Allocate new ID.
-        let font_id = self.index.alloc_id();
-
-        // Build Roaring Bitmap for cmap coverage.
-        let cmap_bitmap = build_cmap_bitmap(codepoints);
-
-        // Serialize metadata with bincode.
-        let meta = IndexedFontMeta {
-            path: path.display().to_string(),
+        let tags = IndexedTags {
+            axis: tag_bytes(axis_tags),
+            feature: tag_bytes(feature_tags),
+            script: tag_bytes(script_tags),
+            table: tag_bytes(table_tags),
+            ..IndexedTags::default()
+        };
+        let font_id = self.insert_face(
+            path,
             ttc_index,
             names,
+            tags,
+            codepoints,
             is_variable,
             weight_class,
             width_class,
             family_class,
-            cmap_bitmap,
-        };
+            None,
+        )?;
+        self.put_path_entry(path, font_id, mtime, None)?;
 
-        let meta_bytes =
-            bincode::serialize(&meta).map_err(|e| anyhow::anyhow!("bincode serialize: {e}"))?;
-        self.index
-            .db_metadata
-            .put(&mut self.wtxn, &font_id, &meta_bytes)?;
+        Ok(FontID(font_id))
+    }
 
-        // Update path-to-ID mapping.
-        let path_entry = PathEntry {
-            font_id,
-            mtime_secs,
-        };
-        self.index.db_path_to_id.put(
-            &mut self.wtxn,
-            &path_hash,
-            bytemuck::bytes_of(&path_entry),
-        )?;
+    /// Index every face of one font file, replacing whatever the index held
+    /// for that path.
+    ///
+    /// Unlike calling [`add_font`](Self::add_font) once per face, this keeps
+    /// all faces of a TTC/OTC collection. `faces` may be empty (the file no
+    /// longer parses), which just drops the old entries. Returns the number
+    /// of faces indexed.
+    pub fn replace_file(
+        &mut self,
+        path: &Path,
+        mtime: SystemTime,
+        faces: &[TypgFontFaceMatch],
+    ) -> Result<usize> {
+        self.remove_file(path)?;
 
-        // Update inverted indices for all tags.
-        for tag in axis_tags
-            .iter()
-            .chain(feature_tags)
-            .chain(script_tags)
-            .chain(table_tags)
-        {
-            self.add_to_inverted_index(tag_to_u32(*tag), font_id)?;
+        let mut first_id = None;
+        for face in faces {
+            let meta = &face.metadata;
+            let font_id = self.insert_face(
+                path,
+                face.source.ttc_index,
+                meta.names.iter().map(|name| name.to_string()).collect(),
+                IndexedTags::of(meta),
+                &meta.codepoints,
+                meta.is_variable,
+                meta.weight_class,
+                meta.width_class,
+                meta.family_class,
+                Some(face_details(meta)?),
+            )?;
+            first_id.get_or_insert(font_id);
         }
-
-        // Add special markers for variable fonts.
-        if is_variable {
-            self.add_to_inverted_index(tag_marker(b"_VAR"), font_id)?;
+        if let Some(font_id) = first_id {
+            let file_hash = faces.first().and_then(|face| face.metadata.file_hash);
+            self.put_path_entry(path, font_id, mtime, file_hash)?;
         }
 
-        Ok(font_id)
-    }
-
-    /// Remove a font by its ID from all indices.
-    fn remove_font_by_id(&mut self, font_id: FontID) -> Result<()> {
-        self.index.db_metadata.delete(&mut self.wtxn, &font_id)?;
-        Ok(())
+        Ok(faces.len())
     }
 
-    /// Remove entries whose source files no longer exist on disk.
-    /// Returns (before_count, after_count).
-    pub fn prune_missing(&mut self) -> Result<(usize, usize)> {
-        // Collect IDs of entries with missing paths.
-        let mut to_remove = Vec::new();
-        let before = self.index.db_metadata.len(&self.wtxn)? as usize;
+    /// Store scanned faces one file at a time with
+    /// [`replace_file`](Self::replace_file), skipping files the index
+    /// already holds unchanged. `faces` must list each file's faces
+    /// together, as [`search`](crate::search::search) returns them. A
+    /// file's mtime comes from disk, or from its faces' `modified` field
+    /// when `recorded_mtimes` is set (faces imported from a JSON cache).
+    /// Returns the faces added and skipped.
+    pub fn add_scanned(
+        &mut self,
+        faces: &[TypgFontFaceMatch],
+        recorded_mtimes: bool,
+    ) -> Result<(usize, usize)> {
+        let mut added = 0usize;
+        let mut skipped = 0usize;
+        for faces in faces.chunk_by(|a, b| a.source.path == b.source.path) {
+            let path = &faces[0].source.path;
+            let mtime = if recorded_mtimes {
+                let secs = faces[0].metadata.modified.unwrap_or(0);
+                SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+            } else {
+                path.metadata()
+                    .and_then(|m| m.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH)
+            };
 
-        for result in self.index.db_metadata.iter(&self.wtxn)? {
-            let (font_id, bytes) = result?;
-            let meta = deserialize_meta(bytes)?;
-            let path = Path::new(&meta.path);
-            if !path.exists() {
-                to_remove.push(font_id);
+            // Check if update is needed; the content hash catches in-place
+            // edits that left the mtime alone.
+            let stale = match faces[0].metadata.file_hash {
+                Some(hash) => self.needs_update_hashed(path, mtime, hash)?,
+                None => self.needs_update(path, mtime)?,
+            };
+            if !stale {
+                skipped += faces.len();
+                continue;
             }
-        }
 
-        // Remove missing entries.
-        for font_id in &to_remove {
-            self.index.db_metadata.delete(&mut self.wtxn, font_id)?;
+            added += self.replace_file(path, mtime, faces)?;
         }
+        Ok((added, skipped))
+    }
 
-        // Also remove path-to-id mappings for missing files.
-        // We need to scan the path_to_id database to clean up stale entries.
-        let mut stale_hashes = Vec::new();
-        for result in self.index.db_path_to_id.iter(&self.wtxn)? {
-            let (hash, bytes) = result?;
-            if bytes.len() == std::mem::size_of::<PathEntry>() {
-                let entry: PathEntry = *bytemuck::from_bytes(bytes);
-                if to_remove.contains(&entry.font_id) {
-                    stale_hashes.push(hash);
-                }
+    /// Store font files as they arrive, each item holding one file's faces,
+    /// the way [`add_scanned`](Self::add_scanned) does: typically fed from
+    /// a channel by [`search_each_file`](crate::search::search_each_file)
+    /// while the scan is still running.
+    ///
+    /// With `commit_every`, the transaction is committed after every that
+    /// many files written (unchanged ones do not count) and a new one
+    /// begun, so memory held by the write transaction and its buffered
+    /// bitmaps stays flat however many files arrive. A failure then keeps
+    /// the batches already committed. Returns the writer holding the last
+    /// batch, for the caller to commit.
+    pub fn ingest(
+        mut self,
+        files: impl IntoIterator<Item = Vec<TypgFontFaceMatch>>,
+        recorded_mtimes: bool,
+        commit_every: Option<NonZeroUsize>,
+    ) -> Result<(Self, IngestStats)> {
+        let mut stats = IngestStats::default();
+        let mut pending = 0;
+        for faces in files {
+            let (added, skipped) = self.add_scanned(&faces, recorded_mtimes)?;
+            stats.added += added;
+            stats.skipped += skipped;
+            if added > 0 {
+                pending += 1;
+            }
+            if commit_every.is_some_and(|n| pending >= n.get()) {
+                self = self.checkpoint()?;
+                stats.commits += 1;
+                pending = 0;
             }
         }
+        Ok((self, stats))
+    }
 
-        for hash in stale_hashes {
-            self.index.db_path_to_id.delete(&mut self.wtxn, &hash)?;
-        }
+    /// Commit the changes so far and continue in a new transaction.
+    pub fn checkpoint(self) -> Result<IndexWriter<'a>> {
+        let index = self.index;
+        self.commit()?;
+        index.writer()
+    }
 
-        let after = self.index.db_metadata.len(&self.wtxn)? as usize;
-        Ok((before, after))
+    /// Remove every face stored for `path`, or for any file under it when
+    /// `path` is a directory. Returns the number of faces removed.
+    ///
+    /// Works from the stored paths alone, so it also cleans up after files
+    /// and folders that are already gone from disk.
+    pub fn remove_path(&mut self, path: &Path) -> Result<usize> {
+        self.remove_where(|meta| Path::new(&meta.path).starts_with(path))
     }
 
-    /// Add a font ID to an inverted index bitmap.
-    fn add_to_inverted_index(&mut self, tag: u32, font_id: FontID) -> Result<()> {
-        let tag_bytes = tag.to_ne_bytes();
-        let mut bitmap = if let Some(bytes) = self.index.db_inverted.get(&self.wtxn, &tag_bytes)? {
-            RoaringBitmap::deserialize_from(bytes)?
-        } else {
-            RoaringBitmap::new()
+    /// Remove the faces stored for the font file at `path` and its
+    /// path-to-ID entry. Returns the number of faces removed.
+    ///
+    /// Unlike [`remove_path`](Self::remove_path) this visits only the
+    /// file's own faces, found through its entry the way
+    /// [`IndexReader::get_by_path`] finds them, so re-indexing a file costs
+    /// the same however many faces the index holds.
+    fn remove_file(&mut self, path: &Path) -> Result<usize> {
+        let path_hash = hash_path(path);
+        let Some(entry) = self.path_entry(path_hash)? else {
+            return Ok(0);
         };
 
-        bitmap.insert(font_id as u32);
-        self.modified_tags.insert(tag);
-
-        let mut buf = Vec::new();
-        bitmap.serialize_into(&mut buf)?;
-        self.index
-            .db_inverted
-            .put(&mut self.wtxn, &tag_bytes, &buf)?;
+        let key = path_key(path);
+        let mut owned = Vec::new();
+        for font_id in entry.font_id.. {
+            match self.index.db_metadata.get(&self.wtxn, &font_id)? {
+                Some(bytes) if path_key(Path::new(&deserialize_meta(bytes)?.path)) == key => {
+                    owned.push(font_id);
+                }
+                _ => break,
+            }
+        }
+        self.delete_faces(&owned)?;
+        self.index
+            .db_path_to_id
+            .delete(&mut self.wtxn, &path_hash)?;
+
+        Ok(owned.len())
+    }
+
+    /// Remove the faces stored under `ids`, e.g. the results of an
+    /// [`IndexReader::find`]. IDs the index does not hold are ignored.
+    /// Returns the number of faces removed.
+    ///
+    /// A file that loses only some of its faces (part of a collection)
+    /// keeps the others, moved to fresh consecutive IDs with their tags so
+    /// that [`IndexReader::get_by_path`] still finds them all. Its
+    /// path-to-ID entry keeps the recorded mtime and hash, so the next
+    /// `cache add --index` does not bring the removed faces back unless the
+    /// file changes; a file with no faces left loses its entry.
+    pub fn remove_faces(&mut self, ids: &[FontID]) -> Result<usize> {
+        let doomed: HashSet<u64> = ids.iter().map(|id| id.0).collect();
+        let mut stored = Vec::new();
+        let mut touched = HashSet::new();
+        for &font_id in &doomed {
+            if let Some(bytes) = self.index.db_metadata.get(&self.wtxn, &font_id)? {
+                touched.insert(deserialize_meta(bytes)?.path);
+                stored.push(font_id);
+            }
+        }
+        let removed = stored.len();
+        if touched.is_empty() {
+            return Ok(0);
+        }
+
+        // Surviving faces of the touched files, in collection order.
+        let mut survivors: HashMap<String, Vec<(Option<u32>, u64)>> = HashMap::new();
+        for result in self.index.db_metadata.iter(&self.wtxn)? {
+            let (font_id, bytes) = result?;
+            if doomed.contains(&font_id) {
+                continue;
+            }
+            let meta = deserialize_meta(bytes)?;
+            if touched.contains(&meta.path) {
+                survivors
+                    .entry(meta.path)
+                    .or_default()
+                    .push((meta.ttc_index, font_id));
+            }
+        }
+        self.delete_faces(&stored)?;
+        for path in touched.iter().filter(|path| !survivors.contains_key(*path)) {
+            self.index
+                .db_path_to_id
+                .delete(&mut self.wtxn, &hash_path(Path::new(path)))?;
+        }
+        if survivors.is_empty() {
+            return Ok(removed);
+        }
+
+        let moving: Vec<u64> = survivors
+            .values()
+            .flatten()
+            .map(|&(_, font_id)| font_id)
+            .collect();
+        let keys = self.filed_keys(&moving)?;
+
+        for (path, mut faces) in survivors {
+            faces.sort();
+            let path_hash = hash_path(Path::new(&path));
+            let mut first_id = None;
+            for (_, old_id) in faces {
+                let bytes = self
+                    .index
+                    .db_metadata
+                    .get(&self.wtxn, &old_id)?
+                    .map(<[u8]>::to_vec)
+                    .unwrap_or_default();
+                self.index.db_metadata.delete(&mut self.wtxn, &old_id)?;
+                let font_id = self.alloc_id();
+                self.index
+                    .db_metadata
+                    .put(&mut self.wtxn, &font_id, &bytes)?;
+                for &key in keys.get(&old_id).into_iter().flatten() {
+                    self.remove_from_inverted_index(key, old_id)?;
+                    self.add_to_inverted_index(key, font_id)?;
+                }
+                first_id.get_or_insert(font_id);
+            }
+            let mut entry = self
+                .path_entry(path_hash)?
+                .unwrap_or_else(PathEntry::zeroed);
+            entry.font_id = first_id.unwrap_or_default();
+            self.index
+                .db_path_to_id
+                .put(&mut self.wtxn, &path_hash, bytemuck::bytes_of(&entry))?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Store one face and register it in the inverted indices.
+    #[allow(clippy::too_many_arguments)]
+    fn insert_face(
+        &mut self,
+        path: &Path,
+        ttc_index: Option<u32>,
+        names: Vec<String>,
+        tags: IndexedTags,
+        codepoints: &[char],
+        is_variable: bool,
+        weight_class: Option<u16>,
+        width_class: Option<u16>,
+        family_class: Option<(u8, u8)>,
+        details: Option<String>,
+    ) -> Result<u64> {
+        // Allocate new ID.
+        let font_id = self.alloc_id();
+
+        // Build Roaring Bitmap for cmap coverage.
+        let cmap_bitmap = build_cmap_bitmap(codepoints);
+
+        // Update inverted indices for all tags.
+        for tag in tags.searchable() {
+            self.add_to_inverted_index(u32::from_be_bytes(*tag), font_id)?;
+        }
+
+        // File the face under the cmap pages it maps, for codepoint queries,
+        // and under the trigrams of its names, for name queries.
+        for page in cmap_pages(codepoints.iter().map(|&cp| cp as u32)) {
+            self.add_to_inverted_index(cmap_page_key(page), font_id)?;
+        }
+        for key in name_trigram_keys(&names) {
+            self.add_to_inverted_index(key, font_id)?;
+        }
+
+        // Serialize metadata with bincode.
+        let meta = IndexedFontMeta {
+            path: path.display().to_string(),
+            ttc_index,
+            names,
+            is_variable,
+            weight_class,
+            width_class,
+            family_class,
+            cmap_bitmap,
+            tags: Some(tags),
+            details,
+        };
+
+        let meta_bytes =
+            bincode::serialize(&meta).map_err(|e| anyhow::anyhow!("bincode serialize: {e}"))?;
+        self.index
+            .db_metadata
+            .put(&mut self.wtxn, &font_id, &meta_bytes)?;
+
+        // Add special markers for variable fonts.
+        if is_variable {
+            self.add_to_inverted_index(tag_marker(b"_VAR"), font_id)?;
+        }
+
+        Ok(font_id)
+    }
+
+    /// Record the path-to-ID mapping used for incremental updates.
+    fn put_path_entry(
+        &mut self,
+        path: &Path,
+        font_id: u64,
+        mtime: SystemTime,
+        file_hash: Option<u64>,
+    ) -> Result<()> {
+        let mtime_secs = mtime
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path_entry = PathEntry {
+            font_id,
+            mtime_secs,
+            file_hash: file_hash.unwrap_or(0),
+        };
+        self.index.db_path_to_id.put(
+            &mut self.wtxn,
+            &hash_path(path),
+            bytemuck::bytes_of(&path_entry),
+        )?;
+        Ok(())
+    }
+
+    /// Remove a font by its ID from all indices.
+    fn remove_font_by_id(&mut self, font_id: u64) -> Result<()> {
+        self.delete_faces(&[font_id])
+    }
+
+    /// Delete the stored faces `ids` and take them out of every bitmap they
+    /// are filed under, so an ID handed out again starts with no tags.
+    fn delete_faces(&mut self, ids: &[u64]) -> Result<()> {
+        let keys = self.filed_keys(ids)?;
+        for font_id in ids {
+            self.index.db_metadata.delete(&mut self.wtxn, font_id)?;
+        }
+        for (font_id, keys) in keys {
+            for key in keys {
+                self.remove_from_inverted_index(key, font_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove entries whose source files no longer exist on disk.
+    /// Returns (before_count, after_count).
+    pub fn prune_missing(&mut self) -> Result<(usize, usize)> {
+        let before = self.index.db_metadata.len(&self.wtxn)? as usize;
+        self.remove_where(|meta| !Path::new(&meta.path).exists())?;
+        let after = self.index.db_metadata.len(&self.wtxn)? as usize;
+        Ok((before, after))
+    }
+
+    /// Remove the faces of files whose bytes no longer hash to the stored
+    /// file hash — modified in place, or unreadable. Files indexed without
+    /// a hash are kept. Returns the number of faces removed.
+    pub fn prune_changed(&mut self) -> Result<usize> {
+        let mut changed = HashSet::new();
+        let mut checked = HashSet::new();
+        for result in self.index.db_metadata.iter(&self.wtxn)? {
+            let (_, bytes) = result?;
+            let path = deserialize_meta(bytes)?.path;
+            if !checked.insert(path.clone()) {
+                continue;
+            }
+            let stored = self
+                .path_entry(hash_path(Path::new(&path)))?
+                .map_or(0, |entry| entry.file_hash);
+            if stored != 0 && hash_file(Path::new(&path)).ok() != Some(stored) {
+                changed.insert(path);
+            }
+        }
+        self.remove_where(|meta| changed.contains(&meta.path))
+    }
+
+    /// Like [`prune_missing`](Self::prune_missing), but only for files
+    /// stored under `dir`. Returns the number of faces removed.
+    pub fn prune_missing_under(&mut self, dir: &Path) -> Result<usize> {
+        self.remove_where(|meta| {
+            let path = Path::new(&meta.path);
+            path.starts_with(dir) && !path.exists()
+        })
+    }
+
+    /// Remove every face, tag bitmap and path entry, leaving an empty index
+    /// that keeps its layout markers and ID counter. Returns the number of
+    /// faces removed.
+    pub fn clear(&mut self) -> Result<usize> {
+        let faces = self.index.db_metadata.len(&self.wtxn)? as usize;
+        self.index.db_metadata.clear(&mut self.wtxn)?;
+        self.index.db_inverted.clear(&mut self.wtxn)?;
+        self.index.db_path_to_id.clear(&mut self.wtxn)?;
+        self.bitmaps.clear();
+        Ok(faces)
+    }
+
+    /// Fix what [`FontIndex::check`] finds, returning the findings from
+    /// before the repair.
+    ///
+    /// Unreadable faces, orphaned path entries and dangling bitmap members
+    /// are dropped, and empty bitmaps with them. Unmapped faces get a path
+    /// entry with no mtime or hash, so the next `cache add --index` re-reads
+    /// their files. A corrupt bitmap is dropped and every path entry is
+    /// marked stale the same way, so re-adding the indexed folders restores
+    /// the tag.
+    pub fn repair(&mut self) -> Result<IndexCheck> {
+        self.flush_bitmaps()?;
+        let findings = find_inconsistencies(self.index, &self.wtxn)?;
+
+        for id in &findings.check.unreadable_faces {
+            self.index.db_metadata.delete(&mut self.wtxn, &id.0)?;
+        }
+        for path_hash in &findings.orphan_paths {
+            self.index.db_path_to_id.delete(&mut self.wtxn, path_hash)?;
+        }
+        for (path, font_id) in &findings.unmapped_paths {
+            self.put_path_entry(Path::new(path), *font_id, SystemTime::UNIX_EPOCH, None)?;
+        }
+        for (key, bitmap) in &findings.bitmaps {
+            match bitmap {
+                Some(bitmap) if !bitmap.is_empty() => {
+                    let mut buf = Vec::new();
+                    bitmap.serialize_into(&mut buf)?;
+                    self.index.db_inverted.put(&mut self.wtxn, key, &buf)?;
+                }
+                _ => {
+                    self.index.db_inverted.delete(&mut self.wtxn, key)?;
+                }
+            }
+        }
+        if !findings.check.corrupt_bitmaps.is_empty() {
+            self.mark_stale(|_| true)?;
+        }
+
+        Ok(findings.check)
+    }
+
+    /// Mark the files of faces `pick` selects stale, so the next
+    /// `cache add --index` re-reads them and stores what older versions
+    /// left out. Returns the number of files marked.
+    fn mark_stale_faces(&mut self, pick: impl Fn(&IndexedFontMeta) -> bool) -> Result<usize> {
+        let mut picked = HashSet::new();
+        for result in self.index.db_metadata.iter(&self.wtxn)? {
+            let (_, bytes) = result?;
+            if let Ok(meta) = deserialize_meta(bytes) {
+                if pick(&meta) {
+                    picked.insert(hash_path(Path::new(&meta.path)));
+                }
+            }
+        }
+        if picked.is_empty() {
+            return Ok(0);
+        }
+        self.mark_stale(|path_hash| picked.contains(&path_hash))
+    }
+
+    /// File every stored face under the `inverted` keys `keys` derives
+    /// from its stored metadata, for indexes written before those keys
+    /// were filed. Returns the number of faces filed.
+    fn file_stored_faces(&mut self, keys: impl Fn(&IndexedFontMeta) -> Vec<u32>) -> Result<usize> {
+        let mut faces = Vec::new();
+        for result in self.index.db_metadata.iter(&self.wtxn)? {
+            let (font_id, bytes) = result?;
+            if let Ok(meta) = deserialize_meta(bytes) {
+                faces.push((font_id, keys(&meta)));
+            }
+        }
+        for (font_id, keys) in &faces {
+            for &key in keys {
+                self.add_to_inverted_index(key, *font_id)?;
+            }
+        }
+        Ok(faces.len())
+    }
+
+    /// Clear the mtime and hash of every path entry `pick` selects, so the
+    /// next `cache add --index` re-reads the file. Returns the number of
+    /// entries marked.
+    fn mark_stale(&mut self, pick: impl Fn(u64) -> bool) -> Result<usize> {
+        let mut stale = Vec::new();
+        for result in self.index.db_path_to_id.iter(&self.wtxn)? {
+            let (path_hash, bytes) = result?;
+            if let Some(entry) = PathEntry::decode(bytes).filter(|_| pick(path_hash)) {
+                stale.push((path_hash, entry.font_id));
+            }
+        }
+        for &(path_hash, font_id) in &stale {
+            let entry = PathEntry {
+                font_id,
+                mtime_secs: 0,
+                file_hash: 0,
+            };
+            self.index
+                .db_path_to_id
+                .put(&mut self.wtxn, &path_hash, bytemuck::bytes_of(&entry))?;
+        }
+        Ok(stale.len())
+    }
+
+    /// Delete every face whose stored metadata satisfies `doomed`, along with
+    /// the path-to-ID mappings of their files. Returns how many were removed.
+    fn remove_where(&mut self, doomed: impl Fn(&IndexedFontMeta) -> bool) -> Result<usize> {
+        let mut to_remove = Vec::new();
+        let mut stale_paths = HashSet::new();
+        for result in self.index.db_metadata.iter(&self.wtxn)? {
+            let (font_id, bytes) = result?;
+            let meta = deserialize_meta(bytes)?;
+            if doomed(&meta) {
+                to_remove.push(font_id);
+                stale_paths.insert(hash_path(Path::new(&meta.path)));
+            }
+        }
+
+        self.delete_faces(&to_remove)?;
+        for hash in stale_paths {
+            self.index.db_path_to_id.delete(&mut self.wtxn, &hash)?;
+        }
+
+        Ok(to_remove.len())
+    }
+
+    /// Merge faces stored under different spellings of one file's path.
+    ///
+    /// Indexes written before paths were compared per platform (see
+    /// [`path_key`]) can hold `C:\Fonts\A.ttf` and `c:\fonts\a.ttf` as two
+    /// files. For each file this keeps the most recently indexed spelling,
+    /// drops the faces stored under the others, and re-keys its
+    /// path-to-ID entry. Returns the number of faces removed.
+    pub fn merge_duplicate_paths(&mut self) -> Result<usize> {
+        // Newest spelling per path key; IDs only grow, so the highest ID
+        // is the most recent write.
+        let mut newest: HashMap<String, (u64, String)> = HashMap::new();
+        let mut faces = Vec::new();
+        for result in self.index.db_metadata.iter(&self.wtxn)? {
+            let (font_id, bytes) = result?;
+            let path = deserialize_meta(bytes)?.path;
+            let key = path_key(Path::new(&path));
+            let slot = newest.entry(key.clone()).or_insert((font_id, path.clone()));
+            if font_id > slot.0 {
+                *slot = (font_id, path.clone());
+            }
+            faces.push((font_id, key, path));
+        }
+
+        let mut removed = Vec::new();
+        let mut spellings = HashSet::new();
+        for (font_id, key, path) in faces {
+            if newest[&key].1 != path {
+                removed.push(font_id);
+            }
+            spellings.insert(path);
+        }
+        self.delete_faces(&removed)?;
+
+        // Move path-to-ID entries from the spelled-path hash to the key hash.
+        for path in spellings {
+            let key = path_key(Path::new(&path));
+            // Older indexes hashed the path as spelled.
+            let legacy = hash_key(&path);
+            if legacy == hash_key(&key) {
+                continue;
+            }
+            let entry = self
+                .index
+                .db_path_to_id
+                .get(&self.wtxn, &legacy)?
+                .map(<[u8]>::to_vec);
+            self.index.db_path_to_id.delete(&mut self.wtxn, &legacy)?;
+            if let (Some(entry), true) = (entry, newest[&key].1 == path) {
+                self.index
+                    .db_path_to_id
+                    .put(&mut self.wtxn, &hash_key(&key), &entry)?;
+            }
+        }
+
+        Ok(removed.len())
+    }
+
+    /// The `inverted` keys each of the stored faces `ids` is filed under,
+    /// worked out from its stored metadata (see [`inverted_keys`]). Faces
+    /// stored before tag lists were kept are looked up in the bitmaps
+    /// instead, which reads the whole `inverted` database.
+    fn filed_keys(&mut self, ids: &[u64]) -> Result<HashMap<u64, Vec<u32>>> {
+        let mut keys = HashMap::new();
+        let mut untagged = RoaringBitmap::new();
+        for &font_id in ids {
+            let Some(bytes) = self.index.db_metadata.get(&self.wtxn, &font_id)? else {
+                continue;
+            };
+            match inverted_keys(&deserialize_meta(bytes)?) {
+                Some(filed) => {
+                    keys.insert(font_id, filed);
+                }
+                None => {
+                    untagged.insert(font_id as u32);
+                }
+            }
+        }
+        if untagged.is_empty() {
+            return Ok(keys);
+        }
+
+        self.flush_bitmaps()?;
+        for result in self.index.db_inverted.iter(&self.wtxn)? {
+            let (key, bytes) = result?;
+            let (Ok(key), Ok(bitmap)) = (
+                <[u8; 4]>::try_from(key),
+                RoaringBitmap::deserialize_from(bytes),
+            ) else {
+                continue;
+            };
+            for font_id in &bitmap & &untagged {
+                keys.entry(u64::from(font_id))
+                    .or_default()
+                    .push(u32::from_ne_bytes(key));
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Hand out the next font ID.
+    fn alloc_id(&mut self) -> u64 {
+        let font_id = self.next_id;
+        self.next_id += 1;
+        font_id
+    }
+
+    /// Add a font ID to an inverted index bitmap.
+    fn add_to_inverted_index(&mut self, tag: u32, font_id: u64) -> Result<()> {
+        self.bitmap_mut(tag)?.insert(font_id as u32);
+        Ok(())
+    }
+
+    /// Take a font ID out of an inverted index bitmap.
+    fn remove_from_inverted_index(&mut self, tag: u32, font_id: u64) -> Result<()> {
+        self.bitmap_mut(tag)?.remove(font_id as u32);
+        Ok(())
+    }
+
+    /// The bitmap for `tag`, loaded from the inverted index on first use
+    /// and kept until [`flush_bitmaps`](Self::flush_bitmaps).
+    fn bitmap_mut(&mut self, tag: u32) -> Result<&mut RoaringBitmap> {
+        Ok(match self.bitmaps.entry(tag) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let stored = self.index.db_inverted.get(&self.wtxn, &tag.to_ne_bytes())?;
+                entry.insert(match stored {
+                    Some(bytes) => RoaringBitmap::deserialize_from(bytes)?,
+                    None => RoaringBitmap::new(),
+                })
+            }
+        })
+    }
+
+    /// Write the bitmaps changed so far back to the inverted index,
+    /// dropping those left empty.
+    fn flush_bitmaps(&mut self) -> Result<()> {
+        let mut buf = Vec::new();
+        for (tag, bitmap) in self.bitmaps.drain() {
+            if bitmap.is_empty() {
+                self.index
+                    .db_inverted
+                    .delete(&mut self.wtxn, &tag.to_ne_bytes())?;
+                continue;
+            }
+            buf.clear();
+            bitmap.serialize_into(&mut buf)?;
+            self.index
+                .db_inverted
+                .put(&mut self.wtxn, &tag.to_ne_bytes(), &buf)?;
+        }
+        Ok(())
+    }
+
+    /// Add the font files of a snapshot written by [`IndexReader::export`],
+    /// replacing whatever the index holds for the same paths, and record
+    /// the snapshot's roots. Saved with the writer's commit.
+    ///
+    /// Each `(from, to)` of `rebase` moves the paths under `from` to `to`,
+    /// the first pair that applies winning, for catalogs built on a machine
+    /// that keeps the fonts elsewhere. Files keep the mtime and hash they
+    /// were exported with, so `cache add --index` only re-reads the ones
+    /// that differ here; files with faces stored before tag lists or full
+    /// metadata were kept are marked stale instead.
+    pub fn import(
+        &mut self,
+        input: impl Read,
+        rebase: &[(PathBuf, PathBuf)],
+    ) -> Result<SnapshotStats> {
+        let mut input = input;
+        let mut head = [0u8; 12];
+        match input.read_exact(&mut head) {
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => {}
+            result => result.context("reading the snapshot header")?,
+        }
+        anyhow::ensure!(
+            head.starts_with(SNAPSHOT_MAGIC),
+            "not a typg index snapshot"
+        );
+        let version = u32::from_le_bytes([head[8], head[9], head[10], head[11]]);
+        anyhow::ensure!(
+            version <= SNAPSHOT_VERSION,
+            "snapshot version {version} is newer than this typg reads ({SNAPSHOT_VERSION})"
+        );
+
+        let mut input = BufReader::new(GzDecoder::new(input));
+        let header: SnapshotHeader =
+            bincode::deserialize_from(&mut input).context("reading the snapshot header")?;
+        let mut roots: Vec<ScanRoot> =
+            serde_json::from_str(&header.roots).context("reading the snapshot roots")?;
+
+        let mut stats = SnapshotStats::default();
+        while let Some(file) = read_snapshot_file(&mut input, version)
+            .context("reading the snapshot; is it truncated?")?
+        {
+            let path = rebased(Path::new(&file.path), rebase);
+            self.remove_file(&path)?;
+
+            let mut first_id = None;
+            let mut partial = false;
+            for meta in file.faces {
+                partial |= meta.tags.is_none() || meta.details.is_none();
+                let font_id = self.insert_face(
+                    &path,
+                    meta.ttc_index,
+                    meta.names,
+                    meta.tags.unwrap_or_default(),
+                    &decode_cmap_bitmap(&meta.cmap_bitmap),
+                    meta.is_variable,
+                    meta.weight_class,
+                    meta.width_class,
+                    meta.family_class,
+                    meta.details,
+                )?;
+                first_id.get_or_insert(font_id);
+                stats.faces += 1;
+            }
+            if let Some(font_id) = first_id {
+                let (mtime_secs, file_hash) = if partial {
+                    (0, 0)
+                } else {
+                    (file.mtime_secs, file.file_hash)
+                };
+                let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(mtime_secs);
+                let file_hash = Some(file_hash).filter(|&hash| hash != 0);
+                self.put_path_entry(&path, font_id, mtime, file_hash)?;
+                stats.files += 1;
+            }
+        }
+
+        for root in &mut roots {
+            root.path = rebased(&root.path, rebase);
+        }
+        self.record_roots(&roots)?;
+        Ok(stats)
+    }
 
+    /// Remember `added` among the roots the index was built from (see
+    /// [`IndexReader::roots`]); a root recorded again takes its new
+    /// settings. Saved with the writer's commit.
+    pub fn record_roots(&mut self, added: &[ScanRoot]) -> Result<()> {
+        let stored = read_roots(self.index, &self.wtxn)?;
+        let roots = record_roots(stored, added.iter().cloned());
+        self.index
+            .db_info
+            .put(&mut self.wtxn, ROOTS_KEY, &serde_json::to_vec(&roots)?)?;
         Ok(())
     }
 
-    /// Commit the transaction.
-    pub fn commit(self) -> Result<()> {
+    /// Write pending bitmaps and commit the transaction.
+    pub fn commit(mut self) -> Result<()> {
+        self.flush_bitmaps()?;
+        self.index
+            .db_info
+            .put(&mut self.wtxn, NEXT_ID_KEY, &self.next_id.to_le_bytes())?;
         self.wtxn.commit()?;
         Ok(())
     }
 
-    /// Abort the transaction without committing.
+    /// Abort the transaction without committing. Pending bitmaps are
+    /// discarded with it.
     pub fn abort(self) {
         self.wtxn.abort();
     }
 }
 
+fn read_roots(index: &FontIndex, txn: &RoTxn) -> Result<Vec<ScanRoot>> {
+    match index.db_info.get(txn, ROOTS_KEY)? {
+        Some(bytes) => serde_json::from_slice(bytes).context("reading the index roots"),
+        None => Ok(Vec::new()),
+    }
+}
+
 /// Reader handle for index queries.
 pub struct IndexReader<'a> {
     index: &'a FontIndex,
     rtxn: RoTxn<'a>,
+    /// The committed transaction `rtxn` reads, when known for sure.
+    txn_id: Option<usize>,
+    // Declared after `rtxn` so the transaction ends before the slot frees.
+    _slot: ReaderSlot<'a>,
 }
 
 impl<'a> IndexReader<'a> {
+    /// The folders and files `cache add --index` scanned into this index,
+    /// with the options it walked them with; empty for an index built
+    /// before roots were recorded.
+    pub fn roots(&self) -> Result<Vec<ScanRoot>> {
+        read_roots(self.index, &self.rtxn)
+    }
+
+    /// Write a portable snapshot of the index to `out`, for
+    /// [`IndexWriter::import`] to load on another machine: every face as
+    /// stored, codepoint bitmap included, the mtime and hash recorded for
+    /// its file, and the roots.
+    ///
+    /// A snapshot is `TYPGSNAP`, [`SNAPSHOT_VERSION`] as a little-endian
+    /// `u32`, and a gzip stream of the records, the same bytes on every
+    /// platform. Face IDs and the `inverted` bitmaps are left out: both
+    /// follow from the faces, and an import files them under the IDs of
+    /// the index it goes into.
+    pub fn export(&self, out: impl Write) -> Result<SnapshotStats> {
+        let mut out = out;
+        out.write_all(SNAPSHOT_MAGIC)?;
+        out.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+        let mut out = GzEncoder::new(out, Compression::default());
+        let header = SnapshotHeader {
+            roots: serde_json::to_string(&self.roots()?)?,
+        };
+        bincode::serialize_into(&mut out, &header)?;
+
+        // The faces of one file hold consecutive IDs, so runs of one path
+        // in numeric ID order are whole files.
+        let mut stats = SnapshotStats::default();
+        let mut pending: Option<SnapshotFile> = None;
+        for font_id in stored_ids(self.index, &self.rtxn)? {
+            let Some(meta) = self.get_metadata(font_id)? else {
+                continue;
+            };
+            if let Some(file) = &mut pending {
+                if path_key(Path::new(&file.path)) == path_key(Path::new(&meta.path)) {
+                    file.faces.push(meta);
+                    continue;
+                }
+            }
+            let entry = self
+                .index
+                .db_path_to_id
+                .get(&self.rtxn, &hash_path(Path::new(&meta.path)))?
+                .and_then(PathEntry::decode);
+            let file = SnapshotFile {
+                path: meta.path.clone(),
+                mtime_secs: entry.map_or(0, |entry| entry.mtime_secs),
+                file_hash: entry.map_or(0, |entry| entry.file_hash),
+                faces: vec![meta],
+            };
+            if let Some(done) = pending.replace(file) {
+                write_snapshot_file(&mut out, &done, &mut stats)?;
+            }
+        }
+        if let Some(done) = pending {
+            write_snapshot_file(&mut out, &done, &mut stats)?;
+        }
+        bincode::serialize_into(&mut out, &None::<SnapshotFile>)?;
+        out.finish()?.flush()?;
+        Ok(stats)
+    }
+
     /// Execute a query and return matching font faces.
     pub fn find(&self, query: &Query) -> Result<Vec<TypgFontFaceMatch>> {
-        // Phase 1: Use inverted indices to get candidate bitmap.
-        let candidates = self.get_candidate_bitmap(query)?;
+        let ids = self.matching_ids(query)?;
 
-        // Phase 2: Filter candidates and hydrate metadata.
         let mut matches = Vec::new();
-        for font_id in candidates.iter() {
+        for font_id in ids.iter() {
             if let Some(meta) = self.get_metadata(font_id as u64)? {
-                if self.passes_filters(&meta, query)? {
-                    matches.push(hydrate_match(&meta));
-                }
+                matches.push(self.hydrate(font_id as u64, &meta)?);
             }
         }
 
@@ -373,6 +1917,7 @@ impl<'a> IndexReader<'a> {
                 .then_with(|| a.source.ttc_index.cmp(&b.source.ttc_index))
         });
 
+        share_strings(&mut matches);
         Ok(matches)
     }
 
@@ -380,9 +1925,9 @@ impl<'a> IndexReader<'a> {
     pub fn list_all(&self) -> Result<Vec<TypgFontFaceMatch>> {
         let mut matches = Vec::new();
         for result in self.index.db_metadata.iter(&self.rtxn)? {
-            let (_, bytes) = result?;
+            let (font_id, bytes) = result?;
             let meta = deserialize_meta(bytes)?;
-            matches.push(hydrate_match(&meta));
+            matches.push(self.hydrate(font_id, &meta)?);
         }
 
         matches.sort_by(|a, b| {
@@ -392,12 +1937,141 @@ impl<'a> IndexReader<'a> {
                 .then_with(|| a.source.ttc_index.cmp(&b.source.ttc_index))
         });
 
+        share_strings(&mut matches);
         Ok(matches)
     }
 
-    /// Get the candidate bitmap by intersecting tag bitmaps.
-    fn get_candidate_bitmap(&self, query: &Query) -> Result<RoaringBitmap> {
-        let mut result: Option<RoaringBitmap> = None;
+    /// How many faces are stored without their full metadata (see
+    /// [`IndexedFontMeta::details`]) and so read back with only names,
+    /// classes, tags and codepoints.
+    pub fn partial_faces(&self) -> Result<usize> {
+        let mut partial = 0;
+        for result in self.index.db_metadata.iter(&self.rtxn)? {
+            let (_, bytes) = result?;
+            if deserialize_meta(bytes)?.details.is_none() {
+                partial += 1;
+            }
+        }
+        Ok(partial)
+    }
+
+    /// The face stored under `id`, or `None` if the index holds no such
+    /// face (never assigned, or removed since).
+    pub fn get(&self, id: FontID) -> Result<Option<TypgFontFaceMatch>> {
+        self.get_metadata(id.0)?
+            .map(|meta| self.hydrate(id.0, &meta))
+            .transpose()
+    }
+
+    /// Every face stored for the font file at `path`, in collection order;
+    /// empty when the file is not indexed. Paths compare the way the
+    /// platform's filesystem compares them (see [`path_key`]).
+    pub fn get_by_path(&self, path: &Path) -> Result<Vec<TypgFontFaceMatch>> {
+        let Some(entry) = self
+            .index
+            .db_path_to_id
+            .get(&self.rtxn, &hash_path(path))?
+            .and_then(PathEntry::decode)
+        else {
+            return Ok(Vec::new());
+        };
+
+        // The faces of one file are written together, so they hold
+        // consecutive IDs starting at the one the path entry records.
+        let key = path_key(path);
+        let mut matches = Vec::new();
+        for font_id in entry.font_id.. {
+            match self.get_metadata(font_id)? {
+                Some(meta) if path_key(Path::new(&meta.path)) == key => {
+                    matches.push(self.hydrate(font_id, &meta)?);
+                }
+                _ => break,
+            }
+        }
+        matches.sort_by_key(|item| item.source.ttc_index);
+
+        share_strings(&mut matches);
+        Ok(matches)
+    }
+
+    /// Convert stored metadata to a match, with its ID and the file hash
+    /// and mtime from the path-to-ID entry.
+    fn hydrate(&self, font_id: u64, meta: &IndexedFontMeta) -> Result<TypgFontFaceMatch> {
+        let mut item = hydrate_match(meta);
+        item.metadata.index_id = Some(FontID(font_id));
+        let entry = self
+            .index
+            .db_path_to_id
+            .get(&self.rtxn, &hash_path(Path::new(&meta.path)))?
+            .and_then(PathEntry::decode);
+        if let Some(entry) = entry {
+            item.metadata.file_hash = Some(entry.file_hash).filter(|&hash| hash != 0);
+            item.metadata.modified = Some(entry.mtime_secs);
+        }
+        Ok(item)
+    }
+
+    /// Resolve a query to the set of matching font IDs.
+    fn matching_ids(&self, query: &Query) -> Result<RoaringBitmap> {
+        // Phase 1: Use inverted indices to get candidate bitmap.
+        let candidates = self.get_candidate_bitmap(query)?;
+
+        // Phase 2: Drop candidates that fail the non-tag filters.
+        let mut ids = RoaringBitmap::new();
+        for font_id in candidates.iter() {
+            if let Some(meta) = self.get_metadata(font_id as u64)? {
+                if self.passes_filters(&meta, query)? && passes_details_filters(&meta, query) {
+                    ids.insert(font_id);
+                }
+            }
+        }
+
+        // Phase 3: Boolean expressions map onto bitmap set operations.
+        if let Some(expr) = query.expr() {
+            ids &= self.expr_ids(expr)?;
+        }
+
+        Ok(ids)
+    }
+
+    /// Evaluate a boolean expression as unions, intersections, and complements.
+    fn expr_ids(&self, expr: &QueryExpr) -> Result<RoaringBitmap> {
+        match expr {
+            QueryExpr::Filter(query) => self.matching_ids(query),
+            QueryExpr::And(children) => {
+                let mut result: Option<RoaringBitmap> = None;
+                for child in children {
+                    result = Some(intersect_optional(result, self.expr_ids(child)?));
+                }
+                match result {
+                    Some(bitmap) => Ok(bitmap),
+                    None => self.all_ids(),
+                }
+            }
+            QueryExpr::Or(children) => {
+                let mut result = RoaringBitmap::new();
+                for child in children {
+                    result |= self.expr_ids(child)?;
+                }
+                Ok(result)
+            }
+            QueryExpr::Not(child) => Ok(self.all_ids()? - self.expr_ids(child)?),
+        }
+    }
+
+    /// Every font ID in the index.
+    fn all_ids(&self) -> Result<RoaringBitmap> {
+        let mut all = RoaringBitmap::new();
+        for r in self.index.db_metadata.iter(&self.rtxn)? {
+            let (id, _) = r?;
+            all.insert(id as u32);
+        }
+        Ok(all)
+    }
+
+    /// Get the candidate bitmap by intersecting tag bitmaps.
+    fn get_candidate_bitmap(&self, query: &Query) -> Result<RoaringBitmap> {
+        let mut result: Option<RoaringBitmap> = None;
 
         // Intersect axis tag bitmaps.
         for tag in query.axes() {
@@ -423,28 +2097,113 @@ impl<'a> IndexReader<'a> {
             result = Some(intersect_optional(result, bitmap));
         }
 
+        // Intersect the bitmaps of the cmap pages holding the queried
+        // codepoints; the exact check against each face's cmap follows.
+        if !query.codepoints().is_empty() && self.has_marker(CMAP_PAGES_MARKER)? {
+            let pages = cmap_pages(query.codepoints().iter().map(|&cp| cp as u32));
+            for page in pages {
+                let bitmap = self.get_tag_bitmap(cmap_page_key(page))?;
+                result = Some(intersect_optional(result, bitmap));
+            }
+        }
+
+        // Narrow to faces whose names hold the trigrams of some name
+        // pattern, when every pattern is a plain word or words; the
+        // regexes still decide per candidate.
+        if !query.name_patterns().is_empty() && self.has_marker(NAME_TRIGRAMS_MARKER)? {
+            if let Some(bitmap) = self.name_candidates(query.name_patterns())? {
+                result = Some(intersect_optional(result, bitmap));
+            }
+        }
+
         // Require variable fonts if specified.
         if query.requires_variable() {
             let bitmap = self.get_tag_bitmap(tag_marker(b"_VAR"))?;
             result = Some(intersect_optional(result, bitmap));
         }
 
-        // If no tag filters, return all fonts.
-        match result {
-            Some(bitmap) => Ok(bitmap),
-            None => {
-                let mut all = RoaringBitmap::new();
-                for r in self.index.db_metadata.iter(&self.rtxn)? {
-                    let (id, _) = r?;
-                    all.insert(id as u32);
+        // If no tag filters, start from all fonts.
+        let mut result = match result {
+            Some(bitmap) => bitmap,
+            None => self.all_ids()?,
+        };
+
+        // Subtract fonts carrying any excluded tag.
+        let excluded = query
+            .excluded_axes()
+            .iter()
+            .chain(query.excluded_features())
+            .chain(query.excluded_scripts())
+            .chain(query.excluded_tables());
+        for tag in excluded {
+            result -= self.get_tag_bitmap(tag_to_u32(*tag))?;
+        }
+        if query.rejects_variable() {
+            result -= self.get_tag_bitmap(tag_marker(b"_VAR"))?;
+        }
+
+        Ok(result)
+    }
+
+    /// Whether the `info` database holds `marker`. An index built before
+    /// cmap pages or name trigrams were filed gets their markers from its
+    /// next writer.
+    fn has_marker(&self, marker: &str) -> Result<bool> {
+        Ok(self.index.db_info.get(&self.rtxn, marker)?.is_some())
+    }
+
+    /// The faces that may match any of `patterns`: for each, the faces
+    /// whose names hold every trigram of one of its literal alternatives.
+    /// `None` when some pattern is not literal or too short to narrow by.
+    fn name_candidates(&self, patterns: &[regex::Regex]) -> Result<Option<RoaringBitmap>> {
+        let mut candidates = RoaringBitmap::new();
+        for pattern in patterns {
+            let Some(alternatives) = literal_alternatives(pattern.as_str()) else {
+                return Ok(None);
+            };
+            for literal in alternatives {
+                let keys = trigram_keys(&literal.to_lowercase());
+                if keys.is_empty() {
+                    return Ok(None);
                 }
-                Ok(all)
+                let mut found: Option<RoaringBitmap> = None;
+                for key in keys {
+                    found = Some(intersect_optional(found, self.get_tag_bitmap(key)?));
+                }
+                candidates |= found.unwrap_or_default();
             }
         }
+        Ok(Some(candidates))
     }
 
-    /// Get the bitmap for a specific tag.
+    /// Get the bitmap for a specific tag, from the index's
+    /// [`TagBitmapCache`] when it holds this snapshot.
     fn get_tag_bitmap(&self, tag: u32) -> Result<RoaringBitmap> {
+        let Some(txn_id) = self.txn_id else {
+            return self.read_tag_bitmap(tag);
+        };
+        {
+            let mut cache = self.index.bitmaps.lock().unwrap_or_else(|p| p.into_inner());
+            if cache.txn_id < txn_id {
+                cache.txn_id = txn_id;
+                cache.bitmaps.clear();
+            }
+            if cache.txn_id == txn_id {
+                if let Some(bitmap) = cache.bitmaps.get(&tag) {
+                    return Ok(bitmap.clone());
+                }
+            }
+        }
+        let bitmap = self.read_tag_bitmap(tag)?;
+        let mut cache = self.index.bitmaps.lock().unwrap_or_else(|p| p.into_inner());
+        if cache.txn_id == txn_id && cache.bitmaps.len() < TAG_BITMAP_CACHE_ENTRIES {
+            cache.bitmaps.insert(tag, bitmap.clone());
+        }
+        Ok(bitmap)
+    }
+
+    /// Decode the bitmap for a specific tag from the `inverted` database.
+    fn read_tag_bitmap(&self, tag: u32) -> Result<RoaringBitmap> {
         let tag_bytes = tag.to_ne_bytes();
         if let Some(bytes) = self.index.db_inverted.get(&self.rtxn, &tag_bytes)? {
             Ok(RoaringBitmap::deserialize_from(bytes)?)
@@ -454,7 +2213,7 @@ impl<'a> IndexReader<'a> {
     }
 
     /// Get metadata for a font ID.
-    fn get_metadata(&self, font_id: FontID) -> Result<Option<IndexedFontMeta>> {
+    fn get_metadata(&self, font_id: u64) -> Result<Option<IndexedFontMeta>> {
         if let Some(bytes) = self.index.db_metadata.get(&self.rtxn, &font_id)? {
             Ok(Some(deserialize_meta(bytes)?))
         } else {
@@ -464,6 +2223,25 @@ impl<'a> IndexReader<'a> {
 
     /// Check if metadata passes query filters that can't use inverted indices.
     fn passes_filters(&self, meta: &IndexedFontMeta, query: &Query) -> Result<bool> {
+        // Excluded path globs and collection filters.
+        let source = TypgFontSource {
+            path: PathBuf::from(&meta.path),
+            ttc_index: meta.ttc_index,
+        };
+        if !query.matches_source(&source) {
+            return Ok(false);
+        }
+
+        // Excluded name patterns.
+        if meta.names.iter().any(|name| {
+            query
+                .excluded_name_patterns()
+                .iter()
+                .any(|p| p.is_match(name))
+        }) {
+            return Ok(false);
+        }
+
         // Name pattern filter.
         if !query.name_patterns().is_empty() {
             let matches_any = meta
@@ -497,287 +2275,1858 @@ impl<'a> IndexReader<'a> {
             }
         }
 
-        // Family class filter.
-        if let Some(filter) = query.family_class() {
-            if let Some((major, sub)) = meta.family_class {
-                if !matches_family_class(major, sub, filter) {
-                    return Ok(false);
-                }
-            } else {
-                return Ok(false);
+        // Family class filter, with heuristic fallback for unclassified
+        // fonts. A face with `details` is checked against the category it
+        // was scanned with, which also weighs PANOSE and the fixed-pitch
+        // flag; older records only have their names to go on.
+        if let Some(filter) = query.family_class() {
+            if meta.details.is_none() {
+                let category = classify(&meta.names, meta.family_class, &[], false);
+                if !filter.matches(meta.family_class, category) {
+                    return Ok(false);
+                }
+            }
+        }
+
+        // Subtable formats are only kept in `details`, checked later; a face
+        // without mapped codepoints cannot pass.
+        if query.requires_unicode_cmap() && meta.cmap_bitmap.is_empty() {
+            return Ok(false);
+        }
+
+        // Codepoint/text filter using cmap bitmap.
+        if !query.codepoints().is_empty() {
+            let cmap =
+                RoaringBitmap::deserialize_from(meta.cmap_bitmap.as_slice()).unwrap_or_default();
+            if !query
+                .codepoints()
+                .iter()
+                .all(|&cp| cmap.contains(cp as u32))
+            {
+                return Ok(false);
+            }
+        }
+
+        // Unicode block coverage counted straight from the cmap bitmap.
+        if !query.coverage().is_empty() {
+            let cmap =
+                RoaringBitmap::deserialize_from(meta.cmap_bitmap.as_slice()).unwrap_or_default();
+            for filter in query.coverage() {
+                let covered = cmap.range_cardinality(filter.block.start..=filter.block.end);
+                if !filter.is_met(coverage_ratio(filter.block, covered)) {
+                    return Ok(false);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+/// Run the whole query against the face's hydrated metadata when it uses
+/// filters the index records hold nothing for.
+///
+/// Those fields are only kept in [`IndexedFontMeta::details`]. Faces stored
+/// without details come back without them, so filters on them reject such
+/// faces until their files are re-read, as a live search rejects fonts
+/// that lack the field.
+fn passes_details_filters(meta: &IndexedFontMeta, query: &Query) -> bool {
+    !needs_details(query) || query.matches(&hydrate_match(meta).metadata)
+}
+
+/// Whether `query` filters on a field the inverted index and the record's
+/// own fields do not hold: languages, glyph count, axis count, vendor,
+/// named instances, Unicode cmap subtables, embedding, creator and license
+/// strings, table checksums, the heuristic style category behind family
+/// classes, or custom predicates.
+fn needs_details(query: &Query) -> bool {
+    !query.languages().is_empty()
+        || query.family_class().is_some()
+        || query.min_glyphs().is_some()
+        || query.min_axes().is_some()
+        || query.max_axes().is_some()
+        || !query.vendors().is_empty()
+        || !query.instances().is_empty()
+        || query.requires_unicode_cmap()
+        || query.embedding().is_some()
+        || !query.creator_patterns().is_empty()
+        || !query.license_patterns().is_empty()
+        || !query.table_checksums().is_empty()
+        || !query.predicates().is_empty()
+}
+
+/// [`IndexCheck`] plus what [`IndexWriter::repair`] needs to act on it.
+struct Findings {
+    check: IndexCheck,
+    /// Path hashes of orphaned `path_to_id` entries.
+    orphan_paths: Vec<u64>,
+    /// Files of unmapped faces, with the lowest ID stored for each.
+    unmapped_paths: HashMap<String, u64>,
+    /// Bitmaps to rewrite, by key: the live members, or `None` to delete
+    /// a bitmap that does not deserialize.
+    bitmaps: Vec<(Vec<u8>, Option<RoaringBitmap>)>,
+}
+
+/// Cross-check the databases of `index` within one transaction.
+fn find_inconsistencies(index: &FontIndex, txn: &RoTxn) -> Result<Findings> {
+    let mut check = IndexCheck::default();
+    let mut live = RoaringBitmap::new();
+    let mut paths: HashMap<String, Vec<u64>> = HashMap::new();
+    for result in index.db_metadata.iter(txn)? {
+        let (font_id, bytes) = result?;
+        check.faces += 1;
+        match deserialize_meta(bytes) {
+            Ok(meta) => {
+                live.insert(font_id as u32);
+                paths.entry(meta.path).or_default().push(font_id);
+            }
+            Err(_) => check.unreadable_faces.push(FontID(font_id)),
+        }
+    }
+
+    let mut orphan_paths = Vec::new();
+    let mut mapped = HashSet::new();
+    for result in index.db_path_to_id.iter(txn)? {
+        let (path_hash, bytes) = result?;
+        match PathEntry::decode(bytes) {
+            Some(entry) if live.contains(entry.font_id as u32) => {
+                mapped.insert(path_hash);
+            }
+            _ => orphan_paths.push(path_hash),
+        }
+    }
+    check.orphan_paths = orphan_paths.len();
+
+    let mut unmapped_paths = HashMap::new();
+    for (path, ids) in paths {
+        if !mapped.contains(&hash_path(Path::new(&path))) {
+            check.unmapped_faces.extend(ids.iter().copied().map(FontID));
+            let first = ids.iter().copied().min().unwrap_or_default();
+            unmapped_paths.insert(path, first);
+        }
+    }
+    check.unmapped_faces.sort();
+
+    let mut bitmaps = Vec::new();
+    let mut orphans = RoaringBitmap::new();
+    for result in index.db_inverted.iter(txn)? {
+        let (key, bytes) = result?;
+        match RoaringBitmap::deserialize_from(bytes) {
+            Ok(bitmap) => {
+                let members = bitmap.len();
+                orphans |= &bitmap - &live;
+                let kept = bitmap & &live;
+                if kept.len() != members {
+                    check.dangling_members += members - kept.len();
+                    bitmaps.push((key.to_vec(), Some(kept)));
+                }
+            }
+            Err(_) => {
+                check.corrupt_bitmaps.push(tag_label(key));
+                bitmaps.push((key.to_vec(), None));
+            }
+        }
+    }
+    check.orphan_ids = orphans.iter().map(|id| FontID(u64::from(id))).collect();
+
+    Ok(Findings {
+        check,
+        orphan_paths,
+        unmapped_paths,
+        bitmaps,
+    })
+}
+
+/// Printable name of an `inverted` key: the tag (`wght`, `_VAR`), the
+/// first codepoint of a cmap page (`cmap U+0400`), the hash of a name
+/// trigram (`name 3fa2c1`), or its bytes in hex.
+fn tag_label(key: &[u8]) -> String {
+    match <[u8; 4]>::try_from(key) {
+        Ok(bytes) => {
+            let key = u32::from_ne_bytes(bytes);
+            if key & 0xFF00_0000 == CMAP_PAGE_PREFIX {
+                return format!("cmap U+{:04X}", (key & 0x00FF_FFFF) << 8);
+            }
+            if key & 0xFF00_0000 == NAME_TRIGRAM_PREFIX {
+                return format!("name {:06x}", key & 0x00FF_FFFF);
+            }
+            let tag = key.to_be_bytes();
+            if tag.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+                return String::from_utf8_lossy(&tag).into_owned();
+            }
+            format!("{:08x}", u32::from_be_bytes(tag))
+        }
+        Err(_) => key.iter().map(|b| format!("{b:02x}")).collect(),
+    }
+}
+
+/// Append one file record to a snapshot stream and count it.
+fn write_snapshot_file(
+    out: &mut impl Write,
+    file: &SnapshotFile,
+    stats: &mut SnapshotStats,
+) -> Result<()> {
+    bincode::serialize_into(out, &Some(file))?;
+    stats.files += 1;
+    stats.faces += file.faces.len();
+    Ok(())
+}
+
+/// `path` moved by the first `(from, to)` of `rebase` it lies under.
+fn rebased(path: &Path, rebase: &[(PathBuf, PathBuf)]) -> PathBuf {
+    rebase
+        .iter()
+        .find_map(|(from, to)| {
+            let rest = path.strip_prefix(from).ok()?;
+            Some(if rest.as_os_str().is_empty() {
+                to.clone()
+            } else {
+                to.join(rest)
+            })
+        })
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Read the next file record of a snapshot in layout `version`; `None`
+/// at the end.
+fn read_snapshot_file(input: impl Read, version: u32) -> bincode::Result<Option<SnapshotFile>> {
+    if version < 2 {
+        let file: Option<SnapshotFileV1> = bincode::deserialize_from(input)?;
+        Ok(file.map(SnapshotFile::from))
+    } else {
+        bincode::deserialize_from(input)
+    }
+}
+
+/// Deserialize metadata from bytes, in the current layout or one of those
+/// written before full metadata or tag lists were stored. bincode accepts
+/// trailing bytes, so the longest layout is tried first.
+fn deserialize_meta(bytes: &[u8]) -> Result<IndexedFontMeta> {
+    bincode::deserialize(bytes)
+        .or_else(|_| bincode::deserialize::<TaggedFontMeta>(bytes).map(IndexedFontMeta::from))
+        .or_else(|_| bincode::deserialize::<LegacyFontMeta>(bytes).map(IndexedFontMeta::from))
+        .map_err(|e| anyhow::anyhow!("bincode deserialize: {e}"))
+}
+
+/// The JSON kept in [`IndexedFontMeta::details`]: the face's metadata
+/// without the codepoints, which the cmap bitmap holds, or its index ID.
+fn face_details(meta: &TypgFontFaceMeta) -> Result<String> {
+    let mut meta = meta.clone();
+    meta.codepoints = Vec::new();
+    meta.index_id = None;
+    Ok(serde_json::to_string(&meta)?)
+}
+
+/// Convert metadata to TypgFontFaceMatch: the stored details with the
+/// bitmap's codepoints, or what the record itself holds for faces without
+/// details.
+fn hydrate_match(meta: &IndexedFontMeta) -> TypgFontFaceMatch {
+    let source = TypgFontSource {
+        path: PathBuf::from(&meta.path),
+        ttc_index: meta.ttc_index,
+    };
+    let details = meta
+        .details
+        .as_deref()
+        .and_then(|json| serde_json::from_str::<TypgFontFaceMeta>(json).ok());
+    if let Some(mut metadata) = details {
+        metadata.codepoints = decode_cmap_bitmap(&meta.cmap_bitmap);
+        return TypgFontFaceMatch { source, metadata };
+    }
+
+    let tags = meta.tags.clone().unwrap_or_default();
+    TypgFontFaceMatch {
+        source,
+        metadata: TypgFontFaceMeta {
+            names: meta
+                .names
+                .iter()
+                .map(|name| Arc::from(name.as_str()))
+                .collect(),
+            family_name: None, // Only kept in `details`
+            style_name: None,
+            units_per_em: None,
+            glyph_count: None,
+            font_revision: None,
+            vendor_id: None,
+            faces_in_collection: None, // Only kept in `details`
+            file_size: None,           // Only kept in `details`
+            modified: None,            // Kept in the path-to-ID entry
+            index_id: None,            // Set by `IndexReader::hydrate`
+            license_description: None,
+            license_url: None,
+            fs_type: None,
+            embedding: None,
+            file_hash: None, // Kept in the path-to-ID entry
+            axis_tags: tags_from_bytes(&tags.axis),
+            feature_tags: tags_from_bytes(&tags.feature),
+            gsub_feature_tags: tags_from_bytes(&tags.gsub_feature),
+            gpos_feature_tags: tags_from_bytes(&tags.gpos_feature),
+            script_tags: tags_from_bytes(&tags.script),
+            language_tags: tags_from_bytes(&tags.language),
+            table_tags: tags_from_bytes(&tags.table),
+            table_checksums: Default::default(), // Only kept in `details`
+            codepoints: decode_cmap_bitmap(&meta.cmap_bitmap),
+            is_variable: meta.is_variable,
+            cmap_subtables: Vec::new(),  // Only kept in `details`
+            named_instances: Vec::new(), // Only kept in `details`
+            axes: Vec::new(),            // Only kept in `details`
+            weight_class: meta.weight_class,
+            width_class: meta.width_class,
+            family_class: meta.family_class,
+            creator_names: Vec::new(), // Only kept in `details`
+            license_names: Vec::new(), // Only kept in `details`
+            // Names only: PANOSE and the fixed-pitch flag are only kept in `details`
+            classified_as: classify(&meta.names, meta.family_class, &[], false).map(str::to_string),
+            extra: Default::default(),
+        },
+    }
+}
+
+/// The `inverted` keys [`IndexWriter::insert_face`] files a face under:
+/// its searchable tags, the variable-font marker, its cmap pages and the
+/// trigrams of its names. `None` for faces stored before tag lists were
+/// kept.
+fn inverted_keys(meta: &IndexedFontMeta) -> Option<Vec<u32>> {
+    let tags = meta.tags.as_ref()?;
+    let mut keys: Vec<u32> = tags
+        .searchable()
+        .map(|tag| u32::from_be_bytes(*tag))
+        .collect();
+    if meta.is_variable {
+        keys.push(tag_marker(b"_VAR"));
+    }
+    let cmap = RoaringBitmap::deserialize_from(meta.cmap_bitmap.as_slice()).unwrap_or_default();
+    keys.extend(cmap_pages(cmap.iter()).into_iter().map(cmap_page_key));
+    keys.extend(name_trigram_keys(&meta.names));
+    Some(keys)
+}
+
+/// Hash a path for the path-to-ID lookup, by its platform [`path_key`].
+fn hash_path(path: &Path) -> u64 {
+    hash_key(&path_key(path))
+}
+
+fn hash_key(key: &str) -> u64 {
+    xxhash_rust::xxh3::xxh3_64(key.as_bytes())
+}
+
+/// Convert Tag to u32.
+fn tag_to_u32(tag: Tag) -> u32 {
+    u32::from_be_bytes(tag.into_bytes())
+}
+
+/// Create a marker tag for special indices.
+fn tag_marker(name: &[u8; 4]) -> u32 {
+    u32::from_be_bytes(*name)
+}
+
+/// The 256-codepoint pages holding `codepoints`, each once, in order.
+fn cmap_pages(codepoints: impl Iterator<Item = u32>) -> Vec<u32> {
+    let mut pages: Vec<u32> = codepoints.map(|cp| cp >> 8).collect();
+    pages.sort_unstable();
+    pages.dedup();
+    pages
+}
+
+/// `inverted` key of the faces mapping any codepoint of `page`.
+fn cmap_page_key(page: u32) -> u32 {
+    CMAP_PAGE_PREFIX | page
+}
+
+/// `inverted` keys of the trigrams of all `names`, each once.
+fn name_trigram_keys(names: &[String]) -> Vec<u32> {
+    let mut keys: Vec<u32> = names
+        .iter()
+        .flat_map(|name| trigram_keys(&name.to_lowercase()))
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+    keys
+}
+
+/// `inverted` keys of every run of three characters in `text`: a hash
+/// of the run under [`NAME_TRIGRAM_PREFIX`]. Runs that share a hash share
+/// a bitmap, which only widens the candidates the regexes then check.
+fn trigram_keys(text: &str) -> Vec<u32> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .windows(3)
+        .map(|run| {
+            let run: String = run.iter().collect();
+            NAME_TRIGRAM_PREFIX | (hash_key(&run) as u32 & 0x00FF_FFFF)
+        })
+        .collect()
+}
+
+/// The literal strings one of which a name must contain for `pattern` to
+/// match it, when `pattern` is plain text or plain alternatives
+/// (`Helvetica`, `Inter|Roboto`), optionally case-insensitive (`(?i)`)
+/// or anchored (`^(?:…)$`, as [`NameMatch::exact`](crate::query::NameMatch::exact) writes it). `None` for anything
+/// else.
+fn literal_alternatives(pattern: &str) -> Option<Vec<String>> {
+    let pattern = pattern.strip_prefix("(?i)").unwrap_or(pattern);
+    let pattern = match pattern
+        .strip_prefix("^(?:")
+        .and_then(|inner| inner.strip_suffix(")$"))
+    {
+        Some(inner) => inner,
+        None => {
+            let pattern = pattern.strip_prefix('^').unwrap_or(pattern);
+            pattern.strip_suffix('$').unwrap_or(pattern)
+        }
+    };
+    pattern
+        .split('|')
+        .map(|literal| {
+            (!literal.is_empty() && regex::escape(literal) == literal).then(|| literal.to_string())
+        })
+        .collect()
+}
+
+/// Build a Roaring Bitmap from codepoints for efficient coverage checks.
+fn build_cmap_bitmap(codepoints: &[char]) -> Vec<u8> {
+    if codepoints.is_empty() {
+        return Vec::new();
+    }
+
+    let mut bitmap = RoaringBitmap::new();
+    for &cp in codepoints {
+        bitmap.insert(cp as u32);
+    }
+
+    let mut buf = Vec::new();
+    bitmap.serialize_into(&mut buf).unwrap_or_default();
+    buf
+}
+
+/// Expand a serialized cmap bitmap back into sorted codepoints.
+fn decode_cmap_bitmap(bytes: &[u8]) -> Vec<char> {
+    RoaringBitmap::deserialize_from(bytes)
+        .map(|bitmap| bitmap.iter().filter_map(char::from_u32).collect())
+        .unwrap_or_default()
+}
+
+/// Intersect an optional bitmap with another bitmap.
+fn intersect_optional(opt: Option<RoaringBitmap>, other: RoaringBitmap) -> RoaringBitmap {
+    match opt {
+        Some(mut bm) => {
+            bm &= &other;
+            bm
+        }
+        None => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{CmapSubtable, Embedding};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_index_open_and_close() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        assert_eq!(index.count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_add_and_query_font() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+
+        // Add a font.
+        let mut writer = index.writer().unwrap();
+        let path = Path::new("/test/font.ttf");
+        let mtime = SystemTime::UNIX_EPOCH;
+
+        writer
+            .add_font(
+                path,
+                None,
+                mtime,
+                vec!["Test Font".to_string()],
+                &[],
+                &[Tag::new(b"smcp")],
+                &[Tag::new(b"latn")],
+                &[],
+                &['a', 'b', 'c'],
+                false,
+                Some(400),
+                Some(5),
+                Some((8, 1)),
+            )
+            .unwrap();
+        writer.commit().unwrap();
+
+        assert_eq!(index.count().unwrap(), 1);
+
+        // Query with feature filter.
+        let reader = index.reader().unwrap();
+        let query = Query::new().with_features(vec![Tag::new(b"smcp")]);
+        let matches = reader.find(&query).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].source.path, path);
+        // Codepoints come back from the cmap bitmap, so font IDs agree
+        // with live scans.
+        assert_eq!(matches[0].metadata.codepoints, ['a', 'b', 'c']);
+    }
+
+    #[test]
+    fn test_writer_flushes_bitmaps_at_commit() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let latn = Tag::new(b"latn");
+        let add = |writer: &mut IndexWriter<'_>, path: &str| {
+            writer
+                .add_font(
+                    Path::new(path),
+                    None,
+                    SystemTime::UNIX_EPOCH,
+                    vec![path.to_string()],
+                    &[],
+                    &[],
+                    &[latn],
+                    &[],
+                    &['a'],
+                    false,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap()
+        };
+
+        let mut writer = index.writer().unwrap();
+        add(&mut writer, "/a.ttf");
+        add(&mut writer, "/b.ttf");
+        let stored = index
+            .db_inverted
+            .get(&writer.wtxn, &tag_to_u32(latn).to_ne_bytes())
+            .unwrap();
+        assert!(stored.is_none(), "bitmaps wait for the commit");
+        writer.commit().unwrap();
+
+        // A later transaction extends the stored bitmap.
+        let mut writer = index.writer().unwrap();
+        add(&mut writer, "/c.ttf");
+        writer.commit().unwrap();
+        let query = Query::new().with_scripts(vec![latn]);
+        assert_eq!(index.reader().unwrap().find(&query).unwrap().len(), 3);
+
+        // An aborted transaction leaves it as it was.
+        let mut writer = index.writer().unwrap();
+        add(&mut writer, "/d.ttf");
+        writer.abort();
+        assert_eq!(index.reader().unwrap().find(&query).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_ingest_commits_every_n_written_files() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let file = |n: usize| {
+            vec![hydrate_match(&IndexedFontMeta {
+                path: format!("/fonts/{n}.ttf"),
+                ttc_index: None,
+                names: vec![format!("Font {n}")],
+                is_variable: false,
+                weight_class: None,
+                width_class: None,
+                family_class: None,
+                cmap_bitmap: Vec::new(),
+                tags: None,
+                details: None,
+            })]
+        };
+        let every_two = NonZeroUsize::new(2);
+
+        let (writer, stats) = index
+            .writer()
+            .unwrap()
+            .ingest((0..5).map(file), true, every_two)
+            .unwrap();
+        assert_eq!(
+            stats,
+            IngestStats {
+                added: 5,
+                skipped: 0,
+                commits: 2
+            }
+        );
+        // The two full batches are saved even when the last one is not.
+        writer.abort();
+        assert_eq!(index.count().unwrap(), 4);
+
+        // Unchanged files are skipped and do not count towards a batch.
+        let (writer, stats) = index
+            .writer()
+            .unwrap()
+            .ingest((0..5).map(file), true, every_two)
+            .unwrap();
+        writer.commit().unwrap();
+        assert_eq!(
+            stats,
+            IngestStats {
+                added: 1,
+                skipped: 4,
+                commits: 0
+            }
+        );
+        assert_eq!(index.count().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_clear_empties_the_index() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let first = add_named(&index, "/a.ttf");
+        add_named(&index, "/b.ttf");
+
+        let mut writer = index.writer().unwrap();
+        assert_eq!(writer.clear().unwrap(), 2);
+        writer.commit().unwrap();
+
+        assert_eq!(index.count().unwrap(), 0);
+        let stats = index.database_stats().unwrap();
+        assert!(stats
+            .iter()
+            .filter(|db| db.name != "info")
+            .all(|db| db.entries == 0));
+        let writer = index.writer().unwrap();
+        assert!(writer
+            .needs_update(Path::new("/a.ttf"), SystemTime::UNIX_EPOCH)
+            .unwrap());
+        writer.abort();
+        assert!(add_named(&index, "/a.ttf") > first, "IDs are not reused");
+    }
+
+    #[test]
+    fn test_warm_reads_every_record() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        assert_eq!(index.warm().unwrap(), 0);
+
+        let mut writer = index.writer().unwrap();
+        writer
+            .add_font(
+                Path::new("/test/font.ttf"),
+                None,
+                SystemTime::UNIX_EPOCH,
+                vec!["Test Font".to_string()],
+                &[],
+                &[Tag::new(b"smcp")],
+                &[Tag::new(b"latn")],
+                &[],
+                &['a'],
+                false,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        writer.commit().unwrap();
+
+        let stored: u64 = index
+            .database_stats()
+            .unwrap()
+            .iter()
+            .map(|db| db.entries as u64)
+            .sum();
+        assert!(index.warm().unwrap() > stored);
+    }
+
+    #[test]
+    fn test_database_stats_count_entries() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let mut writer = index.writer().unwrap();
+        writer
+            .add_font(
+                Path::new("/test/font.ttf"),
+                None,
+                SystemTime::UNIX_EPOCH,
+                vec!["Test Font".to_string()],
+                &[],
+                &[Tag::new(b"smcp")],
+                &[Tag::new(b"latn")],
+                &[],
+                &['a'],
+                false,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        writer.commit().unwrap();
+
+        let stats = index.database_stats().unwrap();
+        let names: Vec<_> = stats.iter().map(|s| s.name).collect();
+        assert_eq!(names, ["metadata", "inverted", "path_to_id", "info"]);
+        assert_eq!(stats[0].entries, 1);
+        assert!(stats[0].size_bytes > 0);
+        assert!(stats[1].entries >= 2, "smcp and latn bitmaps");
+        assert_eq!(stats[2].entries, 1);
+    }
+
+    #[test]
+    fn test_incremental_update() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+
+        let path = Path::new("/test/font.ttf");
+        let mtime1 = SystemTime::UNIX_EPOCH;
+        let mtime2 = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1000);
+
+        // First add.
+        {
+            let mut writer = index.writer().unwrap();
+            assert!(writer.needs_update(path, mtime1).unwrap());
+            writer
+                .add_font(
+                    path,
+                    None,
+                    mtime1,
+                    vec!["V1".to_string()],
+                    &[],
+                    &[],
+                    &[],
+                    &[],
+                    &[],
+                    false,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            writer.commit().unwrap();
+        }
+
+        // Same mtime - no update needed.
+        {
+            let writer = index.writer().unwrap();
+            assert!(!writer.needs_update(path, mtime1).unwrap());
+            writer.abort();
+        }
+
+        // Different mtime - update needed.
+        {
+            let writer = index.writer().unwrap();
+            assert!(writer.needs_update(path, mtime2).unwrap());
+            writer.abort();
+        }
+    }
+
+    #[test]
+    fn test_bitmap_intersection() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+
+        // Add two fonts with different features.
+        {
+            let mut writer = index.writer().unwrap();
+            writer
+                .add_font(
+                    Path::new("/font1.ttf"),
+                    None,
+                    SystemTime::UNIX_EPOCH,
+                    vec!["Font1".to_string()],
+                    &[],
+                    &[Tag::new(b"smcp"), Tag::new(b"liga")],
+                    &[],
+                    &[],
+                    &[],
+                    false,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            writer
+                .add_font(
+                    Path::new("/font2.ttf"),
+                    None,
+                    SystemTime::UNIX_EPOCH,
+                    vec!["Font2".to_string()],
+                    &[],
+                    &[Tag::new(b"smcp")],
+                    &[],
+                    &[],
+                    &[],
+                    false,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            writer.commit().unwrap();
+        }
+
+        let reader = index.reader().unwrap();
+
+        // Query for smcp only - should find both.
+        let q1 = Query::new().with_features(vec![Tag::new(b"smcp")]);
+        assert_eq!(reader.find(&q1).unwrap().len(), 2);
+
+        // Query for smcp AND liga - should find only font1.
+        let q2 = Query::new().with_features(vec![Tag::new(b"smcp"), Tag::new(b"liga")]);
+        let matches = reader.find(&q2).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].source.path, Path::new("/font1.ttf"));
+    }
+
+    #[test]
+    fn test_query_expr_uses_bitmap_set_operations() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+
+        {
+            let mut writer = index.writer().unwrap();
+            for (path, script) in [
+                ("/arab.ttf", b"arab"),
+                ("/hebr.ttf", b"hebr"),
+                ("/latn.ttf", b"latn"),
+            ] {
+                writer
+                    .add_font(
+                        Path::new(path),
+                        None,
+                        SystemTime::UNIX_EPOCH,
+                        vec![path.to_string()],
+                        &[],
+                        &[],
+                        &[Tag::new(script)],
+                        &[],
+                        &[],
+                        false,
+                        None,
+                        None,
+                        None,
+                    )
+                    .unwrap();
+            }
+            writer.commit().unwrap();
+        }
+
+        let reader = index.reader().unwrap();
+
+        let either = crate::query::parse_query_expr("script:arab OR script:hebr").unwrap();
+        let matches = reader.find(&Query::new().with_expr(Some(either))).unwrap();
+        let paths: Vec<_> = matches.iter().map(|m| m.source.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("/arab.ttf"), PathBuf::from("/hebr.ttf")]
+        );
+
+        let not_arab = crate::query::parse_query_expr("NOT script:arab").unwrap();
+        assert_eq!(
+            reader
+                .find(&Query::new().with_expr(Some(not_arab)))
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_excluded_tags_subtract_bitmaps() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+
+        {
+            let mut writer = index.writer().unwrap();
+            for (path, features) in [
+                ("/kerned.ttf", vec![Tag::new(b"kern"), Tag::new(b"liga")]),
+                ("/plain.ttf", vec![Tag::new(b"liga")]),
+            ] {
+                writer
+                    .add_font(
+                        Path::new(path),
+                        None,
+                        SystemTime::UNIX_EPOCH,
+                        vec![path.to_string()],
+                        &[],
+                        &features,
+                        &[],
+                        &[],
+                        &[],
+                        false,
+                        None,
+                        None,
+                        None,
+                    )
+                    .unwrap();
+            }
+            writer.commit().unwrap();
+        }
+
+        let reader = index.reader().unwrap();
+        let query = Query::new().without_features(vec![Tag::new(b"kern")]);
+        let matches = reader.find(&query).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].source.path, Path::new("/plain.ttf"));
+    }
+
+    #[test]
+    fn test_cmap_bitmap() {
+        let codepoints = vec!['a', 'b', 'c', 'ñ', '中'];
+        let bitmap_bytes = build_cmap_bitmap(&codepoints);
+        let bitmap = RoaringBitmap::deserialize_from(bitmap_bytes.as_slice()).unwrap();
+
+        // Should contain all original codepoints.
+        for &cp in &codepoints {
+            assert!(bitmap.contains(cp as u32));
+        }
+
+        // Should not contain a codepoint we didn't add.
+        assert!(!bitmap.contains('z' as u32));
+    }
+
+    #[test]
+    fn test_plain_name_patterns_intersect_trigrams() {
+        use crate::query::{parse_name_pattern, NameMatch};
+
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        {
+            let mut writer = index.writer().unwrap();
+            for name in ["Helvetica Neue", "Inter Display", "Roboto"] {
+                writer
+                    .add_font(
+                        Path::new(&format!("/{name}.ttf")),
+                        None,
+                        SystemTime::UNIX_EPOCH,
+                        vec![name.into()],
+                        &[],
+                        &[],
+                        &[],
+                        &[],
+                        &[],
+                        false,
+                        None,
+                        None,
+                        None,
+                    )
+                    .unwrap();
+            }
+            writer.commit().unwrap();
+        }
+        let reader = index.reader().unwrap();
+        let narrowed = |pattern: &str, mode: NameMatch| {
+            let query =
+                Query::new().with_name_patterns(vec![parse_name_pattern(pattern, mode).unwrap()]);
+            let candidates = reader.get_candidate_bitmap(&query).unwrap().len();
+            (candidates, reader.find(&query).unwrap().len())
+        };
+        let plain = NameMatch::default();
+        let ignore_case = NameMatch {
+            ignore_case: true,
+            ..NameMatch::default()
+        };
+        let exact = NameMatch {
+            exact: true,
+            ..NameMatch::default()
+        };
+        assert_eq!(narrowed("Helvetica", plain), (1, 1));
+        // Trigrams ignore case; the regex does not.
+        assert_eq!(narrowed("helvetica", plain), (1, 0));
+        assert_eq!(narrowed("helvetica", ignore_case), (1, 1));
+        assert_eq!(narrowed("Inter|Roboto", plain), (2, 2));
+        assert_eq!(narrowed("Inter", exact), (1, 0));
+        // Regexes and short words are checked face by face.
+        assert_eq!(narrowed("Hel.*Neue", plain), (3, 1));
+        assert_eq!(narrowed("Ro", plain), (3, 1));
+
+        assert_eq!(
+            literal_alternatives("^(?:Inter|Roboto)$"),
+            Some(vec!["Inter".to_string(), "Roboto".to_string()])
+        );
+        assert_eq!(
+            literal_alternatives("(?i)Noto Sans"),
+            Some(vec!["Noto Sans".into()])
+        );
+        assert_eq!(literal_alternatives("Inter|"), None);
+        assert_eq!(literal_alternatives("Inter (Display)"), None);
+    }
+
+    #[test]
+    fn test_codepoint_queries_intersect_cmap_pages() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let add = |writer: &mut IndexWriter, path: &str, codepoints: &[char]| {
+            writer
+                .add_font(
+                    Path::new(path),
+                    None,
+                    SystemTime::UNIX_EPOCH,
+                    vec![path.into()],
+                    &[],
+                    &[],
+                    &[],
+                    &[],
+                    codepoints,
+                    false,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+        };
+        {
+            let mut writer = index.writer().unwrap();
+            add(&mut writer, "/latin.ttf", &['a', 'b']);
+            add(&mut writer, "/cyrillic.ttf", &['a', 'ж']);
+            add(&mut writer, "/empty.ttf", &[]);
+            writer.commit().unwrap();
+        }
+        let query = Query::new().with_codepoints(vec!['a', 'ж']);
+        let candidates = |index: &FontIndex| {
+            let reader = index.reader().unwrap();
+            let ids = reader.get_candidate_bitmap(&query).unwrap();
+            let found: Vec<_> = reader
+                .find(&query)
+                .unwrap()
+                .into_iter()
+                .map(|m| m.source.path)
+                .collect();
+            (ids.len(), found)
+        };
+        assert_eq!(
+            candidates(&index),
+            (1, vec![PathBuf::from("/cyrillic.ttf")])
+        );
+        assert_eq!(tag_label(&cmap_page_key(4).to_ne_bytes()), "cmap U+0400");
+
+        // An index written before pages were filed falls back to checking
+        // every face until its next writer files them.
+        {
+            let mut writer = index.writer().unwrap();
+            writer.flush_bitmaps().unwrap();
+            for page in [0, 4] {
+                index
+                    .db_inverted
+                    .delete(&mut writer.wtxn, &cmap_page_key(page).to_ne_bytes())
+                    .unwrap();
+            }
+            index
+                .db_info
+                .delete(&mut writer.wtxn, CMAP_PAGES_MARKER)
+                .unwrap();
+            writer.commit().unwrap();
+        }
+        assert_eq!(
+            candidates(&index),
+            (3, vec![PathBuf::from("/cyrillic.ttf")])
+        );
+        index.writer().unwrap().commit().unwrap();
+        assert_eq!(
+            candidates(&index),
+            (1, vec![PathBuf::from("/cyrillic.ttf")])
+        );
+    }
+
+    #[test]
+    fn test_coverage_counts_cmap_bitmap_ranges() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+
+        {
+            let mut writer = index.writer().unwrap();
+            let ascii: Vec<char> = ('\0'..='\x7f').collect();
+            let printable: Vec<char> = (' '..='~').collect();
+            for (path, codepoints) in [("/full.ttf", &ascii), ("/printable.ttf", &printable)] {
+                writer
+                    .add_font(
+                        Path::new(path),
+                        None,
+                        SystemTime::UNIX_EPOCH,
+                        vec![path.to_string()],
+                        &[],
+                        &[],
+                        &[],
+                        &[],
+                        codepoints,
+                        false,
+                        None,
+                        None,
+                        None,
+                    )
+                    .unwrap();
+            }
+            writer.commit().unwrap();
+        }
+
+        let reader = index.reader().unwrap();
+        let full = crate::query::parse_unicode_block("Basic Latin").unwrap();
+        let matches = reader
+            .find(&Query::new().with_coverage(vec![full]))
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].source.path, Path::new("/full.ttf"));
+
+        let most = crate::query::parse_coverage_filter("Basic Latin:70%").unwrap();
+        let matches = reader
+            .find(&Query::new().with_coverage(vec![most]))
+            .unwrap();
+        assert_eq!(matches.len(), 2);
+    }
+
+    /// An index holding `/match.ttf`, whose stored details satisfy every
+    /// details-only filter the tests below use, `/other.ttf`, whose
+    /// details satisfy none, and `/partial.ttf`, stored without details.
+    fn details_index() -> (TempDir, FontIndex) {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let bare = |path: &str| {
+            hydrate_match(&IndexedFontMeta {
+                path: path.to_string(),
+                ttc_index: None,
+                names: vec![path.to_string()],
+                is_variable: false,
+                weight_class: None,
+                width_class: None,
+                family_class: None,
+                cmap_bitmap: build_cmap_bitmap(&['a']),
+                tags: None,
+                details: None,
+            })
+        };
+
+        let mut matching = bare("/match.ttf");
+        let meta = &mut matching.metadata;
+        meta.glyph_count = Some(900);
+        meta.vendor_id = Some("FLAB".into());
+        meta.language_tags = vec![Tag::new(b"TRK ")];
+        meta.axis_tags = vec![Tag::new(b"wght")];
+        meta.is_variable = true;
+        meta.named_instances =
+            serde_json::from_value(serde_json::json!([{ "name": "SemiBold", "coordinates": {} }]))
+                .unwrap();
+        meta.embedding = Some(Embedding::Installable);
+        meta.table_checksums.insert("head".into(), 0xDEADBEEF);
+        meta.cmap_subtables = vec![CmapSubtable {
+            platform_id: 3,
+            encoding_id: 1,
+            format: 4,
+        }];
+
+        let mut other = bare("/other.ttf");
+        let meta = &mut other.metadata;
+        meta.glyph_count = Some(12);
+        meta.vendor_id = Some("ZZZZ".into());
+        meta.language_tags = vec![Tag::new(b"SRB ")];
+        meta.embedding = Some(Embedding::Restricted);
+        meta.table_checksums.insert("head".into(), 1);
+        meta.cmap_subtables = vec![CmapSubtable {
+            platform_id: 1,
+            encoding_id: 0,
+            format: 6,
+        }];
+
+        let mut writer = index.writer().unwrap();
+        for face in [matching, other] {
+            writer
+                .replace_file(&face.source.path.clone(), SystemTime::UNIX_EPOCH, &[face])
+                .unwrap();
+        }
+        writer
+            .add_font(
+                Path::new("/partial.ttf"),
+                None,
+                SystemTime::UNIX_EPOCH,
+                vec!["Partial".into()],
+                &[Tag::new(b"wght")],
+                &[],
+                &[],
+                &[],
+                &['a'],
+                true,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        writer.commit().unwrap();
+        (dir, index)
+    }
+
+    /// The paths of the faces `query` finds in `index`.
+    fn found(index: &FontIndex, query: &Query) -> Vec<PathBuf> {
+        let reader = index.reader().unwrap();
+        let matches = reader.find(query).unwrap();
+        matches.into_iter().map(|m| m.source.path).collect()
+    }
+
+    #[test]
+    fn test_min_glyphs_filters_indexed_faces() {
+        let (_dir, index) = details_index();
+        let query = Query::new().with_min_glyphs(Some(100));
+        assert_eq!(found(&index, &query), [PathBuf::from("/match.ttf")]);
+    }
+
+    #[test]
+    fn test_vendor_filters_indexed_faces() {
+        let (_dir, index) = details_index();
+        let query = Query::new().with_vendors(vec!["flab".into()]);
+        assert_eq!(found(&index, &query), [PathBuf::from("/match.ttf")]);
+        let query = Query::new().with_vendors(vec!["NONE".into()]);
+        assert!(found(&index, &query).is_empty());
+    }
+
+    #[test]
+    fn test_languages_filter_indexed_faces() {
+        let (_dir, index) = details_index();
+        let query = Query::new().with_languages(vec![Tag::new(b"TRK ")]);
+        assert_eq!(found(&index, &query), [PathBuf::from("/match.ttf")]);
+    }
+
+    #[test]
+    fn test_instances_filter_indexed_faces() {
+        let (_dir, index) = details_index();
+        let query = Query::new().with_instances(vec!["semi bold".into()]);
+        assert_eq!(found(&index, &query), [PathBuf::from("/match.ttf")]);
+    }
+
+    #[test]
+    fn test_axis_counts_filter_indexed_faces() {
+        let (_dir, index) = details_index();
+        // Axis tags are kept without details too.
+        let query = Query::new().with_min_axes(Some(1));
+        assert_eq!(
+            found(&index, &query),
+            [PathBuf::from("/match.ttf"), PathBuf::from("/partial.ttf")]
+        );
+        let query = Query::new().with_max_axes(Some(0));
+        assert_eq!(found(&index, &query), [PathBuf::from("/other.ttf")]);
+    }
+
+    #[test]
+    fn test_embedding_filters_indexed_faces() {
+        let (_dir, index) = details_index();
+        let query = Query::new().with_embedding(Some(Embedding::Editable));
+        assert_eq!(found(&index, &query), [PathBuf::from("/match.ttf")]);
+    }
+
+    #[test]
+    fn test_table_checksums_filter_indexed_faces() {
+        let (_dir, index) = details_index();
+        let query = Query::new().with_table_checksums(vec![(Tag::new(b"head"), 0xDEADBEEF)]);
+        assert_eq!(found(&index, &query), [PathBuf::from("/match.ttf")]);
+    }
+
+    #[test]
+    fn test_unicode_cmap_only_filters_indexed_faces() {
+        let (_dir, index) = details_index();
+        // Without recorded subtables, any mapped codepoint passes.
+        let query = Query::new().require_unicode_cmap(true);
+        assert_eq!(
+            found(&index, &query),
+            [PathBuf::from("/match.ttf"), PathBuf::from("/partial.ttf")]
+        );
+    }
+
+    /// A font with a `name` table naming it "Plain Sans Regular" and a
+    /// `post` table flagging it fixed-pitch: monospace by the flag, which
+    /// outranks the "Sans" in its name.
+    fn fixed_pitch_font() -> Vec<u8> {
+        let mut name = Vec::new();
+        let strings: Vec<Vec<u8>> = ["Plain Sans", "Regular"]
+            .iter()
+            .map(|s| s.encode_utf16().flat_map(u16::to_be_bytes).collect())
+            .collect();
+        name.extend_from_slice(&[0, 0, 0, 2, 0, 6 + 2 * 12]);
+        let mut offset = 0u16;
+        for (name_id, string) in [1u16, 2].into_iter().zip(&strings) {
+            for field in [3, 1, 0x409, name_id, string.len() as u16, offset] {
+                name.extend_from_slice(&field.to_be_bytes());
             }
+            offset += string.len() as u16;
         }
+        strings
+            .iter()
+            .for_each(|string| name.extend_from_slice(string));
 
-        // Codepoint/text filter using cmap bitmap.
-        if !query.codepoints().is_empty() && !meta.cmap_bitmap.is_empty() {
-            if let Ok(cmap) = RoaringBitmap::deserialize_from(meta.cmap_bitmap.as_slice()) {
-                for &cp in query.codepoints() {
-                    if !cmap.contains(cp as u32) {
-                        return Ok(false);
-                    }
-                }
+        let mut post = vec![0, 3, 0, 0];
+        post.extend_from_slice(&[0; 8]);
+        post.extend_from_slice(&1u32.to_be_bytes()); // isFixedPitch
+        post.extend_from_slice(&[0; 16]);
+
+        let tables = [(b"name", name), (b"post", post)];
+        let mut font = vec![0, 1, 0, 0, 0, 2, 0, 32, 0, 1, 0, 0];
+        let mut offset = 12 + 16 * tables.len();
+        let mut data = Vec::new();
+        for (tag, table) in &tables {
+            font.extend_from_slice(*tag);
+            font.extend_from_slice(&[0; 4]);
+            font.extend_from_slice(&(offset as u32).to_be_bytes());
+            font.extend_from_slice(&(table.len() as u32).to_be_bytes());
+            data.extend_from_slice(table);
+            while data.len() % 4 != 0 {
+                data.push(0);
             }
+            offset = 12 + 16 * tables.len() + data.len();
         }
-
-        Ok(true)
+        font.extend_from_slice(&data);
+        font
     }
-}
 
-/// Deserialize metadata from bytes.
-fn deserialize_meta(bytes: &[u8]) -> Result<IndexedFontMeta> {
-    bincode::deserialize(bytes).map_err(|e| anyhow::anyhow!("bincode deserialize: {e}"))
-}
+    #[test]
+    fn test_family_class_agrees_with_live_search_for_fixed_pitch_fonts() {
+        use crate::query::parse_family_class;
+        use crate::search::{search, SearchOptions};
 
-/// Convert metadata to TypgFontFaceMatch.
-fn hydrate_match(meta: &IndexedFontMeta) -> TypgFontFaceMatch {
-    use crate::search::{TypgFontFaceMeta, TypgFontSource};
+        let fonts = TempDir::new().unwrap();
+        let path = fonts.path().join("Plain.ttf");
+        std::fs::write(&path, fixed_pitch_font()).unwrap();
+        let dirs = [fonts.path().to_path_buf()];
+        let faces = search(&dirs, &Query::new(), &SearchOptions::default()).unwrap();
+        assert_eq!(faces.len(), 1);
+        assert_eq!(faces[0].metadata.classified_as.as_deref(), Some("mono"));
 
-    TypgFontFaceMatch {
-        source: TypgFontSource {
-            path: PathBuf::from(&meta.path),
-            ttc_index: meta.ttc_index,
-        },
-        metadata: TypgFontFaceMeta {
-            names: meta.names.clone(),
-            axis_tags: Vec::new(),    // Not stored in indexed form
-            feature_tags: Vec::new(), // Not stored in indexed form
-            script_tags: Vec::new(),  // Not stored in indexed form
-            table_tags: Vec::new(),   // Not stored in indexed form
-            codepoints: Vec::new(),   // Stored as bitmap
-            is_variable: meta.is_variable,
-            weight_class: meta.weight_class,
-            width_class: meta.width_class,
-            family_class: meta.family_class,
-            creator_names: Vec::new(), // Not stored in indexed form
-            license_names: Vec::new(), // Not stored in indexed form
-        },
-    }
-}
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let mut writer = index.writer().unwrap();
+        writer
+            .replace_file(&path, SystemTime::UNIX_EPOCH, &faces)
+            .unwrap();
+        writer.commit().unwrap();
 
-/// Hash a path for the path-to-ID lookup.
-fn hash_path(path: &Path) -> u64 {
-    use xxhash_rust::xxh3::xxh3_64;
-    xxh3_64(path.to_string_lossy().as_bytes())
-}
+        for class in ["sans", "script"] {
+            let query = Query::new().with_family_class(Some(parse_family_class(class).unwrap()));
+            let live = search(&dirs, &query, &SearchOptions::default()).unwrap();
+            let live: Vec<PathBuf> = live.into_iter().map(|m| m.source.path).collect();
+            assert_eq!(found(&index, &query), live, "--family-class {class}");
+        }
+        let indexed = index.reader().unwrap().find(&Query::new()).unwrap();
+        assert_eq!(indexed[0].metadata.classified_as.as_deref(), Some("mono"));
+    }
 
-/// Convert Tag to u32.
-fn tag_to_u32(tag: Tag) -> u32 {
-    u32::from_be_bytes(tag.into_bytes())
-}
+    #[test]
+    fn test_replace_file_keeps_collection_faces_and_remove_path_clears_dirs() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let face = |path: &str, ttc_index: Option<u32>, name: &str| {
+            hydrate_match(&IndexedFontMeta {
+                path: path.to_string(),
+                ttc_index,
+                names: vec![name.into()],
+                is_variable: false,
+                weight_class: None,
+                width_class: None,
+                family_class: None,
+                cmap_bitmap: Vec::new(),
+                tags: None,
+                details: None,
+            })
+        };
 
-/// Create a marker tag for special indices.
-fn tag_marker(name: &[u8; 4]) -> u32 {
-    u32::from_be_bytes(*name)
-}
+        {
+            let mut writer = index.writer().unwrap();
+            let ttc = Path::new("/lib/sub/Pair.ttc");
+            let faces = [
+                face("/lib/sub/Pair.ttc", Some(0), "Pair A"),
+                face("/lib/sub/Pair.ttc", Some(1), "Pair B"),
+            ];
+            assert_eq!(
+                writer
+                    .replace_file(ttc, SystemTime::UNIX_EPOCH, &faces)
+                    .unwrap(),
+                2
+            );
+            let solo = [face("/lib/Solo.ttf", None, "Solo")];
+            writer
+                .replace_file(Path::new("/lib/Solo.ttf"), SystemTime::UNIX_EPOCH, &solo)
+                .unwrap();
+            // Re-indexing replaces rather than duplicates.
+            writer
+                .replace_file(ttc, SystemTime::UNIX_EPOCH, &faces)
+                .unwrap();
+            assert!(!writer.needs_update(ttc, SystemTime::UNIX_EPOCH).unwrap());
+            writer.commit().unwrap();
+        }
+        assert_eq!(index.count().unwrap(), 3);
 
-/// Build a Roaring Bitmap from codepoints for efficient coverage checks.
-fn build_cmap_bitmap(codepoints: &[char]) -> Vec<u8> {
-    if codepoints.is_empty() {
-        return Vec::new();
+        {
+            let mut writer = index.writer().unwrap();
+            assert_eq!(writer.remove_path(Path::new("/lib/sub")).unwrap(), 2);
+            assert!(writer
+                .needs_update(Path::new("/lib/sub/Pair.ttc"), SystemTime::UNIX_EPOCH)
+                .unwrap());
+            writer.commit().unwrap();
+        }
+        let names: Vec<String> = index
+            .reader()
+            .unwrap()
+            .list_all()
+            .unwrap()
+            .into_iter()
+            .flat_map(|m| m.metadata.names)
+            .map(|name| name.to_string())
+            .collect();
+        assert_eq!(names, ["Solo"]);
     }
 
-    let mut bitmap = RoaringBitmap::new();
-    for &cp in codepoints {
-        bitmap.insert(cp as u32);
-    }
+    #[test]
+    fn test_replace_file_only_touches_that_files_faces() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let face = |path: &str, ttc_index: Option<u32>, name: &str| {
+            hydrate_match(&IndexedFontMeta {
+                path: path.to_string(),
+                ttc_index,
+                names: vec![name.into()],
+                is_variable: false,
+                weight_class: None,
+                width_class: None,
+                family_class: None,
+                cmap_bitmap: Vec::new(),
+                tags: None,
+                details: None,
+            })
+        };
+        let ttc = Path::new("/lib/Pair.ttc");
+        let solo = Path::new("/lib/Solo.ttf");
+        let last = Path::new("/lib/Z.ttf");
 
-    let mut buf = Vec::new();
-    bitmap.serialize_into(&mut buf).unwrap_or_default();
-    buf
-}
+        {
+            let mut writer = index.writer().unwrap();
+            let pair = [
+                face("/lib/Pair.ttc", Some(0), "Pair A"),
+                face("/lib/Pair.ttc", Some(1), "Pair B"),
+            ];
+            writer
+                .replace_file(ttc, SystemTime::UNIX_EPOCH, &pair)
+                .unwrap();
+            writer
+                .replace_file(
+                    solo,
+                    SystemTime::UNIX_EPOCH,
+                    &[face("/lib/Solo.ttf", None, "Solo")],
+                )
+                .unwrap();
+            writer
+                .replace_file(
+                    last,
+                    SystemTime::UNIX_EPOCH,
+                    &[face("/lib/Z.ttf", None, "Z")],
+                )
+                .unwrap();
+            writer.commit().unwrap();
+        }
 
-/// Intersect an optional bitmap with another bitmap.
-fn intersect_optional(opt: Option<RoaringBitmap>, other: RoaringBitmap) -> RoaringBitmap {
-    match opt {
-        Some(mut bm) => {
-            bm &= &other;
-            bm
+        {
+            // The collection shrinks and the middle file stops parsing;
+            // neither may disturb the faces stored next to them.
+            let mut writer = index.writer().unwrap();
+            writer
+                .replace_file(
+                    ttc,
+                    SystemTime::UNIX_EPOCH,
+                    &[face("/lib/Pair.ttc", Some(0), "Pair A")],
+                )
+                .unwrap();
+            assert_eq!(
+                writer
+                    .replace_file(solo, SystemTime::UNIX_EPOCH, &[])
+                    .unwrap(),
+                0
+            );
+            assert!(writer.needs_update(solo, SystemTime::UNIX_EPOCH).unwrap());
+            writer.commit().unwrap();
         }
-        None => other,
-    }
-}
 
-/// Check if family class matches the filter.
-fn matches_family_class(major: u8, sub: u8, filter: &FamilyClassFilter) -> bool {
-    if major != filter.major {
-        return false;
-    }
-    match filter.subclass {
-        Some(expected_sub) => sub == expected_sub,
-        None => true,
+        assert_eq!(index.count().unwrap(), 2);
+        let reader = index.reader().unwrap();
+        let names = |path: &Path| -> Vec<String> {
+            reader
+                .get_by_path(path)
+                .unwrap()
+                .into_iter()
+                .flat_map(|m| m.metadata.names)
+                .map(|name| name.to_string())
+                .collect()
+        };
+        assert_eq!(names(ttc), ["Pair A"]);
+        assert!(names(solo).is_empty());
+        assert_eq!(names(last), ["Z"]);
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
 
     #[test]
-    fn test_index_open_and_close() {
+    fn test_get_and_get_by_path_address_stored_faces() {
         let dir = TempDir::new().unwrap();
         let index = FontIndex::open(dir.path()).unwrap();
-        assert_eq!(index.count().unwrap(), 0);
+        let face = |path: &str, ttc_index: Option<u32>, name: &str| {
+            hydrate_match(&IndexedFontMeta {
+                path: path.to_string(),
+                ttc_index,
+                names: vec![name.into()],
+                is_variable: false,
+                weight_class: None,
+                width_class: None,
+                family_class: None,
+                cmap_bitmap: Vec::new(),
+                tags: None,
+                details: None,
+            })
+        };
+        let ttc = Path::new("/lib/Pair.ttc");
+        {
+            let mut writer = index.writer().unwrap();
+            let faces = [
+                face("/lib/Pair.ttc", Some(0), "Pair A"),
+                face("/lib/Pair.ttc", Some(1), "Pair B"),
+            ];
+            writer
+                .replace_file(ttc, SystemTime::UNIX_EPOCH, &faces)
+                .unwrap();
+            let solo = [face("/lib/Solo.ttf", None, "Solo")];
+            writer
+                .replace_file(Path::new("/lib/Solo.ttf"), SystemTime::UNIX_EPOCH, &solo)
+                .unwrap();
+            writer.commit().unwrap();
+        }
+
+        let reader = index.reader().unwrap();
+        let pair = reader.get_by_path(ttc).unwrap();
+        let indices: Vec<_> = pair.iter().map(|m| m.source.ttc_index).collect();
+        assert_eq!(indices, [Some(0), Some(1)]);
+        assert!(reader
+            .get_by_path(Path::new("/lib/None.ttf"))
+            .unwrap()
+            .is_empty());
+
+        // Every hydrated match carries the ID that fetches it again.
+        for item in reader.list_all().unwrap() {
+            let id = item.metadata.index_id.expect("index results carry an ID");
+            let fetched = reader.get(id).unwrap().expect("stored face");
+            assert_eq!(fetched.source.path, item.source.path);
+            assert_eq!(fetched.source.ttc_index, item.source.ttc_index);
+        }
+        let last = pair
+            .iter()
+            .filter_map(|m| m.metadata.index_id)
+            .max()
+            .unwrap();
+        assert!(reader.get(FontID(last.0 + 100)).unwrap().is_none());
     }
 
     #[test]
-    fn test_add_and_query_font() {
+    fn test_remove_faces_keeps_the_rest_of_a_collection() {
         let dir = TempDir::new().unwrap();
         let index = FontIndex::open(dir.path()).unwrap();
+        let face = |path: &str, ttc_index: Option<u32>, name: &str| {
+            let mut face = hydrate_match(&IndexedFontMeta {
+                path: path.to_string(),
+                ttc_index,
+                names: vec![name.into()],
+                is_variable: false,
+                weight_class: None,
+                width_class: None,
+                family_class: None,
+                cmap_bitmap: Vec::new(),
+                tags: None,
+                details: None,
+            });
+            face.metadata.script_tags = vec![Tag::new(b"latn")];
+            face
+        };
+        let ttc = Path::new("/lib/Trio.ttc");
+        let mtime = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(7);
+        {
+            let mut writer = index.writer().unwrap();
+            let faces = [
+                face("/lib/Trio.ttc", Some(0), "Trio A"),
+                face("/lib/Trio.ttc", Some(1), "Old Corp B"),
+                face("/lib/Trio.ttc", Some(2), "Trio C"),
+            ];
+            writer.replace_file(ttc, mtime, &faces).unwrap();
+            let solo = [face("/lib/Solo.ttf", None, "Old Corp Solo")];
+            writer
+                .replace_file(Path::new("/lib/Solo.ttf"), mtime, &solo)
+                .unwrap();
+            writer.commit().unwrap();
+        }
 
-        // Add a font.
+        let query = Query::new().with_name_patterns(vec![regex::Regex::new("Old Corp").unwrap()]);
+        let ids: Vec<FontID> = {
+            let reader = index.reader().unwrap();
+            let doomed = reader.find(&query).unwrap();
+            doomed.iter().filter_map(|m| m.metadata.index_id).collect()
+        };
+        assert_eq!(ids.len(), 2);
         let mut writer = index.writer().unwrap();
-        let path = Path::new("/test/font.ttf");
-        let mtime = SystemTime::UNIX_EPOCH;
-
-        writer
-            .add_font(
-                path,
-                None,
-                mtime,
-                vec!["Test Font".to_string()],
-                &[],
-                &[Tag::new(b"smcp")],
-                &[Tag::new(b"latn")],
-                &[],
-                &['a', 'b', 'c'],
-                false,
-                Some(400),
-                Some(5),
-                Some((8, 1)),
-            )
-            .unwrap();
+        assert_eq!(writer.remove_faces(&ids).unwrap(), 2);
+        assert_eq!(writer.remove_faces(&ids).unwrap(), 0);
         writer.commit().unwrap();
 
-        assert_eq!(index.count().unwrap(), 1);
-
-        // Query with feature filter.
         let reader = index.reader().unwrap();
-        let query = Query::new().with_features(vec![Tag::new(b"smcp")]);
-        let matches = reader.find(&query).unwrap();
-        assert_eq!(matches.len(), 1);
-        assert_eq!(matches[0].source.path, path);
+        assert!(reader.find(&query).unwrap().is_empty());
+        let trio = reader.get_by_path(ttc).unwrap();
+        let indices: Vec<_> = trio.iter().map(|m| m.source.ttc_index).collect();
+        assert_eq!(indices, [Some(0), Some(2)]);
+        assert_eq!(trio[0].metadata.modified, Some(7));
+        let latn = Query::new().with_scripts(vec![Tag::new(b"latn")]);
+        assert_eq!(reader.find(&latn).unwrap().len(), 2);
+        assert!(reader
+            .get_by_path(Path::new("/lib/Solo.ttf"))
+            .unwrap()
+            .is_empty());
+        drop(reader);
+
+        // Neither the removed faces nor the moved ones' old IDs linger in
+        // the bitmaps.
+        let check = index.check().unwrap();
+        assert!(check.is_consistent(), "{check:?}");
     }
 
     #[test]
-    fn test_incremental_update() {
+    fn test_removals_take_faces_out_of_their_bitmaps() {
         let dir = TempDir::new().unwrap();
         let index = FontIndex::open(dir.path()).unwrap();
-
-        let path = Path::new("/test/font.ttf");
-        let mtime1 = SystemTime::UNIX_EPOCH;
-        let mtime2 = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1000);
-
-        // First add.
-        {
+        let wght = Query::new().with_axes(vec![Tag::new(b"wght")]);
+        let add = |path: &str, axes: &[Tag]| {
             let mut writer = index.writer().unwrap();
-            assert!(writer.needs_update(path, mtime1).unwrap());
             writer
                 .add_font(
-                    path,
+                    Path::new(path),
                     None,
-                    mtime1,
-                    vec!["V1".to_string()],
-                    &[],
-                    &[],
+                    SystemTime::UNIX_EPOCH,
+                    vec![path.to_string()],
+                    axes,
                     &[],
                     &[],
                     &[],
-                    false,
+                    &['a'],
+                    !axes.is_empty(),
                     None,
                     None,
                     None,
                 )
                 .unwrap();
             writer.commit().unwrap();
-        }
+        };
+        add("/a.ttf", &[Tag::new(b"wght")]);
+        add("/b.ttf", &[Tag::new(b"wght")]);
 
-        // Same mtime - no update needed.
+        // Re-adding a file drops the old face's tags with it.
+        add("/a.ttf", &[]);
         {
-            let writer = index.writer().unwrap();
-            assert!(!writer.needs_update(path, mtime1).unwrap());
-            writer.abort();
+            let mut writer = index.writer().unwrap();
+            writer.remove_path(Path::new("/b.ttf")).unwrap();
+            writer.commit().unwrap();
         }
+        let check = index.check().unwrap();
+        assert!(check.is_consistent(), "{check:?}");
+        assert!(index.reader().unwrap().find(&wght).unwrap().is_empty());
 
-        // Different mtime - update needed.
-        {
-            let writer = index.writer().unwrap();
-            assert!(writer.needs_update(path, mtime2).unwrap());
-            writer.abort();
-        }
+        // Bitmaps left empty are dropped.
+        let reader = index.reader().unwrap();
+        assert!(reader
+            .get_tag_bitmap(tag_to_u32(Tag::new(b"wght")))
+            .unwrap()
+            .is_empty());
+        assert!(index
+            .db_inverted
+            .get(&reader.rtxn, &tag_to_u32(Tag::new(b"wght")).to_ne_bytes())
+            .unwrap()
+            .is_none());
     }
 
     #[test]
-    fn test_bitmap_intersection() {
+    fn test_check_finds_and_repair_fixes_inconsistencies() {
         let dir = TempDir::new().unwrap();
         let index = FontIndex::open(dir.path()).unwrap();
+        add_named(&index, "/a.ttf");
+        let b = add_named(&index, "/b.ttf");
+        add_named(&index, "/c.ttf");
+        assert!(index.check().unwrap().is_consistent());
 
-        // Add two fonts with different features.
         {
             let mut writer = index.writer().unwrap();
+            // Earlier versions left a removed face's ID in its bitmaps.
             writer
-                .add_font(
-                    Path::new("/font1.ttf"),
-                    None,
-                    SystemTime::UNIX_EPOCH,
-                    vec!["Font1".to_string()],
-                    &[],
-                    &[Tag::new(b"smcp"), Tag::new(b"liga")],
-                    &[],
-                    &[],
-                    &[],
-                    false,
-                    None,
-                    None,
-                    None,
-                )
+                .add_to_inverted_index(tag_marker(b"_VAR"), 999)
                 .unwrap();
-            writer
-                .add_font(
-                    Path::new("/font2.ttf"),
-                    None,
-                    SystemTime::UNIX_EPOCH,
-                    vec!["Font2".to_string()],
-                    &[],
-                    &[Tag::new(b"smcp")],
-                    &[],
-                    &[],
-                    &[],
-                    false,
-                    None,
-                    None,
-                    None,
+            writer.flush_bitmaps().unwrap();
+            // Forget /b.ttf's path entry, break a bitmap and a record.
+            let index = writer.index;
+            index
+                .db_path_to_id
+                .delete(&mut writer.wtxn, &hash_path(Path::new("/b.ttf")))
+                .unwrap();
+            index
+                .db_inverted
+                .put(
+                    &mut writer.wtxn,
+                    &tag_marker(b"brok").to_ne_bytes(),
+                    b"junk",
                 )
                 .unwrap();
+            index
+                .db_metadata
+                .put(&mut writer.wtxn, &1000, b"\xff")
+                .unwrap();
+            writer.commit().unwrap();
+        }
+
+        let check = index.check().unwrap();
+        assert_eq!(check.faces, 4);
+        assert_eq!(check.unreadable_faces, [FontID(1000)]);
+        assert_eq!(check.unmapped_faces, [b]);
+        assert_eq!(check.orphan_paths, 0);
+        assert_eq!(check.dangling_members, 1);
+        assert_eq!(check.orphan_ids, [FontID(999)]);
+        assert_eq!(check.corrupt_bitmaps, ["brok"]);
+        assert_eq!(check.problems(), 4);
+
+        let mut writer = index.writer().unwrap();
+        assert_eq!(writer.repair().unwrap(), check);
+        // The unmapped file and, after the corrupt bitmap, every file is
+        // re-read by the next incremental add.
+        assert!(writer
+            .needs_update(Path::new("/b.ttf"), SystemTime::now())
+            .unwrap());
+        assert!(writer
+            .needs_update(Path::new("/a.ttf"), SystemTime::now())
+            .unwrap());
+        writer.commit().unwrap();
+
+        let after = index.check().unwrap();
+        assert!(after.is_consistent(), "{after:?}");
+        assert_eq!(after.faces, 3);
+    }
+
+    #[test]
+    fn test_file_hash_detects_in_place_edits() {
+        let dir = TempDir::new().unwrap();
+        let font = dir.path().join("Edited.ttf");
+        std::fs::write(&font, b"first").unwrap();
+        let index = FontIndex::open(&dir.path().join("index")).unwrap();
+        let mtime = SystemTime::UNIX_EPOCH;
+        let mut face = hydrate_match(&IndexedFontMeta {
+            path: font.display().to_string(),
+            ttc_index: None,
+            names: vec!["Edited".into()],
+            is_variable: false,
+            weight_class: None,
+            width_class: None,
+            family_class: None,
+            cmap_bitmap: Vec::new(),
+            tags: None,
+            details: None,
+        });
+        let first = hash_file(&font).unwrap();
+        face.metadata.file_hash = Some(first);
+
+        {
+            let mut writer = index.writer().unwrap();
+            writer
+                .replace_file(&font, mtime, std::slice::from_ref(&face))
+                .unwrap();
+            assert!(!writer.needs_update_hashed(&font, mtime, first).unwrap());
+            assert!(writer.needs_update_hashed(&font, mtime, first ^ 1).unwrap());
             writer.commit().unwrap();
         }
+        let listed = index.reader().unwrap().list_all().unwrap();
+        assert_eq!(listed[0].metadata.file_hash, Some(first));
 
-        let reader = index.reader().unwrap();
-
-        // Query for smcp only - should find both.
-        let q1 = Query::new().with_features(vec![Tag::new(b"smcp")]);
-        assert_eq!(reader.find(&q1).unwrap().len(), 2);
+        // Same mtime, different bytes: only the hash notices.
+        std::fs::write(&font, b"second").unwrap();
+        let mut writer = index.writer().unwrap();
+        assert!(!writer.needs_update(&font, mtime).unwrap());
+        assert_eq!(writer.prune_changed().unwrap(), 1);
+        writer.commit().unwrap();
+        assert_eq!(index.count().unwrap(), 0);
+    }
 
-        // Query for smcp AND liga - should find only font1.
-        let q2 = Query::new().with_features(vec![Tag::new(b"smcp"), Tag::new(b"liga")]);
-        let matches = reader.find(&q2).unwrap();
-        assert_eq!(matches.len(), 1);
-        assert_eq!(matches[0].source.path, Path::new("/font1.ttf"));
+    #[test]
+    fn test_legacy_path_entries_decode_without_hash() {
+        let legacy = bytemuck::bytes_of(&[7u64, 42u64]).to_vec();
+        let entry = PathEntry::decode(&legacy).unwrap();
+        assert_eq!(
+            (entry.font_id, entry.mtime_secs, entry.file_hash),
+            (7, 42, 0)
+        );
+        assert!(PathEntry::decode(&[0u8; 5]).is_none());
     }
 
     #[test]
-    fn test_cmap_bitmap() {
-        let codepoints = vec!['a', 'b', 'c', 'ñ', '中'];
-        let bitmap_bytes = build_cmap_bitmap(&codepoints);
-        let bitmap = RoaringBitmap::deserialize_from(bitmap_bytes.as_slice()).unwrap();
+    fn test_tag_lists_round_trip_and_untagged_faces_go_stale() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let tagged = Path::new("/lib/Tagged.ttf");
+        let mut face = hydrate_match(&IndexedFontMeta {
+            path: tagged.display().to_string(),
+            ttc_index: None,
+            names: vec!["Tagged".into()],
+            is_variable: true,
+            weight_class: None,
+            width_class: None,
+            family_class: None,
+            cmap_bitmap: Vec::new(),
+            tags: None,
+            details: None,
+        });
+        let meta = &mut face.metadata;
+        meta.axis_tags = vec![Tag::new(b"wght")];
+        meta.feature_tags = vec![Tag::new(b"kern"), Tag::new(b"liga")];
+        meta.gsub_feature_tags = vec![Tag::new(b"liga")];
+        meta.gpos_feature_tags = vec![Tag::new(b"kern")];
+        meta.script_tags = vec![Tag::new(b"latn")];
+        meta.language_tags = vec![Tag::new(b"TRK ")];
+        meta.table_tags = vec![Tag::new(b"fvar"), Tag::new(b"glyf")];
 
-        // Should contain all original codepoints.
-        for &cp in &codepoints {
-            assert!(bitmap.contains(cp as u32));
+        // A face written before tag lists were stored.
+        let untagged = Path::new("/lib/Untagged.ttf");
+        let legacy = bincode::serialize(&(
+            untagged.display().to_string(),
+            None::<u32>,
+            vec!["Untagged".to_string()],
+            false,
+            None::<u16>,
+            None::<u16>,
+            None::<(u8, u8)>,
+            Vec::<u8>::new(),
+        ))
+        .unwrap();
+        {
+            let mut writer = index.writer().unwrap();
+            writer
+                .replace_file(tagged, mtime, std::slice::from_ref(&face))
+                .unwrap();
+            writer
+                .index
+                .db_metadata
+                .put(&mut writer.wtxn, &1000, &legacy)
+                .unwrap();
+            writer.put_path_entry(untagged, 1000, mtime, None).unwrap();
+            index
+                .db_info
+                .delete(&mut writer.wtxn, TAG_LISTS_MARKER)
+                .unwrap();
+            writer.commit().unwrap();
         }
 
-        // Should not contain a codepoint we didn't add.
-        assert!(!bitmap.contains('z' as u32));
+        {
+            let reader = index.reader().unwrap();
+            let stored = &reader.get_by_path(tagged).unwrap()[0].metadata;
+            assert_eq!(stored.axis_tags, face.metadata.axis_tags);
+            assert_eq!(stored.feature_tags, face.metadata.feature_tags);
+            assert_eq!(stored.gsub_feature_tags, face.metadata.gsub_feature_tags);
+            assert_eq!(stored.gpos_feature_tags, face.metadata.gpos_feature_tags);
+            assert_eq!(stored.script_tags, face.metadata.script_tags);
+            assert_eq!(stored.language_tags, face.metadata.language_tags);
+            assert_eq!(stored.table_tags, face.metadata.table_tags);
+            let old = reader.get(FontID(1000)).unwrap().unwrap();
+            assert_eq!(old.metadata.names[0].as_ref(), "Untagged");
+            assert!(old.metadata.table_tags.is_empty());
+        }
+
+        let writer = index.writer().unwrap();
+        assert!(!writer.needs_update(tagged, mtime).unwrap());
+        assert!(writer.needs_update(untagged, mtime).unwrap());
     }
 
     #[test]
@@ -852,4 +4201,549 @@ mod tests {
             .iter()
             .any(|n| n.contains("Existing")));
     }
+
+    #[test]
+    fn test_compact_shrinks_the_file_and_keeps_the_faces() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let mut writer = index.writer().unwrap();
+        let mut ids = Vec::new();
+        for n in 0..1000 {
+            let path = format!("/fonts/{n:04}.ttf");
+            let id = writer
+                .add_font(
+                    Path::new(&path),
+                    None,
+                    SystemTime::UNIX_EPOCH,
+                    vec![format!("{n:04} {}", "x".repeat(2000))],
+                    &[],
+                    &[],
+                    &[],
+                    &[],
+                    &['a'],
+                    false,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            ids.push(id);
+        }
+        writer.commit().unwrap();
+        let mut writer = index.writer().unwrap();
+        writer.remove_faces(&ids[10..]).unwrap();
+        writer.commit().unwrap();
+
+        let stats = index.compact().unwrap();
+        assert!(stats.after_bytes < stats.before_bytes, "{stats:?}");
+        assert_eq!(
+            stats.reclaimed_bytes(),
+            stats.before_bytes - stats.after_bytes
+        );
+        assert!(!dir.path().join("data.mdb.compact").exists());
+
+        let index = FontIndex::open(dir.path()).unwrap();
+        assert_eq!(index.count().unwrap(), 10);
+        add_named(&index, "/fonts/new.ttf");
+        assert_eq!(index.count().unwrap(), 11);
+    }
+
+    #[test]
+    fn test_export_and_import_move_the_catalog_with_rebased_paths() {
+        let source = TempDir::new().unwrap();
+        let index = FontIndex::open(source.path()).unwrap();
+        let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let face = |ttc_index: Option<u32>, name: &str| {
+            let mut face = hydrate_match(&IndexedFontMeta {
+                path: "/build/fonts/Pair.ttc".to_string(),
+                ttc_index,
+                names: vec![name.into()],
+                is_variable: false,
+                weight_class: Some(700),
+                width_class: None,
+                family_class: None,
+                cmap_bitmap: Vec::new(),
+                tags: None,
+                details: None,
+            });
+            face.metadata.feature_tags = vec![Tag::new(b"smcp")];
+            face.metadata.codepoints = vec!['a', 'ж'];
+            face.metadata.file_hash = Some(42);
+            face.metadata.family_name = Some("Pair".into());
+            face
+        };
+        {
+            let mut writer = index.writer().unwrap();
+            writer
+                .replace_file(
+                    Path::new("/build/fonts/Pair.ttc"),
+                    mtime,
+                    &[face(Some(0), "Pair A"), face(Some(1), "Pair B")],
+                )
+                .unwrap();
+            writer
+                .record_roots(&[ScanRoot {
+                    path: PathBuf::from("/build/fonts"),
+                    ..ScanRoot::default()
+                }])
+                .unwrap();
+            writer.commit().unwrap();
+        }
+        add_named(&index, "/other/Solo.ttf");
+
+        let mut snapshot = Vec::new();
+        let exported = index.reader().unwrap().export(&mut snapshot).unwrap();
+        assert_eq!(exported, SnapshotStats { files: 2, faces: 3 });
+        assert!(snapshot.starts_with(SNAPSHOT_MAGIC));
+
+        let target = TempDir::new().unwrap();
+        let index = FontIndex::open(target.path()).unwrap();
+        // IDs in the target index differ from the exported ones.
+        add_named(&index, "/here/Existing.ttf");
+        let mut writer = index.writer().unwrap();
+        let rebase = [(PathBuf::from("/build"), PathBuf::from("/opt/catalog"))];
+        let imported = writer.import(snapshot.as_slice(), &rebase).unwrap();
+        assert_eq!(imported, exported);
+        let moved = Path::new("/opt/catalog/fonts/Pair.ttc");
+        assert!(!writer.needs_update_hashed(moved, mtime, 42).unwrap());
+        writer.commit().unwrap();
+
+        assert_eq!(index.count().unwrap(), 4);
+        let reader = index.reader().unwrap();
+        let pair = reader.get_by_path(moved).unwrap();
+        assert_eq!(pair.len(), 2);
+        assert_eq!(pair[1].metadata.names[0].as_ref(), "Pair B");
+        assert_eq!(pair[1].metadata.weight_class, Some(700));
+        assert_eq!(pair[1].metadata.codepoints, ['a', 'ж']);
+        assert_eq!(pair[1].metadata.file_hash, Some(42));
+        assert_eq!(pair[1].metadata.family_name.as_deref(), Some("Pair"));
+        let smcp = Query::new().with_features(vec![Tag::new(b"smcp")]);
+        assert_eq!(reader.find(&smcp).unwrap().len(), 2);
+        let cyrillic = Query::new().with_codepoints(vec!['ж']);
+        assert_eq!(reader.find(&cyrillic).unwrap().len(), 2);
+        assert_eq!(
+            reader
+                .get_by_path(Path::new("/other/Solo.ttf"))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            reader.roots().unwrap()[0].path,
+            PathBuf::from("/opt/catalog/fonts")
+        );
+    }
+
+    #[test]
+    fn test_export_keeps_collections_whose_ids_cross_a_byte_boundary() {
+        let source = TempDir::new().unwrap();
+        let index = FontIndex::open(source.path()).unwrap();
+        let face = |path: &str, ttc_index: Option<u32>| {
+            let mut face = hydrate_match(&IndexedFontMeta {
+                path: path.to_string(),
+                ttc_index,
+                names: vec![format!("{path} {ttc_index:?}")],
+                is_variable: false,
+                weight_class: None,
+                width_class: None,
+                family_class: None,
+                cmap_bitmap: Vec::new(),
+                tags: None,
+                details: None,
+            });
+            face.metadata.codepoints = vec!['a'];
+            face
+        };
+        let mut writer = index.writer().unwrap();
+        let mut store = |path: &str, faces: u32| {
+            let faces: Vec<_> = (0..faces)
+                .map(|n| face(path, (faces > 1).then_some(n)))
+                .collect();
+            writer
+                .replace_file(Path::new(path), SystemTime::UNIX_EPOCH, &faces)
+                .unwrap();
+        };
+        // IDs 1-254 for single files, 255-258 for the collection, and more
+        // single files after it.
+        for n in 0..254 {
+            store(&format!("/fonts/Solo{n}.ttf"), 1);
+        }
+        store("/fonts/Family.ttc", 4);
+        for n in 254..264 {
+            store(&format!("/fonts/Solo{n}.ttf"), 1);
+        }
+        writer.commit().unwrap();
+        let family = index
+            .reader()
+            .unwrap()
+            .get_by_path(Path::new("/fonts/Family.ttc"))
+            .unwrap();
+        let ids: Vec<_> = family.iter().map(|f| f.metadata.index_id).collect();
+        assert_eq!(ids.first(), Some(&Some(FontID(255))));
+        assert_eq!(ids.last(), Some(&Some(FontID(258))));
+
+        let mut snapshot = Vec::new();
+        let exported = index.reader().unwrap().export(&mut snapshot).unwrap();
+        assert_eq!(
+            exported,
+            SnapshotStats {
+                files: 265,
+                faces: 268
+            }
+        );
+
+        let target = TempDir::new().unwrap();
+        let index = FontIndex::open(target.path()).unwrap();
+        let mut writer = index.writer().unwrap();
+        assert_eq!(writer.import(snapshot.as_slice(), &[]).unwrap(), exported);
+        writer.commit().unwrap();
+        assert_eq!(index.count().unwrap(), 268);
+        let reader = index.reader().unwrap();
+        assert_eq!(
+            reader
+                .get_by_path(Path::new("/fonts/Family.ttc"))
+                .unwrap()
+                .len(),
+            4
+        );
+        assert!(index.check().unwrap().is_consistent());
+    }
+
+    #[test]
+    fn test_import_rejects_foreign_and_truncated_snapshots() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        add_named(&index, "/fonts/A.ttf");
+        let mut snapshot = Vec::new();
+        index.reader().unwrap().export(&mut snapshot).unwrap();
+
+        let mut writer = index.writer().unwrap();
+        let err = writer.import(&b"{\"fonts\": []}"[..], &[]).unwrap_err();
+        assert!(
+            err.to_string().contains("not a typg index snapshot"),
+            "{err}"
+        );
+
+        let mut newer = snapshot.clone();
+        newer[8..12].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+        let err = writer.import(newer.as_slice(), &[]).unwrap_err();
+        assert!(err.to_string().contains("newer"), "{err}");
+
+        let truncated = &snapshot[..snapshot.len() - 12];
+        assert!(writer.import(truncated, &[]).is_err());
+    }
+
+    #[test]
+    fn test_faces_without_details_go_stale_and_v1_snapshots_import() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let tagged = TaggedFontMeta {
+            path: "/lib/Tagged.ttf".to_string(),
+            ttc_index: None,
+            names: vec!["Tagged".into()],
+            is_variable: false,
+            weight_class: Some(300),
+            width_class: None,
+            family_class: None,
+            cmap_bitmap: build_cmap_bitmap(&['a']),
+            tags: Some(IndexedTags::default()),
+        };
+        {
+            // A face written before full metadata was stored.
+            let mut writer = index.writer().unwrap();
+            let bytes = bincode::serialize(&tagged).unwrap();
+            index
+                .db_metadata
+                .put(&mut writer.wtxn, &1000, &bytes)
+                .unwrap();
+            let path = Path::new(&tagged.path);
+            writer.put_path_entry(path, 1000, mtime, None).unwrap();
+            index
+                .db_info
+                .delete(&mut writer.wtxn, DETAILS_MARKER)
+                .unwrap();
+            writer.commit().unwrap();
+        }
+        let reader = index.reader().unwrap();
+        let old = reader.get(FontID(1000)).unwrap().unwrap();
+        assert_eq!(old.metadata.weight_class, Some(300));
+        assert_eq!(reader.partial_faces().unwrap(), 1);
+        drop(reader);
+        let writer = index.writer().unwrap();
+        assert!(writer.needs_update(Path::new(&tagged.path), mtime).unwrap());
+        writer.abort();
+
+        // Version 1 snapshots hold faces in the same older layout.
+        let mut snapshot = SNAPSHOT_MAGIC.to_vec();
+        snapshot.extend_from_slice(&1u32.to_le_bytes());
+        let mut gz = GzEncoder::new(&mut snapshot, Compression::default());
+        let header = SnapshotHeader {
+            roots: "[]".to_string(),
+        };
+        bincode::serialize_into(&mut gz, &header).unwrap();
+        let file = ("/v1/Old.ttf".to_string(), 100u64, 42u64, vec![tagged]);
+        bincode::serialize_into(&mut gz, &Some(file)).unwrap();
+        bincode::serialize_into(&mut gz, &None::<()>).unwrap();
+        gz.finish().unwrap();
+
+        let target = TempDir::new().unwrap();
+        let index = FontIndex::open(target.path()).unwrap();
+        let mut writer = index.writer().unwrap();
+        let stats = writer.import(snapshot.as_slice(), &[]).unwrap();
+        assert_eq!(stats, SnapshotStats { files: 1, faces: 1 });
+        assert!(writer
+            .needs_update_hashed(Path::new("/v1/Old.ttf"), mtime, 42)
+            .unwrap());
+        writer.commit().unwrap();
+        let faces = index
+            .reader()
+            .unwrap()
+            .get_by_path(Path::new("/v1/Old.ttf"))
+            .unwrap();
+        assert_eq!(faces[0].metadata.names[0].as_ref(), "Tagged");
+    }
+
+    fn add_named(index: &FontIndex, path: &str) -> FontID {
+        let mut writer = index.writer().unwrap();
+        let id = writer
+            .add_font(
+                Path::new(path),
+                None,
+                SystemTime::UNIX_EPOCH,
+                vec![path.to_string()],
+                &[],
+                &[],
+                &[],
+                &[],
+                &['a'],
+                false,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        writer.commit().unwrap();
+        id
+    }
+
+    #[test]
+    fn test_cached_bitmaps_follow_new_commits() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let query = Query::new().with_codepoints(vec!['a']);
+        add_named(&index, "/a.ttf");
+        assert_eq!(index.reader().unwrap().find(&query).unwrap().len(), 1);
+        let warm = index.reader().unwrap();
+        assert_eq!(warm.find(&query).unwrap().len(), 1);
+        let cached = |index: &FontIndex| {
+            let cache = index.bitmaps.lock().unwrap();
+            cache.bitmaps.get(&cmap_page_key(0)).map(|b| b.len())
+        };
+        assert_eq!(cached(&index), Some(1));
+
+        add_named(&index, "/b.ttf");
+        // The open reader still sees its own snapshot.
+        assert_eq!(warm.find(&query).unwrap().len(), 1);
+        drop(warm);
+        assert_eq!(index.reader().unwrap().find(&query).unwrap().len(), 2);
+        assert_eq!(cached(&index), Some(2));
+    }
+
+    #[test]
+    fn test_open_ro_reads_but_never_writes() {
+        let dir = TempDir::new().unwrap();
+        let missing = dir.path().join("missing");
+        assert!(FontIndex::open_ro(&missing).is_err());
+        assert!(
+            !missing.exists(),
+            "read-only opens must not create the index"
+        );
+
+        let options = IndexOptions {
+            map_size: 3 << 20,
+            ..IndexOptions::default()
+        };
+        let index = FontIndex::open_with(dir.path(), &options).unwrap();
+        add_named(&index, "/a.ttf");
+        drop(index);
+
+        let index = FontIndex::open_ro(dir.path()).unwrap();
+        assert!(index.options().read_only);
+        assert_eq!(index.count().unwrap(), 1);
+        assert!(index.check().unwrap().is_consistent());
+        let err = index.writer().err().expect("writer on a read-only index");
+        assert!(err.to_string().contains("read-only"), "{err}");
+    }
+
+    #[test]
+    fn test_font_ids_continue_after_reopen() {
+        let dir = TempDir::new().unwrap();
+        let first = {
+            let index = FontIndex::open(dir.path()).unwrap();
+            add_named(&index, "/a.ttf")
+        };
+        let index = FontIndex::open(dir.path()).unwrap();
+        assert_eq!(index.count().unwrap(), 1);
+        assert_eq!(add_named(&index, "/b.ttf"), FontID(first.0 + 1));
+    }
+
+    #[test]
+    fn test_writers_take_ids_from_the_stored_counter() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let a = add_named(&index, "/a.ttf");
+
+        // Another process's writer hands out IDs up to 50 and commits.
+        let mut writer = index.writer().unwrap();
+        writer.next_id = 50;
+        writer
+            .add_font(
+                Path::new("/b.ttf"),
+                None,
+                SystemTime::UNIX_EPOCH,
+                vec!["b".into()],
+                &[],
+                &[],
+                &[],
+                &[],
+                &['a'],
+                false,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        writer.commit().unwrap();
+
+        // This handle's next writer continues after them.
+        let c = add_named(&index, "/c.ttf");
+        assert!(a < FontID(50) && c > FontID(50), "{a:?} {c:?}");
+        assert_eq!(index.count().unwrap(), 3);
+
+        // Removing the newest face does not free its ID for the next one.
+        let mut writer = index.writer().unwrap();
+        writer.remove_path(Path::new("/c.ttf")).unwrap();
+        writer.commit().unwrap();
+        assert!(add_named(&index, "/d.ttf") > c);
+    }
+
+    #[test]
+    fn test_ids_continue_past_byte_boundaries_without_a_counter() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        let mut writer = index.writer().unwrap();
+        for n in 0..300 {
+            writer
+                .add_font(
+                    Path::new(&format!("/f{n}.ttf")),
+                    None,
+                    SystemTime::UNIX_EPOCH,
+                    vec![format!("f{n}")],
+                    &[],
+                    &[],
+                    &[],
+                    &[],
+                    &['a'],
+                    false,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+        }
+        // As written by typg before the counter was stored.
+        writer
+            .index
+            .db_info
+            .delete(&mut writer.wtxn, NEXT_ID_KEY)
+            .unwrap();
+        writer.wtxn.commit().unwrap();
+
+        for n in 0..5 {
+            add_named(&index, &format!("/new{n}.ttf"));
+        }
+        assert_eq!(index.count().unwrap(), 305);
+    }
+
+    #[test]
+    fn test_shared_index_is_opened_once() {
+        let dir = TempDir::new().unwrap();
+        let options = IndexOptions {
+            max_readers: 8,
+            no_read_ahead: true,
+            ..IndexOptions::default()
+        };
+        let a = FontIndex::shared(dir.path(), &options).unwrap();
+        let b = FontIndex::shared(&dir.path().join("."), &IndexOptions::default()).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(b.options(), &options);
+
+        add_named(&a, "/a.ttf");
+        assert_eq!(b.reader().unwrap().list_all().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_readers_wait_for_a_free_slot() {
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let dir = TempDir::new().unwrap();
+        let options = IndexOptions {
+            max_readers: 1,
+            ..IndexOptions::default()
+        };
+        let index = FontIndex::shared(dir.path(), &options).unwrap();
+        add_named(&index, "/a.ttf");
+
+        let held = index.reader().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let waiter = {
+            let index = Arc::clone(&index);
+            std::thread::spawn(move || {
+                let reader = index.reader().unwrap();
+                tx.send(reader.list_all().unwrap().len()).unwrap();
+            })
+        };
+        assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+        drop(held);
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 1);
+        waiter.join().unwrap();
+    }
+
+    #[test]
+    fn test_zero_readers_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let options = IndexOptions {
+            max_readers: 0,
+            ..IndexOptions::default()
+        };
+        assert!(FontIndex::open_with(dir.path(), &options).is_err());
+    }
+
+    #[test]
+    fn test_roots_are_recorded_and_replaced() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(dir.path()).unwrap();
+        assert!(index.reader().unwrap().roots().unwrap().is_empty());
+
+        let root = |path: &str, hidden: bool| ScanRoot {
+            path: PathBuf::from(path),
+            include_hidden: hidden,
+            ..ScanRoot::default()
+        };
+        let mut writer = index.writer().unwrap();
+        writer
+            .record_roots(&[root("/fonts/b", false), root("/fonts/a", false)])
+            .unwrap();
+        writer.commit().unwrap();
+        let mut writer = index.writer().unwrap();
+        writer.record_roots(&[root("/fonts/b", true)]).unwrap();
+        writer.commit().unwrap();
+
+        assert_eq!(
+            index.reader().unwrap().roots().unwrap(),
+            [root("/fonts/a", false), root("/fonts/b", true)]
+        );
+    }
 }
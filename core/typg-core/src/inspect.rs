@@ -0,0 +1,352 @@
+//! Deep dump of one font's tables.
+//!
+//! [`search`](crate::search) extracts the handful of fields queries need and
+//! flattens them — feature tags without their scripts, one preferred name per
+//! ID. When you are looking at a single font you want the rest: every name
+//! record with its language, every table with its length, axis ranges,
+//! which features each script and language system turns on, what the `cmap`
+//! covers, and the OS/2 and `hhea` vertical metrics. [`inspect_font`] reads
+//! all of that into a [`FontInspection`] per face.
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use read_fonts::tables::layout::{FeatureList, LangSys, ScriptList};
+use read_fonts::{FileRef, FontRef, ReadError, TableProvider};
+use serde::Serialize;
+use skrifa::raw::tables::name::Name;
+use skrifa::string::LocalizedString;
+use skrifa::{FontRef as SkrifaFontRef, MetadataProvider};
+
+use crate::blocks::block_of;
+use crate::output::codepoint_ranges;
+use crate::search::{collect_cmap_subtables, CmapSubtable, TypgFontSource};
+use crate::tags::tag_to_string;
+
+/// Everything [`inspect_font`] reads from one face.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct FontInspection {
+    /// File path and collection index.
+    pub source: TypgFontSource,
+    /// Every Unicode or Macintosh Roman `name` record, in table order.
+    pub names: Vec<NameEntry>,
+    /// The table directory, in directory order.
+    pub tables: Vec<TableEntry>,
+    /// `fvar` axes with their ranges; empty for static fonts.
+    pub axes: Vec<AxisInfo>,
+    /// Features per script and language system, GSUB then GPOS.
+    pub layout: Vec<LangSysFeatures>,
+    /// What the `cmap` maps.
+    pub cmap: CmapSummary,
+    /// Selected OS/2 fields; `None` without an OS/2 table.
+    pub os2: Option<Os2Info>,
+    /// `hhea` line metrics; `None` without an `hhea` table.
+    pub hhea: Option<HheaMetrics>,
+    /// Design units per em from `head`.
+    pub units_per_em: Option<u16>,
+}
+
+/// One `name` record.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct NameEntry {
+    /// Name ID, e.g. 1 = family, 4 = full name, 6 = PostScript name.
+    pub name_id: u16,
+    pub platform_id: u16,
+    pub encoding_id: u16,
+    pub language_id: u16,
+    /// BCP-47 tag for `language_id`, e.g. "en-US", when known.
+    pub language: Option<String>,
+    pub value: String,
+}
+
+/// One table directory record.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct TableEntry {
+    pub tag: String,
+    /// Length in bytes, without padding.
+    pub length: u32,
+    pub checksum: u32,
+}
+
+/// One `fvar` axis.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct AxisInfo {
+    pub tag: String,
+    /// Axis name from the `name` table, e.g. "Weight".
+    pub name: Option<String>,
+    pub min: f32,
+    pub default: f32,
+    pub max: f32,
+    /// Whether the axis is flagged hidden from user interfaces.
+    pub hidden: bool,
+}
+
+/// Features one language system of one script turns on.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct LangSysFeatures {
+    /// `GSUB` or `GPOS`.
+    pub table: &'static str,
+    pub script: String,
+    /// Language system tag, trimmed; `None` for the script's default.
+    pub language: Option<String>,
+    /// The required feature first, if any, then the others in list order.
+    pub features: Vec<String>,
+}
+
+/// Summary of the `cmap` table.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct CmapSummary {
+    /// Every encoding record; see [`CmapSubtable`].
+    pub subtables: Vec<CmapSubtable>,
+    /// Number of mapped codepoints.
+    pub codepoints: usize,
+    /// Mapped codepoints as compact ranges (`U+0020-007E, U+00A0-00FF`).
+    pub ranges: String,
+    /// Mapped codepoints per Unicode block, in block order. Codepoints
+    /// outside every block are not counted.
+    pub blocks: BTreeMap<String, usize>,
+}
+
+/// OS/2 classification and vertical metrics.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Os2Info {
+    pub version: u16,
+    pub weight_class: u16,
+    pub width_class: u16,
+    /// Embedding permissions bits (`fsType`).
+    pub fs_type: u16,
+    /// Style bits (`fsSelection`): italic, bold, regular, use typo metrics…
+    pub fs_selection: u16,
+    pub vendor_id: String,
+    pub typo_ascender: i16,
+    pub typo_descender: i16,
+    pub typo_line_gap: i16,
+    pub win_ascent: u16,
+    pub win_descent: u16,
+    /// `sxHeight`, present from version 2.
+    pub x_height: Option<i16>,
+    /// `sCapHeight`, present from version 2.
+    pub cap_height: Option<i16>,
+}
+
+/// `hhea` line metrics.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct HheaMetrics {
+    pub ascender: i16,
+    pub descender: i16,
+    pub line_gap: i16,
+}
+
+/// Inspect every face of a font file on disk.
+pub fn inspect_file(path: &Path) -> Result<Vec<FontInspection>> {
+    let data = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    inspect_font(&data, path).with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Inspect every face of a font held in memory. `path` is only recorded as
+/// the faces' source.
+pub fn inspect_font(data: &[u8], path: &Path) -> Result<Vec<FontInspection>> {
+    FileRef::new(data).map_err(|e| anyhow!("not a font file: {e}"))?;
+    let mut faces = Vec::new();
+    for font in FontRef::fonts(data) {
+        let font = font.map_err(|e| anyhow!("parsing font: {e}"))?;
+        let ttc_index = font.ttc_index();
+        let sfont = match ttc_index {
+            Some(idx) => SkrifaFontRef::from_index(data, idx)?,
+            None => SkrifaFontRef::new(data)?,
+        };
+        let codepoints: Vec<char> = sfont
+            .charmap()
+            .mappings()
+            .filter_map(|(cp, _)| char::from_u32(cp))
+            .collect();
+
+        faces.push(FontInspection {
+            source: TypgFontSource {
+                path: path.to_path_buf(),
+                ttc_index,
+            },
+            names: inspect_names(&font),
+            tables: font
+                .table_directory
+                .table_records()
+                .iter()
+                .map(|rec| TableEntry {
+                    tag: tag_to_string(rec.tag()),
+                    length: rec.length(),
+                    checksum: rec.checksum(),
+                })
+                .collect(),
+            axes: inspect_axes(&sfont),
+            layout: inspect_layout(&font),
+            cmap: summarize_cmap(collect_cmap_subtables(&font), &codepoints),
+            os2: font.os2().ok().map(|os2| Os2Info {
+                version: os2.version(),
+                weight_class: os2.us_weight_class(),
+                width_class: os2.us_width_class(),
+                fs_type: os2.fs_type(),
+                fs_selection: os2.fs_selection().bits(),
+                vendor_id: tag_to_string(os2.ach_vend_id()).trim_end().to_string(),
+                typo_ascender: os2.s_typo_ascender(),
+                typo_descender: os2.s_typo_descender(),
+                typo_line_gap: os2.s_typo_line_gap(),
+                win_ascent: os2.us_win_ascent(),
+                win_descent: os2.us_win_descent(),
+                x_height: os2.sx_height(),
+                cap_height: os2.s_cap_height(),
+            }),
+            hhea: font.hhea().ok().map(|hhea| HheaMetrics {
+                ascender: hhea.ascender().to_i16(),
+                descender: hhea.descender().to_i16(),
+                line_gap: hhea.line_gap().to_i16(),
+            }),
+            units_per_em: font.head().ok().map(|head| head.units_per_em()),
+        });
+    }
+    Ok(faces)
+}
+
+/// Read every decodable `name` record with its language.
+fn inspect_names(font: &FontRef) -> Vec<NameEntry> {
+    let Ok(name) = font.name() else {
+        return Vec::new();
+    };
+    name.name_record()
+        .iter()
+        .filter_map(|record| {
+            let value = record.string(name.string_data()).ok()?.to_string();
+            Some(NameEntry {
+                name_id: record.name_id().to_u16(),
+                platform_id: record.platform_id(),
+                encoding_id: record.encoding_id(),
+                language_id: record.language_id(),
+                language: record_language(&name, record),
+                value,
+            })
+        })
+        .collect()
+}
+
+fn record_language(name: &Name, record: &read_fonts::tables::name::NameRecord) -> Option<String> {
+    LocalizedString::new(name, record)
+        .language()
+        .map(str::to_string)
+}
+
+/// Read the `fvar` axes with their names and ranges.
+fn inspect_axes(font: &SkrifaFontRef) -> Vec<AxisInfo> {
+    font.axes()
+        .iter()
+        .map(|axis| AxisInfo {
+            tag: tag_to_string(axis.tag()),
+            name: font
+                .localized_strings(axis.name_id())
+                .english_or_first()
+                .map(|name| name.to_string()),
+            min: axis.min_value(),
+            default: axis.default_value(),
+            max: axis.max_value(),
+            hidden: axis.is_hidden(),
+        })
+        .collect()
+}
+
+/// List the features of every script and language system in GSUB and GPOS.
+fn inspect_layout(font: &FontRef) -> Vec<LangSysFeatures> {
+    let mut out = Vec::new();
+    if let Ok(gsub) = font.gsub() {
+        layout_features("GSUB", gsub.script_list(), gsub.feature_list(), &mut out);
+    }
+    if let Ok(gpos) = font.gpos() {
+        layout_features("GPOS", gpos.script_list(), gpos.feature_list(), &mut out);
+    }
+    out
+}
+
+fn layout_features(
+    table: &'static str,
+    scripts: Result<ScriptList, ReadError>,
+    features: Result<FeatureList, ReadError>,
+    out: &mut Vec<LangSysFeatures>,
+) {
+    let (Ok(scripts), Ok(features)) = (scripts, features) else {
+        return;
+    };
+    let records = features.feature_records();
+    let tags = |lang_sys: &LangSys| -> Vec<String> {
+        let required = lang_sys.required_feature_index();
+        std::iter::once(required)
+            .filter(|&idx| idx != 0xFFFF)
+            .chain(lang_sys.feature_indices().iter().map(|idx| idx.get()))
+            .filter_map(|idx| records.get(idx as usize))
+            .map(|rec| tag_to_string(rec.feature_tag()))
+            .collect()
+    };
+
+    for record in scripts.script_records() {
+        let Ok(script) = record.script(scripts.offset_data()) else {
+            continue;
+        };
+        let script_tag = tag_to_string(record.script_tag());
+        if let Some(Ok(default)) = script.default_lang_sys() {
+            out.push(LangSysFeatures {
+                table,
+                script: script_tag.clone(),
+                language: None,
+                features: tags(&default),
+            });
+        }
+        for lang in script.lang_sys_records() {
+            if let Ok(lang_sys) = lang.lang_sys(script.offset_data()) {
+                out.push(LangSysFeatures {
+                    table,
+                    script: script_tag.clone(),
+                    language: Some(tag_to_string(lang.lang_sys_tag()).trim_end().to_string()),
+                    features: tags(&lang_sys),
+                });
+            }
+        }
+    }
+}
+
+/// Count codepoints, render their ranges, and tally them per block.
+fn summarize_cmap(subtables: Vec<CmapSubtable>, codepoints: &[char]) -> CmapSummary {
+    let mut blocks = BTreeMap::new();
+    for &cp in codepoints {
+        if let Some(block) = block_of(cp) {
+            *blocks.entry(block.name.to_string()).or_insert(0) += 1;
+        }
+    }
+    CmapSummary {
+        subtables,
+        codepoints: codepoints.len(),
+        ranges: codepoint_ranges(codepoints),
+        blocks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cmap_summary_counts_blocks_and_ranges() {
+        let codepoints: Vec<char> = ('A'..='C').chain(['é', 'Ж']).collect();
+        let summary = summarize_cmap(Vec::new(), &codepoints);
+        assert_eq!(summary.codepoints, 5);
+        assert_eq!(summary.ranges, "U+0041-0043, U+00E9, U+0416");
+        assert_eq!(summary.blocks["Basic Latin"], 3);
+        assert_eq!(summary.blocks["Cyrillic"], 1);
+    }
+}
@@ -0,0 +1,122 @@
+//! Shared storage for repeated strings in large result sets.
+//!
+//! Faces of one family repeat the same family name, the same handful of
+//! style names, and often kilobytes of identical copyright and license text.
+//! A 100k-face result set held by the server or handed to Python would keep
+//! one copy of each per face. [`TypgFontFaceMeta`] stores those strings as
+//! `Arc<str>`, and an [`Interner`] makes equal strings point at one
+//! allocation.
+//!
+//! [`search`](crate::search::search) and the index readers already return
+//! shared results. Anything that builds or loads matches by other means (a
+//! JSON cache, a custom source) can call [`share_strings`].
+//!
+//! Made by FontLab https://www.fontlab.com/
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::search::{TypgFontFaceMatch, TypgFontFaceMeta};
+
+/// Deduplicating store of `Arc<str>` values.
+#[derive(Debug, Default)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    /// Create an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The shared copy of `text`, allocating it on first sight.
+    pub fn intern(&mut self, text: &str) -> Arc<str> {
+        if let Some(shared) = self.strings.get(text) {
+            return Arc::clone(shared);
+        }
+        let shared: Arc<str> = Arc::from(text);
+        self.strings.insert(Arc::clone(&shared));
+        shared
+    }
+
+    /// Point `text` at the shared copy of its contents, adopting it as the
+    /// shared copy if none exists yet.
+    pub fn share(&mut self, text: &mut Arc<str>) {
+        match self.strings.get(&**text) {
+            Some(shared) => *text = Arc::clone(shared),
+            None => {
+                self.strings.insert(Arc::clone(text));
+            }
+        }
+    }
+
+    /// Share every string field of one face's metadata.
+    pub fn share_meta(&mut self, meta: &mut TypgFontFaceMeta) {
+        for text in meta
+            .names
+            .iter_mut()
+            .chain(meta.family_name.as_mut())
+            .chain(meta.style_name.as_mut())
+            .chain(meta.creator_names.iter_mut())
+            .chain(meta.license_names.iter_mut())
+        {
+            self.share(text);
+        }
+        for instance in &mut meta.named_instances {
+            self.share(&mut instance.name);
+            if let Some(postscript_name) = instance.postscript_name.as_mut() {
+                self.share(postscript_name);
+            }
+        }
+    }
+
+    /// Number of distinct strings stored.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Whether nothing has been interned yet.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+/// Make equal strings across `matches` share one allocation.
+pub fn share_strings(matches: &mut [TypgFontFaceMatch]) {
+    let mut interner = Interner::new();
+    for item in matches {
+        interner.share_meta(&mut item.metadata);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intern_returns_one_allocation_per_content() {
+        let mut interner = Interner::new();
+        let a = interner.intern("Regular");
+        let b = interner.intern(&String::from("Regular"));
+        let c = interner.intern("Bold");
+
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn share_adopts_first_copy_and_redirects_later_ones() {
+        let mut interner = Interner::new();
+        let mut first: Arc<str> = Arc::from("SIL Open Font License");
+        let mut second: Arc<str> = Arc::from("SIL Open Font License");
+        let original = Arc::clone(&first);
+
+        interner.share(&mut first);
+        interner.share(&mut second);
+
+        assert!(Arc::ptr_eq(&first, &original));
+        assert!(Arc::ptr_eq(&second, &original));
+    }
+}
@@ -33,6 +33,46 @@
 ///    Queries that would take seconds over thousands of files on disk take
 ///    milliseconds against the index.
 ///
+/// 7. **Classify** ([`classify`]) guesses a style category (serif, sans,
+///    mono, script, display) for fonts whose OS/2 family class is empty.
+///
+/// 8. **Diff** ([`diff`]) compares a fresh scan with saved metadata and
+///    reports faces that gained features, lost coverage, or were reclassified.
+///
+/// 9. **Validate** ([`validate`]) runs a curated set of QA checks (a Rust
+///    take on Font Bakery's structural checks) and reports pass/warn/fail.
+///
+/// 10. **Collector** ([`collector`]) lets embedders register extra per-face
+///     extractors whose results appear under `extra` in the metadata.
+///
+/// 11. **Rank** ([`rank`]) scores fonts by how many criteria they satisfy and
+///     sorts them best-first instead of filtering.
+///
+/// 12. **Family** ([`family`]) groups faces by family, e.g. to keep one
+///     representative face per family.
+///
+/// 13. **Blocks** ([`blocks`]) names Unicode blocks and measures how much of
+///     each one a font covers, for queries like "90% of Cyrillic".
+///
+/// 14. **Presets** ([`presets`]) are ready-made queries for common tasks:
+///     web fonts, Arabic UI text, code.
+///
+/// 15. **Stats** ([`stats`]) summarizes a collection, e.g. how many faces sit
+///     at each standard weight and which families have no bold.
+///
+/// 16. **Inspect** ([`inspect`]) dumps one font in depth: every name record,
+///     the table directory, axis ranges, features per script and language,
+///     a `cmap` summary and OS/2 metrics.
+///
+/// 17. **Select** ([`select`]) picks the face of a family a browser would
+///     use for a requested weight, width and style, by CSS font matching.
+///
+/// 18. **Bench** ([`bench`]) times discovery, reading, parsing and matching
+///     over a corpus and reports fonts and megabytes per second.
+///
+/// 19. **Migrate** ([`migrate`], behind `hpindex`) carries a catalog from the
+///     JSON cache to the index or back without re-reading font files.
+///
 /// # Quick example
 ///
 /// Find all variable fonts with Arabic script support and a weight axis:
@@ -72,10 +112,26 @@
 /// | **OS/2** | A metadata table carrying weight class, width class, font family classification, and other attributes originally designed for IBM's OS/2 operating system (the name stuck). |
 ///
 /// Made by FontLab <https://www.fontlab.com/>
+pub mod bench;
+pub mod blocks;
+pub mod cache;
+pub mod classify;
+pub mod collector;
+pub mod diff;
 pub mod discovery;
+pub mod family;
 #[cfg(feature = "hpindex")]
 pub mod index;
+pub mod inspect;
+pub mod intern;
+#[cfg(feature = "hpindex")]
+pub mod migrate;
 pub mod output;
+pub mod presets;
 pub mod query;
+pub mod rank;
 pub mod search;
+pub mod select;
+pub mod stats;
 pub mod tags;
+pub mod validate;
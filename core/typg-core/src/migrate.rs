@@ -0,0 +1,224 @@
+//! Moving a catalog between the JSON cache and the LMDB index.
+//!
+//! Both stores hold the same [`TypgFontFaceMatch`] records and the same
+//! [`ScanRoot`] list, so a catalog built into one can be carried over to
+//! the other without reading a single font file: [`cache_to_index`] loads
+//! a cache into an index and [`index_to_cache`] writes an index out as a
+//! cache. The target keeps what it already holds; files present in both
+//! take the source's entries.
+//!
+//! The index keeps a face's full metadata only for faces it stored since
+//! it started doing so (see [`IndexedFontMeta::details`]). Older faces
+//! carry over with just their names, classes, tags and codepoints; the
+//! stats count them as `partial`, and `cache add --index` over their
+//! folders stores the rest.
+//!
+//! [`IndexedFontMeta::details`]: crate::index::IndexedFontMeta::details
+//! [`TypgFontFaceMatch`]: crate::search::TypgFontFaceMatch
+//! [`ScanRoot`]: crate::cache::ScanRoot
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::cache::{
+    load_cache, load_roots, merge_entries, record_roots, sort_entries, write_cache, write_roots,
+};
+use crate::index::{IndexReader, IndexWriter};
+
+/// What a migration carried over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MigrateStats {
+    /// Font files.
+    pub files: usize,
+    /// Faces of those files.
+    pub faces: usize,
+    /// Faces that carried over without their full metadata, because the
+    /// index stored them before it kept it.
+    pub partial: usize,
+}
+
+/// Store the cache at `cache_path`, entries and roots, in the index
+/// `writer` writes to, replacing whatever it holds for the same files.
+/// Files keep the mtime and hash the cache recorded for them. Saved with
+/// the writer's commit.
+pub fn cache_to_index(cache_path: &Path, writer: &mut IndexWriter) -> Result<MigrateStats> {
+    let mut entries = load_cache(cache_path)?;
+    sort_entries(&mut entries);
+
+    let mut stats = MigrateStats::default();
+    for faces in entries.chunk_by(|a, b| a.source.path == b.source.path) {
+        let secs = faces[0].metadata.modified.unwrap_or(0);
+        let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        stats.faces += writer.replace_file(&faces[0].source.path, mtime, faces)?;
+        stats.files += 1;
+    }
+    writer.record_roots(&load_roots(cache_path)?)?;
+    Ok(stats)
+}
+
+/// Write every face and root of the index `reader` reads into the cache at
+/// `cache_path`, merged with the entries and roots it already holds.
+pub fn index_to_cache(reader: &IndexReader, cache_path: &Path) -> Result<MigrateStats> {
+    let mut faces = reader.list_all()?;
+    for face in &mut faces {
+        // IDs address the index, and mean nothing in a cache.
+        face.metadata.index_id = None;
+    }
+    let stats = MigrateStats {
+        files: faces
+            .chunk_by(|a, b| a.source.path == b.source.path)
+            .count(),
+        faces: faces.len(),
+        partial: reader.partial_faces()?,
+    };
+
+    let existing = if cache_path.exists() {
+        load_cache(cache_path)?
+    } else {
+        Vec::new()
+    };
+    write_cache(cache_path, &merge_entries(existing, faces))?;
+    let roots = record_roots(load_roots(cache_path)?, reader.roots()?);
+    write_roots(cache_path, &roots)?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::ScanRoot;
+    use crate::index::FontIndex;
+    use crate::search::TypgFontFaceMatch;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn face(path: &str, ttc_index: Option<u32>, name: &str) -> TypgFontFaceMatch {
+        serde_json::from_value(serde_json::json!({
+            "source": { "path": path, "ttc_index": ttc_index },
+            "metadata": {
+                "names": [name],
+                "family_name": "Pair",
+                "style_name": name,
+                "units_per_em": 2048,
+                "glyph_count": 310,
+                "vendor_id": "FLAB",
+                "faces_in_collection": 2,
+                "file_size": 51200,
+                "modified": 100,
+                "file_hash": "000000000000002a",
+                "axis_tags": ["wght"],
+                "feature_tags": ["liga"],
+                "gsub_feature_tags": ["liga"],
+                "gpos_feature_tags": [],
+                "script_tags": ["latn"],
+                "language_tags": [],
+                "table_tags": ["GSUB", "fvar"],
+                "table_checksums": { "GSUB": 3735928559u32 },
+                "codepoints": ["a", "ж"],
+                "is_variable": true,
+                "named_instances": [
+                    { "name": "Bold", "coordinates": { "wght": 700.0 } }
+                ],
+                "axes": [
+                    { "tag": "wght", "min": 100.0, "default": 400.0, "max": 900.0 }
+                ],
+                "weight_class": 400,
+                "extra": { "review": "approved" }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn cache_and_index_round_trip_every_field() {
+        let dir = TempDir::new().unwrap();
+        let cache = dir.path().join("cache.json");
+        let entries = vec![
+            face("/fonts/Pair.ttc", Some(0), "Regular"),
+            face("/fonts/Pair.ttc", Some(1), "Italic"),
+        ];
+        write_cache(&cache, &entries).unwrap();
+        let roots = [ScanRoot {
+            path: PathBuf::from("/fonts"),
+            ..ScanRoot::default()
+        }];
+        write_roots(&cache, &roots).unwrap();
+
+        let index = FontIndex::open(&dir.path().join("index")).unwrap();
+        let mut writer = index.writer().unwrap();
+        let stats = cache_to_index(&cache, &mut writer).unwrap();
+        assert_eq!(
+            stats,
+            MigrateStats {
+                files: 1,
+                faces: 2,
+                partial: 0
+            }
+        );
+        writer.commit().unwrap();
+
+        let back = dir.path().join("back.json");
+        let stats = index_to_cache(&index.reader().unwrap(), &back).unwrap();
+        assert_eq!(
+            stats,
+            MigrateStats {
+                files: 1,
+                faces: 2,
+                partial: 0
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(load_cache(&back).unwrap()).unwrap(),
+            serde_json::to_value(&entries).unwrap()
+        );
+        assert_eq!(load_roots(&back).unwrap()[0].path, roots[0].path);
+    }
+
+    #[test]
+    fn index_to_cache_keeps_other_cache_entries_and_counts_partial_faces() {
+        let dir = TempDir::new().unwrap();
+        let index = FontIndex::open(&dir.path().join("index")).unwrap();
+        let mut writer = index.writer().unwrap();
+        writer
+            .add_font(
+                Path::new("/fonts/Old.ttf"),
+                None,
+                SystemTime::UNIX_EPOCH,
+                vec!["Old".into()],
+                &[],
+                &[],
+                &[],
+                &[],
+                &['a'],
+                false,
+                Some(700),
+                None,
+                None,
+            )
+            .unwrap();
+        writer.commit().unwrap();
+
+        let cache = dir.path().join("cache.json");
+        write_cache(&cache, &[face("/fonts/Kept.ttf", None, "Kept")]).unwrap();
+        let stats = index_to_cache(&index.reader().unwrap(), &cache).unwrap();
+        assert_eq!(
+            stats,
+            MigrateStats {
+                files: 1,
+                faces: 1,
+                partial: 1
+            }
+        );
+
+        let merged = load_cache(&cache).unwrap();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].metadata.names[0].as_ref(), "Kept");
+        assert_eq!(merged[1].metadata.weight_class, Some(700));
+        assert!(merged[1].metadata.index_id.is_none());
+    }
+}
@@ -1,32 +1,468 @@
 /// Output formatting for font search results.
 ///
 /// Made by FontLab https://www.fontlab.com/
+use std::collections::{BTreeMap, HashSet};
 use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-use crate::search::TypgFontFaceMatch;
+use crate::family::{family_key, family_of};
+use crate::search::{TypgFontFaceMatch, TypgFontFaceMeta};
+
+/// How codepoints are represented in JSON output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CodepointFormat {
+    /// One single-character string per codepoint (`["A", "B", "C"]`).
+    #[default]
+    Chars,
+    /// One compact range string (`"U+0020-007E, U+00A0-00FF"`).
+    ///
+    /// Orders of magnitude smaller for CJK fonts. Reading the output back
+    /// into a [`TypgFontFaceMatch`] accepts either form.
+    Ranges,
+}
 
 /// Write results as a single indented JSON array.
-pub fn write_json_pretty(results: &[TypgFontFaceMatch], mut w: impl Write) -> Result<()> {
-    let json = serde_json::to_string_pretty(results)?;
+pub fn write_json_pretty(results: &[TypgFontFaceMatch], w: impl Write) -> Result<()> {
+    write_json_pretty_with(results, w, CodepointFormat::Chars)
+}
+
+/// Write results as a single indented JSON array, choosing how codepoints
+/// are represented.
+pub fn write_json_pretty_with<T: Serialize>(
+    results: &[T],
+    mut w: impl Write,
+    codepoints: CodepointFormat,
+) -> Result<()> {
+    let json = match codepoints {
+        CodepointFormat::Chars => serde_json::to_string_pretty(results)?,
+        CodepointFormat::Ranges => {
+            let values = results
+                .iter()
+                .map(|item| to_json_value(item, codepoints))
+                .collect::<Result<Vec<_>>>()?;
+            serde_json::to_string_pretty(&values)?
+        }
+    };
     w.write_all(json.as_bytes())?;
     Ok(())
 }
 
+/// Where a result set came from, so saved output explains itself later.
+///
+/// Written ahead of the results by [`write_json_envelope`]. An auditor
+/// reading a months-old result file can see which typg produced it, what
+/// was asked, which folders were searched, and when.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Version of typg that ran the search.
+    pub typg_version: String,
+    /// The query as given: command-line arguments after the program name.
+    pub query: Vec<String>,
+    /// Search roots, or the cache/index file the results were read from.
+    pub roots: Vec<PathBuf>,
+    /// When the search ran, as an RFC 3339 UTC timestamp.
+    pub timestamp: String,
+}
+
+impl Provenance {
+    /// Record provenance for a search running now.
+    pub fn capture(query: Vec<String>, roots: Vec<PathBuf>) -> Self {
+        Self {
+            typg_version: env!("CARGO_PKG_VERSION").to_string(),
+            query,
+            roots,
+            timestamp: rfc3339_utc(SystemTime::now()),
+        }
+    }
+}
+
+/// Write results as `{"provenance": {...}, "results": [...]}`.
+pub fn write_json_envelope<T: Serialize>(
+    results: &[T],
+    provenance: &Provenance,
+    mut w: impl Write,
+    codepoints: CodepointFormat,
+) -> Result<()> {
+    let results = results
+        .iter()
+        .map(|item| to_json_value(item, codepoints))
+        .collect::<Result<Vec<_>>>()?;
+    let envelope = json!({ "provenance": provenance, "results": results });
+    w.write_all(serde_json::to_string_pretty(&envelope)?.as_bytes())?;
+    Ok(())
+}
+
+/// Format a time as `YYYY-MM-DDTHH:MM:SSZ`, whole seconds, UTC.
+pub fn rfc3339_utc(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // Civil-from-days (Howard Hinnant), valid for any date after 1970.
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
 /// Write results as newline-delimited JSON (one object per line).
 ///
 /// Each match is serialized on its own line. Suitable for streaming
 /// and line-oriented tools.
-pub fn write_ndjson(results: &[TypgFontFaceMatch], mut w: impl Write) -> Result<()> {
+pub fn write_ndjson(results: &[TypgFontFaceMatch], w: impl Write) -> Result<()> {
+    write_ndjson_with(results, w, CodepointFormat::Chars)
+}
+
+/// Write results as newline-delimited JSON, choosing how codepoints are
+/// represented.
+pub fn write_ndjson_with<T: Serialize>(
+    results: &[T],
+    mut w: impl Write,
+    codepoints: CodepointFormat,
+) -> Result<()> {
     for item in results {
-        let line = serde_json::to_string(item)?;
+        let line = serde_json::to_string(&to_json_value(item, codepoints)?)?;
         w.write_all(line.as_bytes())?;
         w.write_all(b"\n")?;
     }
     Ok(())
 }
 
+/// Serialize one result, rewriting `metadata.codepoints` for
+/// [`CodepointFormat::Ranges`].
+///
+/// Works for anything that serializes with a top-level `metadata` object —
+/// plain matches as well as wrappers that flatten one, like
+/// [`RankedMatch`](crate::rank::RankedMatch).
+pub fn to_json_value<T: Serialize>(item: &T, codepoints: CodepointFormat) -> Result<Value> {
+    let mut value = serde_json::to_value(item)?;
+    if codepoints == CodepointFormat::Ranges {
+        if let Some(slot) = value
+            .get_mut("metadata")
+            .and_then(|meta| meta.get_mut("codepoints"))
+        {
+            let chars: Vec<char> = serde_json::from_value(slot.take())?;
+            *slot = Value::String(codepoint_ranges(&chars));
+        }
+    }
+    Ok(value)
+}
+
+/// Collapse codepoints into a compact range string.
+///
+/// Input is sorted and deduplicated first. Consecutive runs become
+/// `U+XXXX-YYYY`, isolated codepoints `U+XXXX`, joined with `", "`.
+pub fn codepoint_ranges(codepoints: &[char]) -> String {
+    let mut sorted: Vec<u32> = codepoints.iter().map(|&c| c as u32).collect();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let mut parts = Vec::new();
+    let mut iter = sorted.into_iter().peekable();
+    while let Some(start) = iter.next() {
+        let mut end = start;
+        while iter.peek() == Some(&(end + 1)) {
+            end += 1;
+            iter.next();
+        }
+        if start == end {
+            parts.push(format!("U+{start:04X}"));
+        } else {
+            parts.push(format!("U+{start:04X}-{end:04X}"));
+        }
+    }
+    parts.join(", ")
+}
+
+/// Write a fontconfig configuration fragment for the matched fonts.
+///
+/// Every font file gets an `<acceptfont>` pattern matching its `file`
+/// property exactly, so the fonts stay available even where other rules
+/// reject their folder. Patterns rather than globs, because fontconfig
+/// globs have no escape for a `*` or `?` in a path. Families with a style
+/// category ([`classified_as`](crate::search::TypgFontFaceMeta::classified_as))
+/// become `<prefer>` entries of the matching generic family — `sans` for
+/// `sans-serif`, `mono` for `monospace`, `script` for `cursive`, `display`
+/// for `fantasy` — in result order. Save it under
+/// `~/.config/fontconfig/conf.d/` and run `fc-cache`.
+pub fn write_fontconfig(results: &[TypgFontFaceMatch], mut w: impl Write) -> Result<()> {
+    let mut paths = Vec::new();
+    let mut seen_paths = HashSet::new();
+    let mut generics: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    let mut seen_families = HashSet::new();
+    for item in results {
+        if seen_paths.insert(&item.source.path) {
+            paths.push(item.source.path.display().to_string());
+        }
+        let generic = match item.metadata.classified_as.as_deref() {
+            Some("serif") => "serif",
+            Some("sans") => "sans-serif",
+            Some("mono") => "monospace",
+            Some("script") => "cursive",
+            Some("display") => "fantasy",
+            _ => continue,
+        };
+        let family = family_of(&item.metadata);
+        if !family.is_empty() && seen_families.insert((generic, family_key(&item.metadata))) {
+            generics.entry(generic).or_default().push(family);
+        }
+    }
+
+    writeln!(w, r#"<?xml version="1.0"?>"#)?;
+    writeln!(
+        w,
+        r#"<!DOCTYPE fontconfig SYSTEM "urn:fontconfig:fonts.dtd">"#
+    )?;
+    writeln!(w, "<fontconfig>")?;
+    if !paths.is_empty() {
+        writeln!(w, "  <selectfont>")?;
+        writeln!(w, "    <acceptfont>")?;
+        for path in &paths {
+            writeln!(w, "      <pattern>")?;
+            writeln!(
+                w,
+                r#"        <patelt name="file"><string>{}</string></patelt>"#,
+                xml_escape(path)
+            )?;
+            writeln!(w, "      </pattern>")?;
+        }
+        writeln!(w, "    </acceptfont>")?;
+        writeln!(w, "  </selectfont>")?;
+    }
+    for (generic, families) in &generics {
+        writeln!(w, "  <alias>")?;
+        writeln!(w, "    <family>{generic}</family>")?;
+        writeln!(w, "    <prefer>")?;
+        for family in families {
+            writeln!(w, "      <family>{}</family>", xml_escape(family))?;
+        }
+        writeln!(w, "    </prefer>")?;
+        writeln!(w, "  </alias>")?;
+    }
+    writeln!(w, "</fontconfig>")?;
+    Ok(())
+}
+
+/// Write one CSS `@font-face` rule per face.
+///
+/// Each rule names the family ([`family_of`]) and points `src` at the
+/// installed font by name (`local()`) and at the file by its path as given
+/// (`url()`), so a web pipeline can rewrite the URLs. `font-weight` and
+/// `font-stretch` come from OS/2, widened to the named instances' range for
+/// variable fonts; `unicode-range` lists the `cmap` as ranges (see
+/// [`unicode_range`], which `unicode_range_gap` is passed to) so browsers
+/// only fetch fonts a page needs.
+pub fn write_css(
+    results: &[TypgFontFaceMatch],
+    unicode_range_gap: u32,
+    mut w: impl Write,
+) -> Result<()> {
+    for (i, item) in results.iter().enumerate() {
+        let meta = &item.metadata;
+        let family = family_of(meta);
+        let local = match meta.style_name.as_deref() {
+            Some(style) if !style.eq_ignore_ascii_case("regular") => {
+                format!("{family} {style}")
+            }
+            _ => family.to_string(),
+        };
+        let path = item.source.path.display().to_string();
+
+        if i > 0 {
+            writeln!(w)?;
+        }
+        writeln!(w, "@font-face {{")?;
+        writeln!(w, "  font-family: {};", css_string(family))?;
+        let format = css_format(&item.source.path)
+            .map(|format| format!(" format(\"{format}\")"))
+            .unwrap_or_default();
+        writeln!(
+            w,
+            "  src: local({}), url({}){format};",
+            css_string(&local),
+            css_string(&path)
+        )?;
+        if let Some(weight) = css_axis_range(meta, "wght", meta.weight_class.map(f32::from)) {
+            writeln!(w, "  font-weight: {weight};")?;
+        }
+        let stretch = meta.width_class.and_then(width_percent);
+        if let Some(stretch) = css_axis_range(meta, "wdth", stretch) {
+            writeln!(w, "  font-stretch: {stretch};")?;
+        }
+        if let Some(style) = css_style(meta) {
+            writeln!(w, "  font-style: {style};")?;
+        }
+        if !meta.codepoints.is_empty() {
+            writeln!(
+                w,
+                "  unicode-range: {};",
+                unicode_range(&meta.codepoints, unicode_range_gap)
+            )?;
+        }
+        writeln!(w, "}}")?;
+    }
+    Ok(())
+}
+
+/// Express codepoints as a CSS `unicode-range` value.
+///
+/// Runs of consecutive codepoints collapse to `U+XXXX-YYYY`. With a
+/// `max_gap` of 0 the ranges are exact. A larger one also bridges gaps of
+/// up to that many codepoints between runs, which shrinks a Latin or CJK
+/// cmap with scattered holes from hundreds of ranges to a handful; the
+/// price is that a page using a bridged character the font lacks makes the
+/// browser fetch the font anyway, then fall back.
+pub fn unicode_range(codepoints: &[char], max_gap: u32) -> String {
+    let mut sorted: Vec<u32> = codepoints.iter().map(|&c| c as u32).collect();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for cp in sorted {
+        match ranges.last_mut() {
+            Some((_, end)) if cp - *end <= max_gap.saturating_add(1) => *end = cp,
+            _ => ranges.push((cp, cp)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                format!("U+{start:04X}")
+            } else {
+                format!("U+{start:04X}-{end:04X}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A `file://` URI for `path`, made absolute against the current
+/// directory: `/Library/Fonts/Noto Sans.ttf` becomes
+/// `file:///Library/Fonts/Noto%20Sans.ttf`.
+///
+/// Every byte but letters, digits, `-._~`, `/` and `:` is percent-encoded,
+/// so spaces, `#`, `%` and non-ASCII names (as UTF-8) survive, as do file
+/// names that are not UTF-8 at all. Windows paths come out as
+/// `file:///C:/Fonts/arial.ttf` and UNC paths as `file://server/share/x.ttf`.
+pub fn file_uri(path: &Path) -> String {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut bytes = path.as_os_str().as_encoded_bytes().to_vec();
+    if cfg!(windows) {
+        for byte in &mut bytes {
+            if *byte == b'\\' {
+                *byte = b'/';
+            }
+        }
+    }
+
+    let mut uri = String::from(if bytes.starts_with(b"//") {
+        "file:"
+    } else if bytes.starts_with(b"/") {
+        "file://"
+    } else {
+        "file:///"
+    });
+    for byte in bytes {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
+/// A CSS string literal.
+fn css_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The `format()` hint for a font file, from its extension.
+fn css_format(path: &std::path::Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "ttf" => Some("truetype"),
+        "otf" => Some("opentype"),
+        "woff" => Some("woff"),
+        "woff2" => Some("woff2"),
+        _ => None,
+    }
+}
+
+/// The OS/2 value, or for a variable font the `min max` range its named
+/// instances span on `axis` (`100 900`).
+fn css_axis_range(meta: &TypgFontFaceMeta, axis: &str, fixed: Option<f32>) -> Option<String> {
+    let values: Vec<f32> = meta
+        .named_instances
+        .iter()
+        .filter_map(|instance| instance.coordinates.get(axis).copied())
+        .collect();
+    let suffix = if axis == "wdth" { "%" } else { "" };
+    let min = values.iter().copied().reduce(f32::min);
+    let max = values.iter().copied().reduce(f32::max);
+    match (min, max) {
+        (Some(min), Some(max)) if min < max => Some(format!("{min}{suffix} {max}{suffix}")),
+        _ => fixed.map(|value| format!("{value}{suffix}")),
+    }
+}
+
+/// OS/2 `usWidthClass` 1–9 as the CSS `font-stretch` percentage.
+pub(crate) fn width_percent(width_class: u16) -> Option<f32> {
+    const PERCENT: [f32; 9] = [50.0, 62.5, 75.0, 87.5, 100.0, 112.5, 125.0, 150.0, 200.0];
+    PERCENT
+        .get(usize::from(width_class).checked_sub(1)?)
+        .copied()
+}
+
+/// `italic` or `oblique` when the style name says so.
+pub(crate) fn css_style(meta: &TypgFontFaceMeta) -> Option<&'static str> {
+    let style = meta.style_name.as_deref()?.to_lowercase();
+    if style.contains("italic") {
+        Some("italic")
+    } else if style.contains("oblique") {
+        Some("oblique")
+    } else {
+        None
+    }
+}
+
+/// Escape the characters XML text and attribute values cannot hold.
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,18 +476,8 @@ mod tests {
                 ttc_index: None,
             },
             metadata: TypgFontFaceMeta {
-                names: vec!["A".to_string()],
-                axis_tags: Vec::new(),
-                feature_tags: Vec::new(),
-                script_tags: Vec::new(),
-                table_tags: Vec::new(),
-                codepoints: Vec::new(),
-                is_variable: false,
-                weight_class: None,
-                width_class: None,
-                family_class: None,
-                creator_names: Vec::new(),
-                license_names: Vec::new(),
+                names: vec!["A".into()],
+                ..Default::default()
             },
         }
     }
@@ -70,4 +496,68 @@ mod tests {
         let parsed: TypgFontFaceMatch = serde_json::from_str(lines[0]).expect("parse");
         assert_eq!(parsed.source.path, PathBuf::from("/fonts/A.ttf"));
     }
+
+    #[test]
+    fn codepoint_ranges_collapse_runs() {
+        let cps: Vec<char> = ('\u{20}'..='\u{7E}')
+            .chain(['\u{A0}', '\u{A1}', '\u{20AC}'])
+            .collect();
+        assert_eq!(codepoint_ranges(&cps), "U+0020-007E, U+00A0-00A1, U+20AC");
+        assert_eq!(codepoint_ranges(&[]), "");
+    }
+
+    #[test]
+    fn ranges_output_round_trips() {
+        let mut item = sample_match();
+        item.metadata.codepoints = ('A'..='Z').chain(['é']).collect();
+        let mut buf = Vec::new();
+
+        write_ndjson_with(&[item.clone()], &mut buf, CodepointFormat::Ranges).expect("write");
+
+        let text = String::from_utf8(buf).expect("utf8");
+        let raw: Value = serde_json::from_str(text.trim_end()).expect("json");
+        assert_eq!(raw["metadata"]["codepoints"], "U+0041-005A, U+00E9");
+
+        let parsed: TypgFontFaceMatch = serde_json::from_str(text.trim_end()).expect("parse");
+        assert_eq!(parsed.metadata.codepoints, item.metadata.codepoints);
+    }
+
+    #[test]
+    fn rfc3339_formats_utc_dates() {
+        use std::time::Duration;
+
+        assert_eq!(rfc3339_utc(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        let leap_day = UNIX_EPOCH + Duration::from_secs(951_827_696);
+        assert_eq!(rfc3339_utc(leap_day), "2000-02-29T12:34:56Z");
+        let new_year = UNIX_EPOCH + Duration::from_secs(1_735_689_599);
+        assert_eq!(rfc3339_utc(new_year), "2024-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn envelope_wraps_results_with_provenance() {
+        let provenance = Provenance::capture(
+            vec!["find".into(), "--scripts".into(), "arab".into()],
+            vec![PathBuf::from("/fonts")],
+        );
+        let mut buf = Vec::new();
+
+        write_json_envelope(
+            &[sample_match()],
+            &provenance,
+            &mut buf,
+            CodepointFormat::Chars,
+        )
+        .expect("write");
+
+        let raw: Value = serde_json::from_slice(&buf).expect("json");
+        assert_eq!(raw["provenance"]["typg_version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(raw["provenance"]["query"][1], "--scripts");
+        assert_eq!(raw["provenance"]["roots"][0], "/fonts");
+        assert!(raw["provenance"]["timestamp"]
+            .as_str()
+            .is_some_and(|ts| ts.ends_with('Z')));
+        let results: Vec<TypgFontFaceMatch> =
+            serde_json::from_value(raw["results"].clone()).expect("results");
+        assert_eq!(results.len(), 1);
+    }
 }
@@ -0,0 +1,179 @@
+//! Built-in named queries for common tasks.
+//!
+//! Most people looking for fonts ask one of a handful of questions: "what can
+//! I put on a web page?", "what can render an Arabic UI?", "what can I code
+//! in?". Each [`Preset`] answers one of them with a ready-made [`Query`], so
+//! the answer doesn't require knowing which tags, tables and codepoints to
+//! ask for. The criteria are spelled out on each constructor below.
+//!
+//! A preset is an ordinary query. Combine it with further criteria through
+//! [`Query::and`]; the CLI's `--preset` does exactly that with its other
+//! flags.
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use crate::classify::MONO;
+use crate::query::{Query, QueryExpr};
+use crate::tags::tag4;
+
+/// A built-in named query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Preset {
+    /// Fonts that can be served as a web font; see [`Query::webfont_ready`].
+    WebfontReady,
+    /// Fonts that can set Arabic interface text; see [`Query::arabic_ui`].
+    ArabicUi,
+    /// Monospaced fonts for source code; see [`Query::code_fonts`].
+    CodeFonts,
+}
+
+impl Preset {
+    /// Every preset, in the order they are listed in help output.
+    pub const ALL: [Preset; 3] = [Preset::WebfontReady, Preset::ArabicUi, Preset::CodeFonts];
+
+    /// The preset's name as typed on the command line, e.g. `webfont-ready`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::WebfontReady => "webfont-ready",
+            Preset::ArabicUi => "arabic-ui",
+            Preset::CodeFonts => "code-fonts",
+        }
+    }
+
+    /// One-line summary of what the preset selects.
+    pub fn description(self) -> &'static str {
+        match self {
+            Preset::WebfontReady => {
+                "standalone fonts with OS/2 metrics, kerning and printable ASCII"
+            }
+            Preset::ArabicUi => {
+                "Arabic shaping (init/medi/fina/rlig), basic Arabic letters and digits"
+            }
+            Preset::CodeFonts => "monospaced fonts covering printable ASCII",
+        }
+    }
+
+    /// Look a preset up by [`name`](Self::name), ignoring case and treating
+    /// `_` like `-`.
+    pub fn from_name(name: &str) -> Option<Preset> {
+        let wanted = name.trim().to_ascii_lowercase().replace('_', "-");
+        Preset::ALL
+            .into_iter()
+            .find(|preset| preset.name() == wanted)
+    }
+
+    /// The query this preset stands for.
+    pub fn query(self) -> Query {
+        match self {
+            Preset::WebfontReady => Query::webfont_ready(),
+            Preset::ArabicUi => Query::arabic_ui(),
+            Preset::CodeFonts => Query::code_fonts(),
+        }
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parse a preset name, listing the known names when it is not one of them.
+pub fn parse_preset(name: &str) -> Result<Preset> {
+    Preset::from_name(name).ok_or_else(|| {
+        let known: Vec<&str> = Preset::ALL.iter().map(|p| p.name()).collect();
+        anyhow!(
+            "unknown preset '{}' (expected one of: {})",
+            name.trim(),
+            known.join(", ")
+        )
+    })
+}
+
+/// Printable ASCII, U+0020–U+007E: what any Latin UI or source file needs.
+fn printable_ascii() -> Vec<char> {
+    (' '..='~').collect()
+}
+
+impl Query {
+    /// `webfont-ready`: fonts a site can serve as they are.
+    ///
+    /// - a standalone `.ttf`/`.otf` — browsers cannot load a face out of a
+    ///   TTC/OTC collection;
+    /// - `OS/2` and `GPOS` tables, for consistent line metrics and kerning;
+    /// - the `kern` feature;
+    /// - every printable ASCII character (U+0020–U+007E).
+    ///
+    /// Embedding permissions (`fsType`) are not checked.
+    pub fn webfont_ready() -> Query {
+        Query::new()
+            .with_tables(vec![tag4("OS/2").unwrap(), tag4("GPOS").unwrap()])
+            .with_features(vec![tag4("kern").unwrap()])
+            .with_codepoints(printable_ascii())
+            .with_predicate(|meta| meta.faces_in_collection.is_none())
+    }
+
+    /// `arabic-ui`: fonts that shape Arabic interface text correctly.
+    ///
+    /// - the `arab` script in GSUB/GPOS;
+    /// - the joining features `init`, `medi`, `fina` and the required
+    ///   ligatures `rlig` (lam-alef);
+    /// - the basic Arabic letters U+0621–U+063A and U+0641–U+064A;
+    /// - Arabic-Indic digits U+0660–U+0669.
+    pub fn arabic_ui() -> Query {
+        let codepoints: Vec<char> = ('\u{0621}'..='\u{063A}')
+            .chain('\u{0641}'..='\u{064A}')
+            .chain('\u{0660}'..='\u{0669}')
+            .collect();
+        Query::new()
+            .with_scripts(vec![tag4("arab").unwrap()])
+            .with_features(
+                ["fina", "init", "medi", "rlig"]
+                    .into_iter()
+                    .map(|tag| tag4(tag).unwrap())
+                    .collect(),
+            )
+            .with_codepoints(codepoints)
+    }
+
+    /// `code-fonts`: monospaced fonts for editors and terminals.
+    ///
+    /// - classified as `mono` (fixed-pitch flag, PANOSE proportion, or a
+    ///   name keyword, see [`crate::classify`]), or — for index entries,
+    ///   which carry no classification — named `Mono`, `Code` or `Console`;
+    /// - every printable ASCII character (U+0020–U+007E).
+    pub fn code_fonts() -> Query {
+        let classified =
+            Query::new().with_predicate(|meta| meta.classified_as.as_deref() == Some(MONO));
+        let named =
+            Query::new().with_name_patterns(vec![Regex::new(r"(?i)mono|code|console").unwrap()]);
+        Query::new()
+            .with_codepoints(printable_ascii())
+            .with_expr(Some(QueryExpr::Or(vec![
+                QueryExpr::Filter(Box::new(classified)),
+                QueryExpr::Filter(Box::new(named)),
+            ])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preset_names_round_trip() {
+        for preset in Preset::ALL {
+            assert_eq!(Preset::from_name(preset.name()), Some(preset));
+        }
+        assert_eq!(Preset::from_name("Code_Fonts"), Some(Preset::CodeFonts));
+        let err = parse_preset("serif-books").unwrap_err().to_string();
+        assert!(
+            err.contains("webfont-ready, arabic-ui, code-fonts"),
+            "{err}"
+        );
+    }
+}
@@ -4,20 +4,32 @@
 //! When multiple filters are set, they combine with AND logic: a match must
 //! satisfy every active criterion.
 //!
+//! Every tag and name filter has an exclusion twin (`without_*` builders): a
+//! font carrying an excluded tag, or a name matching an excluded pattern, is
+//! rejected. Path globs exclude files before they are even opened.
+//!
+//! For alternatives and exclusions, a [`QueryExpr`] combines sub-queries with
+//! `AND`, `OR`, and `NOT`. [`parse_query_expr`] reads the textual form used by
+//! the CLI's `--query-expr` flag and the HTTP `query` parameter.
+//!
 //! The same query model is reused for live scans, cached searches, and indexed
 //! searches, so filter behavior stays consistent across the CLI, HTTP server,
 //! and Python bindings.
 //!
 //! Made by FontLab <https://www.fontlab.com/>
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::ops::RangeInclusive;
+use std::path::Path;
+use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 use read_fonts::types::Tag;
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
 
-use crate::search::TypgFontFaceMeta;
-use crate::tags::tag4;
+use crate::blocks::{block_coverage, find_block, UnicodeBlock};
+use crate::classify::category_matches_major;
+use crate::search::{Embedding, NamedInstance, TypgFontFaceMeta, TypgFontSource};
+use crate::tags::{tag4, tag_to_string};
 
 /// Filter criteria for font search. Built with chained `with_*` methods.
 ///
@@ -42,6 +54,10 @@ pub struct Query {
     /// Checked against script lists in GSUB and GPOS.
     scripts: Vec<Tag>,
 
+    /// Language system tags the font must declare (e.g., `TRK `, `SRB `).
+    /// Checked against language systems under GSUB and GPOS script records.
+    languages: Vec<Tag>,
+
     /// Top-level table tags the font must contain (e.g., `GSUB`, `CFF `).
     tables: Vec<Tag>,
 
@@ -81,6 +97,109 @@ pub struct Query {
     /// (copyright, license description, license URL). At least one license
     /// string must match at least one pattern.
     license_patterns: Vec<Regex>,
+
+    /// Unicode block coverage thresholds. The font must meet *all* of them.
+    coverage: Vec<CoverageFilter>,
+
+    /// Minimum glyph count (`maxp.numGlyphs`). `None` = no constraint.
+    min_glyphs: Option<u16>,
+
+    /// Bounds on the number of variation axes, inclusive. `None` = no
+    /// constraint.
+    min_axes: Option<usize>,
+    max_axes: Option<usize>,
+
+    /// OS/2 vendor IDs, compared case-insensitively. The font must carry one
+    /// of them.
+    vendors: Vec<String>,
+
+    /// Named instance names (e.g. "SemiBold"). The font must have an `fvar`
+    /// instance matching one of them, compared by [`instance_key`].
+    instances: Vec<String>,
+
+    /// Table checksums the font's table directory must record, by tag.
+    table_checksums: Vec<(Tag, u32)>,
+
+    /// Boolean expression that must also hold, combined with the flat
+    /// criteria above by AND. `None` = no constraint.
+    expr: Option<QueryExpr>,
+
+    /// Axis tags the font must *not* define. Any one present rejects the font.
+    excluded_axes: Vec<Tag>,
+
+    /// Feature tags the font must *not* list (e.g., `kern` to find fonts
+    /// without kerning).
+    excluded_features: Vec<Tag>,
+
+    /// Script tags the font must *not* declare.
+    excluded_scripts: Vec<Tag>,
+
+    /// Table tags the font must *not* contain.
+    excluded_tables: Vec<Tag>,
+
+    /// Regex patterns that reject the font if *any* name matches *any* pattern.
+    excluded_name_patterns: Vec<Regex>,
+
+    /// Path globs (compiled to regexes) that reject matching files.
+    excluded_paths: Vec<Regex>,
+
+    /// When `true`, variable fonts are rejected (static fonts only).
+    static_only: bool,
+
+    /// When `true`, only faces inside TTC/OTC collections match.
+    collections_only: bool,
+
+    /// Face index within a collection the font must have (e.g. `0` for the
+    /// first face of every collection). Implies `collections_only`.
+    collection_index: Option<u32>,
+
+    /// When `true`, faces without a modern Unicode `cmap` subtable are
+    /// rejected. See [`TypgFontFaceMeta::has_unicode_cmap`].
+    unicode_cmap_only: bool,
+
+    /// Least permissive embedding level the font must grant (`editable`
+    /// accepts editable and installable fonts). Fonts without an OS/2
+    /// table are rejected.
+    embedding: Option<Embedding>,
+
+    /// Custom predicates registered by embedding applications.
+    /// A font must satisfy *all* of them.
+    predicates: Vec<Arc<dyn MetaPredicate>>,
+}
+
+/// A custom filter that embedding applications can attach to a [`Query`].
+///
+/// Any `Fn(&TypgFontFaceMeta) -> bool` closure that is `Send + Sync`
+/// implements this trait, so most callers never name it and register a
+/// closure with [`Query::with_predicate`]. Stateful filters can implement the
+/// trait on their own type and register it with [`Query::with_plugin`].
+///
+/// ```
+/// use typg_core::query::Query;
+///
+/// // Only fonts with more than 500 mapped codepoints.
+/// let query = Query::new().with_predicate(|meta| meta.codepoints.len() > 500);
+/// ```
+///
+/// Predicates run after the built-in filters, on rayon worker threads.
+pub trait MetaPredicate: Send + Sync {
+    /// Return `true` if the font passes this filter.
+    fn matches(&self, meta: &TypgFontFaceMeta) -> bool;
+}
+
+impl<F> MetaPredicate for F
+where
+    F: Fn(&TypgFontFaceMeta) -> bool + Send + Sync,
+{
+    fn matches(&self, meta: &TypgFontFaceMeta) -> bool {
+        self(meta)
+    }
+}
+
+impl std::fmt::Debug for dyn MetaPredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MetaPredicate")
+    }
 }
 
 impl Query {
@@ -110,6 +229,13 @@ impl Query {
         self
     }
 
+    /// Require these language systems (e.g., `TRK `, `SRB `). The font must
+    /// declare *all* of them under some script in GSUB or GPOS.
+    pub fn with_languages(mut self, languages: Vec<Tag>) -> Self {
+        self.languages = languages;
+        self
+    }
+
     /// Require these top-level tables. The font file must contain *all*.
     /// Example: `vec![tag4("GSUB")?, tag4("GPOS")?]`
     pub fn with_tables(mut self, tables: Vec<Tag>) -> Self {
@@ -167,6 +293,178 @@ impl Query {
         self
     }
 
+    /// Require minimum coverage of Unicode blocks, e.g. 90% of Cyrillic.
+    pub fn with_coverage(mut self, coverage: Vec<CoverageFilter>) -> Self {
+        self.coverage = coverage;
+        self
+    }
+
+    /// Require at least this many glyphs. Example: `Some(1000)` for fonts
+    /// with broad coverage or many alternates.
+    pub fn with_min_glyphs(mut self, min: Option<u16>) -> Self {
+        self.min_glyphs = min;
+        self
+    }
+
+    /// Require at least this many variation axes. `Some(2)` keeps
+    /// multi-axis designs; every static font has zero.
+    pub fn with_min_axes(mut self, min: Option<usize>) -> Self {
+        self.min_axes = min;
+        self
+    }
+
+    /// Allow at most this many variation axes. `Some(1)` with
+    /// [`require_variable`](Self::require_variable) keeps single-axis
+    /// (typically weight-only) variable fonts.
+    pub fn with_max_axes(mut self, max: Option<usize>) -> Self {
+        self.max_axes = max;
+        self
+    }
+
+    /// Require a named instance called one of these (e.g. `"SemiBold"`).
+    /// Case, spaces and hyphens are ignored, so "Semi Bold" finds "SemiBold".
+    pub fn with_instances(mut self, instances: Vec<String>) -> Self {
+        self.instances = instances
+            .iter()
+            .map(|name| instance_key(name))
+            .filter(|key| !key.is_empty())
+            .collect();
+        self
+    }
+
+    /// Require tables whose table directory checksum is exactly this value,
+    /// e.g. `(tag4("glyf")?, 0xDEADBEEF)`. Pins a search to one build of a
+    /// font, whatever its file is called.
+    pub fn with_table_checksums(mut self, checksums: Vec<(Tag, u32)>) -> Self {
+        self.table_checksums = checksums;
+        self
+    }
+
+    /// Require one of these OS/2 vendor IDs (e.g. `"ADBE"`, `"GOOG"`).
+    /// Trailing spaces are ignored and case does not matter.
+    pub fn with_vendors(mut self, vendors: Vec<String>) -> Self {
+        self.vendors = vendors
+            .into_iter()
+            .map(|vendor| vendor.trim().to_string())
+            .filter(|vendor| !vendor.is_empty())
+            .collect();
+        self
+    }
+
+    /// Require a boolean expression to hold in addition to the flat criteria.
+    pub fn with_expr(mut self, expr: Option<QueryExpr>) -> Self {
+        self.expr = expr;
+        self
+    }
+
+    /// Also require `other` to match, keeping every criterion already set.
+    ///
+    /// Unlike the `with_*` builders, which replace a criterion, this adds
+    /// `other` as a whole to the boolean expression. Used to narrow a
+    /// [`Preset`](crate::presets::Preset) with further filters.
+    pub fn and(mut self, other: Query) -> Self {
+        let other = QueryExpr::Filter(Box::new(other));
+        self.expr = Some(match self.expr.take() {
+            Some(QueryExpr::And(mut children)) => {
+                children.push(other);
+                QueryExpr::And(children)
+            }
+            Some(expr) => QueryExpr::And(vec![expr, other]),
+            None => other,
+        });
+        self
+    }
+
+    /// Reject fonts that define any of these axes.
+    pub fn without_axes(mut self, axes: Vec<Tag>) -> Self {
+        self.excluded_axes = axes;
+        self
+    }
+
+    /// Reject fonts that list any of these features.
+    /// Example: `vec![tag4("kern")?]` finds fonts without kerning.
+    pub fn without_features(mut self, features: Vec<Tag>) -> Self {
+        self.excluded_features = features;
+        self
+    }
+
+    /// Reject fonts that declare any of these scripts.
+    pub fn without_scripts(mut self, scripts: Vec<Tag>) -> Self {
+        self.excluded_scripts = scripts;
+        self
+    }
+
+    /// Reject fonts that contain any of these tables.
+    pub fn without_tables(mut self, tables: Vec<Tag>) -> Self {
+        self.excluded_tables = tables;
+        self
+    }
+
+    /// Reject fonts where any name matches any of these patterns.
+    pub fn without_name_patterns(mut self, patterns: Vec<Regex>) -> Self {
+        self.excluded_name_patterns = patterns;
+        self
+    }
+
+    /// Reject files whose path matches any of these globs.
+    ///
+    /// Compile globs with [`parse_path_glob`]. Files are skipped before they
+    /// are read, so excluding large directories also saves I/O.
+    pub fn without_paths(mut self, globs: Vec<Regex>) -> Self {
+        self.excluded_paths = globs;
+        self
+    }
+
+    /// When `true`, variable fonts are rejected. Default: `false`.
+    pub fn without_variable(mut self, yes: bool) -> Self {
+        self.static_only = yes;
+        self
+    }
+
+    /// Only match faces that live inside a TTC/OTC collection.
+    pub fn require_collection(mut self, yes: bool) -> Self {
+        self.collections_only = yes;
+        self
+    }
+
+    /// Only match the face at this index within its collection.
+    pub fn with_collection_index(mut self, index: Option<u32>) -> Self {
+        self.collection_index = index;
+        self
+    }
+
+    /// Reject faces that map characters only through legacy (format 0/2/6/
+    /// 8/10) or symbol subtables. Default: `false`.
+    pub fn require_unicode_cmap(mut self, yes: bool) -> Self {
+        self.unicode_cmap_only = yes;
+        self
+    }
+
+    /// Require fonts whose `fsType` allows at least this embedding level.
+    /// Example: `Some(Embedding::Installable)` keeps only fonts free of
+    /// embedding restrictions.
+    pub fn with_embedding(mut self, embedding: Option<Embedding>) -> Self {
+        self.embedding = embedding;
+        self
+    }
+
+    /// Add a custom predicate closure. Unlike the other builders this
+    /// appends, so several predicates can be registered; the font must
+    /// satisfy all.
+    pub fn with_predicate<F>(self, predicate: F) -> Self
+    where
+        F: Fn(&TypgFontFaceMeta) -> bool + Send + Sync + 'static,
+    {
+        self.with_plugin(Arc::new(predicate))
+    }
+
+    /// Add a custom predicate implemented as a [`MetaPredicate`] type.
+    /// Appends, like [`with_predicate`](Self::with_predicate).
+    pub fn with_plugin(mut self, plugin: Arc<dyn MetaPredicate>) -> Self {
+        self.predicates.push(plugin);
+        self
+    }
+
     /// The required axis tags, if any.
     pub fn axes(&self) -> &[Tag] {
         &self.axes
@@ -182,6 +480,11 @@ impl Query {
         &self.scripts
     }
 
+    /// The required language system tags, if any.
+    pub fn languages(&self) -> &[Tag] {
+        &self.languages
+    }
+
     /// The required table tags, if any.
     pub fn tables(&self) -> &[Tag] {
         &self.tables
@@ -227,6 +530,309 @@ impl Query {
         &self.license_patterns
     }
 
+    /// The Unicode block coverage thresholds, if any.
+    pub fn coverage(&self) -> &[CoverageFilter] {
+        &self.coverage
+    }
+
+    /// The minimum glyph count, if set.
+    pub fn min_glyphs(&self) -> Option<u16> {
+        self.min_glyphs
+    }
+
+    /// The minimum axis count, if set.
+    pub fn min_axes(&self) -> Option<usize> {
+        self.min_axes
+    }
+
+    /// The maximum axis count, if set.
+    pub fn max_axes(&self) -> Option<usize> {
+        self.max_axes
+    }
+
+    /// The accepted vendor IDs, if any.
+    pub fn vendors(&self) -> &[String] {
+        &self.vendors
+    }
+
+    /// The accepted instance names, normalized by [`instance_key`].
+    pub fn instances(&self) -> &[String] {
+        &self.instances
+    }
+
+    /// The required table checksums, if any.
+    pub fn table_checksums(&self) -> &[(Tag, u32)] {
+        &self.table_checksums
+    }
+
+    /// Whether a named instance passes the instance-name filter. Always
+    /// `true` when no instance names were given.
+    pub fn matches_instance(&self, instance: &NamedInstance) -> bool {
+        self.instances.is_empty() || self.instances.contains(&instance_key(&instance.name))
+    }
+
+    /// The excluded axis tags, if any.
+    pub fn excluded_axes(&self) -> &[Tag] {
+        &self.excluded_axes
+    }
+
+    /// The excluded feature tags, if any.
+    pub fn excluded_features(&self) -> &[Tag] {
+        &self.excluded_features
+    }
+
+    /// The excluded script tags, if any.
+    pub fn excluded_scripts(&self) -> &[Tag] {
+        &self.excluded_scripts
+    }
+
+    /// The excluded table tags, if any.
+    pub fn excluded_tables(&self) -> &[Tag] {
+        &self.excluded_tables
+    }
+
+    /// The excluded name patterns, if any.
+    pub fn excluded_name_patterns(&self) -> &[Regex] {
+        &self.excluded_name_patterns
+    }
+
+    /// The excluded path globs (as compiled regexes), if any.
+    pub fn excluded_paths(&self) -> &[Regex] {
+        &self.excluded_paths
+    }
+
+    /// Whether variable fonts are rejected.
+    pub fn rejects_variable(&self) -> bool {
+        self.static_only
+    }
+
+    /// Whether only collection faces match.
+    pub fn requires_collection(&self) -> bool {
+        self.collections_only || self.collection_index.is_some()
+    }
+
+    /// Whether faces need a modern Unicode `cmap` subtable.
+    pub fn requires_unicode_cmap(&self) -> bool {
+        self.unicode_cmap_only
+    }
+
+    /// The minimum embedding level, if set.
+    pub fn embedding(&self) -> Option<Embedding> {
+        self.embedding
+    }
+
+    /// The required collection face index, if set.
+    pub fn collection_index(&self) -> Option<u32> {
+        self.collection_index
+    }
+
+    /// Test a face's location: excluded path globs plus the collection
+    /// filters.
+    ///
+    /// [`matches`](Self::matches) only sees metadata; callers holding a
+    /// [`TypgFontSource`] check this as well.
+    pub fn matches_source(&self, source: &TypgFontSource) -> bool {
+        if !self.matches_path(&source.path) {
+            return false;
+        }
+        if self.requires_collection() && source.ttc_index.is_none() {
+            return false;
+        }
+        match self.collection_index {
+            Some(index) => source.ttc_index == Some(index),
+            None => true,
+        }
+    }
+
+    /// Test a file path against the excluded path globs.
+    ///
+    /// Returns `false` if any glob matches. Search calls this before opening
+    /// a file; cached and indexed search call it per entry.
+    pub fn matches_path(&self, path: &Path) -> bool {
+        if self.excluded_paths.is_empty() {
+            return true;
+        }
+        let full = path.to_string_lossy().replace('\\', "/");
+        !self.excluded_paths.iter().any(|glob| glob.is_match(&full))
+    }
+
+    /// The boolean expression constraint, if set.
+    pub fn expr(&self) -> Option<&QueryExpr> {
+        self.expr.as_ref()
+    }
+
+    /// The custom predicates, if any.
+    pub fn predicates(&self) -> &[Arc<dyn MetaPredicate>] {
+        &self.predicates
+    }
+
+    /// A copy of this query keeping only its hard constraints.
+    ///
+    /// Drops the positive criteria that [`score`](Self::score) grades
+    /// (required tags, names, codepoints, ranges, classes) and keeps
+    /// exclusions, path globs, the boolean expression, and custom predicates.
+    /// Ranking searches with the relaxed query, then scores every candidate
+    /// against the full one.
+    pub fn relaxed(&self) -> Query {
+        Query {
+            excluded_axes: self.excluded_axes.clone(),
+            excluded_features: self.excluded_features.clone(),
+            excluded_scripts: self.excluded_scripts.clone(),
+            excluded_tables: self.excluded_tables.clone(),
+            excluded_name_patterns: self.excluded_name_patterns.clone(),
+            excluded_paths: self.excluded_paths.clone(),
+            static_only: self.static_only,
+            collections_only: self.collections_only,
+            collection_index: self.collection_index,
+            unicode_cmap_only: self.unicode_cmap_only,
+            embedding: self.embedding,
+            expr: self.expr.clone(),
+            predicates: self.predicates.clone(),
+            ..Query::default()
+        }
+    }
+
+    /// Grade how well a font satisfies the positive criteria, from 0.0 to 1.0.
+    ///
+    /// Where [`matches`](Self::matches) is all-or-nothing, this gives partial
+    /// credit: the fraction of required tags present, the share of requested
+    /// codepoints covered, how much of a name the pattern spans, and how far
+    /// a weight or width falls outside its range. The score is the mean over
+    /// the active criteria; a query with none scores every font 1.0.
+    ///
+    /// Hard constraints (exclusions, expression, predicates) are not graded —
+    /// filter with [`relaxed`](Self::relaxed) first.
+    pub fn score(&self, meta: &TypgFontFaceMeta) -> f32 {
+        let mut parts: Vec<f32> = Vec::new();
+
+        if self.variable_only {
+            parts.push(if meta.is_variable { 1.0 } else { 0.0 });
+        }
+        for (have, want) in [
+            (&meta.axis_tags, &self.axes),
+            (&meta.feature_tags, &self.features),
+            (&meta.script_tags, &self.scripts),
+            (&meta.language_tags, &self.languages),
+            (&meta.table_tags, &self.tables),
+        ] {
+            if !want.is_empty() {
+                let found = want.iter().filter(|tag| have.contains(tag)).count();
+                parts.push(found as f32 / want.len() as f32);
+            }
+        }
+        if let Some(range) = &self.weight_range {
+            parts.push(range_score(meta.weight_class, range, 400.0));
+        }
+        if let Some(range) = &self.width_range {
+            parts.push(range_score(meta.width_class, range, 4.0));
+        }
+        if let Some(filter) = &self.family_class {
+            let ok = filter.matches(meta.family_class, meta.classified_as.as_deref());
+            parts.push(if ok { 1.0 } else { 0.0 });
+        }
+        if !self.codepoints.is_empty() {
+            let available: HashSet<char> = meta.codepoints.iter().copied().collect();
+            let covered = self
+                .codepoints
+                .iter()
+                .filter(|cp| available.contains(cp))
+                .count();
+            parts.push(covered as f32 / self.codepoints.len() as f32);
+        }
+        if !self.name_patterns.is_empty() {
+            parts.push(name_match_quality(&meta.names, &self.name_patterns));
+        }
+        for (names, patterns) in [
+            (&meta.creator_names, &self.creator_patterns),
+            (&meta.license_names, &self.license_patterns),
+        ] {
+            if !patterns.is_empty() {
+                let ok = names
+                    .iter()
+                    .any(|name| patterns.iter().any(|re| re.is_match(name)));
+                parts.push(if ok { 1.0 } else { 0.0 });
+            }
+        }
+
+        for filter in &self.coverage {
+            let ratio = block_coverage(filter.block, &meta.codepoints);
+            parts.push(if filter.min_ratio > 0.0 {
+                (ratio / filter.min_ratio).min(1.0)
+            } else {
+                1.0
+            });
+        }
+        if !self.instances.is_empty() {
+            parts.push(if self.matches_instances(meta) {
+                1.0
+            } else {
+                0.0
+            });
+        }
+        if !self.table_checksums.is_empty() {
+            let found = self
+                .table_checksums
+                .iter()
+                .filter(|&&(tag, sum)| has_table_checksum(meta, tag, sum))
+                .count();
+            parts.push(found as f32 / self.table_checksums.len() as f32);
+        }
+        if self.min_glyphs.is_some()
+            || self.min_axes.is_some()
+            || self.max_axes.is_some()
+            || !self.vendors.is_empty()
+        {
+            parts.push(if self.matches_font_info(meta) {
+                1.0
+            } else {
+                0.0
+            });
+        }
+
+        if parts.is_empty() {
+            1.0
+        } else {
+            parts.iter().sum::<f32>() / parts.len() as f32
+        }
+    }
+
+    /// Check the glyph-count, axis-count and vendor criteria.
+    fn matches_font_info(&self, meta: &TypgFontFaceMeta) -> bool {
+        if let Some(min) = self.min_glyphs {
+            if meta.glyph_count.is_none_or(|count| count < min) {
+                return false;
+            }
+        }
+        let axes = meta.axis_tags.len();
+        if self.min_axes.is_some_and(|min| axes < min)
+            || self.max_axes.is_some_and(|max| axes > max)
+        {
+            return false;
+        }
+        if !self.vendors.is_empty() {
+            let Some(vendor) = meta.vendor_id.as_deref() else {
+                return false;
+            };
+            if !self
+                .vendors
+                .iter()
+                .any(|wanted| wanted.eq_ignore_ascii_case(vendor))
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Check the instance-name criterion against every named instance.
+    fn matches_instances(&self, meta: &TypgFontFaceMeta) -> bool {
+        self.instances.is_empty()
+            || meta
+                .named_instances
+                .iter()
+                .any(|instance| self.matches_instance(instance))
+    }
+
     /// Test a font's metadata against every criterion in this query.
     ///
     /// Returns `true` only if *all* active criteria are satisfied.
@@ -253,10 +859,36 @@ impl Query {
             return false;
         }
 
+        if !contains_all_tags(&meta.language_tags, &self.languages) {
+            return false;
+        }
+
         if !contains_all_tags(&meta.table_tags, &self.tables) {
             return false;
         }
 
+        if self.static_only && meta.is_variable {
+            return false;
+        }
+
+        if self.unicode_cmap_only && !meta.has_unicode_cmap() {
+            return false;
+        }
+
+        if let Some(wanted) = self.embedding {
+            if meta.embedding.is_none_or(|granted| granted < wanted) {
+                return false;
+            }
+        }
+
+        if contains_any_tag(&meta.axis_tags, &self.excluded_axes)
+            || contains_any_tag(&meta.feature_tags, &self.excluded_features)
+            || contains_any_tag(&meta.script_tags, &self.excluded_scripts)
+            || contains_any_tag(&meta.table_tags, &self.excluded_tables)
+        {
+            return false;
+        }
+
         if let Some(range) = &self.weight_range {
             match meta.weight_class {
                 Some(weight) if range.contains(&weight) => {}
@@ -272,18 +904,8 @@ impl Query {
         }
 
         if let Some(filter) = &self.family_class {
-            match meta.family_class {
-                Some((class, subclass)) => {
-                    if class != filter.major {
-                        return false;
-                    }
-                    if let Some(expected_subclass) = filter.subclass {
-                        if subclass != expected_subclass {
-                            return false;
-                        }
-                    }
-                }
-                None => return false,
+            if !filter.matches(meta.family_class, meta.classified_as.as_deref()) {
+                return false;
             }
         }
 
@@ -294,6 +916,26 @@ impl Query {
             }
         }
 
+        if !self
+            .coverage
+            .iter()
+            .all(|filter| filter.matches(&meta.codepoints))
+        {
+            return false;
+        }
+
+        if !self.matches_font_info(meta) || !self.matches_instances(meta) {
+            return false;
+        }
+
+        if !self
+            .table_checksums
+            .iter()
+            .all(|&(tag, sum)| has_table_checksum(meta, tag, sum))
+        {
+            return false;
+        }
+
         if !self.name_patterns.is_empty() {
             let matched = meta
                 .names
@@ -304,6 +946,17 @@ impl Query {
             }
         }
 
+        if !self.excluded_name_patterns.is_empty() {
+            let excluded = meta.names.iter().any(|name| {
+                self.excluded_name_patterns
+                    .iter()
+                    .any(|re| re.is_match(name))
+            });
+            if excluded {
+                return false;
+            }
+        }
+
         if !self.creator_patterns.is_empty() {
             let matched = meta
                 .creator_names
@@ -324,8 +977,281 @@ impl Query {
             }
         }
 
-        true
+        if let Some(expr) = &self.expr {
+            if !expr.matches(meta) {
+                return false;
+            }
+        }
+
+        self.predicates
+            .iter()
+            .all(|predicate| predicate.matches(meta))
+    }
+}
+
+/// A boolean combination of queries.
+///
+/// Leaves are ordinary [`Query`] values, so any filter the flat query supports
+/// can appear inside an expression. `"scripts arab OR hebr"` becomes
+/// `Or([Filter(arab), Filter(hebr)])`; `"(liga AND smcp) OR dlig"` becomes
+/// `Or([And([Filter(liga), Filter(smcp)]), Filter(dlig)])`.
+#[derive(Debug, Clone)]
+pub enum QueryExpr {
+    /// A leaf: the font must satisfy this query.
+    Filter(Box<Query>),
+    /// Every child must match. An empty list matches everything.
+    And(Vec<QueryExpr>),
+    /// At least one child must match. An empty list matches nothing.
+    Or(Vec<QueryExpr>),
+    /// The child must not match.
+    Not(Box<QueryExpr>),
+}
+
+impl QueryExpr {
+    /// Evaluate the expression against one font's metadata.
+    pub fn matches(&self, meta: &TypgFontFaceMeta) -> bool {
+        match self {
+            QueryExpr::Filter(query) => query.matches(meta),
+            QueryExpr::And(children) => children.iter().all(|child| child.matches(meta)),
+            QueryExpr::Or(children) => children.iter().any(|child| child.matches(meta)),
+            QueryExpr::Not(child) => !child.matches(meta),
+        }
+    }
+}
+
+/// Parse a boolean query expression.
+///
+/// Terms are `key:value` pairs joined by `AND`, `OR`, and `NOT` (also `&&`,
+/// `||`, `!`), grouped with parentheses. `NOT` binds tightest, then `AND`,
+/// then `OR`; adjacent terms without an operator are ANDed.
+///
+/// | Key | Value | Example |
+/// |-----|-------|---------|
+/// | `axis` | tag(s) | `axis:wght` |
+/// | `feature` | tag(s) | `feature:liga,smcp` |
+/// | `script` | tag(s) | `script:arab` |
+/// | `lang` | language tag(s) | `lang:TRK` |
+/// | `table` | tag(s) | `table:CFF2` |
+/// | `name`, `creator`, `license` | regex | `name:"Mono$"` |
+/// | `codepoint` | codepoints/ranges | `codepoint:U+0600-U+06FF` |
+/// | `block` | Unicode block (full coverage) | `block:"Greek and Coptic"` |
+/// | `coverage` | block and minimum percent | `coverage:Cyrillic:90%` |
+/// | `text` | sample text | `text:"ß€"` |
+/// | `weight`, `width` | value or range | `weight:300-500` |
+/// | `class` | family class | `class:sans` |
+///
+/// The bare word `variable` requires a variable font. Quote values that
+/// contain spaces or parentheses.
+///
+/// ```
+/// use typg_core::query::parse_query_expr;
+///
+/// let expr = parse_query_expr("(feature:liga AND feature:smcp) OR feature:dlig")?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn parse_query_expr(input: &str) -> Result<QueryExpr> {
+    let tokens = tokenize_expr(input)?;
+    if tokens.is_empty() {
+        return Err(anyhow!("query expression cannot be empty"));
+    }
+    let mut parser = ExprParser { tokens, pos: 0 };
+    let expr = parser.parse_or()?;
+    if let Some(token) = parser.tokens.get(parser.pos) {
+        return Err(anyhow!("unexpected {token} in query expression"));
+    }
+    Ok(expr)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ExprToken {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Term(String),
+}
+
+impl std::fmt::Display for ExprToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExprToken::Open => write!(f, "'('"),
+            ExprToken::Close => write!(f, "')'"),
+            ExprToken::And => write!(f, "AND"),
+            ExprToken::Or => write!(f, "OR"),
+            ExprToken::Not => write!(f, "NOT"),
+            ExprToken::Term(term) => write!(f, "term '{term}'"),
+        }
+    }
+}
+
+fn tokenize_expr(input: &str) -> Result<Vec<ExprToken>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(ExprToken::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(ExprToken::Close);
+            }
+            '!' => {
+                chars.next();
+                tokens.push(ExprToken::Not);
+            }
+            '&' | '|' => {
+                chars.next();
+                if chars.peek() == Some(&ch) {
+                    chars.next();
+                }
+                tokens.push(if ch == '&' {
+                    ExprToken::And
+                } else {
+                    ExprToken::Or
+                });
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')') {
+                        break;
+                    }
+                    chars.next();
+                    if c == '"' {
+                        let mut closed = false;
+                        for q in chars.by_ref() {
+                            if q == '"' {
+                                closed = true;
+                                break;
+                            }
+                            word.push(q);
+                        }
+                        if !closed {
+                            return Err(anyhow!("unterminated quote in query expression"));
+                        }
+                    } else {
+                        word.push(c);
+                    }
+                }
+                tokens.push(match word.to_ascii_uppercase().as_str() {
+                    "AND" => ExprToken::And,
+                    "OR" => ExprToken::Or,
+                    "NOT" => ExprToken::Not,
+                    _ => ExprToken::Term(word),
+                });
+            }
+        }
     }
+
+    Ok(tokens)
+}
+
+struct ExprParser {
+    tokens: Vec<ExprToken>,
+    pos: usize,
+}
+
+impl ExprParser {
+    fn peek(&self) -> Option<&ExprToken> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<ExprToken> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<QueryExpr> {
+        let mut children = vec![self.parse_and()?];
+        while self.peek() == Some(&ExprToken::Or) {
+            self.next();
+            children.push(self.parse_and()?);
+        }
+        Ok(collapse(children, QueryExpr::Or))
+    }
+
+    fn parse_and(&mut self) -> Result<QueryExpr> {
+        let mut children = vec![self.parse_unary()?];
+        loop {
+            match self.peek() {
+                Some(ExprToken::And) => {
+                    self.next();
+                }
+                // Adjacent terms without an operator are implicitly ANDed.
+                Some(ExprToken::Term(_) | ExprToken::Open | ExprToken::Not) => {}
+                _ => break,
+            }
+            children.push(self.parse_unary()?);
+        }
+        Ok(collapse(children, QueryExpr::And))
+    }
+
+    fn parse_unary(&mut self) -> Result<QueryExpr> {
+        match self.next() {
+            Some(ExprToken::Not) => Ok(QueryExpr::Not(Box::new(self.parse_unary()?))),
+            Some(ExprToken::Open) => {
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some(ExprToken::Close) => Ok(inner),
+                    _ => Err(anyhow!("missing ')' in query expression")),
+                }
+            }
+            Some(ExprToken::Term(term)) => Ok(QueryExpr::Filter(Box::new(parse_term(&term)?))),
+            Some(token) => Err(anyhow!("unexpected {token} in query expression")),
+            None => Err(anyhow!("query expression ends unexpectedly")),
+        }
+    }
+}
+
+fn collapse(mut children: Vec<QueryExpr>, wrap: fn(Vec<QueryExpr>) -> QueryExpr) -> QueryExpr {
+    if children.len() == 1 {
+        children.pop().unwrap()
+    } else {
+        wrap(children)
+    }
+}
+
+fn parse_term(term: &str) -> Result<Query> {
+    if term.eq_ignore_ascii_case("variable") {
+        return Ok(Query::new().require_variable(true));
+    }
+
+    let (key, value) = term
+        .split_once(':')
+        .ok_or_else(|| anyhow!("expected key:value in query expression, got '{term}'"))?;
+    if value.is_empty() {
+        return Err(anyhow!("missing value for '{key}' in query expression"));
+    }
+    let tags = || -> Result<Vec<Tag>> { value.split(',').map(tag4).collect() };
+    let regex = || Regex::new(value).map_err(|e| anyhow!("invalid regex '{value}': {e}"));
+
+    let query = Query::new();
+    Ok(match key.to_ascii_lowercase().as_str() {
+        "axis" | "axes" => query.with_axes(tags()?),
+        "feature" | "features" | "feat" => query.with_features(tags()?),
+        "script" | "scripts" => query.with_scripts(tags()?),
+        "lang" | "langs" | "language" => query.with_languages(tags()?),
+        "table" | "tables" => query.with_tables(tags()?),
+        "name" => query.with_name_patterns(vec![regex()?]),
+        "creator" => query.with_creator_patterns(vec![regex()?]),
+        "license" => query.with_license_patterns(vec![regex()?]),
+        "codepoint" | "codepoints" | "cp" => query.with_codepoints(parse_codepoint_list(value)?),
+        "block" => query.with_coverage(vec![parse_unicode_block(value)?]),
+        "coverage" => query.with_coverage(vec![parse_coverage_filter(value)?]),
+        "text" => query.with_codepoints(value.chars().collect()),
+        "weight" => query.with_weight_range(Some(parse_u16_range(value)?)),
+        "width" => query.with_width_range(Some(parse_u16_range(value)?)),
+        "class" | "family-class" => query.with_family_class(Some(parse_family_class(value)?)),
+        other => return Err(anyhow!("unknown query expression key '{other}'")),
+    })
 }
 
 /// Check that `haystack` contains every tag in `needles` (set subset check).
@@ -338,6 +1264,111 @@ fn contains_all_tags(haystack: &[Tag], needles: &[Tag]) -> bool {
     needles.iter().all(|tag| set.contains(tag))
 }
 
+/// Check that the table directory records `sum` for table `tag`.
+fn has_table_checksum(meta: &TypgFontFaceMeta, tag: Tag, sum: u32) -> bool {
+    meta.table_checksums.get(&tag_to_string(tag)) == Some(&sum)
+}
+
+/// Full credit inside the range, decaying linearly to zero `scale` units away.
+fn range_score(value: Option<u16>, range: &RangeInclusive<u16>, scale: f32) -> f32 {
+    let Some(value) = value else {
+        return 0.0;
+    };
+    let distance = if value < *range.start() {
+        range.start() - value
+    } else {
+        value.saturating_sub(*range.end())
+    };
+    (1.0 - distance as f32 / scale).max(0.0)
+}
+
+/// Best name match: 0.5 for any hit, up to 1.0 when the match spans the name.
+fn name_match_quality(names: &[Arc<str>], patterns: &[Regex]) -> f32 {
+    names
+        .iter()
+        .flat_map(|name| {
+            patterns.iter().filter_map(move |re| {
+                let m = re.find(name)?;
+                let span = m.as_str().chars().count() as f32;
+                let total = name.chars().count().max(1) as f32;
+                Some(0.5 + 0.5 * (span / total))
+            })
+        })
+        .fold(0.0, f32::max)
+}
+
+/// Check that `haystack` contains at least one tag from `needles`.
+/// Returns `false` if `needles` is empty (nothing is excluded).
+fn contains_any_tag(haystack: &[Tag], needles: &[Tag]) -> bool {
+    needles.iter().any(|tag| haystack.contains(tag))
+}
+
+/// How [`parse_name_pattern`] compiles a name pattern.
+///
+/// Both default to off, which leaves the pattern as written: a
+/// case-sensitive regex that may match anywhere in a name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NameMatch {
+    /// Match letters regardless of case, as if the pattern began with `(?i)`.
+    pub ignore_case: bool,
+    /// Require the pattern to match a whole name, as if wrapped in `^(?:…)$`.
+    pub exact: bool,
+}
+
+/// Compile a name pattern for [`Query::with_name_patterns`], applying the
+/// case and anchoring choices in `mode`.
+///
+/// With `exact`, `Inter` matches the name "Inter" but not "Inter Display";
+/// alternations are anchored as a whole, so `Inter|Roboto` matches either
+/// name exactly.
+pub fn parse_name_pattern(pattern: &str, mode: NameMatch) -> Result<Regex> {
+    let source = if mode.exact {
+        format!("^(?:{pattern})$")
+    } else {
+        pattern.to_string()
+    };
+    RegexBuilder::new(&source)
+        .case_insensitive(mode.ignore_case)
+        .build()
+        .map_err(|e| anyhow!("invalid regex {pattern}: {e}"))
+}
+
+/// Compile a path glob into an anchored regex for [`Query::without_paths`].
+///
+/// `*` matches within one path component, `**` across components, and `?`
+/// one character. Globs without a `/` are matched against the file name;
+/// globs with a `/` against the whole path (with `/` separators).
+///
+/// Examples: `*Test*`, `*.ttc`, `**/old/**`.
+pub fn parse_path_glob(glob: &str) -> Result<Regex> {
+    let trimmed = glob.trim();
+    if trimmed.is_empty() {
+        return Err(anyhow!("path glob cannot be empty"));
+    }
+
+    // Globs without a slash match the file name, like .gitignore.
+    let mut pattern = String::from(if trimmed.contains('/') {
+        "^"
+    } else {
+        "(?:^|/)"
+    });
+    let mut chars = trimmed.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                pattern.push_str(".*");
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            other => pattern.push_str(&regex::escape(&other.to_string())),
+        }
+    }
+    pattern.push('$');
+
+    Regex::new(&pattern).map_err(|e| anyhow!("invalid path glob {trimmed}: {e}"))
+}
+
 /// Parse a comma-separated list of codepoints or ranges into a `Vec<char>`.
 ///
 /// Accepts single characters ("A"), Unicode escapes ("U+0041"), and ranges
@@ -348,7 +1379,7 @@ pub fn parse_codepoint_list(input: &str) -> Result<Vec<char>> {
         return Ok(result);
     }
 
-    for part in input.split(',') {
+    for part in input.split(',').map(str::trim).filter(|p| !p.is_empty()) {
         if part.contains('-') {
             let pieces: Vec<&str> = part.split('-').collect();
             if pieces.len() != 2 {
@@ -384,6 +1415,15 @@ fn parse_codepoint(token: &str) -> Result<char> {
     char::from_u32(cp).ok_or_else(|| anyhow!("invalid Unicode scalar: U+{cp:04X}"))
 }
 
+/// Comparison key for instance names: lowercase, without spaces, hyphens or
+/// underscores. "Semi Bold", "semi-bold" and "SemiBold" share one key.
+pub fn instance_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
 /// Parse a slice of tag strings (e.g. `"wght"`, `"smcp"`) into `Tag` values.
 ///
 /// Each string must be 1–4 printable ASCII characters.
@@ -391,6 +1431,84 @@ pub fn parse_tag_list(raw: &[String]) -> Result<Vec<Tag>> {
     raw.iter().map(|s| tag4(s)).collect()
 }
 
+/// Parse a table checksum filter: `TAG=CHECKSUM`, e.g. `glyf=0xDEADBEEF`.
+///
+/// The checksum is hexadecimal, with or without a `0x` prefix, as
+/// `typg inspect` prints it. Short tags are space-padded like any other tag.
+pub fn parse_table_checksum(input: &str) -> Result<(Tag, u32)> {
+    let (tag, sum) = input
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected TAG=CHECKSUM, got '{input}'"))?;
+    let tag = tag4(tag.trim())?;
+    let digits = sum.trim();
+    let digits = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+        .unwrap_or(digits);
+    let sum = u32::from_str_radix(digits, 16)
+        .map_err(|_| anyhow!("invalid table checksum '{sum}': expected up to 8 hex digits"))?;
+    Ok((tag, sum))
+}
+
+/// Minimum coverage of a Unicode block, e.g. "at least 90% of Cyrillic".
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoverageFilter {
+    /// The block to measure.
+    pub block: &'static UnicodeBlock,
+    /// Required share of the block's assigned characters, 0.0–1.0.
+    pub min_ratio: f32,
+}
+
+impl CoverageFilter {
+    /// Require complete coverage of `block`.
+    pub fn full(block: &'static UnicodeBlock) -> Self {
+        Self {
+            block,
+            min_ratio: 1.0,
+        }
+    }
+
+    /// Test a sorted codepoint list.
+    pub fn matches(&self, codepoints: &[char]) -> bool {
+        self.is_met(block_coverage(self.block, codepoints))
+    }
+
+    /// Test an already computed coverage ratio.
+    pub fn is_met(&self, ratio: f32) -> bool {
+        // Tolerate float noise so "100%" accepts exactly full coverage.
+        ratio + 1e-6 >= self.min_ratio
+    }
+}
+
+/// Parse a block name into a full-coverage [`CoverageFilter`].
+pub fn parse_unicode_block(input: &str) -> Result<CoverageFilter> {
+    let name = input.trim();
+    find_block(name)
+        .map(CoverageFilter::full)
+        .ok_or_else(|| anyhow!("unknown Unicode block: {name}"))
+}
+
+/// Parse a `Block:percent` coverage spec such as `"Cyrillic:90%"`.
+///
+/// The percentage may omit the `%` sign and must lie in 0–100. Without a
+/// percentage the whole block is required.
+pub fn parse_coverage_filter(input: &str) -> Result<CoverageFilter> {
+    let Some((name, pct)) = input.rsplit_once(':') else {
+        return parse_unicode_block(input);
+    };
+    let pct = pct.trim().trim_end_matches('%').trim();
+    let value: f32 = pct
+        .parse()
+        .map_err(|_| anyhow!("invalid coverage percentage in {input:?}"))?;
+    if !(0.0..=100.0).contains(&value) {
+        return Err(anyhow!("coverage percentage must be 0-100, got {value}"));
+    }
+    Ok(CoverageFilter {
+        min_ratio: value / 100.0,
+        ..parse_unicode_block(name)?
+    })
+}
+
 /// Filter for the OS/2 family-class field.
 ///
 /// `major` selects a broad class such as serif, sans-serif, or script.
@@ -402,6 +1520,30 @@ pub struct FamilyClassFilter {
     pub subclass: Option<u8>,
 }
 
+impl FamilyClassFilter {
+    /// Test a font's OS/2 family class, falling back to its heuristic category.
+    ///
+    /// Fonts with no family class (or major class 0) are matched through
+    /// `classified_as` when the filter names a major class only — a font
+    /// guessed as `sans` satisfies `--family-class sans`. Subclass filters
+    /// never match through the heuristic.
+    pub fn matches(&self, family_class: Option<(u8, u8)>, classified_as: Option<&str>) -> bool {
+        match family_class {
+            Some((major, subclass)) if major != 0 => {
+                major == self.major && self.subclass.is_none_or(|expected| subclass == expected)
+            }
+            Some((0, subclass)) if self.major == 0 => {
+                self.subclass.is_none_or(|expected| subclass == expected)
+            }
+            _ => {
+                self.subclass.is_none()
+                    && classified_as
+                        .is_some_and(|category| category_matches_major(category, self.major))
+            }
+        }
+    }
+}
+
 /// Parse an OS/2 family class specifier into a [`FamilyClassFilter`].
 ///
 /// Accepts numeric values ("8"), hex values ("0x0800"), major.subclass pairs
@@ -452,30 +1594,37 @@ pub fn parse_family_class(input: &str) -> Result<FamilyClassFilter> {
     })
 }
 
+/// Names [`parse_family_class`] accepts for OS/2 family class majors. The
+/// first name listed for each major is its canonical one.
+pub const FAMILY_CLASS_NAMES: &[(&str, u8)] = &[
+    ("none", 0),
+    ("no-class", 0),
+    ("uncategorized", 0),
+    ("oldstyle", 1),
+    ("old-style", 1),
+    ("oldstyle-serif", 1),
+    ("transitional", 2),
+    ("modern", 3),
+    ("clarendon", 4),
+    ("slab", 5),
+    ("slab-serif", 5),
+    ("egyptian", 5),
+    ("freeform", 7),
+    ("freeform-serif", 7),
+    ("sans", 8),
+    ("sans-serif", 8),
+    ("gothic", 8),
+    ("ornamental", 9),
+    ("decorative", 9),
+    ("script", 10),
+    ("symbolic", 12),
+];
+
 fn lookup_family_class_by_name(name: &str) -> Option<u8> {
-    let mut map: HashMap<&str, u8> = HashMap::new();
-    map.insert("none", 0);
-    map.insert("no-class", 0);
-    map.insert("uncategorized", 0);
-    map.insert("oldstyle", 1);
-    map.insert("old-style", 1);
-    map.insert("oldstyle-serif", 1);
-    map.insert("transitional", 2);
-    map.insert("modern", 3);
-    map.insert("clarendon", 4);
-    map.insert("slab", 5);
-    map.insert("slab-serif", 5);
-    map.insert("egyptian", 5);
-    map.insert("freeform", 7);
-    map.insert("freeform-serif", 7);
-    map.insert("sans", 8);
-    map.insert("sans-serif", 8);
-    map.insert("gothic", 8);
-    map.insert("ornamental", 9);
-    map.insert("decorative", 9);
-    map.insert("script", 10);
-    map.insert("symbolic", 12);
-    map.get(name).copied()
+    FAMILY_CLASS_NAMES
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|&(_, major)| major)
 }
 
 fn parse_major_and_subclass(raw: &str) -> Option<(u8, u8)> {
@@ -489,6 +1638,21 @@ fn parse_major_and_subclass(raw: &str) -> Option<(u8, u8)> {
     None
 }
 
+/// Parse an embedding level name: `installable`, `editable`, `preview` or
+/// `restricted`, ignoring case.
+pub fn parse_embedding(input: &str) -> Result<Embedding> {
+    let wanted = input.trim().to_ascii_lowercase();
+    Embedding::ALL
+        .into_iter()
+        .find(|level| level.name() == wanted)
+        .ok_or_else(|| {
+            anyhow!(
+                "unknown embedding level '{}' (expected installable, editable, preview or restricted)",
+                input.trim()
+            )
+        })
+}
+
 /// Parse a single value or range of u16 numbers (e.g., "400" or "300-500").
 pub fn parse_u16_range(input: &str) -> Result<RangeInclusive<u16>> {
     let trimmed = input.trim();
@@ -0,0 +1,113 @@
+//! Relevance ranking for "best font for this job" searches.
+//!
+//! A normal search is a yes/no filter: a font that covers 99 of 100 requested
+//! codepoints is dropped just like one that covers none. Ranking turns the
+//! positive criteria into a score instead (see [`Query::score`]) and sorts
+//! candidates best-first, so near misses show up below the perfect matches
+//! rather than disappearing.
+//!
+//! Exclusions, path globs, boolean expressions, and custom predicates stay
+//! hard filters.
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+use std::cmp::Ordering;
+
+use serde::Serialize;
+
+use crate::query::Query;
+use crate::search::TypgFontFaceMatch;
+
+/// A search result with its relevance score.
+#[derive(Debug, Clone, Serialize)]
+pub struct RankedMatch {
+    /// Relevance from 0.0 (satisfies no criterion) to 1.0 (satisfies all).
+    pub score: f32,
+    /// The matched face; serialized inline next to `score`.
+    #[serde(flatten)]
+    pub face: TypgFontFaceMatch,
+}
+
+/// Score candidates against `query` and sort them best-first.
+///
+/// Candidates failing the query's hard constraints are dropped, as are those
+/// scoring 0.0 or below `min_score`. Ties are broken by path, then TTC index.
+pub fn rank(candidates: Vec<TypgFontFaceMatch>, query: &Query, min_score: f32) -> Vec<RankedMatch> {
+    let hard = query.relaxed();
+    let mut ranked: Vec<RankedMatch> = candidates
+        .into_iter()
+        .filter(|face| hard.matches_path(&face.source.path) && hard.matches(&face.metadata))
+        .map(|face| RankedMatch {
+            score: query.score(&face.metadata),
+            face,
+        })
+        .filter(|item| item.score > 0.0 && item.score >= min_score)
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.face.source.path.cmp(&b.face.source.path))
+            .then_with(|| a.face.source.ttc_index.cmp(&b.face.source.ttc_index))
+    });
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::search::{TypgFontFaceMeta, TypgFontSource};
+
+    fn face(path: &str, codepoints: &[char]) -> TypgFontFaceMatch {
+        TypgFontFaceMatch {
+            source: TypgFontSource {
+                path: PathBuf::from(path),
+                ttc_index: None,
+            },
+            metadata: TypgFontFaceMeta {
+                names: vec![path.into()],
+                codepoints: codepoints.to_vec(),
+                weight_class: Some(400),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn orders_by_coverage_and_drops_zero_scores() {
+        let query = Query::new().with_codepoints(vec!['a', 'b', 'c', 'd']);
+        let candidates = vec![
+            face("/half.ttf", &['a', 'b']),
+            face("/none.ttf", &['x']),
+            face("/full.ttf", &['a', 'b', 'c', 'd']),
+        ];
+
+        let ranked = rank(candidates, &query, 0.0);
+
+        let paths: Vec<_> = ranked.iter().map(|r| r.face.source.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("/full.ttf"), PathBuf::from("/half.ttf")]
+        );
+        assert_eq!(ranked[0].score, 1.0);
+        assert_eq!(ranked[1].score, 0.5);
+    }
+
+    #[test]
+    fn weight_outside_range_gets_partial_credit() {
+        let query = Query::new().with_weight_range(Some(600..=700));
+        let ranked = rank(vec![face("/regular.ttf", &[])], &query, 0.0);
+
+        assert_eq!(ranked.len(), 1);
+        assert!((ranked[0].score - 0.5).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn min_score_filters_weak_candidates() {
+        let query = Query::new().with_codepoints(vec!['a', 'b']);
+        let ranked = rank(vec![face("/half.ttf", &['a'])], &query, 0.75);
+        assert!(ranked.is_empty());
+    }
+}
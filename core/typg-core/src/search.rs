@@ -8,20 +8,32 @@
 //! and OTC can store several faces in a single container.
 //!
 //! Made by FontLab <https://www.fontlab.com/>
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use rayon::prelude::*;
 use rayon::ThreadPoolBuilder;
+use read_fonts::tables::layout::ScriptList;
 use read_fonts::tables::name::NameId;
 use read_fonts::types::Tag;
-use read_fonts::{FontRef, TableProvider};
+use read_fonts::{FileRef, FontRef, TableProvider};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use skrifa::{FontRef as SkrifaFontRef, MetadataProvider};
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
 
+use crate::classify::classify;
+use crate::collector::{run_collectors, MetadataCollector};
 use crate::discovery::{FontDiscovery, PathDiscovery};
+use crate::family::{family_key, family_of};
+use crate::intern::share_strings;
 use crate::query::Query;
 use crate::tags::{tag4, tag_to_string};
 
@@ -33,7 +45,11 @@ use crate::tags::{tag4, tag_to_string};
 ///
 /// All tag vectors are sorted and deduplicated after extraction, so you can
 /// safely use set-intersection logic against them.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Name strings are `Arc<str>` so that faces of one family can share their
+/// family name, style names and license text; see [`crate::intern`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct TypgFontFaceMeta {
     /// Human-readable names for this font face.
     ///
@@ -45,7 +61,23 @@ pub struct TypgFontFaceMeta {
     /// broken name tables.
     ///
     /// Sorted, deduplicated, trimmed of whitespace.
-    pub names: Vec<String>,
+    pub names: Vec<Arc<str>>,
+
+    /// The family this face belongs to.
+    ///
+    /// Typographic family name (name ID 16) when present, else the legacy
+    /// family name (ID 1) — so "Helvetica Neue" for both "Helvetica Neue
+    /// Bold" and "Helvetica Neue Light". Used to group faces by family.
+    /// `None` when the name table has neither.
+    #[serde(default)]
+    pub family_name: Option<Arc<str>>,
+
+    /// The style within the family, e.g. "Bold Italic" or "Condensed Light".
+    ///
+    /// Typographic subfamily name (name ID 17) when present, else the legacy
+    /// subfamily name (ID 2).
+    #[serde(default)]
+    pub style_name: Option<Arc<str>>,
 
     /// Variation axis tags. Empty for static (non-variable) fonts.
     ///
@@ -60,6 +92,7 @@ pub struct TypgFontFaceMeta {
         serialize_with = "serialize_tags",
         deserialize_with = "deserialize_tags"
     )]
+    #[cfg_attr(feature = "json-schema", schemars(with = "Vec<String>"))]
     pub axis_tags: Vec<Tag>,
 
     /// OpenType layout feature tags from GSUB and GPOS tables.
@@ -71,13 +104,37 @@ pub struct TypgFontFaceMeta {
     ///
     /// GSUB features handle glyph *substitution* (replacing one glyph with
     /// another). GPOS features handle glyph *positioning* (adjusting placement).
-    /// Both are merged here because the query doesn't distinguish them.
+    /// Both are merged here because the query doesn't distinguish them; see
+    /// [`gsub_feature_tags`](Self::gsub_feature_tags) and
+    /// [`gpos_feature_tags`](Self::gpos_feature_tags) for the split.
     #[serde(
         serialize_with = "serialize_tags",
         deserialize_with = "deserialize_tags"
     )]
+    #[cfg_attr(feature = "json-schema", schemars(with = "Vec<String>"))]
     pub feature_tags: Vec<Tag>,
 
+    /// Feature tags listed in the GSUB table only.
+    ///
+    /// A `kern` here is a substitution feature — unusual, and handled
+    /// differently by shaping engines than GPOS kerning.
+    #[serde(
+        default,
+        serialize_with = "serialize_tags",
+        deserialize_with = "deserialize_tags"
+    )]
+    #[cfg_attr(feature = "json-schema", schemars(with = "Vec<String>"))]
+    pub gsub_feature_tags: Vec<Tag>,
+
+    /// Feature tags listed in the GPOS table only.
+    #[serde(
+        default,
+        serialize_with = "serialize_tags",
+        deserialize_with = "deserialize_tags"
+    )]
+    #[cfg_attr(feature = "json-schema", schemars(with = "Vec<String>"))]
+    pub gpos_feature_tags: Vec<Tag>,
+
     /// Script tags declaring which writing systems this font supports.
     ///
     /// Read from GSUB and GPOS script lists. Common values: `latn` (Latin),
@@ -92,8 +149,24 @@ pub struct TypgFontFaceMeta {
         serialize_with = "serialize_tags",
         deserialize_with = "deserialize_tags"
     )]
+    #[cfg_attr(feature = "json-schema", schemars(with = "Vec<String>"))]
     pub script_tags: Vec<Tag>,
 
+    /// Language system tags under the GSUB/GPOS script records.
+    ///
+    /// OpenType language tags are uppercase and space-padded: `TRK ` (Turkish),
+    /// `SRB ` (Serbian), `NLD ` (Dutch). A font lists one when it carries
+    /// language-specific shaping — Turkish dotted i in small caps, Serbian
+    /// italic forms, the Dutch IJ ligature. Default language systems are not
+    /// listed.
+    #[serde(
+        default,
+        serialize_with = "serialize_tags",
+        deserialize_with = "deserialize_tags"
+    )]
+    #[cfg_attr(feature = "json-schema", schemars(with = "Vec<String>"))]
+    pub language_tags: Vec<Tag>,
+
     /// Every top-level table present in the font file.
     ///
     /// Useful for structural queries: does this font have `CFF ` (PostScript
@@ -107,8 +180,19 @@ pub struct TypgFontFaceMeta {
         serialize_with = "serialize_tags",
         deserialize_with = "deserialize_tags"
     )]
+    #[cfg_attr(feature = "json-schema", schemars(with = "Vec<String>"))]
     pub table_tags: Vec<Tag>,
 
+    /// The checksum the table directory records for each table, keyed by
+    /// tag as [`table_tags`](Self::table_tags) prints it.
+    ///
+    /// Two builds of a font with a changed `GSUB` differ here even when
+    /// their version strings do not, which pins down one exact build of a
+    /// table across many machines. Empty for entries from caches and
+    /// indexes written before it was recorded.
+    #[serde(default)]
+    pub table_checksums: BTreeMap<String, u32>,
+
     /// Unicode codepoints this font can render, from its `cmap` table.
     ///
     /// The `cmap` (character map) is the font's promise: "give me this
@@ -116,9 +200,24 @@ pub struct TypgFontFaceMeta {
     /// this list, the font has a glyph for it.
     ///
     /// Sorted and deduplicated. Can be large — a CJK font may cover
-    /// 20,000+ codepoints.
+    /// 20,000+ codepoints. Serialized as an array of characters; a compact
+    /// range string (`"U+0020-007E, U+00A0-00FF"`, see
+    /// [`CodepointFormat::Ranges`](crate::output::CodepointFormat::Ranges))
+    /// is accepted when reading back.
+    #[serde(deserialize_with = "deserialize_codepoints")]
     pub codepoints: Vec<char>,
 
+    /// Every `cmap` subtable, in table order: platform, encoding and format.
+    ///
+    /// [`codepoints`](Self::codepoints) comes from whichever subtable the
+    /// parser prefers, so a font that only has a Mac Roman format 0 table or
+    /// a Windows symbol table still lists codepoints — just not ones modern
+    /// text stacks will look up. This list shows what is really there; see
+    /// [`has_unicode_cmap`](Self::has_unicode_cmap). Empty for entries from
+    /// caches and indexes written before it was recorded.
+    #[serde(default)]
+    pub cmap_subtables: Vec<CmapSubtable>,
+
     /// Whether this font has an `fvar` table, making it a variable font.
     ///
     /// Variable fonts contain continuous design axes (weight, width, etc.)
@@ -126,6 +225,25 @@ pub struct TypgFontFaceMeta {
     /// replace an entire family of static fonts.
     pub is_variable: bool,
 
+    /// Named instances from the `fvar` table, in font order.
+    ///
+    /// A variable font's presets — "Light", "SemiBold", "Condensed Bold" —
+    /// each a name plus a position on every axis. Font pickers list these
+    /// rather than the bare axes. Empty for static fonts, and for faces an
+    /// LMDB index stored before it kept full metadata.
+    #[serde(default)]
+    pub named_instances: Vec<NamedInstance>,
+
+    /// Variation axes from the `fvar` table, in font order, with their
+    /// ranges.
+    ///
+    /// [`axis_tags`](Self::axis_tags) says which axes a font has; this says
+    /// how far each goes — `wght` from 100 to 900 with 400 as default. Empty
+    /// for static fonts, and for faces an LMDB index stored before it kept
+    /// full metadata.
+    #[serde(default)]
+    pub axes: Vec<VariationAxis>,
+
     /// OS/2 `usWeightClass` value. Indicates visual weight on a 1–1000 scale.
     ///
     /// Standard values: 100=Thin, 200=ExtraLight, 300=Light, 400=Regular,
@@ -167,7 +285,7 @@ pub struct TypgFontFaceMeta {
     /// Useful for searching by foundry ("Adobe"), designer ("Matthew Carter"),
     /// or license type ("OFL").
     #[serde(default)]
-    pub creator_names: Vec<String>,
+    pub creator_names: Vec<Arc<str>>,
 
     /// License-specific strings from the font's name table.
     ///
@@ -177,7 +295,326 @@ pub struct TypgFontFaceMeta {
     /// Useful for compliance checks: "show me all fonts with an SIL Open
     /// Font License" or "find fonts with no license URL."
     #[serde(default)]
-    pub license_names: Vec<String>,
+    pub license_names: Vec<Arc<str>>,
+
+    /// License description (name ID 13), from the first Unicode record.
+    #[serde(default)]
+    pub license_description: Option<String>,
+
+    /// License info URL (name ID 14), from the first Unicode record.
+    #[serde(default)]
+    pub license_url: Option<String>,
+
+    /// Raw OS/2 `fsType` embedding flags. `None` without an OS/2 table.
+    #[serde(default)]
+    pub fs_type: Option<u16>,
+
+    /// Embedding permission derived from [`fs_type`](Self::fs_type):
+    /// `installable`, `editable`, `preview` or `restricted`.
+    #[serde(default)]
+    pub embedding: Option<Embedding>,
+
+    /// Design units per em from the `head` table (commonly 1000 or 2048).
+    ///
+    /// Every outline coordinate is expressed in these units; renderers divide
+    /// by it to scale glyphs to a point size.
+    #[serde(default)]
+    pub units_per_em: Option<u16>,
+
+    /// Number of glyphs, from `maxp.numGlyphs`.
+    ///
+    /// Includes unencoded glyphs (ligatures, alternates, components), so it
+    /// is usually larger than the codepoint count.
+    #[serde(default)]
+    pub glyph_count: Option<u16>,
+
+    /// Font revision from `head.fontRevision`, rounded to three decimals
+    /// (e.g. `2.003`) the way font editors display it.
+    #[serde(default)]
+    pub font_revision: Option<f64>,
+
+    /// Four-character vendor ID from OS/2 `achVendID` (e.g. "ADBE", "GOOG"),
+    /// with trailing spaces removed.
+    ///
+    /// `None` when the font has no OS/2 table or the ID is blank.
+    #[serde(default)]
+    pub vendor_id: Option<String>,
+
+    /// Number of faces in the collection file this face came from.
+    ///
+    /// `Some(n)` for faces inside a `.ttc`/`.otc` (even when `n` is 1),
+    /// `None` for standalone `.ttf`/`.otf` files. Lets you spot the 30-face
+    /// CJK collections without opening them again.
+    #[serde(default)]
+    pub faces_in_collection: Option<u32>,
+
+    /// xxh3 hash of the whole font file's bytes, shared by every face of a
+    /// collection. Serialized as 16 lowercase hex digits.
+    ///
+    /// Unlike [`TypgFontFaceMatch::font_id`], which identifies a face by its
+    /// metadata, this changes with any edit to the file — so it catches
+    /// fonts modified in place where mtimes can't be trusted (network shares,
+    /// archives), and identical copies share it. `None` for caches written
+    /// before it was recorded.
+    #[serde(
+        default,
+        serialize_with = "serialize_hash",
+        deserialize_with = "deserialize_hash"
+    )]
+    #[cfg_attr(feature = "json-schema", schemars(with = "Option<String>"))]
+    pub file_hash: Option<u64>,
+
+    /// Size of the font file in bytes, shared by every face of a collection.
+    #[serde(default)]
+    pub file_size: Option<u64>,
+
+    /// When the font file was last modified, in seconds since the Unix
+    /// epoch. `None` for fonts read from memory.
+    #[serde(default)]
+    pub modified: Option<u64>,
+
+    /// ID of this face in the LMDB index it was read from (see
+    /// [`FontID`]). `None` for live scans and JSON caches; omitted from
+    /// JSON when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_id: Option<FontID>,
+
+    /// Best-guess style category: `serif`, `sans`, `mono`, `script`, or `display`.
+    ///
+    /// Many fonts leave OS/2 `sFamilyClass` at 0, so this is derived from
+    /// whatever evidence the font carries — fixed-pitch flag, family class,
+    /// PANOSE, name keywords (see [`crate::classify`]). Family-class queries
+    /// fall back to it for unclassified fonts.
+    ///
+    /// `None` when nothing points to a category.
+    #[serde(default)]
+    pub classified_as: Option<String>,
+
+    /// Values produced by custom [`MetadataCollector`]s, keyed by collector.
+    ///
+    /// Empty unless collectors were registered in [`SearchOptions`]; omitted
+    /// from JSON when empty.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl TypgFontFaceMeta {
+    /// Whether the font has a Unicode `cmap` subtable in a modern format
+    /// (4, 12 or 13), as shaping engines and browsers expect.
+    ///
+    /// Fonts whose only subtables are legacy formats (0, 2, 6, 8, 10) or the
+    /// Windows symbol encoding fail. Without recorded
+    /// [`cmap_subtables`](Self::cmap_subtables), falls back to whether any
+    /// codepoint is mapped.
+    pub fn has_unicode_cmap(&self) -> bool {
+        if self.cmap_subtables.is_empty() {
+            return !self.codepoints.is_empty();
+        }
+        self.cmap_subtables
+            .iter()
+            .any(CmapSubtable::is_modern_unicode)
+    }
+
+    /// Content fingerprint of the face: a 64-bit xxh3 hash of its name
+    /// strings, variable flag, OS/2 weight/width/family class and cmap.
+    ///
+    /// Only fields that every metadata source carries — live scans, the JSON
+    /// cache and the LMDB index — go in, so a face hashes the same however it
+    /// was found. The path and TTC index are deliberately left out. The
+    /// encoding is fixed; changing it would change every ID.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Xxh3::new();
+        for name in &self.names {
+            hasher.update(name.as_bytes());
+            hasher.update(&[0]);
+        }
+        hasher.update(&[0xff, u8::from(self.is_variable)]);
+        for value in [self.weight_class, self.width_class] {
+            match value {
+                Some(v) => hasher.update(&[1, (v >> 8) as u8, v as u8]),
+                None => hasher.update(&[0]),
+            }
+        }
+        match self.family_class {
+            Some((major, sub)) => hasher.update(&[1, major, sub]),
+            None => hasher.update(&[0]),
+        }
+        for &cp in &self.codepoints {
+            hasher.update(&(cp as u32).to_le_bytes());
+        }
+        hasher.digest()
+    }
+}
+
+/// A named instance of a variable font: a preset point in its design space.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct NamedInstance {
+    /// Instance name from the `name` table, e.g. "SemiBold" or
+    /// "Condensed Light". Falls back to the name ID ("name 258") when the
+    /// record is missing.
+    pub name: Arc<str>,
+
+    /// PostScript name of the instance ("Inter-SemiBold"), when `fvar`
+    /// points to one.
+    #[serde(default)]
+    pub postscript_name: Option<Arc<str>>,
+
+    /// User-space axis values keyed by axis tag, e.g. `{"wdth": 100.0,
+    /// "wght": 600.0}`.
+    pub coordinates: BTreeMap<String, f32>,
+}
+
+/// A variation axis and its range in user-space units.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct VariationAxis {
+    /// Axis tag, e.g. `wght`.
+    pub tag: String,
+    /// Lowest value the font supports.
+    pub min: f32,
+    /// Value the font uses when the axis is not set.
+    pub default: f32,
+    /// Highest value the font supports.
+    pub max: f32,
+}
+
+/// One `cmap` encoding record: which encoding a subtable claims and how
+/// it is laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct CmapSubtable {
+    /// Platform ID: 0 = Unicode, 1 = Macintosh, 3 = Windows.
+    pub platform_id: u16,
+    /// Platform-specific encoding ID, e.g. 1 = Unicode BMP and 0 = Symbol
+    /// on Windows.
+    pub encoding_id: u16,
+    /// Subtable format: 4 and 12 are the modern ones; 0, 2, 6, 8 and 10
+    /// are legacy; 14 holds variation sequences.
+    pub format: u16,
+}
+
+impl CmapSubtable {
+    /// Whether the encoding is Unicode: platform 0 (other than variation
+    /// sequences, encoding 5), or Windows Unicode BMP (3/1) or full
+    /// repertoire (3/10).
+    pub fn is_unicode(&self) -> bool {
+        match self.platform_id {
+            0 => self.encoding_id != 5,
+            3 => matches!(self.encoding_id, 1 | 10),
+            _ => false,
+        }
+    }
+
+    /// Whether this is the Windows symbol encoding (3/0), which maps
+    /// glyphs into the U+F000 private-use block.
+    pub fn is_symbol(&self) -> bool {
+        self.platform_id == 3 && self.encoding_id == 0
+    }
+
+    /// A Unicode encoding in format 4, 12 or 13.
+    pub fn is_modern_unicode(&self) -> bool {
+        self.is_unicode() && matches!(self.format, 4 | 12 | 13)
+    }
+}
+
+impl fmt::Display for CmapSubtable {
+    /// `platform.encoding:format`, e.g. `3.1:4`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}:{}",
+            self.platform_id, self.encoding_id, self.format
+        )
+    }
+}
+
+/// Embedding permission level from OS/2 `fsType` bits 0–3, most
+/// restrictive first, so `a >= b` means "`a` allows at least what `b`
+/// allows".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum Embedding {
+    /// Bit 1: the font must not be embedded or redistributed without the
+    /// owner's permission.
+    Restricted,
+    /// Bit 2: embeddable for viewing and printing only.
+    Preview,
+    /// Bit 3: embeddable for viewing, printing and editing documents.
+    Editable,
+    /// No bits set: embeddable and installable on the target system.
+    Installable,
+}
+
+impl Embedding {
+    /// Every level, most restrictive first.
+    pub const ALL: [Embedding; 4] = [
+        Embedding::Restricted,
+        Embedding::Preview,
+        Embedding::Editable,
+        Embedding::Installable,
+    ];
+
+    /// The level granted by an `fsType` value.
+    ///
+    /// The usage bits should be exclusive; when several are set, the least
+    /// restrictive one applies, as the OpenType spec directs. The subsetting
+    /// and bitmap-only bits (8, 9) don't change the level.
+    pub fn from_fs_type(fs_type: u16) -> Embedding {
+        if fs_type & 0x0008 != 0 {
+            Embedding::Editable
+        } else if fs_type & 0x0004 != 0 {
+            Embedding::Preview
+        } else if fs_type & 0x0002 != 0 {
+            Embedding::Restricted
+        } else {
+            Embedding::Installable
+        }
+    }
+
+    /// The level's name as used on the command line and in JSON.
+    pub fn name(self) -> &'static str {
+        match self {
+            Embedding::Restricted => "restricted",
+            Embedding::Preview => "preview",
+            Embedding::Editable => "editable",
+            Embedding::Installable => "installable",
+        }
+    }
+}
+
+impl fmt::Display for Embedding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Numeric ID of a face stored in an LMDB index (the `index` module,
+/// hpindex feature).
+///
+/// Assigned when the face is written and kept until the file is re-indexed
+/// or removed, so it addresses one entry of one index for as long as the
+/// file stays put. Unlike [`TypgFontFaceMatch::font_id`] it means nothing
+/// outside its index.
+/// Serializes as a plain number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct FontID(pub u64);
+
+impl fmt::Display for FontID {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::str::FromStr for FontID {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(FontID)
+    }
 }
 
 /// Where a font face lives on disk.
@@ -186,6 +623,7 @@ pub struct TypgFontFaceMeta {
 /// files (`.ttc`/`.otc`) that bundle multiple faces, the `ttc_index`
 /// identifies which face inside the collection this refers to.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct TypgFontSource {
     /// Filesystem path to the font file.
     pub path: PathBuf,
@@ -218,7 +656,10 @@ impl TypgFontSource {
 /// from the font's binary tables ([`TypgFontFaceMeta`]). This is the primary
 /// output type of the search engine — what you iterate over to display results,
 /// build caches, or pipe into downstream tools.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Serializes with a derived `font_id` between `source` and `metadata`; the
+/// field is ignored when reading results back.
+#[derive(Debug, Clone, Deserialize)]
 pub struct TypgFontFaceMatch {
     /// Where the font lives: file path and optional TTC/OTC face index.
     pub source: TypgFontSource,
@@ -226,6 +667,174 @@ pub struct TypgFontFaceMatch {
     pub metadata: TypgFontFaceMeta,
 }
 
+impl TypgFontFaceMatch {
+    /// Stable identifier for this face, as 16 lowercase hex digits.
+    ///
+    /// Derived from [`TypgFontFaceMeta::fingerprint`], so the same face gets
+    /// the same ID on every run and every machine regardless of where the
+    /// file lives — join results by `font_id`, not by path.
+    pub fn font_id(&self) -> String {
+        format!("{:016x}", self.metadata.fingerprint())
+    }
+}
+
+impl Serialize for TypgFontFaceMatch {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("TypgFontFaceMatch", 3)?;
+        state.serialize_field("source", &self.source)?;
+        state.serialize_field("font_id", &self.font_id())?;
+        state.serialize_field("metadata", &self.metadata)?;
+        state.end()
+    }
+}
+
+/// Written out to match the `Serialize` impl above, which adds `font_id`.
+#[cfg(feature = "json-schema")]
+impl schemars::JsonSchema for TypgFontFaceMatch {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "TypgFontFaceMatch".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "object",
+            "description": "A search result: one font face that matched the query.",
+            "properties": {
+                "source": generator.subschema_for::<TypgFontSource>(),
+                "font_id": {
+                    "type": "string",
+                    "description": "Stable identifier of the face's content, as 16 lowercase hex digits.",
+                },
+                "metadata": generator.subschema_for::<TypgFontFaceMeta>(),
+            },
+            "required": ["source", "font_id", "metadata"],
+        })
+    }
+}
+
+/// One typographic family and the matching faces that belong to it.
+///
+/// Produced by [`group_families`]: instead of listing "Inter Regular",
+/// "Inter Bold", "Inter Italic" as three results, they collapse into one
+/// `Inter` entry with three members.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypgFamilyGroup {
+    /// Family name as displayed (see [`family_of`]).
+    pub family: String,
+    /// Faces in this family, ordered by width, weight, then path.
+    pub members: Vec<TypgFamilyMember>,
+}
+
+/// A face within a [`TypgFamilyGroup`]: where it lives and which style it is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypgFamilyMember {
+    /// File path and optional TTC/OTC face index.
+    pub source: TypgFontSource,
+    /// Style name ("Bold Italic"), if the font provides one.
+    pub style: Option<Arc<str>>,
+    /// OS/2 `usWeightClass`.
+    pub weight_class: Option<u16>,
+    /// OS/2 `usWidthClass`.
+    pub width_class: Option<u16>,
+    /// Whether this face is a variable font.
+    pub is_variable: bool,
+}
+
+/// Collapse matches into one entry per typographic family.
+///
+/// Faces are grouped case-insensitively by [`family_key`]. Groups are sorted
+/// by family name so output is stable regardless of search order; members
+/// are sorted by width, then weight, then path.
+pub fn group_families(matches: &[TypgFontFaceMatch]) -> Vec<TypgFamilyGroup> {
+    let mut groups: BTreeMap<String, TypgFamilyGroup> = BTreeMap::new();
+    for item in matches {
+        let group = groups
+            .entry(family_key(&item.metadata))
+            .or_insert_with(|| TypgFamilyGroup {
+                family: family_of(&item.metadata).to_string(),
+                members: Vec::new(),
+            });
+        group.members.push(TypgFamilyMember {
+            source: item.source.clone(),
+            style: item.metadata.style_name.clone(),
+            weight_class: item.metadata.weight_class,
+            width_class: item.metadata.width_class,
+            is_variable: item.metadata.is_variable,
+        });
+    }
+
+    groups
+        .into_values()
+        .map(|mut group| {
+            group.members.sort_by(|a, b| {
+                a.width_class
+                    .cmp(&b.width_class)
+                    .then_with(|| a.weight_class.cmp(&b.weight_class))
+                    .then_with(|| a.source.path.cmp(&b.source.path))
+                    .then_with(|| a.source.ttc_index.cmp(&b.source.ttc_index))
+            });
+            group
+        })
+        .collect()
+}
+
+/// One row of an instance listing: a named instance of a variable font, or a
+/// face that stands for itself.
+///
+/// Produced by [`expand_instances`], so a variable font with nine named
+/// instances shows up as nine rows a font picker can offer directly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypgInstanceRow {
+    /// File path and optional TTC/OTC face index.
+    pub source: TypgFontSource,
+    /// Family name as displayed (see [`family_of`]).
+    pub family: String,
+    /// Instance name, or the face's style name when it has no instances.
+    pub style: Option<Arc<str>>,
+    /// PostScript name of the instance, if the font provides one.
+    pub postscript_name: Option<Arc<str>>,
+    /// Axis values of the instance; empty for faces without instances.
+    pub coordinates: BTreeMap<String, f32>,
+}
+
+/// List each named instance as its own row.
+///
+/// Faces without named instances — static fonts, or entries from the LMDB
+/// index — become one row each. When `query` filters by instance name
+/// (see [`Query::with_instances`]), only the matching instances are listed.
+/// Rows keep the order of `matches`, and instances their `fvar` order.
+pub fn expand_instances(matches: &[TypgFontFaceMatch], query: &Query) -> Vec<TypgInstanceRow> {
+    let mut rows = Vec::new();
+    for item in matches {
+        let family = family_of(&item.metadata).to_string();
+        if item.metadata.named_instances.is_empty() {
+            rows.push(TypgInstanceRow {
+                source: item.source.clone(),
+                family,
+                style: item.metadata.style_name.clone(),
+                postscript_name: None,
+                coordinates: BTreeMap::new(),
+            });
+            continue;
+        }
+        for instance in &item.metadata.named_instances {
+            if !query.matches_instance(instance) {
+                continue;
+            }
+            rows.push(TypgInstanceRow {
+                source: item.source.clone(),
+                family: family.clone(),
+                style: Some(Arc::clone(&instance.name)),
+                postscript_name: instance.postscript_name.clone(),
+                coordinates: instance.coordinates.clone(),
+            });
+        }
+    }
+    rows
+}
+
 /// Controls how the search engine runs: parallelism and traversal behavior.
 #[derive(Debug, Default, Clone)]
 pub struct SearchOptions {
@@ -236,12 +845,279 @@ pub struct SearchOptions {
     /// folders (common on macOS and Linux).
     pub follow_symlinks: bool,
 
+    /// Walk into hidden directories and read hidden files (names starting
+    /// with `.`, e.g. `.Trash` or `._Font.ttf` AppleDouble files).
+    ///
+    /// Off by default, so trash folders, VCS metadata and macOS resource
+    /// forks on shared volumes are not parsed. Roots are always walked.
+    pub include_hidden: bool,
+
     /// Number of parallel worker threads for font parsing.
     ///
     /// `None` (the default) uses all available CPU cores via rayon's
     /// default thread pool. Set to `Some(1)` for single-threaded
     /// operation (useful for debugging or constrained environments).
     pub jobs: Option<usize>,
+
+    /// Extra metadata extractors run on every face.
+    ///
+    /// Results land in [`TypgFontFaceMeta::extra`]. Empty by default.
+    pub collectors: Vec<Arc<dyn MetadataCollector>>,
+
+    /// `name` table IDs whose strings populate [`TypgFontFaceMeta::names`].
+    ///
+    /// `None` (the default) reads [`DEFAULT_NAME_IDS`]. Add 25 (variations
+    /// PostScript name prefix) or any other ID to make it searchable with
+    /// name patterns. Style classification always looks at the default IDs,
+    /// so it does not shift with this setting.
+    pub name_ids: Option<Vec<u16>>,
+
+    /// What to do with files that cannot be read or parsed.
+    ///
+    /// [`ErrorPolicy::Skip`] by default: count them in the
+    /// [`SearchReport`] and move on.
+    pub error_policy: ErrorPolicy,
+
+    /// Faces parsed by earlier searches, reused for files whose mtime and
+    /// size are unchanged. `None` (the default) parses every file.
+    pub scan_cache: Option<Arc<ScanCache>>,
+
+    /// When to give up: once it passes, the directory walk stops, no
+    /// further file is opened, and the search returns what it found so far
+    /// with [`SearchReport::truncated`] set. Files already being read are
+    /// finished first. `None` (the default) runs to completion.
+    pub deadline: Option<Instant>,
+}
+
+impl SearchOptions {
+    /// Whether [`deadline`](Self::deadline) has passed.
+    fn out_of_time(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+/// How a search treats font files it cannot read or parse.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorPolicy {
+    /// Count the file in [`SearchReport::errors`] and carry on.
+    #[default]
+    Skip,
+    /// Carry on, and also record a [`SearchError`] per file in
+    /// [`SearchReport::failures`].
+    Collect,
+    /// Stop the search at the first bad file and return its [`SearchError`]
+    /// as the error. With parallel parsing, "first" means whichever worker
+    /// hits one first; matches already delivered by [`search_each`] stay
+    /// delivered.
+    Fail,
+}
+
+/// A font file a search could not use, and why.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchError {
+    /// The file that failed.
+    pub path: PathBuf,
+    /// What went wrong, e.g. `reading file: Permission denied (os error 13)`.
+    pub reason: String,
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.reason)
+    }
+}
+
+impl std::error::Error for SearchError {}
+
+/// A file with a font name that is debris from copying fonts around, not a
+/// font. Searches skip these before parsing and count them apart from
+/// errors (see [`SearchReport::apple_double`] and
+/// [`SearchReport::empty_files`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyArtifact {
+    /// A macOS AppleDouble file (`._Font.ttf`): the resource fork and
+    /// Finder metadata of `Font.ttf`, which macOS writes next to it on SMB
+    /// shares, FAT/exFAT volumes and in zip archives.
+    AppleDouble,
+    /// A zero-byte file, typically left by an interrupted copy.
+    Empty,
+}
+
+impl CopyArtifact {
+    /// Which artifact `path` is, if any, judging by its name and size.
+    pub fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy();
+        if name.starts_with("._") {
+            return Some(Self::AppleDouble);
+        }
+        match fs::metadata(path) {
+            Ok(meta) if meta.is_file() && meta.len() == 0 => Some(Self::Empty),
+            _ => None,
+        }
+    }
+}
+
+/// Name IDs read into [`TypgFontFaceMeta::names`] unless
+/// [`SearchOptions::name_ids`] says otherwise: family (1), typographic
+/// family (16), subfamily (2), typographic subfamily (17), full name (4)
+/// and PostScript name (6).
+pub const DEFAULT_NAME_IDS: [u16; 6] = [1, 16, 2, 17, 4, 6];
+
+/// What a search did: how much it read, how much matched, what failed.
+///
+/// Returned by [`search_with_report`], [`search_each`] and
+/// [`search_streaming`]. Its
+/// `Display` form is the one-line summary the CLI prints to stderr:
+/// `scanned 12,034 files in 3.2s, 211 matches, 4 errors`, followed by
+/// `, 3 AppleDouble files skipped` or `, 1 empty file skipped` when a
+/// search met [`CopyArtifact`]s.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SearchReport {
+    /// Font files considered (after path exclusions).
+    pub files_scanned: usize,
+    /// Faces that matched the query.
+    pub matches: usize,
+    /// Files that could not be read or parsed and were skipped.
+    pub errors: usize,
+    /// One entry per skipped file, sorted by path. Only filled under
+    /// [`ErrorPolicy::Collect`]; otherwise empty even when `errors > 0`.
+    pub failures: Vec<SearchError>,
+    /// AppleDouble (`._*`) files skipped unparsed; not counted in `errors`.
+    pub apple_double: usize,
+    /// Zero-byte files skipped unparsed; not counted in `errors`.
+    pub empty_files: usize,
+    /// The search hit [`SearchOptions::deadline`] and stopped early, so
+    /// the matches are incomplete.
+    pub truncated: bool,
+    /// Wall-clock time for discovery, parsing, and filtering.
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for SearchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "scanned {} in {:.1}s, {}, {}",
+            plural(self.files_scanned, "file", "files"),
+            self.elapsed.as_secs_f64(),
+            plural(self.matches, "match", "matches"),
+            plural(self.errors, "error", "errors"),
+        )?;
+        if self.apple_double > 0 {
+            let files = plural(self.apple_double, "AppleDouble file", "AppleDouble files");
+            write!(f, ", {files} skipped")?;
+        }
+        if self.empty_files > 0 {
+            let files = plural(self.empty_files, "empty file", "empty files");
+            write!(f, ", {files} skipped")?;
+        }
+        if self.truncated {
+            write!(f, "; out of time, results are incomplete")?;
+        }
+        Ok(())
+    }
+}
+
+/// Running counts shared by the workers of one search.
+#[derive(Default)]
+struct Tally {
+    scanned: AtomicUsize,
+    matched: AtomicUsize,
+    errors: AtomicUsize,
+    failures: Mutex<Vec<SearchError>>,
+    apple_double: AtomicUsize,
+    empty_files: AtomicUsize,
+    truncated: AtomicBool,
+}
+
+impl Tally {
+    /// Read one candidate file and return its faces that match `query`,
+    /// applying the error policy to files that fail to load. Copy artifacts
+    /// are counted and skipped without being read.
+    fn scan(
+        &self,
+        path: &Path,
+        query: &Query,
+        opts: &SearchOptions,
+    ) -> Result<Vec<TypgFontFaceMatch>> {
+        if opts.out_of_time() {
+            self.truncated.store(true, Ordering::Relaxed);
+            return Ok(Vec::new());
+        }
+        self.scanned.fetch_add(1, Ordering::Relaxed);
+        match CopyArtifact::detect(path) {
+            Some(CopyArtifact::AppleDouble) => {
+                self.apple_double.fetch_add(1, Ordering::Relaxed);
+                return Ok(Vec::new());
+            }
+            Some(CopyArtifact::Empty) => {
+                self.empty_files.fetch_add(1, Ordering::Relaxed);
+                return Ok(Vec::new());
+            }
+            None => {}
+        }
+        let faces = match load_metadata(path, opts) {
+            Ok(faces) => faces,
+            Err(err) => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+                let failure = || SearchError {
+                    path: path.to_path_buf(),
+                    reason: format!("{err:#}"),
+                };
+                match opts.error_policy {
+                    ErrorPolicy::Skip => {}
+                    ErrorPolicy::Collect => self
+                        .failures
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .push(failure()),
+                    ErrorPolicy::Fail => return Err(failure().into()),
+                }
+                return Ok(Vec::new());
+            }
+        };
+        let matches: Vec<TypgFontFaceMatch> = faces
+            .into_iter()
+            .filter(|face| query.matches_source(&face.source) && query.matches(&face.metadata))
+            .collect();
+        self.matched.fetch_add(matches.len(), Ordering::Relaxed);
+        Ok(matches)
+    }
+
+    fn into_report(self, started: Instant) -> SearchReport {
+        let mut failures = self
+            .failures
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        failures.sort_by(|a, b| a.path.cmp(&b.path));
+        SearchReport {
+            files_scanned: self.scanned.into_inner(),
+            matches: self.matched.into_inner(),
+            errors: self.errors.into_inner(),
+            failures,
+            apple_double: self.apple_double.into_inner(),
+            empty_files: self.empty_files.into_inner(),
+            truncated: self.truncated.into_inner(),
+            elapsed: started.elapsed(),
+        }
+    }
+}
+
+/// Format a count with thousands separators and the matching noun.
+fn plural(count: usize, one: &str, many: &str) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(ch);
+    }
+    let noun = if count == 1 { one } else { many };
+    format!("{grouped} {noun}")
 }
 
 /// Search directories for fonts matching a query. The main entry point.
@@ -251,92 +1127,211 @@ pub struct SearchOptions {
 /// path (then by TTC index within each path).
 ///
 /// This function collects all results in memory before returning — use
-/// [`search_streaming`] if you want results delivered as they're found
-/// (better for CLI output where users want to see progress immediately).
+/// [`search_each`] or [`search_streaming`] if you want results delivered as
+/// they're found (better for CLI output where users want to see progress
+/// immediately).
 ///
-/// Corrupt or unreadable font files are silently skipped. The search
-/// never fails because of a single bad file.
+/// Corrupt or unreadable font files are skipped unless
+/// [`SearchOptions::error_policy`] is [`ErrorPolicy::Fail`].
 pub fn search(
     paths: &[PathBuf],
     query: &Query,
     opts: &SearchOptions,
 ) -> Result<Vec<TypgFontFaceMatch>> {
-    let discovery = PathDiscovery::new(paths.iter().cloned()).follow_symlinks(opts.follow_symlinks);
+    search_with_report(paths, query, opts).map(|(matches, _)| matches)
+}
+
+/// Like [`search`], but also return a [`SearchReport`] counting scanned
+/// files, matches, and skipped files.
+pub fn search_with_report(
+    paths: &[PathBuf],
+    query: &Query,
+    opts: &SearchOptions,
+) -> Result<(Vec<TypgFontFaceMatch>, SearchReport)> {
+    let started = Instant::now();
+    let discovery = PathDiscovery::new(paths.iter().cloned())
+        .follow_symlinks(opts.follow_symlinks)
+        .include_hidden(opts.include_hidden)
+        .deadline(opts.deadline);
     let candidates = discovery.discover()?;
+    let tally = Tally::default();
+    if opts.out_of_time() {
+        // The walk may have stopped short.
+        tally.truncated.store(true, Ordering::Relaxed);
+    }
 
-    let run_search = || -> Vec<TypgFontFaceMatch> {
-        let mut matches: Vec<TypgFontFaceMatch> = candidates
+    let run_search = || -> Result<Vec<TypgFontFaceMatch>> {
+        let per_file: Vec<Vec<TypgFontFaceMatch>> = candidates
             .par_iter()
-            .flat_map_iter(|loc| match load_metadata(&loc.path) {
-                Ok(faces) => faces,
-                Err(_) => Vec::new(),
-            })
-            .filter(|face| query.matches(&face.metadata))
-            .collect();
+            .filter(|loc| query.matches_path(&loc.path))
+            .map(|loc| tally.scan(&loc.path, query, opts))
+            .collect::<Result<_>>()?;
 
+        let mut matches: Vec<TypgFontFaceMatch> = per_file.into_iter().flatten().collect();
         sort_matches(&mut matches);
-        matches
+        share_strings(&mut matches);
+        Ok(matches)
     };
 
     let matches = if let Some(jobs) = opts.jobs {
         let pool = ThreadPoolBuilder::new().num_threads(jobs).build()?;
-        pool.install(run_search)
+        pool.install(run_search)?
     } else {
-        run_search()
+        run_search()?
     };
 
-    Ok(matches)
+    Ok((matches, tally.into_report(started)))
 }
 
-/// Search directories and stream results as they're discovered.
+/// Search directories and hand each match to `on_match` the moment it's found.
 ///
-/// Unlike [`search`], this doesn't wait until all fonts are processed. Each
-/// match is sent through the channel (`tx`) the moment it's found. Results
-/// arrive in arbitrary order — whichever thread finishes parsing a font
-/// first sends its matches first.
+/// Unlike [`search`], nothing is collected: a match is passed on as soon as
+/// its font has been parsed, so the first results of a minutes-long scan
+/// show up immediately. Results arrive in arbitrary order — whichever thread
+/// finishes parsing a font first delivers its matches first.
 ///
-/// Use this for line-oriented output (plain text, paths, NDJSON) where the
-/// user benefits from seeing results immediately. The CLI's default output
-/// mode uses streaming so results start appearing while the scan is still
-/// running.
+/// `on_match` runs on the rayon worker threads, possibly several at once,
+/// hence `Fn + Sync`: put shared state (an output writer, a dedup set)
+/// behind a `Mutex`. The call returns once every candidate has been
+/// processed.
 ///
-/// The sender (`tx`) is cloned across worker threads via rayon's
-/// `for_each_with`. When all threads finish, every clone is dropped, which
-/// closes the channel — the receiver knows the search is complete.
+/// Corrupt or unreadable font files are handled per
+/// [`SearchOptions::error_policy`]; by default they are skipped and counted
+/// in the returned [`SearchReport`].
 ///
-/// Corrupt or unreadable font files are silently skipped.
-pub fn search_streaming(
+/// ```no_run
+/// use std::path::PathBuf;
+/// use typg_core::query::Query;
+/// use typg_core::search::{search_each, SearchOptions};
+///
+/// let roots = [PathBuf::from("/Library/Fonts")];
+/// let report = search_each(&roots, &Query::new(), &SearchOptions::default(), |m| {
+///     println!("{}", m.source.path_with_index());
+/// })?;
+/// eprintln!("{report}");
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn search_each<F>(
     paths: &[PathBuf],
     query: &Query,
     opts: &SearchOptions,
-    tx: Sender<TypgFontFaceMatch>,
-) -> Result<()> {
-    let discovery = PathDiscovery::new(paths.iter().cloned()).follow_symlinks(opts.follow_symlinks);
+    on_match: F,
+) -> Result<SearchReport>
+where
+    F: Fn(TypgFontFaceMatch) + Sync,
+{
+    search_each_file(paths, query, opts, |faces| {
+        faces.into_iter().for_each(&on_match)
+    })
+}
+
+/// Like [`search_each`], but hand over each file's matches together, in
+/// collection order, as soon as that file has been parsed. Files without a
+/// match are not reported.
+///
+/// For consumers that store a file at a time, such as an index writer fed
+/// over a channel while the scan is still running.
+pub fn search_each_file<F>(
+    paths: &[PathBuf],
+    query: &Query,
+    opts: &SearchOptions,
+    on_file: F,
+) -> Result<SearchReport>
+where
+    F: Fn(Vec<TypgFontFaceMatch>) + Sync,
+{
+    let started = Instant::now();
+    let discovery = PathDiscovery::new(paths.iter().cloned())
+        .follow_symlinks(opts.follow_symlinks)
+        .include_hidden(opts.include_hidden)
+        .deadline(opts.deadline);
     let candidates = discovery.discover()?;
+    let tally = Tally::default();
+    if opts.out_of_time() {
+        // The walk may have stopped short.
+        tally.truncated.store(true, Ordering::Relaxed);
+    }
 
-    let run_search = || {
+    let run_search = || -> Result<()> {
         candidates
             .par_iter()
-            .for_each_with(tx, |tx, loc| match load_metadata(&loc.path) {
-                Ok(faces) => {
-                    for face in faces {
-                        if query.matches(&face.metadata) {
-                            let _ = tx.send(face);
-                        }
-                    }
+            .filter(|loc| query.matches_path(&loc.path))
+            .try_for_each(|loc| {
+                let faces = tally.scan(&loc.path, query, opts)?;
+                if !faces.is_empty() {
+                    on_file(faces);
                 }
-                Err(_) => {}
-            });
+                Ok(())
+            })
     };
 
     if let Some(jobs) = opts.jobs {
         let pool = ThreadPoolBuilder::new().num_threads(jobs).build()?;
-        pool.install(run_search);
+        pool.install(run_search)?;
     } else {
-        run_search();
+        run_search()?;
     }
 
-    Ok(())
+    Ok(tally.into_report(started))
+}
+
+/// Search directories and stream results through a channel.
+///
+/// The channel flavour of [`search_each`]: each match is sent through `tx`
+/// as soon as it's found. Run it on a separate thread and consume the
+/// receiver on yours. When the search finishes `tx` is dropped, which closes
+/// the channel — the receiver knows the search is complete.
+///
+/// Send errors (the receiver hung up early) are ignored; the scan still runs
+/// to completion.
+pub fn search_streaming(
+    paths: &[PathBuf],
+    query: &Query,
+    opts: &SearchOptions,
+    tx: Sender<TypgFontFaceMatch>,
+) -> Result<SearchReport> {
+    search_each(paths, query, opts, |face| {
+        let _ = tx.send(face);
+    })
+}
+
+/// Read exactly the font files in `paths`, in parallel, without walking
+/// directories or filtering. The fast path when the caller already knows
+/// its files (a DAM, a build manifest).
+///
+/// Returns every face of every readable file, in the order of `paths` and
+/// then by collection index, along with a [`SearchReport`] whose `matches`
+/// counts the faces. Files are not checked for font extensions, and a
+/// directory in `paths` counts as a file that could not be read. Bad files
+/// are handled per [`SearchOptions::error_policy`]; `follow_symlinks` and
+/// `include_hidden` do not apply.
+pub fn scan_files(
+    paths: &[PathBuf],
+    opts: &SearchOptions,
+) -> Result<(Vec<TypgFontFaceMatch>, SearchReport)> {
+    let started = Instant::now();
+    let query = Query::new();
+    let tally = Tally::default();
+
+    let run_scan = || -> Result<Vec<TypgFontFaceMatch>> {
+        let per_file: Vec<Vec<TypgFontFaceMatch>> = paths
+            .par_iter()
+            .map(|path| tally.scan(path, &query, opts))
+            .collect::<Result<_>>()?;
+
+        let mut faces: Vec<TypgFontFaceMatch> = per_file.into_iter().flatten().collect();
+        share_strings(&mut faces);
+        Ok(faces)
+    };
+
+    let faces = if let Some(jobs) = opts.jobs {
+        let pool = ThreadPoolBuilder::new().num_threads(jobs).build()?;
+        pool.install(run_scan)?
+    } else {
+        run_scan()?
+    };
+
+    Ok((faces, tally.into_report(started)))
 }
 
 /// Filter pre-loaded font metadata against a query. No disk I/O.
@@ -352,7 +1347,7 @@ pub fn search_streaming(
 pub fn filter_cached(entries: &[TypgFontFaceMatch], query: &Query) -> Vec<TypgFontFaceMatch> {
     let mut matches: Vec<TypgFontFaceMatch> = entries
         .iter()
-        .filter(|entry| query.matches(&entry.metadata))
+        .filter(|entry| query.matches_source(&entry.source) && query.matches(&entry.metadata))
         .cloned()
         .collect();
 
@@ -370,34 +1365,276 @@ pub fn filter_cached(entries: &[TypgFontFaceMatch], query: &Query) -> Vec<TypgFo
 /// tables, names, OS/2 classification) and `skrifa` for higher-level APIs
 /// (cmap/charmap iteration). Both crates come from Google's fontations
 /// project.
-fn load_metadata(path: &Path) -> Result<Vec<TypgFontFaceMatch>> {
-    let data = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+///
+/// With a [`SearchOptions::scan_cache`], an unchanged file's faces come from
+/// the cache and newly parsed ones are added to it.
+fn load_metadata(path: &Path, opts: &SearchOptions) -> Result<Vec<TypgFontFaceMatch>> {
+    let stat = fs::metadata(path).ok();
+    if let (Some(cache), Some(stat)) = (&opts.scan_cache, &stat) {
+        if let Some(faces) = cache.lookup(path, stat) {
+            return Ok(faces);
+        }
+    }
+
+    // Callers attach the path (see `SearchError`), so messages here omit it.
+    let data = fs::read(path).context("reading file")?;
+    let mut faces = read_metadata(&data, path, opts)?;
+    let modified = stat.as_ref().and_then(mtime_secs);
+    for face in &mut faces {
+        face.metadata.modified = modified;
+    }
+    if let Some(cache) = &opts.scan_cache {
+        cache.store(path, &faces);
+    }
+    Ok(faces)
+}
+
+/// Faces parsed by earlier searches, keyed by file path and reused while the
+/// file's mtime and size stay the same.
+///
+/// Put one in [`SearchOptions::scan_cache`]: the search looks every file up
+/// before parsing it and records every file it does parse. [`load`] and
+/// [`save`] keep the cache in a JSON sidecar between runs, so a repeat scan
+/// of a mostly unchanged tree only parses what changed. Unlike the LMDB
+/// index it holds whatever one search saw and needs no separate `cache add`.
+///
+/// Cached faces depend on the `name_ids` and collectors they were read with,
+/// so a cache loaded for different options starts empty.
+///
+/// [`load`]: ScanCache::load
+/// [`save`]: ScanCache::save
+#[derive(Debug, Default)]
+pub struct ScanCache {
+    /// The option fingerprint the faces were read with.
+    settings: String,
+    files: Mutex<HashMap<PathBuf, Vec<TypgFontFaceMatch>>>,
+    /// Files looked up or stored by this run.
+    seen: Mutex<HashSet<PathBuf>>,
+    reused: AtomicUsize,
+    parsed: AtomicUsize,
+}
+
+impl ScanCache {
+    /// An empty cache for searches run with `opts`.
+    pub fn new(opts: &SearchOptions) -> Self {
+        Self {
+            settings: scan_settings(opts),
+            ..Self::default()
+        }
+    }
+
+    /// Read a sidecar written by [`ScanCache::save`] for searches run with
+    /// `opts`. A missing or unreadable file, or one written for other
+    /// options, gives an empty cache: it is only ever a shortcut.
+    pub fn load(path: &Path, opts: &SearchOptions) -> Self {
+        let mut cache = Self::new(opts);
+        let Ok(data) = fs::read(path) else {
+            return cache;
+        };
+        let Ok(stored) = serde_json::from_slice::<StoredScanCache>(&data) else {
+            return cache;
+        };
+        if stored.settings != cache.settings {
+            return cache;
+        }
+        let files = cache.files.get_mut().unwrap_or_else(|p| p.into_inner());
+        for face in stored.faces {
+            files
+                .entry(face.source.path.clone())
+                .or_default()
+                .push(face);
+        }
+        cache
+    }
+
+    /// Write the cache to `path`, replacing it atomically.
+    ///
+    /// Files this run neither found nor parsed are kept while they still
+    /// exist, so a narrower search does not evict the rest of the tree.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let files = self.files.lock().unwrap_or_else(|p| p.into_inner());
+        let seen = self.seen.lock().unwrap_or_else(|p| p.into_inner());
+        let mut paths: Vec<&PathBuf> = files
+            .keys()
+            .filter(|path| seen.contains(*path) || path.exists())
+            .collect();
+        paths.sort();
+        let faces: Vec<&TypgFontFaceMatch> = paths.into_iter().flat_map(|p| &files[p]).collect();
+
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let tmp = path.with_extension("tmp");
+        let json = serde_json::json!({ "settings": self.settings, "faces": faces });
+        fs::write(&tmp, serde_json::to_vec(&json)?)
+            .with_context(|| format!("writing {}", tmp.display()))?;
+        fs::rename(&tmp, path).with_context(|| format!("replacing {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Files whose faces came from the cache.
+    pub fn reused(&self) -> usize {
+        self.reused.load(Ordering::Relaxed)
+    }
+
+    /// Files parsed and added to the cache.
+    pub fn parsed(&self) -> usize {
+        self.parsed.load(Ordering::Relaxed)
+    }
+
+    /// The cached faces of `path` if its mtime and size still match.
+    fn lookup(&self, path: &Path, stat: &fs::Metadata) -> Option<Vec<TypgFontFaceMatch>> {
+        self.seen
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(path.to_path_buf());
+        let files = self.files.lock().unwrap_or_else(|p| p.into_inner());
+        let faces = files.get(path)?;
+        let first = &faces.first()?.metadata;
+        let current = first.modified.is_some()
+            && first.modified == mtime_secs(stat)
+            && first.file_size == Some(stat.len());
+        if !current {
+            return None;
+        }
+        self.reused.fetch_add(1, Ordering::Relaxed);
+        Some(faces.clone())
+    }
+
+    fn store(&self, path: &Path, faces: &[TypgFontFaceMatch]) {
+        self.parsed.fetch_add(1, Ordering::Relaxed);
+        self.files
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(path.to_path_buf(), faces.to_vec());
+    }
+}
+
+/// On-disk form of a [`ScanCache`].
+#[derive(Deserialize)]
+struct StoredScanCache {
+    settings: String,
+    faces: Vec<TypgFontFaceMatch>,
+}
+
+/// The options that shape parsed faces, e.g. `names=1,16,2;collectors=a`.
+fn scan_settings(opts: &SearchOptions) -> String {
+    let ids = opts.name_ids.as_deref().unwrap_or(&DEFAULT_NAME_IDS);
+    let ids: Vec<String> = ids.iter().map(u16::to_string).collect();
+    let collectors: Vec<&str> = opts.collectors.iter().map(|c| c.key()).collect();
+    format!(
+        "names={};collectors={}",
+        ids.join(","),
+        collectors.join(",")
+    )
+}
+
+/// A file's modification time in seconds since the Unix epoch.
+fn mtime_secs(meta: &fs::Metadata) -> Option<u64> {
+    meta.modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|age| age.as_secs())
+}
+
+/// Extract metadata for every face in a font already held in memory.
+///
+/// The in-memory counterpart of reading a file during [`search`], for fonts
+/// that never touch disk — piped from another tool, fetched over the
+/// network, or generated on the fly. `path` is recorded as each face's
+/// source and its file stem becomes the fallback name; pass `-` for
+/// standard input, which adds no fallback name.
+///
+/// ```no_run
+/// use std::io::Read;
+/// use std::path::Path;
+/// use typg_core::search::{read_metadata, SearchOptions};
+///
+/// let mut data = Vec::new();
+/// std::io::stdin().read_to_end(&mut data)?;
+/// for face in read_metadata(&data, Path::new("-"), &SearchOptions::default())? {
+///     println!("{:?}", face.metadata.names);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn read_metadata(
+    data: &[u8],
+    path: &Path,
+    opts: &SearchOptions,
+) -> Result<Vec<TypgFontFaceMatch>> {
     let mut metas = Vec::new();
 
-    for font in FontRef::fonts(&data) {
-        let font = font?;
+    let file_hash = Some(hash_bytes(data));
+    let file_size = Some(data.len() as u64);
+    let faces_in_collection =
+        match FileRef::new(data).map_err(|e| anyhow!("not a font file: {e}"))? {
+            FileRef::Collection(collection) => Some(collection.len()),
+            FileRef::Font(_) => None,
+        };
+    for font in FontRef::fonts(data) {
+        let font = font.map_err(|e| anyhow!("parsing font: {e}"))?;
         let ttc_index = font.ttc_index();
         let sfont = if let Some(idx) = ttc_index {
-            SkrifaFontRef::from_index(&data, idx)?
+            SkrifaFontRef::from_index(data, idx)?
         } else {
-            SkrifaFontRef::new(&data)?
+            SkrifaFontRef::new(data)?
         };
 
-        let names = collect_names(&font);
+        let names = collect_names(&font, opts.name_ids.as_deref().unwrap_or(&DEFAULT_NAME_IDS));
+        let family_name =
+            collect_name_with_fallback(&font, NameId::TYPOGRAPHIC_FAMILY_NAME, NameId::FAMILY_NAME);
+        let style_name = collect_name_with_fallback(
+            &font,
+            NameId::TYPOGRAPHIC_SUBFAMILY_NAME,
+            NameId::SUBFAMILY_NAME,
+        );
+        let units_per_em = font.head().ok().map(|head| head.units_per_em());
+        let glyph_count = font.maxp().ok().map(|maxp| maxp.num_glyphs());
+        let font_revision = font
+            .head()
+            .ok()
+            .map(|head| (head.font_revision().to_f64() * 1000.0).round() / 1000.0);
+        let vendor_id = collect_vendor_id(&font);
         let mut axis_tags = collect_axes(&font);
-        let mut feature_tags = collect_features(&font);
+        let (mut gsub_feature_tags, mut gpos_feature_tags) = collect_features(&font);
+        let mut feature_tags: Vec<Tag> = gsub_feature_tags
+            .iter()
+            .chain(&gpos_feature_tags)
+            .copied()
+            .collect();
         let mut script_tags = collect_scripts(&font);
+        let mut language_tags = collect_languages(&font);
         let mut table_tags = collect_tables(&font);
+        let table_checksums = collect_table_checksums(&font);
         let mut codepoints = collect_codepoints(&sfont);
+        let cmap_subtables = collect_cmap_subtables(&font);
         let fvar_tag = Tag::new(b"fvar");
         let is_variable = table_tags.contains(&fvar_tag);
+        let named_instances = collect_named_instances(&sfont);
+        let axes = collect_axis_ranges(&sfont);
         let (weight_class, width_class, family_class) = collect_classification(&font);
         let mut creator_names = collect_creator_names(&font);
         let mut license_names = collect_license_names(&font);
+        let license_description = collect_name(&font, NameId::LICENSE_DESCRIPTION);
+        let license_url = collect_name(&font, NameId::LICENSE_URL);
+        let fs_type = font.os2().ok().map(|os2| os2.fs_type());
+        let classified_as = match &opts.name_ids {
+            Some(ids) if ids.as_slice() != DEFAULT_NAME_IDS => collect_style_category(
+                &font,
+                &collect_names(&font, &DEFAULT_NAME_IDS),
+                family_class,
+            ),
+            _ => collect_style_category(&font, &names, family_class),
+        };
+        let extra = run_collectors(&opts.collectors, &font);
 
         dedup_tags(&mut axis_tags);
         dedup_tags(&mut feature_tags);
+        dedup_tags(&mut gsub_feature_tags);
+        dedup_tags(&mut gpos_feature_tags);
         dedup_tags(&mut script_tags);
+        dedup_tags(&mut language_tags);
         dedup_tags(&mut table_tags);
         dedup_codepoints(&mut codepoints);
         creator_names.sort_unstable();
@@ -412,17 +1649,41 @@ fn load_metadata(path: &Path) -> Result<Vec<TypgFontFaceMatch>> {
             },
             metadata: TypgFontFaceMeta {
                 names: dedup_names(names, path),
+                family_name: family_name.map(Arc::from),
+                style_name: style_name.map(Arc::from),
                 axis_tags,
                 feature_tags,
+                gsub_feature_tags,
+                gpos_feature_tags,
                 script_tags,
+                language_tags,
                 table_tags,
+                table_checksums,
                 codepoints,
+                cmap_subtables,
                 is_variable,
+                named_instances,
+                axes,
                 weight_class,
                 width_class,
                 family_class,
-                creator_names,
-                license_names,
+                creator_names: creator_names.into_iter().map(Arc::from).collect(),
+                license_names: license_names.into_iter().map(Arc::from).collect(),
+                license_description,
+                license_url,
+                fs_type,
+                embedding: fs_type.map(Embedding::from_fs_type),
+                units_per_em,
+                glyph_count,
+                font_revision,
+                vendor_id,
+                faces_in_collection,
+                file_hash,
+                file_size,
+                modified: None,
+                index_id: None,
+                classified_as,
+                extra,
             },
         });
     }
@@ -430,6 +1691,19 @@ fn load_metadata(path: &Path) -> Result<Vec<TypgFontFaceMatch>> {
     Ok(metas)
 }
 
+/// The xxh3 hash recorded as [`TypgFontFaceMeta::file_hash`] for a font
+/// file's bytes.
+pub fn hash_bytes(data: &[u8]) -> u64 {
+    xxh3_64(data)
+}
+
+/// Hash a font file on disk the way a scan would, for checking a stored
+/// [`TypgFontFaceMeta::file_hash`] against the file as it is now.
+pub fn hash_file(path: &Path) -> Result<u64> {
+    let data = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    Ok(hash_bytes(&data))
+}
+
 /// List every top-level table tag in the font's table directory.
 ///
 /// The table directory is the index at the start of every OpenType file.
@@ -444,6 +1718,15 @@ fn collect_tables(font: &FontRef) -> Vec<Tag> {
         .collect()
 }
 
+/// The checksum of every table in the font's table directory, by tag.
+fn collect_table_checksums(font: &FontRef) -> BTreeMap<String, u32> {
+    font.table_directory
+        .table_records()
+        .iter()
+        .map(|rec| (tag_to_string(rec.tag()), rec.checksum()))
+        .collect()
+}
+
 /// Extract variation axis tags from the `fvar` table.
 ///
 /// Returns an empty vec for static (non-variable) fonts. For variable fonts,
@@ -458,27 +1741,109 @@ fn collect_axes(font: &FontRef) -> Vec<Tag> {
     Vec::new()
 }
 
+/// Read each `fvar` axis with its minimum, default and maximum.
+fn collect_axis_ranges(font: &SkrifaFontRef) -> Vec<VariationAxis> {
+    font.axes()
+        .iter()
+        .map(|axis| VariationAxis {
+            tag: tag_to_string(axis.tag()),
+            min: axis.min_value(),
+            default: axis.default_value(),
+            max: axis.max_value(),
+        })
+        .collect()
+}
+
+/// Read the named instances from the `fvar` table.
+///
+/// Each instance record names a subfamily string and gives one user-space
+/// value per axis, in axis order. Empty for static fonts.
+fn collect_named_instances(font: &SkrifaFontRef) -> Vec<NamedInstance> {
+    let axes: Vec<String> = font
+        .axes()
+        .iter()
+        .map(|axis| tag_to_string(axis.tag()))
+        .collect();
+    let lookup = |id: NameId| {
+        font.localized_strings(id)
+            .english_or_first()
+            .map(|name| name.to_string().trim().to_string())
+            .filter(|name| !name.is_empty())
+    };
+
+    font.named_instances()
+        .iter()
+        .map(|instance| {
+            let name_id = instance.subfamily_name_id();
+            NamedInstance {
+                name: lookup(name_id)
+                    .unwrap_or_else(|| format!("name {}", name_id.to_u16()))
+                    .into(),
+                postscript_name: instance
+                    .postscript_name_id()
+                    .and_then(lookup)
+                    .map(Arc::from),
+                coordinates: axes.iter().cloned().zip(instance.user_coords()).collect(),
+            }
+        })
+        .collect()
+}
+
 /// Collect OpenType feature tags from GSUB and GPOS tables.
 ///
 /// GSUB (glyph substitution) holds features like `liga` (ligatures), `smcp`
 /// (small caps), `calt` (contextual alternates). GPOS (glyph positioning)
 /// holds features like `kern` (kerning), `mark` (mark-to-base positioning).
-/// Both tables' feature lists are merged into one flat vec.
-fn collect_features(font: &FontRef) -> Vec<Tag> {
-    let mut tags = Vec::new();
+/// Returns the two tables' feature lists separately, as `(gsub, gpos)`.
+fn collect_features(font: &FontRef) -> (Vec<Tag>, Vec<Tag>) {
+    let mut gsub_tags = Vec::new();
     if let Ok(gsub) = font.gsub() {
         if let Ok(list) = gsub.feature_list() {
-            tags.extend(list.feature_records().iter().map(|rec| rec.feature_tag()));
+            gsub_tags.extend(list.feature_records().iter().map(|rec| rec.feature_tag()));
         }
     }
+    let mut gpos_tags = Vec::new();
     if let Ok(gpos) = font.gpos() {
         if let Ok(list) = gpos.feature_list() {
-            tags.extend(list.feature_records().iter().map(|rec| rec.feature_tag()));
+            gpos_tags.extend(list.feature_records().iter().map(|rec| rec.feature_tag()));
+        }
+    }
+    (gsub_tags, gpos_tags)
+}
+
+/// Collect language system tags from the GSUB and GPOS script lists.
+///
+/// Each script record may carry a default language system plus any number
+/// of tagged ones (`TRK `, `ROM `, `SRB `); only the tagged ones are
+/// returned, merged across scripts and both tables.
+fn collect_languages(font: &FontRef) -> Vec<Tag> {
+    let mut tags = Vec::new();
+    if let Ok(gsub) = font.gsub() {
+        if let Ok(list) = gsub.script_list() {
+            collect_langsys_tags(&list, &mut tags);
+        }
+    }
+    if let Ok(gpos) = font.gpos() {
+        if let Ok(list) = gpos.script_list() {
+            collect_langsys_tags(&list, &mut tags);
         }
     }
     tags
 }
 
+fn collect_langsys_tags(list: &ScriptList, tags: &mut Vec<Tag>) {
+    for record in list.script_records() {
+        if let Ok(script) = record.script(list.offset_data()) {
+            tags.extend(
+                script
+                    .lang_sys_records()
+                    .iter()
+                    .map(|rec| rec.lang_sys_tag()),
+            );
+        }
+    }
+}
+
 /// Collect script tags from GSUB and GPOS tables.
 ///
 /// Script tags identify writing systems: `latn` (Latin), `arab` (Arabic),
@@ -519,10 +1884,32 @@ fn collect_codepoints(font: &SkrifaFontRef) -> Vec<char> {
     cps
 }
 
+/// List the encoding records of the font's `cmap` table.
+///
+/// Records whose subtable cannot be parsed are skipped; a font without a
+/// `cmap` yields an empty list.
+pub(crate) fn collect_cmap_subtables(font: &FontRef) -> Vec<CmapSubtable> {
+    let Ok(cmap) = font.cmap() else {
+        return Vec::new();
+    };
+    cmap.encoding_records()
+        .iter()
+        .filter_map(|record| {
+            let subtable = record.subtable(cmap.offset_data()).ok()?;
+            Some(CmapSubtable {
+                platform_id: record.platform_id() as u16,
+                encoding_id: record.encoding_id(),
+                format: subtable.format(),
+            })
+        })
+        .collect()
+}
+
 /// Extract identifying name strings from the font's `name` table.
 ///
 /// The `name` table stores human-readable strings in multiple languages and
-/// encodings. We read only Unicode-encoded records for these name IDs:
+/// encodings. We read only Unicode-encoded records for the requested name
+/// IDs, by default [`DEFAULT_NAME_IDS`]:
 ///
 /// - **Family Name** (ID 1): e.g., "Helvetica Neue"
 /// - **Typographic Family Name** (ID 16): preferred family grouping
@@ -533,25 +1920,17 @@ fn collect_codepoints(font: &SkrifaFontRef) -> Vec<char> {
 ///
 /// Non-Unicode records (legacy Mac Roman, Windows symbol) are skipped.
 /// Empty or whitespace-only strings are discarded.
-fn collect_names(font: &FontRef) -> Vec<String> {
+fn collect_names(font: &FontRef, wanted: &[u16]) -> Vec<String> {
     let mut names = Vec::new();
 
     if let Ok(name_table) = font.name() {
         let data = name_table.string_data();
-        let wanted = [
-            NameId::FAMILY_NAME,
-            NameId::TYPOGRAPHIC_FAMILY_NAME,
-            NameId::SUBFAMILY_NAME,
-            NameId::TYPOGRAPHIC_SUBFAMILY_NAME,
-            NameId::FULL_NAME,
-            NameId::POSTSCRIPT_NAME,
-        ];
 
         for record in name_table.name_record() {
             if !record.is_unicode() {
                 continue;
             }
-            if !wanted.contains(&record.name_id()) {
+            if !wanted.contains(&record.name_id().to_u16()) {
                 continue;
             }
             if let Ok(entry) = record.string(data) {
@@ -566,6 +1945,32 @@ fn collect_names(font: &FontRef) -> Vec<String> {
     names
 }
 
+/// Read a name record, preferring `preferred` and falling back to `fallback`.
+///
+/// Used for the family (ID 16, else 1) and style (ID 17, else 2) names:
+/// the typographic IDs group large families correctly, but many fonts only
+/// carry the legacy ones.
+fn collect_name_with_fallback(
+    font: &FontRef,
+    preferred: NameId,
+    fallback: NameId,
+) -> Option<String> {
+    collect_name(font, preferred).or_else(|| collect_name(font, fallback))
+}
+
+/// Read the first non-blank Unicode record of one name ID.
+fn collect_name(font: &FontRef, id: NameId) -> Option<String> {
+    let name_table = font.name().ok()?;
+    let data = name_table.string_data();
+    name_table
+        .name_record()
+        .iter()
+        .filter(|record| record.is_unicode() && record.name_id() == id)
+        .filter_map(|record| record.string(data).ok())
+        .map(|entry| entry.to_string().trim().to_string())
+        .find(|rendered| !rendered.is_empty())
+}
+
 /// Extract creator and provenance strings from the `name` table.
 ///
 /// Covers a broad range of attribution fields: copyright notice (ID 0),
@@ -672,6 +2077,35 @@ fn collect_classification(font: &FontRef) -> (Option<u16>, Option<u16>, Option<(
     }
 }
 
+/// Read the OS/2 `achVendID` as a string, trimming space/NUL padding.
+///
+/// Returns `None` for a missing OS/2 table or an all-blank ID.
+fn collect_vendor_id(font: &FontRef) -> Option<String> {
+    let tag = font.os2().ok()?.ach_vend_id();
+    let id = tag_to_string(tag).trim_end_matches([' ', '\0']).to_string();
+    (!id.is_empty()).then_some(id)
+}
+
+/// Guess a style category from `post`, OS/2 PANOSE, family class, and names.
+///
+/// Called before names are deduplicated so that the file-stem fallback
+/// doesn't sway the keyword heuristic.
+fn collect_style_category(
+    font: &FontRef,
+    names: &[String],
+    family_class: Option<(u8, u8)>,
+) -> Option<String> {
+    let fixed_pitch = font
+        .post()
+        .map(|post| post.is_fixed_pitch() != 0)
+        .unwrap_or(false);
+    let panose = font
+        .os2()
+        .map(|os2| os2.panose_10().to_vec())
+        .unwrap_or_default();
+    classify(names, family_class, &panose, fixed_pitch).map(str::to_string)
+}
+
 /// Sort results by file path, then by TTC index within each file.
 /// Produces deterministic output regardless of thread scheduling order.
 fn sort_matches(matches: &mut [TypgFontFaceMatch]) {
@@ -683,6 +2117,149 @@ fn sort_matches(matches: &mut [TypgFontFaceMatch]) {
     });
 }
 
+/// What to order results by, for [`sort_matches_by`] and
+/// [`sort_matches_by_keys`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortKey {
+    /// File path, then collection index — the default order.
+    Path,
+    /// First name, case-insensitively.
+    Name,
+    /// Family name, case-insensitively (see [`family_of`]).
+    Family,
+    /// OS/2 weight class.
+    Weight,
+    /// OS/2 width class.
+    Width,
+    /// File size in bytes.
+    Size,
+    /// File modification time.
+    Modified,
+    /// Upright before italic and oblique, judged from the style name
+    /// (or, lacking one, the other names).
+    Italic,
+}
+
+impl std::str::FromStr for SortKey {
+    type Err = anyhow::Error;
+
+    /// Parse one key: `path`, `name`, `family`, `weight`, `width`, `size`,
+    /// `mtime` (or `modified`) or `italic`, ignoring case.
+    fn from_str(input: &str) -> Result<Self> {
+        Ok(match input.trim().to_ascii_lowercase().as_str() {
+            "path" => Self::Path,
+            "name" => Self::Name,
+            "family" => Self::Family,
+            "weight" => Self::Weight,
+            "width" => Self::Width,
+            "size" => Self::Size,
+            "mtime" | "modified" => Self::Modified,
+            "italic" => Self::Italic,
+            other => {
+                return Err(anyhow!(
+                    "unknown sort key `{other}` (expected path, name, family, weight, width, size, mtime or italic)"
+                ))
+            }
+        })
+    }
+}
+
+/// Parse a comma-separated list of sort keys such as
+/// `family,weight,width,italic`, most significant first.
+pub fn parse_sort_keys(input: &str) -> Result<Vec<SortKey>> {
+    let keys = input
+        .split(',')
+        .filter(|key| !key.trim().is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<_>>>()?;
+    if keys.is_empty() {
+        return Err(anyhow!("no sort keys given"));
+    }
+    Ok(keys)
+}
+
+impl TypgFontFaceMatch {
+    /// Compare two faces by `key`, ascending. Faces missing the value sort
+    /// after those that have it; ties fall back to path order.
+    pub fn cmp_by(&self, other: &Self, key: SortKey) -> std::cmp::Ordering {
+        self.cmp_by_keys(other, &[key])
+    }
+
+    /// Compare two faces by each of `keys` in turn, ascending, so that
+    /// `[Family, Weight, Width, Italic]` lists every family Regular before
+    /// Bold and each roman before its italic, wherever the files lie. Ties
+    /// on every key fall back to path order, then collection index.
+    pub fn cmp_by_keys(&self, other: &Self, keys: &[SortKey]) -> std::cmp::Ordering {
+        keys.iter()
+            .map(|&key| self.cmp_key(other, key))
+            .find(|order| order.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| self.source.path.cmp(&other.source.path))
+            .then_with(|| self.source.ttc_index.cmp(&other.source.ttc_index))
+    }
+
+    fn cmp_key(&self, other: &Self, key: SortKey) -> std::cmp::Ordering {
+        let (a, b) = (&self.metadata, &other.metadata);
+        match key {
+            SortKey::Path => std::cmp::Ordering::Equal,
+            SortKey::Name => {
+                let name = |m: &TypgFontFaceMeta| m.names.first().map(|n| n.to_lowercase());
+                cmp_present_first(name(a), name(b))
+            }
+            SortKey::Family => family_key(a).cmp(&family_key(b)),
+            SortKey::Weight => cmp_present_first(a.weight_class, b.weight_class),
+            SortKey::Width => cmp_present_first(a.width_class, b.width_class),
+            SortKey::Size => cmp_present_first(a.file_size, b.file_size),
+            SortKey::Modified => cmp_present_first(a.modified, b.modified),
+            SortKey::Italic => is_sloped(a).cmp(&is_sloped(b)),
+        }
+    }
+}
+
+/// Whether a face is italic or oblique, by its style name when it has one
+/// and otherwise by any of its names.
+fn is_sloped(meta: &TypgFontFaceMeta) -> bool {
+    let sloped = |name: &str| {
+        let name = name.to_lowercase();
+        name.contains("italic") || name.contains("oblique")
+    };
+    match meta.style_name.as_deref() {
+        Some(style) => sloped(style),
+        None => meta.names.iter().any(|name| sloped(name)),
+    }
+}
+
+/// Order `None` after every value.
+fn cmp_present_first<T: Ord>(a: Option<T>, b: Option<T>) -> std::cmp::Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    }
+}
+
+/// Sort results by `key`, descending when `reverse` is set.
+///
+/// Reversing flips the whole order, so faces missing the value come first.
+pub fn sort_matches_by(matches: &mut [TypgFontFaceMatch], key: SortKey, reverse: bool) {
+    sort_matches_by_keys(matches, &[key], reverse);
+}
+
+/// Sort results by each of `keys` in turn (see
+/// [`TypgFontFaceMatch::cmp_by_keys`]), descending when `reverse` is set.
+/// No keys means path order.
+pub fn sort_matches_by_keys(matches: &mut [TypgFontFaceMatch], keys: &[SortKey], reverse: bool) {
+    matches.sort_by(|a, b| {
+        let order = a.cmp_by_keys(b, keys);
+        if reverse {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+}
+
 fn dedup_tags(tags: &mut Vec<Tag>) {
     tags.sort_unstable();
     tags.dedup();
@@ -696,14 +2273,17 @@ fn dedup_codepoints(codepoints: &mut Vec<char>) {
 /// Deduplicate name strings and add a filename-based fallback.
 ///
 /// The file stem (e.g., "HelveticaNeue-Bold" from "HelveticaNeue-Bold.otf")
-/// is always appended. This ensures every font has at least one searchable
-/// name, even if its `name` table is empty or broken.
-fn dedup_names(mut names: Vec<String>, path: &Path) -> Vec<String> {
-    names.push(
-        path.file_stem()
-            .map(|s| s.to_string_lossy().to_string())
-            .unwrap_or_else(|| path.display().to_string()),
-    );
+/// is appended for every file. This ensures every font has at least one
+/// searchable name, even if its `name` table is empty or broken. Standard
+/// input (`-`) has no file name to offer.
+fn dedup_names(mut names: Vec<String>, path: &Path) -> Vec<Arc<str>> {
+    if path != Path::new("-") {
+        names.push(
+            path.file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string()),
+        );
+    }
 
     for name in names.iter_mut() {
         *name = name.trim().to_string();
@@ -712,7 +2292,7 @@ fn dedup_names(mut names: Vec<String>, path: &Path) -> Vec<String> {
     names.retain(|n| !n.is_empty());
     names.sort_unstable();
     names.dedup();
-    names
+    names.into_iter().map(Arc::from).collect()
 }
 
 /// Serialize OpenType tags as human-readable strings in JSON.
@@ -739,10 +2319,75 @@ where
         .collect()
 }
 
+/// Serialize a file hash as 16 lowercase hex digits, like `font_id`.
+fn serialize_hash<S>(hash: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    hash.map(|h| format!("{h:016x}")).serialize(serializer)
+}
+
+/// Deserialize a file hash from its hex string.
+fn deserialize_hash<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|hex| u64::from_str_radix(&hex, 16).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// Deserialize codepoints from either an array of characters or a compact
+/// range string such as `"U+0020-007E, U+00A0-00FF"`.
+fn deserialize_codepoints<'de, D>(deserializer: D) -> Result<Vec<char>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Codepoints {
+        Chars(Vec<char>),
+        Ranges(String),
+    }
+
+    match Codepoints::deserialize(deserializer)? {
+        Codepoints::Chars(chars) => Ok(chars),
+        Codepoints::Ranges(ranges) => {
+            crate::query::parse_codepoint_list(&ranges).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn search_report_summarizes_with_separators() {
+        let report = SearchReport {
+            files_scanned: 12_034,
+            matches: 211,
+            errors: 1,
+            elapsed: Duration::from_millis(3_240),
+            ..SearchReport::default()
+        };
+        assert_eq!(
+            report.to_string(),
+            "scanned 12,034 files in 3.2s, 211 matches, 1 error"
+        );
+
+        let report = SearchReport {
+            apple_double: 3,
+            empty_files: 1,
+            ..report
+        };
+        assert_eq!(
+            report.to_string(),
+            "scanned 12,034 files in 3.2s, 211 matches, 1 error, \
+             3 AppleDouble files skipped, 1 empty file skipped"
+        );
+    }
+
     #[test]
     fn dedup_names_adds_fallback_and_trims() {
         let names = vec!["  Alpha  ".to_string(), "Alpha".to_string()];
@@ -750,11 +2395,11 @@ mod tests {
         let deduped = dedup_names(names, path);
 
         assert!(
-            deduped.contains(&"Alpha".to_string()),
+            deduped.contains(&"Alpha".into()),
             "original names should be trimmed and kept"
         );
         assert!(
-            deduped.contains(&"Beta".to_string()),
+            deduped.contains(&"Beta".into()),
             "file stem should be added as fallback name"
         );
         assert_eq!(
@@ -764,6 +2409,81 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dedup_names_skips_fallback_for_stdin() {
+        let deduped = dedup_names(vec!["Alpha".to_string()], Path::new("-"));
+        assert_eq!(deduped, vec![Arc::<str>::from("Alpha")]);
+    }
+
+    #[test]
+    fn file_hash_serializes_as_hex() {
+        #[derive(Serialize, Deserialize)]
+        struct Wrapper {
+            #[serde(
+                serialize_with = "serialize_hash",
+                deserialize_with = "deserialize_hash"
+            )]
+            hash: Option<u64>,
+        }
+        let json = serde_json::to_string(&Wrapper {
+            hash: Some(hash_bytes(b"font")),
+        })
+        .unwrap();
+        assert_eq!(json, format!(r#"{{"hash":"{:016x}"}}"#, xxh3_64(b"font")));
+        let back: Wrapper = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.hash, Some(xxh3_64(b"font")));
+        let none: Wrapper = serde_json::from_str(r#"{"hash":null}"#).unwrap();
+        assert_eq!(none.hash, None);
+    }
+
+    #[test]
+    fn scan_cache_reuses_unchanged_files_across_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let font = dir.path().join("A.ttf");
+        let sidecar = dir.path().join("scans").join("a.json");
+        fs::write(&font, b"font").unwrap();
+        let stat = fs::metadata(&font).unwrap();
+        let face: TypgFontFaceMatch = serde_json::from_value(serde_json::json!({
+            "source": {"path": font, "ttc_index": null},
+            "metadata": {
+                "names": ["A"],
+                "axis_tags": [],
+                "feature_tags": [],
+                "script_tags": [],
+                "table_tags": [],
+                "codepoints": [],
+                "is_variable": false,
+                "file_size": 4,
+                "modified": mtime_secs(&stat),
+            },
+        }))
+        .unwrap();
+
+        let opts = SearchOptions::default();
+        let cache = ScanCache::new(&opts);
+        assert!(cache.lookup(&font, &stat).is_none());
+        cache.store(&font, std::slice::from_ref(&face));
+        cache.save(&sidecar).unwrap();
+
+        let cache = ScanCache::load(&sidecar, &opts);
+        let faces = cache
+            .lookup(&font, &stat)
+            .expect("unchanged file is reused");
+        assert_eq!(faces[0].metadata.names, face.metadata.names);
+        assert_eq!((cache.reused(), cache.parsed()), (1, 0));
+
+        fs::write(&font, b"longer font").unwrap();
+        let grown = fs::metadata(&font).unwrap();
+        assert!(cache.lookup(&font, &grown).is_none(), "size changed");
+
+        let other = SearchOptions {
+            name_ids: Some(vec![1, 25]),
+            ..SearchOptions::default()
+        };
+        let cache = ScanCache::load(&sidecar, &other);
+        assert!(cache.lookup(&font, &stat).is_none(), "other name IDs");
+    }
+
     #[test]
     fn dedup_tags_sorts_and_dedups() {
         let mut tags = vec![
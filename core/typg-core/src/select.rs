@@ -0,0 +1,170 @@
+//! Picking the face a browser would draw text with.
+//!
+//! Given the faces of one family and a requested weight, width and slope,
+//! [`nearest`] runs the font matching algorithm of CSS Fonts Level 4
+//! (§5.2, step 4): keep the faces nearest the requested width, of those
+//! the ones nearest the requested style, and of those the ones nearest
+//! the requested weight. Skia and DirectWrite follow the same rules, so
+//! the answer is the face a page asking for `font-weight: 650` renders in.
+//!
+//! Variable faces match every value their `wght`, `wdth`, `ital` and
+//! `slnt` axes reach. Static faces use OS/2 `usWeightClass` and
+//! `usWidthClass` (400 and 100% when missing) and their style name.
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+use crate::output::{css_style, width_percent};
+use crate::search::{TypgFontFaceMatch, TypgFontFaceMeta};
+
+/// CSS `font-weight` of text that asks for nothing else.
+pub const NORMAL_WEIGHT: f32 = 400.0;
+
+/// CSS `font-stretch` of text that asks for nothing else, in percent.
+pub const NORMAL_WIDTH: f32 = 100.0;
+
+/// The face of `faces` that CSS font matching picks for `weight`
+/// (`font-weight`, 1–1000), `width` (`font-stretch` in percent) and
+/// `italic`, or `None` when `faces` is empty.
+///
+/// `faces` should be one family: matching compares styles, not names.
+/// Faces that tie on all three go by path and collection index.
+pub fn nearest(
+    faces: &[TypgFontFaceMatch],
+    weight: f32,
+    width: f32,
+    italic: bool,
+) -> Option<&TypgFontFaceMatch> {
+    let candidates: Vec<&TypgFontFaceMatch> = faces.iter().collect();
+
+    // Up to 100% narrower widths win, above it wider ones.
+    let candidates = keep_nearest(candidates, width, width_range, |above, _| {
+        u8::from(above == (width <= NORMAL_WIDTH))
+    });
+
+    let order = if italic {
+        [Slope::Italic, Slope::Oblique, Slope::Normal]
+    } else {
+        [Slope::Normal, Slope::Oblique, Slope::Italic]
+    };
+    let candidates = order
+        .iter()
+        .map(|slope| {
+            candidates
+                .iter()
+                .copied()
+                .filter(|face| slopes(&face.metadata).contains(slope))
+                .collect::<Vec<_>>()
+        })
+        .find(|faces| !faces.is_empty())
+        .unwrap_or(candidates);
+
+    // 400–500 looks up to 500 first, then down, then above 500; lighter
+    // requests look down first and bolder ones up.
+    let candidates = keep_nearest(candidates, weight, weight_range, |above, value| {
+        if (NORMAL_WEIGHT..=500.0).contains(&weight) {
+            match (above, value <= 500.0) {
+                (true, true) => 0,
+                (false, _) => 1,
+                (true, false) => 2,
+            }
+        } else {
+            u8::from(above == (weight < NORMAL_WEIGHT))
+        }
+    });
+
+    candidates.into_iter().min_by(|a, b| a.cmp_by_keys(b, &[]))
+}
+
+/// How a face slopes; a variable face can offer several.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Slope {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+/// The faces whose range of `range` values comes closest to `desired`.
+///
+/// Faces whose range holds `desired` match exactly. Otherwise each face is
+/// represented by the end of its range nearest `desired`, and `tier` ranks
+/// that value (`above` says on which side it lies) before distance does.
+fn keep_nearest(
+    faces: Vec<&TypgFontFaceMatch>,
+    desired: f32,
+    range: impl Fn(&TypgFontFaceMeta) -> (f32, f32),
+    tier: impl Fn(bool, f32) -> u8,
+) -> Vec<&TypgFontFaceMatch> {
+    let rank = |face: &TypgFontFaceMatch| {
+        let (min, max) = range(&face.metadata);
+        if min > desired {
+            (1 + tier(true, min), min - desired)
+        } else if max < desired {
+            (1 + tier(false, max), desired - max)
+        } else {
+            (0, 0.0)
+        }
+    };
+    let best = faces
+        .iter()
+        .map(|face| rank(face))
+        .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    match best {
+        Some(best) => faces
+            .into_iter()
+            .filter(|face| rank(face) == best)
+            .collect(),
+        None => faces,
+    }
+}
+
+/// The `wght` axis range, or the OS/2 weight class.
+fn weight_range(meta: &TypgFontFaceMeta) -> (f32, f32) {
+    axis_range(meta, "wght").unwrap_or_else(|| {
+        let weight = meta.weight_class.map_or(NORMAL_WEIGHT, f32::from);
+        (weight, weight)
+    })
+}
+
+/// The `wdth` axis range, or the OS/2 width class as a percentage.
+fn width_range(meta: &TypgFontFaceMeta) -> (f32, f32) {
+    axis_range(meta, "wdth").unwrap_or_else(|| {
+        let width = meta
+            .width_class
+            .and_then(width_percent)
+            .unwrap_or(NORMAL_WIDTH);
+        (width, width)
+    })
+}
+
+fn axis_range(meta: &TypgFontFaceMeta, tag: &str) -> Option<(f32, f32)> {
+    meta.axes
+        .iter()
+        .find(|axis| axis.tag == tag)
+        .map(|axis| (axis.min, axis.max))
+}
+
+/// The slopes a face offers: the one its style name gives, plus those an
+/// `ital` (0 upright, 1 italic) or `slnt` (0 upright) axis reaches.
+fn slopes(meta: &TypgFontFaceMeta) -> Vec<Slope> {
+    let mut slopes = vec![match css_style(meta) {
+        Some("italic") => Slope::Italic,
+        Some(_) => Slope::Oblique,
+        None => Slope::Normal,
+    }];
+    if let Some((min, max)) = axis_range(meta, "ital") {
+        if min <= 0.0 {
+            slopes.push(Slope::Normal);
+        }
+        if max >= 1.0 {
+            slopes.push(Slope::Italic);
+        }
+    }
+    if let Some((min, max)) = axis_range(meta, "slnt") {
+        if min <= 0.0 && max >= 0.0 {
+            slopes.push(Slope::Normal);
+        }
+        if min < 0.0 || max > 0.0 {
+            slopes.push(Slope::Oblique);
+        }
+    }
+    slopes
+}
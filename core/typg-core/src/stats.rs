@@ -0,0 +1,130 @@
+//! Aggregate statistics over a set of faces.
+//!
+//! Where a search answers "which fonts?", these helpers answer "what does the
+//! collection look like?". The weight histogram buckets OS/2
+//! `usWeightClass` into the nine standard weights and names the families
+//! that have no bold, which is what a completeness audit of a UI font set
+//! usually starts with.
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::family::{family_key, family_of};
+use crate::search::TypgFontFaceMatch;
+
+/// The nine standard weights and their common names, Thin to Black.
+pub const STANDARD_WEIGHTS: [(u16, &str); 9] = [
+    (100, "Thin"),
+    (200, "ExtraLight"),
+    (300, "Light"),
+    (400, "Regular"),
+    (500, "Medium"),
+    (600, "SemiBold"),
+    (700, "Bold"),
+    (800, "ExtraBold"),
+    (900, "Black"),
+];
+
+/// The standard weight a `usWeightClass` value belongs to.
+///
+/// Values round to the nearest hundred (350 → 400, 349 → 300) and clamp to
+/// 100–900, so the odd 1–9 values some old fonts use land in Thin.
+pub fn weight_bucket(weight: u16) -> u16 {
+    (weight.saturating_add(50) / 100 * 100).clamp(100, 900)
+}
+
+/// Number of faces in one standard weight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeightBucket {
+    /// The standard weight, 100–900.
+    pub weight: u16,
+    /// Its common name, e.g. "SemiBold".
+    pub name: String,
+    /// Faces whose weight class falls in this bucket.
+    pub faces: usize,
+}
+
+/// Weight distribution of a set of faces. Produced by [`weight_stats`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeightStats {
+    /// Faces counted.
+    pub faces: usize,
+    /// One entry per standard weight, Thin to Black, including empty ones.
+    pub buckets: Vec<WeightBucket>,
+    /// Faces without an OS/2 table, and so without a weight class.
+    pub unknown: usize,
+    /// Families with no Bold (700) face, sorted by name.
+    ///
+    /// A variable font counts as the family's bold when one of its named
+    /// instances sits at `wght` 700.
+    pub families_without_bold: Vec<String>,
+}
+
+/// Bucket every face's weight class and find the families without a bold.
+///
+/// Families are grouped like [`group_families`](crate::search::group_families),
+/// case-insensitively by [`family_key`].
+pub fn weight_stats(matches: &[TypgFontFaceMatch]) -> WeightStats {
+    let mut counts: BTreeMap<u16, usize> = STANDARD_WEIGHTS.iter().map(|&(w, _)| (w, 0)).collect();
+    let mut unknown = 0;
+    // family key -> (display name, has bold)
+    let mut families: BTreeMap<String, (String, bool)> = BTreeMap::new();
+
+    for item in matches {
+        let meta = &item.metadata;
+        let bucket = meta.weight_class.map(weight_bucket);
+        match bucket {
+            Some(bucket) => *counts.entry(bucket).or_default() += 1,
+            None => unknown += 1,
+        }
+
+        let bold_instance = meta.named_instances.iter().any(|instance| {
+            instance
+                .coordinates
+                .get("wght")
+                .is_some_and(|&wght| weight_bucket(wght.round() as u16) == 700)
+        });
+        let family = families
+            .entry(family_key(meta))
+            .or_insert_with(|| (family_of(meta).to_string(), false));
+        family.1 |= bucket == Some(700) || bold_instance;
+    }
+
+    let mut families_without_bold: Vec<String> = families
+        .into_values()
+        .filter(|(_, has_bold)| !has_bold)
+        .map(|(name, _)| name)
+        .collect();
+    families_without_bold.sort();
+
+    WeightStats {
+        faces: matches.len(),
+        buckets: STANDARD_WEIGHTS
+            .iter()
+            .map(|&(weight, name)| WeightBucket {
+                weight,
+                name: name.to_string(),
+                faces: counts[&weight],
+            })
+            .collect(),
+        unknown,
+        families_without_bold,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weight_bucket_rounds_to_nearest_standard_weight() {
+        assert_eq!(weight_bucket(400), 400);
+        assert_eq!(weight_bucket(349), 300);
+        assert_eq!(weight_bucket(350), 400);
+        assert_eq!(weight_bucket(1), 100);
+        assert_eq!(weight_bucket(950), 900);
+        assert_eq!(weight_bucket(u16::MAX), 900);
+    }
+}
@@ -34,7 +34,10 @@
 ///
 /// Made by FontLab <https://www.fontlab.com/>
 use anyhow::{anyhow, Result};
-use read_fonts::types::Tag;
+
+/// The OpenType tag type used throughout typg, re-exported so dependents
+/// need not depend on `read-fonts` to name it.
+pub use read_fonts::types::Tag;
 
 /// Parse a 1–4 character string into an OpenType [`Tag`].
 ///
@@ -90,3 +93,151 @@ pub fn tag4(raw: &str) -> Result<Tag> {
 pub fn tag_to_string(tag: Tag) -> String {
     String::from_utf8_lossy(&tag.to_be_bytes()).to_string()
 }
+
+/// A tag typg knows by name: its four-byte form, as [`tag_to_string`]
+/// prints it, and what it stands for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KnownTag {
+    pub tag: &'static str,
+    pub name: &'static str,
+}
+
+const fn known(tag: &'static str, name: &'static str) -> KnownTag {
+    KnownTag { tag, name }
+}
+
+/// The registered variation axes, plus the common unregistered ones.
+///
+/// This list and the others below are curated, not exhaustive: any valid
+/// tag can be searched for. They give bindings and pickers names to offer.
+pub const KNOWN_AXES: &[KnownTag] = &[
+    known("wght", "Weight"),
+    known("wdth", "Width"),
+    known("opsz", "Optical size"),
+    known("ital", "Italic"),
+    known("slnt", "Slant"),
+    known("GRAD", "Grade"),
+    known("XTRA", "Parametric counter width"),
+    known("XOPQ", "Parametric thick stroke"),
+    known("YOPQ", "Parametric thin stroke"),
+    known("CASL", "Casual"),
+    known("MONO", "Monospace"),
+];
+
+/// Frequently used OpenType layout features.
+pub const KNOWN_FEATURES: &[KnownTag] = &[
+    known("aalt", "Access all alternates"),
+    known("c2sc", "Small capitals from capitals"),
+    known("calt", "Contextual alternates"),
+    known("case", "Case-sensitive forms"),
+    known("ccmp", "Glyph composition/decomposition"),
+    known("clig", "Contextual ligatures"),
+    known("cpsp", "Capital spacing"),
+    known("curs", "Cursive positioning"),
+    known("cv01", "Character variant 1"),
+    known("dlig", "Discretionary ligatures"),
+    known("dnom", "Denominators"),
+    known("fina", "Terminal forms"),
+    known("frac", "Fractions"),
+    known("hist", "Historical forms"),
+    known("init", "Initial forms"),
+    known("isol", "Isolated forms"),
+    known("kern", "Kerning"),
+    known("liga", "Standard ligatures"),
+    known("lnum", "Lining figures"),
+    known("locl", "Localized forms"),
+    known("mark", "Mark positioning"),
+    known("medi", "Medial forms"),
+    known("mkmk", "Mark-to-mark positioning"),
+    known("numr", "Numerators"),
+    known("onum", "Oldstyle figures"),
+    known("ordn", "Ordinals"),
+    known("pcap", "Petite capitals"),
+    known("pnum", "Proportional figures"),
+    known("rlig", "Required ligatures"),
+    known("salt", "Stylistic alternates"),
+    known("sinf", "Scientific inferiors"),
+    known("smcp", "Small capitals"),
+    known("ss01", "Stylistic set 1"),
+    known("ss02", "Stylistic set 2"),
+    known("subs", "Subscript"),
+    known("sups", "Superscript"),
+    known("swsh", "Swash"),
+    known("titl", "Titling"),
+    known("tnum", "Tabular figures"),
+    known("unic", "Unicase"),
+    known("zero", "Slashed zero"),
+];
+
+/// OpenType script tags of the widely used writing systems.
+pub const KNOWN_SCRIPTS: &[KnownTag] = &[
+    known("DFLT", "Default"),
+    known("arab", "Arabic"),
+    known("armn", "Armenian"),
+    known("beng", "Bengali"),
+    known("bng2", "Bengali v.2"),
+    known("cyrl", "Cyrillic"),
+    known("deva", "Devanagari"),
+    known("dev2", "Devanagari v.2"),
+    known("ethi", "Ethiopic"),
+    known("geor", "Georgian"),
+    known("grek", "Greek"),
+    known("gujr", "Gujarati"),
+    known("guru", "Gurmukhi"),
+    known("hang", "Hangul"),
+    known("hani", "CJK Ideographic"),
+    known("hebr", "Hebrew"),
+    known("kana", "Hiragana and Katakana"),
+    known("khmr", "Khmer"),
+    known("knda", "Kannada"),
+    known("lao ", "Lao"),
+    known("latn", "Latin"),
+    known("mlym", "Malayalam"),
+    known("mym2", "Myanmar"),
+    known("orya", "Odia"),
+    known("sinh", "Sinhala"),
+    known("syrc", "Syriac"),
+    known("taml", "Tamil"),
+    known("telu", "Telugu"),
+    known("thaa", "Thaana"),
+    known("thai", "Thai"),
+    known("tibt", "Tibetan"),
+];
+
+/// Common font tables.
+pub const KNOWN_TABLES: &[KnownTag] = &[
+    known("BASE", "Baseline data"),
+    known("CBDT", "Color bitmap data"),
+    known("CFF ", "Compact Font Format"),
+    known("CFF2", "Compact Font Format 2"),
+    known("COLR", "Color table"),
+    known("CPAL", "Color palette"),
+    known("DSIG", "Digital signature"),
+    known("GDEF", "Glyph definition data"),
+    known("GPOS", "Glyph positioning"),
+    known("GSUB", "Glyph substitution"),
+    known("HVAR", "Horizontal metrics variations"),
+    known("MATH", "Mathematical typesetting"),
+    known("MVAR", "Metrics variations"),
+    known("OS/2", "OS/2 and Windows metrics"),
+    known("STAT", "Style attributes"),
+    known("SVG ", "SVG glyphs"),
+    known("avar", "Axis variations"),
+    known("cmap", "Character to glyph mapping"),
+    known("fvar", "Font variations"),
+    known("gasp", "Grid-fitting and scan-conversion"),
+    known("glyf", "Glyph data"),
+    known("gvar", "Glyph variations"),
+    known("head", "Font header"),
+    known("hhea", "Horizontal header"),
+    known("hmtx", "Horizontal metrics"),
+    known("kern", "Kerning (legacy)"),
+    known("loca", "Index to location"),
+    known("maxp", "Maximum profile"),
+    known("meta", "Metadata"),
+    known("name", "Naming table"),
+    known("post", "PostScript information"),
+    known("sbix", "Standard bitmap graphics"),
+    known("vhea", "Vertical header"),
+    known("vmtx", "Vertical metrics"),
+];
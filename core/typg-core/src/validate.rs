@@ -0,0 +1,1009 @@
+//! Lightweight font QA checks for CI gates.
+//!
+//! Font Bakery is the reference QA suite for fonts, but it is a large Python
+//! tool. Most CI pipelines only need a handful of structural checks to catch
+//! broken builds: is there a PostScript name, is `usWeightClass` sane, does the
+//! cmap map the space character, are there outlines at all. This module runs a
+//! curated subset of those checks in Rust, with no Python dependency.
+//!
+//! Every check yields `pass`, `warn`, or `fail`. A font's overall status is the
+//! worst of its checks.
+//!
+//! The `names` profile audits naming instead: whether the family, subfamily,
+//! full and PostScript names agree with each other, and with the OS/2
+//! weight, width and style bits. Apps build their font menus and bold/italic
+//! style links from these, so a mismatch shows up as a missing, duplicated or
+//! misgrouped style.
+//!
+//! [`duplicate_names`] looks across files instead: PostScript and full
+//! names that more than one file uses make installers and font managers
+//! refuse, replace or mix up fonts.
+//!
+//! Made by FontLab <https://www.fontlab.com/>
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use read_fonts::tables::name::NameId;
+use read_fonts::types::Tag;
+use read_fonts::{FontRef, TableProvider};
+use serde::Serialize;
+use skrifa::{FontRef as SkrifaFontRef, MetadataProvider};
+
+use crate::discovery::{FontDiscovery, PathDiscovery};
+use crate::search::{hash_file, SearchOptions, TypgFontSource};
+
+/// Outcome of a single check, ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// Result of running one check against one face.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    /// Stable check identifier, e.g. `name/postscript`.
+    pub id: &'static str,
+    pub status: CheckStatus,
+    /// Explanation for warnings and failures; a short confirmation otherwise.
+    pub message: String,
+}
+
+/// All check results for one face, plus the worst status among them.
+#[derive(Debug, Clone, Serialize)]
+pub struct FontReport {
+    pub source: TypgFontSource,
+    pub status: CheckStatus,
+    pub checks: Vec<CheckResult>,
+}
+
+/// Named sets of checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationProfile {
+    /// Structural subset of Font Bakery's universal profile.
+    FontbakeryLite,
+    /// Naming consistency: names against each other and against OS/2.
+    Names,
+}
+
+/// Validate every font under `paths` in parallel.
+///
+/// Files that can't be parsed at all get a single failing `font/parse`
+/// check instead of being skipped — a CI gate should notice them.
+/// Reports are sorted by path, then TTC index.
+pub fn validate(
+    paths: &[PathBuf],
+    profile: ValidationProfile,
+    opts: &SearchOptions,
+) -> Result<Vec<FontReport>> {
+    let discovery = PathDiscovery::new(paths.iter().cloned())
+        .follow_symlinks(opts.follow_symlinks)
+        .include_hidden(opts.include_hidden);
+    let candidates = discovery.discover()?;
+
+    let run = || -> Vec<FontReport> {
+        let mut reports: Vec<FontReport> = candidates
+            .par_iter()
+            .flat_map_iter(|loc| validate_file(&loc.path, profile))
+            .collect();
+        reports.sort_by(|a, b| {
+            a.source
+                .path
+                .cmp(&b.source.path)
+                .then_with(|| a.source.ttc_index.cmp(&b.source.ttc_index))
+        });
+        reports
+    };
+
+    let reports = if let Some(jobs) = opts.jobs {
+        let pool = ThreadPoolBuilder::new().num_threads(jobs).build()?;
+        pool.install(run)
+    } else {
+        run()
+    };
+
+    Ok(reports)
+}
+
+/// Validate every face in one font file.
+pub fn validate_file(path: &Path, profile: ValidationProfile) -> Vec<FontReport> {
+    let parse_failure = |message: String| {
+        vec![report(
+            TypgFontSource {
+                path: path.to_path_buf(),
+                ttc_index: None,
+            },
+            vec![CheckResult {
+                id: "font/parse",
+                status: CheckStatus::Fail,
+                message,
+            }],
+        )]
+    };
+
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(err) => return parse_failure(format!("cannot read file: {err}")),
+    };
+
+    let mut reports = Vec::new();
+    for font in FontRef::fonts(&data) {
+        let font = match font {
+            Ok(font) => font,
+            Err(err) => return parse_failure(format!("cannot parse font: {err}")),
+        };
+        let source = TypgFontSource {
+            path: path.to_path_buf(),
+            ttc_index: font.ttc_index(),
+        };
+        let checks = match profile {
+            ValidationProfile::FontbakeryLite => fontbakery_lite(&font, &data),
+            ValidationProfile::Names => names_audit(&font),
+        };
+        reports.push(report(source, checks));
+    }
+
+    if reports.is_empty() {
+        return parse_failure("file contains no font faces".to_string());
+    }
+    reports
+}
+
+/// Which name a [`DuplicateName`] shares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateKind {
+    /// PostScript name (ID 6).
+    Postscript,
+    /// Full name (ID 4).
+    Full,
+}
+
+/// A PostScript or full name that faces in more than one file share.
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateName {
+    pub kind: DuplicateKind,
+    pub name: String,
+    /// Whether the files hold different bytes. Identical copies are clutter;
+    /// different fonts under one name are the conflicts that break installs.
+    pub contents_differ: bool,
+    /// The faces using the name, by path, then TTC index.
+    pub faces: Vec<TypgFontSource>,
+}
+
+/// Find PostScript and full names used by faces in more than one file
+/// under `paths`, reading files in parallel.
+///
+/// Names compare exactly; faces of one collection sharing a name are not
+/// reported. Files that do not parse are skipped (`validate` reports them).
+/// Names whose files differ come first, then identical copies, each by
+/// kind and name.
+pub fn duplicate_names(paths: &[PathBuf], opts: &SearchOptions) -> Result<Vec<DuplicateName>> {
+    let discovery = PathDiscovery::new(paths.iter().cloned())
+        .follow_symlinks(opts.follow_symlinks)
+        .include_hidden(opts.include_hidden);
+    let candidates = discovery.discover()?;
+
+    let run = || -> Vec<DuplicateName> {
+        let faces: Vec<(TypgFontSource, StyleNames)> = candidates
+            .par_iter()
+            .flat_map_iter(|loc| face_names(&loc.path))
+            .collect();
+        group_duplicates(faces, |path| hash_file(path).ok())
+    };
+
+    Ok(match opts.jobs {
+        Some(jobs) => ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build()?
+            .install(run),
+        None => run(),
+    })
+}
+
+/// The names of every face in one font file; empty if it does not parse.
+fn face_names(path: &Path) -> Vec<(TypgFontSource, StyleNames)> {
+    let Ok(data) = fs::read(path) else {
+        return Vec::new();
+    };
+    FontRef::fonts(&data)
+        .filter_map(Result::ok)
+        .map(|font| {
+            let source = TypgFontSource {
+                path: path.to_path_buf(),
+                ttc_index: font.ttc_index(),
+            };
+            (source, StyleNames::read(&font))
+        })
+        .collect()
+}
+
+/// Group faces by PostScript and full name, keep the names more than one
+/// file uses, and tell apart differing files by their `hash`.
+fn group_duplicates(
+    faces: Vec<(TypgFontSource, StyleNames)>,
+    hash: impl Fn(&Path) -> Option<u64> + Sync,
+) -> Vec<DuplicateName> {
+    let mut groups: BTreeMap<(DuplicateKind, String), Vec<TypgFontSource>> = BTreeMap::new();
+    for (source, names) in faces {
+        let named = [
+            (DuplicateKind::Postscript, names.postscript),
+            (DuplicateKind::Full, names.full),
+        ];
+        for (kind, name) in named {
+            if let Some(name) = name {
+                groups.entry((kind, name)).or_default().push(source.clone());
+            }
+        }
+    }
+    groups.retain(|_, faces| {
+        faces
+            .iter()
+            .map(|face| &face.path)
+            .collect::<BTreeSet<_>>()
+            .len()
+            > 1
+    });
+
+    let paths: BTreeSet<&Path> = groups
+        .values()
+        .flatten()
+        .map(|face| face.path.as_path())
+        .collect();
+    let hashes: HashMap<&Path, Option<u64>> = paths
+        .into_par_iter()
+        .map(|path| (path, hash(path)))
+        .collect();
+
+    let mut duplicates: Vec<DuplicateName> = groups
+        .iter()
+        .map(|((kind, name), faces)| {
+            let mut faces = faces.clone();
+            faces.sort_by(|a, b| {
+                a.path
+                    .cmp(&b.path)
+                    .then_with(|| a.ttc_index.cmp(&b.ttc_index))
+            });
+            // A file that cannot be hashed counts as different.
+            let contents: BTreeSet<Option<u64>> = faces
+                .iter()
+                .map(|face| hashes[face.path.as_path()])
+                .collect();
+            DuplicateName {
+                kind: *kind,
+                name: name.clone(),
+                contents_differ: contents.len() > 1 || contents.contains(&None),
+                faces,
+            }
+        })
+        .collect();
+    duplicates.sort_by_key(|dup| !dup.contents_differ);
+    duplicates
+}
+
+fn report(source: TypgFontSource, checks: Vec<CheckResult>) -> FontReport {
+    let status = checks
+        .iter()
+        .map(|c| c.status)
+        .max()
+        .unwrap_or(CheckStatus::Pass);
+    FontReport {
+        source,
+        status,
+        checks,
+    }
+}
+
+fn fontbakery_lite(font: &FontRef, data: &[u8]) -> Vec<CheckResult> {
+    let sfont = match font.ttc_index() {
+        Some(idx) => SkrifaFontRef::from_index(data, idx).ok(),
+        None => SkrifaFontRef::new(data).ok(),
+    };
+
+    vec![
+        check_family_name(font),
+        check_postscript_name(font),
+        check_outlines(font),
+        check_units_per_em(font),
+        check_weight_class(font),
+        check_width_class(font),
+        check_space_glyph(sfont.as_ref()),
+        check_fs_type(font),
+        check_license(font),
+    ]
+}
+
+fn pass(id: &'static str, message: impl Into<String>) -> CheckResult {
+    CheckResult {
+        id,
+        status: CheckStatus::Pass,
+        message: message.into(),
+    }
+}
+
+fn warn(id: &'static str, message: impl Into<String>) -> CheckResult {
+    CheckResult {
+        id,
+        status: CheckStatus::Warn,
+        message: message.into(),
+    }
+}
+
+fn fail(id: &'static str, message: impl Into<String>) -> CheckResult {
+    CheckResult {
+        id,
+        status: CheckStatus::Fail,
+        message: message.into(),
+    }
+}
+
+/// First Unicode-encoded string for a name ID, if any.
+fn name_string(font: &FontRef, id: NameId) -> Option<String> {
+    let table = font.name().ok()?;
+    let data = table.string_data();
+    table
+        .name_record()
+        .iter()
+        .filter(|record| record.is_unicode() && record.name_id() == id)
+        .filter_map(|record| record.string(data).ok())
+        .map(|s| s.to_string())
+        .find(|s| !s.trim().is_empty())
+}
+
+fn check_family_name(font: &FontRef) -> CheckResult {
+    const ID: &str = "name/family";
+    match name_string(font, NameId::FAMILY_NAME) {
+        Some(name) => pass(ID, format!("family name is {name:?}")),
+        None => fail(ID, "name table has no family name (ID 1)"),
+    }
+}
+
+fn check_postscript_name(font: &FontRef) -> CheckResult {
+    const ID: &str = "name/postscript";
+    match name_string(font, NameId::POSTSCRIPT_NAME) {
+        Some(name) => match postscript_name_problem(&name) {
+            Some(problem) => fail(ID, format!("PostScript name {name:?} {problem}")),
+            None => pass(ID, format!("PostScript name is {name:?}")),
+        },
+        None => fail(ID, "name table has no PostScript name (ID 6)"),
+    }
+}
+
+/// Describe why a PostScript name is invalid, or `None` if it is fine.
+///
+/// The OpenType spec limits PostScript names to 63 printable ASCII
+/// characters excluding `[](){}<>/%` and space.
+pub fn postscript_name_problem(name: &str) -> Option<&'static str> {
+    if name.len() > 63 {
+        return Some("is longer than 63 characters");
+    }
+    let forbidden = |c: char| !(c.is_ascii_graphic()) || "[](){}<>/%".contains(c);
+    if name.chars().any(forbidden) {
+        return Some("contains spaces, non-ASCII, or reserved characters");
+    }
+    None
+}
+
+fn check_outlines(font: &FontRef) -> CheckResult {
+    const ID: &str = "font/outlines";
+    let tables: Vec<Tag> = font
+        .table_directory
+        .table_records()
+        .iter()
+        .map(|rec| rec.tag())
+        .collect();
+    let has = |tag: &[u8; 4]| tables.contains(&Tag::new(tag));
+    if has(b"glyf") || has(b"CFF ") || has(b"CFF2") {
+        pass(ID, "font has glyf or CFF outlines")
+    } else {
+        fail(ID, "font has neither glyf, CFF, nor CFF2 outlines")
+    }
+}
+
+fn check_units_per_em(font: &FontRef) -> CheckResult {
+    const ID: &str = "head/units-per-em";
+    match font.head() {
+        Ok(head) => units_per_em_result(head.units_per_em()),
+        Err(_) => fail(ID, "font has no head table"),
+    }
+}
+
+/// Judge a `head.unitsPerEm` value: 16–16384 is legal, and the common
+/// values are 1000 or a power of two.
+pub fn units_per_em_result(upem: u16) -> CheckResult {
+    const ID: &str = "head/units-per-em";
+    if !(16..=16384).contains(&upem) {
+        fail(ID, format!("unitsPerEm {upem} is outside 16–16384"))
+    } else if upem != 1000 && !upem.is_power_of_two() {
+        warn(
+            ID,
+            format!("unitsPerEm {upem} is neither 1000 nor a power of two"),
+        )
+    } else {
+        pass(ID, format!("unitsPerEm is {upem}"))
+    }
+}
+
+fn check_weight_class(font: &FontRef) -> CheckResult {
+    const ID: &str = "os2/weight-class";
+    let Ok(os2) = font.os2() else {
+        return fail(ID, "font has no OS/2 table");
+    };
+    let is_variable = font.fvar().is_ok();
+    weight_class_result(os2.us_weight_class(), is_variable)
+}
+
+/// Judge an `usWeightClass` value. Static fonts should use multiples of 100;
+/// variable fonts may use any value in 1–1000.
+pub fn weight_class_result(weight: u16, is_variable: bool) -> CheckResult {
+    const ID: &str = "os2/weight-class";
+    if !(1..=1000).contains(&weight) {
+        fail(ID, format!("usWeightClass {weight} is outside 1–1000"))
+    } else if !is_variable && !weight.is_multiple_of(100) {
+        warn(
+            ID,
+            format!("usWeightClass {weight} is not a multiple of 100"),
+        )
+    } else {
+        pass(ID, format!("usWeightClass is {weight}"))
+    }
+}
+
+fn check_width_class(font: &FontRef) -> CheckResult {
+    const ID: &str = "os2/width-class";
+    let Ok(os2) = font.os2() else {
+        return fail(ID, "font has no OS/2 table");
+    };
+    let width = os2.us_width_class();
+    if (1..=9).contains(&width) {
+        pass(ID, format!("usWidthClass is {width}"))
+    } else {
+        fail(ID, format!("usWidthClass {width} is outside 1–9"))
+    }
+}
+
+fn check_space_glyph(font: Option<&SkrifaFontRef>) -> CheckResult {
+    const ID: &str = "cmap/space";
+    let Some(font) = font else {
+        return fail(ID, "cmap could not be read");
+    };
+    let charmap = font.charmap();
+    if !charmap.has_map() {
+        fail(ID, "font has no usable Unicode cmap subtable")
+    } else if charmap.map(' ').is_none() {
+        warn(ID, "cmap does not map U+0020 SPACE")
+    } else {
+        pass(ID, "cmap maps U+0020 SPACE")
+    }
+}
+
+fn check_fs_type(font: &FontRef) -> CheckResult {
+    const ID: &str = "os2/fstype";
+    let Ok(os2) = font.os2() else {
+        return fail(ID, "font has no OS/2 table");
+    };
+    let fs_type = os2.fs_type();
+    if fs_type & 0x0002 != 0 {
+        warn(
+            ID,
+            format!("fsType 0x{fs_type:04X} marks the font as restricted-license embedding"),
+        )
+    } else {
+        pass(ID, format!("fsType is 0x{fs_type:04X}"))
+    }
+}
+
+fn check_license(font: &FontRef) -> CheckResult {
+    const ID: &str = "name/license";
+    let description = name_string(font, NameId::LICENSE_DESCRIPTION);
+    let url = name_string(font, NameId::LICENSE_URL);
+    match (description, url) {
+        (Some(_), Some(_)) => pass(ID, "license description and URL are present"),
+        (Some(_), None) => warn(ID, "license URL (ID 14) is missing"),
+        (None, Some(_)) => warn(ID, "license description (ID 13) is missing"),
+        (None, None) => warn(ID, "no license description (ID 13) or URL (ID 14)"),
+    }
+}
+
+fn names_audit(font: &FontRef) -> Vec<CheckResult> {
+    let names = StyleNames::read(font);
+    let os2 = font.os2().ok().map(|os2| Os2Style {
+        weight: os2.us_weight_class(),
+        width: os2.us_width_class(),
+        fs_selection: os2.fs_selection().bits(),
+    });
+
+    vec![
+        check_family_name(font),
+        check_postscript_name(font),
+        full_name_result(&names),
+        postscript_match_result(&names),
+        subfamily_result(&names, os2.as_ref()),
+        weight_match_result(&names, os2.as_ref()),
+        width_match_result(&names, os2.as_ref()),
+    ]
+}
+
+/// The name strings the naming checks compare.
+#[derive(Debug, Default)]
+struct StyleNames {
+    /// ID 1.
+    family: Option<String>,
+    /// ID 2.
+    subfamily: Option<String>,
+    /// ID 4.
+    full: Option<String>,
+    /// ID 6.
+    postscript: Option<String>,
+    /// ID 16.
+    typographic_family: Option<String>,
+    /// ID 17.
+    typographic_subfamily: Option<String>,
+}
+
+impl StyleNames {
+    fn read(font: &FontRef) -> Self {
+        Self {
+            family: name_string(font, NameId::FAMILY_NAME),
+            subfamily: name_string(font, NameId::SUBFAMILY_NAME),
+            full: name_string(font, NameId::FULL_NAME),
+            postscript: name_string(font, NameId::POSTSCRIPT_NAME),
+            typographic_family: name_string(font, NameId::TYPOGRAPHIC_FAMILY_NAME),
+            typographic_subfamily: name_string(font, NameId::TYPOGRAPHIC_SUBFAMILY_NAME),
+        }
+    }
+
+    /// The family apps group the face under: typographic, else legacy.
+    fn menu_family(&self) -> Option<&str> {
+        self.typographic_family
+            .as_deref()
+            .or(self.family.as_deref())
+    }
+
+    /// The style apps list the face as: typographic, else legacy.
+    fn menu_style(&self) -> Option<&str> {
+        self.typographic_subfamily
+            .as_deref()
+            .or(self.subfamily.as_deref())
+    }
+}
+
+/// The OS/2 values the naming checks compare names against.
+#[derive(Debug, Clone, Copy)]
+struct Os2Style {
+    weight: u16,
+    width: u16,
+    fs_selection: u16,
+}
+
+const FS_SELECTION_ITALIC: u16 = 0x0001;
+const FS_SELECTION_BOLD: u16 = 0x0020;
+
+/// Style words and the `usWeightClass` they name, compounds before the
+/// words they contain. `None` marks words without one agreed value.
+const WEIGHT_WORDS: &[(&str, Option<u16>)] = &[
+    ("extralight", Some(200)),
+    ("ultralight", Some(200)),
+    ("semilight", None),
+    ("demilight", None),
+    ("semibold", Some(600)),
+    ("demibold", Some(600)),
+    ("extrabold", Some(800)),
+    ("ultrabold", Some(800)),
+    ("extrablack", None),
+    ("ultrablack", None),
+    ("hairline", Some(100)),
+    ("thin", Some(100)),
+    ("light", Some(300)),
+    ("regular", Some(400)),
+    ("normal", Some(400)),
+    ("book", None),
+    ("medium", Some(500)),
+    ("bold", Some(700)),
+    ("black", Some(900)),
+    ("heavy", Some(900)),
+];
+
+/// Width words and the `usWidthClass` they name, as [`WEIGHT_WORDS`].
+const WIDTH_WORDS: &[(&str, Option<u16>)] = &[
+    ("ultracondensed", Some(1)),
+    ("extracondensed", Some(2)),
+    ("semicondensed", Some(4)),
+    ("condensed", Some(3)),
+    ("ultraexpanded", Some(9)),
+    ("extraexpanded", Some(8)),
+    ("semiexpanded", Some(6)),
+    ("expanded", Some(7)),
+    ("narrow", None),
+    ("compressed", None),
+    ("wide", None),
+];
+
+/// The first word of `table` found in `name`, ignoring case, spaces and
+/// hyphens, with its value.
+fn style_word(
+    name: &str,
+    table: &[(&'static str, Option<u16>)],
+) -> Option<(&'static str, Option<u16>)> {
+    let squashed: String = name
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect();
+    table
+        .iter()
+        .copied()
+        .find(|(word, _)| squashed.contains(word))
+}
+
+/// Whether a style name is the default style, which full and PostScript
+/// names may leave out.
+fn is_regular(style: &str) -> bool {
+    style.eq_ignore_ascii_case("regular")
+}
+
+/// `name` with runs of whitespace collapsed to one space.
+fn collapse_spaces(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `name` with the characters a PostScript name may not hold removed.
+fn postscript_part(name: &str) -> String {
+    name.chars()
+        .filter(|&c| c.is_ascii_graphic() && !"[](){}<>/%".contains(c))
+        .collect()
+}
+
+/// The full name (ID 4) should read family, then style.
+fn full_name_result(names: &StyleNames) -> CheckResult {
+    const ID: &str = "name/full-name";
+    let Some(full) = names.full.as_deref() else {
+        return warn(ID, "name table has no full name (ID 4)");
+    };
+    let (Some(family), Some(style)) = (names.menu_family(), names.menu_style()) else {
+        return warn(
+            ID,
+            "no family and style names to compare the full name with",
+        );
+    };
+    let expected = collapse_spaces(&format!("{family} {style}"));
+    let full = collapse_spaces(full);
+    if full == expected || (is_regular(style) && full == collapse_spaces(family)) {
+        pass(ID, format!("full name {full:?} is family plus style"))
+    } else {
+        warn(
+            ID,
+            format!("full name {full:?} does not match family plus style {expected:?}"),
+        )
+    }
+}
+
+/// The PostScript name (ID 6) should read family, a hyphen, then style,
+/// without spaces. Where the hyphen falls is not judged: width words often
+/// move to the family part (`FamilyCondensed-Bold`).
+fn postscript_match_result(names: &StyleNames) -> CheckResult {
+    const ID: &str = "name/postscript-match";
+    let Some(postscript) = names.postscript.as_deref() else {
+        return warn(ID, "name table has no PostScript name (ID 6)");
+    };
+    let (Some(family), Some(style)) = (names.menu_family(), names.menu_style()) else {
+        return warn(
+            ID,
+            "no family and style names to compare the PostScript name with",
+        );
+    };
+    let family = postscript_part(family);
+    let expected = format!("{family}-{}", postscript_part(style));
+    let unhyphenated = |name: &str| name.replace('-', "");
+    if unhyphenated(postscript) == unhyphenated(&expected)
+        || (is_regular(style) && postscript == family)
+    {
+        pass(
+            ID,
+            format!("PostScript name {postscript:?} is family plus style"),
+        )
+    } else {
+        warn(
+            ID,
+            format!("PostScript name {postscript:?} does not match family plus style {expected:?}"),
+        )
+    }
+}
+
+/// The legacy subfamily (ID 2) should be Regular, Italic, Bold or Bold
+/// Italic, with the OS/2 `fsSelection` bold and italic bits to match: apps
+/// link the four styles of a family by them.
+fn subfamily_result(names: &StyleNames, os2: Option<&Os2Style>) -> CheckResult {
+    const ID: &str = "name/subfamily";
+    let Some(subfamily) = names.subfamily.as_deref() else {
+        return fail(ID, "name table has no subfamily name (ID 2)");
+    };
+    let (bold, italic) = match subfamily {
+        "Regular" => (false, false),
+        "Italic" => (false, true),
+        "Bold" => (true, false),
+        "Bold Italic" => (true, true),
+        _ => {
+            return warn(
+                ID,
+                format!(
+                    "subfamily {subfamily:?} is not Regular, Italic, Bold or Bold Italic; \
+                     move the style to the typographic subfamily (ID 17)"
+                ),
+            )
+        }
+    };
+    let Some(os2) = os2 else {
+        return fail(ID, "font has no OS/2 table");
+    };
+    let mut mismatches = Vec::new();
+    if bold != (os2.fs_selection & FS_SELECTION_BOLD != 0) {
+        mismatches.push("bold");
+    }
+    if italic != (os2.fs_selection & FS_SELECTION_ITALIC != 0) {
+        mismatches.push("italic");
+    }
+    if mismatches.is_empty() {
+        pass(ID, format!("subfamily {subfamily:?} matches fsSelection"))
+    } else {
+        fail(
+            ID,
+            format!(
+                "subfamily {subfamily:?} disagrees with the fsSelection {} bit (0x{:04X})",
+                mismatches.join(" and "),
+                os2.fs_selection
+            ),
+        )
+    }
+}
+
+/// The weight a style or family name spells out should be the
+/// `usWeightClass`; a name without one means Regular (400).
+fn weight_match_result(names: &StyleNames, os2: Option<&Os2Style>) -> CheckResult {
+    const ID: &str = "name/weight-match";
+    let Some(os2) = os2 else {
+        return fail(ID, "font has no OS/2 table");
+    };
+    let found = names
+        .menu_style()
+        .and_then(|style| style_word(style, WEIGHT_WORDS))
+        .or_else(|| {
+            names
+                .menu_family()
+                .and_then(|family| style_word(family, WEIGHT_WORDS))
+        });
+    named_class_result(ID, "usWeightClass", os2.weight, found, 400)
+}
+
+/// The width a style or family name spells out should be the
+/// `usWidthClass`; a name without one means Normal (5).
+fn width_match_result(names: &StyleNames, os2: Option<&Os2Style>) -> CheckResult {
+    const ID: &str = "name/width-match";
+    let Some(os2) = os2 else {
+        return fail(ID, "font has no OS/2 table");
+    };
+    let found = names
+        .menu_style()
+        .and_then(|style| style_word(style, WIDTH_WORDS))
+        .or_else(|| {
+            names
+                .menu_family()
+                .and_then(|family| style_word(family, WIDTH_WORDS))
+        });
+    named_class_result(ID, "usWidthClass", os2.width, found, 5)
+}
+
+/// Judge an OS/2 class against the word found in the names, if any.
+fn named_class_result(
+    id: &'static str,
+    field: &str,
+    value: u16,
+    found: Option<(&'static str, Option<u16>)>,
+    default: u16,
+) -> CheckResult {
+    match found {
+        Some((word, None)) => pass(
+            id,
+            format!("{field} {value}; {word:?} names no single value"),
+        ),
+        Some((word, Some(expected))) if expected == value => {
+            pass(id, format!("{field} {value} matches {word:?}"))
+        }
+        Some((word, Some(expected))) => warn(
+            id,
+            format!("{field} {value} does not match {word:?} in the name ({expected})"),
+        ),
+        None if value == default => pass(id, format!("{field} is {value}")),
+        None => warn(
+            id,
+            format!("{field} is {value}, but the name spells out none (expected {default})"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn postscript_names_reject_spaces_and_length() {
+        assert!(postscript_name_problem("NotoSans-Regular").is_none());
+        assert!(postscript_name_problem("Noto Sans").is_some());
+        assert!(postscript_name_problem(&"A".repeat(64)).is_some());
+    }
+
+    #[test]
+    fn units_per_em_warns_on_odd_values() {
+        assert_eq!(units_per_em_result(1000).status, CheckStatus::Pass);
+        assert_eq!(units_per_em_result(2048).status, CheckStatus::Pass);
+        assert_eq!(units_per_em_result(1500).status, CheckStatus::Warn);
+        assert_eq!(units_per_em_result(8).status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn weight_class_allows_odd_values_for_variable_fonts() {
+        assert_eq!(weight_class_result(450, false).status, CheckStatus::Warn);
+        assert_eq!(weight_class_result(450, true).status, CheckStatus::Pass);
+        assert_eq!(weight_class_result(0, true).status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn unreadable_file_reports_parse_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.ttf");
+        fs::write(&path, b"not a font").unwrap();
+
+        let reports = validate_file(&path, ValidationProfile::FontbakeryLite);
+
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].status, CheckStatus::Fail);
+        assert_eq!(reports[0].checks[0].id, "font/parse");
+    }
+
+    fn style_names(family: &str, subfamily: &str, full: &str, postscript: &str) -> StyleNames {
+        StyleNames {
+            family: Some(family.into()),
+            subfamily: Some(subfamily.into()),
+            full: Some(full.into()),
+            postscript: Some(postscript.into()),
+            ..StyleNames::default()
+        }
+    }
+
+    #[test]
+    fn consistent_names_pass_the_naming_audit() {
+        let os2 = Os2Style {
+            weight: 700,
+            width: 5,
+            fs_selection: FS_SELECTION_BOLD | FS_SELECTION_ITALIC,
+        };
+        let names = style_names(
+            "Noto Sans",
+            "Bold Italic",
+            "Noto Sans Bold Italic",
+            "NotoSans-BoldItalic",
+        );
+        assert_eq!(full_name_result(&names).status, CheckStatus::Pass);
+        assert_eq!(postscript_match_result(&names).status, CheckStatus::Pass);
+        assert_eq!(
+            subfamily_result(&names, Some(&os2)).status,
+            CheckStatus::Pass
+        );
+        assert_eq!(
+            weight_match_result(&names, Some(&os2)).status,
+            CheckStatus::Pass
+        );
+        assert_eq!(
+            width_match_result(&names, Some(&os2)).status,
+            CheckStatus::Pass
+        );
+
+        // Regular may be left out of the full and PostScript names.
+        let regular = style_names("Noto Sans", "Regular", "Noto Sans", "NotoSans");
+        assert_eq!(full_name_result(&regular).status, CheckStatus::Pass);
+        assert_eq!(postscript_match_result(&regular).status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn naming_audit_flags_disagreements() {
+        let mut names = style_names(
+            "Noto Sans Condensed",
+            "Regular",
+            "Noto Sans SemiBold",
+            "NotoSansCond-SemiBold",
+        );
+        names.typographic_family = Some("Noto Sans Condensed".into());
+        names.typographic_subfamily = Some("SemiBold".into());
+        let os2 = Os2Style {
+            weight: 700,
+            width: 5,
+            fs_selection: FS_SELECTION_BOLD,
+        };
+        assert_eq!(full_name_result(&names).status, CheckStatus::Warn);
+        assert_eq!(postscript_match_result(&names).status, CheckStatus::Warn);
+        assert_eq!(
+            subfamily_result(&names, Some(&os2)).status,
+            CheckStatus::Fail
+        );
+        let weight = weight_match_result(&names, Some(&os2));
+        assert_eq!(weight.status, CheckStatus::Warn);
+        assert!(weight.message.contains("semibold"), "{}", weight.message);
+        assert_eq!(
+            width_match_result(&names, Some(&os2)).status,
+            CheckStatus::Warn
+        );
+
+        names.subfamily = Some("SemiBold".into());
+        assert_eq!(
+            subfamily_result(&names, Some(&os2)).status,
+            CheckStatus::Warn
+        );
+    }
+
+    #[test]
+    fn duplicate_names_span_files_and_put_conflicts_first() {
+        let face = |path: &str, ttc_index: Option<u32>, postscript: &str, full: &str| {
+            (
+                TypgFontSource {
+                    path: PathBuf::from(path),
+                    ttc_index,
+                },
+                StyleNames {
+                    postscript: Some(postscript.into()),
+                    full: Some(full.into()),
+                    ..StyleNames::default()
+                },
+            )
+        };
+        let faces = vec![
+            face("/a/Sans.ttf", None, "Sans-Regular", "Sans"),
+            face("/b/Sans.ttf", None, "Sans-Regular", "Sans"),
+            face("/a/Serif.ttf", None, "Serif-Bold", "Serif Bold"),
+            face("/b/Serif.otf", None, "Serif-Bold", "Serif Bold Alt"),
+            face("/a/Pair.ttc", Some(0), "Pair", "Pair"),
+            face("/a/Pair.ttc", Some(1), "Pair", "Pair"),
+        ];
+        let hash = |path: &Path| Some(if path.ends_with("Serif.otf") { 2 } else { 1 });
+
+        let found: Vec<_> = group_duplicates(faces, hash)
+            .into_iter()
+            .map(|dup| (dup.kind, dup.name, dup.contents_differ, dup.faces.len()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (DuplicateKind::Postscript, "Serif-Bold".to_string(), true, 2),
+                (
+                    DuplicateKind::Postscript,
+                    "Sans-Regular".to_string(),
+                    false,
+                    2
+                ),
+                (DuplicateKind::Full, "Sans".to_string(), false, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn ambiguous_style_words_are_not_judged() {
+        assert_eq!(
+            style_word("Semi Light", WEIGHT_WORDS),
+            Some(("semilight", None))
+        );
+        assert_eq!(
+            style_word("Extra-Light Italic", WEIGHT_WORDS),
+            Some(("extralight", Some(200)))
+        );
+        assert_eq!(style_word("Italic", WEIGHT_WORDS), None);
+        assert_eq!(
+            style_word("SemiCondensed Bold", WIDTH_WORDS),
+            Some(("semicondensed", Some(4)))
+        );
+    }
+}
@@ -2,7 +2,9 @@
 use std::path::PathBuf;
 
 use typg_core::query::Query;
-use typg_core::search::{filter_cached, TypgFontFaceMatch, TypgFontFaceMeta, TypgFontSource};
+use typg_core::search::{
+    filter_cached, group_families, TypgFontFaceMatch, TypgFontFaceMeta, TypgFontSource,
+};
 use typg_core::tags::tag4;
 
 fn metadata_with(name: &str, axis: Option<&str>, ttc_index: Option<u32>) -> TypgFontFaceMatch {
@@ -12,18 +14,11 @@ fn metadata_with(name: &str, axis: Option<&str>, ttc_index: Option<u32>) -> Typg
             ttc_index,
         },
         metadata: TypgFontFaceMeta {
-            names: vec![name.to_string()],
+            names: vec![name.into()],
             axis_tags: axis.into_iter().map(|t| tag4(t).expect("tag")).collect(),
-            feature_tags: Vec::new(),
-            script_tags: Vec::new(),
-            table_tags: Vec::new(),
             codepoints: vec!['A'],
             is_variable: axis.is_some(),
-            weight_class: None,
-            width_class: None,
-            family_class: None,
-            creator_names: Vec::new(),
-            license_names: Vec::new(),
+            ..Default::default()
         },
     }
 }
@@ -43,7 +38,7 @@ fn filters_cached_metadata_without_io() {
     let matches = filter_cached(&entries, &query);
 
     assert_eq!(matches.len(), 1);
-    assert_eq!(matches[0].metadata.names[0], "Sans");
+    assert_eq!(&*matches[0].metadata.names[0], "Sans");
     assert!(matches[0].metadata.is_variable);
 }
 
@@ -60,7 +55,7 @@ fn sorts_by_path_and_ttc_index() {
 
     let names: Vec<(String, Option<u32>)> = matches
         .iter()
-        .map(|m| (m.metadata.names[0].clone(), m.source.ttc_index))
+        .map(|m| (m.metadata.names[0].to_string(), m.source.ttc_index))
         .collect();
 
     assert_eq!(
@@ -72,3 +67,47 @@ fn sorts_by_path_and_ttc_index() {
         ]
     );
 }
+
+#[test]
+fn groups_faces_by_family() {
+    let face = |name: &str, family: &str, style: &str, weight: u16| {
+        let mut entry = metadata_with(name, None, None);
+        entry.metadata.family_name = Some(family.into());
+        entry.metadata.style_name = Some(style.into());
+        entry.metadata.weight_class = Some(weight);
+        entry
+    };
+    let entries = vec![
+        face("Inter-Bold", "Inter", "Bold", 700),
+        face("Alegreya", "Alegreya", "Regular", 400),
+        face("Inter-Regular", "inter", "Regular", 400),
+    ];
+
+    let groups = group_families(&entries);
+
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].family, "Alegreya");
+    assert_eq!(groups[1].family, "Inter");
+    let styles: Vec<_> = groups[1]
+        .members
+        .iter()
+        .map(|m| m.style.as_deref().unwrap())
+        .collect();
+    assert_eq!(styles, vec!["Regular", "Bold"]);
+}
+
+#[test]
+fn collection_filters_check_face_index() {
+    let entries = vec![
+        metadata_with("Single", None, None),
+        metadata_with("Noto", None, Some(0)),
+        metadata_with("Noto", None, Some(1)),
+    ];
+
+    let only = filter_cached(&entries, &Query::new().require_collection(true));
+    assert_eq!(only.len(), 2);
+
+    let first = filter_cached(&entries, &Query::new().with_collection_index(Some(1)));
+    assert_eq!(first.len(), 1);
+    assert_eq!(first[0].source.ttc_index, Some(1));
+}
@@ -1,9 +1,11 @@
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use regex::Regex;
 use typg_core::query::Query;
-use typg_core::search::{search, SearchOptions};
+use typg_core::search::{read_metadata, scan_files, search, search_each, SearchOptions};
 
 fn fonts_dir() -> Option<PathBuf> {
     if let Ok(env_override) = env::var("TYPF_TEST_FONTS") {
@@ -58,3 +60,150 @@ fn name_queries_use_name_table_strings() {
         "expected NotoSans-Regular.ttf to match name-table regex"
     );
 }
+
+#[test]
+fn search_each_delivers_the_same_faces_as_search() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let roots = [fonts];
+    let opts = SearchOptions::default();
+
+    let expected = search(&roots, &Query::new(), &opts).expect("search fonts");
+    let streamed = Mutex::new(Vec::new());
+    let report = search_each(&roots, &Query::new(), &opts, |m| {
+        streamed.lock().unwrap().push(m.source.path_with_index());
+    })
+    .expect("stream fonts");
+
+    let mut streamed = streamed.into_inner().unwrap();
+    streamed.sort();
+    let mut expected: Vec<String> = expected
+        .iter()
+        .map(|m| m.source.path_with_index())
+        .collect();
+    expected.sort();
+    assert_eq!(streamed, expected);
+    assert_eq!(report.matches, expected.len());
+}
+
+#[test]
+fn scan_files_reads_the_listed_files_in_order() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let opts = SearchOptions::default();
+    let mut files: Vec<PathBuf> = search(std::slice::from_ref(&fonts), &Query::new(), &opts)
+        .expect("search fonts")
+        .into_iter()
+        .map(|m| m.source.path)
+        .collect();
+    files.dedup();
+    files.reverse();
+    files.push(fonts.join("missing.ttf"));
+    files.push(fonts);
+
+    let (faces, report) = scan_files(&files, &opts).expect("scan files");
+
+    let mut read: Vec<PathBuf> = faces.into_iter().map(|m| m.source.path).collect();
+    read.dedup();
+    assert_eq!(read, files[..files.len() - 2]);
+    assert_eq!(report.files_scanned, files.len());
+    assert_eq!(report.errors, 2, "a missing file and a directory");
+}
+
+#[test]
+fn name_ids_option_selects_name_records() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let roots = [fonts];
+
+    let defaults = search(&roots, &Query::new(), &SearchOptions::default()).expect("search");
+    let postscript_only = SearchOptions {
+        name_ids: Some(vec![6]),
+        ..SearchOptions::default()
+    };
+    let narrowed = search(&roots, &Query::new(), &postscript_only).expect("search");
+
+    assert_eq!(defaults.len(), narrowed.len());
+    for (full, ps) in defaults.iter().zip(&narrowed) {
+        assert!(ps.metadata.names.len() <= full.metadata.names.len());
+        assert!(ps
+            .metadata
+            .names
+            .iter()
+            .all(|name| full.metadata.names.contains(name)));
+        assert_eq!(ps.metadata.classified_as, full.metadata.classified_as);
+    }
+}
+
+#[test]
+fn variable_fonts_list_named_instances() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let roots = [fonts];
+    let variable = Query::new().require_variable(true);
+    let matches = search(&roots, &variable, &SearchOptions::default()).expect("search");
+
+    let Some(kalnia) = matches
+        .iter()
+        .find(|m| m.source.path.ends_with("Kalnia[wdth,wght].ttf"))
+    else {
+        return; // fixture set without Kalnia
+    };
+    let instances = &kalnia.metadata.named_instances;
+    assert!(!instances.is_empty(), "Kalnia should list named instances");
+    for instance in instances {
+        assert!(!instance.name.is_empty());
+        assert!(instance.coordinates.contains_key("wght"));
+        assert!(instance.coordinates.contains_key("wdth"));
+    }
+
+    let axes = &kalnia.metadata.axes;
+    let tags: Vec<&str> = axes.iter().map(|axis| axis.tag.as_str()).collect();
+    assert_eq!(tags.len(), kalnia.metadata.axis_tags.len());
+    for axis in axes {
+        assert!(axis.min <= axis.default && axis.default <= axis.max);
+        for instance in instances {
+            let value = instance.coordinates[&axis.tag];
+            assert!(
+                (axis.min..=axis.max).contains(&value),
+                "{} {value}",
+                axis.tag
+            );
+        }
+    }
+
+    let first = instances[0].name.to_uppercase();
+    let by_instance = Query::new().with_instances(vec![first]);
+    let found = search(&roots, &by_instance, &SearchOptions::default()).expect("search");
+    assert!(found.iter().any(|m| m.source.path == kalnia.source.path));
+    assert!(found.iter().all(|m| m.metadata.is_variable));
+}
+
+#[test]
+fn read_metadata_matches_a_file_scan() {
+    let fonts = match fonts_dir() {
+        Some(dir) => dir,
+        None => return, // skip when fixtures are unavailable
+    };
+    let opts = SearchOptions::default();
+    let scanned = search(&[fonts], &Query::new(), &opts).expect("search");
+    let Some(first) = scanned.first() else {
+        return;
+    };
+
+    let data = fs::read(&first.source.path).expect("read font");
+    let faces = read_metadata(&data, &first.source.path, &opts).expect("parse bytes");
+    assert!(faces.iter().any(|f| f.font_id() == first.font_id()));
+
+    let piped = read_metadata(&data, Path::new("-"), &opts).expect("parse bytes");
+    assert_eq!(piped.len(), faces.len());
+    assert!(piped[0].metadata.names.iter().all(|name| &**name != "-"));
+}
@@ -1,5 +1,7 @@
-use typg_core::output::{write_json_pretty, write_ndjson};
-use typg_core::search::{TypgFontFaceMatch, TypgFontFaceMeta, TypgFontSource};
+use typg_core::output::{
+    file_uri, unicode_range, write_css, write_fontconfig, write_json_pretty, write_ndjson,
+};
+use typg_core::search::{NamedInstance, TypgFontFaceMatch, TypgFontFaceMeta, TypgFontSource};
 use typg_core::tags::tag4;
 
 #[test]
@@ -30,6 +32,133 @@ fn writes_pretty_json_array() {
     assert_eq!(parsed.as_array().unwrap().len(), 2);
 }
 
+#[test]
+fn extra_metadata_is_emitted_only_when_present() {
+    let mut fonts = sample_fonts();
+    fonts[1]
+        .metadata
+        .extra
+        .insert("units_per_em".into(), serde_json::json!(2048));
+    let mut buf = Vec::new();
+
+    write_ndjson(&fonts, &mut buf).expect("write");
+    let text = String::from_utf8(buf).expect("utf8");
+    let lines: Vec<serde_json::Value> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json"))
+        .collect();
+
+    assert!(lines[0]["metadata"].get("extra").is_none());
+    assert_eq!(lines[1]["metadata"]["extra"]["units_per_em"], 2048);
+}
+
+#[test]
+fn writes_fontconfig_accept_and_alias_rules() {
+    let mut fonts = sample_fonts();
+    fonts[0].source.path = "fonts/A&B.ttf".into();
+    fonts[0].metadata.classified_as = Some("sans".into());
+    fonts[1].metadata.classified_as = Some("mono".into());
+    fonts[1].metadata.family_name = Some("Beta Mono".into());
+    let mut second_face = fonts[1].clone();
+    second_face.source.ttc_index = Some(2);
+    fonts.push(second_face);
+    let mut buf = Vec::new();
+
+    write_fontconfig(&fonts, &mut buf).expect("write");
+    let text = String::from_utf8(buf).expect("utf8");
+
+    assert!(text.starts_with("<?xml version=\"1.0\"?>\n<!DOCTYPE fontconfig"));
+    assert!(text.contains("      <pattern>\n        <patelt name=\"file\"><string>fonts/A&amp;B.ttf</string></patelt>\n      </pattern>\n"), "{text}");
+    assert!(
+        text.contains("<string>fonts/B.otf</string></patelt>\n      </pattern>\n    </acceptfont>"),
+        "{text}"
+    );
+    assert_eq!(text.matches("<pattern>").count(), 2, "one pattern per file");
+    assert!(text.contains("    <family>monospace</family>\n    <prefer>\n      <family>Beta Mono</family>\n    </prefer>"), "{text}");
+    assert!(
+        text.contains(
+            "    <family>sans-serif</family>\n    <prefer>\n      <family>Alpha</family>"
+        ),
+        "{text}"
+    );
+    assert_eq!(text.matches("<family>Beta Mono</family>").count(), 1);
+    assert!(text.ends_with("</fontconfig>\n"));
+}
+
+#[test]
+fn fontconfig_paths_are_matched_literally() {
+    let mut fonts = sample_fonts();
+    fonts.truncate(1);
+    fonts[0].source.path = "fonts/[Old] Type*/A?.ttf".into();
+    let mut buf = Vec::new();
+
+    write_fontconfig(&fonts, &mut buf).expect("write");
+    let text = String::from_utf8(buf).expect("utf8");
+
+    assert!(
+        text.contains(r#"<patelt name="file"><string>fonts/[Old] Type*/A?.ttf</string></patelt>"#),
+        "{text}"
+    );
+    assert!(!text.contains("<glob>"), "{text}");
+}
+
+#[test]
+fn writes_css_font_face_rules() {
+    let mut fonts = sample_fonts();
+    fonts[0].metadata.named_instances = [("Thin", 100.0), ("Black", 900.0)]
+        .into_iter()
+        .map(|(name, wght)| NamedInstance {
+            name: name.into(),
+            postscript_name: None,
+            coordinates: [("wght".to_string(), wght)].into(),
+        })
+        .collect();
+    fonts[1].metadata.family_name = Some("Beta \"Sans\"".into());
+    fonts[1].metadata.style_name = Some("Bold Italic".into());
+    let mut buf = Vec::new();
+
+    write_css(&fonts, 0, &mut buf).expect("write");
+    let text = String::from_utf8(buf).expect("utf8");
+
+    assert_eq!(
+        text,
+        "@font-face {\n  font-family: \"Alpha\";\n  src: local(\"Alpha\"), url(\"fonts/A.ttf\") format(\"truetype\");\n  font-weight: 100 900;\n  font-stretch: 100%;\n  unicode-range: U+0041-0042;\n}\n\n\
+         @font-face {\n  font-family: \"Beta \\\"Sans\\\"\";\n  src: local(\"Beta \\\"Sans\\\" Bold Italic\"), url(\"fonts/B.otf\") format(\"opentype\");\n  font-weight: 700;\n  font-style: italic;\n  unicode-range: U+0041;\n}\n"
+    );
+}
+
+#[test]
+fn unicode_range_is_exact_unless_given_a_gap() {
+    let latin: Vec<char> = (' '..='~').chain(['é', 'ó']).collect();
+    assert_eq!(unicode_range(&latin, 0), "U+0020-007E, U+00E9, U+00F3");
+    assert_eq!(unicode_range(&latin, 16), "U+0020-007E, U+00E9-00F3");
+    assert_eq!(unicode_range(&['A', 'Ж'], 16), "U+0041, U+0416");
+    assert_eq!(unicode_range(&[], 0), "");
+}
+
+#[test]
+#[cfg(unix)]
+fn file_uri_percent_encodes_absolute_paths() {
+    use std::path::Path;
+
+    assert_eq!(
+        file_uri(Path::new("/Library/Fonts/Noto Sans#2 (100%).ttf")),
+        "file:///Library/Fonts/Noto%20Sans%232%20%28100%25%29.ttf"
+    );
+    assert_eq!(
+        file_uri(Path::new("/fonts/Ünïcode/Schrift.otf")),
+        "file:///fonts/%C3%9Cn%C3%AFcode/Schrift.otf"
+    );
+    let relative = file_uri(Path::new("fonts/A.ttf"));
+    let cwd = std::env::current_dir().unwrap();
+    assert_eq!(relative, file_uri(&cwd.join("fonts/A.ttf")));
+    assert!(relative.starts_with("file:///"), "{relative}");
+
+    use std::os::unix::ffi::OsStrExt;
+    let raw = std::ffi::OsStr::from_bytes(b"/fonts/caf\xe9.ttf");
+    assert_eq!(file_uri(Path::new(raw)), "file:///fonts/caf%E9.ttf");
+}
+
 fn sample_fonts() -> Vec<TypgFontFaceMatch> {
     vec![
         TypgFontFaceMatch {
@@ -40,16 +169,13 @@ fn sample_fonts() -> Vec<TypgFontFaceMatch> {
             metadata: TypgFontFaceMeta {
                 names: vec!["Alpha".into()],
                 axis_tags: vec![tag4("wght").unwrap()],
-                feature_tags: vec![],
-                script_tags: vec![],
                 table_tags: vec![tag4("fvar").unwrap()],
                 codepoints: vec!['A', 'B'],
                 is_variable: true,
                 weight_class: Some(400),
                 width_class: Some(5),
                 family_class: Some((8, 0)),
-                creator_names: Vec::new(),
-                license_names: Vec::new(),
+                ..Default::default()
             },
         },
         TypgFontFaceMatch {
@@ -59,18 +185,56 @@ fn sample_fonts() -> Vec<TypgFontFaceMatch> {
             },
             metadata: TypgFontFaceMeta {
                 names: vec!["Beta".into()],
-                axis_tags: vec![],
-                feature_tags: vec![],
-                script_tags: vec![],
-                table_tags: vec![],
                 codepoints: vec!['A'],
-                is_variable: false,
                 weight_class: Some(700),
-                width_class: None,
-                family_class: None,
-                creator_names: Vec::new(),
-                license_names: Vec::new(),
+                ..Default::default()
             },
         },
     ]
 }
+
+#[test]
+fn gsub_and_gpos_features_serialize_separately() {
+    let mut fonts = sample_fonts();
+    fonts[0].metadata.gsub_feature_tags = vec![tag4("liga").unwrap()];
+    fonts[0].metadata.gpos_feature_tags = vec![tag4("kern").unwrap()];
+    let mut buf = Vec::new();
+
+    write_ndjson(&fonts[..1], &mut buf).expect("write");
+    let value: serde_json::Value =
+        serde_json::from_str(String::from_utf8(buf).expect("utf8").trim_end()).expect("json");
+
+    assert_eq!(
+        value["metadata"]["gsub_feature_tags"],
+        serde_json::json!(["liga"])
+    );
+    assert_eq!(
+        value["metadata"]["gpos_feature_tags"],
+        serde_json::json!(["kern"])
+    );
+}
+
+#[test]
+fn font_id_is_content_derived_and_path_independent() {
+    let fonts = sample_fonts();
+    let mut moved = fonts[0].clone();
+    moved.source.path = "/elsewhere/renamed.ttf".into();
+    moved.source.ttc_index = Some(3);
+    assert_eq!(moved.font_id(), fonts[0].font_id());
+    assert_eq!(fonts[0].font_id().len(), 16);
+    assert_ne!(fonts[0].font_id(), fonts[1].font_id());
+
+    let mut edited = fonts[0].clone();
+    edited.metadata.codepoints.push('\u{20ac}');
+    assert_ne!(edited.font_id(), fonts[0].font_id());
+
+    let mut buf = Vec::new();
+    write_ndjson(&fonts, &mut buf).expect("write");
+    let text = String::from_utf8(buf).expect("utf8");
+    let first: serde_json::Value =
+        serde_json::from_str(text.lines().next().unwrap()).expect("json");
+    assert_eq!(first["font_id"], fonts[0].font_id());
+
+    let back: TypgFontFaceMatch = serde_json::from_value(first).expect("round trip");
+    assert_eq!(back.font_id(), fonts[0].font_id());
+}
@@ -1,6 +1,12 @@
 use proptest::prelude::*;
 
-use typg_core::query::{parse_codepoint_list, parse_family_class, parse_u16_range};
+use typg_core::query::{
+    parse_codepoint_list, parse_family_class, parse_name_pattern, parse_u16_range, NameMatch,
+    FAMILY_CLASS_NAMES,
+};
+use typg_core::tags::{
+    tag4, tag_to_string, KNOWN_AXES, KNOWN_FEATURES, KNOWN_SCRIPTS, KNOWN_TABLES,
+};
 
 #[test]
 fn parses_single_codepoint_and_range() {
@@ -59,3 +65,39 @@ fn parses_family_class_names() {
     let sans = parse_family_class("sans-serif").expect("parse sans");
     assert_eq!(sans.major, 8);
 }
+
+#[test]
+fn known_tags_and_class_names_parse() {
+    for known in [KNOWN_AXES, KNOWN_FEATURES, KNOWN_SCRIPTS, KNOWN_TABLES].concat() {
+        let tag = tag4(known.tag).expect(known.tag);
+        assert_eq!(tag_to_string(tag), known.tag, "tags are listed in full");
+    }
+    for &(name, major) in FAMILY_CLASS_NAMES {
+        assert_eq!(parse_family_class(name).expect(name).major, major);
+    }
+}
+
+#[test]
+fn name_pattern_modes_control_case_and_anchoring() {
+    let plain = parse_name_pattern("inter", NameMatch::default()).expect("plain");
+    assert!(!plain.is_match("Inter Display"));
+
+    let folded = NameMatch {
+        ignore_case: true,
+        ..NameMatch::default()
+    };
+    assert!(parse_name_pattern("inter", folded)
+        .expect("folded")
+        .is_match("Inter Display"));
+
+    let exact = NameMatch {
+        ignore_case: true,
+        exact: true,
+    };
+    let whole = parse_name_pattern("inter|roboto", exact).expect("exact");
+    assert!(whole.is_match("Inter"));
+    assert!(whole.is_match("ROBOTO"));
+    assert!(!whole.is_match("Inter Display"));
+
+    assert!(parse_name_pattern("(", exact).is_err());
+}
@@ -1,7 +1,21 @@
-use typg_core::query::{FamilyClassFilter, Query};
-use typg_core::search::TypgFontFaceMeta;
+use std::path::Path;
+
+use typg_core::presets::Preset;
+use typg_core::query::{
+    parse_coverage_filter, parse_embedding, parse_path_glob, parse_query_expr,
+    parse_table_checksum, parse_unicode_block, FamilyClassFilter, Query,
+};
+use typg_core::search::{
+    expand_instances, parse_sort_keys, scan_files, search_each, search_with_report,
+    sort_matches_by, sort_matches_by_keys, CmapSubtable, CopyArtifact, Embedding, ErrorPolicy,
+    NamedInstance, SearchError, SearchOptions, SortKey, TypgFontFaceMatch, TypgFontFaceMeta,
+    TypgFontSource, VariationAxis,
+};
+use typg_core::select::nearest;
+use typg_core::stats::weight_stats;
 use typg_core::tags::tag4;
 
+#[allow(clippy::too_many_arguments)]
 fn metadata_with(
     name: &str,
     axes: &[&str],
@@ -15,7 +29,7 @@ fn metadata_with(
     family_class: Option<(u8, u8)>,
 ) -> TypgFontFaceMeta {
     TypgFontFaceMeta {
-        names: vec![name.to_string()],
+        names: vec![name.into()],
         axis_tags: axes.iter().map(|t| tag4(t).unwrap()).collect(),
         feature_tags: features.iter().map(|t| tag4(t).unwrap()).collect(),
         script_tags: scripts.iter().map(|t| tag4(t).unwrap()).collect(),
@@ -25,8 +39,7 @@ fn metadata_with(
         weight_class,
         width_class,
         family_class,
-        creator_names: Vec::new(),
-        license_names: Vec::new(),
+        ..Default::default()
     }
 }
 
@@ -84,11 +97,33 @@ fn fails_when_missing_table() {
     assert!(!query.matches(&meta));
 }
 
+#[test]
+fn table_checksums_must_all_match() {
+    let mut meta = metadata_with("File", &[], &[], &[], &[], &[], false, None, None, None);
+    meta.table_checksums = [("glyf".to_string(), 0xDEAD_BEEF), ("CFF ".to_string(), 7)]
+        .into_iter()
+        .collect();
+    let glyf = parse_table_checksum("glyf=0xdeadbeef").unwrap();
+
+    assert!(Query::new().with_table_checksums(vec![glyf]).matches(&meta));
+    assert!(Query::new()
+        .with_table_checksums(vec![glyf, parse_table_checksum("CFF=7").unwrap()])
+        .matches(&meta));
+
+    let wrong = Query::new().with_table_checksums(vec![glyf, (tag4("loca").unwrap(), 1)]);
+    assert!(!wrong.matches(&meta));
+    assert_eq!(wrong.score(&meta), 0.5);
+
+    // Entries without recorded checksums (older caches) never match.
+    meta.table_checksums.clear();
+    assert!(!Query::new().with_table_checksums(vec![glyf]).matches(&meta));
+}
+
 #[test]
 fn name_regex_must_match_any_name() {
     let query = Query::new().with_name_patterns(vec![regex::Regex::new("Mono").unwrap()]);
     let mut meta = metadata_with("Sans", &[], &[], &[], &[], &[], false, None, None, None);
-    meta.names.push("Mono Sans".to_string());
+    meta.names.push("Mono Sans".into());
 
     assert!(query.matches(&meta));
 }
@@ -229,3 +264,799 @@ fn family_class_filters_on_subclass_when_present() {
     );
     assert!(!query.matches(&different_subclass));
 }
+
+#[test]
+fn family_class_falls_back_to_heuristic_category() {
+    let query = Query::new().with_family_class(Some(FamilyClassFilter {
+        major: 8,
+        subclass: None,
+    }));
+
+    let mut unclassified = metadata_with(
+        "Plain Sans",
+        &[],
+        &[],
+        &[],
+        &[],
+        &[],
+        false,
+        None,
+        None,
+        Some((0, 0)),
+    );
+    unclassified.classified_as = Some("sans".to_string());
+    assert!(query.matches(&unclassified));
+
+    unclassified.classified_as = Some("serif".to_string());
+    assert!(!query.matches(&unclassified));
+
+    let subclass_query = Query::new().with_family_class(Some(FamilyClassFilter {
+        major: 8,
+        subclass: Some(1),
+    }));
+    unclassified.classified_as = Some("sans".to_string());
+    assert!(!subclass_query.matches(&unclassified));
+}
+
+fn with_features(name: &str, features: &[&str]) -> TypgFontFaceMeta {
+    metadata_with(name, &[], features, &[], &[], &[], false, None, None, None)
+}
+
+#[test]
+fn query_expr_combines_and_or() {
+    let expr = parse_query_expr("(feature:liga AND feature:smcp) OR feature:dlig").unwrap();
+    let query = Query::new().with_expr(Some(expr));
+
+    assert!(query.matches(&with_features("Both", &["liga", "smcp"])));
+    assert!(query.matches(&with_features("Dlig", &["dlig"])));
+    assert!(!query.matches(&with_features("LigaOnly", &["liga"])));
+}
+
+#[test]
+fn query_expr_supports_not_and_implicit_and() {
+    let expr = parse_query_expr("feature:liga !name:\"^Bad \"").unwrap();
+    let query = Query::new().with_expr(Some(expr));
+
+    assert!(query.matches(&with_features("Good Sans", &["liga"])));
+    assert!(!query.matches(&with_features("Bad Sans", &["liga"])));
+}
+
+#[test]
+fn presets_select_their_documented_fonts() {
+    let ascii: Vec<char> = (' '..='~').collect();
+
+    let mut web = metadata_with(
+        "Web Sans",
+        &[],
+        &["kern"],
+        &[],
+        &["OS/2", "GPOS"],
+        &ascii,
+        false,
+        None,
+        None,
+        None,
+    );
+    assert!(Preset::WebfontReady.query().matches(&web));
+    web.faces_in_collection = Some(2);
+    assert!(!Preset::WebfontReady.query().matches(&web));
+
+    let mut code = metadata_with("Plex", &[], &[], &[], &[], &ascii, false, None, None, None);
+    assert!(!Preset::CodeFonts.query().matches(&code));
+    code.classified_as = Some("mono".to_string());
+    assert!(Preset::CodeFonts.query().matches(&code));
+    code.classified_as = None;
+    code.names = vec!["JetBrains Mono".into()];
+    assert!(Preset::CodeFonts.query().matches(&code));
+
+    let arabic: Vec<char> = ('\u{0621}'..='\u{064A}')
+        .chain('\u{0660}'..='\u{0669}')
+        .collect();
+    let ui = metadata_with(
+        "Naskh",
+        &[],
+        &["fina", "init", "medi", "rlig"],
+        &["arab"],
+        &[],
+        &arabic,
+        false,
+        None,
+        None,
+        None,
+    );
+    assert!(Preset::ArabicUi.query().matches(&ui));
+    assert!(!Preset::ArabicUi.query().matches(&code));
+}
+
+#[test]
+fn and_keeps_both_queries_criteria() {
+    let query = Query::new()
+        .with_features(vec![tag4("liga").unwrap()])
+        .with_expr(Some(parse_query_expr("NOT name:Bad").unwrap()))
+        .and(Query::new().with_features(vec![tag4("smcp").unwrap()]));
+
+    assert!(query.matches(&with_features("Good", &["liga", "smcp"])));
+    assert!(!query.matches(&with_features("Good", &["liga"])));
+    assert!(!query.matches(&with_features("Good", &["smcp"])));
+    assert!(!query.matches(&with_features("Bad", &["liga", "smcp"])));
+}
+
+#[test]
+fn query_expr_rejects_malformed_input() {
+    assert!(parse_query_expr("").is_err());
+    assert!(parse_query_expr("(feature:liga").is_err());
+    assert!(parse_query_expr("feature:liga OR").is_err());
+    assert!(parse_query_expr("colour:red").is_err());
+}
+
+#[test]
+fn custom_predicates_must_all_pass() {
+    let query = Query::new()
+        .with_features(vec![tag4("liga").unwrap()])
+        .with_predicate(|meta| meta.names.iter().any(|n| n.ends_with("Pro")))
+        .with_predicate(|meta| !meta.feature_tags.is_empty());
+
+    assert!(query.matches(&with_features("Sans Pro", &["liga"])));
+    assert!(!query.matches(&with_features("Sans", &["liga"])));
+    assert!(!query.matches(&with_features("Sans Pro", &[])));
+}
+
+#[test]
+fn predicate_plugins_can_carry_state() {
+    use std::sync::Arc;
+    use typg_core::query::MetaPredicate;
+
+    struct MinCodepoints(usize);
+
+    impl MetaPredicate for MinCodepoints {
+        fn matches(&self, meta: &TypgFontFaceMeta) -> bool {
+            meta.codepoints.len() >= self.0
+        }
+    }
+
+    let query = Query::new().with_plugin(Arc::new(MinCodepoints(2)));
+    let small = metadata_with("Small", &[], &[], &[], &[], &['A'], false, None, None, None);
+    let large = metadata_with(
+        "Large",
+        &[],
+        &[],
+        &[],
+        &[],
+        &['A', 'B'],
+        false,
+        None,
+        None,
+        None,
+    );
+
+    assert!(!query.matches(&small));
+    assert!(query.matches(&large));
+}
+
+#[test]
+fn excluded_tags_reject_fonts_that_carry_them() {
+    let query = Query::new().without_features(vec![tag4("kern").unwrap()]);
+
+    assert!(query.matches(&with_features("NoKern", &["liga"])));
+    assert!(!query.matches(&with_features("Kerned", &["liga", "kern"])));
+}
+
+#[test]
+fn without_variable_keeps_static_fonts_only() {
+    let query = Query::new().without_variable(true);
+    let static_font = metadata_with("Static", &[], &[], &[], &[], &[], false, None, None, None);
+    let variable_font = metadata_with("Var", &["wght"], &[], &[], &[], &[], true, None, None, None);
+
+    assert!(query.matches(&static_font));
+    assert!(!query.matches(&variable_font));
+}
+
+#[test]
+fn excluded_name_patterns_reject_any_matching_name() {
+    let query = Query::new().without_name_patterns(vec![regex::Regex::new("(?i)test").unwrap()]);
+
+    assert!(query.matches(&with_features("Sans", &[])));
+    assert!(!query.matches(&with_features("Sans Test", &[])));
+}
+
+#[test]
+fn path_globs_match_file_names_or_full_paths() {
+    let query = Query::new().without_paths(vec![
+        parse_path_glob("*Test*").unwrap(),
+        parse_path_glob("**/old/**").unwrap(),
+    ]);
+
+    assert!(query.matches_path(Path::new("/fonts/Sans-Regular.ttf")));
+    assert!(!query.matches_path(Path::new("/fonts/SansTest-Regular.ttf")));
+    assert!(!query.matches_path(Path::new("/fonts/old/Sans-Regular.ttf")));
+}
+
+#[test]
+fn min_glyphs_and_vendor_filter_font_info() {
+    let query = Query::new()
+        .with_min_glyphs(Some(500))
+        .with_vendors(vec!["adbe".into(), "GOOG".into()]);
+    let font = |glyphs: Option<u16>, vendor: Option<&str>| {
+        let mut meta = with_features("Sans", &[]);
+        meta.glyph_count = glyphs;
+        meta.vendor_id = vendor.map(str::to_string);
+        meta
+    };
+
+    assert!(query.matches(&font(Some(800), Some("ADBE"))));
+    assert!(!query.matches(&font(Some(200), Some("ADBE"))));
+    assert!(!query.matches(&font(Some(800), Some("MS"))));
+    assert!(!query.matches(&font(None, Some("GOOG"))));
+    assert!(!query.matches(&font(Some(800), None)));
+}
+
+fn instance(name: &str, wght: f32) -> NamedInstance {
+    NamedInstance {
+        name: name.into(),
+        postscript_name: None,
+        coordinates: [("wght".to_string(), wght)].into_iter().collect(),
+    }
+}
+
+#[test]
+fn instance_filter_ignores_case_spaces_and_hyphens() {
+    let mut meta = metadata_with(
+        "Flex",
+        &["wght"],
+        &[],
+        &[],
+        &[],
+        &[],
+        true,
+        None,
+        None,
+        None,
+    );
+    meta.named_instances = vec![instance("Light", 300.0), instance("SemiBold", 600.0)];
+
+    for wanted in ["SemiBold", "semi bold", "Semi-Bold"] {
+        let query = Query::new().with_instances(vec![wanted.to_string()]);
+        assert!(query.matches(&meta), "{wanted} should match");
+    }
+    assert!(!Query::new()
+        .with_instances(vec!["Black".into()])
+        .matches(&meta));
+
+    let static_face = with_features("Sans", &[]);
+    assert!(!Query::new()
+        .with_instances(vec!["Light".into()])
+        .matches(&static_face));
+}
+
+#[test]
+fn expand_instances_lists_matching_instances_and_static_faces() {
+    let mut variable = metadata_with(
+        "Flex",
+        &["wght"],
+        &[],
+        &[],
+        &[],
+        &[],
+        true,
+        None,
+        None,
+        None,
+    );
+    variable.family_name = Some("Flex".into());
+    variable.named_instances = vec![instance("Light", 300.0), instance("Bold", 700.0)];
+    let mut fixed = with_features("Sans", &[]);
+    fixed.style_name = Some("Regular".into());
+    let matches: Vec<TypgFontFaceMatch> = [("/f/Flex.ttf", variable), ("/f/Sans.ttf", fixed)]
+        .into_iter()
+        .map(|(path, metadata)| TypgFontFaceMatch {
+            source: TypgFontSource {
+                path: path.into(),
+                ttc_index: None,
+            },
+            metadata,
+        })
+        .collect();
+
+    let rows = expand_instances(&matches, &Query::new());
+    let styles: Vec<_> = rows.iter().map(|row| row.style.as_deref()).collect();
+    assert_eq!(styles, [Some("Light"), Some("Bold"), Some("Regular")]);
+    assert_eq!(rows[1].family, "Flex");
+    assert_eq!(rows[1].coordinates["wght"], 700.0);
+    assert!(rows[2].coordinates.is_empty());
+
+    let bold = Query::new().with_instances(vec!["bold".into()]);
+    let rows = expand_instances(&matches[..1], &bold);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].style.as_deref(), Some("Bold"));
+}
+
+#[test]
+fn languages_require_every_langsys_tag() {
+    let query = Query::new().with_languages(vec![tag4("TRK").unwrap(), tag4("AZE").unwrap()]);
+    let mut meta = with_features("Sans", &[]);
+    meta.language_tags = vec![tag4("TRK").unwrap()];
+    assert!(!query.matches(&meta));
+
+    meta.language_tags.push(tag4("AZE").unwrap());
+    assert!(query.matches(&meta));
+
+    let expr = parse_query_expr("lang:TRK").unwrap();
+    assert!(expr.matches(&meta));
+}
+
+#[test]
+fn coverage_filters_measure_assigned_block_share() {
+    // Printable ASCII: 95 of Basic Latin's 128 assigned codepoints (~74%).
+    let mut meta = with_features("Sans", &[]);
+    meta.codepoints = (' '..='~').collect();
+
+    let at = |spec: &str| Query::new().with_coverage(vec![parse_coverage_filter(spec).unwrap()]);
+    assert!(at("Basic Latin:70%").matches(&meta));
+    assert!(!at("basic-latin:80").matches(&meta));
+    assert!(!at("Cyrillic:1%").matches(&meta));
+
+    let full = Query::new().with_coverage(vec![parse_unicode_block("Basic Latin").unwrap()]);
+    assert!(!full.matches(&meta));
+    meta.codepoints = ('\0'..='\x7f').collect();
+    assert!(full.matches(&meta));
+
+    let expr = parse_query_expr(r#"coverage:"Basic Latin:100%" block:basiclatin"#).unwrap();
+    assert!(expr.matches(&meta));
+
+    assert!(parse_coverage_filter("Cyrillic:120%").is_err());
+    assert!(parse_unicode_block("Klingon").is_err());
+}
+
+#[test]
+fn search_each_counts_unparsable_files_without_calling_back() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    std::fs::write(temp.path().join("broken.ttf"), b"\0\0not a font").unwrap();
+
+    let calls = std::sync::atomic::AtomicUsize::new(0);
+    let report = search_each(
+        &[temp.path().to_path_buf()],
+        &Query::new(),
+        &SearchOptions::default(),
+        |_| {
+            calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        },
+    )
+    .expect("search");
+
+    assert_eq!(calls.into_inner(), 0);
+    assert_eq!(report.files_scanned, 1);
+    assert_eq!(report.errors, 1);
+    assert_eq!(report.matches, 0);
+}
+
+#[test]
+fn error_policy_collects_or_fails_on_unparsable_files() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let broken = temp.path().join("broken.otf");
+    std::fs::write(&broken, b"not a font").unwrap();
+    let roots = [temp.path().to_path_buf()];
+
+    let skip = search_with_report(&roots, &Query::new(), &SearchOptions::default()).unwrap();
+    assert_eq!(skip.1.errors, 1);
+    assert!(skip.1.failures.is_empty());
+
+    let collect = SearchOptions {
+        error_policy: ErrorPolicy::Collect,
+        ..SearchOptions::default()
+    };
+    let (matches, report) = search_with_report(&roots, &Query::new(), &collect).unwrap();
+    assert!(matches.is_empty());
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].path, broken);
+
+    let fail = SearchOptions {
+        error_policy: ErrorPolicy::Fail,
+        ..SearchOptions::default()
+    };
+    let err = search_each(&roots, &Query::new(), &fail, |_| {}).unwrap_err();
+    let failure = err.downcast_ref::<SearchError>().expect("SearchError");
+    assert_eq!(failure.path, broken);
+}
+
+#[test]
+fn copy_artifacts_are_skipped_apart_from_errors() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let apple_double = temp.path().join("._Font.ttf");
+    let empty = temp.path().join("Empty.otf");
+    std::fs::write(&apple_double, b"\x00\x05\x16\x07Mac OS X").unwrap();
+    std::fs::write(&empty, b"").unwrap();
+    std::fs::write(temp.path().join("broken.ttf"), b"not a font").unwrap();
+    assert_eq!(
+        CopyArtifact::detect(&apple_double),
+        Some(CopyArtifact::AppleDouble)
+    );
+    assert_eq!(CopyArtifact::detect(&empty), Some(CopyArtifact::Empty));
+
+    let collect = SearchOptions {
+        include_hidden: true,
+        error_policy: ErrorPolicy::Collect,
+        ..SearchOptions::default()
+    };
+    let roots = [temp.path().to_path_buf()];
+    let (_, report) = search_with_report(&roots, &Query::new(), &collect).unwrap();
+    assert_eq!(report.files_scanned, 3);
+    assert_eq!((report.apple_double, report.empty_files), (1, 1));
+    assert_eq!(report.errors, 1);
+    assert_eq!(report.failures.len(), 1);
+
+    // Even a strict scan of exactly these files passes them over.
+    let strict = SearchOptions {
+        error_policy: ErrorPolicy::Fail,
+        ..SearchOptions::default()
+    };
+    let (faces, report) = scan_files(&[apple_double, empty], &strict).unwrap();
+    assert!(faces.is_empty());
+    assert_eq!(report.errors, 0);
+}
+
+#[test]
+fn deadline_stops_the_search_and_flags_the_report() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let broken = temp.path().join("broken.otf");
+    std::fs::write(&broken, b"not a font").unwrap();
+    let roots = [temp.path().to_path_buf()];
+
+    let (_, report) = search_with_report(&roots, &Query::new(), &SearchOptions::default()).unwrap();
+    assert!(!report.truncated);
+    assert_eq!(report.files_scanned, 1);
+
+    // Out of time before the first file: nothing is opened, not even to fail.
+    let late = SearchOptions {
+        error_policy: ErrorPolicy::Fail,
+        deadline: Some(std::time::Instant::now()),
+        ..SearchOptions::default()
+    };
+    let (matches, report) = search_with_report(&roots, &Query::new(), &late).unwrap();
+    assert!(matches.is_empty());
+    assert!(report.truncated);
+    assert_eq!(report.files_scanned, 0);
+    assert!(report
+        .to_string()
+        .ends_with("out of time, results are incomplete"));
+
+    let (faces, report) = scan_files(&[broken], &late).unwrap();
+    assert!(faces.is_empty());
+    assert!(report.truncated);
+    let report = search_each(&roots, &Query::new(), &late, |_| {}).unwrap();
+    assert!(report.truncated);
+}
+
+#[test]
+fn weight_stats_buckets_faces_and_finds_families_without_bold() {
+    let face = |family: &str, weight: Option<u16>| {
+        let mut meta = metadata_with(family, &[], &[], &[], &[], &[], false, weight, None, None);
+        meta.family_name = Some(family.into());
+        TypgFontFaceMatch {
+            source: TypgFontSource {
+                path: format!("/fonts/{family}-{weight:?}.ttf").into(),
+                ttc_index: None,
+            },
+            metadata: meta,
+        }
+    };
+    let mut variable = face("Flex", Some(400));
+    variable.metadata.named_instances = vec![NamedInstance {
+        name: "Bold".into(),
+        postscript_name: None,
+        coordinates: [("wght".to_string(), 700.0)].into_iter().collect(),
+    }];
+    let matches = vec![
+        face("Inter", Some(400)),
+        face("inter", Some(700)),
+        face("Lora", Some(400)),
+        face("Lora", Some(650)),
+        face("Bare", None),
+        variable,
+    ];
+
+    let stats = weight_stats(&matches);
+    assert_eq!(stats.faces, 6);
+    assert_eq!(stats.unknown, 1);
+    let count = |weight: u16| {
+        stats
+            .buckets
+            .iter()
+            .find(|b| b.weight == weight)
+            .map(|b| b.faces)
+    };
+    assert_eq!(stats.buckets.len(), 9);
+    assert_eq!(count(400), Some(3));
+    assert_eq!(count(700), Some(2), "650 rounds up to Bold");
+    assert_eq!(count(100), Some(0));
+    assert_eq!(stats.families_without_bold, vec!["Bare".to_string()]);
+}
+
+#[test]
+fn unicode_cmap_only_rejects_legacy_and_symbol_cmaps() {
+    let subtable = |platform_id, encoding_id, format| CmapSubtable {
+        platform_id,
+        encoding_id,
+        format,
+    };
+    let with_cmap = |subtables: Vec<CmapSubtable>| {
+        let mut meta = metadata_with("Face", &[], &[], &[], &[], &['A'], false, None, None, None);
+        meta.cmap_subtables = subtables;
+        meta
+    };
+    let query = Query::new().require_unicode_cmap(true);
+
+    assert!(query.matches(&with_cmap(vec![subtable(1, 0, 6), subtable(3, 1, 4)])));
+    assert!(query.matches(&with_cmap(vec![subtable(0, 4, 12)])));
+    assert!(!query.matches(&with_cmap(vec![subtable(1, 0, 0), subtable(0, 3, 6)])));
+    assert!(!query.matches(&with_cmap(vec![subtable(3, 0, 4)])));
+    assert!(subtable(3, 0, 4).is_symbol());
+    assert_eq!(subtable(3, 10, 12).to_string(), "3.10:12");
+
+    // Metadata without recorded subtables falls back to mapped codepoints.
+    assert!(query.matches(&with_cmap(Vec::new())));
+    let mut empty = with_cmap(Vec::new());
+    empty.codepoints.clear();
+    assert!(!query.matches(&empty));
+    assert!(Query::new().matches(&empty));
+}
+
+#[test]
+fn axis_count_bounds_separate_simple_and_complex_variable_fonts() {
+    let face = |axes: &[&str]| {
+        metadata_with(
+            "Face",
+            axes,
+            &[],
+            &[],
+            &[],
+            &[],
+            !axes.is_empty(),
+            None,
+            None,
+            None,
+        )
+    };
+    let weight_only = face(&["wght"]);
+    let multi = face(&["wght", "wdth", "opsz"]);
+    let static_face = face(&[]);
+
+    let complex = Query::new().with_min_axes(Some(2));
+    assert!(!complex.matches(&weight_only));
+    assert!(complex.matches(&multi));
+
+    let simple = Query::new().require_variable(true).with_max_axes(Some(1));
+    assert!(simple.matches(&weight_only));
+    assert!(!simple.matches(&multi));
+    assert!(!simple.matches(&static_face));
+    assert!(Query::new().with_max_axes(Some(1)).matches(&static_face));
+}
+
+#[test]
+fn embedding_filter_keeps_fonts_at_least_as_permissive() {
+    let with_fs_type = |fs_type: Option<u16>| {
+        let mut meta = metadata_with("Face", &[], &[], &[], &[], &[], false, None, None, None);
+        meta.fs_type = fs_type;
+        meta.embedding = fs_type.map(Embedding::from_fs_type);
+        meta
+    };
+    let installable = with_fs_type(Some(0x0000));
+    let restricted = with_fs_type(Some(0x0002));
+    let preview = with_fs_type(Some(0x0004));
+    // Editable plus no-subsetting: bits 8/9 don't change the level.
+    let editable = with_fs_type(Some(0x0108));
+    let no_os2 = with_fs_type(None);
+    assert_eq!(editable.embedding, Some(Embedding::Editable));
+    // Several usage bits set: the least restrictive wins.
+    assert_eq!(Embedding::from_fs_type(0x0006), Embedding::Preview);
+
+    let strict = Query::new().with_embedding(Some(parse_embedding("Installable").unwrap()));
+    assert!(strict.matches(&installable));
+    assert!(!strict.matches(&editable));
+    assert!(!strict.matches(&no_os2));
+
+    let editing = Query::new().with_embedding(Some(Embedding::Editable));
+    assert!(editing.matches(&installable));
+    assert!(editing.matches(&editable));
+    assert!(!editing.matches(&preview));
+    assert!(!editing.matches(&restricted));
+
+    let err = parse_embedding("free").unwrap_err().to_string();
+    assert!(
+        err.contains("installable, editable, preview or restricted"),
+        "{err}"
+    );
+}
+
+#[test]
+fn sort_matches_by_orders_by_key_with_missing_values_last() {
+    let face = |path: &str, name: &str, weight: Option<u16>, size: Option<u64>| {
+        let mut metadata = with_features(name, &[]);
+        metadata.weight_class = weight;
+        metadata.file_size = size;
+        TypgFontFaceMatch {
+            source: TypgFontSource {
+                path: path.into(),
+                ttc_index: None,
+            },
+            metadata,
+        }
+    };
+    let mut matches = vec![
+        face("/f/a.ttf", "zeta", Some(700), Some(300)),
+        face("/f/b.ttf", "Alpha", None, Some(100)),
+        face("/f/c.ttf", "beta", Some(400), None),
+        face("/f/d.ttf", "Gamma", Some(400), Some(200)),
+    ];
+    let paths = |matches: &[TypgFontFaceMatch]| -> Vec<String> {
+        matches
+            .iter()
+            .map(|m| m.source.path.display().to_string())
+            .collect()
+    };
+
+    sort_matches_by(&mut matches, SortKey::Name, false);
+    assert_eq!(
+        paths(&matches),
+        ["/f/b.ttf", "/f/c.ttf", "/f/d.ttf", "/f/a.ttf"]
+    );
+
+    // Ties (weight 400) fall back to path order; missing weights go last.
+    sort_matches_by(&mut matches, SortKey::Weight, false);
+    assert_eq!(
+        paths(&matches),
+        ["/f/c.ttf", "/f/d.ttf", "/f/a.ttf", "/f/b.ttf"]
+    );
+
+    sort_matches_by(&mut matches, SortKey::Size, true);
+    assert_eq!(
+        paths(&matches),
+        ["/f/c.ttf", "/f/a.ttf", "/f/d.ttf", "/f/b.ttf"]
+    );
+
+    sort_matches_by(&mut matches, SortKey::Path, false);
+    assert_eq!(
+        paths(&matches),
+        ["/f/a.ttf", "/f/b.ttf", "/f/c.ttf", "/f/d.ttf"]
+    );
+}
+
+#[test]
+fn sort_matches_by_keys_orders_families_across_collections() {
+    let face = |path: &str, ttc_index: Option<u32>, family: &str, style: &str, weight: u16| {
+        let mut metadata = with_features(&format!("{family} {style}"), &[]);
+        metadata.family_name = Some(family.into());
+        metadata.style_name = Some(style.into());
+        metadata.weight_class = Some(weight);
+        metadata.width_class = Some(5);
+        TypgFontFaceMatch {
+            source: TypgFontSource {
+                path: path.into(),
+                ttc_index,
+            },
+            metadata,
+        }
+    };
+    // Collection order and file names put Bold Italic and Bold first.
+    let mut matches = vec![
+        face("/f/Sans.ttc", Some(0), "Sans", "Bold Italic", 700),
+        face("/f/Sans.ttc", Some(1), "Sans", "Bold", 700),
+        face("/f/Sans.ttc", Some(2), "Sans", "Italic", 400),
+        face("/f/Sans.ttc", Some(3), "Sans", "Regular", 400),
+        face("/f/Mono-Bold.ttf", None, "Mono", "Bold", 700),
+        face("/f/Mono-Regular.ttf", None, "Mono", "Regular", 400),
+    ];
+
+    let keys = parse_sort_keys("family, weight,width,ITALIC").unwrap();
+    assert_eq!(
+        keys,
+        [
+            SortKey::Family,
+            SortKey::Weight,
+            SortKey::Width,
+            SortKey::Italic
+        ]
+    );
+    sort_matches_by_keys(&mut matches, &keys, false);
+    let styles: Vec<String> = matches
+        .iter()
+        .map(|m| m.metadata.names[0].to_string())
+        .collect();
+    assert_eq!(
+        styles,
+        [
+            "Mono Regular",
+            "Mono Bold",
+            "Sans Regular",
+            "Sans Italic",
+            "Sans Bold",
+            "Sans Bold Italic"
+        ]
+    );
+
+    let err = parse_sort_keys("family,slant").unwrap_err().to_string();
+    assert!(err.contains("unknown sort key `slant`"), "{err}");
+    assert!(parse_sort_keys(" , ").is_err());
+}
+
+fn styled_face(style: &str, weight: u16, width_class: u16) -> TypgFontFaceMatch {
+    let mut metadata = with_features(&format!("Sans {style}"), &[]);
+    metadata.family_name = Some("Sans".into());
+    metadata.style_name = Some(style.into());
+    metadata.weight_class = Some(weight);
+    metadata.width_class = Some(width_class);
+    TypgFontFaceMatch {
+        source: TypgFontSource {
+            path: format!("/f/Sans-{}.ttf", style.replace(' ', "")).into(),
+            ttc_index: None,
+        },
+        metadata,
+    }
+}
+
+#[test]
+fn nearest_follows_css_weight_matching() {
+    let faces = vec![
+        styled_face("Light", 300, 5),
+        styled_face("Regular", 400, 5),
+        styled_face("SemiBold", 600, 5),
+        styled_face("Bold", 700, 5),
+    ];
+    let pick =
+        |weight: f32| nearest(&faces, weight, 100.0, false).map(|face| face.metadata.weight_class);
+
+    // Above 500, heavier faces come first; below 400, lighter ones.
+    assert_eq!(pick(650.0), Some(Some(700)));
+    assert_eq!(pick(800.0), Some(Some(700)));
+    assert_eq!(pick(350.0), Some(Some(300)));
+    assert_eq!(pick(200.0), Some(Some(300)));
+    // 400–500 looks up to 500, then down, then above 500.
+    assert_eq!(pick(450.0), Some(Some(400)));
+    assert_eq!(pick(500.0), Some(Some(400)));
+    assert_eq!(pick(400.0), Some(Some(400)));
+    assert!(nearest(&[], 400.0, 100.0, false).is_none());
+}
+
+#[test]
+fn nearest_narrows_width_then_style_then_weight() {
+    let faces = vec![
+        styled_face("Bold", 700, 5),
+        styled_face("Italic", 400, 5),
+        styled_face("Condensed Bold Italic", 700, 3),
+        styled_face("Oblique", 700, 5),
+    ];
+    let pick = |weight: f32, width: f32, italic: bool| {
+        nearest(&faces, weight, width, italic)
+            .and_then(|face| face.metadata.style_name.as_deref())
+            .map(str::to_string)
+    };
+
+    // Width wins over style and weight: 87.5% finds the condensed face.
+    assert_eq!(
+        pick(400.0, 87.5, false).as_deref(),
+        Some("Condensed Bold Italic")
+    );
+    // Style wins over weight: italic at 700 still takes the 400 italic.
+    assert_eq!(pick(700.0, 100.0, true).as_deref(), Some("Italic"));
+    // Upright text prefers upright faces, then oblique before italic.
+    assert_eq!(pick(400.0, 100.0, false).as_deref(), Some("Bold"));
+    let sloped = &faces[1..];
+    let upright = nearest(sloped, 700.0, 100.0, false).unwrap();
+    assert_eq!(upright.metadata.style_name.as_deref(), Some("Oblique"));
+}
+
+#[test]
+fn nearest_matches_anywhere_on_a_variable_axis() {
+    let mut variable = styled_face("Regular", 400, 5);
+    variable.source.path = "/f/Sans[wght].ttf".into();
+    variable.metadata.is_variable = true;
+    variable.metadata.axes = vec![VariationAxis {
+        tag: "wght".into(),
+        min: 100.0,
+        default: 400.0,
+        max: 900.0,
+    }];
+    let faces = vec![styled_face("SemiBold", 600, 5), variable];
+
+    let pick = nearest(&faces, 650.0, 100.0, false).unwrap();
+    assert_eq!(pick.source.path, Path::new("/f/Sans[wght].ttf"));
+}
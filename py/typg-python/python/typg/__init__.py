@@ -12,8 +12,14 @@ should feel like running into an old friend, not searching for lost keys.
 
 - **find()**: Stroll through directories, collect font friends
 - **find_paths()**: Just the GPS coordinates when you want to visit yourself  
+- **find_async()** / **find_each()**: The same search, awaited or streamed to a callback
 - **filter_cached()**: Browse your collection without bothering the disk
+- **inspect()**: Every name record, table, axis and feature of one font
+- **inspect_many()**: Full metadata for a list of files you already know, read in parallel
+- **load_cache()** / **save_cache()**: Read and write `typg cache` JSON files
 - **find_indexed()**: Sprint through pre-built indexes when caffeine wears off
+- **get_indexed()** / **get_indexed_by_path()**: Pull one indexed font back by its `index_id` or file
+- **TypgIndex**: Keep an index open and change it in `with index.writer() as w:` transactions
 
 ## How it Rolling
 
@@ -21,10 +27,8 @@ should feel like running into an old friend, not searching for lost keys.
 import typg
 
 # Find variable fonts that understand Arabic perfectly
-results = typg.find(
-    paths=["/System/Library/Fonts", "~/fonts"],
-    query=lambda q: q.has_script("Arab").has_axis("wght").is_variable()
-)
+arabic = typg.Query().scripts("arab").axes("wght").variable()
+results = typg.find(["/System/Library/Fonts", "~/fonts"], query=arabic)
 
 # Discover fonts with familiar faces
 sans_fonts = typg.find(
@@ -33,18 +37,25 @@ sans_fonts = typg.find(
 )
 
 # Browse cached data without waking up the file system
-installed_fonts = typg.filter_cached(cached_data, query=lambda q: q.has_feature("liga"))
+installed_fonts = typg.filter_cached(cached_data, query=typg.Query().features("liga"))
 ```
 
 ## Speak Your Language
 
 The query system actually listens:
-- Script support (has_script) - "Find fonts that read Arabic"
-- Variable font axes (has_axis) - "Show me fonts with weight knobs"  
-- OpenType features (has_feature) - "I need those pretty ligatures"
-- Font names (name_contains, name_matches) - "Remember fonts with 'Garamond'"
-- Variable fonts only (is_variable) - "Just the flexible ones, thanks"
-- Boolean logic (AND, OR, NOT) - "This but not that, or both actually"
+- Script support (scripts) - "Find fonts that read Arabic"
+- Variable font axes (axes) - "Show me fonts with weight knobs"
+- OpenType features (features) - "I need those pretty ligatures"
+- Font names (names) - "Remember fonts matching 'Garamond'"
+- Coverage (text, codepoints) - "Can it spell naïve?"
+- OS/2 classes (weight, width, family_class) - "Bold-ish, normal width"
+- Variable fonts only (variable) - "Just the flexible ones, thanks"
+
+`Axes`, `Features`, `Scripts`, `Tables` and `FamilyClass` name the common
+values (`Scripts.ARAB`, `FamilyClass.SANS`), so a typo fails loudly.
+
+Every `Query` method returns a new query, so a base query can be reused
+and refined; `to_dict()` / `Query.from_dict()` (and pickle) save it.
 
 ## Behind the Curtain
 
@@ -71,16 +82,73 @@ from __future__ import annotations
 from importlib import metadata
 
 # Import the workhorses: these functions actually do the heavy lifting
-from typg_python import filter_cached, find, find_paths
+from typg_python import (
+    Axes,
+    FamilyClass,
+    Features,
+    FontMatch,
+    FontMeta,
+    FontSource,
+    Query,
+    Scripts,
+    Tables,
+    filter_cached,
+    find,
+    find_async,
+    find_each,
+    find_paths,
+    inspect,
+    inspect_many,
+    load_cache,
+    save_cache,
+)
 
 # Public API - what we proudly show off to the world
-__all__ = ["find", "find_paths", "filter_cached", "__version__"]
+__all__ = [
+    "Axes",
+    "FamilyClass",
+    "Features",
+    "FontMatch",
+    "FontMeta",
+    "FontSource",
+    "Query",
+    "Scripts",
+    "Tables",
+    "find",
+    "find_async",
+    "find_each",
+    "find_paths",
+    "filter_cached",
+    "inspect",
+    "inspect_many",
+    "load_cache",
+    "save_cache",
+    "__version__",
+]
 
 # Optional speed boosters (only available if built with hpindex feature)
 try:
-    from typg_python import count_indexed, find_indexed, list_indexed
-
-    __all__.extend(["find_indexed", "list_indexed", "count_indexed"])
+    from typg_python import (
+        IndexWriter,
+        TypgIndex,
+        count_indexed,
+        find_indexed,
+        get_indexed,
+        get_indexed_by_path,
+        list_indexed,
+    )
+
+    __all__.extend(
+        [
+            "IndexWriter",
+            "TypgIndex",
+            "find_indexed",
+            "list_indexed",
+            "count_indexed",
+            "get_indexed",
+            "get_indexed_by_path",
+        ]
+    )
 except ImportError:
     pass  # No hpindex feature? No worries, we've got your back anyway
 
@@ -7,20 +7,72 @@ fluently - translating between Rust's speed and Python's comfort without breakin
 a sweat. No caffeine required, but fonts may become surprisingly addictive.
 """
 
+import asyncio
+import functools
+
 # Core search functions - always ready for action
+from ._typg_python import FontMatch, FontMeta, FontSource, Query
 from ._typg_python import filter_cached_py as filter_cached
+from ._typg_python import find_each_py as find_each
 from ._typg_python import find_py as find
 from ._typg_python import find_paths_py as find_paths
+from ._typg_python import inspect_many_py as inspect_many
+from ._typg_python import inspect_py as inspect
+from ._typg_python import load_cache_py as load_cache
+from ._typg_python import save_cache_py as save_cache
+from .tags import Axes, FamilyClass, Features, Scripts, Tables
+
+__all__ = [
+    "Axes",
+    "FamilyClass",
+    "Features",
+    "FontMatch",
+    "FontMeta",
+    "FontSource",
+    "Query",
+    "Scripts",
+    "Tables",
+    "find",
+    "find_async",
+    "find_each",
+    "find_paths",
+    "filter_cached",
+    "inspect",
+    "inspect_many",
+    "load_cache",
+    "save_cache",
+]
+
+
+async def find_async(paths, **filters):
+    """Awaitable `find`: scans on the loop's default executor.
 
-__all__ = ["find", "find_paths", "filter_cached"]
+    The scan releases the GIL, so the event loop keeps serving other tasks
+    while it runs. Takes the same arguments as `find`, including `query=`.
+    """
+    loop = asyncio.get_running_loop()
+    return await loop.run_in_executor(None, functools.partial(find, paths, **filters))
 
 # Premium indexed search - like having a personal font librarian
 # Only appears if you built with the hpindex feature flag
 try:
+    from ._typg_python import IndexWriter, TypgIndex
     from ._typg_python import count_indexed_py as count_indexed
     from ._typg_python import find_indexed_py as find_indexed
+    from ._typg_python import get_indexed_by_path_py as get_indexed_by_path
+    from ._typg_python import get_indexed_py as get_indexed
     from ._typg_python import list_indexed_py as list_indexed
 
-    __all__.extend(["find_indexed", "list_indexed", "count_indexed"])
+    __all__.extend(
+        [
+            "IndexWriter",
+            "TypgIndex",
+            "find_indexed",
+            "list_indexed",
+            "count_indexed",
+            "get_indexed",
+            "get_indexed_by_path",
+        ]
+    )
 except ImportError:
     pass  # Feature flag not enabled - enjoy the standard experience
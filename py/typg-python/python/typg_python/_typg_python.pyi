@@ -0,0 +1,283 @@
+"""Type stubs for the typg_python native extension."""
+
+from collections.abc import Callable
+from os import PathLike
+from types import TracebackType
+from typing import Any, final
+
+@final
+class FontSource:
+    """Where a face lives. Compares, sorts and hashes by (path, ttc_index)."""
+
+    def __init__(self, path: str, ttc_index: int | None = None) -> None: ...
+    @property
+    def path(self) -> str: ...
+    @property
+    def ttc_index(self) -> int | None: ...
+    @property
+    def path_with_index(self) -> str: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __lt__(self, other: FontSource) -> bool: ...
+    def __le__(self, other: FontSource) -> bool: ...
+    def __gt__(self, other: FontSource) -> bool: ...
+    def __ge__(self, other: FontSource) -> bool: ...
+    def __hash__(self) -> int: ...
+
+@final
+class FontMeta:
+    """What typg read from one face. Equal when the font_id fingerprint is."""
+
+    @property
+    def names(self) -> list[str]: ...
+    @property
+    def family_name(self) -> str | None: ...
+    @property
+    def style_name(self) -> str | None: ...
+    @property
+    def axis_tags(self) -> list[str]: ...
+    @property
+    def feature_tags(self) -> list[str]: ...
+    @property
+    def gsub_feature_tags(self) -> list[str]: ...
+    @property
+    def gpos_feature_tags(self) -> list[str]: ...
+    @property
+    def script_tags(self) -> list[str]: ...
+    @property
+    def language_tags(self) -> list[str]: ...
+    @property
+    def table_tags(self) -> list[str]: ...
+    @property
+    def codepoints(self) -> list[str]: ...
+    @property
+    def is_variable(self) -> bool: ...
+    @property
+    def named_instances(self) -> list[dict[str, Any]]: ...
+    @property
+    def axes(self) -> list[dict[str, Any]]: ...
+    @property
+    def weight_class(self) -> int | None: ...
+    @property
+    def width_class(self) -> int | None: ...
+    @property
+    def family_class(self) -> tuple[int, int] | None: ...
+    @property
+    def creator_names(self) -> list[str]: ...
+    @property
+    def license_names(self) -> list[str]: ...
+    @property
+    def classified_as(self) -> str | None: ...
+    @property
+    def units_per_em(self) -> int | None: ...
+    @property
+    def glyph_count(self) -> int | None: ...
+    @property
+    def font_revision(self) -> float | None: ...
+    @property
+    def vendor_id(self) -> str | None: ...
+    @property
+    def embedding(self) -> str | None: ...
+    @property
+    def faces_in_collection(self) -> int | None: ...
+    @property
+    def file_size(self) -> int | None: ...
+    @property
+    def modified(self) -> int | None: ...
+    @property
+    def index_id(self) -> int | None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    def __getitem__(self, key: str) -> Any: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+@final
+class FontMatch:
+    """One matching face, as returned by the search functions.
+
+    Compares, sorts and hashes by (source, font_id). `to_dict()` gives the
+    plain dict earlier releases returned; `m["path"]` still works.
+    """
+
+    @property
+    def source(self) -> FontSource: ...
+    @property
+    def path(self) -> str: ...
+    @property
+    def ttc_index(self) -> int | None: ...
+    @property
+    def font_id(self) -> str: ...
+    @property
+    def metadata(self) -> FontMeta: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    def __getitem__(self, key: str) -> Any: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __lt__(self, other: FontMatch) -> bool: ...
+    def __le__(self, other: FontMatch) -> bool: ...
+    def __gt__(self, other: FontMatch) -> bool: ...
+    def __ge__(self, other: FontMatch) -> bool: ...
+    def __hash__(self) -> int: ...
+
+@final
+class Query:
+    """Reusable search filters. Each method returns a new Query."""
+
+    def __init__(self) -> None: ...
+    def axes(self, *tags: str) -> Query: ...
+    def features(self, *tags: str) -> Query: ...
+    def scripts(self, *tags: str) -> Query: ...
+    def tables(self, *tags: str) -> Query: ...
+    def names(self, *patterns: str) -> Query: ...
+    def creator(self, *patterns: str) -> Query: ...
+    def license(self, *patterns: str) -> Query: ...
+    def codepoints(self, *specs: str) -> Query: ...
+    def text(self, text: str) -> Query: ...
+    def weight(self, range: int | str) -> Query: ...
+    def width(self, range: int | str) -> Query: ...
+    def family_class(self, value: str) -> Query: ...
+    def variable(self, variable: bool = True) -> Query: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @staticmethod
+    def from_dict(data: dict[str, Any]) -> Query: ...
+    def __eq__(self, other: object) -> bool: ...
+
+@final
+class TypgIndex:
+    """An LMDB font index, open while the object lives (hpindex builds only)."""
+
+    def __init__(self, index_path: str | PathLike[str]) -> None: ...
+    @property
+    def path(self) -> str: ...
+    def writer(self) -> IndexWriter: ...
+    def __len__(self) -> int: ...
+
+@final
+class IndexWriter:
+    """A write transaction: commits when its `with` block ends, aborts if it raises."""
+
+    def add(
+        self,
+        paths: list[str | PathLike[str]],
+        follow_symlinks: bool = False,
+        jobs: int | None = None,
+    ) -> int: ...
+    def remove_path(self, path: str | PathLike[str]) -> None: ...
+    def remove_faces(self, ids: list[int]) -> None: ...
+    def clear(self) -> None: ...
+    def commit(self) -> None: ...
+    def abort(self) -> None: ...
+    @property
+    def closed(self) -> bool: ...
+    def __enter__(self) -> IndexWriter: ...
+    def __exit__(
+        self,
+        exc_type: type[BaseException] | None,
+        exc_value: BaseException | None,
+        traceback: TracebackType | None,
+    ) -> bool: ...
+
+def find_py(
+    paths: list[str | PathLike[str]],
+    axes: list[str] | None = None,
+    features: list[str] | None = None,
+    scripts: list[str] | None = None,
+    tables: list[str] | None = None,
+    names: list[str] | None = None,
+    codepoints: list[str] | None = None,
+    text: str | None = None,
+    weight: str | None = None,
+    width: str | None = None,
+    family_class: str | None = None,
+    creator: list[str] | None = None,
+    license: list[str] | None = None,
+    variable: bool = False,
+    follow_symlinks: bool = False,
+    jobs: int | None = None,
+    query: Query | None = None,
+) -> list[FontMatch]: ...
+def find_paths_py(
+    paths: list[str | PathLike[str]],
+    axes: list[str] | None = None,
+    features: list[str] | None = None,
+    scripts: list[str] | None = None,
+    tables: list[str] | None = None,
+    names: list[str] | None = None,
+    codepoints: list[str] | None = None,
+    text: str | None = None,
+    weight: str | None = None,
+    width: str | None = None,
+    family_class: str | None = None,
+    creator: list[str] | None = None,
+    license: list[str] | None = None,
+    variable: bool = False,
+    follow_symlinks: bool = False,
+    jobs: int | None = None,
+    query: Query | None = None,
+) -> list[str]: ...
+def find_each_py(
+    callback: Callable[[FontMatch], object],
+    paths: list[str | PathLike[str]],
+    axes: list[str] | None = None,
+    features: list[str] | None = None,
+    scripts: list[str] | None = None,
+    tables: list[str] | None = None,
+    names: list[str] | None = None,
+    codepoints: list[str] | None = None,
+    text: str | None = None,
+    weight: str | None = None,
+    width: str | None = None,
+    family_class: str | None = None,
+    creator: list[str] | None = None,
+    license: list[str] | None = None,
+    variable: bool = False,
+    follow_symlinks: bool = False,
+    jobs: int | None = None,
+    query: Query | None = None,
+) -> int: ...
+def filter_cached_py(
+    entries: list[FontMatch] | list[Any],
+    axes: list[str] | None = None,
+    features: list[str] | None = None,
+    scripts: list[str] | None = None,
+    tables: list[str] | None = None,
+    names: list[str] | None = None,
+    codepoints: list[str] | None = None,
+    text: str | None = None,
+    weight: str | None = None,
+    width: str | None = None,
+    family_class: str | None = None,
+    creator: list[str] | None = None,
+    license: list[str] | None = None,
+    variable: bool = False,
+    query: Query | None = None,
+) -> list[FontMatch]: ...
+def find_indexed_py(
+    index_path: str | PathLike[str],
+    axes: list[str] | None = None,
+    features: list[str] | None = None,
+    scripts: list[str] | None = None,
+    tables: list[str] | None = None,
+    names: list[str] | None = None,
+    codepoints: list[str] | None = None,
+    text: str | None = None,
+    weight: str | None = None,
+    width: str | None = None,
+    family_class: str | None = None,
+    creator: list[str] | None = None,
+    license: list[str] | None = None,
+    variable: bool = False,
+    query: Query | None = None,
+) -> list[FontMatch]: ...
+def list_indexed_py(index_path: str | PathLike[str]) -> list[FontMatch]: ...
+def count_indexed_py(index_path: str | PathLike[str]) -> int: ...
+def get_indexed_py(index_path: str | PathLike[str], index_id: int) -> FontMatch | None: ...
+def get_indexed_by_path_py(
+    index_path: str | PathLike[str], path: str | PathLike[str]
+) -> list[FontMatch]: ...
+def inspect_py(path: str | PathLike[str]) -> list[dict[str, Any]]: ...
+def inspect_many_py(
+    paths: list[str | PathLike[str]], jobs: int | None = None
+) -> list[FontMatch]: ...
+def known_tags_py() -> dict[str, list[tuple[str, Any]]]: ...
+def load_cache_py(path: str | PathLike[str]) -> list[FontMatch]: ...
+def save_cache_py(path: str | PathLike[str], matches: list[FontMatch] | list[Any]) -> None: ...
@@ -143,7 +143,7 @@ def find_cli(
             jobs=jobs,
         )
 
-    return find(
+    matches = find(
         gathered,
         axes=list(axes) if axes else None,
         features=list(features) if features else None,
@@ -159,6 +159,8 @@ def find_cli(
         follow_symlinks=follow_symlinks,
         jobs=jobs,
     )
+    # Fire prints plain data; FontMatch objects would print as their repr.
+    return [match.to_dict() for match in matches]
 
 
 def main():
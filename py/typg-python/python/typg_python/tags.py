@@ -0,0 +1,61 @@
+"""Named constants for tags and family classes.
+
+`Axes`, `Features`, `Scripts` and `Tables` list the tags typg knows by
+name, `FamilyClass` the OS/2 family classes. They are built from the
+registry in typg-core, and their members are plain strings underneath, so
+they go anywhere a tag or class string does and compare equal to the tags
+in results::
+
+    find(paths, scripts=[Scripts.ARAB], axes=[Axes.WGHT], family_class=FamilyClass.SANS)
+    Axes.WGHT in match.metadata.axis_tags
+
+A misspelt member is an `AttributeError` at the call site rather than a
+search that quietly finds nothing. The lists are curated, not complete:
+any valid tag can still be passed as a string.
+"""
+
+from __future__ import annotations
+
+import re
+from enum import Enum
+
+from ._typg_python import known_tags_py
+
+__all__ = ["Axes", "FamilyClass", "Features", "Scripts", "Tables"]
+
+
+class _Named(str, Enum):
+    """A member that is its string value, with a human-readable description."""
+
+    description: str
+
+    def __new__(cls, value: str, description: str) -> _Named:
+        member = str.__new__(cls, value)
+        member._value_ = value
+        member.description = description
+        return member
+
+    def __str__(self) -> str:
+        return str.__str__(self)
+
+
+def _member_name(text: str) -> str:
+    # "OS/2" -> "OS_2", "lao " -> "LAO", "sans-serif" -> "SANS_SERIF"
+    return re.sub(r"[^0-9A-Za-z]+", "_", text.strip()).strip("_").upper()
+
+
+def _enum(name: str, pairs: list[tuple[str, str]]) -> type[_Named]:
+    members = [(_member_name(tag), (tag, description)) for tag, description in pairs]
+    return _Named(name, members, module=__name__, qualname=name)
+
+
+_KNOWN = known_tags_py()
+
+Axes = _enum("Axes", _KNOWN["axes"])
+Features = _enum("Features", _KNOWN["features"])
+Scripts = _enum("Scripts", _KNOWN["scripts"])
+Tables = _enum("Tables", _KNOWN["tables"])
+FamilyClass = _enum(
+    "FamilyClass",
+    [(name, f"OS/2 family class {major}") for name, major in _KNOWN["family_classes"]],
+)
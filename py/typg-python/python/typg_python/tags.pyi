@@ -0,0 +1,144 @@
+"""Type stubs for typg_python.tags; members mirror the typg-core registry."""
+
+from enum import Enum
+
+class _Named(str, Enum):
+    description: str
+
+class Axes(_Named):
+    WGHT = "wght"
+    WDTH = "wdth"
+    OPSZ = "opsz"
+    ITAL = "ital"
+    SLNT = "slnt"
+    GRAD = "GRAD"
+    XTRA = "XTRA"
+    XOPQ = "XOPQ"
+    YOPQ = "YOPQ"
+    CASL = "CASL"
+    MONO = "MONO"
+
+class Features(_Named):
+    AALT = "aalt"
+    C2SC = "c2sc"
+    CALT = "calt"
+    CASE = "case"
+    CCMP = "ccmp"
+    CLIG = "clig"
+    CPSP = "cpsp"
+    CURS = "curs"
+    CV01 = "cv01"
+    DLIG = "dlig"
+    DNOM = "dnom"
+    FINA = "fina"
+    FRAC = "frac"
+    HIST = "hist"
+    INIT = "init"
+    ISOL = "isol"
+    KERN = "kern"
+    LIGA = "liga"
+    LNUM = "lnum"
+    LOCL = "locl"
+    MARK = "mark"
+    MEDI = "medi"
+    MKMK = "mkmk"
+    NUMR = "numr"
+    ONUM = "onum"
+    ORDN = "ordn"
+    PCAP = "pcap"
+    PNUM = "pnum"
+    RLIG = "rlig"
+    SALT = "salt"
+    SINF = "sinf"
+    SMCP = "smcp"
+    SS01 = "ss01"
+    SS02 = "ss02"
+    SUBS = "subs"
+    SUPS = "sups"
+    SWSH = "swsh"
+    TITL = "titl"
+    TNUM = "tnum"
+    UNIC = "unic"
+    ZERO = "zero"
+
+class Scripts(_Named):
+    DFLT = "DFLT"
+    ARAB = "arab"
+    ARMN = "armn"
+    BENG = "beng"
+    BNG2 = "bng2"
+    CYRL = "cyrl"
+    DEVA = "deva"
+    DEV2 = "dev2"
+    ETHI = "ethi"
+    GEOR = "geor"
+    GREK = "grek"
+    GUJR = "gujr"
+    GURU = "guru"
+    HANG = "hang"
+    HANI = "hani"
+    HEBR = "hebr"
+    KANA = "kana"
+    KHMR = "khmr"
+    KNDA = "knda"
+    LAO = "lao "
+    LATN = "latn"
+    MLYM = "mlym"
+    MYM2 = "mym2"
+    ORYA = "orya"
+    SINH = "sinh"
+    SYRC = "syrc"
+    TAML = "taml"
+    TELU = "telu"
+    THAA = "thaa"
+    THAI = "thai"
+    TIBT = "tibt"
+
+class Tables(_Named):
+    BASE = "BASE"
+    CBDT = "CBDT"
+    CFF = "CFF "
+    CFF2 = "CFF2"
+    COLR = "COLR"
+    CPAL = "CPAL"
+    DSIG = "DSIG"
+    GDEF = "GDEF"
+    GPOS = "GPOS"
+    GSUB = "GSUB"
+    HVAR = "HVAR"
+    MATH = "MATH"
+    MVAR = "MVAR"
+    OS_2 = "OS/2"
+    STAT = "STAT"
+    SVG = "SVG "
+    AVAR = "avar"
+    CMAP = "cmap"
+    FVAR = "fvar"
+    GASP = "gasp"
+    GLYF = "glyf"
+    GVAR = "gvar"
+    HEAD = "head"
+    HHEA = "hhea"
+    HMTX = "hmtx"
+    KERN = "kern"
+    LOCA = "loca"
+    MAXP = "maxp"
+    META = "meta"
+    NAME = "name"
+    POST = "post"
+    SBIX = "sbix"
+    VHEA = "vhea"
+    VMTX = "vmtx"
+
+class FamilyClass(_Named):
+    NONE = "none"
+    OLDSTYLE = "oldstyle"
+    TRANSITIONAL = "transitional"
+    MODERN = "modern"
+    CLARENDON = "clarendon"
+    SLAB = "slab"
+    FREEFORM = "freeform"
+    SANS = "sans"
+    ORNAMENTAL = "ornamental"
+    SCRIPT = "script"
+    SYMBOLIC = "symbolic"
@@ -0,0 +1,482 @@
+//! Result classes returned by the search functions.
+//!
+//! A match is a [`FontMatch`] holding a [`FontSource`] (where the face
+//! lives) and a [`FontMeta`] (what was read from it). The classes keep the
+//! Rust values and convert a field only when Python reads it, so names and
+//! tags shared by many faces stay shared. `to_dict()` gives the plain
+//! dictionaries earlier releases returned, and `match["path"]` still works
+//! for code written against them.
+//!
+//! Built by FontLab (https://www.fontlab.com/).
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::PyKeyError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3::IntoPyObjectExt;
+use typg_core::search::{TypgFontFaceMatch, TypgFontFaceMeta, TypgFontSource};
+use typg_core::tags::{tag_to_string, Tag};
+
+/// Where a face lives: a file path and, for collections, the face index.
+///
+/// Compares, sorts and hashes by `(path, ttc_index)`.
+#[pyclass(module = "typg_python", frozen, eq, ord, hash)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FontSource {
+    /// Path of the font file.
+    #[pyo3(get)]
+    path: String,
+    /// Face index inside a TTC/OTC collection; `None` for single-face files.
+    #[pyo3(get)]
+    ttc_index: Option<u32>,
+}
+
+#[pymethods]
+impl FontSource {
+    #[new]
+    #[pyo3(signature = (path, ttc_index=None))]
+    fn new(path: PathBuf, ttc_index: Option<u32>) -> Self {
+        Self {
+            path: path.to_string_lossy().to_string(),
+            ttc_index,
+        }
+    }
+
+    /// `path#index` for collection members, the plain path otherwise.
+    #[getter]
+    fn path_with_index(&self) -> String {
+        match self.ttc_index {
+            Some(idx) => format!("{}#{idx}", self.path),
+            None => self.path.clone(),
+        }
+    }
+
+    /// `{"path": ..., "ttc_index": ...}`.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("path", &self.path)?;
+        dict.set_item("ttc_index", self.ttc_index)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "FontSource(path={}, ttc_index={})",
+            repr(py, &self.path)?,
+            repr(py, self.ttc_index)?
+        ))
+    }
+}
+
+impl From<&TypgFontSource> for FontSource {
+    fn from(source: &TypgFontSource) -> Self {
+        Self {
+            path: source.path.to_string_lossy().to_string(),
+            ttc_index: source.ttc_index,
+        }
+    }
+}
+
+/// Everything typg read from one face: names, tags, coverage and OS/2
+/// classification.
+///
+/// Two `FontMeta` are equal when they describe the same face, i.e. have the
+/// same `font_id` fingerprint (names, OS/2 classes, variable flag and
+/// cmap), whatever file they came from.
+#[pyclass(module = "typg_python", frozen)]
+pub struct FontMeta {
+    inner: TypgFontFaceMeta,
+}
+
+#[pymethods]
+impl FontMeta {
+    #[getter]
+    fn names(&self) -> Vec<&str> {
+        texts(&self.inner.names)
+    }
+
+    #[getter]
+    fn family_name(&self) -> Option<&str> {
+        self.inner.family_name.as_deref()
+    }
+
+    #[getter]
+    fn style_name(&self) -> Option<&str> {
+        self.inner.style_name.as_deref()
+    }
+
+    #[getter]
+    fn axis_tags(&self) -> Vec<String> {
+        tags(&self.inner.axis_tags)
+    }
+
+    #[getter]
+    fn feature_tags(&self) -> Vec<String> {
+        tags(&self.inner.feature_tags)
+    }
+
+    #[getter]
+    fn gsub_feature_tags(&self) -> Vec<String> {
+        tags(&self.inner.gsub_feature_tags)
+    }
+
+    #[getter]
+    fn gpos_feature_tags(&self) -> Vec<String> {
+        tags(&self.inner.gpos_feature_tags)
+    }
+
+    #[getter]
+    fn script_tags(&self) -> Vec<String> {
+        tags(&self.inner.script_tags)
+    }
+
+    #[getter]
+    fn language_tags(&self) -> Vec<String> {
+        tags(&self.inner.language_tags)
+    }
+
+    #[getter]
+    fn table_tags(&self) -> Vec<String> {
+        tags(&self.inner.table_tags)
+    }
+
+    /// Mapped characters, one single-character string each.
+    #[getter]
+    fn codepoints(&self) -> Vec<String> {
+        self.inner
+            .codepoints
+            .iter()
+            .map(|c| c.to_string())
+            .collect()
+    }
+
+    #[getter]
+    fn is_variable(&self) -> bool {
+        self.inner.is_variable
+    }
+
+    /// `{"name", "postscript_name", "coordinates"}` per named instance.
+    #[getter]
+    fn named_instances<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.inner
+            .named_instances
+            .iter()
+            .map(|instance| {
+                let entry = PyDict::new(py);
+                entry.set_item("name", &*instance.name)?;
+                entry.set_item("postscript_name", instance.postscript_name.as_deref())?;
+                entry.set_item("coordinates", &instance.coordinates)?;
+                Ok(entry)
+            })
+            .collect()
+    }
+
+    /// `{"tag", "min", "default", "max"}` per variation axis, in font order.
+    #[getter]
+    fn axes<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.inner
+            .axes
+            .iter()
+            .map(|axis| {
+                let entry = PyDict::new(py);
+                entry.set_item("tag", &axis.tag)?;
+                entry.set_item("min", axis.min)?;
+                entry.set_item("default", axis.default)?;
+                entry.set_item("max", axis.max)?;
+                Ok(entry)
+            })
+            .collect()
+    }
+
+    #[getter]
+    fn weight_class(&self) -> Option<u16> {
+        self.inner.weight_class
+    }
+
+    #[getter]
+    fn width_class(&self) -> Option<u16> {
+        self.inner.width_class
+    }
+
+    /// `(class, subclass)` from OS/2 `sFamilyClass`.
+    #[getter]
+    fn family_class(&self) -> Option<(u8, u8)> {
+        self.inner.family_class
+    }
+
+    #[getter]
+    fn creator_names(&self) -> Vec<&str> {
+        texts(&self.inner.creator_names)
+    }
+
+    #[getter]
+    fn license_names(&self) -> Vec<&str> {
+        texts(&self.inner.license_names)
+    }
+
+    #[getter]
+    fn classified_as(&self) -> Option<&str> {
+        self.inner.classified_as.as_deref()
+    }
+
+    #[getter]
+    fn units_per_em(&self) -> Option<u16> {
+        self.inner.units_per_em
+    }
+
+    #[getter]
+    fn glyph_count(&self) -> Option<u16> {
+        self.inner.glyph_count
+    }
+
+    #[getter]
+    fn font_revision(&self) -> Option<f64> {
+        self.inner.font_revision
+    }
+
+    #[getter]
+    fn vendor_id(&self) -> Option<&str> {
+        self.inner.vendor_id.as_deref()
+    }
+
+    /// `restricted`, `preview`, `editable` or `installable`.
+    #[getter]
+    fn embedding(&self) -> Option<&'static str> {
+        self.inner.embedding.map(|e| e.name())
+    }
+
+    #[getter]
+    fn faces_in_collection(&self) -> Option<u32> {
+        self.inner.faces_in_collection
+    }
+
+    #[getter]
+    fn file_size(&self) -> Option<u64> {
+        self.inner.file_size
+    }
+
+    /// Modification time in seconds since the Unix epoch.
+    #[getter]
+    fn modified(&self) -> Option<u64> {
+        self.inner.modified
+    }
+
+    /// ID of the face in the index it came from; `None` outside the
+    /// indexed functions.
+    #[getter]
+    fn index_id(&self) -> Option<u64> {
+        self.inner.index_id.map(|id| id.0)
+    }
+
+    /// The metadata as the plain dictionary earlier releases returned.
+    fn to_dict<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(slf.py());
+        for key in META_KEYS {
+            dict.set_item(key, slf.getattr(key)?)?;
+        }
+        Ok(dict)
+    }
+
+    fn __getitem__<'py>(slf: &Bound<'py, Self>, key: &str) -> PyResult<Bound<'py, PyAny>> {
+        get_item(slf.as_any(), key, &META_KEYS)
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.inner.fingerprint() == other.inner.fingerprint()
+    }
+
+    fn __hash__(&self) -> u64 {
+        self.inner.fingerprint()
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "FontMeta(family_name={}, style_name={}, weight_class={}, is_variable={})",
+            repr(py, self.family_name())?,
+            repr(py, self.style_name())?,
+            repr(py, self.inner.weight_class)?,
+            repr(py, self.inner.is_variable)?
+        ))
+    }
+}
+
+/// Keys of [`FontMeta::to_dict`], in dictionary order.
+const META_KEYS: [&str; 29] = [
+    "names",
+    "axis_tags",
+    "feature_tags",
+    "gsub_feature_tags",
+    "gpos_feature_tags",
+    "script_tags",
+    "language_tags",
+    "table_tags",
+    "codepoints",
+    "is_variable",
+    "named_instances",
+    "axes",
+    "weight_class",
+    "width_class",
+    "family_class",
+    "creator_names",
+    "license_names",
+    "classified_as",
+    "family_name",
+    "style_name",
+    "units_per_em",
+    "glyph_count",
+    "font_revision",
+    "vendor_id",
+    "embedding",
+    "faces_in_collection",
+    "file_size",
+    "modified",
+    "index_id",
+];
+
+/// One face that matched a query.
+///
+/// Compares and sorts by `(source, font_id)`, so `sorted(matches)` orders
+/// by path and collection index, and hashes the same way.
+#[pyclass(module = "typg_python", frozen)]
+pub struct FontMatch {
+    source: FontSource,
+    font_id: String,
+    metadata: Py<FontMeta>,
+}
+
+impl FontMatch {
+    /// Wrap a core match.
+    pub fn new(py: Python<'_>, item: TypgFontFaceMatch) -> PyResult<Py<Self>> {
+        let source = FontSource::from(&item.source);
+        let font_id = item.font_id();
+        let metadata = Py::new(
+            py,
+            FontMeta {
+                inner: item.metadata,
+            },
+        )?;
+        Py::new(
+            py,
+            Self {
+                source,
+                font_id,
+                metadata,
+            },
+        )
+    }
+
+    /// The core match this wraps.
+    pub fn to_core(&self) -> TypgFontFaceMatch {
+        TypgFontFaceMatch {
+            source: TypgFontSource {
+                path: PathBuf::from(&self.source.path),
+                ttc_index: self.source.ttc_index,
+            },
+            metadata: self.metadata.get().inner.clone(),
+        }
+    }
+
+    fn key(&self) -> (&FontSource, &str) {
+        (&self.source, &self.font_id)
+    }
+}
+
+#[pymethods]
+impl FontMatch {
+    #[getter]
+    fn source(&self) -> FontSource {
+        self.source.clone()
+    }
+
+    /// Shorthand for `source.path`.
+    #[getter]
+    fn path(&self) -> &str {
+        &self.source.path
+    }
+
+    /// Shorthand for `source.ttc_index`.
+    #[getter]
+    fn ttc_index(&self) -> Option<u32> {
+        self.source.ttc_index
+    }
+
+    /// Stable face ID: 16 hex digits hashed from names, OS/2 classes,
+    /// variable flag and cmap, not from the path.
+    #[getter]
+    fn font_id(&self) -> &str {
+        &self.font_id
+    }
+
+    #[getter]
+    fn metadata(&self, py: Python<'_>) -> Py<FontMeta> {
+        self.metadata.clone_ref(py)
+    }
+
+    /// `{"path", "ttc_index", "font_id", "metadata": {...}}`, the shape
+    /// earlier releases returned.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("path", &self.source.path)?;
+        dict.set_item("ttc_index", self.source.ttc_index)?;
+        dict.set_item("font_id", &self.font_id)?;
+        dict.set_item("metadata", FontMeta::to_dict(self.metadata.bind(py))?)?;
+        Ok(dict)
+    }
+
+    fn __getitem__<'py>(slf: &Bound<'py, Self>, key: &str) -> PyResult<Bound<'py, PyAny>> {
+        get_item(
+            slf.as_any(),
+            key,
+            &["path", "ttc_index", "font_id", "metadata"],
+        )
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> bool {
+        op.matches(self.key().cmp(&other.key()))
+    }
+
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.key().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "FontMatch(path={}, ttc_index={}, font_id={})",
+            repr(py, &self.source.path)?,
+            repr(py, self.source.ttc_index)?,
+            repr(py, &self.font_id)?
+        ))
+    }
+}
+
+/// Dictionary-style access to the attributes in `keys`.
+fn get_item<'py>(obj: &Bound<'py, PyAny>, key: &str, keys: &[&str]) -> PyResult<Bound<'py, PyAny>> {
+    if !keys.contains(&key) {
+        return Err(PyKeyError::new_err(key.to_string()));
+    }
+    obj.getattr(key)
+}
+
+/// Python's `repr()` of a value.
+pub(crate) fn repr<'py, T: IntoPyObject<'py>>(py: Python<'py>, value: T) -> PyResult<String>
+where
+    T::Error: Into<PyErr>,
+{
+    let value = value.into_bound_py_any(py)?;
+    Ok(value.repr()?.to_string())
+}
+
+fn texts(values: &[Arc<str>]) -> Vec<&str> {
+    values.iter().map(|value| &**value).collect()
+}
+
+fn tags(tags: &[Tag]) -> Vec<String> {
+    tags.iter().map(|&tag| tag_to_string(tag)).collect()
+}
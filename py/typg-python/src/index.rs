@@ -0,0 +1,236 @@
+//! The `TypgIndex` class and its writer transactions.
+//!
+//! `TypgIndex(path)` opens an LMDB index once and keeps it open, so the
+//! module-level `*_indexed` functions and any number of writers share it.
+//! `index.writer()` starts a transaction:
+//!
+//! ```python
+//! with index.writer() as w:
+//!     w.add(["/Library/Fonts"])
+//!     w.remove_path("/Library/Fonts/Old")
+//! ```
+//!
+//! The block commits when it ends normally and aborts when it raises, the
+//! way an `IndexWriter` in Rust commits on `commit()` and aborts when it is
+//! dropped. LMDB ties a write transaction to the thread that opened it, so
+//! the writer collects its changes and applies them all in one transaction
+//! on commit: readers see either none of them or all of them. Fonts are
+//! scanned when `add()` is called, so an unreadable path raises there.
+//!
+//! Built by FontLab (https://www.fontlab.com/).
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::PyType;
+use typg_core::index::{FontID, FontIndex, IndexOptions};
+use typg_core::query::Query;
+use typg_core::search::{search, SearchOptions, TypgFontFaceMatch};
+
+use crate::classes::repr;
+use crate::{check_search_args, to_py_err};
+
+/// Open `index_path`, sharing one environment per directory across the
+/// process: LMDB refuses a second open of a path that is still open.
+pub(crate) fn open_index(index_path: &Path) -> Result<Arc<FontIndex>> {
+    FontIndex::shared(index_path, &IndexOptions::default())
+}
+
+/// An LMDB font index, open for as long as the object lives.
+#[pyclass(module = "typg_python", name = "TypgIndex", frozen)]
+pub struct PyTypgIndex {
+    index: Arc<FontIndex>,
+    path: PathBuf,
+}
+
+#[pymethods]
+impl PyTypgIndex {
+    /// Open the index at `index_path`, creating it if needed.
+    #[new]
+    fn new(py: Python<'_>, index_path: PathBuf) -> PyResult<Self> {
+        let index = py.detach(|| open_index(&index_path)).map_err(to_py_err)?;
+        Ok(Self {
+            index,
+            path: index_path,
+        })
+    }
+
+    /// The index directory.
+    #[getter]
+    fn path(&self) -> String {
+        self.path.to_string_lossy().to_string()
+    }
+
+    /// Start a write transaction; use it as a context manager.
+    fn writer(&self) -> PyIndexWriter {
+        PyIndexWriter {
+            index: Arc::clone(&self.index),
+            changes: Some(Vec::new()),
+        }
+    }
+
+    /// Faces in the index.
+    fn __len__(&self, py: Python<'_>) -> PyResult<usize> {
+        py.detach(|| self.index.count()).map_err(to_py_err)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!("TypgIndex({})", repr(py, self.path())?))
+    }
+}
+
+/// One change staged by an [`PyIndexWriter`].
+enum Change {
+    Add(Vec<TypgFontFaceMatch>),
+    RemovePath(PathBuf),
+    RemoveFaces(Vec<FontID>),
+    Clear,
+}
+
+/// A write transaction on a `TypgIndex`.
+///
+/// Changes take effect together on `commit()`, or when a `with` block
+/// ends without an exception; `abort()`, an exception in the block, or
+/// dropping the writer discards them. A writer is closed once committed
+/// or aborted.
+#[pyclass(module = "typg_python", name = "IndexWriter")]
+pub struct PyIndexWriter {
+    index: Arc<FontIndex>,
+    /// Staged changes; `None` once the writer is closed.
+    changes: Option<Vec<Change>>,
+}
+
+#[pymethods]
+impl PyIndexWriter {
+    /// Scan `paths` and stage their faces. Files the index already holds
+    /// unchanged are left alone on commit. Returns the faces found.
+    #[pyo3(signature = (paths, follow_symlinks=false, jobs=None))]
+    fn add(
+        &mut self,
+        py: Python<'_>,
+        paths: Vec<PathBuf>,
+        follow_symlinks: bool,
+        jobs: Option<usize>,
+    ) -> PyResult<usize> {
+        check_search_args(&paths, jobs)?;
+        let changes = self.staged()?;
+        let opts = SearchOptions {
+            follow_symlinks,
+            jobs,
+            ..SearchOptions::default()
+        };
+        let faces = py
+            .detach(|| search(&paths, &Query::new(), &opts))
+            .map_err(to_py_err)?;
+        let found = faces.len();
+        changes.push(Change::Add(faces));
+        Ok(found)
+    }
+
+    /// Stage removing every face of `path`, or of any file under it when it
+    /// is a directory.
+    fn remove_path(&mut self, path: PathBuf) -> PyResult<()> {
+        self.staged()?.push(Change::RemovePath(path));
+        Ok(())
+    }
+
+    /// Stage removing faces by their `index_id`.
+    fn remove_faces(&mut self, ids: Vec<u64>) -> PyResult<()> {
+        let ids = ids.into_iter().map(FontID).collect();
+        self.staged()?.push(Change::RemoveFaces(ids));
+        Ok(())
+    }
+
+    /// Stage removing every face.
+    fn clear(&mut self) -> PyResult<()> {
+        self.staged()?.push(Change::Clear);
+        Ok(())
+    }
+
+    /// Apply the staged changes in one transaction and close the writer.
+    /// If applying fails, nothing is written.
+    fn commit(&mut self, py: Python<'_>) -> PyResult<()> {
+        let changes = self.close()?;
+        let index = &self.index;
+        py.detach(|| {
+            let mut writer = index.writer()?;
+            for change in changes {
+                match change {
+                    Change::Add(faces) => {
+                        writer.add_scanned(&faces, false)?;
+                    }
+                    Change::RemovePath(path) => {
+                        writer.remove_path(&path)?;
+                    }
+                    Change::RemoveFaces(ids) => {
+                        writer.remove_faces(&ids)?;
+                    }
+                    Change::Clear => {
+                        writer.clear()?;
+                    }
+                }
+            }
+            writer.commit()
+        })
+        .map_err(to_py_err)
+    }
+
+    /// Discard the staged changes and close the writer.
+    fn abort(&mut self) -> PyResult<()> {
+        self.close().map(drop)
+    }
+
+    /// Whether the writer has been committed or aborted.
+    #[getter]
+    fn closed(&self) -> bool {
+        self.changes.is_none()
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    /// Commit when the block ended normally, abort when it raised. A
+    /// writer already closed inside the block is left as it is.
+    #[pyo3(signature = (exc_type, _exc_value, _traceback))]
+    fn __exit__(
+        &mut self,
+        py: Python<'_>,
+        exc_type: Option<Bound<'_, PyType>>,
+        _exc_value: Option<Bound<'_, PyAny>>,
+        _traceback: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        if self.closed() {
+            return Ok(false);
+        }
+        match exc_type {
+            None => self.commit(py)?,
+            Some(_) => self.abort()?,
+        }
+        Ok(false)
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.changes {
+            Some(changes) => format!("<IndexWriter: {} staged changes>", changes.len()),
+            None => "<IndexWriter: closed>".to_string(),
+        }
+    }
+}
+
+impl PyIndexWriter {
+    fn staged(&mut self) -> PyResult<&mut Vec<Change>> {
+        self.changes
+            .as_mut()
+            .ok_or_else(|| PyRuntimeError::new_err("writer is closed"))
+    }
+
+    fn close(&mut self) -> PyResult<Vec<Change>> {
+        self.changes
+            .take()
+            .ok_or_else(|| PyRuntimeError::new_err("writer is closed"))
+    }
+}
@@ -2,32 +2,47 @@
 //!
 //! These bindings expose the same query model as the Rust CLI: live scans,
 //! in-memory filtering of cached metadata, and optional indexed search when the
-//! `hpindex` feature is enabled.
+//! `hpindex` feature is enabled. Filters are keyword arguments, or a
+//! reusable `Query` passed as `query=` (see the `query` module).
 //!
-//! Search functions return Python dictionaries shaped like:
-//! `{ "path": str, "ttc_index": int | None, "metadata": { ... } }`.
+//! Search functions return `FontMatch` objects (see the `classes` module)
+//! with `source` (a `FontSource`), `path`, `ttc_index`, `font_id` and
+//! `metadata` (a `FontMeta` with one attribute per field). `to_dict()`
+//! gives the plain dictionaries earlier releases returned, and
+//! `match["path"]` still works for code written against them. `inspect`
+//! returns one dictionary per face with the keys of `typg inspect --json`.
 //!
 //! Built by FontLab (https://www.fontlab.com/).
 
-use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
 
 use anyhow::{anyhow, Result};
-use pyo3::exceptions::PyValueError;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
-use regex::Regex;
-use typg_core::query::{
-    parse_codepoint_list, parse_family_class, parse_tag_list, parse_u16_range, FamilyClassFilter,
-    Query,
-};
+use typg_core::cache::{load_cache, merge_entries, write_cache};
+use typg_core::inspect::{inspect_file, FontInspection};
+use typg_core::query::{parse_tag_list, Query, FAMILY_CLASS_NAMES};
 use typg_core::search::{
-    filter_cached, search, SearchOptions, TypgFontFaceMatch, TypgFontFaceMeta, TypgFontSource,
+    filter_cached, scan_files, search, search_streaming, SearchOptions, TypgFontFaceMatch,
+    TypgFontFaceMeta, TypgFontSource,
 };
-use typg_core::tags::tag_to_string;
+use typg_core::tags::{KNOWN_AXES, KNOWN_FEATURES, KNOWN_SCRIPTS, KNOWN_TABLES};
+
+#[cfg(feature = "hpindex")]
+use typg_core::index::FontID;
 
+mod classes;
 #[cfg(feature = "hpindex")]
-use typg_core::index::FontIndex;
+mod index;
+mod query;
+
+use classes::{FontMatch, FontMeta, FontSource};
+#[cfg(feature = "hpindex")]
+use index::{open_index, PyIndexWriter, PyTypgIndex};
+use query::{parse_codepoints, PyQuery, QuerySpec};
 
 /// Input structure holding font metadata provided from Python.
 ///
@@ -47,9 +62,18 @@ struct MetadataInput {
     /// OpenType feature tags available for advanced typography
     #[pyo3(default)]
     feature_tags: Vec<String>,
+    /// Feature tags from the GSUB table only
+    #[pyo3(default)]
+    gsub_feature_tags: Vec<String>,
+    /// Feature tags from the GPOS table only
+    #[pyo3(default)]
+    gpos_feature_tags: Vec<String>,
     /// Script tags indicating supported writing systems
     #[pyo3(default)]
     script_tags: Vec<String>,
+    /// Language system tags (e.g. TRK, SRB) under GSUB/GPOS scripts
+    #[pyo3(default)]
+    language_tags: Vec<String>,
     /// Font table tags included in this font file
     #[pyo3(default)]
     table_tags: Vec<String>,
@@ -77,13 +101,38 @@ struct MetadataInput {
     /// License-related name strings (copyright, license, license URL)
     #[pyo3(default)]
     license_names: Vec<String>,
+    /// Heuristic style category (serif, sans, mono, script, display)
+    #[pyo3(default)]
+    classified_as: Option<String>,
+    /// Family name (typographic family, else legacy family)
+    #[pyo3(default)]
+    family_name: Option<String>,
+    /// Style name (typographic subfamily, else legacy subfamily)
+    #[pyo3(default)]
+    style_name: Option<String>,
+    /// Design units per em from the head table
+    #[pyo3(default)]
+    units_per_em: Option<u16>,
+    /// Glyph count from the maxp table
+    #[pyo3(default)]
+    glyph_count: Option<u16>,
+    /// Font revision from the head table (e.g. 2.003)
+    #[pyo3(default)]
+    font_revision: Option<f64>,
+    /// OS/2 vendor ID (e.g. "ADBE")
+    #[pyo3(default)]
+    vendor_id: Option<String>,
+    /// Number of faces in the source collection (None for single-font files)
+    #[pyo3(default)]
+    faces_in_collection: Option<u32>,
 }
 
-/// Search directories and return matching fonts as Python dictionaries.
+/// Search directories and return the matching faces as `FontMatch` objects.
 ///
-/// Each result has the shape `{path, ttc_index, metadata}`. `metadata`
-/// contains the extracted typg fields such as names, tags, coverage, and OS/2
-/// classification values.
+/// Each match carries `source`, `path`, `ttc_index`, `font_id` and
+/// `metadata`, a `FontMeta` whose attributes are the extracted typg fields
+/// such as names, tags, coverage and OS/2 classification values. Call
+/// `to_dict()` on a match for a plain dictionary.
 #[pyfunction]
 #[pyo3(
     signature = (
@@ -102,7 +151,8 @@ struct MetadataInput {
         license=None,
         variable=false,
         follow_symlinks=false,
-        jobs=None
+        jobs=None,
+        query=None
     )
 )]
 #[allow(clippy::too_many_arguments)]
@@ -124,21 +174,13 @@ fn find_py(
     variable: bool,
     follow_symlinks: bool,
     jobs: Option<usize>,
-) -> PyResult<Vec<Py<PyAny>>> {
-    if paths.is_empty() {
-        return Err(PyValueError::new_err(
-            "at least one search path is required",
-        ));
-    }
-
-    if matches!(jobs, Some(0)) {
-        return Err(PyValueError::new_err(
-            "jobs must be at least 1 when provided",
-        ));
-    }
+    query: Option<PyRef<'_, PyQuery>>,
+) -> PyResult<Vec<Py<FontMatch>>> {
+    check_search_args(&paths, jobs)?;
 
     // Build query from parameters
     let query = build_query(
+        query.as_deref(),
         axes,
         features,
         scripts,
@@ -158,9 +200,12 @@ fn find_py(
     let opts = SearchOptions {
         follow_symlinks,
         jobs,
+        ..SearchOptions::default()
     };
 
-    let matches = search(&paths, &query, &opts).map_err(to_py_err)?;
+    let matches = py
+        .detach(|| search(&paths, &query, &opts))
+        .map_err(to_py_err)?;
     to_py_matches(py, matches)
 }
 
@@ -186,11 +231,13 @@ fn find_py(
         license=None,
         variable=false,
         follow_symlinks=false,
-        jobs=None
+        jobs=None,
+        query=None
     )
 )]
 #[allow(clippy::too_many_arguments)]
 fn find_paths_py(
+    py: Python<'_>,
     paths: Vec<PathBuf>,
     axes: Option<Vec<String>>,
     features: Option<Vec<String>>,
@@ -207,20 +254,12 @@ fn find_paths_py(
     variable: bool,
     follow_symlinks: bool,
     jobs: Option<usize>,
+    query: Option<PyRef<'_, PyQuery>>,
 ) -> PyResult<Vec<String>> {
-    if paths.is_empty() {
-        return Err(PyValueError::new_err(
-            "at least one search path is required",
-        ));
-    }
-
-    if matches!(jobs, Some(0)) {
-        return Err(PyValueError::new_err(
-            "jobs must be at least 1 when provided",
-        ));
-    }
+    check_search_args(&paths, jobs)?;
 
     let query = build_query(
+        query.as_deref(),
         axes,
         features,
         scripts,
@@ -240,8 +279,11 @@ fn find_paths_py(
     let opts = SearchOptions {
         follow_symlinks,
         jobs,
+        ..SearchOptions::default()
     };
-    let matches = search(&paths, &query, &opts).map_err(to_py_err)?;
+    let matches = py
+        .detach(|| search(&paths, &query, &opts))
+        .map_err(to_py_err)?;
 
     Ok(matches
         .into_iter()
@@ -249,6 +291,115 @@ fn find_paths_py(
         .collect())
 }
 
+/// Search directories and hand each match to `callback` as it is found.
+///
+/// Matches arrive as the same `FontMatch` objects [`find_py`] returns, in
+/// discovery order rather than sorted, and `callback` runs on the calling
+/// thread. The GIL is released while waiting for the next match, so other
+/// Python threads (a Qt event loop, an asyncio loop) keep running. If
+/// `callback` raises, no further matches are delivered and the exception
+/// propagates once the scan has wound down. Returns the number of matches
+/// delivered.
+#[pyfunction]
+#[pyo3(
+    signature = (
+        callback,
+        paths,
+        axes=None,
+        features=None,
+        scripts=None,
+        tables=None,
+        names=None,
+        codepoints=None,
+        text=None,
+        weight=None,
+        width=None,
+        family_class=None,
+        creator=None,
+        license=None,
+        variable=false,
+        follow_symlinks=false,
+        jobs=None,
+        query=None
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+fn find_each_py(
+    py: Python<'_>,
+    callback: Py<PyAny>,
+    paths: Vec<PathBuf>,
+    axes: Option<Vec<String>>,
+    features: Option<Vec<String>>,
+    scripts: Option<Vec<String>>,
+    tables: Option<Vec<String>>,
+    names: Option<Vec<String>>,
+    codepoints: Option<Vec<String>>,
+    text: Option<String>,
+    weight: Option<String>,
+    width: Option<String>,
+    family_class: Option<String>,
+    creator: Option<Vec<String>>,
+    license: Option<Vec<String>>,
+    variable: bool,
+    follow_symlinks: bool,
+    jobs: Option<usize>,
+    query: Option<PyRef<'_, PyQuery>>,
+) -> PyResult<usize> {
+    check_search_args(&paths, jobs)?;
+
+    let query = build_query(
+        query.as_deref(),
+        axes,
+        features,
+        scripts,
+        tables,
+        names,
+        codepoints,
+        text,
+        weight,
+        width,
+        family_class,
+        creator,
+        license,
+        variable,
+    )
+    .map_err(to_py_err)?;
+
+    let opts = SearchOptions {
+        follow_symlinks,
+        jobs,
+        ..SearchOptions::default()
+    };
+
+    let (tx, rx) = mpsc::channel();
+    // `detach` needs a `Send` closure, and `&Receiver` is not.
+    let rx = Mutex::new(rx);
+    thread::scope(|scope| {
+        let scan = scope.spawn(|| search_streaming(&paths, &query, &opts, tx));
+
+        let mut delivered = 0;
+        let mut outcome = Ok(());
+        while let Ok(item) = py.detach(|| rx.lock().map_or(Err(mpsc::RecvError), |rx| rx.recv())) {
+            let delivery =
+                FontMatch::new(py, item).and_then(|item| callback.call1(py, (item,)).map(drop));
+            if let Err(err) = delivery {
+                outcome = Err(err);
+                break;
+            }
+            delivered += 1;
+        }
+        // Hang up so the scan stops sending, then wait for it without the GIL.
+        drop(rx);
+        let scanned = py.detach(|| scan.join());
+
+        outcome?;
+        match scanned {
+            Ok(report) => report.map(|_| delivered).map_err(to_py_err),
+            Err(_) => Err(PyRuntimeError::new_err("font search thread panicked")),
+        }
+    })
+}
+
 /// Filter pre-collected font metadata without touching the filesystem.
 ///
 /// `entries` must follow the same structure returned by the search functions.
@@ -268,13 +419,14 @@ fn find_paths_py(
         family_class=None,
         creator=None,
         license=None,
-        variable=false
+        variable=false,
+        query=None
     )
 )]
 #[allow(clippy::too_many_arguments)]
 fn filter_cached_py(
     py: Python<'_>,
-    entries: Vec<MetadataInput>,
+    entries: Vec<CacheEntry>,
     axes: Option<Vec<String>>,
     features: Option<Vec<String>>,
     scripts: Option<Vec<String>>,
@@ -288,11 +440,13 @@ fn filter_cached_py(
     creator: Option<Vec<String>>,
     license: Option<Vec<String>>,
     variable: bool,
-) -> PyResult<Vec<Py<PyAny>>> {
+    query: Option<PyRef<'_, PyQuery>>,
+) -> PyResult<Vec<Py<FontMatch>>> {
     // Convert Python metadata input to internal Rust structures
-    let metadata = convert_metadata(entries).map_err(to_py_err)?;
+    let metadata = convert_entries(entries).map_err(to_py_err)?;
 
     let query = build_query(
+        query.as_deref(),
         axes,
         features,
         scripts,
@@ -309,10 +463,37 @@ fn filter_cached_py(
     )
     .map_err(to_py_err)?;
 
-    let matches = filter_cached(&metadata, &query);
+    let matches = py.detach(|| filter_cached(&metadata, &query));
     to_py_matches(py, matches)
 }
 
+/// Read a `typg cache` JSON file (as written by `typg cache add` or
+/// [`save_cache_py`]) into matches, sorted by path.
+#[pyfunction]
+fn load_cache_py(py: Python<'_>, path: PathBuf) -> PyResult<Vec<Py<FontMatch>>> {
+    let entries = py.detach(|| load_cache(&path)).map_err(to_py_err)?;
+    to_py_matches(py, entries)
+}
+
+/// A face given to `filter_cached` or `save_cache`: a search result, or
+/// metadata with the attributes of [`MetadataInput`].
+#[derive(FromPyObject)]
+#[allow(clippy::large_enum_variant)]
+enum CacheEntry {
+    Match(Py<FontMatch>),
+    Metadata(MetadataInput),
+}
+
+/// Write matches to a `typg cache` JSON file that `typg cache find` and
+/// [`load_cache_py`] read, replacing the file. Entries are sorted by path;
+/// when one face appears twice the later entry is kept.
+#[pyfunction]
+fn save_cache_py(py: Python<'_>, path: PathBuf, matches: Vec<CacheEntry>) -> PyResult<()> {
+    let entries = convert_entries(matches).map_err(to_py_err)?;
+    py.detach(|| write_cache(&path, &merge_entries(Vec::new(), entries)))
+        .map_err(to_py_err)
+}
+
 /// Search fonts through the LMDB index instead of scanning directories.
 ///
 /// Requires a build compiled with the `hpindex` feature.
@@ -333,7 +514,8 @@ fn filter_cached_py(
         family_class=None,
         creator=None,
         license=None,
-        variable=false
+        variable=false,
+        query=None
     )
 )]
 #[allow(clippy::too_many_arguments)]
@@ -353,8 +535,10 @@ fn find_indexed_py(
     creator: Option<Vec<String>>,
     license: Option<Vec<String>>,
     variable: bool,
-) -> PyResult<Vec<Py<PyAny>>> {
+    query: Option<PyRef<'_, PyQuery>>,
+) -> PyResult<Vec<Py<FontMatch>>> {
     let query = build_query(
+        query.as_deref(),
         axes,
         features,
         scripts,
@@ -372,9 +556,13 @@ fn find_indexed_py(
     .map_err(to_py_err)?;
 
     // Execute indexed search
-    let index = FontIndex::open(&index_path).map_err(to_py_err)?;
-    let reader = index.reader().map_err(to_py_err)?;
-    let matches = reader.find(&query).map_err(to_py_err)?;
+    let matches = py
+        .detach(|| {
+            let index = open_index(&index_path)?;
+            let reader = index.reader()?;
+            reader.find(&query)
+        })
+        .map_err(to_py_err)?;
     to_py_matches(py, matches)
 }
 
@@ -383,10 +571,48 @@ fn find_indexed_py(
 /// Requires a build compiled with the `hpindex` feature.
 #[cfg(feature = "hpindex")]
 #[pyfunction]
-fn list_indexed_py(py: Python<'_>, index_path: PathBuf) -> PyResult<Vec<Py<PyAny>>> {
-    let index = FontIndex::open(&index_path).map_err(to_py_err)?;
-    let reader = index.reader().map_err(to_py_err)?;
-    let matches = reader.list_all().map_err(to_py_err)?;
+fn list_indexed_py(py: Python<'_>, index_path: PathBuf) -> PyResult<Vec<Py<FontMatch>>> {
+    let matches = py
+        .detach(|| {
+            let index = open_index(&index_path)?;
+            let reader = index.reader()?;
+            reader.list_all()
+        })
+        .map_err(to_py_err)?;
+    to_py_matches(py, matches)
+}
+
+/// Fetch one face from the LMDB index by its `index_id`, or `None` when
+/// the index no longer holds it.
+///
+/// Requires a build compiled with the `hpindex` feature.
+#[cfg(feature = "hpindex")]
+#[pyfunction]
+fn get_indexed_py(
+    py: Python<'_>,
+    index_path: PathBuf,
+    index_id: u64,
+) -> PyResult<Option<Py<FontMatch>>> {
+    let found = py
+        .detach(|| open_index(&index_path)?.reader()?.get(FontID(index_id)))
+        .map_err(to_py_err)?;
+    Ok(to_py_matches(py, found.into_iter().collect())?.pop())
+}
+
+/// Fetch every face the LMDB index holds for one font file, in collection
+/// order; empty when the file is not indexed.
+///
+/// Requires a build compiled with the `hpindex` feature.
+#[cfg(feature = "hpindex")]
+#[pyfunction]
+fn get_indexed_by_path_py(
+    py: Python<'_>,
+    index_path: PathBuf,
+    path: PathBuf,
+) -> PyResult<Vec<Py<FontMatch>>> {
+    let matches = py
+        .detach(|| open_index(&index_path)?.reader()?.get_by_path(&path))
+        .map_err(to_py_err)?;
     to_py_matches(py, matches)
 }
 
@@ -395,9 +621,191 @@ fn list_indexed_py(py: Python<'_>, index_path: PathBuf) -> PyResult<Vec<Py<PyAny
 /// Requires a build compiled with the `hpindex` feature.
 #[cfg(feature = "hpindex")]
 #[pyfunction]
-fn count_indexed_py(index_path: PathBuf) -> PyResult<usize> {
-    let index = FontIndex::open(&index_path).map_err(to_py_err)?;
-    index.count().map_err(to_py_err)
+fn count_indexed_py(py: Python<'_>, index_path: PathBuf) -> PyResult<usize> {
+    py.detach(|| open_index(&index_path)?.count())
+        .map_err(to_py_err)
+}
+
+/// The tags and family class names typg knows, for the enums in
+/// `typg_python.tags`.
+///
+/// `axes`, `features`, `scripts` and `tables` map to `(tag, name)` pairs;
+/// `family_classes` to `(name, major)` pairs, one canonical name per major.
+#[pyfunction]
+fn known_tags_py(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    let dict = PyDict::new(py);
+    for (key, tags) in [
+        ("axes", KNOWN_AXES),
+        ("features", KNOWN_FEATURES),
+        ("scripts", KNOWN_SCRIPTS),
+        ("tables", KNOWN_TABLES),
+    ] {
+        let pairs: Vec<_> = tags.iter().map(|known| (known.tag, known.name)).collect();
+        dict.set_item(key, pairs)?;
+    }
+    let mut classes: Vec<(&str, u8)> = Vec::new();
+    for &(name, major) in FAMILY_CLASS_NAMES {
+        if !classes.iter().any(|&(_, seen)| seen == major) {
+            classes.push((name, major));
+        }
+    }
+    dict.set_item("family_classes", classes)?;
+    Ok(dict)
+}
+
+/// Read one font file in depth: name records, tables, axes, features per
+/// script and language, a cmap summary and OS/2 metrics.
+///
+/// Returns one dict per face, with the keys of `typg inspect --json`.
+#[pyfunction]
+fn inspect_py(py: Python<'_>, path: PathBuf) -> PyResult<Vec<Py<PyAny>>> {
+    let faces = py.detach(|| inspect_file(&path)).map_err(to_py_err)?;
+    faces
+        .into_iter()
+        .map(|face| inspection_to_py(py, face))
+        .collect()
+}
+
+/// Read a known list of font files in parallel, skipping directory
+/// discovery, and return their faces with full metadata.
+///
+/// Results follow the order of `paths` (faces of a collection in index
+/// order). Files that cannot be read or parsed are left out, as `find`
+/// leaves them out.
+#[pyfunction]
+#[pyo3(signature = (paths, jobs=None))]
+fn inspect_many_py(
+    py: Python<'_>,
+    paths: Vec<PathBuf>,
+    jobs: Option<usize>,
+) -> PyResult<Vec<Py<FontMatch>>> {
+    if matches!(jobs, Some(0)) {
+        return Err(PyValueError::new_err(
+            "jobs must be at least 1 when provided",
+        ));
+    }
+    let opts = SearchOptions {
+        jobs,
+        ..SearchOptions::default()
+    };
+    let (faces, _) = py.detach(|| scan_files(&paths, &opts)).map_err(to_py_err)?;
+    to_py_matches(py, faces)
+}
+
+fn inspection_to_py(py: Python<'_>, face: FontInspection) -> PyResult<Py<PyAny>> {
+    let names = face
+        .names
+        .into_iter()
+        .map(|name| {
+            let entry = PyDict::new(py);
+            entry.set_item("name_id", name.name_id)?;
+            entry.set_item("platform_id", name.platform_id)?;
+            entry.set_item("encoding_id", name.encoding_id)?;
+            entry.set_item("language_id", name.language_id)?;
+            entry.set_item("language", name.language)?;
+            entry.set_item("value", name.value)?;
+            Ok(entry)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let tables = face
+        .tables
+        .into_iter()
+        .map(|table| {
+            let entry = PyDict::new(py);
+            entry.set_item("tag", table.tag)?;
+            entry.set_item("length", table.length)?;
+            entry.set_item("checksum", table.checksum)?;
+            Ok(entry)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let axes = face
+        .axes
+        .into_iter()
+        .map(|axis| {
+            let entry = PyDict::new(py);
+            entry.set_item("tag", axis.tag)?;
+            entry.set_item("name", axis.name)?;
+            entry.set_item("min", axis.min)?;
+            entry.set_item("default", axis.default)?;
+            entry.set_item("max", axis.max)?;
+            entry.set_item("hidden", axis.hidden)?;
+            Ok(entry)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let layout = face
+        .layout
+        .into_iter()
+        .map(|lang_sys| {
+            let entry = PyDict::new(py);
+            entry.set_item("table", lang_sys.table)?;
+            entry.set_item("script", lang_sys.script)?;
+            entry.set_item("language", lang_sys.language)?;
+            entry.set_item("features", lang_sys.features)?;
+            Ok(entry)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let cmap = PyDict::new(py);
+    let subtables: Vec<String> = face.cmap.subtables.iter().map(|t| t.to_string()).collect();
+    cmap.set_item("subtables", subtables)?;
+    cmap.set_item("codepoints", face.cmap.codepoints)?;
+    cmap.set_item("ranges", face.cmap.ranges)?;
+    cmap.set_item("blocks", face.cmap.blocks)?;
+
+    let os2 = face
+        .os2
+        .map(|os2| {
+            let entry = PyDict::new(py);
+            entry.set_item("version", os2.version)?;
+            entry.set_item("weight_class", os2.weight_class)?;
+            entry.set_item("width_class", os2.width_class)?;
+            entry.set_item("fs_type", os2.fs_type)?;
+            entry.set_item("fs_selection", os2.fs_selection)?;
+            entry.set_item("vendor_id", os2.vendor_id)?;
+            entry.set_item("typo_ascender", os2.typo_ascender)?;
+            entry.set_item("typo_descender", os2.typo_descender)?;
+            entry.set_item("typo_line_gap", os2.typo_line_gap)?;
+            entry.set_item("win_ascent", os2.win_ascent)?;
+            entry.set_item("win_descent", os2.win_descent)?;
+            entry.set_item("x_height", os2.x_height)?;
+            entry.set_item("cap_height", os2.cap_height)?;
+            Ok::<_, PyErr>(entry)
+        })
+        .transpose()?;
+    let hhea = face
+        .hhea
+        .map(|hhea| {
+            let entry = PyDict::new(py);
+            entry.set_item("ascender", hhea.ascender)?;
+            entry.set_item("descender", hhea.descender)?;
+            entry.set_item("line_gap", hhea.line_gap)?;
+            Ok::<_, PyErr>(entry)
+        })
+        .transpose()?;
+
+    let outer = PyDict::new(py);
+    outer.set_item("path", face.source.path.to_string_lossy().to_string())?;
+    outer.set_item("ttc_index", face.source.ttc_index)?;
+    outer.set_item("names", names)?;
+    outer.set_item("tables", tables)?;
+    outer.set_item("axes", axes)?;
+    outer.set_item("layout", layout)?;
+    outer.set_item("cmap", cmap)?;
+    outer.set_item("os2", os2)?;
+    outer.set_item("hhea", hhea)?;
+    outer.set_item("units_per_em", face.units_per_em)?;
+    Ok(outer.into_any().unbind())
+}
+
+fn convert_entries(entries: Vec<CacheEntry>) -> Result<Vec<TypgFontFaceMatch>> {
+    let mut converted = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match entry {
+            CacheEntry::Match(item) => converted.push(item.get().to_core()),
+            CacheEntry::Metadata(input) => converted.extend(convert_metadata(vec![input])?),
+        }
+    }
+    Ok(converted)
 }
 
 fn convert_metadata(entries: Vec<MetadataInput>) -> Result<Vec<TypgFontFaceMatch>> {
@@ -415,20 +823,44 @@ fn convert_metadata(entries: Vec<MetadataInput>) -> Result<Vec<TypgFontFaceMatch
                     ttc_index: entry.ttc_index,
                 },
                 metadata: TypgFontFaceMeta {
-                    names,
+                    names: names.into_iter().map(Arc::from).collect(),
+                    family_name: entry.family_name.map(Arc::from),
+                    style_name: entry.style_name.map(Arc::from),
+                    units_per_em: entry.units_per_em,
+                    glyph_count: entry.glyph_count,
+                    font_revision: entry.font_revision,
+                    vendor_id: entry.vendor_id,
+                    faces_in_collection: entry.faces_in_collection,
+                    file_size: None,
+                    modified: None,
+                    index_id: None,
+                    license_description: None,
+                    license_url: None,
+                    fs_type: None,
+                    embedding: None,
+                    file_hash: None,
                     axis_tags: parse_tag_list(&entry.axis_tags)?,
                     feature_tags: parse_tag_list(&entry.feature_tags)?,
+                    gsub_feature_tags: parse_tag_list(&entry.gsub_feature_tags)?,
+                    gpos_feature_tags: parse_tag_list(&entry.gpos_feature_tags)?,
                     script_tags: parse_tag_list(&entry.script_tags)?,
+                    language_tags: parse_tag_list(&entry.language_tags)?,
                     table_tags: parse_tag_list(&entry.table_tags)?,
                     codepoints: parse_codepoints(&entry.codepoints)?,
                     is_variable: entry.is_variable,
+                    cmap_subtables: Vec::new(),
+                    table_checksums: Default::default(),
+                    named_instances: Vec::new(),
+                    axes: Vec::new(),
                     weight_class: entry.weight_class,
                     width_class: entry.width_class,
                     family_class: entry
                         .family_class
                         .map(|raw| (((raw >> 8) & 0xFF) as u8, (raw & 0x00FF) as u8)),
-                    creator_names: entry.creator_names,
-                    license_names: entry.license_names,
+                    creator_names: entry.creator_names.into_iter().map(Arc::from).collect(),
+                    license_names: entry.license_names.into_iter().map(Arc::from).collect(),
+                    classified_as: entry.classified_as,
+                    extra: Default::default(),
                 },
             })
         })
@@ -441,8 +873,12 @@ fn default_name(path: &Path) -> String {
         .unwrap_or_else(|| path.display().to_string())
 }
 
+/// The query a search function runs: `query` if given, else one built
+/// from the keyword filters. Giving both is an error, since it is not
+/// obvious which should win.
 #[allow(clippy::too_many_arguments)]
 fn build_query(
+    query: Option<&PyQuery>,
     axes: Option<Vec<String>>,
     features: Option<Vec<String>>,
     scripts: Option<Vec<String>>,
@@ -457,131 +893,51 @@ fn build_query(
     license: Option<Vec<String>>,
     variable: bool,
 ) -> Result<Query> {
-    let axes = parse_tag_list(&axes.unwrap_or_default())?;
-    let features = parse_tag_list(&features.unwrap_or_default())?;
-    let scripts = parse_tag_list(&scripts.unwrap_or_default())?;
-    let tables = parse_tag_list(&tables.unwrap_or_default())?;
-    let name_patterns = compile_patterns(&names.unwrap_or_default())?;
-    let creator_patterns = compile_patterns(&creator.unwrap_or_default())?;
-    let license_patterns = compile_patterns(&license.unwrap_or_default())?;
-
-    let weight_range = parse_optional_range(weight)?;
-    let width_range = parse_optional_range(width)?;
-    let family_class = parse_optional_family_class(family_class)?;
-
-    let mut cps = parse_codepoints(&codepoints.unwrap_or_default())?;
-    if let Some(text) = text {
-        cps.extend(text.chars());
+    let spec = QuerySpec {
+        axes: axes.unwrap_or_default(),
+        features: features.unwrap_or_default(),
+        scripts: scripts.unwrap_or_default(),
+        tables: tables.unwrap_or_default(),
+        names: names.unwrap_or_default(),
+        codepoints: codepoints.unwrap_or_default(),
+        text,
+        weight,
+        width,
+        family_class,
+        creator: creator.unwrap_or_default(),
+        license: license.unwrap_or_default(),
+        variable,
+    };
+    match query {
+        Some(_) if spec != QuerySpec::default() => Err(anyhow!(
+            "pass filters either as keyword arguments or as query=, not both"
+        )),
+        Some(query) => query.spec.build(),
+        None => spec.build(),
     }
-    dedup_chars(&mut cps);
-
-    Ok(Query::new()
-        .with_axes(axes)
-        .with_features(features)
-        .with_scripts(scripts)
-        .with_tables(tables)
-        .with_name_patterns(name_patterns)
-        .with_creator_patterns(creator_patterns)
-        .with_license_patterns(license_patterns)
-        .with_codepoints(cps)
-        .require_variable(variable)
-        .with_weight_range(weight_range)
-        .with_width_range(width_range)
-        .with_family_class(family_class))
 }
 
-fn parse_codepoints(raw: &[String]) -> Result<Vec<char>> {
-    let mut cps = Vec::new();
-    for chunk in raw {
-        cps.extend(parse_codepoint_list(chunk)?);
-    }
-    Ok(cps)
+fn to_py_matches(py: Python<'_>, matches: Vec<TypgFontFaceMatch>) -> PyResult<Vec<Py<FontMatch>>> {
+    matches
+        .into_iter()
+        .map(|item| FontMatch::new(py, item))
+        .collect()
 }
 
-fn parse_optional_range(raw: Option<String>) -> Result<Option<RangeInclusive<u16>>> {
-    match raw {
-        Some(value) => Ok(Some(parse_u16_range(&value)?)),
-        None => Ok(None),
+fn check_search_args(paths: &[PathBuf], jobs: Option<usize>) -> PyResult<()> {
+    if paths.is_empty() {
+        return Err(PyValueError::new_err(
+            "at least one search path is required",
+        ));
     }
-}
 
-fn parse_optional_family_class(raw: Option<String>) -> Result<Option<FamilyClassFilter>> {
-    match raw {
-        Some(value) => Ok(Some(parse_family_class(&value)?)),
-        None => Ok(None),
+    if matches!(jobs, Some(0)) {
+        return Err(PyValueError::new_err(
+            "jobs must be at least 1 when provided",
+        ));
     }
-}
-
-fn compile_patterns(patterns: &[String]) -> Result<Vec<Regex>> {
-    patterns
-        .iter()
-        .map(|p| Regex::new(p).map_err(|e| anyhow!("invalid regex {p}: {e}")))
-        .collect()
-}
-
-fn dedup_chars(cps: &mut Vec<char>) {
-    cps.sort();
-    cps.dedup();
-}
 
-fn to_py_matches(py: Python<'_>, matches: Vec<TypgFontFaceMatch>) -> PyResult<Vec<Py<PyAny>>> {
-    matches
-        .into_iter()
-        .map(|item| {
-            let meta = &item.metadata;
-
-            let meta_dict = PyDict::new(py);
-            meta_dict.set_item("names", meta.names.clone())?;
-            meta_dict.set_item(
-                "axis_tags",
-                meta.axis_tags
-                    .iter()
-                    .map(|t| tag_to_string(*t))
-                    .collect::<Vec<_>>(),
-            )?;
-            meta_dict.set_item(
-                "feature_tags",
-                meta.feature_tags
-                    .iter()
-                    .map(|t| tag_to_string(*t))
-                    .collect::<Vec<_>>(),
-            )?;
-            meta_dict.set_item(
-                "script_tags",
-                meta.script_tags
-                    .iter()
-                    .map(|t| tag_to_string(*t))
-                    .collect::<Vec<_>>(),
-            )?;
-            meta_dict.set_item(
-                "table_tags",
-                meta.table_tags
-                    .iter()
-                    .map(|t| tag_to_string(*t))
-                    .collect::<Vec<_>>(),
-            )?;
-            meta_dict.set_item(
-                "codepoints",
-                meta.codepoints
-                    .iter()
-                    .map(|c| c.to_string())
-                    .collect::<Vec<_>>(),
-            )?;
-            meta_dict.set_item("is_variable", meta.is_variable)?;
-            meta_dict.set_item("weight_class", meta.weight_class)?;
-            meta_dict.set_item("width_class", meta.width_class)?;
-            meta_dict.set_item("family_class", meta.family_class)?;
-            meta_dict.set_item("creator_names", meta.creator_names.clone())?;
-            meta_dict.set_item("license_names", meta.license_names.clone())?;
-
-            let outer = PyDict::new(py);
-            outer.set_item("path", item.source.path.to_string_lossy().to_string())?;
-            outer.set_item("ttc_index", item.source.ttc_index)?;
-            outer.set_item("metadata", meta_dict)?;
-
-            Ok(outer.into_any().unbind())
-        })
-        .collect()
+    Ok(())
 }
 
 fn to_py_err(err: anyhow::Error) -> PyErr {
@@ -591,15 +947,29 @@ fn to_py_err(err: anyhow::Error) -> PyErr {
 #[pymodule]
 #[pyo3(name = "_typg_python")]
 fn typg_python(_py: Python<'_>, m: &Bound<PyModule>) -> PyResult<()> {
+    m.add_class::<FontMatch>()?;
+    m.add_class::<FontMeta>()?;
+    m.add_class::<FontSource>()?;
+    m.add_class::<PyQuery>()?;
     m.add_function(wrap_pyfunction!(find_py, m)?)?;
     m.add_function(wrap_pyfunction!(find_paths_py, m)?)?;
+    m.add_function(wrap_pyfunction!(find_each_py, m)?)?;
     m.add_function(wrap_pyfunction!(filter_cached_py, m)?)?;
+    m.add_function(wrap_pyfunction!(inspect_py, m)?)?;
+    m.add_function(wrap_pyfunction!(inspect_many_py, m)?)?;
+    m.add_function(wrap_pyfunction!(known_tags_py, m)?)?;
+    m.add_function(wrap_pyfunction!(load_cache_py, m)?)?;
+    m.add_function(wrap_pyfunction!(save_cache_py, m)?)?;
 
     #[cfg(feature = "hpindex")]
     {
+        m.add_class::<PyTypgIndex>()?;
+        m.add_class::<PyIndexWriter>()?;
         m.add_function(wrap_pyfunction!(find_indexed_py, m)?)?;
         m.add_function(wrap_pyfunction!(list_indexed_py, m)?)?;
         m.add_function(wrap_pyfunction!(count_indexed_py, m)?)?;
+        m.add_function(wrap_pyfunction!(get_indexed_py, m)?)?;
+        m.add_function(wrap_pyfunction!(get_indexed_by_path_py, m)?)?;
     }
 
     Ok(())
@@ -609,13 +979,16 @@ fn typg_python(_py: Python<'_>, m: &Bound<PyModule>) -> PyResult<()> {
 mod tests {
     use super::*;
 
-    fn metadata(path: &str, names: &[&str], axes: &[&str], variable: bool) -> MetadataInput {
-        MetadataInput {
+    fn metadata(path: &str, names: &[&str], axes: &[&str], variable: bool) -> CacheEntry {
+        CacheEntry::Metadata(MetadataInput {
             path: PathBuf::from(path),
             names: names.iter().map(|s| s.to_string()).collect(),
             axis_tags: axes.iter().map(|s| s.to_string()).collect(),
             feature_tags: Vec::new(),
+            gsub_feature_tags: Vec::new(),
+            gpos_feature_tags: Vec::new(),
             script_tags: Vec::new(),
+            language_tags: Vec::new(),
             table_tags: Vec::new(),
             codepoints: vec!["A".into()],
             is_variable: variable,
@@ -625,7 +998,15 @@ mod tests {
             family_class: None,
             creator_names: Vec::new(),
             license_names: Vec::new(),
-        }
+            classified_as: None,
+            family_name: None,
+            style_name: None,
+            units_per_em: None,
+            glyph_count: None,
+            font_revision: None,
+            vendor_id: None,
+            faces_in_collection: None,
+        })
     }
 
     #[test]
@@ -653,22 +1034,48 @@ mod tests {
                 None,
                 None,
                 true,
+                None,
             );
 
             assert!(result.is_ok(), "expected Ok from filter_cached_py");
             let objs = result.unwrap();
             assert_eq!(objs.len(), 1, "only variable font with axis should match");
-            let py_any: Py<PyAny> = objs[0].clone_ref(py);
-            let bound_any = py_any.bind(py);
-            let dict = bound_any.downcast::<PyDict>().unwrap();
+            let item = objs[0].bind(py);
+            assert_eq!(
+                item.getattr("path").unwrap().extract::<String>().unwrap(),
+                "VariableVF.ttf"
+            );
+            let metadata = item.getattr("metadata").unwrap();
+            assert!(metadata
+                .getattr("is_variable")
+                .unwrap()
+                .extract::<bool>()
+                .unwrap());
             assert_eq!(
-                dict.get_item("path")
-                    .expect("path lookup")
-                    .expect("path field")
-                    .extract::<String>()
+                metadata
+                    .getattr("axis_tags")
+                    .unwrap()
+                    .extract::<Vec<String>>()
                     .unwrap(),
+                ["wght"]
+            );
+
+            // Dict access and to_dict() keep code written for dicts working.
+            assert_eq!(
+                item.get_item("path").unwrap().extract::<String>().unwrap(),
                 "VariableVF.ttf"
             );
+            assert!(item.get_item("nope").is_err());
+            let dict = item.call_method0("to_dict").unwrap();
+            let dict = dict.downcast::<PyDict>().unwrap();
+            assert!(dict
+                .get_item("metadata")
+                .unwrap()
+                .expect("metadata field")
+                .downcast::<PyDict>()
+                .unwrap()
+                .contains("named_instances")
+                .unwrap());
         });
     }
 
@@ -692,6 +1099,7 @@ mod tests {
                 None,
                 None,
                 false,
+                None,
             )
             .unwrap_err();
 
@@ -703,6 +1111,119 @@ mod tests {
         });
     }
 
+    #[test]
+    fn query_builder_drives_filter_cached() {
+        Python::initialize();
+        Python::attach(|py| {
+            let entries = || {
+                vec![
+                    metadata("VariableVF.ttf", &["Pro VF"], &["wght"], true),
+                    metadata("Static.ttf", &["Static Sans"], &[], false),
+                ]
+            };
+            let base = Bound::new(py, PyQuery::default()).unwrap();
+            let query = base
+                .call_method1("axes", ("wght",))
+                .unwrap()
+                .call_method1("names", ("Pro",))
+                .unwrap()
+                .call_method0("variable")
+                .unwrap();
+            let query = query.downcast::<PyQuery>().unwrap();
+            assert_eq!(base.get().spec, QuerySpec::default(), "methods copy");
+
+            let run = |query: &Bound<'_, PyQuery>, axes: Option<Vec<String>>| {
+                filter_cached_py(
+                    py,
+                    entries(),
+                    axes,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    false,
+                    Some(query.borrow()),
+                )
+            };
+            let found = run(query, None).unwrap();
+            assert_eq!(found.len(), 1);
+            assert!(run(query, Some(vec!["wght".into()])).is_err());
+
+            let query_type = py.get_type::<PyQuery>();
+            let dict = query.call_method0("to_dict").unwrap();
+            let restored = query_type.call_method1("from_dict", (dict,)).unwrap();
+            assert!(restored.eq(query).unwrap());
+
+            assert!(base.call_method1("axes", ("abcde",)).is_err());
+            assert!(base.call_method1("weight", ("heavy",)).is_err());
+            let bad = PyDict::new(py);
+            bad.set_item("colour", "red").unwrap();
+            assert!(query_type.call_method1("from_dict", (bad,)).is_err());
+        });
+    }
+
+    #[test]
+    fn save_and_load_cache_round_trip() {
+        Python::initialize();
+        Python::attach(|py| {
+            let dir = tempfile::tempdir().expect("tempdir");
+            let path = dir.path().join("cache").join("fonts.json");
+            let found = filter_cached_py(
+                py,
+                vec![metadata("B.ttf", &["B"], &["wght"], true)],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                false,
+                None,
+            )
+            .unwrap();
+            let entries = vec![
+                CacheEntry::Match(found[0].clone_ref(py)),
+                metadata("A.ttf", &["A"], &[], false),
+            ];
+
+            save_cache_py(py, path.clone(), entries).unwrap();
+            let loaded = load_cache_py(py, path).unwrap();
+
+            let paths: Vec<PathBuf> = loaded
+                .iter()
+                .map(|m| m.get().to_core().source.path)
+                .collect();
+            assert_eq!(paths, [PathBuf::from("A.ttf"), PathBuf::from("B.ttf")]);
+            assert!(loaded[1].bind(py).eq(found[0].bind(py)).unwrap());
+        });
+    }
+
+    #[test]
+    fn inspect_rejects_non_font_files() {
+        Python::initialize();
+        Python::attach(|py| {
+            let dir = tempfile::tempdir().expect("tempdir");
+            let path = dir.path().join("notes.ttf");
+            std::fs::write(&path, b"not a font").expect("write");
+
+            let err = inspect_py(py, path).expect_err("garbage");
+            assert!(err.to_string().contains("notes.ttf"), "{err}");
+        });
+    }
+
     #[test]
     fn find_requires_paths() {
         Python::initialize();
@@ -725,6 +1246,7 @@ mod tests {
                 false,
                 false,
                 None,
+                None,
             )
             .unwrap_err();
 
@@ -738,8 +1260,9 @@ mod tests {
     #[test]
     fn find_paths_requires_paths() {
         Python::initialize();
-        Python::attach(|_| {
+        Python::attach(|py| {
             let err = find_paths_py(
+                py,
                 Vec::new(),
                 None,
                 None,
@@ -756,6 +1279,7 @@ mod tests {
                 false,
                 false,
                 None,
+                None,
             )
             .unwrap_err();
 
@@ -763,6 +1287,111 @@ mod tests {
         });
     }
 
+    #[test]
+    fn find_each_returns_delivered_count() {
+        Python::initialize();
+        Python::attach(|py| {
+            let dir = tempfile::tempdir().expect("tempdir");
+            std::fs::write(dir.path().join("notes.txt"), b"not a font").expect("write");
+            let seen = pyo3::types::PyList::empty(py);
+            let callback = seen.getattr("append").unwrap().unbind();
+
+            let delivered = find_each_py(
+                py,
+                callback,
+                vec![dir.path().to_path_buf()],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                false,
+                false,
+                Some(2),
+                None,
+            )
+            .unwrap();
+
+            assert_eq!(delivered, 0);
+            assert!(seen.is_empty());
+        });
+    }
+
+    #[test]
+    fn known_tags_list_one_name_per_family_class() {
+        Python::initialize();
+        Python::attach(|py| {
+            let known = known_tags_py(py).unwrap();
+            let classes: Vec<(String, u8)> = known
+                .get_item("family_classes")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert!(classes.contains(&("sans".to_string(), 8)));
+            assert!(!classes.iter().any(|(name, _)| name == "sans-serif"));
+            let axes: Vec<(String, String)> =
+                known.get_item("axes").unwrap().unwrap().extract().unwrap();
+            assert_eq!(axes[0], ("wght".to_string(), "Weight".to_string()));
+        });
+    }
+
+    #[test]
+    fn inspect_many_skips_unreadable_files_and_zero_jobs() {
+        Python::initialize();
+        Python::attach(|py| {
+            let dir = tempfile::tempdir().expect("tempdir");
+            let broken = dir.path().join("broken.ttf");
+            std::fs::write(&broken, b"not a font").expect("write");
+
+            let faces = inspect_many_py(py, vec![broken.clone(), dir.path().into()], None);
+            assert!(faces.unwrap().is_empty());
+            assert!(inspect_many_py(py, vec![broken], Some(0)).is_err());
+        });
+    }
+
+    #[test]
+    fn font_meta_lists_axis_ranges() {
+        use typg_core::search::VariationAxis;
+
+        Python::initialize();
+        Python::attach(|py| {
+            let CacheEntry::Metadata(entry) = metadata("VF.ttf", &["VF"], &["wght"], true) else {
+                unreachable!()
+            };
+            let mut item = convert_metadata(vec![entry]).unwrap().remove(0);
+            item.metadata.axes = vec![VariationAxis {
+                tag: "wght".into(),
+                min: 100.0,
+                default: 400.0,
+                max: 900.0,
+            }];
+
+            let found = FontMatch::new(py, item).unwrap();
+            let meta = found.bind(py).getattr("metadata").unwrap();
+            let axes = meta.getattr("axes").unwrap();
+            let axis = axes.get_item(0).unwrap();
+            assert_eq!(
+                axis.get_item("tag").unwrap().extract::<String>().unwrap(),
+                "wght"
+            );
+            let range: Vec<f32> = ["min", "default", "max"]
+                .iter()
+                .map(|key| axis.get_item(key).unwrap().extract().unwrap())
+                .collect();
+            assert_eq!(range, [100.0, 400.0, 900.0]);
+            let dict = meta.call_method0("to_dict").unwrap();
+            assert!(dict.get_item("axes").unwrap().eq(axes).unwrap());
+        });
+    }
+
     #[cfg(feature = "hpindex")]
     #[test]
     fn indexed_search_returns_results() {
@@ -803,7 +1432,7 @@ mod tests {
         Python::initialize();
         Python::attach(|py| {
             // Test count_indexed_py.
-            let count = count_indexed_py(index_path.clone()).unwrap();
+            let count = count_indexed_py(py, index_path.clone()).unwrap();
             assert_eq!(count, 1);
 
             // Test list_indexed_py.
@@ -827,6 +1456,7 @@ mod tests {
                 None,
                 None,
                 true,
+                None,
             )
             .unwrap();
             assert_eq!(matches.len(), 1);
@@ -848,11 +1478,103 @@ mod tests {
                 None,
                 None,
                 false,
+                None,
             )
             .unwrap();
             assert_eq!(no_matches.len(), 0);
+
+            // Index results carry the ID that fetches them again.
+            let id: u64 = all[0]
+                .bind(py)
+                .getattr("metadata")
+                .and_then(|meta| meta.getattr("index_id"))
+                .and_then(|id| id.extract())
+                .unwrap();
+            let found = get_indexed_py(py, index_path.clone(), id).unwrap();
+            assert!(found.unwrap().bind(py).eq(all[0].bind(py)).unwrap());
+            assert!(get_indexed_py(py, index_path.clone(), id + 1)
+                .unwrap()
+                .is_none());
+
+            let by_path =
+                get_indexed_by_path_py(py, index_path.clone(), "/test/IndexedFont.ttf".into())
+                    .unwrap();
+            assert_eq!(by_path.len(), 1);
         });
 
         // dir is dropped here, after all tests complete.
     }
+
+    #[cfg(feature = "hpindex")]
+    #[test]
+    fn index_writer_commits_or_aborts_with_the_block() {
+        use pyo3::types::PyDict;
+        use read_fonts::types::Tag;
+        use std::time::SystemTime;
+        use typg_core::index::FontIndex;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        {
+            let index = FontIndex::open(dir.path()).unwrap();
+            let mut writer = index.writer().unwrap();
+            for path in ["/test/A.ttf", "/test/B.ttf"] {
+                writer
+                    .add_font(
+                        Path::new(path),
+                        None,
+                        SystemTime::UNIX_EPOCH,
+                        vec!["Font".to_string()],
+                        &[],
+                        &[],
+                        &[Tag::new(b"latn")],
+                        &[],
+                        &['a'],
+                        false,
+                        Some(400),
+                        Some(5),
+                        None,
+                    )
+                    .unwrap();
+            }
+            writer.commit().unwrap();
+        }
+
+        Python::initialize();
+        Python::attach(|py| {
+            let index = py.get_type::<PyTypgIndex>().call1((dir.path(),)).unwrap();
+            let locals = PyDict::new(py);
+            locals.set_item("index", index).unwrap();
+            py.run(
+                cr#"
+try:
+    with index.writer() as w:
+        w.remove_path("/test/A.ttf")
+        raise KeyError("stop")
+except KeyError:
+    pass
+assert len(index) == 2, "a raising block must not commit"
+
+with index.writer() as w:
+    w.remove_path("/test/A.ttf")
+    assert len(index) == 2, "changes wait for the commit"
+assert w.closed
+assert len(index) == 1
+
+w = index.writer()
+w.clear()
+w.abort()
+assert len(index) == 1
+try:
+    w.clear()
+except RuntimeError:
+    pass
+else:
+    raise AssertionError("a closed writer must refuse changes")
+"#,
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+        });
+    }
 }
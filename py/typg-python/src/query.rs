@@ -0,0 +1,308 @@
+//! The `Query` builder.
+//!
+//! `Query().scripts("arab").axes("wght").weight("400-700")` collects the
+//! same filters the search functions take as keyword arguments, checks each
+//! one as it is added, and can be passed to any of them as `query=`. A
+//! query is immutable: every method returns a new one, so a base query can
+//! be refined in several directions and reused across searches.
+//!
+//! `to_dict()` uses the keyword names, which are also the field names of a
+//! `typg serve` `/search` request body; `Query.from_dict()` reads it back
+//! and pickling goes through the same pair.
+//!
+//! Built by FontLab (https://www.fontlab.com/).
+
+use std::ops::RangeInclusive;
+
+use anyhow::{anyhow, Result};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+use regex::Regex;
+use typg_core::query::{
+    parse_codepoint_list, parse_family_class, parse_tag_list, parse_u16_range, FamilyClassFilter,
+    Query,
+};
+
+use crate::classes::repr;
+use crate::to_py_err;
+
+/// Search filters as given from Python, before parsing.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct QuerySpec {
+    pub axes: Vec<String>,
+    pub features: Vec<String>,
+    pub scripts: Vec<String>,
+    pub tables: Vec<String>,
+    pub names: Vec<String>,
+    pub codepoints: Vec<String>,
+    pub text: Option<String>,
+    pub weight: Option<String>,
+    pub width: Option<String>,
+    pub family_class: Option<String>,
+    pub creator: Vec<String>,
+    pub license: Vec<String>,
+    pub variable: bool,
+}
+
+impl QuerySpec {
+    /// Parse every filter into a typg-core [`Query`].
+    pub fn build(&self) -> Result<Query> {
+        let mut cps = parse_codepoints(&self.codepoints)?;
+        if let Some(text) = &self.text {
+            cps.extend(text.chars());
+        }
+        cps.sort();
+        cps.dedup();
+
+        Ok(Query::new()
+            .with_axes(parse_tag_list(&self.axes)?)
+            .with_features(parse_tag_list(&self.features)?)
+            .with_scripts(parse_tag_list(&self.scripts)?)
+            .with_tables(parse_tag_list(&self.tables)?)
+            .with_name_patterns(compile_patterns(&self.names)?)
+            .with_creator_patterns(compile_patterns(&self.creator)?)
+            .with_license_patterns(compile_patterns(&self.license)?)
+            .with_codepoints(cps)
+            .require_variable(self.variable)
+            .with_weight_range(parse_optional_range(self.weight.as_deref())?)
+            .with_width_range(parse_optional_range(self.width.as_deref())?)
+            .with_family_class(parse_optional_family_class(self.family_class.as_deref())?))
+    }
+}
+
+/// A `weight`/`width` argument: a single class (`700`) or a range string
+/// (`"400-700"`).
+#[derive(FromPyObject)]
+enum RangeArg {
+    Class(u16),
+    Range(String),
+}
+
+impl RangeArg {
+    fn into_spec(self) -> String {
+        match self {
+            Self::Class(class) => class.to_string(),
+            Self::Range(range) => range,
+        }
+    }
+}
+
+/// A reusable set of search filters, built by chaining.
+///
+/// Every method returns a new `Query`; the receiver is left unchanged.
+/// List filters accumulate across calls, single-valued ones are replaced.
+/// Invalid tags, patterns, ranges and codepoints raise `ValueError` from
+/// the method that received them.
+#[pyclass(module = "typg_python", name = "Query", frozen, eq)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PyQuery {
+    pub(crate) spec: QuerySpec,
+}
+
+impl PyQuery {
+    /// A copy with `edit` applied, rejected if the result no longer parses.
+    fn with(&self, edit: impl FnOnce(&mut QuerySpec)) -> PyResult<Self> {
+        let mut spec = self.spec.clone();
+        edit(&mut spec);
+        spec.build().map_err(to_py_err)?;
+        Ok(Self { spec })
+    }
+}
+
+#[pymethods]
+impl PyQuery {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require variation axes, e.g. `.axes("wght", "wdth")`.
+    #[pyo3(signature = (*tags))]
+    fn axes(&self, tags: Vec<String>) -> PyResult<Self> {
+        self.with(|spec| spec.axes.extend(tags))
+    }
+
+    /// Require OpenType features, e.g. `.features("liga", "smcp")`.
+    #[pyo3(signature = (*tags))]
+    fn features(&self, tags: Vec<String>) -> PyResult<Self> {
+        self.with(|spec| spec.features.extend(tags))
+    }
+
+    /// Require GSUB/GPOS scripts, e.g. `.scripts("arab")`.
+    #[pyo3(signature = (*tags))]
+    fn scripts(&self, tags: Vec<String>) -> PyResult<Self> {
+        self.with(|spec| spec.scripts.extend(tags))
+    }
+
+    /// Require font tables, e.g. `.tables("COLR")`.
+    #[pyo3(signature = (*tags))]
+    fn tables(&self, tags: Vec<String>) -> PyResult<Self> {
+        self.with(|spec| spec.tables.extend(tags))
+    }
+
+    /// Require a name matching each regex.
+    #[pyo3(signature = (*patterns))]
+    fn names(&self, patterns: Vec<String>) -> PyResult<Self> {
+        self.with(|spec| spec.names.extend(patterns))
+    }
+
+    /// Require a creator name (copyright, manufacturer, designer, ...)
+    /// matching each regex.
+    #[pyo3(signature = (*patterns))]
+    fn creator(&self, patterns: Vec<String>) -> PyResult<Self> {
+        self.with(|spec| spec.creator.extend(patterns))
+    }
+
+    /// Require a license name matching each regex.
+    #[pyo3(signature = (*patterns))]
+    fn license(&self, patterns: Vec<String>) -> PyResult<Self> {
+        self.with(|spec| spec.license.extend(patterns))
+    }
+
+    /// Require codepoints, e.g. `.codepoints("U+0600-U+06FF", "A")`.
+    #[pyo3(signature = (*specs))]
+    fn codepoints(&self, specs: Vec<String>) -> PyResult<Self> {
+        self.with(|spec| spec.codepoints.extend(specs))
+    }
+
+    /// Require every character of `text`.
+    fn text(&self, text: String) -> PyResult<Self> {
+        self.with(|spec| spec.text.get_or_insert_with(String::new).push_str(&text))
+    }
+
+    /// Restrict the OS/2 weight class: `.weight(700)` or `.weight("400-700")`.
+    fn weight(&self, range: RangeArg) -> PyResult<Self> {
+        self.with(|spec| spec.weight = Some(range.into_spec()))
+    }
+
+    /// Restrict the OS/2 width class: `.width(5)` or `.width("3-5")`.
+    fn width(&self, range: RangeArg) -> PyResult<Self> {
+        self.with(|spec| spec.width = Some(range.into_spec()))
+    }
+
+    /// Restrict the OS/2 family class, e.g. `.family_class("8")` or `"8.1"`.
+    fn family_class(&self, value: String) -> PyResult<Self> {
+        self.with(|spec| spec.family_class = Some(value))
+    }
+
+    /// Keep only variable fonts (or, with `False`, drop the requirement).
+    #[pyo3(signature = (variable=true))]
+    fn variable(&self, variable: bool) -> PyResult<Self> {
+        self.with(|spec| spec.variable = variable)
+    }
+
+    /// The filters that are set, keyed by their keyword-argument names.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let spec = &self.spec;
+        let dict = PyDict::new(py);
+        for (key, values) in [
+            ("axes", &spec.axes),
+            ("features", &spec.features),
+            ("scripts", &spec.scripts),
+            ("tables", &spec.tables),
+            ("names", &spec.names),
+            ("codepoints", &spec.codepoints),
+            ("creator", &spec.creator),
+            ("license", &spec.license),
+        ] {
+            if !values.is_empty() {
+                dict.set_item(key, values)?;
+            }
+        }
+        for (key, value) in [
+            ("text", &spec.text),
+            ("weight", &spec.weight),
+            ("width", &spec.width),
+            ("family_class", &spec.family_class),
+        ] {
+            if let Some(value) = value {
+                dict.set_item(key, value)?;
+            }
+        }
+        if spec.variable {
+            dict.set_item("variable", true)?;
+        }
+        Ok(dict)
+    }
+
+    /// Rebuild a query from `to_dict()` output. Unknown keys raise
+    /// `ValueError`.
+    #[staticmethod]
+    fn from_dict(data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let mut spec = QuerySpec::default();
+        for (key, value) in data.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "axes" => spec.axes = value.extract()?,
+                "features" => spec.features = value.extract()?,
+                "scripts" => spec.scripts = value.extract()?,
+                "tables" => spec.tables = value.extract()?,
+                "names" => spec.names = value.extract()?,
+                "codepoints" => spec.codepoints = value.extract()?,
+                "creator" => spec.creator = value.extract()?,
+                "license" => spec.license = value.extract()?,
+                "text" => spec.text = value.extract()?,
+                "weight" => {
+                    spec.weight = value
+                        .extract::<Option<RangeArg>>()?
+                        .map(RangeArg::into_spec)
+                }
+                "width" => {
+                    spec.width = value
+                        .extract::<Option<RangeArg>>()?
+                        .map(RangeArg::into_spec)
+                }
+                "family_class" => spec.family_class = value.extract()?,
+                "variable" => spec.variable = value.extract()?,
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "unknown query key '{other}'"
+                    )))
+                }
+            }
+        }
+        spec.build().map_err(to_py_err)?;
+        Ok(Self { spec })
+    }
+
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyTuple>)> {
+        let from_dict = slf.get_type().getattr("from_dict")?;
+        let args = PyTuple::new(slf.py(), [slf.get().to_dict(slf.py())?])?;
+        Ok((from_dict, args))
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let fields = self
+            .to_dict(py)?
+            .iter()
+            .map(|(key, value)| Ok(format!("{key}={}", repr(py, value)?)))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(format!("Query({})", fields.join(", ")))
+    }
+}
+
+pub(crate) fn parse_codepoints(raw: &[String]) -> Result<Vec<char>> {
+    let mut cps = Vec::new();
+    for chunk in raw {
+        cps.extend(parse_codepoint_list(chunk)?);
+    }
+    Ok(cps)
+}
+
+fn parse_optional_range(raw: Option<&str>) -> Result<Option<RangeInclusive<u16>>> {
+    raw.map(parse_u16_range).transpose()
+}
+
+fn parse_optional_family_class(raw: Option<&str>) -> Result<Option<FamilyClassFilter>> {
+    raw.map(parse_family_class).transpose()
+}
+
+fn compile_patterns(patterns: &[String]) -> Result<Vec<Regex>> {
+    patterns
+        .iter()
+        .map(|p| Regex::new(p).map_err(|e| anyhow!("invalid regex {p}: {e}")))
+        .collect()
+}
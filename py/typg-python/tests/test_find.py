@@ -6,7 +6,9 @@ behaves as expected - like checking if the cat will land on its feet.
 
 from __future__ import annotations
 
+import asyncio
 import os
+import pickle
 from pathlib import Path
 from types import SimpleNamespace
 
@@ -50,6 +52,23 @@ def test_find_filters_variable_flag(fonts_dir: Path) -> None:
     assert "wght" in match["metadata"]["axis_tags"], "variable font should expose axes"
 
 
+def test_find_returns_typed_matches(fonts_dir: Path) -> None:
+    # Attributes for typed code, dict access and to_dict() for everyone else.
+    results = typg_python.find([str(fonts_dir / "NotoSans-Regular.ttf")])
+
+    assert len(results) == 1
+    match = results[0]
+    assert isinstance(match, typg_python.FontMatch)
+    assert isinstance(match.metadata, typg_python.FontMeta)
+    assert match.source == typg_python.FontSource(match.path)
+    assert match.metadata.weight_class == 400
+    assert match["metadata"]["weight_class"] == 400
+    assert match.to_dict()["font_id"] == match.font_id
+    assert "NotoSans-Regular.ttf" in repr(match)
+    assert sorted(results * 2) == [match, match]
+    assert len({match, match}) == 1
+
+
 def test_cli_uses_system_font_env_override(fonts_dir: Path, monkeypatch: pytest.MonkeyPatch) -> None:
     # Environment variables are like secret menu items - they can send
     # our font fishing expedition to completely different waters.
@@ -76,6 +95,27 @@ def test_find_rejects_zero_jobs(fonts_dir: Path) -> None:
         typg_python.find([str(fonts_dir)], jobs=0)
 
 
+def test_find_async_and_find_each_agree_with_find(fonts_dir: Path) -> None:
+    # Same search, three ways to wait for it: blocking, awaited, streamed.
+    expected = sorted(item["path"] for item in typg_python.find([str(fonts_dir)], scripts=["latn"]))
+
+    awaited = asyncio.run(typg_python.find_async([str(fonts_dir)], scripts=["latn"]))
+    streamed: list[dict] = []
+    delivered = typg_python.find_each(streamed.append, [str(fonts_dir)], scripts=["latn"])
+
+    assert sorted(item["path"] for item in awaited) == expected
+    assert delivered == len(streamed)
+    assert sorted(item["path"] for item in streamed) == expected
+
+
+def test_find_each_propagates_callback_errors(fonts_dir: Path) -> None:
+    def explode(_match: dict) -> None:
+        raise RuntimeError("stop")
+
+    with pytest.raises(RuntimeError, match="stop"):
+        typg_python.find_each(explode, [str(fonts_dir)])
+
+
 def test_find_paths_returns_strings_only(fonts_dir: Path) -> None:
     # Sometimes you just want the phone numbers, not the life stories.
     # Paths-only mode gives you just the facts - like a librarian who
@@ -161,3 +201,113 @@ def test_filter_cached_family_class_filters_major_and_subclass() -> None:
     subclass = typg_python.filter_cached(entries, family_class="8.11")
     assert len(subclass) == 1
     assert subclass[0]["path"] == "Sans.ttf"
+
+
+def test_query_builder_matches_keyword_filters() -> None:
+    # A Query is a shopping list you can photocopy: add to the copy,
+    # the original stays as it was.
+    entries = [
+        _metadata("Thin.ttf", weight_class=250, width_class=3),
+        _metadata("Regular.ttf", weight_class=400, width_class=5),
+    ]
+    base = typg_python.Query().width("4-6")
+    query = base.weight("300-450")
+
+    assert base.to_dict() == {"width": "4-6"}
+    assert typg_python.filter_cached(entries, query=query) == typg_python.filter_cached(
+        entries, weight="300-450", width="4-6"
+    )
+    assert typg_python.Query.from_dict(query.to_dict()) == query
+    assert pickle.loads(pickle.dumps(query)) == query
+
+    with pytest.raises(ValueError):
+        typg_python.filter_cached(entries, query=query, weight="800")
+    with pytest.raises(ValueError):
+        typg_python.Query().scripts("toolong")
+
+
+def test_save_cache_round_trips_through_load_cache(tmp_path: Path) -> None:
+    # A cache is a lunchbox: what goes in at night comes out at noon.
+    entries = [
+        _metadata("Thin.ttf", weight_class=250, width_class=3),
+        _metadata("Regular.ttf", weight_class=400, width_class=5),
+    ]
+    regular = typg_python.filter_cached(entries, weight="400")
+    cache = tmp_path / "cache.json"
+
+    typg_python.save_cache(cache, regular + [entries[0]])
+    loaded = typg_python.load_cache(cache)
+
+    assert [m.path for m in loaded] == ["Regular.ttf", "Thin.ttf"]
+    assert loaded[0] == regular[0]
+    assert typg_python.filter_cached(loaded, weight="250") == [loaded[1]]
+
+
+def test_inspect_lists_names_and_tables(fonts_dir: Path) -> None:
+    # inspect is the magnifying glass: every name record and table, one font.
+    faces = typg_python.inspect(str(fonts_dir / "NotoSans-Regular.ttf"))
+
+    assert len(faces) == 1
+    face = faces[0]
+    assert any(n["name_id"] == 1 and n["value"] == "Noto Sans" for n in face["names"])
+    assert "cmap" in {table["tag"] for table in face["tables"]}
+    assert face["cmap"]["codepoints"] > 0
+    assert face["os2"]["weight_class"] == 400
+
+
+def test_inspect_many_reads_the_listed_files_in_order(fonts_dir: Path) -> None:
+    # inspect_many skips the walk: you bring the shopping list, it fetches.
+    files = sorted(str(p) for p in fonts_dir.glob("*.ttf"))[:3][::-1]
+    missing = str(fonts_dir / "missing.ttf")
+
+    faces = typg_python.inspect_many([*files, missing], jobs=2)
+
+    assert [face.path for face in faces] == files
+    assert faces == typg_python.find(files)[::-1]
+    assert typg_python.inspect_many([]) == []
+    with pytest.raises(ValueError):
+        typg_python.inspect_many(files, jobs=0)
+
+
+def test_index_writer_commits_with_the_block(fonts_dir: Path, tmp_path: Path) -> None:
+    # A writer is a shopping basket: nothing is bought until you reach the till.
+    if not hasattr(typg_python, "TypgIndex"):
+        pytest.skip("built without hpindex")
+    index = typg_python.TypgIndex(tmp_path / "index")
+    font = fonts_dir / "NotoSans-Regular.ttf"
+
+    with pytest.raises(KeyError):
+        with index.writer() as w:
+            assert w.add([str(font)]) == 1
+            raise KeyError("changed my mind")
+    assert len(index) == 0
+
+    with index.writer() as w:
+        w.add([str(font)])
+    assert w.closed
+    assert [m.path for m in typg_python.list_indexed(tmp_path / "index")] == [str(font)]
+
+
+def test_tag_constants_are_the_strings_typg_uses(fonts_dir: Path) -> None:
+    # Constants are name tags on the spice jars: same spice, fewer typos.
+    from typg_python import Axes, FamilyClass, Scripts, Tables
+
+    assert Axes.WGHT == "wght" and str(Tables.OS_2) == "OS/2"
+    assert Axes.WGHT.description == "Weight"
+    results = typg_python.find(
+        [str(fonts_dir / "NotoSans-Regular.ttf")],
+        scripts=[Scripts.LATN],
+        family_class=FamilyClass.SANS,
+    )
+    assert len(results) == 1
+    assert Scripts.LATN in results[0].metadata.script_tags
+
+
+def test_tag_stubs_list_every_member() -> None:
+    # The .pyi is written by hand; make sure it has not drifted from the registry.
+    from typg_python import tags
+
+    stub = (Path(tags.__file__).with_suffix(".pyi")).read_text()
+    for enum in (tags.Axes, tags.Features, tags.Scripts, tags.Tables, tags.FamilyClass):
+        for member in enum:
+            assert f'    {member.name} = "{member.value}"' in stub, member